@@ -1,14 +1,17 @@
 // Browser Extension Integration Commands
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tauri::State;
 use crate::state::app_state::AppState;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct BrowserDownloadRequest {
     pub url: String,
     pub referrer: Option<String>,
     pub filename: Option<String>,
+    #[serde(default)]
+    pub page_title: Option<String>,
 }
 
 /// Add download from browser extension
@@ -26,6 +29,8 @@ pub async fn add_download_from_browser(
         None, // Use default save path
         request.filename,
         request.referrer,
+        request.page_title,
+        None,
         state_clone,
     )
     .await
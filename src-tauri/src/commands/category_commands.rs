@@ -1,6 +1,10 @@
 use tauri::State;
 use crate::state::app_state::AppState;
 use crate::core::category::{Category, CategoryStats};
+use crate::core::undo::{UndoAction, UndoKind};
+use crate::utils::constants::MAX_SEGMENTS;
+use crate::utils::file_utils::{self, FolderKind};
+use crate::utils::permissions::FilePermissions;
 use std::path::PathBuf;
 
 #[tauri::command]
@@ -31,11 +35,15 @@ pub async fn create_category(
     save_path: Option<String>,
 ) -> Result<Category, String> {
     let save_path_buf = save_path.map(PathBuf::from);
+    if let Some(path) = &save_path_buf {
+        file_utils::validate_folder_path(path)?;
+    }
     let category = Category::new(name, color, icon, save_path_buf);
     
     state.db.create_category(&category)
         .await
         .map_err(|e| e.to_string())?;
+    state.refresh_category_dirs().await;
     
     Ok(category)
 }
@@ -48,6 +56,11 @@ pub async fn update_category(
     color: Option<String>,
     icon: Option<String>,
     save_path: Option<String>,
+    file_mode: Option<String>,
+    file_group: Option<String>,
+    default_segments: Option<u8>,
+    default_max_retries: Option<i64>,
+    require_checksum: Option<bool>,
 ) -> Result<(), String> {
     let mut category = state.db.get_category(&category_id)
         .await
@@ -63,14 +76,45 @@ pub async fn update_category(
         category.icon = Some(i);
     }
     if let Some(p) = save_path {
-        category.save_path = Some(PathBuf::from(p));
+        let path = PathBuf::from(p);
+        file_utils::validate_folder_path(&path)?;
+        category.save_path = Some(path);
+    }
+    // An empty string clears the override
+    if let Some(mode) = file_mode {
+        category.file_permissions.mode = FilePermissions::new(Some(mode), None).mode;
+    }
+    if let Some(group) = file_group {
+        category.file_permissions.group = FilePermissions::new(None, Some(group)).group;
+    }
+    category.file_permissions.validate()?;
+    // 0 segments or a negative retry count clears the default
+    if let Some(segments) = default_segments {
+        category.default_segments = (segments > 0).then_some(segments.min(MAX_SEGMENTS));
+    }
+    if let Some(retries) = default_max_retries {
+        category.default_max_retries = u32::try_from(retries).ok();
+    }
+    if let Some(require) = require_checksum {
+        category.require_checksum = require;
     }
     
     category.updated_at = chrono::Utc::now().timestamp();
     
     state.db.update_category(&category)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    state.refresh_category_dirs().await;
+
+    Ok(())
+}
+
+/// The platform's standard folder for `kind` (videos, music, documents,
+/// images, software, compressed or downloads), for folder pickers
+#[tauri::command]
+pub async fn get_default_folder(kind: String) -> Result<String, String> {
+    let kind: FolderKind = kind.parse()?;
+    Ok(file_utils::get_default_folder(kind).to_string_lossy().to_string())
 }
 
 #[tauri::command]
@@ -83,9 +127,24 @@ pub async fn delete_category(
         return Err("Cannot delete default category".to_string());
     }
     
+    let category = state.db.get_category(&category_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let download_ids = state.db.download_ids_in_category(&category_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
     state.db.delete_category(&category_id)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    state.refresh_category_dirs().await;
+
+    state.undo.record(
+        UndoKind::DeleteCategory,
+        format!("Delete category {}", category.name),
+        UndoAction::DeleteCategory { category, download_ids },
+    );
+    Ok(())
 }
 
 #[tauri::command]
@@ -115,13 +174,17 @@ pub async fn auto_categorize_download(
     download_id: String,
     file_name: String,
 ) -> Result<String, String> {
-    // Detect category from file extension
-    let extension = file_name
-        .rsplit('.')
-        .next()
-        .unwrap_or("");
-    
-    let category_id = Category::detect_from_extension(extension);
+    // Detect category from file extension, falling back to the stored Content-Type
+    let content_type = match uuid::Uuid::parse_str(&download_id) {
+        Ok(id) => state.db.get_download(id)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|task| task.content_type),
+        Err(_) => None,
+    };
+
+    let category_id = Category::detect(&file_name, content_type.as_deref());
     
     // Assign the category
     state.db.assign_download_category(&download_id, &category_id)
@@ -0,0 +1,130 @@
+// src-tauri/src/commands/clipboard_commands.rs
+
+use serde::Deserialize;
+use std::sync::Arc;
+use tauri::{Manager, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use uuid::Uuid;
+
+use crate::commands::transfer_commands::collect_transfers;
+use crate::core::transfer::{TransferFilter, TransferSummary};
+use crate::services::clipboard_service::ClipboardMonitor;
+use crate::state::app_state::AppState;
+use crate::utils::security::redact_credentials;
+
+/// What `copy_to_clipboard` copies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CopyKind {
+    Url,
+    /// The URL after shorteners and redirects were followed
+    FinalUrl,
+    SavePath,
+    /// The computed checksum, or the expected one before it is verified
+    Checksum,
+    /// Torrents only
+    Magnet,
+}
+
+/// Copy a value of a download (by UUID) or torrent (by info hash) to the
+/// clipboard, read from the database or session rather than the UI.
+/// URLs are copied without credentials. Returns the copied text.
+#[tauri::command]
+pub async fn copy_to_clipboard(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    kind: CopyKind,
+    id: String,
+) -> Result<String, String> {
+    let text = match Uuid::parse_str(&id) {
+        Ok(uuid) => download_value(&state, kind, uuid).await?,
+        Err(_) => torrent_value(&state, kind, &id).await?,
+    };
+
+    write_clipboard(&app_handle, &text).await?;
+    Ok(text)
+}
+
+/// Copy the URLs of the transfers `filter` selects, one per line; torrents
+/// are copied as magnet links. Returns the copied text.
+#[tauri::command]
+pub async fn copy_all_urls(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    filter: Option<TransferFilter>,
+) -> Result<String, String> {
+    let filter = filter.unwrap_or_default();
+    let selected = filter.apply(collect_transfers(&state, filter.kind).await?);
+
+    let mut urls = Vec::with_capacity(selected.items.len());
+    for transfer in &selected.items {
+        let id = &transfer.fields().id;
+        let url = match transfer {
+            TransferSummary::Download(_) => {
+                let uuid = Uuid::parse_str(id).map_err(|e| e.to_string())?;
+                download_value(&state, CopyKind::Url, uuid).await
+            }
+            TransferSummary::Torrent(_) => torrent_value(&state, CopyKind::Magnet, id).await,
+        };
+        match url {
+            Ok(url) => urls.push(url),
+            // Removed while we were collecting
+            Err(e) => tracing::debug!("Skipping {} when copying URLs: {}", id, e),
+        }
+    }
+    if urls.is_empty() {
+        return Err("No transfers match the selection".to_string());
+    }
+
+    let text = urls.join("\n");
+    write_clipboard(&app_handle, &text).await?;
+    Ok(text)
+}
+
+/// Put `text` on the clipboard without the monitor detecting it as a link
+/// to download
+async fn write_clipboard(app_handle: &tauri::AppHandle, text: &str) -> Result<(), String> {
+    if let Some(monitor) = app_handle.try_state::<Arc<ClipboardMonitor>>() {
+        monitor.note_own_copy(text).await;
+    }
+    app_handle.clipboard().write_text(text.to_string()).map_err(|e| e.to_string())
+}
+
+async fn download_value(state: &AppState, kind: CopyKind, id: Uuid) -> Result<String, String> {
+    let task = state.db.get_download(id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Download not found")?;
+
+    match kind {
+        CopyKind::Url => Ok(redact_credentials(&task.url)),
+        CopyKind::FinalUrl => task.final_url
+            .as_deref()
+            .map(redact_credentials)
+            .ok_or_else(|| "The download's URL was not redirected".to_string()),
+        CopyKind::SavePath => Ok(task.save_path.to_string_lossy().to_string()),
+        CopyKind::Checksum => task.actual_checksum
+            .or(task.expected_checksum)
+            .ok_or_else(|| "The download has no checksum".to_string()),
+        CopyKind::Magnet => Err("Only torrents have magnet links".to_string()),
+    }
+}
+
+async fn torrent_value(state: &AppState, kind: CopyKind, info_hash: &str) -> Result<String, String> {
+    match kind {
+        CopyKind::Magnet | CopyKind::Url => state.torrent_client
+            .get_magnet_uri(info_hash)
+            .await
+            .map_err(|e| e.to_string()),
+        CopyKind::SavePath => {
+            let (handle, metadata) = state.torrent_client
+                .snapshot(info_hash)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(metadata.save_path.join(&handle.info.name).to_string_lossy().to_string())
+        }
+        CopyKind::FinalUrl | CopyKind::Checksum => {
+            Err("Torrents have no such value to copy".to_string())
+        }
+    }
+}
@@ -1,15 +1,50 @@
 // src-tauri/src/commands/download_commands.rs
 use tauri::State;
-use tauri::Emitter;
+use tauri::{Emitter, Manager};
 use uuid::Uuid;
+use futures_util::StreamExt;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
-
-use crate::state::app_state::{AppState, ActiveDownload};
-use crate::core::download_engine::AddDownloadRequest;
+use tokio_util::sync::CancellationToken;
+
+use crate::state::app_state::{spawn_active, ActiveDownload, AppState, IfActive};
+use crate::core::download_engine::{AddDownloadRequest, DownloadEngine};
+use crate::database::db::Database;
+use crate::core::bandwidth::{domain_of, BandwidthBreakdown};
+use crate::core::content_filter::{CompiledFilter, ContentFilter};
+use crate::core::data_usage::{DataUsage, UsagePeriod};
+use crate::core::download_plan::DownloadPlan;
+use crate::core::forecast::{self, ForecastSnapshot, ForecastTask, QueueForecast};
+use crate::core::page_media::{MediaAddOptions, MediaProbe, ProbeBatchProgress, ProbeBatchSummary};
+use crate::core::playlist::{self, EntryOutcome, PlaylistEntry, PlaylistMode};
+use crate::core::post_action::PostAction;
+use crate::core::queue_manager::QueueConsistencyReport;
+use crate::core::resource_guard::BlockingCondition;
+use crate::core::size_guard::{ConfirmationReason, DownloadSource};
+use crate::core::task_launch;
+use crate::core::undo::{self, UndoAction, UndoKind};
+use crate::core::waste::{self, WasteWarning};
+use crate::core::ytdlp_env;
+use crate::core::ytdlp_fallback::{self, FallbackNotice, AUTO_FALLBACK_KEY};
+use crate::network::http_client::ProbeMode;
+use crate::network::resolvers::ResolvedUrl;
+use crate::network::url_parser::{BatchUrlAction, NormalizeOptions, NormalizedUrl, UrlParser};
+use crate::network::url_refresh::UrlRefresh;
 use crate::network::youtube_downloader::{YouTubeDownloader, YouTubeDownloadOptions, VideoInfo, QualityOption};
 use crate::core::download_task::{
-    DownloadTask, DownloadStatus, DownloadProgress, FileInfo
+    DownloadTask, DownloadStatus, DownloadProgress, FileInfo, DownloadMetadataPatch, IntegrityStatus
 };
+use crate::events::download_events::{emit_completed, emit_failed, emit_progress};
+use crate::services::hook_service::{self, HookEvent};
+use crate::services::integrity_service::{self, SweepSummary};
+use crate::services::notification_service::{NotificationService, NotificationType};
+use crate::services::watchdog_service::{self, DeadDownload, WatchdogRecovery};
+use crate::utils::binaries::Tool;
+use crate::utils::constants::DEFAULT_PLAYLIST_ENTRY_RETRIES;
+use crate::utils::error::DownloadError;
+use crate::utils::file_in_use;
+use crate::utils::file_utils;
+use crate::utils::shell_open;
 
 /// Sanitize filename by removing or replacing invalid characters
 fn sanitize_filename(filename: &str) -> String {
@@ -46,57 +81,583 @@ fn sanitize_filename(filename: &str) -> String {
     sanitized
 }
 
+/// Persist the current queue order so it survives a restart
+async fn persist_queue(state: &AppState) {
+    let (active, queued) = {
+        let queue = state.queue.read().await;
+        (queue.get_active(), queue.get_queue())
+    };
+
+    if let Err(e) = state.db.save_queue(&active, &queued).await {
+        tracing::error!("Failed to persist download queue: {}", e);
+    }
+}
+
+/// Mark a download as held by the power/network policy. The reasons are
+/// kept in its error message so the UI can show them.
+fn hold_for_conditions(task: &mut DownloadTask, conditions: &[BlockingCondition]) {
+    let reasons: Vec<String> = conditions.iter().map(ToString::to_string).collect();
+    task.status = DownloadStatus::WaitingForCondition;
+    task.error_message = Some(reasons.join("; "));
+    tracing::info!("Holding download {}: {}", task.id, reasons.join("; "));
+}
+
+/// Tell the frontend which conditions are holding a download
+fn emit_held(app_handle: &tauri::AppHandle, task: &DownloadTask, conditions: &[BlockingCondition]) {
+    let reasons: Vec<String> = conditions.iter().map(ToString::to_string).collect();
+    let _ = app_handle.emit(
+        "download-waiting-for-condition",
+        serde_json::json!({ "id": task.id, "conditions": conditions, "reasons": reasons }),
+    );
+}
+
+/// Park an automatically added download until the user confirms it
+async fn hold_for_confirmation(
+    app_handle: &tauri::AppHandle,
+    db: &crate::database::db::Database,
+    task: &mut DownloadTask,
+    reason: ConfirmationReason,
+) -> Result<(), String> {
+    task.status = DownloadStatus::NeedsConfirmation;
+    task.error_message = Some(reason.to_string());
+    db.reserve_and_insert(task).await.map_err(|e| e.to_string())?;
+
+    tracing::info!("Download {} needs confirmation: {}", task.id, reason);
+    let _ = app_handle.emit(
+        "download-needs-confirmation",
+        serde_json::json!({ "download": task.for_event(), "reason": reason, "message": reason.to_string() }),
+    );
+    Ok(())
+}
+
+/// Start downloads the queue just moved into its active list. Downloads the
+/// power/network policy holds give their slot back and the queue is refilled.
+async fn start_dequeued(app_handle: &tauri::AppHandle, state: &State<'_, AppState>, ids: Vec<Uuid>) {
+    let mut ids = ids;
+    while !ids.is_empty() {
+        let mut held_any = false;
+        for uuid in ids {
+            if start_one_dequeued(app_handle, state, uuid).await {
+                continue;
+            }
+            state.queue.write().await.release(uuid);
+            held_any = true;
+        }
+        ids = if held_any {
+            let refill = state.queue.write().await.fill();
+            persist_queue(state).await;
+            refill
+        } else {
+            Vec::new()
+        };
+    }
+}
+
+/// Returns false when the download was held instead of started
+async fn start_one_dequeued(app_handle: &tauri::AppHandle, state: &State<'_, AppState>, uuid: Uuid) -> bool {
+    let Ok(Some(mut task)) = state.db.get_download(uuid).await else {
+        return true;
+    };
+
+    let conditions = state.resource_guard.check(task.total_size).await;
+    if !conditions.is_empty() {
+        hold_for_conditions(&mut task, &conditions);
+        if let Err(e) = state.db.update_download(&task).await {
+            tracing::error!("Failed to update task {}: {}", uuid, e);
+        }
+        emit_held(app_handle, &task, &conditions);
+        return false;
+    }
+
+    task.status = DownloadStatus::Downloading;
+
+    if let Err(e) = state.db.update_download(&task).await {
+        tracing::error!("Failed to update task {}: {}", uuid, e);
+        return true;
+    }
+
+    // Emit event
+    let _ = app_handle.emit("download-started", &task.for_event());
+
+    // Start the download
+    if let Err(e) = spawn_download_task(app_handle.clone(), state, task, IfActive::Skip).await {
+        tracing::error!("Failed to spawn download {}: {}", uuid, e);
+    }
+    true
+}
+
+/// Queue held downloads whose power/network conditions are now met and
+/// start whatever fits. Probes the machine once for all of them.
+pub async fn promote_held_downloads(app_handle: &tauri::AppHandle) -> Result<usize, String> {
+    let state = app_handle.state::<AppState>();
+    if state.resource_guard.is_data_capped() {
+        return Ok(0);
+    }
+    let held = state.db
+        .get_downloads_with_status(DownloadStatus::WaitingForCondition)
+        .await
+        .map_err(|e| e.to_string())?;
+    if held.is_empty() {
+        return Ok(0);
+    }
+
+    let policy = state.resource_guard.policy();
+    let environment = state.resource_guard.environment().await;
+    let mut promoted = 0;
+    // Oldest first so they keep their original order in the queue
+    for mut task in held.into_iter().rev() {
+        if !policy.blocking(task.total_size, &environment).is_empty() {
+            continue;
+        }
+        task.status = DownloadStatus::Queued;
+        task.error_message = None;
+        if let Err(e) = state.db.update_download(&task).await {
+            tracing::error!("Failed to update task {}: {}", task.id, e);
+            continue;
+        }
+        state.queue.write().await.push_waiting(task.id);
+        let _ = app_handle.emit("download-condition-cleared", &task.for_event());
+        promoted += 1;
+    }
+
+    if promoted > 0 {
+        tracing::info!("Conditions met for {} held downloads", promoted);
+        let to_start = state.queue.write().await.fill();
+        persist_queue(&state).await;
+        start_dequeued(app_handle, &state, to_start).await;
+    }
+    Ok(promoted)
+}
+
+/// Let the queue promote waiting downloads again after a hold, and start
+/// whatever fits
+pub async fn lift_queue_hold(app_handle: &tauri::AppHandle) {
+    let state = app_handle.state::<AppState>();
+    let to_start = {
+        let mut queue = state.queue.write().await;
+        queue.set_held(false);
+        queue.fill()
+    };
+    persist_queue(&state).await;
+    start_dequeued(app_handle, &state, to_start).await;
+}
+
+/// Stop every running download and hold it until the data cap lifts.
+/// Held downloads give up their queue slots without starting others.
+pub async fn hold_active_for_data_cap(app_handle: &tauri::AppHandle) -> usize {
+    let state = app_handle.state::<AppState>();
+    let active: Vec<(Uuid, ActiveDownload)> = state.active_downloads.write().await.drain().collect();
+    let conditions = [BlockingCondition::DataCapReached];
+
+    let mut held = 0;
+    for (uuid, download) in active {
+        download.cancel_token.cancel();
+        // Let the task record its progress before marking it held
+        let _ = download.task_handle.await;
+        state.taskbar.mark_paused(uuid);
+        state.queue.write().await.release(uuid);
+
+        let Ok(Some(mut task)) = state.db.get_download(uuid).await else {
+            continue;
+        };
+        hold_for_conditions(&mut task, &conditions);
+        if let Err(e) = state.db.update_download(&task).await {
+            tracing::error!("Failed to update task {}: {}", uuid, e);
+            continue;
+        }
+        emit_held(app_handle, &task, &conditions);
+        held += 1;
+    }
+
+    persist_queue(&state).await;
+    held
+}
+
+/// Take a download out of the queue and start whatever that frees a slot for.
+/// Downloads started outside the queue aren't in it, which is fine here.
+async fn leave_queue(app_handle: &tauri::AppHandle, state: &State<'_, AppState>, id: Uuid) {
+    let next = match state.queue.write().await.remove(id) {
+        Ok(next) => next,
+        Err(e) => {
+            tracing::debug!("{}", e);
+            None
+        }
+    };
+    persist_queue(state).await;
+    start_dequeued(app_handle, state, next.into_iter().collect()).await;
+}
+
+/// Tell the frontend which category a download was placed in
+pub(crate) fn emit_categorized(app_handle: &tauri::AppHandle, id: Uuid, category: &str) {
+    let _ = app_handle.emit(
+        "download-categorized",
+        serde_json::json!({ "id": id, "category": category }),
+    );
+}
+
+/// Tell the frontend a download has thrown away more of its size than
+/// the warning fraction allows
+fn emit_waste_warning(app_handle: &tauri::AppHandle, task: &DownloadTask) {
+    tracing::warn!("Download {} has wasted {} bytes", task.id, task.wasted_bytes);
+    let _ = app_handle.emit(
+        "download-waste-warning",
+        WasteWarning {
+            id: task.id,
+            file_name: task.file_name.clone(),
+            wasted_bytes: task.wasted_bytes,
+            total_size: task.total_size,
+        },
+    );
+}
+
+/// Tell the frontend a download's segment count changed. `running` is how
+/// many segments of an active download may transfer right now.
+fn emit_segments_changed(app_handle: &tauri::AppHandle, id: Uuid, segments: u8, running: Option<usize>) {
+    let _ = app_handle.emit(
+        "download-segments-changed",
+        serde_json::json!({ "id": id, "segments": segments, "running": running }),
+    );
+}
+
 // Helper function to spawn download task with progress handling
+/// Start running `task`. Starting a download that is already running
+/// fails or does nothing according to `if_active`, so a double-clicked
+/// resume or the scheduler racing the user can't run it twice.
 async fn spawn_download_task(
     app_handle: tauri::AppHandle,
     state: &State<'_, AppState>,
-    task: DownloadTask,
+    mut task: DownloadTask,
+    if_active: IfActive,
 ) -> Result<(), String> {
-    let engine = state.engine.clone();
-    let cancel_token = tokio_util::sync::CancellationToken::new();
-    let cancel_clone = cancel_token.clone();
-    let task_id = task.id;
-
-    let (progress_tx, progress_rx) = flume::unbounded::<DownloadProgress>();
+    // Checked again atomically when registering; this just skips the
+    // segment bookkeeping for a duplicate
+    if state.active_downloads.read().await.contains_key(&task.id) {
+        return match if_active {
+            IfActive::Fail => Err(DownloadError::AlreadyActive(format!("download {} is already running", task.id)).to_string()),
+            IfActive::Skip => Ok(()),
+        };
+    }
 
-    // Progress event emitter
-    let app_handle_clone = app_handle.clone();
-    tokio::spawn(async move {
-        while let Ok(progress) = progress_rx.recv_async().await {
-            let _ = app_handle_clone.emit("download-progress", &progress);
+    // Fresh downloads get fewer segments while many others are running
+    if task.downloaded_size == 0 {
+        let active_count = state.active_downloads.read().await.len();
+        let segments = state.engine.segments_under_contention(task.segments, active_count);
+        if segments != task.segments {
+            tracing::info!(
+                "Starting {} with {} segments instead of {} ({} downloads active)",
+                task.id, segments, task.segments, active_count
+            );
+            task.segments = segments;
+            if let Err(e) = state.db.update_segments(task.id, segments).await {
+                tracing::warn!("Failed to save segment count for {}: {}", task.id, e);
+            }
+            emit_segments_changed(&app_handle, task.id, segments, None);
         }
-    });
+    }
 
+    let engine = state.engine.clone();
+    let task_id = task.id;
     let mut task_clone = task.clone();
     let db = state.db.clone();
+    let taskbar = state.taskbar.clone();
+    let app_state = state.inner().clone();
+    let metrics = state.metrics.clone();
+    let metrics_url = task.url.clone();
+    let resumed_from = task.downloaded_size;
+    let bandwidth = state.bandwidth.clone();
+    let bandwidth_category = task.category.clone();
+    let wasted_before = task.wasted_bytes;
+    let watchdog = state.watchdog.clone();
+    let soft_pauses = state.soft_pauses.clone();
+
+    // Nothing is spawned unless the download is registered, and it is
+    // registered before it can finish and clean up after itself
+    let started = spawn_active(&state.active_downloads, task, if_active, move |cancel_token| {
+        let (progress_tx, progress_rx) = flume::unbounded::<DownloadProgress>();
+
+        // Progress event emitter
+        let app_handle_clone = app_handle.clone();
+        let progress_db = db.clone();
+        taskbar.track(task_id);
+        let progress_taskbar = taskbar.clone();
+        metrics.begin(task_id, &metrics_url, resumed_from);
+        let progress_metrics = metrics.clone();
+        let source = task_id.to_string();
+        bandwidth.begin(&source, bandwidth_category.as_deref(), &domain_of(&metrics_url), resumed_from);
+        tokio::spawn(async move {
+            while let Ok(mut progress) = progress_rx.recv_async().await {
+                // The engine doesn't know about soft pauses
+                if soft_pauses.is_paused(progress.id) {
+                    progress.status = DownloadStatus::Paused;
+                    progress.speed = 0.0;
+                }
+                progress_taskbar.record(&progress);
+                progress_metrics.record_progress(progress.id, progress.downloaded_size);
+                watchdog.record_progress(progress.id, progress.downloaded_size, progress.phase_progress);
+                bandwidth.record_total(&source, progress.downloaded_size);
+                if let Some(category) = &progress.category {
+                    bandwidth.recategorize(&source, category);
+                    if let Err(e) = progress_db
+                        .assign_download_category(&progress.id.to_string(), category)
+                        .await
+                    {
+                        tracing::warn!("Failed to save category for {}: {}", progress.id, e);
+                    }
+                    emit_categorized(&app_handle_clone, progress.id, category);
+                }
+                let _ = app_handle_clone.emit("download-progress", &progress);
+            }
+            // The engine dropped its sender: the transfer is over
+            bandwidth.end(&source);
+        });
+
+        tokio::spawn(async move {
+            let result = engine.start_download(&mut task_clone, cancel_token.clone(), progress_tx).await;
+            if waste::crossed_threshold(
+                wasted_before,
+                task_clone.wasted_bytes,
+                task_clone.total_size,
+                engine.waste_warning_fraction(),
+            ) {
+                emit_waste_warning(&app_handle, &task_clone);
+            }
 
-    let task_handle = tokio::spawn(async move {
-        let result = engine.start_download(&mut task_clone, cancel_clone, progress_tx).await;
-
-        match result {
-            Ok(()) => {
-                task_clone.status = DownloadStatus::Completed;
-                let _ = db.update_download(&task_clone).await;
-                let _ = app_handle.emit("download-complete", &task_clone);
+            match save_run_end(&db, &mut task_clone, &result, cancel_token.is_cancelled()).await {
+                RunEnd::PendingReplace => {
+                    metrics.record_completed(task_id);
+                    taskbar.complete(task_id);
+                    let _ = app_handle.emit("download-pending-replace", &task_clone.for_event());
+                }
+                RunEnd::Completed => {
+                    metrics.record_completed(task_id);
+                    taskbar.complete(task_id);
+                    announce_completed(&app_handle, &app_state, &task_clone).await;
+                }
+                // Pause and cancel are reported through their own commands
+                RunEnd::Interrupted => metrics.end(task_id),
+                RunEnd::Failed => {
+                    let Err(e) = result else {
+                        unreachable!("only a run that returned an error is failed");
+                    };
+                    metrics.record_failed(task_id, &metrics_url);
+                    taskbar.fail(task_id);
+                    // A refused media link retries through yt-dlp, which
+                    // reports how that ends
+                    if !fallback_after_failure(&app_handle, &task_clone, &e).await {
+                        emit_failed(&app_handle, &task_clone, Some(&e));
+                        hook_service::run(&app_handle, HookEvent::DownloadFailed, Some(&task_clone));
+                        NotificationService::notify(
+                            &app_handle,
+                            NotificationType::DownloadFailed {
+                                file_name: task_clone.file_name.clone(),
+                                error: e.to_string(),
+                            },
+                        )
+                        .await;
+                    }
+                }
             }
-            Err(e) => {
-                task_clone.status = DownloadStatus::Failed;
-                task_clone.error_message = Some(e.to_string());
-                let _ = db.update_download(&task_clone).await;
-                let _ = app_handle.emit("download-failed", &task_clone);
+
+            // Pause and cancel already dropped the handle, and a resume may
+            // have registered a new one under the same id
+            if !cancel_token.is_cancelled() {
+                app_state.active_downloads.write().await.remove(&task_id);
+                app_state.queue.write().await.release(task_id);
+                persist_queue(&app_state).await;
+                hook_service::run_if_all_complete(&app_handle).await;
             }
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if !started {
+        tracing::debug!("Download {} is already running", task_id);
+    }
+    Ok(())
+}
+
+/// How an engine run ended, once saved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunEnd {
+    Completed,
+    PendingReplace,
+    Failed,
+    /// Paused, cancelled or held: the cancel token fired
+    Interrupted,
+}
+
+/// Save how an engine run ended. An interrupted run is left alone: whoever
+/// fired its cancel token already wrote the row as Paused, Cancelled or
+/// held, and a Failed written here would race that.
+pub async fn save_run_end(
+    db: &Database,
+    task: &mut DownloadTask,
+    result: &Result<(), DownloadError>,
+    interrupted: bool,
+) -> RunEnd {
+    let end = match result {
+        Ok(()) if task.status == DownloadStatus::CompletedPendingReplace => RunEnd::PendingReplace,
+        Ok(()) => {
+            task.status = DownloadStatus::Completed;
+            RunEnd::Completed
         }
-    });
+        Err(_) if interrupted => return RunEnd::Interrupted,
+        Err(e) => {
+            task.status = DownloadStatus::Failed;
+            task.error_message = Some(e.to_string());
+            RunEnd::Failed
+        }
+    };
+    let _ = db.update_download(task).await;
+    end
+}
 
-    // Store handle for pause/cancel
-    let mut active = state.active_downloads.write().await;
-    active.insert(task_id, ActiveDownload {
-        cancel_token,
-        task_handle,
-        task: std::sync::Arc::new(tokio::sync::RwLock::new(task)),
-    });
+/// Recover a download the watchdog took off the running list: stop what
+/// is left of it, fail it with WATCHDOG_RECOVERED keeping what it
+/// downloaded so it can be resumed, and free its queue slot. One that
+/// had already finished is only reported.
+pub async fn recover_dead_download(app_handle: &tauri::AppHandle, state: &State<'_, AppState>, dead: DeadDownload) {
+    let id = dead.id;
+    dead.handle.cancel_token.cancel();
+    dead.handle.task_handle.abort();
+    if state.sleep_guard.forget(id) {
+        state.sleep_guard.save(&state.db).await;
+    }
 
-    Ok(())
+    let mut task = match state.db.get_download(id).await {
+        Ok(Some(task)) => task,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::error!("Watchdog could not load download {}: {}", id, e);
+            return;
+        }
+    };
+
+    let error = watchdog_service::fail_dead_download(
+        &state.engine,
+        &state.db,
+        &mut task,
+        dead.reason,
+        dead.last_downloaded,
+    )
+    .await;
+    let failed = error.is_some();
+    if let Some(error) = error {
+        state.metrics.record_failed(id, &task.url);
+        state.taskbar.fail(id);
+        emit_failed(app_handle, &task, Some(&error));
+        hook_service::run(app_handle, HookEvent::DownloadFailed, Some(&task));
+    } else {
+        state.metrics.end(id);
+        state.taskbar.forget(id);
+    }
+
+    tracing::warn!(
+        "Watchdog recovered {} ({}), {} bytes kept",
+        task.file_name,
+        dead.reason,
+        task.downloaded_size
+    );
+    let _ = app_handle.emit(
+        "download-watchdog-recovered",
+        WatchdogRecovery { id, file_name: task.file_name.clone(), reason: dead.reason, failed },
+    );
+    leave_queue(app_handle, state, id).await;
+    hook_service::run_if_all_complete(app_handle).await;
+}
+
+/// Tell the UI and the user a download is done, and run its post action
+async fn announce_completed(app_handle: &tauri::AppHandle, state: &AppState, task: &DownloadTask) {
+    state.dir_sizes.invalidate(&task.save_path).await;
+    let _ = app_handle.emit("download-complete", &task.for_event());
+    NotificationService::notify(
+        app_handle,
+        NotificationType::DownloadComplete {
+            file_name: task.file_name.clone(),
+            size: task.total_size.unwrap_or(task.downloaded_size),
+        },
+    )
+    .await;
+    hook_service::run(app_handle, HookEvent::DownloadComplete, Some(task));
+    state.recent_files.record(task);
+    if task.post_action.is_some() {
+        state.conversions.enqueue(app_handle.clone(), task.clone());
+    }
+}
+
+/// Move a download waiting on a file in use into place. False when the
+/// file is still open elsewhere.
+pub async fn finalize_pending_replace(
+    app_handle: &tauri::AppHandle,
+    state: &AppState,
+    mut task: DownloadTask,
+) -> Result<bool, String> {
+    if task.status != DownloadStatus::CompletedPendingReplace {
+        return Err(format!("Download {} isn't waiting to replace a file", task.id));
+    }
+    let pending = file_in_use::pending_path(&task.save_path);
+    if !pending.exists() {
+        return Err(format!("The finished file for {} is missing", task.file_name));
+    }
+    if !DownloadEngine::replace_when_free(&pending, &task.save_path).await.map_err(|e| e.to_string())? {
+        return Ok(false);
+    }
+
+    state.engine.apply_file_permissions(&task);
+    task.status = DownloadStatus::Completed;
+    state.db.update_download(&task).await.map_err(|e| e.to_string())?;
+    tracing::info!("Moved finished download {} into place", task.id);
+    announce_completed(app_handle, state, &task).await;
+    Ok(true)
+}
+
+/// Retry replacing a file that was in use when the download finished.
+/// Fails with FILE_IN_USE while another program still has it open.
+#[tauri::command]
+pub async fn finalize_download(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<(), String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    let task = state.db.get_download(uuid)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Download not found".to_string())?;
+    let path = task.save_path.clone();
+    if finalize_pending_replace(&app_handle, &state, task).await? {
+        Ok(())
+    } else {
+        Err(format!("FILE_IN_USE: {} is open in another program", path.display()))
+    }
+}
+
+/// Try every download waiting on a file in use; the number moved into place
+pub async fn finalize_pending_replaces(app_handle: &tauri::AppHandle) -> Result<usize, String> {
+    let state = app_handle.state::<AppState>();
+    let waiting = state.db.get_downloads_with_status(DownloadStatus::CompletedPendingReplace)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut moved = 0;
+    for task in waiting {
+        let id = task.id;
+        match finalize_pending_replace(app_handle, &state, task).await {
+            Ok(true) => moved += 1,
+            Ok(false) => {}
+            Err(e) => tracing::warn!("Failed to finalize download {}: {}", id, e),
+        }
+    }
+    Ok(moved)
+}
+
+/// What `add_download` returns: the new download, or with `simulate` set
+/// the plan adding it would follow
+#[derive(Debug, serde::Serialize)]
+#[serde(untagged)]
+pub enum AddDownloadResult {
+    Added(DownloadTask),
+    Simulated(Box<DownloadPlan>),
 }
 
 #[tauri::command]
@@ -104,56 +665,302 @@ pub async fn add_download(
     app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
     request: AddDownloadRequest,
+) -> Result<AddDownloadResult, String> {
+    if request.simulate {
+        if YouTubeDownloader::is_supported_url(&request.url) {
+            return Err("Simulating yt-dlp downloads is not supported".to_string());
+        }
+        let plan = plan_add(&state, &request).await.map_err(|e| e.to_string())?;
+        return Ok(AddDownloadResult::Simulated(Box::new(plan)));
+    }
+    add_download_task(app_handle, state, request)
+        .await
+        .map(AddDownloadResult::Added)
+}
+
+/// Everything adding `request` would decide, including what the database
+/// and the machine's conditions say, without adding it
+pub async fn plan_add(state: &AppState, request: &AddDownloadRequest) -> Result<DownloadPlan, DownloadError> {
+    let mut plan = state.engine.plan_download(request).await?;
+    plan.duplicates = state.db.find_by_url(&request.url, plan.resolved_url.as_deref()).await?;
+    plan.confirmation = state.engine.size_limits().confirmation_reason(plan.total_size, request.source);
+    plan.held_for = state.resource_guard.check(plan.total_size).await;
+    Ok(plan)
+}
+
+/// Add a download and start, queue or hold it as its plan says
+pub async fn add_download_task(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    request: AddDownloadRequest,
 ) -> Result<DownloadTask, String> {
+    match plan_download_task(&app_handle, &state, &request).await? {
+        AddStep::Done(task) => Ok(task),
+        AddStep::Planned(task, conditions) => start_planned(app_handle, &state, task, conditions).await,
+    }
+}
+
+/// How far `plan_download_task` took an add
+enum AddStep {
+    /// Handed to yt-dlp or held for confirmation; nothing left to do
+    Done(DownloadTask),
+    /// Probed and checked but not saved yet; `start_planned` saves and
+    /// starts it
+    Planned(DownloadTask, Vec<BlockingCondition>),
+}
+
+/// The part of an add that can fail on the request itself: the probe,
+/// the save path and size checks
+async fn plan_download_task(
+    app_handle: &tauri::AppHandle,
+    state: &State<'_, AppState>,
+    request: &AddDownloadRequest,
+) -> Result<AddStep, String> {
     // Check if URL is supported by yt-dlp (YouTube, Vimeo, etc.)
     if YouTubeDownloader::is_supported_url(&request.url) {
-        return handle_youtube_download(app_handle, state, request).await;
+        return handle_youtube_download(app_handle.clone(), state.clone(), request.clone(), None)
+            .await
+            .map(AddStep::Done);
     }
 
-    // Create task via engine helper so logic stays centralized
-    let mut task = state
-        .engine
-        .create_task(&request)
+    let mut plan = match plan_add(state, request).await {
+        Ok(plan) => plan,
+        Err(e) => return fallback_on_add(app_handle, state, request, e).await.map(AddStep::Done),
+    };
+    for warning in &plan.warnings {
+        tracing::warn!("{}", warning);
+    }
+    let confirmation = plan.confirmation.take();
+    let conditions = std::mem::take(&mut plan.held_for);
+    let mut task = plan.into_task(request);
+
+    if let Some(reason) = confirmation {
+        hold_for_confirmation(app_handle, &state.db, &mut task, reason).await?;
+        return Ok(AddStep::Done(task));
+    }
+    Ok(AddStep::Planned(task, conditions))
+}
+
+/// Save a planned download and start it, or hold it for `conditions`
+async fn start_planned(
+    app_handle: tauri::AppHandle,
+    state: &State<'_, AppState>,
+    mut task: DownloadTask,
+    conditions: Vec<BlockingCondition>,
+) -> Result<DownloadTask, String> {
+    if conditions.is_empty() {
+        // The row is written before the download starts
+        task_launch::launch(&state.db, &mut task, |task| {
+            spawn_download_task(app_handle.clone(), state, task, IfActive::Fail)
+        })
         .await
         .map_err(|e| e.to_string())?;
+    } else {
+        hold_for_conditions(&mut task, &conditions);
+        state.db.reserve_and_insert(&mut task).await.map_err(|e| e.to_string())?;
+        emit_held(&app_handle, &task, &conditions);
+    }
 
-    // When first created, mark as downloading
-    task.status = DownloadStatus::Downloading;
+    if let Some(category) = &task.category {
+        emit_categorized(&app_handle, task.id, category);
+    }
 
-    // Save to database
-    state.db.insert_download(&task).await
-        .map_err(|e| e.to_string())?;
+    Ok(task)
+}
 
-    // Start download in background using helper
-    let task_clone = task.clone();
-    spawn_download_task(app_handle, &state, task_clone).await?;
+/// Release a download held for confirmation into the queue
+#[tauri::command]
+pub async fn confirm_download(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<DownloadTask, String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    let mut task = state.db.get_download(uuid)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Download not found")?;
+    if task.status != DownloadStatus::NeedsConfirmation {
+        return Err("Download is not waiting for confirmation".to_string());
+    }
+
+    task.status = DownloadStatus::Queued;
+    task.error_message = None;
+    state.db.update_download(&task).await.map_err(|e| e.to_string())?;
+
+    let to_start = {
+        let mut queue = state.queue.write().await;
+        queue.push_waiting(task.id);
+        queue.fill()
+    };
+    persist_queue(&state).await;
+    start_dequeued(&app_handle, &state, to_start).await;
 
+    tracing::info!("Download {} confirmed", task.id);
     Ok(task)
 }
 
+/// Change how many segments a download uses. Queued and paused downloads
+/// just take the new plan. A running segmented download stops its newest
+/// segments at their next network chunk when lowered; they finish later as
+/// slots free up. Raising a running download applies when it next starts.
+#[tauri::command]
+pub async fn set_download_segments(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+    segments: u8,
+) -> Result<(), String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    if !(1..=crate::utils::constants::MAX_SEGMENTS).contains(&segments) {
+        return Err(format!(
+            "Segments must be between 1 and {}",
+            crate::utils::constants::MAX_SEGMENTS
+        ));
+    }
+    let task = state.db.get_download(uuid)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Download not found")?;
+    if matches!(task.status, DownloadStatus::Completed | DownloadStatus::Cancelled) {
+        return Err("Download has already finished".to_string());
+    }
+
+    state.db.update_segments(uuid, segments).await.map_err(|e| e.to_string())?;
+    let running = state.engine.reduce_live_segments(uuid, segments);
+    if let Some(active) = state.active_downloads.read().await.get(&uuid) {
+        active.task.write().await.segments = segments;
+    }
+
+    tracing::info!("Download {} set to {} segments (running: {:?})", uuid, segments, running);
+    emit_segments_changed(&app_handle, uuid, segments, running);
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn pause_download(
     app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
     id: String,
+    soft: Option<bool>,
 ) -> Result<(), String> {
     let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
 
+    // A quick pause keeps the connections open, when soft pausing is on
+    if soft.unwrap_or(false) {
+        if let Some(limit) = state.soft_pauses.limit() {
+            if soft_pause(&app_handle, &state, uuid, limit).await? {
+                return Ok(());
+            }
+        }
+    }
+
+    if state.sleep_guard.forget(uuid) {
+        state.sleep_guard.save(&state.db).await;
+    }
+
     let mut active = state.active_downloads.write().await;
     if let Some(handle) = active.remove(&uuid) {
         handle.cancel_token.cancel();
+        state.taskbar.mark_paused(uuid);
         state.db.update_status(uuid, DownloadStatus::Paused)
             .await.map_err(|e| e.to_string())?;
         
         // Get updated task and emit event
         if let Some(task) = state.db.get_download(uuid).await.map_err(|e| e.to_string())? {
-            let _ = app_handle.emit("download-paused", &task);
+            let _ = app_handle.emit("download-paused", &task.for_event());
         }
     }
+    drop(active);
+    // A soft pause becoming a real one lets its segments see the cancel
+    state.soft_pauses.resume(uuid);
+    state.engine.speed_limiter.release(uuid);
+
+    // A paused download is no longer part of the queue
+    leave_queue(&app_handle, &state, uuid).await;
 
     Ok(())
 }
 
+/// Hold a running download at zero bandwidth with its connections open.
+/// Past `limit` it becomes a real pause. False when it isn't running.
+async fn soft_pause(
+    app_handle: &tauri::AppHandle,
+    state: &State<'_, AppState>,
+    uuid: Uuid,
+    limit: std::time::Duration,
+) -> Result<bool, String> {
+    let active = state.active_downloads.read().await;
+    let Some(handle) = active.get(&uuid) else {
+        return Ok(false);
+    };
+    let Some(pause) = state.soft_pauses.begin(uuid) else {
+        // Already soft paused
+        return Ok(true);
+    };
+    state.engine.speed_limiter.hold(uuid);
+    handle.task.write().await.status = DownloadStatus::Paused;
+    let cancel_token = handle.cancel_token.clone();
+    drop(active);
+
+    state.taskbar.mark_paused(uuid);
+    // Should the app die meanwhile, the download comes back as paused
+    state.db.update_status(uuid, DownloadStatus::Paused)
+        .await.map_err(|e| e.to_string())?;
+    if let Some(task) = state.db.get_download(uuid).await.map_err(|e| e.to_string())? {
+        let _ = app_handle.emit("download-paused", &task.for_event());
+    }
+
+    let app_handle = app_handle.clone();
+    tokio::spawn(async move {
+        let state = app_handle.state::<AppState>();
+        tokio::select! {
+            _ = tokio::time::sleep(limit) => {
+                if state.soft_pauses.finish(uuid, pause.generation) {
+                    tracing::info!("Soft pause of {} ran past {:?}, pausing it", uuid, limit);
+                    if let Err(e) = pause_download(app_handle.clone(), state.clone(), uuid.to_string(), None).await {
+                        tracing::warn!("Failed to pause {} after its soft pause: {}", uuid, e);
+                    }
+                }
+            }
+            // Resuming releases the hold itself
+            _ = pause.resumed.cancelled() => {}
+            // Cancelled or removed meanwhile
+            _ = cancel_token.cancelled() => {
+                if state.soft_pauses.finish(uuid, pause.generation) {
+                    state.engine.speed_limiter.release(uuid);
+                }
+            }
+        }
+    });
+
+    tracing::info!("Soft paused {} for up to {:?}", uuid, limit);
+    Ok(true)
+}
+
+/// A soft paused download carries on over the connections it kept.
+/// False when `uuid` isn't soft paused.
+async fn resume_soft_paused(
+    app_handle: &tauri::AppHandle,
+    state: &AppState,
+    uuid: Uuid,
+) -> Result<bool, String> {
+    if !state.soft_pauses.resume(uuid) {
+        return Ok(false);
+    }
+    if let Some(handle) = state.active_downloads.read().await.get(&uuid) {
+        handle.task.write().await.status = DownloadStatus::Downloading;
+    }
+    state.engine.speed_limiter.release(uuid);
+    state.taskbar.mark_resumed(uuid);
+    state.db.update_status(uuid, DownloadStatus::Downloading)
+        .await.map_err(|e| e.to_string())?;
+    if let Some(task) = state.db.get_download(uuid).await.map_err(|e| e.to_string())? {
+        let _ = app_handle.emit("download-resumed", &task.for_event());
+    }
+    Ok(true)
+}
+
 #[tauri::command]
 pub async fn resume_download(
     app_handle: tauri::AppHandle,
@@ -162,11 +969,28 @@ pub async fn resume_download(
 ) -> Result<(), String> {
     let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
 
+    if resume_soft_paused(&app_handle, &state, uuid).await? {
+        return Ok(());
+    }
+
+    // A second click while it is already running does nothing
+    if state.active_downloads.read().await.contains_key(&uuid) {
+        return Ok(());
+    }
+
     let mut task = state.db.get_download(uuid)
         .await
         .map_err(|e| e.to_string())?
         .ok_or("Download not found")?;
 
+    // Starting it, by hand or on schedule, uses up its night slot
+    if task.scheduled_for.take().is_some() {
+        state.scheduler.remove_for_download(&id).await;
+        state.db.set_scheduled_for(uuid, None)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
     // Update status to downloading
     task.status = DownloadStatus::Downloading;
     state.db.update_download(&task)
@@ -174,34 +998,66 @@ pub async fn resume_download(
         .map_err(|e| e.to_string())?;
     
     // Emit event so UI updates immediately
-    let _ = app_handle.emit("download-resumed", &task);
+    let _ = app_handle.emit("download-resumed", &task.for_event());
 
     // Re-start download with resume using helper
-    spawn_download_task(app_handle.clone(), &state, task).await?;
+    spawn_download_task(app_handle.clone(), &state, task, IfActive::Skip).await?;
 
     Ok(())
 }
 
 #[tauri::command]
 pub async fn cancel_download(
+    app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
     id: String,
 ) -> Result<(), String> {
     let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
 
+    // Transfers outside the engine record their own end
+    if let Some(transfer) = state.cancel_remote_transfer(uuid) {
+        if let Some(restart) = transfer.restart {
+            state.undo.record(
+                UndoKind::CancelDownload,
+                format!("Cancel {}", restart.name()),
+                UndoAction::CancelRemote { id: uuid, restart },
+            );
+        }
+        return Ok(());
+    }
+
     let mut active = state.active_downloads.write().await;
     if let Some(handle) = active.remove(&uuid) {
         handle.cancel_token.cancel();
     }
+    drop(active);
+    state.taskbar.forget(uuid);
+    if state.sleep_guard.forget(uuid) {
+        state.sleep_guard.save(&state.db).await;
+    }
+    let schedules = state.scheduler.remove_for_download(&id).await;
+
+    leave_queue(&app_handle, &state, uuid).await;
 
+    let previous = state.db.get_download(uuid)
+        .await.map_err(|e| e.to_string())?;
     state.db.update_status(uuid, DownloadStatus::Cancelled)
         .await.map_err(|e| e.to_string())?;
 
+    if let Some(task) = previous.filter(|t| t.status != DownloadStatus::Cancelled) {
+        state.undo.record(
+            UndoKind::CancelDownload,
+            format!("Cancel {}", task.file_name),
+            UndoAction::Cancel { previous: vec![(uuid, task.status)], schedules },
+        );
+    }
+
     Ok(())
 }
 
 #[tauri::command]
 pub async fn remove_download(
+    app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
     id: String,
     delete_file: bool,
@@ -214,67 +1070,298 @@ pub async fn remove_download(
         handle.cancel_token.cancel();
     }
     drop(active);
+    state.taskbar.forget(uuid);
+    let schedules = state.scheduler.remove_for_download(&id).await;
+
+    leave_queue(&app_handle, &state, uuid).await;
+
+    let task = state.db.get_download(uuid)
+        .await.map_err(|e| e.to_string())?;
+
+    // A removal can be undone unless the file was deleted for good
+    let mut undoable = true;
+    let mut trashed_file = None;
+    if delete_file {
+        if let Some(task) = &task {
+            // Until the swap, the download's file is the waiting copy; the
+            // one at the path belongs to whoever has it open
+            let path = match task.status {
+                DownloadStatus::CompletedPendingReplace => file_in_use::pending_path(&task.save_path),
+                _ => task.save_path.clone(),
+            };
+            if !state.undo.settings().soft_delete {
+                let _ = tokio::fs::remove_file(&path).await;
+                undoable = false;
+            } else if path.exists() {
+                undo::move_to_trash(&path).await.map_err(|e| e.to_string())?;
+                trashed_file = Some(path);
+            }
+        }
+    }
+
+    state.db.delete_download(uuid)
+        .await.map_err(|e| e.to_string())?;
+
+    if let Some(task) = task.filter(|_| undoable) {
+        state.undo.record(
+            UndoKind::RemoveDownload,
+            format!("Remove {}", task.file_name),
+            UndoAction::RemoveDownload { task: Box::new(task), trashed_file, schedules },
+        );
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn retry_download(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<(), String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+
+    // A second click while it is already running does nothing
+    if state.active_downloads.read().await.contains_key(&uuid) {
+        return Ok(());
+    }
+
+    let mut task = state.db.get_download(uuid)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Download not found")?;
 
-    if delete_file {
-        if let Some(task) = state.db.get_download(uuid)
-            .await.map_err(|e| e.to_string())? 
-        {
-            let _ = tokio::fs::remove_file(&task.save_path).await;
+    requeue_for_retry(&state, &mut task).await?;
+    let to_start = state.queue.write().await.fill();
+    persist_queue(&state).await;
+    start_dequeued(&app_handle, &state, to_start).await;
+
+    Ok(())
+}
+
+/// Put a download back in the queue under its own row, so the queue and
+/// the database agree on it. A completed download whose file has gone
+/// missing starts over; anything else keeps what it has to resume from.
+pub async fn requeue_for_retry(state: &AppState, task: &mut DownloadTask) -> Result<(), String> {
+    if matches!(task.status, DownloadStatus::Completed | DownloadStatus::CompletedPendingReplace) {
+        if task.save_path.exists() {
+            return Err("The file is still there; download it again as a new entry instead".to_string());
         }
+        state.engine.discard_progress(task).await.map_err(|e| e.to_string())?;
+        task.completed_at = None;
+        task.actual_checksum = None;
     }
 
-    state.db.delete_download(uuid)
-        .await.map_err(|e| e.to_string())?;
+    task.status = DownloadStatus::Queued;
+    task.error_message = None;
+    task.speed = 0.0;
+    task.eta = None;
+    state.db.update_download(task).await.map_err(|e| e.to_string())?;
+    if task.integrity_status.take().is_some() {
+        state.db.set_integrity_status(task.id, None).await.map_err(|e| e.to_string())?;
+    }
 
+    state.queue.write().await.push_waiting(task.id);
+    persist_queue(state).await;
     Ok(())
 }
 
+/// Download a completed file again as a new history entry.
+///
+/// If the server now reports a different size or ETag, the old file is
+/// either kept under a dated name (`keep_old`) or replaced, and checksum
+/// expectations are dropped. The old entry is linked to the new one via
+/// `superseded_by`.
 #[tauri::command]
-pub async fn retry_download(
+pub async fn redownload(
     app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
     id: String,
-) -> Result<(), String> {
+    keep_old: bool,
+) -> Result<DownloadTask, String> {
     let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
 
-    let task = state.db.get_download(uuid)
+    let mut old_task = state.db.get_download(uuid)
         .await
         .map_err(|e| e.to_string())?
         .ok_or("Download not found")?;
 
-    // Reset status in DB
-    state
-        .db
-        .update_status(uuid, DownloadStatus::Queued)
+    if old_task.status != DownloadStatus::Completed {
+        return Err("Only completed downloads can be re-downloaded; use retry instead".to_string());
+    }
+
+    let info = state.engine
+        .get_file_info(&old_task.url)
         .await
         .map_err(|e| e.to_string())?;
 
-    // Re-trigger download using unified AddDownloadRequest
+    let size_changed = matches!(
+        (info.total_size, old_task.total_size),
+        (Some(new), Some(old)) if new != old
+    );
+    let etag_changed = matches!(
+        (&info.etag, &old_task.etag),
+        (Some(new), Some(old)) if new != old
+    );
+    let changed = size_changed || etag_changed;
+
+    let original_name = old_task.file_name.clone();
+
+    // The new entry gets its own id, so it starts from a fresh resume state
     let request = AddDownloadRequest {
-        url: task.url.clone(),
-        save_path: task
+        url: old_task.url.clone(),
+        save_path: old_task
             .save_path
             .parent()
             .map(|p| p.to_string_lossy().to_string()),
-        segments: Some(task.segments),
-        max_retries: Some(task.retry_count),
-        expected_checksum: task.expected_checksum.clone(),
-        checksum_type: task
-            .checksum_algorithm
-            .as_ref()
-            .map(|alg| alg.to_string()),
-        file_name: Some(task.file_name.clone()),
-        category: task.category.clone(),
-        priority: Some(task.priority),
+        segments: Some(old_task.segments),
+        max_retries: None,
+        // A checksum for the old content can't match the new content
+        expected_checksum: if changed { None } else { old_task.expected_checksum.clone() },
+        checksum_type: if changed {
+            None
+        } else {
+            old_task.checksum_algorithm.as_ref().map(|alg| alg.to_string())
+        },
+        file_name: Some(original_name.clone()),
+        category: old_task.category.clone(),
+        priority: Some(old_task.priority),
         youtube_format: None,
         youtube_quality: None,
         youtube_video_format: None,
         youtube_audio_format: None,
+        notes: old_task.notes.clone(),
+        source_page_url: old_task.source_page_url.clone(),
+        source_page_title: old_task.source_page_title.clone(),
+        retry: old_task.retry_policy.clone(),
+        source: DownloadSource::Manual,
+        probe: ProbeMode::Required,
+        temp_dir: old_task.temp_dir.as_ref().map(|p| p.to_string_lossy().to_string()),
+        url_refresh: old_task.url_refresh.clone(),
+        post_action: old_task.post_action.clone(),
+        simulate: false,
+        playlist_mode: None,
+        existing_location: true,
     };
 
-    add_download(app_handle, state, request).await?;
+    // The old file is only moved or deleted once the new download is
+    // planned, so a failed probe or a refused path leaves it in place
+    let step = plan_download_task(&app_handle, &state, &request).await?;
+
+    if old_task.save_path.exists() {
+        if changed && keep_old {
+            let date = old_task.completed_at.unwrap_or(old_task.created_at).date();
+            let versioned = file_utils::get_versioned_path(&old_task.save_path, date);
+            tokio::fs::rename(&old_task.save_path, &versioned)
+                .await
+                .map_err(|e| format!("Failed to keep old version: {}", e))?;
+
+            tracing::info!("Kept previous version of {} as {:?}", original_name, versioned);
+            old_task.file_name = versioned
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| original_name.clone());
+            old_task.save_path = versioned;
+            state.db.update_download(&old_task).await.map_err(|e| e.to_string())?;
+        } else {
+            tokio::fs::remove_file(&old_task.save_path)
+                .await
+                .map_err(|e| format!("Failed to replace old file: {}", e))?;
+        }
+    }
 
-    Ok(())
+    let new_task = match step {
+        AddStep::Done(task) => task,
+        AddStep::Planned(mut task, conditions) => {
+            // It was planned beside the old file; take the name back
+            let original_path = task.save_path.with_file_name(&original_name);
+            if !original_path.exists() {
+                task.save_path = original_path;
+                task.file_name = original_name;
+            }
+            start_planned(app_handle, &state, task, conditions).await?
+        }
+    };
+
+    state.db.mark_superseded(old_task.id, new_task.id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(new_task)
+}
+
+/// Check completed downloads for files that no longer exist on disk
+#[tauri::command]
+pub async fn check_missing_files(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<SweepSummary, String> {
+    integrity_service::run_sweep(&app_handle, &state).await
+}
+
+/// Retry every download flagged missing by the integrity sweep, keeping
+/// the original file names. Limited to one category when given.
+#[tauri::command]
+pub async fn redownload_missing(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    category: Option<String>,
+) -> Result<Vec<String>, String> {
+    let retried = requeue_missing(&state, category).await?;
+    let to_start = state.queue.write().await.fill();
+    persist_queue(&state).await;
+    start_dequeued(&app_handle, &state, to_start).await;
+    tracing::info!("Redownloading {} missing files", retried.len());
+    Ok(retried)
+}
+
+/// Queue every download flagged missing again under its own row
+pub async fn requeue_missing(state: &AppState, category: Option<String>) -> Result<Vec<String>, String> {
+    let missing: Vec<DownloadTask> = state.db.get_completed_downloads()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|task| task.integrity_status == Some(IntegrityStatus::Missing))
+        .filter(|task| category.is_none() || task.category == category)
+        .collect();
+
+    let mut retried = Vec::new();
+    for mut task in missing {
+        if let Err(e) = requeue_for_retry(state, &mut task).await {
+            tracing::error!("Failed to redownload missing file {}: {}", task.id, e);
+            continue;
+        }
+        retried.push(task.id.to_string());
+    }
+    Ok(retried)
+}
+
+/// Edit the notes and source page of a download
+#[tauri::command]
+pub async fn update_download_metadata(
+    state: State<'_, AppState>,
+    id: String,
+    patch: DownloadMetadataPatch,
+) -> Result<DownloadTask, String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+
+    let mut task = state.db.get_download(uuid)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Download not found")?;
+
+    patch.apply(&mut task);
+
+    state.db.update_download_metadata(&task)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Keep the in-memory copy of an active download in sync
+    if let Some(active) = state.active_downloads.read().await.get(&uuid) {
+        patch.apply(&mut *active.task.write().await);
+    }
+
+    Ok(task)
 }
 
 #[tauri::command]
@@ -297,35 +1384,396 @@ pub async fn get_file_info(
         .map_err(|e| e.to_string())
 }
 
+/// A URL a batch add could not add, and why
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchAddFailure {
+    pub url: String,
+    pub reason: String,
+}
+
+/// Outcome of `add_batch_downloads`
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchAddSummary {
+    pub batch_id: Uuid,
+    pub added: Vec<DownloadTask>,
+    pub failed: Vec<BatchAddFailure>,
+    /// Left out by the content filter
+    pub skipped: Vec<BatchAddFailure>,
+    /// Every input URL, what it normalized to and whether it was added,
+    /// dropped as a duplicate or invalid
+    pub normalized: Vec<NormalizedUrl>,
+}
+
+impl BatchAddSummary {
+    /// A summary with nothing added yet. Invalid URLs still count as
+    /// failed, as they did before normalizing.
+    fn new(batch_id: Uuid, normalized: Vec<NormalizedUrl>) -> Self {
+        let failed = normalized
+            .iter()
+            .filter(|entry| entry.action == BatchUrlAction::Invalid)
+            .map(|entry| BatchAddFailure {
+                url: entry.input.clone(),
+                reason: entry.error.clone().unwrap_or_default(),
+            })
+            .collect();
+        Self {
+            batch_id,
+            added: Vec::new(),
+            failed,
+            skipped: Vec::new(),
+            normalized,
+        }
+    }
+}
+
+/// Emitted as `batch-add-progress` each time a batch URL is probed
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchAddProgress<'a> {
+    batch_id: Uuid,
+    completed: usize,
+    total: usize,
+    added: usize,
+    failed: usize,
+    url: &'a str,
+    error: Option<&'a str>,
+}
+
+/// Add many URLs at once. URLs are probed a few at a time (the batch
+/// concurrency setting) and reported as `batch-add-progress` events; the
+/// new rows are then written in one transaction and queued. Video site
+/// links go through yt-dlp and are saved on their own. A URL that fails is
+/// listed in the summary and doesn't stop the rest. With a `filter`,
+/// probed files it leaves out are listed as skipped instead of added;
+/// yt-dlp links are added before their file is known and aren't filtered.
+/// URLs are normalized first (see `normalize_urls`); duplicates are only
+/// listed in `normalized`, invalid URLs also under `failed`.
 #[tauri::command]
 pub async fn add_batch_downloads(
     app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
     urls: Vec<String>,
     save_path: Option<String>,
-) -> Result<Vec<DownloadTask>, String> {
-    let mut tasks = Vec::new();
-    for url in urls {
-        let request = AddDownloadRequest {
-            url,
-            save_path: save_path.clone(),
-            segments: None,
-            max_retries: None,
-            expected_checksum: None,
-            checksum_type: None,
-            file_name: None,
-            category: None,
-            priority: None,
-            youtube_format: None,
-            youtube_quality: None,
-            youtube_video_format: None,
-            youtube_audio_format: None,
-        };
+    filter: Option<ContentFilter>,
+    normalize: Option<NormalizeOptions>,
+) -> Result<BatchAddSummary, String> {
+    let filter = filter.map(|filter| filter.compile()).transpose()?;
+    let batch_id = Uuid::new_v4();
+    let normalized = UrlParser::normalize_batch(&urls, &state.engine.normalize_options(normalize));
+    let urls: Vec<String> = normalized
+        .iter()
+        .filter(|entry| entry.action == BatchUrlAction::Added)
+        .filter_map(|entry| entry.normalized.clone())
+        .collect();
+    let total = urls.len();
+    tracing::info!(
+        "Batch {}: adding {} URLs ({} duplicate or invalid)",
+        batch_id,
+        total,
+        normalized.len() - total
+    );
+
+    let mut summary = BatchAddSummary::new(batch_id, normalized);
+    let requests = urls.into_iter().map(|url| batch_request(url, save_path.clone())).collect();
+    add_requests(&app_handle, &state, requests, filter.as_ref(), &mut summary).await;
+    Ok(summary)
+}
+
+/// What a batch adds for `url` when nothing else is asked for
+fn batch_request(url: String, save_path: Option<String>) -> AddDownloadRequest {
+    AddDownloadRequest {
+        url,
+        save_path,
+        segments: None,
+        max_retries: None,
+        expected_checksum: None,
+        checksum_type: None,
+        file_name: None,
+        category: None,
+        priority: None,
+        youtube_format: None,
+        youtube_quality: None,
+        youtube_video_format: None,
+        youtube_audio_format: None,
+        notes: None,
+        source_page_url: None,
+        source_page_title: None,
+        retry: None,
+        source: DownloadSource::Manual,
+        probe: ProbeMode::Required,
+        temp_dir: None,
+        url_refresh: None,
+        post_action: None,
+        simulate: false,
+        playlist_mode: None,
+        existing_location: false,
+    }
+}
+
+/// Probe `requests` a few at a time, reporting each as `batch-add-progress`,
+/// then write the new rows in one transaction and queue them. What was
+/// added, skipped by `filter` or failed is recorded in `summary`.
+async fn add_requests(
+    app_handle: &tauri::AppHandle,
+    state: &State<'_, AppState>,
+    requests: Vec<AddDownloadRequest>,
+    filter: Option<&CompiledFilter>,
+    summary: &mut BatchAddSummary,
+) {
+    let batch_id = summary.batch_id;
+    let total = requests.len();
+    let mut probes = futures_util::stream::iter(requests.into_iter().enumerate().map(|(index, request)| {
+        let app_handle = app_handle.clone();
+        let state = state.clone();
+        async move {
+            // (task, already saved)
+            let result = if YouTubeDownloader::is_supported_url(&request.url) {
+                add_download_task(app_handle, state, request.clone()).await.map(|task| (task, true))
+            } else {
+                state.engine
+                    .create_task(&request)
+                    .await
+                    .map(|task| (task, false))
+                    .map_err(|e| e.to_string())
+            };
+            (index, request.url, result)
+        }
+    }))
+    .buffer_unordered(state.engine.batch_concurrency());
+
+    let mut results = Vec::with_capacity(total);
+    let (mut added, mut failed) = (0, 0);
+    while let Some((index, url, result)) = probes.next().await {
+        match &result {
+            Ok(_) => added += 1,
+            Err(e) => {
+                tracing::warn!("Batch {}: failed to add {}: {}", batch_id, url, e);
+                failed += 1;
+            }
+        }
+        let _ = app_handle.emit("batch-add-progress", BatchAddProgress {
+            batch_id,
+            completed: results.len() + 1,
+            total,
+            added,
+            failed,
+            url: &url,
+            error: result.as_ref().err().map(String::as_str),
+        });
+        results.push((index, url, result));
+    }
+    drop(probes);
+    // Keep the order the URLs were given in
+    results.sort_by_key(|(index, ..)| *index);
+
+    let mut pending = Vec::new();
+    for (_, url, result) in results {
+        match result {
+            Ok((task, true)) => summary.added.push(task),
+            Ok((task, false)) => match filter.map(|f| f.check(&task.file_name, task.total_size)) {
+                Some(Err(reason)) => summary.skipped.push(BatchAddFailure { url, reason: reason.to_string() }),
+                _ => pending.push(task),
+            },
+            Err(reason) => summary.failed.push(BatchAddFailure { url, reason }),
+        }
+    }
+
+    let sizes: Vec<Option<u64>> = pending.iter().map(|task| task.total_size).collect();
+    let conditions = state.resource_guard.check_all(&sizes).await;
+    for (task, conditions) in pending.iter_mut().zip(&conditions) {
+        if conditions.is_empty() {
+            task.status = DownloadStatus::Queued;
+        } else {
+            hold_for_conditions(task, conditions);
+        }
+    }
+
+    if let Err(e) = state.db.reserve_and_insert_all(&mut pending).await {
+        // Nothing from the transaction was written
+        tracing::error!("Batch {}: failed to save downloads: {}", batch_id, e);
+        summary.failed.extend(pending.into_iter().map(|task| BatchAddFailure {
+            url: task.url,
+            reason: e.to_string(),
+        }));
+        return;
+    }
+
+    {
+        let mut queue = state.queue.write().await;
+        for task in pending.iter().filter(|task| task.status == DownloadStatus::Queued) {
+            queue.push_waiting(task.id);
+        }
+    }
+    for (task, conditions) in pending.iter().zip(&conditions) {
+        if !conditions.is_empty() {
+            emit_held(app_handle, task, conditions);
+        }
+        if let Some(category) = &task.category {
+            emit_categorized(app_handle, task.id, category);
+        }
+    }
+    let to_start = state.queue.write().await.fill();
+    persist_queue(state).await;
+    start_dequeued(app_handle, state, to_start).await;
+
+    tracing::info!(
+        "Batch {}: {} added, {} failed, {} skipped",
+        batch_id,
+        pending.len() + summary.added.len(),
+        summary.failed.len(),
+        summary.skipped.len()
+    );
+    summary.added.extend(pending);
+}
+
+/// Normalize pasted or imported URLs and drop duplicates without adding
+/// anything, so a batch can be reviewed first. Tracking parameters come
+/// from settings unless `options` turns stripping off; relative URLs need
+/// `options.base_url`.
+#[tauri::command]
+pub async fn normalize_urls(
+    state: State<'_, AppState>,
+    urls: Vec<String>,
+    options: Option<NormalizeOptions>,
+) -> Result<Vec<NormalizedUrl>, String> {
+    Ok(UrlParser::normalize_batch(&urls, &state.engine.normalize_options(options)))
+}
+
+/// Normalizing for a page's media list: relative URLs are the page's
+fn page_normalize_options(state: &AppState, referrer: Option<&str>) -> NormalizeOptions {
+    state.engine.normalize_options(Some(NormalizeOptions {
+        base_url: referrer.map(str::to_string),
+        ..NormalizeOptions::default()
+    }))
+}
+
+/// Statuses of the downloads already made from `url`
+async fn existing_statuses(
+    db: &crate::database::db::Database,
+    url: &str,
+    final_url: Option<&str>,
+) -> Vec<DownloadStatus> {
+    let ids = db.find_by_url(url, final_url).await.unwrap_or_default();
+    let mut statuses = Vec::with_capacity(ids.len());
+    for id in ids {
+        if let Ok(Some(task)) = db.get_download(id).await {
+            statuses.push(task.status);
+        }
+    }
+    statuses
+}
+
+/// Probe a page's media list for the selection dialog without adding
+/// anything. URLs are probed a few at a time, and only a few per host, with
+/// the page sent as Referer; each result is emitted as
+/// `probe-batch-progress` as soon as it is known. Repeats on the page and
+/// URLs already in the download list are flagged rather than dropped.
+#[tauri::command]
+pub async fn probe_batch(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    urls: Vec<String>,
+    referrer: Option<String>,
+) -> Result<ProbeBatchSummary, String> {
+    let batch_id = Uuid::new_v4();
+    let referrer = referrer.as_deref();
+    let normalized = UrlParser::normalize_batch(&urls, &page_normalize_options(&state, referrer));
+    let total = normalized.len();
+    tracing::info!("Probe batch {}: {} media URLs from {:?}", batch_id, total, referrer);
+
+    let mut items = Vec::with_capacity(total);
+    let mut to_probe = Vec::new();
+    for (index, entry) in normalized.iter().enumerate() {
+        match (entry.action, &entry.normalized) {
+            (BatchUrlAction::Added, Some(url)) => to_probe.push((index, url.clone())),
+            _ => items.push(MediaProbe::unprobed(index, entry)),
+        }
+    }
+    // Repeats and invalid URLs are known without asking anyone
+    for (completed, item) in items.iter().enumerate() {
+        let _ = app_handle.emit("probe-batch-progress", ProbeBatchProgress {
+            batch_id,
+            completed: completed + 1,
+            total,
+            item,
+        });
+    }
 
-        let task = add_download(app_handle.clone(), state.clone(), request).await?;
-        tasks.push(task);
+    let state = &state;
+    let mut probes = futures_util::stream::iter(to_probe.into_iter().map(|(index, url)| async move {
+        let result = state.engine.probe_media(&url, referrer).await.map_err(|e| e.to_string());
+        let final_url = result.as_ref().ok().map(|info| info.url.clone());
+        let mut item = MediaProbe::probed(index, url, result);
+        item.mark_existing(&existing_statuses(&state.db, &item.url, final_url.as_deref()).await);
+        item
+    }))
+    .buffer_unordered(state.engine.batch_concurrency());
+
+    while let Some(item) = probes.next().await {
+        let _ = app_handle.emit("probe-batch-progress", ProbeBatchProgress {
+            batch_id,
+            completed: items.len() + 1,
+            total,
+            item: &item,
+        });
+        items.push(item);
     }
-    Ok(tasks)
+    drop(probes);
+    items.sort_by_key(|item| item.index);
+    Ok(ProbeBatchSummary { batch_id, items })
+}
+
+/// Add the URLs picked from a page's media list as one group: they share
+/// `options`, go into a folder named after the page when asked, and are
+/// added like a batch (see `add_batch_downloads`). Probes `probe_batch`
+/// made within the file info cache's lifetime aren't repeated.
+#[tauri::command]
+pub async fn add_selected(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    urls: Vec<String>,
+    options: MediaAddOptions,
+) -> Result<BatchAddSummary, String> {
+    let batch_id = Uuid::new_v4();
+    let normalized = UrlParser::normalize_batch(&urls, &page_normalize_options(&state, options.referrer.as_deref()));
+    let save_path = match options.folder_name() {
+        Some(folder) => {
+            let parent = options
+                .save_path
+                .clone()
+                .map(PathBuf::from)
+                .or_else(|| options.category.as_deref().and_then(|category| state.engine.category_dir(category)))
+                .unwrap_or_else(|| state.engine.default_download_dir().clone());
+            Some(parent.join(sanitize_filename(&folder)).to_string_lossy().to_string())
+        }
+        None => options.save_path.clone(),
+    };
+
+    let requests: Vec<AddDownloadRequest> = normalized
+        .iter()
+        .filter(|entry| entry.action == BatchUrlAction::Added)
+        .filter_map(|entry| entry.normalized.clone())
+        .map(|url| AddDownloadRequest {
+            category: options.category.clone(),
+            priority: options.priority,
+            source_page_url: options.referrer.clone(),
+            source_page_title: options.page_title.clone(),
+            ..batch_request(url, save_path.clone())
+        })
+        .collect();
+    tracing::info!(
+        "Media group {}: adding {} of {} selected URLs to {:?}",
+        batch_id,
+        requests.len(),
+        urls.len(),
+        save_path
+    );
+
+    let mut summary = BatchAddSummary::new(batch_id, normalized);
+    add_requests(&app_handle, &state, requests, None, &mut summary).await;
+    Ok(summary)
 }
 
 // Additional command placeholders
@@ -356,6 +1804,7 @@ pub async fn pause_all(
         let mut active = state.active_downloads.write().await;
         if let Some(handle) = active.remove(&uuid) {
             handle.cancel_token.cancel();
+            state.taskbar.mark_paused(uuid);
             drop(active); // Release lock before database operation
             
             // Update database status
@@ -366,7 +1815,7 @@ pub async fn pause_all(
             
             // Get updated task and emit event
             if let Ok(Some(task)) = state.db.get_download(uuid).await {
-                let _ = app_handle.emit("download-paused", &task);
+                let _ = app_handle.emit("download-paused", &task.for_event());
             }
             
             paused_ids.push(uuid.to_string());
@@ -396,6 +1845,19 @@ pub async fn resume_all(
     
     // Resume each paused download
     for mut task in paused_downloads {
+        // Soft paused ones are still running and only need letting go
+        match resume_soft_paused(&app_handle, &state, task.id).await {
+            Ok(true) => {
+                resumed_ids.push(task.id.to_string());
+                continue;
+            }
+            Ok(false) => {}
+            Err(e) => {
+                tracing::error!("Failed to resume {}: {}", task.id, e);
+                continue;
+            }
+        }
+
         // Update status to downloading
         task.status = DownloadStatus::Downloading;
         
@@ -405,10 +1867,10 @@ pub async fn resume_all(
         }
         
         // Emit event so UI updates immediately
-        let _ = app_handle.emit("download-resumed", &task);
+        let _ = app_handle.emit("download-resumed", &task.for_event());
         
         // Re-start download with resume
-        if let Err(e) = spawn_download_task(app_handle.clone(), &state, task.clone()).await {
+        if let Err(e) = spawn_download_task(app_handle.clone(), &state, task.clone(), IfActive::Skip).await {
             tracing::error!("Failed to spawn download {}: {}", task.id, e);
             continue;
         }
@@ -426,6 +1888,11 @@ pub async fn cancel_all(
     state: State<'_, AppState>,
 ) -> Result<Vec<String>, String> {
     let mut cancelled_ids = Vec::new();
+
+    // Empty the queue first so nothing gets promoted while running
+    // downloads are being cancelled
+    let drained = state.queue.write().await.drain_all();
+    persist_queue(&state).await;
     
     // Get all active download IDs
     let active_ids: Vec<Uuid> = {
@@ -433,12 +1900,19 @@ pub async fn cancel_all(
         active.keys().copied().collect()
     };
     
+    // Statuses to put back if the cancel is undone
+    let mut previous = Vec::new();
+
     // Cancel each active download
     for uuid in active_ids {
         let mut active = state.active_downloads.write().await;
         if let Some(handle) = active.remove(&uuid) {
             handle.cancel_token.cancel();
+            state.taskbar.forget(uuid);
             drop(active); // Release lock before database operation
+            if let Ok(Some(task)) = state.db.get_download(uuid).await {
+                previous.push((uuid, task.status));
+            }
             
             // Update database status
             if let Err(e) = state.db.update_status(uuid, DownloadStatus::Cancelled).await {
@@ -448,30 +1922,34 @@ pub async fn cancel_all(
             
             // Get updated task and emit event
             if let Ok(Some(task)) = state.db.get_download(uuid).await {
-                let _ = app_handle.emit("download-cancelled", &task);
+                let _ = app_handle.emit("download-cancelled", &task.for_event());
             }
             
             cancelled_ids.push(uuid.to_string());
         }
     }
     
-    // Also clear the queue
-    {
-        let mut queue = state.queue.write().await;
-        let queued = queue.get_queue();
-        for uuid in queued {
-            if let Err(e) = state.db.update_status(uuid, DownloadStatus::Cancelled).await {
-                tracing::error!("Failed to cancel queued download {}: {}", uuid, e);
-            } else {
-                cancelled_ids.push(uuid.to_string());
-            }
+    // Then everything that was still waiting
+    for uuid in drained {
+        let id = uuid.to_string();
+        if cancelled_ids.contains(&id) {
+            continue;
         }
-        // Remove all from queue
-        for uuid in queue.get_queue() {
-            queue.remove(uuid);
+        if let Err(e) = state.db.update_status(uuid, DownloadStatus::Cancelled).await {
+            tracing::error!("Failed to cancel queued download {}: {}", uuid, e);
+        } else {
+            cancelled_ids.push(id);
+            previous.push((uuid, DownloadStatus::Queued));
         }
     }
     
+    if !previous.is_empty() {
+        state.undo.record(
+            UndoKind::CancelAll,
+            format!("Cancel {} downloads", previous.len()),
+            UndoAction::Cancel { previous, schedules: Vec::new() },
+        );
+    }
     tracing::info!("Cancelled {} downloads", cancelled_ids.len());
     Ok(cancelled_ids)
 }
@@ -488,22 +1966,7 @@ pub async fn open_file(
         .map_err(|e| e.to_string())?
         .ok_or("Download not found")?;
     
-    // Check if file exists
-    if !tokio::fs::metadata(&task.save_path).await.is_ok() {
-        return Err("File not found on disk".to_string());
-    }
-    
-    // Open the file with default application
-    let result = tokio::task::spawn_blocking(move || {
-        opener::open(&task.save_path).map_err(|e| format!("Failed to open file: {}", e))
-    })
-    .await
-    .map_err(|e| format!("Task failed: {}", e))?;
-    
-    match result {
-        Ok(_) => Ok(()),
-        Err(e) => Err(e),
-    }
+    shell_open::open(&task.save_path).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -518,129 +1981,130 @@ pub async fn open_file_location(
         .map_err(|e| e.to_string())?
         .ok_or("Download not found")?;
     
-    tracing::info!("Opening file location for: {:?}", task.save_path);
-    
-    // Validate file path exists
-    let file_exists = tokio::fs::metadata(&task.save_path).await.is_ok();
-    tracing::info!("File exists: {}", file_exists);
-    
-    // Get the parent directory (folder containing the file)
-    let folder_path = task.save_path
-        .parent()
-        .ok_or("Invalid file path - no parent directory")?
-        .to_path_buf();
-    
-    tracing::info!("Folder path: {:?}", folder_path);
-    
-    // Check if folder exists
-    if !tokio::fs::metadata(&folder_path).await.is_ok() {
-        tracing::error!("Folder not found: {:?}", folder_path);
-        return Err(format!("Folder not found: {}", folder_path.display()));
-    }
-    
-    // Open the folder and select the file if possible
-    let file_path = task.save_path.clone();
-    tracing::info!("Attempting to open/select file: {:?}", file_path);
-    
-    let result = tokio::task::spawn_blocking(move || -> Result<(), String> {
-        // Try to reveal the file in the folder (platform-specific)
-        #[cfg(target_os = "windows")]
-        {
-            use std::process::Command;
-            use std::os::windows::process::CommandExt;
-            
-            // Normalize the path for Windows (convert forward slashes to backslashes)
-            let file_path_str = file_path
-                .to_string_lossy()
-                .replace("/", "\\");
-            
-            tracing::info!("Windows: Opening with explorer /select,{}", file_path_str);
-            
-            // Windows: Use explorer with /select to highlight the file
-            // CREATE_NO_WINDOW flag (0x08000000) to prevent console window from appearing
-            const CREATE_NO_WINDOW: u32 = 0x08000000;
-            
-            let result = Command::new("explorer")
-                .creation_flags(CREATE_NO_WINDOW)
-                .arg(format!("/select,{}", file_path_str))
-                .spawn();
-            
-            match result {
-                Ok(_) => {
-                    tracing::info!("Successfully opened Windows Explorer");
-                    Ok(())
-                },
-                Err(e) => {
-                    tracing::error!("Failed to open Windows Explorer: {}", e);
-                    Err(format!("Failed to open Windows Explorer: {}", e))
-                },
-            }
-        }
-        
-        #[cfg(target_os = "macos")]
-        {
-            use std::process::Command;
-            let file_path_str = file_path.to_string_lossy().to_string();
-            tracing::info!("macOS: Opening with 'open -R {}'", file_path_str);
-            
-            let result = Command::new("open")
-                .arg("-R")
-                .arg(&file_path_str)
-                .spawn();
-            if let Err(e) = result {
-                tracing::error!("Failed to open Finder: {}", e);
-                return Err(format!("Failed to open Finder: {}", e));
-            }
-            tracing::info!("Successfully opened Finder");
-            Ok(())
-        }
-        
-        #[cfg(target_os = "linux")]
-        {
-            use std::process::Command;
-            // Try to use file manager to show the file
-            // First try xdg-open with the parent folder
-            let folder = file_path.parent().ok_or("Invalid path")?;
-            let folder_str = folder.to_string_lossy();
-            tracing::info!("Linux: Opening with 'xdg-open {}'", folder_str);
-            
-            let result = Command::new("xdg-open")
-                .arg(folder)
-                .spawn();
-            
-            if let Err(e) = result {
-                tracing::error!("Failed to open folder: {}", e);
-                return Err(format!("Failed to open folder: {}", e));
-            }
-            tracing::info!("Successfully opened file manager");
-            Ok(())
-        }
-        
-        #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
-        {
-            let folder = file_path.parent().ok_or("Invalid path")?;
-            opener::open(folder).map_err(|e| format!("Failed to open folder: {}", e))
+    shell_open::reveal(&task.save_path).await.map_err(|e| e.to_string())
+}
+
+/// Global download statistics
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GlobalStats {
+    pub total_downloads: u32,
+    pub active_downloads: u32,
+    pub queued_downloads: u32,
+    pub completed_downloads: u32,
+    pub failed_downloads: u32,
+    pub paused_downloads: u32,
+    pub total_downloaded_bytes: u64,
+    pub total_size_bytes: u64,
+    pub current_speed: f64,
+    pub estimated_time_remaining: Option<u64>,
+    /// Share of `current_speed` from HTTP/FTP downloads and from torrents
+    pub http_speed: f64,
+    pub torrent_download_speed: u64,
+    pub torrent_upload_speed: u64,
+}
+
+/// Follow a link through shorteners and redirect pages to the file it
+/// leads to, without adding it
+#[tauri::command]
+pub async fn resolve_url(
+    state: State<'_, AppState>,
+    url: String,
+) -> Result<ResolvedUrl, String> {
+    UrlParser::parse(&url).map_err(|e| e.to_string())?;
+    state.engine.resolvers.resolve(&url).await.map_err(|e| e.to_string())
+}
+
+/// Set what runs on a download's file once it completes, and run it now
+/// if the download already has. None clears it.
+#[tauri::command]
+pub async fn set_post_action(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+    post_action: Option<PostAction>,
+) -> Result<(), String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    let mut task = state
+        .db
+        .get_download(uuid)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Download not found")?;
+    if let Some(PostAction::Convert { profile }) = &post_action {
+        if state.conversions.profile(profile).is_none() {
+            return Err(format!("No conversion profile named {}", profile));
         }
-    })
-    .await
-    .map_err(|e| format!("Task failed: {}", e))?;
-    
-    result
+    }
+
+    task.post_action = post_action;
+    state.db.set_post_action(uuid, task.post_action.as_ref()).await.map_err(|e| e.to_string())?;
+    if task.status == DownloadStatus::Completed && task.post_action.is_some() {
+        state.conversions.enqueue(app_handle, task);
+    }
+    Ok(())
 }
 
-/// Global download statistics
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct GlobalStats {
-    pub total_downloads: u32,
-    pub active_downloads: u32,
-    pub queued_downloads: u32,
-    pub completed_downloads: u32,
-    pub failed_downloads: u32,
-    pub paused_downloads: u32,
-    pub total_downloaded_bytes: u64,
-    pub total_size_bytes: u64,
-    pub current_speed: f64,
-    pub estimated_time_remaining: Option<u64>,
+/// Point a download at a new link, such as a fresh mirror or a signed URL
+/// that replaced an expired one, and resume it from its partial data.
+///
+/// With `validate`, the link must report the same size (and ETag, when
+/// both are known) or a `URL_MISMATCH` error is returned; `force` then
+/// restarts the download from zero under the new link, keeping its entry.
+/// A running download is paused first.
+#[tauri::command]
+pub async fn update_download_url(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+    new_url: String,
+    validate: bool,
+    force: Option<bool>,
+) -> Result<DownloadTask, String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    if state.active_downloads.read().await.contains_key(&uuid) {
+        pause_download(app_handle.clone(), state.clone(), id.clone(), None).await?;
+    }
+    let mut task = state.db.get_download(uuid)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Download not found")?;
+    if task.status == DownloadStatus::Completed {
+        return Err("Download has already finished".to_string());
+    }
+
+    state.engine
+        .replace_url(&mut task, &new_url, validate, force.unwrap_or(false))
+        .await
+        .map_err(|e| e.to_string())?;
+    task.error_message = None;
+    state.db.update_download(&task).await.map_err(|e| e.to_string())?;
+
+    tracing::info!("Download {} now uses a new link", uuid);
+    let _ = app_handle.emit("download-url-updated", &task.for_event());
+    resume_download(app_handle, state, id).await?;
+    Ok(task)
+}
+
+/// Bytes transferred in the current week or month ("weekly"/"monthly",
+/// default the data cap's period), against the data budget
+#[tauri::command]
+pub async fn get_data_usage(
+    state: State<'_, AppState>,
+    period: Option<String>,
+) -> Result<DataUsage, String> {
+    let policy = state.resource_guard.data_cap_policy();
+    let period = match period {
+        Some(p) => p.parse::<UsagePeriod>()?,
+        None => policy.period,
+    };
+
+    let now = chrono::Local::now().naive_local();
+    let (start, end) = policy.bounds(period, now.date());
+    let (downloaded, uploaded) = state.db
+        .get_usage_between(start, end)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(policy.usage(period, now, downloaded, uploaded))
 }
 
 #[tauri::command]
@@ -664,6 +2128,9 @@ pub async fn get_global_stats(
         total_size_bytes: 0,
         current_speed: 0.0,
         estimated_time_remaining: None,
+        http_speed: 0.0,
+        torrent_download_speed: 0,
+        torrent_upload_speed: 0,
     };
     
     let mut remaining_bytes = 0u64;
@@ -675,7 +2142,8 @@ pub async fn get_global_stats(
                 stats.active_downloads += 1;
                 stats.current_speed += task.speed;
             }
-            DownloadStatus::Queued => stats.queued_downloads += 1,
+            // Counted from the queue below, like get_queue_info
+            DownloadStatus::Queued => {}
             DownloadStatus::Completed => stats.completed_downloads += 1,
             DownloadStatus::Failed => stats.failed_downloads += 1,
             DownloadStatus::Paused => stats.paused_downloads += 1,
@@ -694,14 +2162,33 @@ pub async fn get_global_stats(
         }
     }
     
+    stats.queued_downloads = state.queue.read().await.info().queued_count;
+
     // Calculate ETA if there's active speed
     if stats.current_speed > 0.0 && remaining_bytes > 0 {
         stats.estimated_time_remaining = Some((remaining_bytes as f64 / stats.current_speed) as u64);
     }
+
+    // Torrents don't count towards the ETA above, which covers HTTP bytes only
+    let torrents = state.torrent_client.session_info().await;
+    stats.http_speed = stats.current_speed;
+    stats.torrent_download_speed = torrents.download_rate;
+    stats.torrent_upload_speed = torrents.upload_rate;
+    stats.current_speed += torrents.download_rate as f64;
     
     Ok(stats)
 }
 
+/// Where the bandwidth went over the last few seconds: the `limit`
+/// (default 5) biggest categories and domains with their rates
+#[tauri::command]
+pub async fn get_live_bandwidth_breakdown(
+    state: State<'_, AppState>,
+    limit: Option<usize>,
+) -> Result<BandwidthBreakdown, String> {
+    Ok(state.bandwidth.breakdown(limit.unwrap_or(5)))
+}
+
 #[tauri::command]
 pub async fn set_speed_limit(
     state: State<'_, AppState>,
@@ -728,6 +2215,146 @@ pub async fn get_queue_info(
     Ok(serde_json::to_value(&info).map_err(|e| e.to_string())?)
 }
 
+/// The queue as the completion forecast sees it now: running downloads
+/// with their live progress, then the queue in order, then paused ones
+/// waiting on the scheduler
+pub async fn forecast_snapshot(state: &AppState) -> Result<ForecastSnapshot, String> {
+    let downloads: HashMap<Uuid, DownloadTask> = state.db.get_all_downloads()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|task| (task.id, task))
+        .collect();
+    let (active, queued, max_concurrent) = {
+        let queue = state.queue.read().await;
+        (queue.get_active(), queue.get_queue(), queue.info().max_concurrent as usize)
+    };
+
+    let mut starts: HashMap<Uuid, chrono::DateTime<chrono::Utc>> = HashMap::new();
+    for entry in state.scheduler.get_all_tasks().await.into_iter().filter(|e| e.enabled) {
+        let Ok(id) = Uuid::parse_str(&entry.download_id) else { continue };
+        let start = starts.entry(id).or_insert(entry.scheduled_time);
+        *start = (*start).min(entry.scheduled_time);
+    }
+
+    let to_forecast = |task: &DownloadTask, active: bool| ForecastTask {
+        id: task.id,
+        remaining: task.total_size.map(|total| total.saturating_sub(task.downloaded_size)),
+        active,
+        not_before: starts.get(&task.id).copied(),
+        deadline_at: task.deadline_at.map(|at| at.and_utc()),
+    };
+
+    let mut tasks = Vec::new();
+    let mut measured = 0.0;
+    {
+        let running = state.active_downloads.read().await;
+        for id in &active {
+            let task = match running.get(id) {
+                Some(handle) => handle.task.read().await.clone(),
+                None => match downloads.get(id) {
+                    Some(task) => task.clone(),
+                    None => continue,
+                },
+            };
+            measured += task.speed;
+            tasks.push(to_forecast(&task, true));
+        }
+    }
+    tasks.extend(queued.iter().filter_map(|id| downloads.get(id)).map(|task| to_forecast(task, false)));
+
+    let mut scheduled: Vec<&DownloadTask> = downloads
+        .values()
+        .filter(|task| task.status == DownloadStatus::Paused && starts.contains_key(&task.id))
+        .collect();
+    scheduled.sort_by_key(|task| starts[&task.id]);
+    tasks.extend(scheduled.into_iter().map(|task| to_forecast(task, false)));
+
+    // What is flowing now, held to the limit in case it was just lowered;
+    // with nothing flowing the limit is the only guide
+    let limit = state.engine.speed_limiter.effective_limit().await.map(|l| l as f64);
+    let rate = match (measured > 0.0, limit) {
+        (true, Some(limit)) => Some(measured.min(limit)),
+        (true, None) => Some(measured),
+        (false, limit) => limit,
+    };
+
+    Ok(ForecastSnapshot {
+        now: chrono::Utc::now(),
+        max_concurrent,
+        rate,
+        tasks,
+    })
+}
+
+/// When each running and queued download should complete, and when the
+/// whole queue is done, at the current speed and limits
+#[tauri::command]
+pub async fn get_queue_completion_forecast(
+    state: State<'_, AppState>,
+) -> Result<QueueForecast, String> {
+    Ok(forecast::forecast(&forecast_snapshot(&state).await?))
+}
+
+/// Set when a download needs to be finished by (RFC 3339), or clear it.
+/// The forecast warns with `deadline-at-risk` when it won't make it.
+#[tauri::command]
+pub async fn set_download_deadline(
+    state: State<'_, AppState>,
+    id: String,
+    deadline_at: Option<String>,
+) -> Result<(), String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    let deadline_at = match deadline_at {
+        Some(at) => {
+            let at: chrono::DateTime<chrono::Utc> = at
+                .parse()
+                .map_err(|e| format!("Invalid datetime format: {}", e))?;
+            if at <= chrono::Utc::now() {
+                return Err("The deadline has already passed".to_string());
+            }
+            Some(at.naive_utc())
+        }
+        None => None,
+    };
+
+    state.db.set_deadline_at(uuid, deadline_at).await.map_err(|e| e.to_string())?;
+    if let Some(handle) = state.active_downloads.read().await.get(&uuid) {
+        handle.task.write().await.deadline_at = deadline_at;
+    }
+    Ok(())
+}
+
+/// Report divergence between database statuses, the queue and the running
+/// downloads. Used by tests and debug builds to catch bookkeeping bugs.
+#[tauri::command]
+pub async fn validate_queue_consistency(
+    state: State<'_, AppState>,
+) -> Result<QueueConsistencyReport, String> {
+    let report = queue_consistency(&state).await?;
+    if !report.is_consistent() {
+        tracing::warn!("Queue diverged from the database: {:?}", report);
+    }
+    Ok(report)
+}
+
+/// Compare the queue with the database rows and the running downloads
+pub async fn queue_consistency(state: &AppState) -> Result<QueueConsistencyReport, String> {
+    let downloads = state.db.get_all_downloads()
+        .await
+        .map_err(|e| e.to_string())?;
+    // Playlist rows are run by their playlist, not the queue
+    let statuses: Vec<_> = downloads
+        .iter()
+        .rev()
+        .filter(|d| d.playlist_id.is_none())
+        .map(|d| (d.id, d.status))
+        .collect();
+    let running: Vec<Uuid> = state.active_downloads.read().await.keys().copied().collect();
+
+    Ok(state.queue.read().await.check_consistency(&statuses, &running))
+}
+
 #[tauri::command]
 pub async fn set_max_concurrent(
     app_handle: tauri::AppHandle,
@@ -737,36 +2364,46 @@ pub async fn set_max_concurrent(
     let mut queue = state.queue.write().await;
     let to_start = queue.set_max_concurrent(max as u32);
     drop(queue); // Release lock before spawning tasks
+    persist_queue(&state).await;
     
     // Start the newly dequeued downloads
-    for uuid in to_start {
-        if let Ok(Some(mut task)) = state.db.get_download(uuid).await {
-            task.status = DownloadStatus::Downloading;
-            
-            if let Err(e) = state.db.update_download(&task).await {
-                tracing::error!("Failed to update task {}: {}", uuid, e);
-                continue;
-            }
-            
-            // Emit event
-            let _ = app_handle.emit("download-started", &task);
-            
-            // Start the download
-            if let Err(e) = spawn_download_task(app_handle.clone(), &state, task).await {
-                tracing::error!("Failed to spawn download {}: {}", uuid, e);
-            }
-        }
-    }
+    start_dequeued(&app_handle, &state, to_start).await;
     
     tracing::info!("Max concurrent downloads set to {}", max);
     Ok(())
 }
 
+/// Move a queued download to a new position in the queue
+#[tauri::command]
+pub async fn reorder_queue(
+    state: State<'_, AppState>,
+    id: String,
+    position: usize,
+) -> Result<Vec<String>, String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+
+    let order = {
+        let mut queue = state.queue.write().await;
+        queue.reorder(uuid, position)?;
+        queue.get_queue()
+    };
+    persist_queue(&state).await;
+
+    Ok(order.into_iter().map(|id| id.to_string()).collect())
+}
+
+/// yt-dlp wrapper using the resolved yt-dlp and ffmpeg binaries
+fn youtube_downloader(state: &AppState) -> YouTubeDownloader {
+    YouTubeDownloader::with_binaries(
+        state.ytdlp_manager.get_binary_path(),
+        state.binaries.resolve(Tool::Ffmpeg).map(|resolved| resolved.path),
+    )
+}
+
 /// Check if yt-dlp is installed
 #[tauri::command]
 pub async fn check_ytdlp_installed(state: State<'_, AppState>) -> Result<bool, String> {
-    let ytdlp_path = state.ytdlp_manager.get_binary_path();
-    let youtube_dl = YouTubeDownloader::with_binary_path(ytdlp_path.clone());
+    let youtube_dl = youtube_downloader(&state);
     youtube_dl.check_installation()
         .await
         .map_err(|e| e.to_string())
@@ -775,8 +2412,7 @@ pub async fn check_ytdlp_installed(state: State<'_, AppState>) -> Result<bool, S
 /// Get video information for a URL
 #[tauri::command]
 pub async fn get_video_info(url: String, state: State<'_, AppState>) -> Result<VideoInfo, String> {
-    let ytdlp_path = state.ytdlp_manager.get_binary_path();
-    let youtube_dl = YouTubeDownloader::with_binary_path(ytdlp_path.clone());
+    let youtube_dl = youtube_downloader(&state);
     youtube_dl
         .get_video_info(&url)
         .await
@@ -786,8 +2422,7 @@ pub async fn get_video_info(url: String, state: State<'_, AppState>) -> Result<V
 /// Get available quality options for a video
 #[tauri::command]
 pub async fn get_video_qualities(url: String, state: State<'_, AppState>) -> Result<Vec<QualityOption>, String> {
-    let ytdlp_path = state.ytdlp_manager.get_binary_path();
-    let youtube_dl = YouTubeDownloader::with_binary_path(ytdlp_path.clone());
+    let youtube_dl = youtube_downloader(&state);
     youtube_dl
         .get_available_qualities(&url)
         .await
@@ -797,8 +2432,7 @@ pub async fn get_video_qualities(url: String, state: State<'_, AppState>) -> Res
 /// Check if URL is a playlist
 #[tauri::command]
 pub async fn check_is_playlist(url: String, state: State<'_, AppState>) -> Result<bool, String> {
-    let ytdlp_path = state.ytdlp_manager.get_binary_path();
-    let youtube_dl = YouTubeDownloader::with_binary_path(ytdlp_path.clone());
+    let youtube_dl = youtube_downloader(&state);
     youtube_dl
         .is_playlist(&url)
         .await
@@ -849,22 +2483,24 @@ pub async fn get_file_size(
 
 pub async fn resume_download_internal(
     download_id: uuid::Uuid,
-    state: AppState,
+    app_handle: &tauri::AppHandle,
 ) -> Result<(), anyhow::Error> {
-    // Simple implementation without app_handle - just log
     tracing::info!("Scheduled download resume requested for: {}", download_id);
-    // TODO: Implement actual resume logic when app_handle is available
-    Ok(())
+    let state = app_handle.state::<AppState>();
+    resume_download(app_handle.clone(), state, download_id.to_string())
+        .await
+        .map_err(anyhow::Error::msg)
 }
 
 pub async fn retry_download_internal(
     download_id: uuid::Uuid,
-    state: AppState,
+    app_handle: &tauri::AppHandle,
 ) -> Result<(), anyhow::Error> {
-    // Simple implementation without app_handle - just log
     tracing::info!("Scheduled download retry requested for: {}", download_id);
-    // TODO: Implement actual retry logic when app_handle is available
-    Ok(())
+    let state = app_handle.state::<AppState>();
+    retry_download(app_handle.clone(), state, download_id.to_string())
+        .await
+        .map_err(anyhow::Error::msg)
 }
 
 pub async fn add_download_internal(
@@ -872,8 +2508,11 @@ pub async fn add_download_internal(
     save_path: Option<String>,
     filename: Option<String>,
     referrer: Option<String>,
-    state: AppState,
+    page_title: Option<String>,
+    url_refresh: Option<UrlRefresh>,
+    app_handle: &tauri::AppHandle,
 ) -> Result<String, anyhow::Error> {
+    let state = app_handle.state::<AppState>();
     let request = AddDownloadRequest {
         url: url.clone(),
         save_path,
@@ -888,30 +2527,172 @@ pub async fn add_download_internal(
         youtube_quality: None,
         youtube_video_format: None,
         youtube_audio_format: None,
+        notes: None,
+        source_page_url: referrer,
+        source_page_title: page_title,
+        retry: None,
+        source: DownloadSource::BrowserExtension,
+        probe: ProbeMode::Required,
+        temp_dir: None,
+        url_refresh,
+        post_action: None,
+        simulate: false,
+        playlist_mode: None,
+        existing_location: false,
     };
 
-    // Check if URL is supported by yt-dlp
-    if YouTubeDownloader::is_supported_url(&url) {
-        let mut task = state
-            .engine
-            .create_task(&request)
-            .await?;
-        
-        task.status = DownloadStatus::Queued;
-        state.db.insert_download(&task).await?;
-        
-        Ok(task.id.to_string())
-    } else {
-        let mut task = state
-            .engine
-            .create_task(&request)
-            .await?;
-        
-        task.status = DownloadStatus::Queued;
-        state.db.insert_download(&task).await?;
-        
-        Ok(task.id.to_string())
+    // yt-dlp URLs are queued the same way and picked up by the queue
+    let mut task = state
+        .engine
+        .create_task(&request)
+        .await?;
+
+    if let Some(reason) = state.engine.size_limits().confirmation_reason(task.total_size, request.source) {
+        hold_for_confirmation(app_handle, &state.db, &mut task, reason)
+            .await
+            .map_err(anyhow::Error::msg)?;
+        return Ok(task.id.to_string());
+    }
+
+    task.status = DownloadStatus::Queued;
+    state.db.reserve_and_insert(&mut task).await?;
+
+    state.queue.write().await.push_waiting(task.id);
+    persist_queue(&state).await;
+
+    Ok(task.id.to_string())
+}
+
+/// Whether refused media links should retry through yt-dlp
+async fn auto_fallback_enabled(state: &AppState) -> bool {
+    match state.db.get_setting(AUTO_FALLBACK_KEY).await {
+        Ok(value) => value.as_deref() != Some("false"),
+        Err(e) => {
+            tracing::warn!("Failed to read {}: {}", AUTO_FALLBACK_KEY, e);
+            false
+        }
+    }
+}
+
+/// The yt-dlp download that takes over from `original`, refused at `page`'s
+/// media link, in the same folder and category
+fn fallback_request(original: &DownloadTask, page: &str) -> AddDownloadRequest {
+    AddDownloadRequest {
+        url: page.to_string(),
+        save_path: original.save_path.parent().map(|p| p.to_string_lossy().to_string()),
+        segments: None,
+        max_retries: None,
+        expected_checksum: None,
+        checksum_type: None,
+        // The link's name ("videoplayback") says less than the video's title
+        file_name: None,
+        category: original.category.clone(),
+        priority: Some(original.priority),
+        youtube_format: None,
+        youtube_quality: None,
+        youtube_video_format: None,
+        youtube_audio_format: None,
+        notes: original.notes.clone(),
+        source_page_url: Some(page.to_string()),
+        source_page_title: original.source_page_title.clone(),
+        retry: original.retry_policy.clone(),
+        source: DownloadSource::Manual,
+        probe: ProbeMode::default(),
+        temp_dir: None,
+        url_refresh: None,
+        post_action: original.post_action.clone(),
+        simulate: false,
+        playlist_mode: None,
+        existing_location: true,
+    }
+}
+
+/// Hand the failed `original` to yt-dlp with its source page `page`,
+/// linking the two both ways, and tell the frontend
+async fn start_ytdlp_fallback(
+    app_handle: &tauri::AppHandle,
+    original: &DownloadTask,
+    page: &str,
+    error: &DownloadError,
+) -> Result<DownloadTask, String> {
+    tracing::info!("'{}' was refused ({}), retrying through yt-dlp with {}", original.file_name, error, page);
+    let state = app_handle.state::<AppState>();
+    let request = fallback_request(original, page);
+    let fallback = handle_youtube_download(app_handle.clone(), state.clone(), request, Some(original.id)).await?;
+    state.db.mark_superseded(original.id, fallback.id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let _ = app_handle.emit(
+        "download-fallback",
+        FallbackNotice {
+            original_id: original.id,
+            fallback_id: fallback.id,
+            file_name: original.file_name.clone(),
+            page_url: page.to_string(),
+            reason: error.to_string(),
+        },
+    );
+    Ok(fallback)
+}
+
+/// Hand a download that failed while running to yt-dlp, when its link was
+/// refused and it came from a supported page. True when the fallback
+/// started.
+async fn fallback_after_failure(app_handle: &tauri::AppHandle, task: &DownloadTask, error: &DownloadError) -> bool {
+    let Some(page) = ytdlp_fallback::fallback_page(&task.url, task.source_page_url.as_deref(), error) else {
+        return false;
+    };
+    if !auto_fallback_enabled(&app_handle.state::<AppState>()).await {
+        return false;
+    }
+    match start_ytdlp_fallback(app_handle, task, page, error).await {
+        Ok(_) => true,
+        Err(e) => {
+            tracing::warn!("yt-dlp fallback for {} failed: {}", task.id, e);
+            false
+        }
+    }
+}
+
+/// A link refused while being added: keep it in history as failed and
+/// retry it through yt-dlp when it can be, otherwise fail the add
+async fn fallback_on_add(
+    app_handle: &tauri::AppHandle,
+    state: &AppState,
+    request: &AddDownloadRequest,
+    error: DownloadError,
+) -> Result<DownloadTask, String> {
+    let Some(page) = ytdlp_fallback::fallback_page(&request.url, request.source_page_url.as_deref(), &error) else {
+        return Err(error.to_string());
+    };
+    if !auto_fallback_enabled(state).await {
+        return Err(error.to_string());
     }
+
+    let file_name = request
+        .file_name
+        .clone()
+        .or_else(|| UrlParser::parse(&request.url).ok().map(|parsed| parsed.filename))
+        .unwrap_or_else(|| "download".to_string());
+    let save_dir = request
+        .save_path
+        .clone()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| state.engine.default_download_dir().clone());
+    let mut original = DownloadTask::new(request.url.clone(), file_name.clone(), save_dir.join(&file_name), 1);
+    original.status = DownloadStatus::Failed;
+    original.error_message = Some(error.to_string());
+    original.category = request.category.clone();
+    original.priority = request.priority.unwrap_or_default();
+    original.notes = request.notes.clone();
+    original.source_page_url = request.source_page_url.clone();
+    original.source_page_title = request.source_page_title.clone();
+    original.retry_policy = request.retry.clone();
+    original.post_action = request.post_action.clone();
+    state.db.insert_download(&original).await.map_err(|e| e.to_string())?;
+
+    start_ytdlp_fallback(app_handle, &original, page, &error).await
 }
 
 // YouTube download helper function
@@ -919,9 +2700,9 @@ async fn handle_youtube_download(
     app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
     request: AddDownloadRequest,
+    fallback_of: Option<Uuid>,
 ) -> Result<DownloadTask, String> {
-    let ytdlp_path = state.ytdlp_manager.get_binary_path();
-    let youtube_dl = YouTubeDownloader::with_binary_path(ytdlp_path.clone());
+    let youtube_dl = youtube_downloader(&state);
 
     // Get video info first
     let video_info = youtube_dl
@@ -929,26 +2710,44 @@ async fn handle_youtube_download(
         .await
         .map_err(|e| format!("Failed to get video info: {}", e))?;
 
+    let playlist_mode = request.playlist_mode.filter(|_| video_info.is_playlist);
+    if playlist_mode == Some(PlaylistMode::PerEntry) {
+        return start_playlist_per_entry(app_handle, &state, request).await;
+    }
+    let single_process_playlist = playlist_mode == Some(PlaylistMode::SingleProcess);
+
     // Determine save path and sanitize filename
     let raw_file_name = request.file_name.clone().unwrap_or(video_info.title.clone());
     
     // Sanitize filename - remove invalid characters for filesystem
     let file_name = sanitize_filename(&raw_file_name);
     
-    let extension = if request.youtube_format.as_deref() == Some("audio") {
-        request.youtube_audio_format.as_deref().unwrap_or("mp3")
-    } else {
-        request.youtube_video_format.as_deref().unwrap_or("mp4")
-    };
+    let extension = youtube_extension(&request);
     
     let full_file_name = format!("{}.{}", file_name, extension);
-    let save_path = PathBuf::from(
-        request.save_path.clone().unwrap_or_else(|| state.engine.default_download_dir().to_string_lossy().to_string())
-    ).join(&full_file_name);
+
+    // Requested category first, then detection, then the generic youtube bucket
+    let category = request
+        .category
+        .clone()
+        .or_else(|| state.engine.detect_category(&full_file_name, None))
+        .filter(|c| c != "default")
+        .unwrap_or_else(|| "youtube".to_string());
+
+    let save_dir = request
+        .save_path
+        .clone()
+        .map(PathBuf::from)
+        .or_else(|| state.engine.category_dir(&category))
+        .unwrap_or_else(|| state.engine.default_download_dir().clone());
+    let save_path = save_dir.join(&full_file_name);
+    if !request.existing_location {
+        state.engine.check_save_path(&save_path).map_err(|e| e.to_string())?;
+    }
 
     // Create download task
     let task_id = Uuid::new_v4();
-    let task = DownloadTask {
+    let mut task = DownloadTask {
         id: task_id,
         url: request.url.clone(),
         final_url: None,
@@ -956,7 +2755,7 @@ async fn handle_youtube_download(
         save_path: save_path.clone(),
         total_size: video_info.filesize,
         downloaded_size: 0,
-        status: DownloadStatus::Downloading,
+        status: DownloadStatus::Queued,
         speed: 0.0,
         eta: None,
         segments: 1,
@@ -970,84 +2769,471 @@ async fn handle_youtube_download(
         error_message: None,
         created_at: chrono::Utc::now().naive_utc(),
         completed_at: None,
-        priority: request.priority.unwrap_or(0),
-        category: Some("youtube".to_string()),
+        priority: request.priority.unwrap_or_default(),
+        category: Some(category.clone()),
         segment_progress: vec![],
+        superseded_by: None,
+        fallback_of,
+        playlist_id: None,
+        temp_dir: None,
+        notes: request.notes.clone(),
+        source_page_url: request.source_page_url.clone(),
+        source_page_title: request.source_page_title.clone(),
+        integrity_status: None,
+        retry_policy: request.retry.clone(),
+        checksum_required: false,
+        redirect_chain: Vec::new(),
+        capability_notes: None,
+        url_refresh: None,
+        post_action: None,
+        post_action_status: None,
+        scheduled_for: None,
+        deadline_at: None,
+        unknown_origin: false,
+        wasted_bytes: 0,
     };
 
-    // Save to database
-    state.db.insert_download(&task).await.map_err(|e| e.to_string())?;
+    let format_type = request.youtube_format.unwrap_or("video".to_string());
+    let video_quality = request.youtube_quality.unwrap_or("best".to_string());
+    let video_format = request.youtube_video_format.unwrap_or("mp4".to_string());
+    let audio_format = request.youtube_audio_format.unwrap_or("mp3".to_string());
+
+    let db = state.db.clone();
+    let app_handle_clone = app_handle.clone();
+    let ytdlp_path = state.ytdlp_manager.get_binary_path();
+    let ffmpeg_path = state.binaries.resolve(Tool::Ffmpeg).map(|resolved| resolved.path);
+    let taskbar = state.taskbar.clone();
+    let metrics = state.metrics.clone();
+    let recent_files = state.recent_files.clone();
+    let logger = state.logger.clone();
+    let binaries = state.binaries.clone();
+    let download_archive = single_process_playlist.then(|| state.ytdlp_archive.clone());
+
+    // The row is written before yt-dlp starts
+    task_launch::launch(&state.db, &mut task, |task_clone| async move {
+        // Reserving the save path may have renamed the file; a whole
+        // playlist keeps each entry's own title instead
+        let output_filename = std::path::Path::new(&task_clone.file_name)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .filter(|_| !single_process_playlist);
+        let options = YouTubeDownloadOptions {
+            url: task_clone.url.clone(),
+            format_type,
+            video_quality,
+            video_format,
+            audio_format,
+            save_path: task_clone.save_path.clone(),
+            is_playlist: single_process_playlist,
+            output_filename,
+            download_archive,
+        };
+        taskbar.track(task_id);
+        metrics.begin(task_id, &task_clone.url, 0);
+
+        // Spawn the download task in background using Tauri's runtime
+        // Create a new YouTubeDownloader instance inside the spawn to avoid Send issues
+        tauri::async_runtime::spawn(async move {
+            let youtube_dl = YouTubeDownloader::with_binaries(ytdlp_path, ffmpeg_path);
+            match youtube_dl.download(options).await {
+                Ok(final_path) => {
+                    tracing::info!("YouTube download completed successfully: {:?}", final_path);
+                    
+                    // Get actual file size from disk
+                    let actual_size = tokio::fs::metadata(&final_path)
+                        .await
+                        .ok()
+                        .map(|m| m.len());
+                    
+                    // Extract the actual filename from the path
+                    let actual_filename = final_path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("downloaded_video")
+                        .to_string();
+                    
+                    taskbar.complete(task_id);
+                    // yt-dlp reports no byte counts here, so the file's
+                    // size is counted when it is done
+                    metrics.record_progress(task_id, actual_size.unwrap_or(0));
+                    metrics.record_completed(task_id);
+                    let mut completed_task = task_clone;
+                    completed_task.status = DownloadStatus::Completed;
+                    completed_task.completed_at = Some(chrono::Utc::now().naive_utc());
+                    completed_task.save_path = final_path.clone();
+                    completed_task.file_name = actual_filename; // Update with actual filename including extension
+                    completed_task.total_size = actual_size; // Update with actual file size
+                    completed_task.downloaded_size = actual_size.unwrap_or(0); // Set downloaded size
+                    
+                    if let Err(e) = db.update_download(&completed_task).await {
+                        tracing::error!("Failed to update completed download in DB: {}", e);
+                    }
+                    if let Err(e) = app_handle_clone.emit("download-complete", &completed_task.for_event()) {
+                        tracing::error!("Failed to emit download-complete event: {}", e);
+                    }
+                    recent_files.record(&completed_task);
+                    hook_service::run(&app_handle_clone, HookEvent::DownloadComplete, Some(&completed_task));
+                    NotificationService::notify(
+                        &app_handle_clone,
+                        NotificationType::DownloadComplete {
+                            file_name: completed_task.file_name.clone(),
+                            size: completed_task.downloaded_size,
+                        },
+                    )
+                    .await;
+                }
+                Err(e) => {
+                    tracing::error!("YouTube download failed: {}", e);
+                    ytdlp_env::record_failure(&logger, &binaries, task_id, &e).await;
+                    taskbar.fail(task_id);
+                    metrics.record_failed(task_id, &task_clone.url);
+                    let mut failed_task = task_clone;
+                    failed_task.status = DownloadStatus::Failed;
+                    failed_task.error_message = Some(e.to_string());
+                    if let Err(e) = db.update_download(&failed_task).await {
+                        tracing::error!("Failed to update failed download in DB: {}", e);
+                    }
+                    emit_failed(&app_handle_clone, &failed_task, None);
+                    hook_service::run(&app_handle_clone, HookEvent::DownloadFailed, Some(&failed_task));
+                    NotificationService::notify(
+                        &app_handle_clone,
+                        NotificationType::DownloadFailed {
+                            file_name: failed_task.file_name.clone(),
+                            error: e.to_string(),
+                        },
+                    )
+                    .await;
+                }
+            }
+            hook_service::run_if_all_complete(&app_handle_clone).await;
+        });
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())?;
 
     // Emit download-added event so UI updates immediately
-    let _ = app_handle.emit("download-added", &task);
+    let _ = app_handle.emit("download-added", &task.for_event());
+    emit_categorized(&app_handle, task.id, &category);
+
+    Ok(task)
+}
+
+fn youtube_extension(request: &AddDownloadRequest) -> &str {
+    if request.youtube_format.as_deref() == Some("audio") {
+        request.youtube_audio_format.as_deref().unwrap_or("mp3")
+    } else {
+        request.youtube_video_format.as_deref().unwrap_or("mp4")
+    }
+}
+
+/// Download a playlist with a yt-dlp process per entry, a few at a time.
+/// The returned task stands for the whole playlist; each entry gets its own
+/// row, so its progress, retries and failure show on their own.
+async fn start_playlist_per_entry(
+    app_handle: tauri::AppHandle,
+    state: &AppState,
+    request: AddDownloadRequest,
+) -> Result<DownloadTask, String> {
+    let listing = youtube_downloader(state)
+        .get_playlist_entries(&request.url)
+        .await
+        .map_err(|e| e.to_string())?;
+    if listing.entries.is_empty() {
+        return Err("The playlist has no entries".to_string());
+    }
+
+    let category = request.category.clone().unwrap_or_else(|| "youtube".to_string());
+    let playlist_name = sanitize_filename(
+        request
+            .file_name
+            .as_deref()
+            .or(listing.title.as_deref())
+            .unwrap_or("Playlist"),
+    );
+    let save_dir = request
+        .save_path
+        .clone()
+        .map(PathBuf::from)
+        .or_else(|| state.engine.category_dir(&category))
+        .unwrap_or_else(|| state.engine.default_download_dir().clone())
+        .join(&playlist_name);
+    state.engine.check_save_path(&save_dir).map_err(|e| e.to_string())?;
+
+    // Entries finished by either mode are skipped without a row
+    let archived = tokio::fs::read_to_string(&state.ytdlp_archive)
+        .await
+        .map(|contents| playlist::parse_archive(&contents))
+        .unwrap_or_default();
+
+    let mut group = DownloadTask::new(request.url.clone(), playlist_name.clone(), save_dir.clone(), 1);
+    group.status = DownloadStatus::Downloading;
+    group.category = Some(category.clone());
+    group.priority = request.priority.unwrap_or_default();
+    group.notes = request.notes.clone();
+    group.source_page_url = request.source_page_url.clone();
+    group.source_page_title = request.source_page_title.clone();
+    group.playlist_id = Some(group.id);
+
+    let extension = youtube_extension(&request);
+    let mut entry_tasks: Vec<DownloadTask> = listing
+        .entries
+        .iter()
+        .filter(|entry| !archived.contains(&entry.archive_key()))
+        .map(|entry| {
+            let name = sanitize_filename(entry.title.as_deref().unwrap_or(&entry.id));
+            let file_name = format!("{}.{}", name, extension);
+            let mut task = DownloadTask::new(entry.url.clone(), file_name.clone(), save_dir.join(&file_name), 1);
+            task.category = Some(category.clone());
+            task.priority = group.priority;
+            task.source_page_url = Some(request.url.clone());
+            task.source_page_title = Some(playlist_name.clone());
+            task.playlist_id = Some(group.id);
+            task
+        })
+        .collect();
+
+    state.db.reserve_and_insert(&mut group).await.map_err(|e| e.to_string())?;
+    state.db.reserve_and_insert_all(&mut entry_tasks).await.map_err(|e| e.to_string())?;
+    tracing::info!(
+        "Playlist {} added with {} entries ({} already downloaded)",
+        request.url,
+        listing.entries.len(),
+        listing.entries.len() - entry_tasks.len()
+    );
+
+    let _ = app_handle.emit("download-added", &group.for_event());
+    for task in &entry_tasks {
+        let _ = app_handle.emit("download-added", &task.for_event());
+    }
+    emit_categorized(&app_handle, group.id, &category);
+
+    // Reserving paths may have renamed entries, so match them up afterwards
+    let entry_ids = listing
+        .entries
+        .iter()
+        .filter(|entry| !archived.contains(&entry.archive_key()))
+        .map(|entry| entry.id.clone());
+    let tasks: HashMap<String, DownloadTask> = entry_ids.zip(entry_tasks).collect();
 
-    // Download in background
     let options = YouTubeDownloadOptions {
         url: request.url.clone(),
-        format_type: request.youtube_format.unwrap_or("video".to_string()),
-        video_quality: request.youtube_quality.unwrap_or("best".to_string()),
-        video_format: request.youtube_video_format.unwrap_or("mp4".to_string()),
-        audio_format: request.youtube_audio_format.unwrap_or("mp3".to_string()),
-        save_path: save_path.clone(),
-        is_playlist: false,  // Default to single video
-        output_filename: Some(file_name.clone()),
+        format_type: request.youtube_format.clone().unwrap_or_else(|| "video".to_string()),
+        video_quality: request.youtube_quality.clone().unwrap_or_else(|| "best".to_string()),
+        video_format: request.youtube_video_format.clone().unwrap_or_else(|| "mp4".to_string()),
+        audio_format: request.youtube_audio_format.clone().unwrap_or_else(|| "mp3".to_string()),
+        save_path: save_dir,
+        is_playlist: false,
+        output_filename: None,
+        download_archive: Some(state.ytdlp_archive.clone()),
     };
+    let attempts = 1 + request.max_retries.unwrap_or(DEFAULT_PLAYLIST_ENTRY_RETRIES);
+
+    tauri::async_runtime::spawn(run_playlist_group(
+        app_handle,
+        group.clone(),
+        listing.entries,
+        tasks,
+        archived,
+        options,
+        attempts,
+    ));
+    Ok(group)
+}
 
-    let task_clone = task.clone();
-    let db = state.db.clone();
-    let app_handle_clone = app_handle.clone();
-    let ytdlp_path_clone = ytdlp_path.clone();
-
-    // Spawn the download task in background using Tauri's runtime
-    // Create a new YouTubeDownloader instance inside the spawn to avoid Send issues
-    tauri::async_runtime::spawn(async move {
-        let youtube_dl = YouTubeDownloader::with_binary_path(ytdlp_path_clone);
-        match youtube_dl.download(options).await {
-            Ok(final_path) => {
-                tracing::info!("YouTube download completed successfully: {:?}", final_path);
-                
-                // Get actual file size from disk
-                let actual_size = tokio::fs::metadata(&final_path)
-                    .await
-                    .ok()
-                    .map(|m| m.len());
-                
-                // Extract the actual filename from the path
-                let actual_filename = final_path
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("downloaded_video")
-                    .to_string();
-                
-                let mut completed_task = task_clone;
-                completed_task.status = DownloadStatus::Completed;
-                completed_task.completed_at = Some(chrono::Utc::now().naive_utc());
-                completed_task.save_path = final_path.clone();
-                completed_task.file_name = actual_filename; // Update with actual filename including extension
-                completed_task.total_size = actual_size; // Update with actual file size
-                completed_task.downloaded_size = actual_size.unwrap_or(0); // Set downloaded size
-                
-                if let Err(e) = db.update_download(&completed_task).await {
-                    tracing::error!("Failed to update completed download in DB: {}", e);
-                }
-                if let Err(e) = app_handle_clone.emit("download-complete", &completed_task) {
-                    tracing::error!("Failed to emit download-complete event: {}", e);
+/// Work through a per-entry playlist and record how the group ended
+async fn run_playlist_group(
+    app_handle: tauri::AppHandle,
+    mut group: DownloadTask,
+    entries: Vec<PlaylistEntry>,
+    tasks: HashMap<String, DownloadTask>,
+    archived: HashSet<String>,
+    options: YouTubeDownloadOptions,
+    attempts: u32,
+) {
+    let state = app_handle.state::<AppState>();
+    let cancel = CancellationToken::new();
+    state.track_remote_transfer(group.id, cancel.clone());
+    state.taskbar.track(group.id);
+
+    let group_id = group.id;
+    let summary = playlist::run_entries(
+        group_id,
+        entries,
+        &archived,
+        state.engine.playlist_concurrency(),
+        attempts,
+        |entry, attempt| {
+            let task = tasks.get(&entry.id).cloned();
+            let app_handle = app_handle.clone();
+            let options = options.clone();
+            let cancel = cancel.child_token();
+            async move {
+                match task {
+                    Some(task) => run_playlist_entry(&app_handle, task, options, attempt == attempts, cancel).await,
+                    None => EntryOutcome::Skipped,
                 }
             }
-            Err(e) => {
-                tracing::error!("YouTube download failed: {}", e);
-                let mut failed_task = task_clone;
-                failed_task.status = DownloadStatus::Failed;
-                failed_task.error_message = Some(e.to_string());
-                if let Err(e) = db.update_download(&failed_task).await {
-                    tracing::error!("Failed to update failed download in DB: {}", e);
-                }
-                if let Err(e) = app_handle_clone.emit("download-failed", &failed_task) {
-                    tracing::error!("Failed to emit download-failed event: {}", e);
-                }
+        },
+        |_, _, progress| {
+            let _ = app_handle.emit("playlist-group-progress", progress);
+            emit_progress(&app_handle, &DownloadProgress {
+                id: group_id,
+                downloaded_size: 0,
+                total_size: None,
+                speed: 0.0,
+                eta: None,
+                status: DownloadStatus::Downloading,
+                percent: progress.percent(),
+                error_message: None,
+                phase_progress: None,
+                category: None,
+            });
+        },
+    )
+    .await;
+    state.end_remote_transfer(group_id);
+
+    if cancel.is_cancelled() {
+        state.taskbar.forget(group_id);
+        group.status = DownloadStatus::Cancelled;
+    } else if summary.failed > 0 {
+        state.taskbar.fail(group_id);
+        group.status = DownloadStatus::Failed;
+        group.error_message = Some(format!("{} of {} entries failed", summary.failed, summary.total));
+    } else {
+        state.taskbar.complete(group_id);
+        group.status = DownloadStatus::Completed;
+        group.completed_at = Some(chrono::Utc::now().naive_utc());
+    }
+    if let Err(e) = state.db.update_download(&group).await {
+        tracing::error!("Failed to record playlist {}: {}", group_id, e);
+    }
+    match group.status {
+        DownloadStatus::Completed => emit_completed(&app_handle, &group),
+        DownloadStatus::Failed => emit_failed(&app_handle, &group, None),
+        _ => {}
+    }
+    if group.status != DownloadStatus::Cancelled {
+        hook_service::run_if_all_complete(&app_handle).await;
+    }
+    tracing::info!(
+        "Playlist {} finished: {} completed, {} skipped, {} failed",
+        group_id,
+        summary.completed,
+        summary.skipped,
+        summary.failed
+    );
+}
+
+/// One try at one playlist entry, in its own yt-dlp process. Cancelling
+/// the entry skips it; a failure only marks the row failed on the last try.
+async fn run_playlist_entry(
+    app_handle: &tauri::AppHandle,
+    mut task: DownloadTask,
+    mut options: YouTubeDownloadOptions,
+    last_attempt: bool,
+    cancel: CancellationToken,
+) -> EntryOutcome {
+    let state = app_handle.state::<AppState>();
+    let id = task.id;
+    if cancel.is_cancelled() {
+        task.status = DownloadStatus::Cancelled;
+        if let Err(e) = state.db.update_download(&task).await {
+            tracing::error!("Failed to update playlist entry {}: {}", id, e);
+        }
+        return EntryOutcome::Skipped;
+    }
+
+    state.track_remote_transfer(id, cancel.clone());
+    state.taskbar.track(id);
+    state.metrics.begin(id, &task.url, 0);
+    task.status = DownloadStatus::Downloading;
+    task.error_message = None;
+    if let Err(e) = state.db.update_download(&task).await {
+        tracing::error!("Failed to update playlist entry {}: {}", id, e);
+    }
+
+    options.url = task.url.clone();
+    options.save_path = task.save_path.clone();
+    options.output_filename = std::path::Path::new(&task.file_name)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string());
+
+    let progress_handle = app_handle.clone();
+    let progress_metrics = state.metrics.clone();
+    let youtube_dl = youtube_downloader(&state);
+    let result = tokio::select! {
+        result = youtube_dl.download_with_progress(options, move |p| {
+            progress_metrics.record_progress(id, p.downloaded_bytes);
+            emit_progress(&progress_handle, &DownloadProgress {
+                id,
+                downloaded_size: p.downloaded_bytes,
+                total_size: (p.total_bytes > 0).then_some(p.total_bytes),
+                speed: p.speed,
+                eta: Some(p.eta),
+                status: DownloadStatus::Downloading,
+                percent: p.percentage,
+                error_message: None,
+                phase_progress: None,
+                category: None,
+            });
+        }) => Some(result),
+        _ = cancel.cancelled() => None,
+    };
+    state.end_remote_transfer(id);
+
+    let outcome = match result {
+        None => {
+            state.taskbar.forget(id);
+            state.metrics.end(id);
+            task.status = DownloadStatus::Cancelled;
+            EntryOutcome::Skipped
+        }
+        Some(Ok(final_path)) => {
+            state.taskbar.complete(id);
+            let size = tokio::fs::metadata(&final_path).await.ok().map(|m| m.len());
+            state.metrics.record_progress(id, size.unwrap_or(0));
+            state.metrics.record_completed(id);
+            if let Some(name) = final_path.file_name().and_then(|n| n.to_str()) {
+                task.file_name = name.to_string();
             }
+            task.save_path = final_path;
+            task.total_size = size;
+            task.downloaded_size = size.unwrap_or(0);
+            task.status = DownloadStatus::Completed;
+            task.completed_at = Some(chrono::Utc::now().naive_utc());
+            EntryOutcome::Completed
         }
-    });
+        Some(Err(e)) => {
+            tracing::warn!("Playlist entry {} failed: {}", task.url, e);
+            task.error_message = Some(e.to_string());
+            if last_attempt {
+                ytdlp_env::record_failure(&state.logger, &state.binaries, id, &e).await;
+                state.taskbar.fail(id);
+                state.metrics.record_failed(id, &task.url);
+                task.status = DownloadStatus::Failed;
+            } else {
+                state.metrics.end(id);
+                task.status = DownloadStatus::Queued;
+            }
+            EntryOutcome::Failed { error: e.to_string() }
+        }
+    };
 
-    Ok(task)
+    if let Err(e) = state.db.update_download(&task).await {
+        tracing::error!("Failed to update playlist entry {}: {}", id, e);
+    }
+    match task.status {
+        DownloadStatus::Completed => {
+            emit_completed(app_handle, &task);
+            hook_service::run(app_handle, HookEvent::DownloadComplete, Some(&task));
+        }
+        DownloadStatus::Failed => {
+            emit_failed(app_handle, &task, None);
+            hook_service::run(app_handle, HookEvent::DownloadFailed, Some(&task));
+        }
+        _ => {}
+    }
+    outcome
 }
@@ -1,8 +1,12 @@
 use crate::state::app_state::AppState;
+use crate::core::dedup::{self, DedupOutcome, DedupStrategy, DuplicateReport};
+use crate::core::rebuild::{self, RebuildReport, RebuildSelection};
 use crate::core::download_task::DownloadTask;
+use crate::database::queries::{StatsBucket, StatsGroupBy};
 use tauri::State;
 use serde::{Serialize, Deserialize};
 use chrono::NaiveDateTime;
+use std::path::PathBuf;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DownloadHistoryItem {
@@ -16,6 +20,12 @@ pub struct DownloadHistoryItem {
     pub category: Option<String>,
     pub download_speed_avg: f64,
     pub download_time: Option<i64>,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub source_page_url: Option<String>,
+    #[serde(default)]
+    pub source_page_title: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +35,9 @@ pub struct HistoryStats {
     pub failed_downloads: usize,
     pub total_bytes_downloaded: u64,
     pub average_speed: f64,
+    /// Bytes downloaded and then thrown away, across all downloads
+    #[serde(default)]
+    pub total_wasted_bytes: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +45,9 @@ pub struct HistoryFilter {
     pub status: Option<String>,
     pub category: Option<String>,
     pub limit: Option<usize>,
+    /// Matches file name, URL or notes
+    #[serde(default)]
+    pub search: Option<String>,
 }
 
 /// Get download history with optional filters
@@ -57,6 +73,9 @@ pub async fn get_download_history(
             category: task.category.clone(),
             download_speed_avg: task.speed,
             download_time: None,
+            notes: task.notes.map(|n| n.0),
+            source_page_url: task.source_page_url,
+            source_page_title: task.source_page_title,
         })
         .collect();
     
@@ -70,6 +89,16 @@ pub async fn get_download_history(
                 item.category.as_ref().map(|c| c == &category).unwrap_or(false)
             });
         }
+        if let Some(search) = f.search {
+            let search = search.to_lowercase();
+            history.retain(|item| {
+                item.file_name.to_lowercase().contains(&search)
+                    || item.url.to_lowercase().contains(&search)
+                    || item.notes.as_ref()
+                        .map(|n| n.to_lowercase().contains(&search))
+                        .unwrap_or(false)
+            });
+        }
         if let Some(limit) = f.limit {
             history.truncate(limit);
         }
@@ -96,6 +125,10 @@ pub async fn get_history_stats(state: State<'_, AppState>) -> Result<HistoryStat
     let total_bytes_downloaded: u64 = downloads.iter()
         .filter_map(|d| d.total_size)
         .sum();
+
+    let total_wasted_bytes: u64 = downloads.iter()
+        .map(|d| d.wasted_bytes)
+        .sum();
     
     let average_speed = if !downloads.is_empty() {
         downloads.iter().map(|d| d.speed).sum::<f64>() / downloads.len() as f64
@@ -109,9 +142,33 @@ pub async fn get_history_stats(state: State<'_, AppState>) -> Result<HistoryStat
         failed_downloads,
         total_bytes_downloaded,
         average_speed,
+        total_wasted_bytes,
     })
 }
 
+/// Time range for statistics, matched against the download's creation time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsRange {
+    pub from: Option<NaiveDateTime>,
+    pub to: Option<NaiveDateTime>,
+}
+
+/// Get history statistics grouped by category, domain, month or status
+#[tauri::command]
+pub async fn get_stats_breakdown(
+    state: State<'_, AppState>,
+    group_by: StatsGroupBy,
+    range: Option<StatsRange>,
+) -> Result<Vec<StatsBucket>, String> {
+    let (from, to) = range
+        .map(|r| (r.from, r.to))
+        .unwrap_or((None, None));
+
+    state.db.get_stats_breakdown(group_by, from, to)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// Clear download history (delete completed downloads)
 #[tauri::command]
 pub async fn clear_download_history(state: State<'_, AppState>) -> Result<usize, String> {
@@ -222,3 +279,44 @@ pub async fn export_history(
     
     Ok(())
 }
+
+/// Group completed downloads whose files have identical contents
+#[tauri::command]
+pub async fn find_duplicate_files(state: State<'_, AppState>) -> Result<DuplicateReport, String> {
+    dedup::find_duplicates(&state.db).await.map_err(|e| e.to_string())
+}
+
+/// Reclaim the space taken by a group from `find_duplicate_files`
+#[tauri::command]
+pub async fn deduplicate(
+    state: State<'_, AppState>,
+    group_id: String,
+    strategy: DedupStrategy,
+) -> Result<DedupOutcome, String> {
+    dedup::deduplicate(&state.db, &group_id, strategy)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Rebuild the download list from files in `scan_dirs`, matching them
+/// against surviving rows and the `history_file` export. Without `apply`
+/// this is a dry run that only reports; with it the recognized files and
+/// the selected unknown files and segment folders are imported.
+#[tauri::command]
+pub async fn rebuild_from_disk(
+    state: State<'_, AppState>,
+    scan_dirs: Vec<PathBuf>,
+    history_file: Option<PathBuf>,
+    apply: Option<RebuildSelection>,
+) -> Result<RebuildReport, String> {
+    if scan_dirs.is_empty() {
+        return Err("Choose at least one folder to scan".to_string());
+    }
+    let report = rebuild::rebuild(&state.db, &scan_dirs, history_file.as_deref(), apply.as_ref())
+        .await
+        .map_err(|e| e.to_string())?;
+    if report.applied {
+        tracing::info!("Rebuilt {} downloads from disk", report.imported);
+    }
+    Ok(report)
+}
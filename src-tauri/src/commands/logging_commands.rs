@@ -1,7 +1,28 @@
 // Commands for logging and monitoring
 use tauri::State;
 use crate::state::app_state::AppState;
-use crate::utils::logging::{LogEntry, LogLevel, DownloadHistoryEntry, PerformanceMetrics};
+use crate::network::torrent_client_librqbit::TorrentSessionInfo;
+use crate::network::file_info_cache::FileInfoCacheStats;
+use crate::services::watchdog_service::WatchdogCounters;
+use serde::Serialize;
+use crate::utils::logging::{
+    LogEntry, LogLevel, DownloadHistoryEntry, PerformanceMetrics, LogRetention, LogStoreStats,
+    LOG_RETENTION_KEY,
+};
+
+/// Recorded performance samples plus the current size of the log store
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerformanceReport {
+    pub samples: Vec<PerformanceMetrics>,
+    pub logs: LogStoreStats,
+    pub database_bytes: Option<u64>,
+    pub torrent_session: TorrentSessionInfo,
+    /// Downloads the watchdog had to recover since the app started
+    pub watchdog: WatchdogCounters,
+    /// How often URL probes were answered from the file info cache
+    pub file_info_cache: FileInfoCacheStats,
+}
 
 #[tauri::command]
 pub async fn get_logs(
@@ -36,6 +57,15 @@ pub async fn get_logs_by_category(
     Ok(state.logger.get_logs_by_category(&category).await)
 }
 
+/// Log entries for a single download, e.g. its retry attempts
+#[tauri::command]
+pub async fn get_download_logs(
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<Vec<LogEntry>, String> {
+    Ok(state.logger.get_logs_for_download(&id).await)
+}
+
 #[tauri::command]
 pub async fn get_logger_download_history(
     state: State<'_, AppState>,
@@ -48,8 +78,35 @@ pub async fn get_logger_download_history(
 pub async fn get_performance_metrics(
     state: State<'_, AppState>,
     limit: Option<usize>,
-) -> Result<Vec<PerformanceMetrics>, String> {
-    Ok(state.logger.get_metrics(limit).await)
+) -> Result<PerformanceReport, String> {
+    Ok(PerformanceReport {
+        samples: state.logger.get_metrics(limit).await,
+        logs: state.logger.store_stats().await,
+        database_bytes: state.db.size_bytes().await.ok(),
+        torrent_session: state.torrent_client.session_info().await,
+        watchdog: state.watchdog.counters(),
+        file_info_cache: state.engine.file_info_cache_stats(),
+    })
+}
+
+#[tauri::command]
+pub async fn get_log_retention(
+    state: State<'_, AppState>,
+) -> Result<LogRetention, String> {
+    Ok(state.logger.retention())
+}
+
+/// Store a new retention policy and prune against it right away
+#[tauri::command]
+pub async fn set_log_retention(
+    state: State<'_, AppState>,
+    retention: LogRetention,
+) -> Result<(), String> {
+    let json = serde_json::to_string(&retention).map_err(|e| e.to_string())?;
+    state.db.set_setting(LOG_RETENTION_KEY, &json).await.map_err(|e| e.to_string())?;
+    state.logger.set_retention(retention);
+    state.logger.prune(chrono::Utc::now()).await;
+    Ok(())
 }
 
 #[tauri::command]
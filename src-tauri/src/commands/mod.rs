@@ -1,5 +1,6 @@
 pub mod browser_commands;
 pub mod category_commands;
+pub mod clipboard_commands;
 pub mod download_commands;
 pub mod queue_commands;
 pub mod settings_commands;
@@ -7,8 +8,12 @@ pub mod system_commands;
 pub mod scheduler_commands;
 pub mod ftp_commands;
 pub mod sftp_commands;
+pub mod remote_commands;
 pub mod torrent_commands;
 pub mod logging_commands;
 pub mod security_commands;
 pub mod ytdlp_commands;
 pub mod history_commands;
+pub mod transfer_commands;
+pub mod update_commands;
+pub mod undo_commands;
@@ -0,0 +1,102 @@
+// src-tauri/src/commands/remote_commands.rs
+// Checks behind the FTP and SFTP connect dialogs, and the passwords they
+// remember for the session
+
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tauri::State;
+
+use crate::network::ftp_client::FtpClient;
+use crate::network::remote_url::{self, RemoteProtocol, RemoteUrlCheck};
+use crate::network::sftp_client::SftpClient;
+use crate::state::app_state::AppState;
+use crate::utils::error::DownloadError;
+
+/// How long "Test connection" waits for connect, login and NOOP together
+pub const TEST_CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionTest {
+    /// Connect, login and NOOP, end to end
+    pub latency_ms: u64,
+    /// The FTP welcome message or SSH banner, if the server sent one
+    pub banner: Option<String>,
+}
+
+/// Take a remote URL apart and say what looks wrong, without connecting.
+/// `protocol_hint` fills in the scheme when the URL has none.
+#[tauri::command]
+pub async fn validate_remote_url(
+    state: State<'_, AppState>,
+    url: String,
+    protocol_hint: Option<RemoteProtocol>,
+) -> Result<RemoteUrlCheck, String> {
+    let (parts, warnings) = remote_url::parse_remote_url(&url, protocol_hint)?;
+    let has_saved_credentials = state.credential_vault.has(&parts.account());
+    Ok(RemoteUrlCheck { parts, warnings, has_saved_credentials })
+}
+
+/// Connect, log in and send NOOP, for the "Test connection" button. A
+/// password missing from both the URL and `password` comes from the vault.
+#[tauri::command]
+pub async fn test_remote_connection(
+    state: State<'_, AppState>,
+    url: String,
+    password: Option<String>,
+    key_path: Option<String>,
+) -> Result<ConnectionTest, String> {
+    let (parts, _) = remote_url::parse_remote_url(&url, None)?;
+    let full_url = remote_url::with_scheme(&url, None)?;
+    let password = password.or_else(|| state.credential_vault.lookup(&parts.account()));
+
+    let started = Instant::now();
+    let attempt = async {
+        match parts.protocol {
+            RemoteProtocol::Ftp | RemoteProtocol::Ftps => {
+                let (client, _) = FtpClient::from_url(&full_url)?;
+                client.with_password(password).test_connection().await
+            }
+            RemoteProtocol::Sftp => {
+                let key_path = key_path.map(PathBuf::from);
+                tokio::task::spawn_blocking(move || {
+                    let (client, _) = SftpClient::from_url(&full_url, password, key_path)?;
+                    client.test_connection()
+                })
+                .await
+                .map_err(|e| DownloadError::Unknown(e.to_string()))?
+            }
+        }
+    };
+    let banner = tokio::time::timeout(TEST_CONNECTION_TIMEOUT, attempt)
+        .await
+        .map_err(|_| format!("{} didn't answer within {} seconds", parts.host, TEST_CONNECTION_TIMEOUT.as_secs()))?
+        .map_err(|e| e.to_string())?;
+
+    Ok(ConnectionTest {
+        latency_ms: started.elapsed().as_millis() as u64,
+        banner,
+    })
+}
+
+/// Remember the password for the account in `url`
+#[tauri::command]
+pub async fn save_remote_credential(
+    state: State<'_, AppState>,
+    url: String,
+    password: String,
+) -> Result<(), String> {
+    let (parts, _) = remote_url::parse_remote_url(&url, None)?;
+    state.credential_vault.save(&parts.account(), &password)
+}
+
+/// Returns whether a password was saved for the account in `url`
+#[tauri::command]
+pub async fn forget_remote_credential(
+    state: State<'_, AppState>,
+    url: String,
+) -> Result<bool, String> {
+    let (parts, _) = remote_url::parse_remote_url(&url, None)?;
+    Ok(state.credential_vault.forget(&parts.account()))
+}
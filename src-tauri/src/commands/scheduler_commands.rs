@@ -1,7 +1,11 @@
-use tauri::State;
-use crate::core::scheduler::{ScheduledTask, RepeatInterval};
+use tauri::{Emitter, State};
+use crate::core::download_engine::AddDownloadRequest;
+use crate::core::download_task::{DownloadStatus, DownloadTask};
+use crate::core::scheduler::{
+    next_time_of_day, parse_time_of_day, ScheduledTask, RepeatInterval, DEFAULT_NIGHT_START,
+};
 use crate::state::app_state::AppState;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Local, Utc};
 
 #[tauri::command]
 pub async fn schedule_download(
@@ -52,6 +56,52 @@ pub async fn schedule_download(
     Ok(task_id)
 }
 
+/// Add a download that waits, paused, until the next night start (the
+/// `night_start` setting unless `start_time` is given, HH:MM local time).
+/// Past that time today it starts tomorrow night. Resuming it by hand
+/// drops the schedule.
+#[tauri::command]
+pub async fn schedule_for_tonight(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    request: AddDownloadRequest,
+    start_time: Option<String>,
+) -> Result<DownloadTask, String> {
+    let start_time = match start_time {
+        Some(time) => time,
+        None => state
+            .db
+            .get_setting("night_start")
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| DEFAULT_NIGHT_START.to_string()),
+    };
+    let at = parse_time_of_day(&start_time)?;
+    let when = next_time_of_day(&Local::now(), at);
+
+    let mut task = state.engine.create_task(&request).await.map_err(|e| e.to_string())?;
+    task.status = DownloadStatus::Paused;
+    task.scheduled_for = Some(when.naive_local());
+    state.db.reserve_and_insert(&mut task).await.map_err(|e| e.to_string())?;
+
+    state
+        .scheduler
+        .add_task(ScheduledTask {
+            id: uuid::Uuid::new_v4().to_string(),
+            download_id: task.id.to_string(),
+            scheduled_time: when.with_timezone(&Utc),
+            repeat_interval: None,
+            enabled: true,
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    tracing::info!("Download {} scheduled for {}", task.id, when);
+    let _ = app_handle.emit("download-added", &task.for_event());
+    Ok(task)
+}
+
 #[tauri::command]
 pub async fn cancel_scheduled_download(
     state: State<'_, AppState>,
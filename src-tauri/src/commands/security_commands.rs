@@ -32,9 +32,12 @@ pub async fn validate_url(
 
 #[tauri::command]
 pub async fn validate_file_path(
+    state: State<'_, AppState>,
     path: String,
-) -> Result<(), String> {
-    InputValidator::validate_file_path(&path)
+) -> Result<String, String> {
+    InputValidator::validate_file_path(&path, &state.engine.allowed_save_roots())
+        .map(|resolved| resolved.to_string_lossy().to_string())
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -3,11 +3,33 @@
 use tauri::State;
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 
+use crate::core::content_filter::{ContentFilter, ContentFilterPreset, FilterPreview, FilterSample};
+use crate::core::data_usage::{DataCapPolicy, UsagePeriod};
+use crate::core::post_action::ConversionProfile;
+use crate::core::resource_guard::ConditionPolicy;
+use crate::core::retry::RetryConfig;
+use crate::core::scheduler::{parse_time_of_day, DEFAULT_NIGHT_START};
+use crate::core::segment_gate::ContentionPolicy;
+use crate::core::size_guard::SizeLimits;
+use crate::core::speed_limiter::PriorityWeights;
+use crate::core::undo::{UndoSettings, DEFAULT_UNDO_EXPIRY_SECS, DEFAULT_UNDO_MAX_ENTRIES};
+use crate::core::waste::{self, DEFAULT_WASTE_WARNING_FRACTION, WASTE_WARNING_FRACTION_KEY};
+use crate::core::ytdlp_fallback::AUTO_FALLBACK_KEY;
+use crate::network::url_parser::DEFAULT_TRACKING_PARAMS;
+use crate::services::hook_service::{self, HookScripts, DEFAULT_HOOK_TIMEOUT_SECS};
+use crate::services::metrics_service;
+use crate::services::notification_service::{validate_sound_file, SoundSettings};
+use crate::services::recent_files_service::DEFAULT_SENSITIVE_EXTENSIONS;
+use crate::services::update_service::{UpdateChannel, UpdateSettings, DEFAULT_CHECK_INTERVAL_HOURS, DEFAULT_RELEASES_URL};
 use crate::state::app_state::AppState;
+use crate::utils::constants::{DEFAULT_BATCH_CONCURRENCY, DEFAULT_FILE_INFO_TTL, DEFAULT_MAX_RESOLVE_HOPS, DEFAULT_PLAYLIST_CONCURRENCY, DEFAULT_PROBE_TIMEOUT, DEFAULT_SOFT_PAUSE_SECS, MAX_SOFT_PAUSE_SECS};
+use crate::utils::permissions::{FilePermissions, COMPLETED_FILE_GROUP_KEY, COMPLETED_FILE_MODE_KEY};
+use crate::utils::privacy::{self, PrivacyLevel, PRIVACY_LEVEL_KEY};
 
 /// Application settings structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AppSettings {
     pub download_path: String,
     pub max_concurrent_downloads: u32,
@@ -19,6 +41,420 @@ pub struct AppSettings {
     pub monitor_clipboard: bool,
     pub auto_start_downloads: bool,
     pub default_category: String,
+    #[serde(default)]
+    pub start_torrents_paused: bool,
+    #[serde(default = "default_true")]
+    pub auto_categorize_on_add: bool,
+    #[serde(default = "default_true")]
+    pub show_taskbar_progress: bool,
+    #[serde(default = "default_true")]
+    pub integrity_sweep_on_startup: bool,
+    /// Path prefixes of network mounts; downloads on an offline mount are
+    /// skipped by the missing-file sweep
+    #[serde(default)]
+    pub network_mount_prefixes: Vec<String>,
+    /// Backoff between retries; downloads can override these individually
+    #[serde(default = "default_retry_initial_delay_ms")]
+    pub retry_initial_delay_ms: u64,
+    #[serde(default = "default_retry_max_delay_ms")]
+    pub retry_max_delay_ms: u64,
+    #[serde(default = "default_retry_backoff_multiplier")]
+    pub retry_backoff_multiplier: f64,
+    #[serde(default = "default_true")]
+    pub retry_jitter: bool,
+    /// Bandwidth share of each priority when downloads contend for the limit
+    #[serde(default)]
+    pub priority_weights: PriorityWeights,
+    /// Octal mode and group given to completed downloads on Unix; empty
+    /// leaves them as created
+    #[serde(default)]
+    pub completed_file_mode: String,
+    #[serde(default)]
+    pub completed_file_group: String,
+    /// Releases offered by the update check, and hours between automatic
+    /// checks (0 = only when asked)
+    #[serde(default)]
+    pub update_channel: UpdateChannel,
+    #[serde(default = "default_update_check_interval_hours")]
+    pub update_check_interval_hours: u32,
+    #[serde(default = "default_update_releases_url")]
+    pub update_releases_url: String,
+    /// Hold downloads of at least `large_download_threshold_mb` while on
+    /// battery, or on a network other than `allowed_networks` (Wi-Fi SSIDs
+    /// or "Ethernet"; empty allows any)
+    #[serde(default)]
+    pub only_on_ac_power: bool,
+    #[serde(default)]
+    pub allowed_networks: Vec<String>,
+    #[serde(default = "default_large_download_threshold_mb")]
+    pub large_download_threshold_mb: u64,
+    /// Bytes above which downloads from the browser extension or clipboard
+    /// wait for confirmation, and above which any download is refused
+    /// (0 = no limit)
+    #[serde(default)]
+    pub max_auto_download_size: u64,
+    #[serde(default)]
+    pub hard_max_download_size: u64,
+    /// Automated downloads of unknown size also wait for confirmation
+    #[serde(default)]
+    pub confirm_unknown_size_downloads: bool,
+    /// Once `contention_active_threshold` downloads are running, new ones
+    /// get fewer segments to keep the total near `max_total_connections`
+    #[serde(default)]
+    pub reduce_segments_under_contention: bool,
+    #[serde(default = "default_contention_active_threshold")]
+    pub contention_active_threshold: u32,
+    #[serde(default = "default_max_total_connections")]
+    pub max_total_connections: u32,
+    /// Seconds allowed for probing a URL's size and range support
+    #[serde(default = "default_probe_timeout_secs")]
+    pub probe_timeout_secs: u64,
+    /// Seconds a probed URL's file info is reused (0 = probe every time)
+    #[serde(default = "default_file_info_cache_secs")]
+    pub file_info_cache_secs: u64,
+    /// Quick pauses keep connections open for up to `soft_pause_secs`,
+    /// then become real pauses
+    #[serde(default)]
+    pub soft_pause: bool,
+    #[serde(default = "default_soft_pause_secs")]
+    pub soft_pause_secs: u64,
+    /// Bytes allowed per week or month, starting on `data_cap_reset_day`
+    /// (0 = no cap). Past `data_cap_soft_percent` transfers slow to
+    /// `data_cap_throttle_to` bytes/s (0 = don't slow); at the cap
+    /// everything is held until the next period.
+    #[serde(default)]
+    pub data_cap_period: UsagePeriod,
+    #[serde(default)]
+    pub data_cap_bytes: u64,
+    #[serde(default = "default_data_cap_reset_day")]
+    pub data_cap_reset_day: u32,
+    #[serde(default = "default_data_cap_soft_percent")]
+    pub data_cap_soft_percent: u8,
+    #[serde(default)]
+    pub data_cap_throttle_to: u64,
+    #[serde(default = "default_true")]
+    pub data_cap_pause_at_cap: bool,
+    /// Count torrent uploads against the cap
+    #[serde(default)]
+    pub data_cap_count_uploads: bool,
+    /// Follow shorteners and redirect pages to the file before adding,
+    /// giving up after `max_resolve_hops`
+    #[serde(default = "default_true")]
+    pub resolve_urls: bool,
+    #[serde(default = "default_max_resolve_hops")]
+    pub max_resolve_hops: u32,
+    /// URLs a batch add probes at once
+    #[serde(default = "default_batch_concurrency")]
+    pub batch_concurrency: u32,
+    /// Folders picked in the save dialog that downloads may be saved
+    /// under, besides `download_path` and category folders
+    #[serde(default)]
+    pub allowed_save_roots: Vec<String>,
+    /// Folder new torrents are saved to; empty uses `download_path`
+    #[serde(default)]
+    pub torrent_download_dir: String,
+    /// Play a chime when downloads finish and a buzz when they fail;
+    /// the paths replace the bundled sounds (empty = bundled)
+    #[serde(default)]
+    pub notification_sounds: bool,
+    #[serde(default)]
+    pub completion_sound: String,
+    #[serde(default)]
+    pub failure_sound: String,
+    /// Keep sounds and non-critical notifications quiet while the OS is
+    /// in do-not-disturb or focus mode
+    #[serde(default = "default_true")]
+    pub respect_do_not_disturb: bool,
+    /// ffmpeg conversions downloads can run once they complete
+    #[serde(default = "ConversionProfile::defaults")]
+    pub conversion_profiles: Vec<ConversionProfile>,
+    /// Time of day (HH:MM) "download tonight" starts downloads at
+    #[serde(default = "default_night_start")]
+    pub night_start: String,
+    /// yt-dlp processes a per-entry playlist runs at once
+    #[serde(default = "default_playlist_concurrency")]
+    pub playlist_concurrency: u32,
+    /// Named filters offered when adding batches, folders and torrents
+    #[serde(default)]
+    pub content_filter_presets: Vec<ContentFilterPreset>,
+    /// host:port to serve Prometheus metrics on; empty keeps it off.
+    /// Read at startup.
+    #[serde(default)]
+    pub metrics_address: String,
+    /// How much of a download's URL and file name goes out in webhooks,
+    /// metrics and logs; the app's own windows always see everything
+    #[serde(default)]
+    pub privacy_level: PrivacyLevel,
+    /// Scripts run for every download that completes or fails, and once
+    /// nothing is left running or queued; empty runs nothing
+    #[serde(default)]
+    pub on_download_complete_script: String,
+    #[serde(default)]
+    pub on_download_failed_script: String,
+    #[serde(default)]
+    pub on_all_complete_script: String,
+    /// Seconds a hook script may run before it is killed
+    #[serde(default = "default_hook_timeout_secs")]
+    pub hook_timeout_secs: u64,
+    /// Warn when a download has thrown away this fraction of its size
+    #[serde(default = "default_waste_warning_fraction")]
+    pub wasted_bytes_warning_fraction: f64,
+    /// Retry media links refused with 403 through yt-dlp with the page
+    /// they came from
+    #[serde(default = "default_true")]
+    pub auto_fallback_to_ytdlp: bool,
+    /// How long a destructive action can be undone, and how many are kept
+    #[serde(default = "default_undo_expiry_secs")]
+    pub undo_expiry_secs: u64,
+    #[serde(default = "default_undo_max_entries")]
+    pub undo_max_entries: u32,
+    /// Files deleted with their download go to the trash, so that can be
+    /// undone too
+    #[serde(default)]
+    pub soft_delete_files: bool,
+    /// Refuse to add a download there's no room for on its destination or
+    /// temp volume, instead of only warning
+    #[serde(default)]
+    pub refuse_without_space: bool,
+    /// Query parameters stripped from pasted and imported URLs before
+    /// duplicates are dropped; `utm_*` matches every `utm_` parameter
+    #[serde(default = "default_tracking_params")]
+    pub tracking_params: Vec<String>,
+    /// List finished downloads in the OS's recent files and, on Windows,
+    /// the taskbar Jump List
+    #[serde(default = "default_true")]
+    pub add_to_recent_files: bool,
+    /// Extensions of files never listed there
+    #[serde(default = "default_recent_files_excluded_extensions")]
+    pub recent_files_excluded_extensions: Vec<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_tracking_params() -> Vec<String> {
+    DEFAULT_TRACKING_PARAMS.iter().map(|p| p.to_string()).collect()
+}
+
+fn default_recent_files_excluded_extensions() -> Vec<String> {
+    DEFAULT_SENSITIVE_EXTENSIONS.iter().map(|e| e.to_string()).collect()
+}
+
+fn default_update_check_interval_hours() -> u32 {
+    DEFAULT_CHECK_INTERVAL_HOURS
+}
+
+fn default_update_releases_url() -> String {
+    DEFAULT_RELEASES_URL.to_string()
+}
+
+fn default_large_download_threshold_mb() -> u64 {
+    ConditionPolicy::default().large_download_bytes / (1024 * 1024)
+}
+
+fn default_contention_active_threshold() -> u32 {
+    ContentionPolicy::default().active_threshold as u32
+}
+
+fn default_max_total_connections() -> u32 {
+    ContentionPolicy::default().max_total_connections as u32
+}
+
+fn default_probe_timeout_secs() -> u64 {
+    DEFAULT_PROBE_TIMEOUT
+}
+
+fn default_file_info_cache_secs() -> u64 {
+    DEFAULT_FILE_INFO_TTL
+}
+
+fn default_soft_pause_secs() -> u64 {
+    DEFAULT_SOFT_PAUSE_SECS
+}
+
+fn default_max_resolve_hops() -> u32 {
+    DEFAULT_MAX_RESOLVE_HOPS as u32
+}
+
+fn default_batch_concurrency() -> u32 {
+    DEFAULT_BATCH_CONCURRENCY as u32
+}
+
+fn default_playlist_concurrency() -> u32 {
+    DEFAULT_PLAYLIST_CONCURRENCY as u32
+}
+
+fn default_hook_timeout_secs() -> u64 {
+    DEFAULT_HOOK_TIMEOUT_SECS
+}
+
+fn default_undo_expiry_secs() -> u64 {
+    DEFAULT_UNDO_EXPIRY_SECS
+}
+
+fn default_undo_max_entries() -> u32 {
+    DEFAULT_UNDO_MAX_ENTRIES as u32
+}
+
+fn default_waste_warning_fraction() -> f64 {
+    DEFAULT_WASTE_WARNING_FRACTION
+}
+
+fn default_data_cap_reset_day() -> u32 {
+    DataCapPolicy::default().reset_day
+}
+
+fn default_night_start() -> String {
+    DEFAULT_NIGHT_START.to_string()
+}
+
+fn default_data_cap_soft_percent() -> u8 {
+    DataCapPolicy::default().soft_threshold_percent
+}
+
+fn default_retry_initial_delay_ms() -> u64 {
+    RetryConfig::default().initial_delay_ms
+}
+
+fn default_retry_max_delay_ms() -> u64 {
+    RetryConfig::default().max_delay_ms
+}
+
+fn default_retry_backoff_multiplier() -> f64 {
+    RetryConfig::default().backoff_multiplier
+}
+
+impl AppSettings {
+    /// Global retry policy described by these settings
+    pub fn retry_config(&self) -> RetryConfig {
+        RetryConfig {
+            initial_delay_ms: self.retry_initial_delay_ms,
+            max_delay_ms: self.retry_max_delay_ms,
+            backoff_multiplier: self.retry_backoff_multiplier,
+            jitter: self.retry_jitter,
+            ..RetryConfig::default()
+        }
+    }
+
+    /// How the app checks for its own updates
+    pub fn update_settings(&self) -> UpdateSettings {
+        UpdateSettings {
+            channel: self.update_channel,
+            check_interval_hours: self.update_check_interval_hours,
+            releases_url: self.update_releases_url.clone(),
+        }
+    }
+
+    /// Hook scripts as saved; they were checked when the settings were
+    pub fn hook_scripts(&self) -> HookScripts {
+        let script = |value: &str| Some(value.trim()).filter(|v| !v.is_empty()).map(std::path::PathBuf::from);
+        HookScripts {
+            on_download_complete: script(&self.on_download_complete_script),
+            on_download_failed: script(&self.on_download_failed_script),
+            on_all_complete: script(&self.on_all_complete_script),
+            timeout: std::time::Duration::from_secs(self.hook_timeout_secs),
+        }
+    }
+
+    pub fn undo_settings(&self) -> UndoSettings {
+        UndoSettings {
+            max_entries: self.undo_max_entries as usize,
+            expiry: std::time::Duration::from_secs(self.undo_expiry_secs),
+            soft_delete: self.soft_delete_files,
+        }
+    }
+
+    /// Power and network conditions large downloads wait for
+    pub fn condition_policy(&self) -> ConditionPolicy {
+        ConditionPolicy {
+            only_on_ac_power: self.only_on_ac_power,
+            allowed_networks: self.allowed_networks.clone(),
+            large_download_bytes: self.large_download_threshold_mb * 1024 * 1024,
+        }
+    }
+
+    /// Size thresholds checked when downloads are added
+    pub fn size_limits(&self) -> SizeLimits {
+        SizeLimits {
+            max_auto_download_size: self.max_auto_download_size,
+            hard_max_download_size: self.hard_max_download_size,
+            confirm_unknown_size: self.confirm_unknown_size_downloads,
+        }
+    }
+
+    /// When new downloads get fewer segments
+    pub fn contention_policy(&self) -> ContentionPolicy {
+        ContentionPolicy {
+            enabled: self.reduce_segments_under_contention,
+            active_threshold: self.contention_active_threshold as usize,
+            max_total_connections: self.max_total_connections as usize,
+        }
+    }
+
+    /// Overall budget for probing a URL before it is added
+    pub fn probe_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.probe_timeout_secs.max(1))
+    }
+
+    /// How long a probed URL's file info is reused
+    pub fn file_info_ttl(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.file_info_cache_secs)
+    }
+
+    /// How long a soft pause keeps connections open
+    pub fn soft_pause_limit(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.soft_pause_secs)
+    }
+
+    /// Data budget and what happens as it runs out
+    pub fn data_cap_policy(&self) -> DataCapPolicy {
+        DataCapPolicy {
+            period: self.data_cap_period,
+            budget_bytes: self.data_cap_bytes,
+            reset_day: self.data_cap_reset_day.clamp(1, 31),
+            soft_threshold_percent: self.data_cap_soft_percent.min(100),
+            throttle_to: (self.data_cap_throttle_to > 0).then_some(self.data_cap_throttle_to),
+            pause_at_cap: self.data_cap_pause_at_cap,
+            count_uploads: self.data_cap_count_uploads,
+        }
+    }
+
+    /// Mode and group applied to completed downloads
+    pub fn file_permissions(&self) -> FilePermissions {
+        FilePermissions::new(
+            Some(self.completed_file_mode.clone()),
+            Some(self.completed_file_group.clone()),
+        )
+    }
+
+    /// Folder new torrents go to, when set apart from `download_path`
+    pub fn torrent_download_dir(&self) -> Option<std::path::PathBuf> {
+        Some(self.torrent_download_dir.trim())
+            .filter(|dir| !dir.is_empty())
+            .map(std::path::PathBuf::from)
+    }
+
+    /// Notification sounds and do-not-disturb handling
+    pub fn sound_settings(&self) -> SoundSettings {
+        let path = |p: &str| Some(p.trim()).filter(|p| !p.is_empty()).map(std::path::PathBuf::from);
+        SoundSettings {
+            enabled: self.notification_sounds,
+            completion_sound: path(&self.completion_sound),
+            failure_sound: path(&self.failure_sound),
+            respect_do_not_disturb: self.respect_do_not_disturb,
+        }
+    }
+
+    /// Folders approved for saving besides the engine's default and
+    /// category folders
+    pub fn approved_save_roots(&self) -> Vec<std::path::PathBuf> {
+        std::iter::once(&self.download_path)
+            .chain(&self.allowed_save_roots)
+            .filter(|root| !root.is_empty())
+            .map(std::path::PathBuf::from)
+            .collect()
+    }
 }
 
 impl Default for AppSettings {
@@ -34,6 +470,69 @@ impl Default for AppSettings {
             monitor_clipboard: true,
             auto_start_downloads: false,
             default_category: "general".to_string(),
+            start_torrents_paused: false,
+            auto_categorize_on_add: true,
+            show_taskbar_progress: true,
+            integrity_sweep_on_startup: true,
+            network_mount_prefixes: Vec::new(),
+            retry_initial_delay_ms: default_retry_initial_delay_ms(),
+            retry_max_delay_ms: default_retry_max_delay_ms(),
+            retry_backoff_multiplier: default_retry_backoff_multiplier(),
+            retry_jitter: true,
+            priority_weights: PriorityWeights::default(),
+            completed_file_mode: String::new(),
+            completed_file_group: String::new(),
+            update_channel: UpdateChannel::Stable,
+            update_check_interval_hours: default_update_check_interval_hours(),
+            update_releases_url: default_update_releases_url(),
+            only_on_ac_power: false,
+            allowed_networks: Vec::new(),
+            large_download_threshold_mb: default_large_download_threshold_mb(),
+            max_auto_download_size: 0,
+            hard_max_download_size: 0,
+            confirm_unknown_size_downloads: false,
+            reduce_segments_under_contention: false,
+            contention_active_threshold: default_contention_active_threshold(),
+            max_total_connections: default_max_total_connections(),
+            probe_timeout_secs: default_probe_timeout_secs(),
+            file_info_cache_secs: default_file_info_cache_secs(),
+            soft_pause: false,
+            soft_pause_secs: default_soft_pause_secs(),
+            data_cap_period: UsagePeriod::Monthly,
+            data_cap_bytes: 0,
+            data_cap_reset_day: default_data_cap_reset_day(),
+            data_cap_soft_percent: default_data_cap_soft_percent(),
+            data_cap_throttle_to: 0,
+            data_cap_pause_at_cap: true,
+            data_cap_count_uploads: false,
+            resolve_urls: true,
+            max_resolve_hops: default_max_resolve_hops(),
+            batch_concurrency: default_batch_concurrency(),
+            allowed_save_roots: Vec::new(),
+            torrent_download_dir: String::new(),
+            notification_sounds: false,
+            completion_sound: String::new(),
+            failure_sound: String::new(),
+            respect_do_not_disturb: true,
+            conversion_profiles: ConversionProfile::defaults(),
+            night_start: default_night_start(),
+            playlist_concurrency: default_playlist_concurrency(),
+            content_filter_presets: Vec::new(),
+            metrics_address: String::new(),
+            privacy_level: PrivacyLevel::default(),
+            on_download_complete_script: String::new(),
+            on_download_failed_script: String::new(),
+            on_all_complete_script: String::new(),
+            hook_timeout_secs: default_hook_timeout_secs(),
+            wasted_bytes_warning_fraction: default_waste_warning_fraction(),
+            auto_fallback_to_ytdlp: true,
+            undo_expiry_secs: default_undo_expiry_secs(),
+            undo_max_entries: default_undo_max_entries(),
+            soft_delete_files: false,
+            refuse_without_space: false,
+            tracking_params: default_tracking_params(),
+            add_to_recent_files: true,
+            recent_files_excluded_extensions: default_recent_files_excluded_extensions(),
         }
     }
 }
@@ -65,6 +564,63 @@ pub async fn update_settings(
     state: State<'_, AppState>,
     settings: AppSettings,
 ) -> Result<(), String> {
+    let file_permissions = settings.file_permissions();
+    file_permissions.validate()?;
+    let sound_settings = settings.sound_settings();
+    for path in [&sound_settings.completion_sound, &sound_settings.failure_sound].into_iter().flatten() {
+        validate_sound_file(path)?;
+    }
+    for (i, profile) in settings.conversion_profiles.iter().enumerate() {
+        profile.validate()?;
+        if settings.conversion_profiles[..i].iter().any(|p| p.name == profile.name) {
+            return Err(format!("Two conversion profiles are named {}", profile.name));
+        }
+    }
+    parse_time_of_day(&settings.night_start)?;
+    if !(1..=8).contains(&settings.playlist_concurrency) {
+        return Err("Playlist concurrency must be between 1 and 8".to_string());
+    }
+    if let Some(param) = settings.tracking_params.iter().find(|p| p.contains(['&', '=', '\n'])) {
+        return Err(format!("Invalid tracking parameter: {}", param));
+    }
+    if let Some(extension) = settings
+        .recent_files_excluded_extensions
+        .iter()
+        .find(|e| e.contains(['/', '\\', '\n']))
+    {
+        return Err(format!("Invalid file extension: {}", extension));
+    }
+    for (i, preset) in settings.content_filter_presets.iter().enumerate() {
+        if preset.name.trim().is_empty() {
+            return Err("Content filter presets need a name".to_string());
+        }
+        if settings.content_filter_presets[..i].iter().any(|p| p.name == preset.name) {
+            return Err(format!("Two content filter presets are named {}", preset.name));
+        }
+        preset.filter.compile().map_err(|e| format!("{}: {}", preset.name, e))?;
+    }
+    metrics_service::parse_address(&settings.metrics_address)?;
+    for script in [
+        &settings.on_download_complete_script,
+        &settings.on_download_failed_script,
+        &settings.on_all_complete_script,
+    ] {
+        hook_service::validate_script(script)?;
+    }
+    if !(1..=3600).contains(&settings.hook_timeout_secs) {
+        return Err("Hook timeout must be between 1 and 3600 seconds".to_string());
+    }
+    waste::validate_fraction(settings.wasted_bytes_warning_fraction)?;
+    if !(10..=86_400).contains(&settings.undo_expiry_secs) {
+        return Err("Undo expiry must be between 10 seconds and a day".to_string());
+    }
+    if !(1..=200).contains(&settings.undo_max_entries) {
+        return Err("Undo history must keep between 1 and 200 actions".to_string());
+    }
+    if !(1..=MAX_SOFT_PAUSE_SECS).contains(&settings.soft_pause_secs) {
+        return Err(format!("Soft pauses must last between 1 and {} seconds", MAX_SOFT_PAUSE_SECS));
+    }
+
     // Convert settings to key-value pairs and save to database
     state.db.set_setting("download_path", &settings.download_path).await.map_err(|e| e.to_string())?;
     state.db.set_setting("max_concurrent_downloads", &settings.max_concurrent_downloads.to_string()).await.map_err(|e| e.to_string())?;
@@ -76,6 +632,109 @@ pub async fn update_settings(
     state.db.set_setting("monitor_clipboard", &settings.monitor_clipboard.to_string()).await.map_err(|e| e.to_string())?;
     state.db.set_setting("auto_start_downloads", &settings.auto_start_downloads.to_string()).await.map_err(|e| e.to_string())?;
     state.db.set_setting("default_category", &settings.default_category).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("start_torrents_paused", &settings.start_torrents_paused.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("auto_categorize_on_add", &settings.auto_categorize_on_add.to_string()).await.map_err(|e| e.to_string())?;
+
+    state.db.set_setting("show_taskbar_progress", &settings.show_taskbar_progress.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("integrity_sweep_on_startup", &settings.integrity_sweep_on_startup.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("network_mount_prefixes", &settings.network_mount_prefixes.join("\n")).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("retry_initial_delay_ms", &settings.retry_initial_delay_ms.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("retry_max_delay_ms", &settings.retry_max_delay_ms.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("retry_backoff_multiplier", &settings.retry_backoff_multiplier.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("retry_jitter", &settings.retry_jitter.to_string()).await.map_err(|e| e.to_string())?;
+    let priority_weights = serde_json::to_string(&settings.priority_weights).map_err(|e| e.to_string())?;
+    state.db.set_setting("priority_weights", &priority_weights).await.map_err(|e| e.to_string())?;
+    state.db.set_setting(COMPLETED_FILE_MODE_KEY, file_permissions.mode.as_deref().unwrap_or_default()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting(COMPLETED_FILE_GROUP_KEY, file_permissions.group.as_deref().unwrap_or_default()).await.map_err(|e| e.to_string())?;
+    let update_channel = serde_json::to_string(&settings.update_channel).map_err(|e| e.to_string())?;
+    state.db.set_setting("update_channel", update_channel.trim_matches('"')).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("update_check_interval_hours", &settings.update_check_interval_hours.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("update_releases_url", &settings.update_releases_url).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("only_on_ac_power", &settings.only_on_ac_power.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("allowed_networks", &settings.allowed_networks.join("\n")).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("large_download_threshold_mb", &settings.large_download_threshold_mb.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("max_auto_download_size", &settings.max_auto_download_size.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("hard_max_download_size", &settings.hard_max_download_size.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("confirm_unknown_size_downloads", &settings.confirm_unknown_size_downloads.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("reduce_segments_under_contention", &settings.reduce_segments_under_contention.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("contention_active_threshold", &settings.contention_active_threshold.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("max_total_connections", &settings.max_total_connections.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("probe_timeout_secs", &settings.probe_timeout_secs.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("file_info_cache_secs", &settings.file_info_cache_secs.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("soft_pause", &settings.soft_pause.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("soft_pause_secs", &settings.soft_pause_secs.to_string()).await.map_err(|e| e.to_string())?;
+    let data_cap_period = serde_json::to_string(&settings.data_cap_period).map_err(|e| e.to_string())?;
+    state.db.set_setting("data_cap_period", data_cap_period.trim_matches('"')).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("data_cap_bytes", &settings.data_cap_bytes.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("data_cap_reset_day", &settings.data_cap_reset_day.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("data_cap_soft_percent", &settings.data_cap_soft_percent.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("data_cap_throttle_to", &settings.data_cap_throttle_to.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("data_cap_pause_at_cap", &settings.data_cap_pause_at_cap.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("data_cap_count_uploads", &settings.data_cap_count_uploads.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("resolve_urls", &settings.resolve_urls.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("max_resolve_hops", &settings.max_resolve_hops.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("batch_concurrency", &settings.batch_concurrency.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("allowed_save_roots", &settings.allowed_save_roots.join("\n")).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("torrent_download_dir", &settings.torrent_download_dir).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("notification_sounds", &settings.notification_sounds.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("completion_sound", &settings.completion_sound).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("failure_sound", &settings.failure_sound).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("respect_do_not_disturb", &settings.respect_do_not_disturb.to_string()).await.map_err(|e| e.to_string())?;
+    let conversion_profiles = serde_json::to_string(&settings.conversion_profiles).map_err(|e| e.to_string())?;
+    state.db.set_setting("conversion_profiles", &conversion_profiles).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("night_start", settings.night_start.trim()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("playlist_concurrency", &settings.playlist_concurrency.to_string()).await.map_err(|e| e.to_string())?;
+    let content_filter_presets = serde_json::to_string(&settings.content_filter_presets).map_err(|e| e.to_string())?;
+    state.db.set_setting("content_filter_presets", &content_filter_presets).await.map_err(|e| e.to_string())?;
+    state.db.set_setting(metrics_service::METRICS_ADDRESS_KEY, settings.metrics_address.trim()).await.map_err(|e| e.to_string())?;
+    let privacy_level = serde_json::to_string(&settings.privacy_level).map_err(|e| e.to_string())?;
+    state.db.set_setting(PRIVACY_LEVEL_KEY, privacy_level.trim_matches('"')).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("on_download_complete_script", settings.on_download_complete_script.trim()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("on_download_failed_script", settings.on_download_failed_script.trim()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("on_all_complete_script", settings.on_all_complete_script.trim()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("hook_timeout_secs", &settings.hook_timeout_secs.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting(WASTE_WARNING_FRACTION_KEY, &settings.wasted_bytes_warning_fraction.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting(AUTO_FALLBACK_KEY, &settings.auto_fallback_to_ytdlp.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("undo_expiry_secs", &settings.undo_expiry_secs.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("undo_max_entries", &settings.undo_max_entries.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("soft_delete_files", &settings.soft_delete_files.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("refuse_without_space", &settings.refuse_without_space.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("tracking_params", &settings.tracking_params.join("\n")).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("add_to_recent_files", &settings.add_to_recent_files.to_string()).await.map_err(|e| e.to_string())?;
+    state.db.set_setting("recent_files_excluded_extensions", &settings.recent_files_excluded_extensions.join("\n")).await.map_err(|e| e.to_string())?;
+
+    state.engine.set_auto_categorize(settings.auto_categorize_on_add);
+    state.engine.set_refuse_without_space(settings.refuse_without_space);
+    state.engine.set_tracking_params(settings.tracking_params.clone());
+    state.recent_files.configure(settings.add_to_recent_files, &settings.recent_files_excluded_extensions);
+    state.taskbar.set_enabled(settings.show_taskbar_progress);
+    state.engine.set_retry_defaults(settings.retry_config());
+    state.engine.speed_limiter.set_weights(settings.priority_weights);
+    state.engine.set_file_permissions(file_permissions);
+    state.updates.set_settings(settings.update_settings());
+    state.resource_guard.set_policy(settings.condition_policy());
+    state.engine.set_size_limits(settings.size_limits());
+    state.engine.set_contention_policy(settings.contention_policy());
+    state.engine.set_probe_timeout(settings.probe_timeout());
+    state.engine.set_file_info_ttl(settings.file_info_ttl());
+    state.soft_pauses.configure(settings.soft_pause, settings.soft_pause_limit());
+    state.resource_guard.set_data_cap_policy(settings.data_cap_policy());
+    state.engine.resolvers.set_enabled(settings.resolve_urls);
+    state.engine.resolvers.set_max_hops(settings.max_resolve_hops as usize);
+    state.engine.set_batch_concurrency(settings.batch_concurrency as usize);
+    state.engine.set_playlist_concurrency(settings.playlist_concurrency as usize);
+    state.engine.set_waste_warning_fraction(settings.wasted_bytes_warning_fraction);
+    state.engine.set_approved_save_roots(settings.approved_save_roots());
+    if let Some(dir) = settings.torrent_download_dir() {
+        state.torrent_client.set_download_dir(dir);
+    }
+    state.sounds.set_settings(sound_settings);
+    state.hooks.set_scripts(settings.hook_scripts());
+    state.undo.set_settings(settings.undo_settings());
+    state.conversions.set_profiles(settings.conversion_profiles);
+    state.engine.url_refresher.set_privacy(settings.privacy_level);
+    state.metrics.set_privacy(settings.privacy_level);
+    privacy::set_logging_privacy(settings.privacy_level);
 
     tracing::info!("Settings updated successfully");
     Ok(())
@@ -88,8 +747,44 @@ pub async fn reset_settings(state: State<'_, AppState>) -> Result<(), String> {
     update_settings(state, defaults).await
 }
 
+/// Approve a folder the user picked, so downloads may be saved under
+/// it. Without `remember` the approval lasts until the app quits.
+#[tauri::command]
+pub async fn allow_save_root(
+    state: State<'_, AppState>,
+    path: String,
+    remember: bool,
+) -> Result<(), String> {
+    let root = std::path::Path::new(&path);
+    if !root.is_absolute() {
+        return Err(format!("{} is not an absolute path", path));
+    }
+    if !remember {
+        state.engine.add_session_save_root(root.to_path_buf());
+        return Ok(());
+    }
+
+    let mut settings = map_to_settings(&state.db.get_all_settings().await.map_err(|e| e.to_string())?);
+    if settings.allowed_save_roots.contains(&path) {
+        return Ok(());
+    }
+    settings.allowed_save_roots.push(path);
+    state.db.set_setting("allowed_save_roots", &settings.allowed_save_roots.join("\n")).await.map_err(|e| e.to_string())?;
+    state.engine.set_approved_save_roots(settings.approved_save_roots());
+    Ok(())
+}
+
+/// Show which of `samples` a filter would take, before it is used
+#[tauri::command]
+pub async fn test_content_filter(
+    filter: ContentFilter,
+    samples: Vec<FilterSample>,
+) -> Result<Vec<FilterPreview>, String> {
+    Ok(filter.compile()?.preview(samples))
+}
+
 /// Helper function to convert database map to AppSettings
-fn map_to_settings(map: &HashMap<String, String>) -> AppSettings {
+pub fn map_to_settings(map: &HashMap<String, String>) -> AppSettings {
     AppSettings {
         download_path: map.get("download_path").cloned().unwrap_or_default(),
         max_concurrent_downloads: map.get("max_concurrent_downloads")
@@ -117,5 +812,179 @@ fn map_to_settings(map: &HashMap<String, String>) -> AppSettings {
         default_category: map.get("default_category")
             .cloned()
             .unwrap_or_else(|| "general".to_string()),
+        start_torrents_paused: map.get("start_torrents_paused")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(false),
+        auto_categorize_on_add: map.get("auto_categorize_on_add")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(true),
+        show_taskbar_progress: map.get("show_taskbar_progress")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(true),
+        integrity_sweep_on_startup: map.get("integrity_sweep_on_startup")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(true),
+        network_mount_prefixes: map.get("network_mount_prefixes")
+            .map(|s| s.lines().map(str::trim).filter(|l| !l.is_empty()).map(String::from).collect())
+            .unwrap_or_default(),
+        retry_initial_delay_ms: map.get("retry_initial_delay_ms")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(default_retry_initial_delay_ms),
+        retry_max_delay_ms: map.get("retry_max_delay_ms")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(default_retry_max_delay_ms),
+        retry_backoff_multiplier: map.get("retry_backoff_multiplier")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(default_retry_backoff_multiplier),
+        retry_jitter: map.get("retry_jitter")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(true),
+        priority_weights: map.get("priority_weights")
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or_default(),
+        completed_file_mode: map.get(COMPLETED_FILE_MODE_KEY).cloned().unwrap_or_default(),
+        completed_file_group: map.get(COMPLETED_FILE_GROUP_KEY).cloned().unwrap_or_default(),
+        update_channel: map.get("update_channel")
+            .and_then(|s| serde_json::from_value(serde_json::Value::String(s.clone())).ok())
+            .unwrap_or_default(),
+        update_check_interval_hours: map.get("update_check_interval_hours")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(default_update_check_interval_hours),
+        update_releases_url: map.get("update_releases_url")
+            .filter(|s| !s.is_empty())
+            .cloned()
+            .unwrap_or_else(default_update_releases_url),
+        only_on_ac_power: map.get("only_on_ac_power")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(false),
+        allowed_networks: map.get("allowed_networks")
+            .map(|s| s.lines().map(str::trim).filter(|l| !l.is_empty()).map(String::from).collect())
+            .unwrap_or_default(),
+        large_download_threshold_mb: map.get("large_download_threshold_mb")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(default_large_download_threshold_mb),
+        max_auto_download_size: map.get("max_auto_download_size")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0),
+        hard_max_download_size: map.get("hard_max_download_size")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0),
+        confirm_unknown_size_downloads: map.get("confirm_unknown_size_downloads")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(false),
+        reduce_segments_under_contention: map.get("reduce_segments_under_contention")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(false),
+        contention_active_threshold: map.get("contention_active_threshold")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(default_contention_active_threshold),
+        max_total_connections: map.get("max_total_connections")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(default_max_total_connections),
+        probe_timeout_secs: map.get("probe_timeout_secs")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(default_probe_timeout_secs),
+        file_info_cache_secs: map.get("file_info_cache_secs")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(default_file_info_cache_secs),
+        soft_pause: map.get("soft_pause")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(false),
+        soft_pause_secs: map.get("soft_pause_secs")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(default_soft_pause_secs),
+        data_cap_period: map.get("data_cap_period")
+            .and_then(|s| serde_json::from_value(serde_json::Value::String(s.clone())).ok())
+            .unwrap_or_default(),
+        data_cap_bytes: map.get("data_cap_bytes")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0),
+        data_cap_reset_day: map.get("data_cap_reset_day")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(default_data_cap_reset_day),
+        data_cap_soft_percent: map.get("data_cap_soft_percent")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(default_data_cap_soft_percent),
+        data_cap_throttle_to: map.get("data_cap_throttle_to")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0),
+        data_cap_pause_at_cap: map.get("data_cap_pause_at_cap")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(true),
+        data_cap_count_uploads: map.get("data_cap_count_uploads")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(false),
+        resolve_urls: map.get("resolve_urls")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(true),
+        max_resolve_hops: map.get("max_resolve_hops")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(default_max_resolve_hops),
+        batch_concurrency: map.get("batch_concurrency")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(default_batch_concurrency),
+        allowed_save_roots: map.get("allowed_save_roots")
+            .map(|s| s.lines().map(str::trim).filter(|l| !l.is_empty()).map(String::from).collect())
+            .unwrap_or_default(),
+        torrent_download_dir: map.get("torrent_download_dir").cloned().unwrap_or_default(),
+        notification_sounds: map.get("notification_sounds")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(false),
+        completion_sound: map.get("completion_sound").cloned().unwrap_or_default(),
+        failure_sound: map.get("failure_sound").cloned().unwrap_or_default(),
+        respect_do_not_disturb: map.get("respect_do_not_disturb")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(true),
+        conversion_profiles: map.get("conversion_profiles")
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or_else(ConversionProfile::defaults),
+        night_start: map.get("night_start")
+            .filter(|s| parse_time_of_day(s).is_ok())
+            .cloned()
+            .unwrap_or_else(default_night_start),
+        playlist_concurrency: map.get("playlist_concurrency")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(default_playlist_concurrency),
+        content_filter_presets: map.get("content_filter_presets")
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or_default(),
+        metrics_address: map.get(metrics_service::METRICS_ADDRESS_KEY).cloned().unwrap_or_default(),
+        privacy_level: map.get(PRIVACY_LEVEL_KEY)
+            .and_then(|s| serde_json::from_value(serde_json::Value::String(s.clone())).ok())
+            .unwrap_or_default(),
+        on_download_complete_script: map.get("on_download_complete_script").cloned().unwrap_or_default(),
+        on_download_failed_script: map.get("on_download_failed_script").cloned().unwrap_or_default(),
+        on_all_complete_script: map.get("on_all_complete_script").cloned().unwrap_or_default(),
+        hook_timeout_secs: map.get("hook_timeout_secs")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(default_hook_timeout_secs),
+        wasted_bytes_warning_fraction: map.get(WASTE_WARNING_FRACTION_KEY)
+            .and_then(|s| s.parse().ok())
+            .and_then(|f| waste::validate_fraction(f).ok())
+            .unwrap_or_else(default_waste_warning_fraction),
+        auto_fallback_to_ytdlp: map.get(AUTO_FALLBACK_KEY)
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(true),
+        undo_expiry_secs: map.get("undo_expiry_secs")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(default_undo_expiry_secs),
+        undo_max_entries: map.get("undo_max_entries")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(default_undo_max_entries),
+        soft_delete_files: map.get("soft_delete_files")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(false),
+        refuse_without_space: map.get("refuse_without_space")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(false),
+        tracking_params: map.get("tracking_params")
+            .map(|s| s.lines().map(str::trim).filter(|l| !l.is_empty()).map(String::from).collect())
+            .unwrap_or_else(default_tracking_params),
+        add_to_recent_files: map.get("add_to_recent_files")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(true),
+        recent_files_excluded_extensions: map.get("recent_files_excluded_extensions")
+            .map(|s| s.lines().map(str::trim).filter(|l| !l.is_empty()).map(String::from).collect())
+            .unwrap_or_else(default_recent_files_excluded_extensions),
     }
 }
@@ -2,6 +2,16 @@ use tauri::State;
 use crate::state::app_state::AppState;
 use crate::network::sftp_client::{SftpFileInfo, SftpClient};
 use std::path::PathBuf;
+use uuid::Uuid;
+use crate::core::bandwidth::domain_of;
+use crate::core::category::Category;
+use crate::core::download_task::DownloadStatus;
+use crate::events::download_events;
+use crate::services::hook_service::{self, HookEvent, HookSubject};
+use crate::core::content_filter::{ContentFilter, SkippedFile};
+use crate::core::undo::RemoteRestart;
+use crate::network::remote_progress::{local_file_name, DirectoryDownloadFailure, DirectoryDownloadSummary};
+use crate::utils::error::DownloadError;
 
 #[tauri::command]
 pub async fn sftp_connect(
@@ -46,20 +56,27 @@ pub async fn sftp_list_files(
 
 #[tauri::command]
 pub async fn sftp_download_file(
-    _state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
     url: String,
     local_path: String,
     password: Option<String>,
     key_path: Option<String>,
     resume: Option<bool>,
+    transfer_id: Option<String>,
 ) -> Result<u64, String> {
     // Parse SFTP URL and create client
     let key_path_buf = key_path.map(PathBuf::from);
+    let local_path_buf = PathBuf::from(local_path);
+    let restart = RemoteRestart::SftpFile {
+        url: url.clone(),
+        local_path: local_path_buf.clone(),
+        password: password.clone(),
+        key_path: key_path_buf.clone(),
+    };
     let (client, remote_path) = SftpClient::from_url(&url, password, key_path_buf)
         .map_err(|e| format!("Failed to parse SFTP URL: {}", e))?;
     
-    let local_path_buf = PathBuf::from(local_path);
-    
     // Check if we should resume
     let resume_from = if resume.unwrap_or(false) && local_path_buf.exists() {
         tokio::fs::metadata(&local_path_buf)
@@ -70,10 +87,128 @@ pub async fn sftp_download_file(
         None
     };
     
-    // Download the file
-    client.download_file(&remote_path, &local_path_buf, resume_from)
+    // Progress goes out as download-progress events under `transfer_id`,
+    // which cancel_download also accepts
+    let id = match transfer_id {
+        Some(id) => Uuid::parse_str(&id).map_err(|e| e.to_string())?,
+        None => Uuid::new_v4(),
+    };
+    let control = state.begin_remote_transfer(id, restart);
+    state.metrics.begin(id, &url, resume_from.unwrap_or(0));
+    let source = id.to_string();
+    let category = Category::detect(&local_path_buf.to_string_lossy(), None);
+    state.bandwidth.begin(&source, Some(&category), &domain_of(&url), resume_from.unwrap_or(0));
+    let mut last = None;
+    let result = client
+        .download_file(&remote_path, &local_path_buf, resume_from, &control, |progress| {
+            last = Some(progress);
+            state.metrics.record_progress(id, progress.transferred);
+            state.bandwidth.record_total(&source, progress.transferred);
+            download_events::emit_progress(&app_handle, &progress.to_download_progress(id, DownloadStatus::Downloading));
+        })
+        .await;
+    state.end_remote_transfer(id);
+    state.bandwidth.end(&source);
+    state.metrics.finish(id, &url, &result);
+
+    download_events::emit_remote_finished(&app_handle, id, last, &result);
+    let bytes = result.as_ref().copied().unwrap_or_else(|_| last.map_or(0, |p| p.transferred));
+    let subject = HookSubject::remote(id, &url, &local_path_buf, bytes, Some(category));
+    hook_service::run_remote(&app_handle, HookEvent::for_result(&result), subject).await;
+    result.map_err(|e| format!("SFTP download failed: {}", e))
+}
+
+/// Download the files of the folder at `url` into `local_dir`, one at a
+/// time, leaving out those `filter` rejects. Subfolders are not entered.
+/// Progress goes out under `transfer_id`; cancelling it stops the rest.
+#[tauri::command]
+pub async fn sftp_download_directory(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    url: String,
+    local_dir: String,
+    password: Option<String>,
+    key_path: Option<String>,
+    filter: Option<ContentFilter>,
+    transfer_id: Option<String>,
+) -> Result<DirectoryDownloadSummary, String> {
+    let key_path_buf = key_path.map(PathBuf::from);
+    let restart = RemoteRestart::SftpFolder {
+        url: url.clone(),
+        local_dir: PathBuf::from(&local_dir),
+        password: password.clone(),
+        key_path: key_path_buf.clone(),
+        filter: filter.clone(),
+    };
+    let filter = filter.unwrap_or_default().compile()?;
+    let local_dir = PathBuf::from(local_dir);
+    state.engine.check_save_path(&local_dir).map_err(|e| e.to_string())?;
+    let (client, remote_dir) = SftpClient::from_url(&url, password, key_path_buf)
+        .map_err(|e| format!("Failed to parse SFTP URL: {}", e))?;
+    let files = client.list_directory(&remote_dir)
+        .await
+        .map_err(|e| format!("Failed to list directory: {}", e))?;
+
+    tokio::fs::create_dir_all(&local_dir)
         .await
-        .map_err(|e| format!("SFTP download failed: {}", e))
+        .map_err(|e| format!("Failed to create {}: {}", local_dir.display(), e))?;
+
+    let id = match transfer_id {
+        Some(id) => Uuid::parse_str(&id).map_err(|e| e.to_string())?,
+        None => Uuid::new_v4(),
+    };
+    let control = state.begin_remote_transfer(id, restart);
+    let mut summary = DirectoryDownloadSummary::default();
+    let mut last = None;
+    for file in files.into_iter().filter(|f| !f.is_dir) {
+        if let Err(reason) = filter.check(&file.file_name, file.file_size) {
+            summary.skipped.push(SkippedFile::new(file.file_name, file.file_size, &reason));
+            continue;
+        }
+        let Some(name) = local_file_name(&file.file_name) else {
+            tracing::warn!("Skipping SFTP entry with an unsafe name: {}", file.file_name);
+            continue;
+        };
+        if control.cancel.is_cancelled() {
+            break;
+        }
+
+        let remote_path = format!("{}/{}", remote_dir.trim_end_matches('/'), name);
+        let result = client
+            .download_file(&remote_path, &local_dir.join(name), None, &control, |progress| {
+                last = Some(progress);
+                download_events::emit_progress(&app_handle, &progress.to_download_progress(id, DownloadStatus::Downloading));
+            })
+            .await;
+        match result {
+            Ok(bytes) => {
+                summary.bytes += bytes;
+                summary.downloaded.push(file.file_name);
+            }
+            Err(DownloadError::Cancelled) => break,
+            Err(e) => summary.failed.push(DirectoryDownloadFailure { name: file.file_name, reason: e.to_string() }),
+        }
+    }
+    state.end_remote_transfer(id);
+
+    summary.cancelled = control.cancel.is_cancelled();
+    let result = if summary.cancelled { Err(DownloadError::Cancelled) } else { Ok(summary.bytes) };
+    download_events::emit_remote_finished(&app_handle, id, last, &result);
+    let event = match (summary.cancelled, summary.failed.is_empty()) {
+        (true, _) => None,
+        (false, true) => Some(HookEvent::DownloadComplete),
+        (false, false) => Some(HookEvent::DownloadFailed),
+    };
+    let subject = HookSubject::remote(id, &url, &local_dir, summary.bytes, None);
+    hook_service::run_remote(&app_handle, event, subject).await;
+    tracing::info!(
+        "SFTP folder {}: {} downloaded, {} skipped, {} failed",
+        url,
+        summary.downloaded.len(),
+        summary.skipped.len(),
+        summary.failed.len()
+    );
+    Ok(summary)
 }
 
 #[tauri::command]
@@ -1,9 +1,13 @@
 // src-tauri/src/commands/system_commands.rs
 
-use tauri::State;
+use tauri::{Emitter, State};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
+use crate::network::speed_test::{self, SpeedTestResult};
 use crate::state::app_state::AppState;
+use crate::utils::api_schema;
+use crate::utils::dir_size::DirectorySize;
 
 /// System information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +41,16 @@ pub async fn get_system_info(
     })
 }
 
+/// Write JSON Schemas for the command and event payloads to `dest_dir`,
+/// returning the files written
+#[tauri::command]
+pub async fn export_api_schema(dest_dir: String) -> Result<Vec<PathBuf>, String> {
+    let dest_dir = PathBuf::from(dest_dir);
+    let written = api_schema::export(&dest_dir).await.map_err(|e| e.to_string())?;
+    tracing::info!("Wrote {} API schemas to {}", written.len(), dest_dir.display());
+    Ok(written)
+}
+
 /// Check if there's enough disk space for a download
 #[tauri::command]
 pub async fn check_disk_space(
@@ -54,6 +68,85 @@ pub async fn check_disk_space(
     Ok(available_space >= required_with_buffer)
 }
 
+/// Compute total size and file count for each directory.
+/// Running totals for large folders are emitted as `directory-size-progress`.
+#[tauri::command]
+pub async fn get_directory_sizes(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    paths: Vec<PathBuf>,
+) -> Result<Vec<DirectorySize>, String> {
+    let cancel = state.dir_sizes.begin_scan();
+
+    let sizes = state.dir_sizes
+        .compute(paths, cancel, move |partial| {
+            let _ = app_handle.emit("directory-size-progress", &partial);
+        })
+        .await;
+
+    Ok(sizes)
+}
+
+/// Cancel an in-progress directory size scan
+#[tauri::command]
+pub async fn cancel_directory_sizes(
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state.dir_sizes.cancel_scan();
+    Ok(())
+}
+
+/// Measure download throughput with 1 and 4 connections. Live samples are
+/// emitted as `speed-test-sample`; the body is discarded, never saved.
+#[tauri::command]
+pub async fn run_speed_test(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    url: Option<String>,
+    duration_secs: u64,
+) -> Result<SpeedTestResult, String> {
+    let url = match url.filter(|u| !u.trim().is_empty()) {
+        Some(url) => url,
+        None => state.db.get_setting(speed_test::SPEED_TEST_URL_KEY)
+            .await
+            .ok()
+            .flatten()
+            .filter(|u| !u.trim().is_empty())
+            .unwrap_or_else(|| speed_test::DEFAULT_SPEED_TEST_URL.to_string()),
+    };
+
+    let cancel = state.speed_test.begin();
+    let result = state.speed_test
+        .run(
+            state.engine.http_client(),
+            &url,
+            std::time::Duration::from_secs(duration_secs),
+            cancel,
+            move |sample| {
+                let _ = app_handle.emit("speed-test-sample", &sample);
+            },
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Offered as the segment default during first-run setup
+    state.db.set_setting(
+        speed_test::RECOMMENDED_SEGMENTS_KEY,
+        &result.recommended_segments.to_string(),
+    ).await.map_err(|e| e.to_string())?;
+
+    Ok(result)
+}
+
+/// Cancel a running speed test
+#[tauri::command]
+pub async fn cancel_speed_test(
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state.speed_test.cancel();
+    Ok(())
+}
+
 /// Get available and total disk space for a path
 fn get_disk_space(path: &std::path::Path) -> Result<(u64, u64), String> {
     #[cfg(target_os = "windows")]
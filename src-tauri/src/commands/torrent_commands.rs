@@ -1,37 +1,213 @@
-use tauri::State;
+use tauri::{Emitter, State};
 use crate::state::app_state::AppState;
-use crate::network::torrent_client_librqbit::{TorrentStats, TorrentState, TorrentInfo};
-use crate::network::torrent_helpers::{TorrentPriority, BandwidthLimit, TorrentSchedule, TorrentMetadata};
+use crate::core::content_filter::ContentFilter;
+use crate::network::torrent_client_librqbit::{TorrentStats, TorrentState, TorrentInfo, TorrentSessionInfo};
+use crate::network::torrent_fetch;
+use crate::network::torrent_helpers::{content_path, TorrentPriority, BandwidthLimit, TorrentSchedule, TorrentMetadata, TorrentFilter};
 use crate::network::torrent_advanced::{
-    WebSeed, WebSeedType, EncryptionConfig, EncryptionMode, IpFilter, 
+    WebSeed, WebSeedStatus, WebSeedType, EncryptionConfig, EncryptionMode, IpFilter, 
     AdvancedTorrentOptions, TorrentAdvancedConfig
 };
-use std::path::PathBuf;
+use crate::database::torrent_queries;
+use crate::utils::error::AppError;
+use crate::utils::shell_open;
+use std::path::{Path, PathBuf};
+
+/// Resolve the add-paused flag, falling back to the global setting
+async fn resolve_add_paused(state: &AppState, add_paused: Option<bool>) -> bool {
+    match add_paused {
+        Some(paused) => paused,
+        None => state.db.get_setting("start_torrents_paused")
+            .await
+            .ok()
+            .flatten()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(false),
+    }
+}
+
+/// Assign a detected category to a newly added torrent that has none,
+/// based on its largest file (or its name before metadata is known)
+async fn auto_categorize_torrent(
+    app_handle: &tauri::AppHandle,
+    state: &AppState,
+    info_hash: &str,
+) {
+    let has_category = state.torrent_client.get_metadata(info_hash)
+        .await
+        .map(|meta| meta.category.is_some())
+        .unwrap_or(true);
+    if has_category {
+        return;
+    }
+
+    let info = match state.torrent_client.get_torrent_info(info_hash).await {
+        Ok(info) => info,
+        Err(_) => return,
+    };
+    let file_name = info.files
+        .iter()
+        .max_by_key(|f| f.size)
+        .map(|f| f.path.to_string_lossy().to_string())
+        .unwrap_or(info.name);
+
+    let category = match state.engine.detect_category(&file_name, None) {
+        Some(category) => category,
+        None => return,
+    };
+
+    if state.torrent_client.set_category(info_hash, Some(category.clone())).await.is_ok() {
+        let _ = app_handle.emit(
+            "torrent-categorized",
+            serde_json::json!({ "infoHash": info_hash, "category": category }),
+        );
+    }
+}
+
+/// Save a torrent's current info, state and metadata to the database
+async fn save_torrent_row(state: &AppState, info_hash: &str) -> Result<(), AppError> {
+    let (handle, metadata) = state.torrent_client.snapshot(info_hash).await?;
+    torrent_queries::save_torrent(
+        state.db.pool(),
+        &handle.info,
+        &handle.stats,
+        &metadata,
+        &handle.state,
+    ).await?;
+    torrent_queries::save_torrent_source(
+        state.db.pool(),
+        info_hash,
+        handle.metainfo.as_deref(),
+        &handle.trackers,
+    ).await
+}
+
+/// Persist a torrent that was just added paused, then start it.
+///
+/// A torrent whose row can't be written is removed from the session again,
+/// so nothing downloads without a row; one that fails to start is recorded
+/// in the Error state rather than left looking paused.
+async fn persist_and_start(
+    state: &AppState,
+    info_hash: &str,
+    add_paused: bool,
+) -> Result<(), String> {
+    if let Err(e) = save_torrent_row(state, info_hash).await {
+        tracing::error!("Failed to persist torrent {}: {}", info_hash, e);
+        if let Err(remove_err) = state.torrent_client.remove(info_hash, false).await {
+            tracing::error!("Failed to remove unpersisted torrent {}: {}", info_hash, remove_err);
+        }
+        return Err(e.to_string());
+    }
+
+    if add_paused {
+        return Ok(());
+    }
+
+    let new_state = match state.torrent_client.resume(info_hash).await {
+        Ok(state) => state,
+        Err(e) => TorrentState::Error(e.to_string()),
+    };
+    torrent_queries::update_torrent_state(state.db.pool(), info_hash, &new_state)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match new_state {
+        TorrentState::Error(e) => Err(e),
+        _ => Ok(()),
+    }
+}
 
 #[tauri::command]
 pub async fn add_torrent_file(
+    app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
     file_path: String,
+    add_paused: Option<bool>,
+    filter: Option<ContentFilter>,
 ) -> Result<String, String> {
     let path = PathBuf::from(file_path);
+    let add_paused = resolve_add_paused(&state, add_paused).await;
+    let filter = filter.map(|f| f.compile()).transpose()?;
     
-    state
+    let info_hash = state
         .torrent_client
-        .add_torrent_file(&path)
+        .add_torrent_file(&path, true, filter.as_ref())
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+
+    auto_categorize_torrent(&app_handle, &state, &info_hash).await;
+    persist_and_start(&state, &info_hash, add_paused).await?;
+    Ok(info_hash)
+}
+
+/// Add a torrent from a link to a .torrent file
+#[tauri::command]
+pub async fn add_torrent_url(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    url: String,
+    add_paused: Option<bool>,
+    category: Option<String>,
+    filter: Option<ContentFilter>,
+) -> Result<String, String> {
+    add_torrent_url_internal(&app_handle, &state, &url, add_paused, category, filter).await
+}
+
+/// Shared with deep links, which have no `State`
+pub async fn add_torrent_url_internal(
+    app_handle: &tauri::AppHandle,
+    state: &AppState,
+    url: &str,
+    add_paused: Option<bool>,
+    category: Option<String>,
+    filter: Option<ContentFilter>,
+) -> Result<String, String> {
+    let add_paused = resolve_add_paused(state, add_paused).await;
+    let filter = filter.map(|f| f.compile()).transpose()?;
+
+    let metainfo = torrent_fetch::fetch_torrent(&state.engine.http_client().client(), url)
+        .await
+        .map_err(|e| e.to_string())?;
+    torrent_fetch::parse_info_hash(&metainfo).map_err(|e| e.to_string())?;
+
+    let info_hash = state
+        .torrent_client
+        .add_torrent_bytes(metainfo, true, filter.as_ref())
+        .await
+        .map_err(|e| e.to_string())?;
+    tracing::info!("Added torrent {} from {}", info_hash, url);
+
+    match category {
+        Some(category) => {
+            if let Err(e) = state.torrent_client.set_category(&info_hash, Some(category)).await {
+                tracing::warn!("Failed to set category for torrent {}: {}", info_hash, e);
+            }
+        }
+        None => auto_categorize_torrent(app_handle, state, &info_hash).await,
+    }
+    persist_and_start(state, &info_hash, add_paused).await?;
+    Ok(info_hash)
 }
 
 #[tauri::command]
 pub async fn add_magnet_link(
+    app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
     magnet_link: String,
+    add_paused: Option<bool>,
 ) -> Result<String, String> {
-    state
+    let add_paused = resolve_add_paused(&state, add_paused).await;
+
+    let info_hash = state
         .torrent_client
-        .add_magnet(&magnet_link)
+        .add_magnet(&magnet_link, true)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+
+    auto_categorize_torrent(&app_handle, &state, &info_hash).await;
+    persist_and_start(&state, &info_hash, add_paused).await?;
+    Ok(info_hash)
 }
 
 #[tauri::command]
@@ -51,12 +227,10 @@ pub async fn get_torrent_state(
     state: State<'_, AppState>,
     info_hash: String,
 ) -> Result<TorrentState, String> {
-    // Stub implementation - torrent client disabled
     state
         .torrent_client
-        .get_stats(&info_hash)
+        .get_state(&info_hash)
         .await
-        .map(|_| TorrentState::Paused)
         .map_err(|e| e.to_string())
 }
 
@@ -65,9 +239,24 @@ pub async fn pause_torrent(
     state: State<'_, AppState>,
     info_hash: String,
 ) -> Result<(), String> {
+    if state.sleep_guard.forget_torrent(&info_hash) {
+        state.sleep_guard.save(&state.db).await;
+    }
     state
         .torrent_client
         .pause(&info_hash)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // A manual pause takes the torrent out of schedule control until the next transition
+    state.torrent_client.set_auto_paused(&info_hash, false)
+        .await
+        .map_err(|e| e.to_string())?;
+    torrent_queries::update_torrent_auto_paused(state.db.pool(), &info_hash, false)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    torrent_queries::update_torrent_state(state.db.pool(), &info_hash, &TorrentState::Paused)
         .await
         .map_err(|e| e.to_string())
 }
@@ -80,6 +269,18 @@ pub async fn resume_torrent(
     state
         .torrent_client
         .resume(&info_hash)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // A manual resume takes the torrent out of schedule control until the next transition
+    state.torrent_client.set_auto_paused(&info_hash, false)
+        .await
+        .map_err(|e| e.to_string())?;
+    torrent_queries::update_torrent_auto_paused(state.db.pool(), &info_hash, false)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    torrent_queries::update_torrent_state(state.db.pool(), &info_hash, &TorrentState::Downloading)
         .await
         .map_err(|e| e.to_string())
 }
@@ -90,9 +291,16 @@ pub async fn remove_torrent(
     info_hash: String,
     delete_files: Option<bool>,
 ) -> Result<(), String> {
+    if state.sleep_guard.forget_torrent(&info_hash) {
+        state.sleep_guard.save(&state.db).await;
+    }
     state
         .torrent_client
         .remove(&info_hash, delete_files.unwrap_or(false))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    torrent_queries::delete_torrent(state.db.pool(), &info_hash)
         .await
         .map_err(|e| e.to_string())
 }
@@ -100,12 +308,19 @@ pub async fn remove_torrent(
 #[tauri::command]
 pub async fn list_torrents(
     state: State<'_, AppState>,
+    filter: Option<TorrentFilter>,
 ) -> Result<Vec<TorrentInfo>, String> {
-    state
+    let filter = filter.unwrap_or_default();
+    Ok(state
         .torrent_client
-        .list_torrents()
+        .snapshot_all()
         .await
-        .map_err(|e| e.to_string())
+        .into_iter()
+        .filter(|(handle, metadata)| {
+            filter.matches(&handle.info.name, handle.state.as_str(), metadata)
+        })
+        .map(|(handle, _)| handle.info)
+        .collect())
 }
 
 #[tauri::command]
@@ -279,6 +494,23 @@ pub async fn get_torrent_metadata(
         .map_err(|e| e.to_string())
 }
 
+/// Turn super-seeding on or off. Fails with `UNSUPPORTED` when the torrent
+/// backend can't super-seed, so the UI can disable the toggle.
+#[tauri::command]
+pub async fn set_torrent_super_seeding(
+    state: State<'_, AppState>,
+    info_hash: String,
+    enabled: bool,
+) -> Result<(), String> {
+    state.torrent_client
+        .set_super_seeding(&info_hash, enabled)
+        .await
+        .map_err(|e| e.to_string())?;
+    torrent_queries::update_torrent_super_seeding(state.db.pool(), &info_hash, enabled)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 // Advanced features commands
 
 #[tauri::command]
@@ -319,7 +551,7 @@ pub async fn remove_web_seed(
 pub async fn get_web_seeds(
     state: State<'_, AppState>,
     info_hash: String,
-) -> Result<Vec<WebSeed>, String> {
+) -> Result<Vec<WebSeedStatus>, String> {
     state
         .torrent_client
         .get_web_seeds(&info_hash)
@@ -327,6 +559,17 @@ pub async fn get_web_seeds(
         .map_err(|e| e.to_string())
 }
 
+/// Clear a web seed's failures so it is tried again right away
+#[tauri::command]
+pub async fn reset_web_seed_health(
+    state: State<'_, AppState>,
+    info_hash: String,
+    url: String,
+) -> Result<(), String> {
+    state.torrent_client.reset_web_seed_health(&info_hash, &url);
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn set_encryption_config(
     state: State<'_, AppState>,
@@ -463,3 +706,191 @@ pub async fn set_max_connections(
         .await
         .map_err(|e| e.to_string())
 }
+
+#[tauri::command]
+pub async fn export_torrent_file(
+    state: State<'_, AppState>,
+    info_hash: String,
+    dest_path: String,
+) -> Result<(), String> {
+    state
+        .torrent_client
+        .export_torrent_file(&info_hash, &PathBuf::from(dest_path))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_magnet_uri(
+    state: State<'_, AppState>,
+    info_hash: String,
+) -> Result<String, String> {
+    state
+        .torrent_client
+        .get_magnet_uri(&info_hash)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// DHT, listen port and aggregate rates of the torrent session
+#[tauri::command]
+pub async fn get_torrent_session_info(
+    state: State<'_, AppState>,
+) -> Result<TorrentSessionInfo, String> {
+    Ok(state.torrent_client.session_info().await)
+}
+
+/// Torrent settings applied to the running session
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TorrentSettingsPatch {
+    /// New folder for torrents added from now on
+    pub download_dir: Option<String>,
+    /// Also move existing torrents' data into the new folder
+    #[serde(default)]
+    pub move_existing: bool,
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RelocationStatus {
+    Moving,
+    Moved,
+    Failed,
+}
+
+/// Progress of one torrent being moved, emitted as `torrent-relocation`
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TorrentRelocation {
+    pub info_hash: String,
+    pub name: String,
+    pub from: PathBuf,
+    pub to: PathBuf,
+    pub status: RelocationStatus,
+    pub error: Option<String>,
+}
+
+/// Check `dir` can take new torrents, creating it if needed
+async fn check_writable_dir(dir: &std::path::Path) -> Result<(), String> {
+    if !dir.is_absolute() {
+        return Err(format!("{} is not an absolute path", dir.display()));
+    }
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+    let probe = dir.join(format!(".afk-dunld-write-test-{}", uuid::Uuid::new_v4()));
+    tokio::fs::write(&probe, b"")
+        .await
+        .map_err(|e| format!("{} is not writable: {}", dir.display(), e))?;
+    let _ = tokio::fs::remove_file(&probe).await;
+    Ok(())
+}
+
+/// Change the torrent download folder without restarting the session.
+/// New torrents use it straight away; with `move_existing`, torrents
+/// elsewhere are moved one by one, each reported as `torrent-relocation`.
+/// Nothing changes unless the folder is allowed, writable and, when
+/// moving, has room for every torrent.
+#[tauri::command]
+pub async fn apply_torrent_settings(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    patch: TorrentSettingsPatch,
+) -> Result<Vec<TorrentRelocation>, String> {
+    let Some(dir) = patch.download_dir.filter(|d| !d.trim().is_empty()) else {
+        return Ok(Vec::new());
+    };
+    let dir = PathBuf::from(dir.trim());
+    state.engine.check_save_path(&dir).map_err(|e| e.to_string())?;
+    check_writable_dir(&dir).await?;
+
+    let to_move: Vec<_> = if patch.move_existing {
+        state.torrent_client.snapshot_all().await
+            .into_iter()
+            .filter(|(_, meta)| meta.save_path != dir)
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let required: u64 = to_move.iter().map(|(handle, _)| handle.info.total_size).sum();
+    if required > 0 {
+        let available = crate::utils::file_utils::get_available_space(&dir)
+            .await
+            .map_err(|e| e.to_string())?;
+        if available < required {
+            return Err(format!(
+                "{} has {} bytes free but the torrents being moved need {}",
+                dir.display(), available, required
+            ));
+        }
+    }
+
+    state.db.set_setting("torrent_download_dir", &dir.to_string_lossy())
+        .await
+        .map_err(|e| e.to_string())?;
+    state.torrent_client.set_download_dir(dir.clone());
+
+    let mut results = Vec::with_capacity(to_move.len());
+    for (handle, meta) in to_move {
+        let info_hash = handle.info.info_hash;
+        let mut relocation = TorrentRelocation {
+            info_hash: info_hash.clone(),
+            name: handle.info.name,
+            from: meta.save_path,
+            to: dir.clone(),
+            status: RelocationStatus::Moving,
+            error: None,
+        };
+        let _ = app_handle.emit("torrent-relocation", &relocation);
+
+        let moved = match state.torrent_client.set_location(&info_hash, &dir).await {
+            Ok(()) => save_torrent_row(&state, &info_hash).await,
+            Err(e) => Err(e),
+        };
+        match moved {
+            Ok(()) => relocation.status = RelocationStatus::Moved,
+            Err(e) => {
+                tracing::warn!("Failed to move torrent {}: {}", info_hash, e);
+                relocation.status = RelocationStatus::Failed;
+                relocation.error = Some(e.to_string());
+            }
+        }
+        let _ = app_handle.emit("torrent-relocation", &relocation);
+        results.push(relocation);
+    }
+    Ok(results)
+}
+
+/// Open a finished torrent's content, or show it in the file manager when
+/// `reveal` is set. Opens file `file_index`, or the largest file when no
+/// index is given.
+#[tauri::command]
+pub async fn open_torrent_content(
+    state: State<'_, AppState>,
+    info_hash: String,
+    file_index: Option<usize>,
+    reveal: Option<bool>,
+) -> Result<(), String> {
+    let (torrent, files, ..) = torrent_queries::load_torrent(state.db.pool(), &info_hash)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Torrent not found")?;
+
+    let files: Vec<(PathBuf, u64)> = files
+        .into_iter()
+        .map(|f| (PathBuf::from(f.path), f.size.max(0) as u64))
+        .collect();
+    let path = content_path(Path::new(&torrent.save_path), &torrent.name, &files, file_index)
+        .ok_or_else(|| match file_index {
+            Some(index) => format!("Torrent has no file {}", index),
+            None => "Torrent has no files yet".to_string(),
+        })?;
+
+    if reveal.unwrap_or(false) {
+        shell_open::reveal(&path).await
+    } else {
+        shell_open::open(&path).await
+    }
+    .map_err(|e| e.to_string())
+}
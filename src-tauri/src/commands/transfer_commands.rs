@@ -0,0 +1,44 @@
+// src-tauri/src/commands/transfer_commands.rs
+
+use tauri::State;
+
+use crate::core::transfer::{TransferFilter, TransferKind, TransferPage, TransferSummary};
+use crate::state::app_state::AppState;
+
+/// Downloads and torrents in one filtered, sorted and paginated list
+/// for the "All transfers" screen
+#[tauri::command]
+pub async fn get_unified_transfers(
+    state: State<'_, AppState>,
+    filter: Option<TransferFilter>,
+) -> Result<TransferPage, String> {
+    let filter = filter.unwrap_or_default();
+    let transfers = collect_transfers(&state, filter.kind).await?;
+    Ok(filter.apply(transfers))
+}
+
+/// Every download and torrent, or only those of `kind`
+pub(crate) async fn collect_transfers(
+    state: &AppState,
+    kind: Option<TransferKind>,
+) -> Result<Vec<TransferSummary>, String> {
+    let mut transfers = Vec::new();
+
+    if kind != Some(TransferKind::Torrent) {
+        let downloads = state.db.get_all_downloads()
+            .await
+            .map_err(|e| e.to_string())?;
+        transfers.extend(downloads.iter().map(TransferSummary::from_download));
+    }
+
+    if kind != Some(TransferKind::Download) {
+        let torrents = state.torrent_client.snapshot_all().await;
+        transfers.extend(
+            torrents
+                .iter()
+                .map(|(handle, metadata)| TransferSummary::from_torrent(handle, metadata)),
+        );
+    }
+
+    Ok(transfers)
+}
@@ -0,0 +1,125 @@
+use crate::commands::{ftp_commands, sftp_commands};
+use crate::core::scheduler::ScheduledTask;
+use crate::core::undo::{self, RemoteRestart, UndoAction, UndoableAction};
+use crate::state::app_state::AppState;
+use crate::utils::error::DownloadError;
+use std::path::Path;
+use tauri::{AppHandle, Emitter, Manager, State};
+use uuid::Uuid;
+
+/// Actions that can still be undone this session, newest first
+#[tauri::command]
+pub async fn get_undoable_actions(state: State<'_, AppState>) -> Result<Vec<UndoableAction>, String> {
+    Ok(state.undo.list())
+}
+
+/// Undo the newest action that hasn't expired. An action that can't be
+/// undone, such as a file gone from the trash, is dropped with the error.
+#[tauri::command]
+pub async fn undo_last_action(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<UndoableAction, String> {
+    let entry = state.undo.pop().ok_or("Nothing to undo")?;
+    let summary = entry.summary(state.undo.settings().expiry, std::time::Instant::now());
+
+    apply(&app_handle, &state, entry.action).await.map_err(|e| e.to_string())?;
+
+    tracing::info!("Undid: {}", summary.description);
+    let _ = app_handle.emit("action-undone", &summary);
+    Ok(summary)
+}
+
+async fn apply(app_handle: &AppHandle, state: &AppState, action: UndoAction) -> Result<(), DownloadError> {
+    match action {
+        UndoAction::RemoveDownload { task, trashed_file, schedules } => {
+            if let Some(path) = &trashed_file {
+                undo::restore_from_trash(path).await?;
+            }
+            let mut task = *task;
+            task.status = undo::restored_status(task.status);
+            task.speed = 0.0;
+            task.eta = None;
+            state.db.insert_download(&task).await?;
+            reschedule(state, schedules).await?;
+        }
+        UndoAction::Cancel { previous, schedules } => {
+            for (id, status) in previous {
+                state.db.update_status(id, undo::restored_status(status)).await?;
+            }
+            reschedule(state, schedules).await?;
+        }
+        UndoAction::CancelRemote { id, restart } => {
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = restart_remote(&app_handle, id, restart).await {
+                    tracing::warn!("Restarted transfer {} failed: {}", id, e);
+                }
+            });
+        }
+        UndoAction::DeleteCategory { category, download_ids } => {
+            state.db.create_category(&category).await?;
+            for id in &download_ids {
+                state.db.assign_download_category(id, &category.id).await?;
+            }
+            state.refresh_category_dirs().await;
+        }
+    }
+    Ok(())
+}
+
+fn text(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+async fn reschedule(state: &AppState, schedules: Vec<ScheduledTask>) -> Result<(), DownloadError> {
+    for schedule in schedules {
+        state.scheduler.add_task(schedule).await.map_err(|e| DownloadError::Unknown(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Run a cancelled FTP or SFTP transfer again under its old id, so its
+/// progress shows where it did before
+async fn restart_remote(app_handle: &AppHandle, id: Uuid, restart: RemoteRestart) -> Result<(), String> {
+    let state = app_handle.state::<AppState>();
+    let transfer_id = Some(id.to_string());
+    match restart {
+        RemoteRestart::FtpFile { url, local_path } => {
+            ftp_commands::ftp_download_file(app_handle.clone(), state, url, Some(text(&local_path)), Some(true), transfer_id)
+                .await
+                .map(drop)
+        }
+        RemoteRestart::SftpFile { url, local_path, password, key_path } => sftp_commands::sftp_download_file(
+            app_handle.clone(),
+            state,
+            url,
+            text(&local_path),
+            password,
+            key_path.as_deref().map(text),
+            Some(true),
+            transfer_id,
+        )
+        .await
+        .map(drop),
+        RemoteRestart::FtpFolder { url, local_dir, filter } => {
+            ftp_commands::ftp_download_directory(app_handle.clone(), state, url, text(&local_dir), filter, transfer_id)
+                .await
+                .map(drop)
+        }
+        RemoteRestart::SftpFolder { url, local_dir, password, key_path, filter } => {
+            sftp_commands::sftp_download_directory(
+                app_handle.clone(),
+                state,
+                url,
+                text(&local_dir),
+                password,
+                key_path.as_deref().map(text),
+                filter,
+                transfer_id,
+            )
+            .await
+            .map(drop)
+        }
+    }
+}
@@ -0,0 +1,128 @@
+// src-tauri/src/commands/update_commands.rs
+
+use tauri::State;
+use uuid::Uuid;
+
+use crate::commands::download_commands;
+use crate::core::download_engine::AddDownloadRequest;
+use crate::core::download_task::{DownloadStatus, DownloadTask};
+use crate::core::size_guard::DownloadSource;
+use crate::network::http_client::ProbeMode;
+use crate::services::update_service::AppUpdate;
+use crate::state::app_state::AppState;
+
+/// Check the releases endpoint for a newer version of the app
+#[tauri::command]
+pub async fn check_app_update(state: State<'_, AppState>) -> Result<AppUpdate, String> {
+    state.updates.check().await
+}
+
+/// Download the installer for the latest release through the download
+/// engine, verified against the release's SHA-256 checksum. Progress is
+/// reported with the usual download events.
+#[tauri::command]
+pub async fn download_app_update(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<DownloadTask, String> {
+    let update = match state.updates.latest() {
+        Some(update) => update,
+        None => state.updates.check().await?,
+    };
+    if !update.update_available {
+        return Err(format!("Already on the latest version ({})", update.current_version));
+    }
+    let asset = update.asset
+        .ok_or("The latest release has no installer for this platform")?;
+    let sha256 = asset.sha256
+        .ok_or("The latest release publishes no checksum for its installer")?;
+
+    let updates_dir = state.updates.updates_dir();
+    tokio::fs::create_dir_all(updates_dir).await.map_err(|e| e.to_string())?;
+
+    let request = AddDownloadRequest {
+        url: asset.url,
+        save_path: Some(updates_dir.to_string_lossy().to_string()),
+        file_name: Some(asset.name),
+        segments: None,
+        max_retries: None,
+        expected_checksum: Some(sha256),
+        checksum_type: Some("sha256".to_string()),
+        category: None,
+        priority: None,
+        youtube_format: None,
+        youtube_quality: None,
+        youtube_video_format: None,
+        youtube_audio_format: None,
+        notes: None,
+        source_page_url: update.release_url,
+        source_page_title: Some(format!("AFK-Dunld {}", update.latest_version)),
+        retry: None,
+        source: DownloadSource::Manual,
+        probe: ProbeMode::Required,
+        temp_dir: None,
+        url_refresh: None,
+        post_action: None,
+        simulate: false,
+        playlist_mode: None,
+        existing_location: false,
+    };
+    download_commands::add_download_task(app_handle, state, request).await
+}
+
+/// Start a downloaded installer, then shut the app down so it can replace
+/// it. Only completed, checksum-verified downloads in the updates folder
+/// are accepted.
+#[tauri::command]
+pub async fn launch_installer(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    download_id: String,
+) -> Result<(), String> {
+    let id = Uuid::parse_str(&download_id).map_err(|e| e.to_string())?;
+    let task = state.db.get_download(id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Download not found")?;
+
+    if task.status != DownloadStatus::Completed || task.expected_checksum.is_none() {
+        return Err("The installer hasn't finished downloading and been verified".to_string());
+    }
+    let updates_dir = tokio::fs::canonicalize(state.updates.updates_dir())
+        .await
+        .map_err(|e| e.to_string())?;
+    let installer = tokio::fs::canonicalize(&task.save_path)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !installer.starts_with(&updates_dir) {
+        return Err("Only installers downloaded by the update check can be launched".to_string());
+    }
+
+    start_installer(&installer)?;
+    tracing::info!("Launched installer {}, shutting down", installer.display());
+
+    state.pause_active_for_shutdown().await;
+    app_handle.exit(0);
+    Ok(())
+}
+
+/// AppImages are run directly; other formats go to the system handler
+fn start_installer(path: &std::path::Path) -> Result<(), String> {
+    let is_appimage = path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("appimage"));
+
+    if is_appimage {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+                .map_err(|e| e.to_string())?;
+        }
+        std::process::Command::new(path)
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| format!("Failed to start installer: {}", e))
+    } else {
+        opener::open(path).map_err(|e| format!("Failed to start installer: {}", e))
+    }
+}
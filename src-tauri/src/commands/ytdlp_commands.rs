@@ -1,4 +1,6 @@
 use crate::state::app_state::AppState;
+use crate::utils::binaries::{is_executable, Tool, ToolStatus};
+use std::path::{Path, PathBuf};
 use tauri::State;
 
 /// Update yt-dlp to the latest version
@@ -27,3 +29,31 @@ pub async fn get_bundled_ytdlp_version(state: State<'_, AppState>) -> Result<Str
         .get_bundled_version()
         .ok_or_else(|| "Bundled version information not available".to_string())
 }
+
+/// Get the resolved path, version and origin of every external tool
+#[tauri::command]
+pub async fn get_tool_status(state: State<'_, AppState>) -> Result<Vec<ToolStatus>, String> {
+    Ok(state.binaries.get_tool_status().await)
+}
+
+/// Set (or clear, with an empty path) the explicit path used for a tool
+#[tauri::command]
+pub async fn set_tool_path(
+    state: State<'_, AppState>,
+    tool: Tool,
+    path: Option<String>,
+) -> Result<ToolStatus, String> {
+    let path = path.filter(|p| !p.trim().is_empty());
+    if let Some(ref path) = path {
+        if !is_executable(Path::new(path)) {
+            return Err(format!("{} is not an executable file", path));
+        }
+    }
+
+    state.db.set_setting(tool.setting_key(), path.as_deref().unwrap_or(""))
+        .await
+        .map_err(|e| e.to_string())?;
+    state.binaries.set_override(tool, path.map(PathBuf::from));
+
+    Ok(state.binaries.status(tool).await)
+}
@@ -0,0 +1,271 @@
+// src-tauri/src/core/bandwidth.rs
+// What is using the bandwidth right now, by category and by domain. Each
+// progress update adds to atomic counters under a read lock; a once a
+// second tick moves those into ten-second windows. A key that has moved
+// nothing for a whole window is dropped.
+
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often `tick` should run
+pub const BANDWIDTH_TICK: Duration = Duration::from_secs(1);
+
+/// Ticks a rate is averaged over
+pub const BANDWIDTH_WINDOW_TICKS: usize = 10;
+
+/// Category of downloads that have none
+pub const UNCATEGORIZED: &str = "Uncategorized";
+
+/// Domain label for torrent traffic, which has peers rather than a host
+pub const TORRENT_DOMAIN: &str = "BitTorrent";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    Category(String),
+    Domain(String),
+}
+
+#[derive(Default)]
+struct Counter {
+    /// Bytes since the last tick
+    pending: AtomicU64,
+    /// Bytes per tick, newest last
+    window: parking_lot::Mutex<VecDeque<u64>>,
+}
+
+impl Counter {
+    fn window_bytes(&self) -> u64 {
+        self.window.lock().iter().sum()
+    }
+}
+
+/// A transfer feeding the tracker with running totals
+struct Source {
+    category: String,
+    domain: String,
+    last: AtomicU64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BandwidthEntry {
+    pub name: String,
+    pub bytes_per_sec: f64,
+    /// Bytes moved in the window
+    pub bytes: u64,
+}
+
+/// What `get_live_bandwidth_breakdown` returns, biggest consumers first
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BandwidthBreakdown {
+    pub window_secs: u64,
+    pub total_bytes_per_sec: f64,
+    pub categories: Vec<BandwidthEntry>,
+    pub domains: Vec<BandwidthEntry>,
+}
+
+#[derive(Default)]
+pub struct BandwidthTracker {
+    counters: parking_lot::RwLock<HashMap<Key, Counter>>,
+    /// Running transfers by download id or info hash
+    sources: parking_lot::RwLock<HashMap<String, Arc<Source>>>,
+}
+
+/// Domain label for a URL: its host, or "unknown"
+pub fn domain_of(url: &str) -> String {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|host| host.trim_start_matches("www.").to_lowercase()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+impl BandwidthTracker {
+    /// A transfer starts with `total` bytes already moved
+    pub fn begin(&self, source: &str, category: Option<&str>, domain: &str, total: u64) {
+        let source_entry = Source {
+            category: category.filter(|c| !c.is_empty()).unwrap_or(UNCATEGORIZED).to_string(),
+            domain: domain.to_string(),
+            last: AtomicU64::new(total),
+        };
+        self.sources.write().insert(source.to_string(), Arc::new(source_entry));
+    }
+
+    /// Bytes from here on count toward `category`
+    pub fn recategorize(&self, source: &str, category: &str) {
+        let mut sources = self.sources.write();
+        if let Some(entry) = sources.get_mut(source) {
+            let last = entry.last.load(Ordering::Relaxed);
+            *entry = Arc::new(Source {
+                category: category.to_string(),
+                domain: entry.domain.clone(),
+                last: AtomicU64::new(last),
+            });
+        }
+    }
+
+    /// A running total from `source`; only what is new since the last one
+    /// counts. Unknown sources are ignored.
+    pub fn record_total(&self, source: &str, total: u64) {
+        let Some(entry) = self.sources.read().get(source).cloned() else {
+            return;
+        };
+        let previous = entry.last.fetch_max(total, Ordering::Relaxed);
+        let bytes = total.saturating_sub(previous);
+        if bytes > 0 {
+            self.add(Key::Category(entry.category.clone()), bytes);
+            self.add(Key::Domain(entry.domain.clone()), bytes);
+        }
+    }
+
+    /// Register `source` on first sight and record its total after that.
+    /// For transfers polled rather than reporting, like torrents.
+    pub fn observe(&self, source: &str, category: Option<&str>, domain: &str, total: u64) {
+        if self.sources.read().contains_key(source) {
+            self.record_total(source, total);
+        } else {
+            self.begin(source, category, domain, total);
+        }
+    }
+
+    pub fn end(&self, source: &str) {
+        self.sources.write().remove(source);
+    }
+
+    /// Ids of the transfers being tracked
+    pub fn sources(&self) -> Vec<String> {
+        self.sources.read().keys().cloned().collect()
+    }
+
+    fn add(&self, key: Key, bytes: u64) {
+        if let Some(counter) = self.counters.read().get(&key) {
+            counter.pending.fetch_add(bytes, Ordering::Relaxed);
+            return;
+        }
+        self.counters.write().entry(key).or_default().pending.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Close the current second; keys idle for a whole window expire
+    pub fn tick(&self) {
+        let mut counters = self.counters.write();
+        counters.retain(|_, counter| {
+            let bytes = counter.pending.swap(0, Ordering::Relaxed);
+            let mut window = counter.window.lock();
+            window.push_back(bytes);
+            while window.len() > BANDWIDTH_WINDOW_TICKS {
+                window.pop_front();
+            }
+            window.len() < BANDWIDTH_WINDOW_TICKS || window.iter().any(|b| *b > 0)
+        });
+    }
+
+    /// The `limit` biggest categories and domains over the window
+    pub fn breakdown(&self, limit: usize) -> BandwidthBreakdown {
+        let window_secs = BANDWIDTH_TICK.as_secs() * BANDWIDTH_WINDOW_TICKS as u64;
+        let rate = |bytes: u64| bytes as f64 / window_secs as f64;
+
+        let mut categories = Vec::new();
+        let mut domains = Vec::new();
+        for (key, counter) in self.counters.read().iter() {
+            let bytes = counter.window_bytes();
+            if bytes == 0 {
+                continue;
+            }
+            let (list, name) = match key {
+                Key::Category(name) => (&mut categories, name),
+                Key::Domain(name) => (&mut domains, name),
+            };
+            list.push(BandwidthEntry { name: name.clone(), bytes_per_sec: rate(bytes), bytes });
+        }
+
+        // Every byte is counted once per category and once per domain
+        let total: u64 = categories.iter().map(|e: &BandwidthEntry| e.bytes).sum();
+        for list in [&mut categories, &mut domains] {
+            list.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+            list.truncate(limit);
+        }
+        BandwidthBreakdown {
+            window_secs,
+            total_bytes_per_sec: rate(total),
+            categories,
+            domains,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breakdown_sums_match_what_was_fed() {
+        let tracker = BandwidthTracker::default();
+        tracker.begin("a", Some("Video"), &domain_of("https://www.cdn.example/a.mp4"), 1_000);
+        tracker.begin("b", None, &domain_of("ftp://files.example/b.iso"), 0);
+        tracker.begin("c", Some("Video"), &domain_of("https://files.example/c.mkv"), 0);
+
+        // Synthetic progress: running totals, including a repeat and a
+        // total that went back after a segment restart
+        let mut fed = 0;
+        for (source, total, new) in [
+            ("a", 1_500, 500),
+            ("b", 2_000, 2_000),
+            ("a", 1_500, 0),
+            ("c", 300, 300),
+            ("c", 200, 0),
+            ("a", 4_000, 2_500),
+            ("unknown", 9_999, 0),
+        ] {
+            tracker.record_total(source, total);
+            fed += new;
+        }
+        tracker.tick();
+        tracker.observe("t1", None, TORRENT_DOMAIN, 50_000);
+        tracker.observe("t1", None, TORRENT_DOMAIN, 51_000);
+        fed += 1_000;
+        tracker.tick();
+
+        let breakdown = tracker.breakdown(10);
+        let category_bytes: u64 = breakdown.categories.iter().map(|e| e.bytes).sum();
+        let domain_bytes: u64 = breakdown.domains.iter().map(|e| e.bytes).sum();
+        assert_eq!(category_bytes, fed);
+        assert_eq!(domain_bytes, fed);
+        assert_eq!(breakdown.total_bytes_per_sec, fed as f64 / 10.0);
+
+        assert_eq!(breakdown.categories[0].name, "Video");
+        assert_eq!(breakdown.categories[0].bytes, 3_300);
+        assert_eq!(breakdown.categories[1].name, UNCATEGORIZED);
+        let domain = |name: &str| breakdown.domains.iter().find(|e| e.name == name).map(|e| e.bytes);
+        assert_eq!(domain("cdn.example"), Some(3_000));
+        assert_eq!(domain("files.example"), Some(2_300));
+        assert_eq!(domain(TORRENT_DOMAIN), Some(1_000));
+
+        assert_eq!(tracker.breakdown(1).domains.len(), 1);
+    }
+
+    #[test]
+    fn test_idle_keys_expire() {
+        let tracker = BandwidthTracker::default();
+        tracker.begin("a", Some("Music"), "music.example", 0);
+        tracker.record_total("a", 100);
+        tracker.tick();
+        tracker.recategorize("a", "Podcasts");
+        tracker.record_total("a", 150);
+        tracker.tick();
+        assert_eq!(tracker.breakdown(10).categories.len(), 2);
+
+        // Music moved nothing after its first second and drops out once
+        // that second leaves the window; Podcasts follows a tick later
+        for _ in 0..BANDWIDTH_WINDOW_TICKS - 1 {
+            tracker.tick();
+        }
+        let names: Vec<_> = tracker.breakdown(10).categories.into_iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["Podcasts"]);
+        tracker.tick();
+        assert!(tracker.counters.read().get(&Key::Category("Podcasts".into())).is_none());
+        assert_eq!(tracker.breakdown(10), BandwidthBreakdown { window_secs: 10, ..Default::default() });
+    }
+}
@@ -3,6 +3,8 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+use crate::utils::permissions::FilePermissions;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Category {
     pub id: String,
@@ -12,6 +14,18 @@ pub struct Category {
     pub save_path: Option<PathBuf>,
     pub created_at: i64,
     pub updated_at: i64,
+    /// Overrides the global completed-file mode and group
+    #[serde(default)]
+    pub file_permissions: FilePermissions,
+    /// Segments used when an add request doesn't choose
+    #[serde(default)]
+    pub default_segments: Option<u8>,
+    /// Retry attempts used when an add request doesn't choose
+    #[serde(default)]
+    pub default_max_retries: Option<u32>,
+    /// Downloads in this category are expected to come with a checksum
+    #[serde(default)]
+    pub require_checksum: bool,
 }
 
 impl Category {
@@ -25,6 +39,10 @@ impl Category {
             save_path,
             created_at: now,
             updated_at: now,
+            file_permissions: FilePermissions::default(),
+            default_segments: None,
+            default_max_retries: None,
+            require_checksum: false,
         }
     }
 
@@ -38,6 +56,19 @@ impl Category {
             save_path: None,
             created_at: 0,
             updated_at: 0,
+            file_permissions: FilePermissions::default(),
+            default_segments: None,
+            default_max_retries: None,
+            require_checksum: false,
+        }
+    }
+
+    /// The add-time defaults this category carries
+    pub fn download_defaults(&self) -> CategoryDefaults {
+        CategoryDefaults {
+            segments: self.default_segments,
+            max_retries: self.default_max_retries,
+            require_checksum: self.require_checksum,
         }
     }
 
@@ -84,6 +115,49 @@ impl Category {
             "default".to_string()
         }
     }
+
+    /// Detect category from a file name, falling back to the Content-Type
+    /// when the extension is missing or unknown
+    pub fn detect(file_name: &str, content_type: Option<&str>) -> String {
+        let by_extension = std::path::Path::new(file_name)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(Self::detect_from_extension)
+            .unwrap_or_else(|| "default".to_string());
+
+        if by_extension != "default" {
+            return by_extension;
+        }
+
+        content_type
+            .map(Self::detect_from_mime)
+            .unwrap_or(by_extension)
+    }
+}
+
+/// Values a category fills in for add requests that leave them unset
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CategoryDefaults {
+    pub segments: Option<u8>,
+    pub max_retries: Option<u32>,
+    pub require_checksum: bool,
+}
+
+impl CategoryDefaults {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A category folder that no longer exists, sent with
+/// `category-folder-missing`. Downloads go to `fallback` until it's back.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingCategoryFolder {
+    pub category_id: String,
+    pub name: String,
+    pub path: PathBuf,
+    pub fallback: PathBuf,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -117,4 +191,25 @@ mod tests {
         assert_eq!(Category::detect_from_mime("application/pdf"), "documents");
         assert_eq!(Category::detect_from_mime("application/zip"), "compressed");
     }
+
+    #[test]
+    fn test_detect_prefers_extension_then_mime() {
+        assert_eq!(Category::detect("movie.mkv", Some("application/octet-stream")), "videos");
+        assert_eq!(Category::detect("download", Some("audio/mpeg")), "music");
+        assert_eq!(Category::detect("file.bin", Some("application/pdf")), "documents");
+        assert_eq!(Category::detect("file.bin", None), "default");
+    }
+
+    #[test]
+    fn test_download_defaults() {
+        let mut category = Category::new("Software".to_string(), None, None, None);
+        assert!(category.download_defaults().is_empty());
+
+        category.default_segments = Some(2);
+        category.require_checksum = true;
+        let defaults = category.download_defaults();
+        assert_eq!(defaults.segments, Some(2));
+        assert_eq!(defaults.max_retries, None);
+        assert!(defaults.require_checksum && !defaults.is_empty());
+    }
 }
@@ -6,7 +6,7 @@ use tokio::io::AsyncReadExt;
 use std::path::Path;
 use crate::utils::error::DownloadError;
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 pub enum ChecksumAlgorithm {
     Md5,
     Sha256,
@@ -43,6 +43,19 @@ impl ChecksumVerifier {
         file_path: &Path,
         algorithm: &ChecksumAlgorithm,
     ) -> Result<String, DownloadError> {
+        Self::calculate_with_progress(file_path, algorithm, |_, _| {}).await
+    }
+
+    /// Calculate checksum of a file, reporting `(bytes_hashed, total_bytes)`
+    /// after every buffer read
+    pub async fn calculate_with_progress<F>(
+        file_path: &Path,
+        algorithm: &ChecksumAlgorithm,
+        mut on_progress: F,
+    ) -> Result<String, DownloadError>
+    where
+        F: FnMut(u64, u64),
+    {
         tracing::info!(
             "Calculating {:?} checksum for: {}",
             algorithm,
@@ -55,15 +68,20 @@ impl ChecksumVerifier {
                 format!("Cannot open file for checksum: {}", e)
             ))?;
 
+        let total = file.metadata()
+            .await
+            .map(|m| m.len())
+            .unwrap_or(0);
+
         let checksum = match algorithm {
             ChecksumAlgorithm::Md5 => {
-                Self::calculate_md5(&mut file).await?
+                Self::calculate_md5(&mut file, total, &mut on_progress).await?
             }
             ChecksumAlgorithm::Sha256 => {
-                Self::calculate_sha256(&mut file).await?
+                Self::calculate_sha256(&mut file, total, &mut on_progress).await?
             }
             ChecksumAlgorithm::Crc32 => {
-                Self::calculate_crc32(&mut file).await?
+                Self::calculate_crc32(&mut file, total, &mut on_progress).await?
             }
         };
 
@@ -77,7 +95,20 @@ impl ChecksumVerifier {
         expected: &str,
         algorithm: &ChecksumAlgorithm,
     ) -> Result<bool, DownloadError> {
-        let actual = Self::calculate(file_path, algorithm).await?;
+        Self::verify_with_progress(file_path, expected, algorithm, |_, _| {}).await
+    }
+
+    /// Verify file checksum, reporting hashing progress as it goes
+    pub async fn verify_with_progress<F>(
+        file_path: &Path,
+        expected: &str,
+        algorithm: &ChecksumAlgorithm,
+        on_progress: F,
+    ) -> Result<bool, DownloadError>
+    where
+        F: FnMut(u64, u64),
+    {
+        let actual = Self::calculate_with_progress(file_path, algorithm, on_progress).await?;
         let matches = actual.eq_ignore_ascii_case(expected);
 
         if !matches {
@@ -91,11 +122,14 @@ impl ChecksumVerifier {
         Ok(matches)
     }
 
-    async fn calculate_md5(
+    async fn calculate_md5<F: FnMut(u64, u64)>(
         file: &mut tokio::fs::File,
+        total: u64,
+        on_progress: &mut F,
     ) -> Result<String, DownloadError> {
         let mut hasher = Md5::new();
         let mut buffer = vec![0u8; 65536];
+        let mut hashed: u64 = 0;
 
         loop {
             let bytes_read = file.read(&mut buffer)
@@ -107,16 +141,21 @@ impl ChecksumVerifier {
             }
 
             hasher.update(&buffer[..bytes_read]);
+            hashed += bytes_read as u64;
+            on_progress(hashed, total);
         }
 
         Ok(format!("{:x}", hasher.finalize()))
     }
 
-    async fn calculate_sha256(
+    async fn calculate_sha256<F: FnMut(u64, u64)>(
         file: &mut tokio::fs::File,
+        total: u64,
+        on_progress: &mut F,
     ) -> Result<String, DownloadError> {
         let mut hasher = Sha256::new();
         let mut buffer = vec![0u8; 65536];
+        let mut hashed: u64 = 0;
 
         loop {
             let bytes_read = file.read(&mut buffer)
@@ -128,16 +167,21 @@ impl ChecksumVerifier {
             }
 
             hasher.update(&buffer[..bytes_read]);
+            hashed += bytes_read as u64;
+            on_progress(hashed, total);
         }
 
         Ok(format!("{:x}", hasher.finalize()))
     }
 
-    async fn calculate_crc32(
+    async fn calculate_crc32<F: FnMut(u64, u64)>(
         file: &mut tokio::fs::File,
+        total: u64,
+        on_progress: &mut F,
     ) -> Result<String, DownloadError> {
         let mut hasher = crc32fast::Hasher::new();
         let mut buffer = vec![0u8; 65536];
+        let mut hashed: u64 = 0;
 
         loop {
             let bytes_read = file.read(&mut buffer)
@@ -149,6 +193,8 @@ impl ChecksumVerifier {
             }
 
             hasher.update(&buffer[..bytes_read]);
+            hashed += bytes_read as u64;
+            on_progress(hashed, total);
         }
 
         Ok(format!("{:08x}", hasher.finalize()))
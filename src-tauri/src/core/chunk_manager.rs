@@ -3,7 +3,7 @@
 use crate::utils::constants::*;
 
 /// Represents a byte range for a download segment
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct Chunk {
     /// Segment ID (0-indexed)
     pub id: u32,
@@ -20,10 +20,18 @@ impl Chunk {
     }
 }
 
+/// Layout produced by the original even split, kept so downloads
+/// started by older versions resume with the same segment ranges
+pub const LEGACY_LAYOUT_VERSION: u32 = 1;
+
+/// Block-aligned layout with tail merging
+pub const CURRENT_LAYOUT_VERSION: u32 = 2;
+
 pub struct ChunkManager;
 
 impl ChunkManager {
-    /// Split a file into chunks for multi-segment download
+    /// Split a file into chunks for multi-segment download, aligning
+    /// boundaries to `CHUNK_ALIGNMENT`
     ///
     /// # Arguments
     /// * `total_size` - Total file size in bytes
@@ -33,26 +41,69 @@ impl ChunkManager {
     /// Vector of chunks. May return fewer segments than requested
     /// if the file is too small.
     pub fn split(total_size: u64, requested_segments: u8) -> Vec<Chunk> {
-        // Don't split if file is too small
-        if total_size < MIN_SIZE_FOR_SEGMENTS {
-            tracing::debug!(
-                "File too small for segmentation ({} bytes), using 1 segment",
-                total_size
-            );
-            return vec![Chunk {
-                id: 0,
-                start: 0,
-                end: total_size - 1,
-            }];
+        Self::split_aligned(total_size, requested_segments, CHUNK_ALIGNMENT)
+    }
+
+    /// Split using the geometry of a given layout version
+    pub fn split_for_layout(layout_version: u32, total_size: u64, requested_segments: u8) -> Vec<Chunk> {
+        if layout_version <= LEGACY_LAYOUT_VERSION {
+            Self::split_even(total_size, requested_segments)
+        } else {
+            Self::split(total_size, requested_segments)
         }
+    }
+
+    /// Split with every internal boundary on a multiple of `block_size`.
+    /// A final chunk smaller than a quarter block is merged into its
+    /// predecessor instead of taking up a connection of its own.
+    pub fn split_aligned(total_size: u64, requested_segments: u8, block_size: u64) -> Vec<Chunk> {
+        let num_segments = match Self::segment_count(total_size, requested_segments) {
+            Some(n) => n,
+            None => return vec![Self::whole(total_size)],
+        };
+        let block = block_size.max(1);
 
-        // Calculate actual number of segments
-        // Ensure each segment is at least MIN_SEGMENT_SIZE
-        let max_possible = (total_size / MIN_SEGMENT_SIZE).max(1) as u8;
-        let num_segments = requested_segments
-            .min(max_possible)
-            .min(MAX_SEGMENTS)
-            .max(1);
+        // Ideal even boundaries, rounded to the nearest block
+        let mut boundaries: Vec<u64> = Vec::with_capacity(num_segments as usize);
+        for i in 1..num_segments as u64 {
+            let ideal = (total_size as u128 * i as u128 / num_segments as u128) as u64;
+            let aligned = (ideal + block / 2) / block * block;
+            if aligned > 0
+                && aligned < total_size
+                && boundaries.last().map_or(true, |&last| aligned > last)
+            {
+                boundaries.push(aligned);
+            }
+        }
+
+        // Merge a tiny tail into the previous chunk
+        if let Some(&last) = boundaries.last() {
+            if total_size - last < block / 4 {
+                boundaries.pop();
+            }
+        }
+
+        let mut chunks = Vec::with_capacity(boundaries.len() + 1);
+        let mut start = 0;
+        for (i, end) in boundaries.into_iter().chain(std::iter::once(total_size)).enumerate() {
+            chunks.push(Chunk {
+                id: i as u32,
+                start,
+                end: end - 1,
+            });
+            start = end;
+        }
+
+        Self::log_chunks(total_size, &chunks);
+        chunks
+    }
+
+    /// Original layout: equal chunks with the remainder on the last one
+    pub fn split_even(total_size: u64, requested_segments: u8) -> Vec<Chunk> {
+        let num_segments = match Self::segment_count(total_size, requested_segments) {
+            Some(n) => n,
+            None => return vec![Self::whole(total_size)],
+        };
 
         let segment_size = total_size / num_segments as u64;
         let mut chunks = Vec::with_capacity(num_segments as usize);
@@ -73,22 +124,49 @@ impl ChunkManager {
             });
         }
 
+        Self::log_chunks(total_size, &chunks);
+        chunks
+    }
+
+    /// Number of segments to use, or `None` when the file should not be split.
+    /// Never more than one segment per `MIN_SEGMENT_SIZE` bytes.
+    fn segment_count(total_size: u64, requested_segments: u8) -> Option<u8> {
+        // Don't split if file is too small
+        if total_size < MIN_SIZE_FOR_SEGMENTS {
+            tracing::debug!(
+                "File too small for segmentation ({} bytes), using 1 segment",
+                total_size
+            );
+            return None;
+        }
+
+        let max_possible = (total_size / MIN_SEGMENT_SIZE).clamp(1, MAX_SEGMENTS as u64) as u8;
+        Some(requested_segments.min(max_possible).max(1))
+    }
+
+    fn whole(total_size: u64) -> Chunk {
+        Chunk {
+            id: 0,
+            start: 0,
+            end: total_size.saturating_sub(1),
+        }
+    }
+
+    fn log_chunks(total_size: u64, chunks: &[Chunk]) {
         tracing::info!(
             "Split {} bytes into {} segments (avg {} bytes each)",
             total_size,
             chunks.len(),
-            segment_size,
+            total_size / chunks.len().max(1) as u64,
         );
 
         // Log each chunk for debugging
-        for chunk in &chunks {
+        for chunk in chunks {
             tracing::debug!(
                 "  Segment {}: bytes {}-{} ({} bytes)",
                 chunk.id, chunk.start, chunk.end, chunk.size()
             );
         }
-
-        chunks
     }
 
     /// Re-split chunks for resume, accounting for already downloaded bytes
@@ -163,10 +241,67 @@ mod tests {
         let chunks = ChunkManager::split(100_000_000, 8);
         assert_eq!(chunks.len(), 8);
 
-        // All segments except last should be equal
-        let first_size = chunks[0].size();
-        for chunk in &chunks[..chunks.len() - 1] {
-            assert_eq!(chunk.size(), first_size);
+        // Each boundary moves at most half a block, so sizes stay within
+        // two blocks of each other
+        let sizes: Vec<u64> = chunks.iter().map(|c| c.size()).collect();
+        let max = *sizes.iter().max().unwrap();
+        let min = *sizes.iter().min().unwrap();
+        assert!(max - min <= 2 * CHUNK_ALIGNMENT);
+    }
+
+    #[test]
+    fn test_tiny_tail_is_merged() {
+        // 4 MiB + 100 KiB in 5 segments would leave a 100 KiB tail
+        let total = 4 * CHUNK_ALIGNMENT + 100 * 1024;
+        let chunks = ChunkManager::split(total, 5);
+        assert_eq!(chunks.len(), 4);
+        assert_eq!(chunks.last().unwrap().end, total - 1);
+        assert!(chunks.last().unwrap().size() > CHUNK_ALIGNMENT);
+    }
+
+    #[test]
+    fn test_legacy_layout_is_unchanged() {
+        let chunks = ChunkManager::split_for_layout(LEGACY_LAYOUT_VERSION, 100_000_000, 8);
+        assert_eq!(chunks.len(), 8);
+        assert_eq!(chunks[1].start, 12_500_000);
+        assert_eq!(chunks.last().unwrap().end, 99_999_999);
+    }
+
+    #[test]
+    fn test_split_properties_random_sizes() {
+        // Small deterministic LCG so the test needs no extra dependencies
+        let mut seed: u64 = 0x2545_F491_4F6C_DD1D;
+        let mut next = || {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+            seed >> 11
+        };
+
+        for _ in 0..5_000 {
+            let total = 1 + next() % (8 * 1024 * 1024 * 1024);
+            let requested = (next() % MAX_SEGMENTS as u64) as u8 + 1;
+            let block = [CHUNK_ALIGNMENT, 64 * 1024, 4 * CHUNK_ALIGNMENT][(next() % 3) as usize];
+            let chunks = ChunkManager::split_aligned(total, requested, block);
+
+            assert!(!chunks.is_empty());
+            assert_eq!(chunks[0].start, 0, "total {}", total);
+            assert_eq!(chunks.last().unwrap().end, total - 1, "total {}", total);
+            for pair in chunks.windows(2) {
+                // Contiguous and non-overlapping
+                assert_eq!(pair[1].start, pair[0].end + 1, "total {}", total);
+                // Internal boundaries are aligned
+                assert_eq!(pair[1].start % block, 0, "total {} block {}", total, block);
+            }
+            for (i, chunk) in chunks.iter().enumerate() {
+                assert_eq!(chunk.id, i as u32);
+                assert!(chunk.start <= chunk.end);
+            }
+            assert!(chunks.len() <= requested as usize);
+            assert!(chunks.len() as u64 <= (total / MIN_SEGMENT_SIZE).max(1));
+            if chunks.len() > 1 {
+                assert!(chunks.last().unwrap().size() >= block / 4);
+            }
+            let covered: u64 = chunks.iter().map(|c| c.size()).sum();
+            assert_eq!(covered, total);
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,241 @@
+// src-tauri/src/core/content_filter.rs
+// Which files of a batch, remote folder or torrent to take, by extension,
+// size and name. Every rule must pass; unset rules pass everything.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use thiserror::Error;
+
+use crate::utils::format_utils::format_bytes;
+
+/// A filter as the UI and settings store it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ContentFilter {
+    /// Only these extensions, e.g. `["mkv"]`; empty allows any
+    pub include_ext: Vec<String>,
+    /// Never these extensions, e.g. `["nfo", "txt"]`
+    pub exclude_ext: Vec<String>,
+    /// Bytes. Files of unknown size pass both size rules.
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    /// Must match somewhere in the name
+    pub name_regex: Option<String>,
+}
+
+/// A filter saved under a name in settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ContentFilterPreset {
+    pub name: String,
+    pub filter: ContentFilter,
+}
+
+/// Why a file was left out
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SkipReason {
+    #[error(".{0} files are excluded")]
+    ExcludedExtension(String),
+
+    #[error("not one of the included file types")]
+    NotIncluded,
+
+    #[error("smaller than {}", format_bytes(*.0))]
+    TooSmall(u64),
+
+    #[error("larger than {}", format_bytes(*.0))]
+    TooLarge(u64),
+
+    #[error("name doesn't match the pattern")]
+    NameMismatch,
+}
+
+impl ContentFilter {
+    /// Check the rules make sense and prepare them for matching
+    pub fn compile(&self) -> Result<CompiledFilter, String> {
+        if let (Some(min), Some(max)) = (self.min_size, self.max_size) {
+            if min > max {
+                return Err("The minimum size is larger than the maximum".to_string());
+            }
+        }
+        let name_regex = match self.name_regex.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+            Some(pattern) => Some(Regex::new(pattern).map_err(|e| format!("Invalid name pattern: {}", e))?),
+            None => None,
+        };
+        Ok(CompiledFilter {
+            include_ext: normalize_extensions(&self.include_ext),
+            exclude_ext: normalize_extensions(&self.exclude_ext),
+            min_size: self.min_size,
+            max_size: self.max_size,
+            name_regex,
+        })
+    }
+}
+
+fn normalize_extensions(extensions: &[String]) -> Vec<String> {
+    extensions
+        .iter()
+        .map(|ext| ext.trim().trim_start_matches('.').to_lowercase())
+        .filter(|ext| !ext.is_empty())
+        .collect()
+}
+
+/// A validated filter, ready to match many files
+#[derive(Debug, Clone)]
+pub struct CompiledFilter {
+    include_ext: Vec<String>,
+    exclude_ext: Vec<String>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    name_regex: Option<Regex>,
+}
+
+impl CompiledFilter {
+    /// Whether `name` of `size` bytes is taken. `name` may be a relative
+    /// path, as in a torrent; extensions are read from its end, so
+    /// `tar.gz` works as an extension too.
+    pub fn check(&self, name: &str, size: Option<u64>) -> Result<(), SkipReason> {
+        let lower = name.to_lowercase();
+        let has_ext = |ext: &String| lower.ends_with(&format!(".{}", ext));
+
+        if let Some(ext) = self.exclude_ext.iter().find(|ext| has_ext(ext)) {
+            return Err(SkipReason::ExcludedExtension(ext.clone()));
+        }
+        if !self.include_ext.is_empty() && !self.include_ext.iter().any(has_ext) {
+            return Err(SkipReason::NotIncluded);
+        }
+        if let (Some(min), Some(size)) = (self.min_size, size) {
+            if size < min {
+                return Err(SkipReason::TooSmall(min));
+            }
+        }
+        if let (Some(max), Some(size)) = (self.max_size, size) {
+            if size > max {
+                return Err(SkipReason::TooLarge(max));
+            }
+        }
+        if let Some(regex) = &self.name_regex {
+            if !regex.is_match(name) {
+                return Err(SkipReason::NameMismatch);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn matches(&self, name: &str, size: Option<u64>) -> bool {
+        self.check(name, size).is_ok()
+    }
+
+    /// What the filter would do with each sample, for previews
+    pub fn preview(&self, samples: Vec<FilterSample>) -> Vec<FilterPreview> {
+        samples
+            .into_iter()
+            .map(|sample| {
+                let reason = self.check(&sample.name, sample.size).err().map(|r| r.to_string());
+                FilterPreview {
+                    name: sample.name,
+                    size: sample.size,
+                    included: reason.is_none(),
+                    reason,
+                }
+            })
+            .collect()
+    }
+}
+
+/// A file left out by a filter
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedFile {
+    pub name: String,
+    pub size: Option<u64>,
+    pub reason: String,
+}
+
+impl SkippedFile {
+    pub fn new(name: impl Into<String>, size: Option<u64>, reason: &SkipReason) -> Self {
+        Self { name: name.into(), size, reason: reason.to_string() }
+    }
+}
+
+/// A name and size to try a filter on
+#[derive(Debug, Clone, Deserialize)]
+pub struct FilterSample {
+    pub name: String,
+    pub size: Option<u64>,
+}
+
+/// What a filter would do with a sample
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilterPreview {
+    pub name: String,
+    pub size: Option<u64>,
+    pub included: bool,
+    pub reason: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MB: u64 = 1024 * 1024;
+
+    #[test]
+    fn test_only_large_mkv() {
+        let filter = ContentFilter {
+            include_ext: vec![".MKV".to_string()],
+            min_size: Some(500 * MB),
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+
+        assert!(filter.matches("Show/S01E01.mkv", Some(700 * MB)));
+        assert_eq!(filter.check("sample.mkv", Some(20 * MB)), Err(SkipReason::TooSmall(500 * MB)));
+        assert_eq!(filter.check("info.nfo", Some(MB)), Err(SkipReason::NotIncluded));
+        // Size unknown until the transfer: only the name can rule it out
+        assert!(filter.matches("movie.mkv", None));
+    }
+
+    #[test]
+    fn test_excludes_and_pattern() {
+        let filter = ContentFilter {
+            exclude_ext: vec!["nfo".to_string(), "tar.gz".to_string()],
+            max_size: Some(MB),
+            name_regex: Some("(?i)^track".to_string()),
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+
+        assert!(filter.matches("Track 01.flac", Some(1000)));
+        assert_eq!(filter.check("Track.nfo", Some(10)), Err(SkipReason::ExcludedExtension("nfo".to_string())));
+        assert!(!filter.matches("track-src.tar.gz", Some(10)));
+        assert_eq!(filter.check("cover.jpg", Some(10)), Err(SkipReason::NameMismatch));
+        assert_eq!(filter.check("Track 02.flac", Some(2 * MB)), Err(SkipReason::TooLarge(MB)));
+    }
+
+    #[test]
+    fn test_invalid_filters_are_rejected() {
+        let bad_regex = ContentFilter { name_regex: Some("(".to_string()), ..Default::default() };
+        assert!(bad_regex.compile().is_err());
+        let bad_sizes = ContentFilter { min_size: Some(10), max_size: Some(5), ..Default::default() };
+        assert!(bad_sizes.compile().is_err());
+        assert!(ContentFilter::default().compile().unwrap().matches("anything", Some(0)));
+    }
+
+    #[test]
+    fn test_preview() {
+        let filter = ContentFilter { exclude_ext: vec!["txt".to_string()], ..Default::default() }
+            .compile()
+            .unwrap();
+        let preview = filter.preview(vec![
+            FilterSample { name: "a.txt".to_string(), size: Some(1) },
+            FilterSample { name: "b.iso".to_string(), size: None },
+        ]);
+        assert!(!preview[0].included);
+        assert_eq!(preview[0].reason.as_deref(), Some(".txt files are excluded"));
+        assert!(preview[1].included);
+    }
+}
@@ -0,0 +1,261 @@
+// src-tauri/src/core/data_usage.rs
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+/// Share of the budget at which `data-usage-warning` is emitted
+pub const WARNING_PERCENTS: [u8; 2] = [80, 95];
+
+/// Span a data budget covers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum UsagePeriod {
+    /// Monday to Sunday
+    Weekly,
+    /// From the reset day of one month to the next
+    #[default]
+    Monthly,
+}
+
+impl std::str::FromStr for UsagePeriod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "weekly" | "week" => Ok(UsagePeriod::Weekly),
+            "monthly" | "month" => Ok(UsagePeriod::Monthly),
+            _ => Err(format!("Unknown usage period: {}", s)),
+        }
+    }
+}
+
+impl UsagePeriod {
+    /// First day of the period containing `today` and the first day of the
+    /// next one. Monthly periods start on `reset_day`, or the month's last
+    /// day when it is shorter.
+    pub fn bounds(self, today: NaiveDate, reset_day: u32) -> (NaiveDate, NaiveDate) {
+        match self {
+            UsagePeriod::Weekly => {
+                let start = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+                (start, start + Duration::days(7))
+            }
+            UsagePeriod::Monthly => {
+                let this_month = reset_date(today.year(), today.month(), reset_day);
+                let start = if this_month <= today {
+                    this_month
+                } else {
+                    let (year, month) = previous_month(today.year(), today.month());
+                    reset_date(year, month, reset_day)
+                };
+                let (year, month) = next_month(start.year(), start.month());
+                (start, reset_date(year, month, reset_day))
+            }
+        }
+    }
+}
+
+fn previous_month(year: i32, month: u32) -> (i32, u32) {
+    if month == 1 { (year - 1, 12) } else { (year, month - 1) }
+}
+
+fn next_month(year: i32, month: u32) -> (i32, u32) {
+    if month == 12 { (year + 1, 1) } else { (year, month + 1) }
+}
+
+/// `day` of the month, clamped to the month's length
+fn reset_date(year: i32, month: u32, day: u32) -> NaiveDate {
+    let day = day.clamp(1, 31);
+    (1..=day)
+        .rev()
+        .find_map(|d| NaiveDate::from_ymd_opt(year, month, d))
+        .expect("every month has a first day")
+}
+
+/// A data budget and what happens as it runs out
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataCapPolicy {
+    pub period: UsagePeriod,
+    /// Bytes allowed per period; 0 turns the cap off
+    pub budget_bytes: u64,
+    /// Day of the month monthly periods start on
+    pub reset_day: u32,
+    /// Past this share of the budget, transfers are slowed to `throttle_to`
+    pub soft_threshold_percent: u8,
+    /// Bytes per second once over the soft threshold; None leaves speed alone
+    pub throttle_to: Option<u64>,
+    /// Hold all downloads once the budget is used up, until the next period
+    pub pause_at_cap: bool,
+    /// Count torrent uploads as well as downloads
+    pub count_uploads: bool,
+}
+
+impl Default for DataCapPolicy {
+    fn default() -> Self {
+        Self {
+            period: UsagePeriod::Monthly,
+            budget_bytes: 0,
+            reset_day: 1,
+            soft_threshold_percent: 80,
+            throttle_to: None,
+            pause_at_cap: true,
+            count_uploads: false,
+        }
+    }
+}
+
+/// Where usage stands against the budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CapState {
+    Normal,
+    /// Over the soft threshold
+    Throttled,
+    /// Budget used up
+    Capped,
+}
+
+/// Bytes transferred in a period, as returned by `get_data_usage`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataUsage {
+    pub period: UsagePeriod,
+    pub period_start: NaiveDate,
+    /// First day of the next period
+    pub period_end: NaiveDate,
+    pub downloaded_bytes: u64,
+    pub uploaded_bytes: u64,
+    /// Bytes counted against the budget
+    pub used_bytes: u64,
+    /// None when no budget is set
+    pub budget_bytes: Option<u64>,
+    pub remaining_bytes: Option<u64>,
+    pub percent_used: Option<f64>,
+    /// Usage at the end of the period if the pace so far continues
+    pub projected_bytes: u64,
+    pub state: CapState,
+}
+
+impl DataCapPolicy {
+    pub fn is_enabled(&self) -> bool {
+        self.budget_bytes > 0
+    }
+
+    pub fn bounds(&self, period: UsagePeriod, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+        period.bounds(today, self.reset_day)
+    }
+
+    /// Bytes counted against the budget
+    pub fn counted(&self, downloaded: u64, uploaded: u64) -> u64 {
+        if self.count_uploads { downloaded + uploaded } else { downloaded }
+    }
+
+    pub fn state(&self, used: u64) -> CapState {
+        if !self.is_enabled() {
+            CapState::Normal
+        } else if used >= self.budget_bytes {
+            CapState::Capped
+        } else if used as f64 >= self.budget_bytes as f64 * self.soft_threshold_percent as f64 / 100.0 {
+            CapState::Throttled
+        } else {
+            CapState::Normal
+        }
+    }
+
+    /// Usage for `period` containing `now`, given the bytes recorded so far.
+    /// The budget only applies to the configured period.
+    pub fn usage(&self, period: UsagePeriod, now: NaiveDateTime, downloaded: u64, uploaded: u64) -> DataUsage {
+        let (start, end) = self.bounds(period, now.date());
+        let used = self.counted(downloaded, uploaded);
+
+        let period_start = start.and_hms_opt(0, 0, 0).unwrap_or_default();
+        let total = (end - start).num_seconds() as f64;
+        // At least an hour so a quiet start doesn't project wildly
+        let elapsed = ((now - period_start).num_seconds() as f64).clamp(3600.0, total);
+        let projected_bytes = (used as f64 * total / elapsed) as u64;
+
+        let budget = (self.is_enabled() && period == self.period).then_some(self.budget_bytes);
+        DataUsage {
+            period,
+            period_start: start,
+            period_end: end,
+            downloaded_bytes: downloaded,
+            uploaded_bytes: uploaded,
+            used_bytes: used,
+            budget_bytes: budget,
+            remaining_bytes: budget.map(|b| b.saturating_sub(used)),
+            percent_used: budget.map(|b| used as f64 * 100.0 / b as f64),
+            projected_bytes,
+            state: if budget.is_some() { self.state(used) } else { CapState::Normal },
+        }
+    }
+
+    /// Warning thresholds crossed going from `before` to `after` bytes used
+    pub fn crossed_warnings(&self, before: u64, after: u64) -> Vec<u8> {
+        if !self.is_enabled() {
+            return Vec::new();
+        }
+        let at = |percent: u8| self.budget_bytes as f64 * percent as f64 / 100.0;
+        WARNING_PERCENTS
+            .into_iter()
+            .filter(|p| (before as f64) < at(*p) && after as f64 >= at(*p))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GB: u64 = 1024 * 1024 * 1024;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_monthly_bounds_follow_reset_day() {
+        let monthly = UsagePeriod::Monthly;
+        assert_eq!(monthly.bounds(date(2024, 3, 20), 15), (date(2024, 3, 15), date(2024, 4, 15)));
+        assert_eq!(monthly.bounds(date(2024, 3, 10), 15), (date(2024, 2, 15), date(2024, 3, 15)));
+        assert_eq!(monthly.bounds(date(2024, 1, 5), 15), (date(2023, 12, 15), date(2024, 1, 15)));
+        // Short months reset on their last day
+        assert_eq!(monthly.bounds(date(2024, 3, 1), 31), (date(2024, 2, 29), date(2024, 3, 31)));
+    }
+
+    #[test]
+    fn test_weekly_bounds_start_on_monday() {
+        // 2024-03-20 is a Wednesday
+        assert_eq!(UsagePeriod::Weekly.bounds(date(2024, 3, 20), 1), (date(2024, 3, 18), date(2024, 3, 25)));
+    }
+
+    #[test]
+    fn test_states_and_projection() {
+        let policy = DataCapPolicy { budget_bytes: 100 * GB, reset_day: 1, count_uploads: true, ..Default::default() };
+        assert_eq!(policy.state(50 * GB), CapState::Normal);
+        assert_eq!(policy.state(85 * GB), CapState::Throttled);
+        assert_eq!(policy.state(100 * GB), CapState::Capped);
+
+        // 30 GB by the start of day 16 of a 30-day month projects to 60 GB
+        let now = date(2024, 4, 16).and_hms_opt(0, 0, 0).unwrap();
+        let usage = policy.usage(UsagePeriod::Monthly, now, 20 * GB, 10 * GB);
+        assert_eq!(usage.used_bytes, 30 * GB);
+        assert_eq!(usage.remaining_bytes, Some(70 * GB));
+        assert_eq!(usage.projected_bytes, 60 * GB);
+
+        // Uploads only count when asked to, and weekly usage has no budget
+        let downloads_only = DataCapPolicy { count_uploads: false, ..policy.clone() };
+        assert_eq!(downloads_only.usage(UsagePeriod::Monthly, now, 20 * GB, 10 * GB).used_bytes, 20 * GB);
+        assert_eq!(policy.usage(UsagePeriod::Weekly, now, GB, 0).budget_bytes, None);
+    }
+
+    #[test]
+    fn test_warnings_fire_once_per_threshold() {
+        let policy = DataCapPolicy { budget_bytes: 100 * GB, ..Default::default() };
+        assert_eq!(policy.crossed_warnings(70 * GB, 81 * GB), vec![80]);
+        assert!(policy.crossed_warnings(81 * GB, 90 * GB).is_empty());
+        assert_eq!(policy.crossed_warnings(70 * GB, 96 * GB), vec![80, 95]);
+        assert!(DataCapPolicy::default().crossed_warnings(0, u64::MAX).is_empty());
+    }
+}
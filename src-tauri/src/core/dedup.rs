@@ -0,0 +1,396 @@
+// src-tauri/src/core/dedup.rs
+// Finds completed downloads that are the same file under different names
+// and reclaims the space, either by deleting the extra copies or by
+// turning them into hardlinks of one copy. Removed files go to the system
+// trash so a wrong call can be undone.
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::core::checksum::{ChecksumAlgorithm, ChecksumVerifier};
+use crate::core::download_task::DownloadTask;
+use crate::database::db::Database;
+use crate::utils::error::DownloadError;
+
+/// What `deduplicate` does with a group
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DedupStrategy {
+    /// Keep the most recently completed copy and trash the others; their
+    /// downloads then point at the kept file
+    KeepNewestDeleteRest,
+    /// Replace the other copies with hardlinks to the newest one, so every
+    /// download keeps its own path. Copies on another volume are skipped.
+    KeepAllHardlink,
+    /// Change nothing
+    ReportOnly,
+}
+
+/// A completed download whose file is part of a duplicate group
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateFile {
+    pub id: Uuid,
+    pub file_name: String,
+    pub path: PathBuf,
+    pub completed_at: Option<NaiveDateTime>,
+    /// Already a hardlink of an earlier file in the group
+    pub linked: bool,
+}
+
+/// Completed downloads with identical contents
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    /// Identifies the group to `deduplicate`
+    pub id: String,
+    pub checksum: String,
+    pub size: u64,
+    /// Newest first; the first is the copy that is kept
+    pub files: Vec<DuplicateFile>,
+    /// Space freed by keeping a single copy
+    pub reclaimable_bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateReport {
+    pub groups: Vec<DuplicateGroup>,
+    pub reclaimable_bytes: u64,
+}
+
+/// A copy `deduplicate` left alone, and why
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DedupSkip {
+    pub id: Uuid,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DedupOutcome {
+    pub group_id: String,
+    pub strategy: DedupStrategy,
+    pub kept: Uuid,
+    /// Copies moved to the trash
+    pub trashed: Vec<Uuid>,
+    /// Copies replaced by hardlinks
+    pub linked: Vec<Uuid>,
+    pub skipped: Vec<DedupSkip>,
+    pub reclaimed_bytes: u64,
+}
+
+/// A completed download's file as found on disk
+#[derive(Debug, Clone)]
+struct Candidate {
+    task: DownloadTask,
+    size: u64,
+    /// Device and inode, where the platform exposes them
+    identity: Option<(u64, u64)>,
+}
+
+/// Scan completed downloads for files with the same size and checksum
+pub async fn find_duplicates(db: &Database) -> Result<DuplicateReport, DownloadError> {
+    let mut candidates = Vec::new();
+    for task in db.get_completed_downloads().await? {
+        let Ok(metadata) = tokio::fs::metadata(&task.save_path).await else {
+            continue;
+        };
+        if metadata.is_file() && metadata.len() > 0 {
+            candidates.push(Candidate {
+                size: metadata.len(),
+                identity: file_identity(&metadata),
+                task,
+            });
+        }
+    }
+
+    // Only files sharing a size can be duplicates, so only those are hashed
+    let mut hashed = Vec::new();
+    for group in same_size(candidates) {
+        for candidate in group {
+            match checksum_of(db, &candidate.task).await {
+                Ok(checksum) => hashed.push((candidate, checksum)),
+                Err(e) => tracing::warn!("Not checking {:?} for duplicates: {}", candidate.task.save_path, e),
+            }
+        }
+    }
+    Ok(build_report(hashed))
+}
+
+/// Apply `strategy` to the group `group_id` from `find_duplicates`. Every
+/// copy is hashed again first so a file changed since the scan is left alone.
+pub async fn deduplicate(
+    db: &Database,
+    group_id: &str,
+    strategy: DedupStrategy,
+) -> Result<DedupOutcome, DownloadError> {
+    let report = find_duplicates(db).await?;
+    let group = report
+        .groups
+        .into_iter()
+        .find(|g| g.id == group_id)
+        .ok_or_else(|| DownloadError::NotFound(format!("No duplicate group {}", group_id)))?;
+
+    let keep = &group.files[0];
+    let mut outcome = DedupOutcome {
+        group_id: group.id.clone(),
+        strategy,
+        kept: keep.id,
+        trashed: Vec::new(),
+        linked: Vec::new(),
+        skipped: Vec::new(),
+        reclaimed_bytes: 0,
+    };
+    if strategy == DedupStrategy::ReportOnly {
+        return Ok(outcome);
+    }
+
+    if sha256(&keep.path).await? != group.checksum {
+        return Err(DownloadError::FileError(format!(
+            "{} changed since it was scanned; scan again",
+            keep.path.display()
+        )));
+    }
+
+    for file in &group.files[1..] {
+        if file.linked {
+            outcome.skipped.push(DedupSkip { id: file.id, reason: "Already a hardlink".to_string() });
+            continue;
+        }
+        match sha256(&file.path).await {
+            Ok(checksum) if checksum == group.checksum => {}
+            Ok(_) => {
+                outcome.skipped.push(DedupSkip { id: file.id, reason: "Changed since it was scanned".to_string() });
+                continue;
+            }
+            Err(e) => {
+                outcome.skipped.push(DedupSkip { id: file.id, reason: e.to_string() });
+                continue;
+            }
+        }
+
+        let (result, done) = match strategy {
+            DedupStrategy::KeepNewestDeleteRest => (trash(&file.path).await, &mut outcome.trashed),
+            DedupStrategy::KeepAllHardlink => (link_over(&keep.path, &file.path).await, &mut outcome.linked),
+            DedupStrategy::ReportOnly => continue,
+        };
+        match result {
+            Ok(()) => {
+                done.push(file.id);
+                outcome.reclaimed_bytes += group.size;
+            }
+            Err(e) => outcome.skipped.push(DedupSkip { id: file.id, reason: e.to_string() }),
+        }
+    }
+
+    // Downloads whose copy was trashed now refer to the one that was kept
+    for id in &outcome.trashed {
+        db.set_save_path(*id, &keep.path).await?;
+    }
+
+    tracing::info!(
+        "Deduplicated group {}: kept {}, trashed {}, linked {}, skipped {}",
+        group.id,
+        keep.path.display(),
+        outcome.trashed.len(),
+        outcome.linked.len(),
+        outcome.skipped.len()
+    );
+    Ok(outcome)
+}
+
+/// Candidates grouped by size, dropping sizes only one file has
+fn same_size(candidates: Vec<Candidate>) -> Vec<Vec<Candidate>> {
+    let mut by_size: HashMap<u64, Vec<Candidate>> = HashMap::new();
+    for candidate in candidates {
+        by_size.entry(candidate.size).or_default().push(candidate);
+    }
+    by_size.into_values().filter(|group| group.len() > 1).collect()
+}
+
+fn build_report(hashed: Vec<(Candidate, String)>) -> DuplicateReport {
+    let mut by_content: HashMap<(String, u64), Vec<Candidate>> = HashMap::new();
+    for (candidate, checksum) in hashed {
+        by_content.entry((checksum, candidate.size)).or_default().push(candidate);
+    }
+
+    let mut report = DuplicateReport::default();
+    for ((checksum, size), mut members) in by_content {
+        if members.len() < 2 {
+            continue;
+        }
+        members.sort_by(|a, b| {
+            b.task
+                .completed_at
+                .cmp(&a.task.completed_at)
+                .then(b.task.created_at.cmp(&a.task.created_at))
+        });
+
+        let mut seen = Vec::new();
+        let files: Vec<DuplicateFile> = members
+            .into_iter()
+            .map(|c| {
+                let linked = c.identity.is_some_and(|identity| seen.contains(&identity));
+                seen.extend(c.identity);
+                DuplicateFile {
+                    id: c.task.id,
+                    file_name: c.task.file_name,
+                    path: c.task.save_path,
+                    completed_at: c.task.completed_at,
+                    linked,
+                }
+            })
+            .collect();
+        let copies = files.iter().filter(|f| !f.linked).count() as u64;
+        if copies < 2 {
+            continue;
+        }
+
+        let reclaimable_bytes = size * (copies - 1);
+        report.reclaimable_bytes += reclaimable_bytes;
+        report.groups.push(DuplicateGroup {
+            id: format!("{}-{}", &checksum[..checksum.len().min(16)], size),
+            checksum,
+            size,
+            files,
+            reclaimable_bytes,
+        });
+    }
+    report.groups.sort_by(|a, b| b.reclaimable_bytes.cmp(&a.reclaimable_bytes));
+    report
+}
+
+/// SHA-256 of a download's file, reusing the stored checksum when it is one
+async fn checksum_of(db: &Database, task: &DownloadTask) -> Result<String, DownloadError> {
+    let is_sha256 = matches!(task.checksum_algorithm, None | Some(ChecksumAlgorithm::Sha256));
+    if let (Some(checksum), Some(ChecksumAlgorithm::Sha256)) = (&task.actual_checksum, &task.checksum_algorithm) {
+        return Ok(checksum.to_ascii_lowercase());
+    }
+
+    let checksum = sha256(&task.save_path).await?;
+    // A checksum of another algorithm belongs to the expected one
+    if is_sha256 {
+        if let Err(e) = db.set_actual_checksum(task.id, &checksum, &ChecksumAlgorithm::Sha256).await {
+            tracing::warn!("Failed to store checksum of {}: {}", task.id, e);
+        }
+    }
+    Ok(checksum)
+}
+
+async fn sha256(path: &Path) -> Result<String, DownloadError> {
+    Ok(ChecksumVerifier::calculate(path, &ChecksumAlgorithm::Sha256)
+        .await?
+        .to_ascii_lowercase())
+}
+
+async fn trash(path: &Path) -> Result<(), DownloadError> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || trash::delete(&path))
+        .await
+        .map_err(|e| DownloadError::Unknown(e.to_string()))?
+        .map_err(|e| DownloadError::FileError(format!("Cannot move to trash: {}", e)))
+}
+
+/// Replace `duplicate` with a hardlink to `keep`. The link is made next to
+/// the duplicate first, so a copy on another volume is left untouched.
+async fn link_over(keep: &Path, duplicate: &Path) -> Result<(), DownloadError> {
+    let name = duplicate
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let temp = duplicate.with_file_name(format!(".{}.dedup", name));
+
+    tokio::fs::hard_link(keep, &temp)
+        .await
+        .map_err(|e| DownloadError::FileError(format!("Cannot hardlink (different volume?): {}", e)))?;
+    if let Err(e) = trash(duplicate).await {
+        let _ = tokio::fs::remove_file(&temp).await;
+        return Err(e);
+    }
+    tokio::fs::rename(&temp, duplicate)
+        .await
+        .map_err(|e| DownloadError::FileError(format!("Cannot put link in place of {}: {}", duplicate.display(), e)))
+}
+
+#[cfg(unix)]
+fn file_identity(metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_identity(_metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(name: &str, size: u64, completed_day: u32, identity: Option<(u64, u64)>) -> Candidate {
+        let mut task = DownloadTask::new(format!("https://example.com/{}", name), name.into(), format!("/dl/{}", name).into(), 1);
+        task.completed_at = chrono::NaiveDate::from_ymd_opt(2024, 1, completed_day)
+            .and_then(|d| d.and_hms_opt(12, 0, 0));
+        Candidate { task, size, identity }
+    }
+
+    #[test]
+    fn test_same_size_drops_unique_sizes() {
+        let groups = same_size(vec![
+            candidate("a.iso", 100, 1, None),
+            candidate("b.iso", 100, 2, None),
+            candidate("c.iso", 50, 3, None),
+        ]);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].len(), 2);
+    }
+
+    #[test]
+    fn test_report_groups_by_checksum() {
+        let report = build_report(vec![
+            (candidate("ubuntu.iso", 100, 1, Some((1, 10))), "aa".to_string()),
+            (candidate("ubuntu (1).iso", 100, 3, Some((1, 11))), "aa".to_string()),
+            (candidate("ubuntu-copy.iso", 100, 2, Some((1, 12))), "aa".to_string()),
+            (candidate("other.iso", 100, 2, Some((1, 13))), "bb".to_string()),
+        ]);
+
+        assert_eq!(report.groups.len(), 1);
+        let group = &report.groups[0];
+        assert_eq!(group.size, 100);
+        assert_eq!(group.reclaimable_bytes, 200);
+        assert_eq!(report.reclaimable_bytes, 200);
+        // Newest first, so it is the one kept
+        let names: Vec<_> = group.files.iter().map(|f| f.file_name.as_str()).collect();
+        assert_eq!(names, ["ubuntu (1).iso", "ubuntu-copy.iso", "ubuntu.iso"]);
+    }
+
+    #[test]
+    fn test_existing_hardlinks_not_reclaimable() {
+        let report = build_report(vec![
+            (candidate("a.iso", 100, 2, Some((1, 10))), "aa".to_string()),
+            (candidate("b.iso", 100, 1, Some((1, 10))), "aa".to_string()),
+        ]);
+        assert!(report.groups.is_empty());
+
+        let report = build_report(vec![
+            (candidate("a.iso", 100, 3, Some((1, 10))), "aa".to_string()),
+            (candidate("b.iso", 100, 2, Some((1, 10))), "aa".to_string()),
+            (candidate("c.iso", 100, 1, Some((1, 20))), "aa".to_string()),
+        ]);
+        let group = &report.groups[0];
+        assert!(group.files[1].linked);
+        assert_eq!(group.reclaimable_bytes, 100);
+    }
+
+    #[test]
+    fn test_strategy_names() {
+        let strategy: DedupStrategy = serde_json::from_str("\"keep_all_hardlink\"").unwrap();
+        assert_eq!(strategy, DedupStrategy::KeepAllHardlink);
+        assert_eq!(serde_json::to_string(&DedupStrategy::KeepNewestDeleteRest).unwrap(), "\"keep_newest_delete_rest\"");
+    }
+}
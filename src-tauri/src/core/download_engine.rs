@@ -1,20 +1,40 @@
 use futures_util::StreamExt;
-use std::path::PathBuf;
-use tokio::io::AsyncWriteExt;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 
-use crate::core::checksum::{ChecksumVerifier, ChecksumAlgorithm};
-use crate::core::chunk_manager::{Chunk, ChunkManager};
+use crate::core::category::{Category, CategoryDefaults};
+use crate::core::checksum::ChecksumVerifier;
+use crate::core::chunk_manager::{
+    Chunk, ChunkManager, CURRENT_LAYOUT_VERSION, LEGACY_LAYOUT_VERSION,
+};
+use crate::core::download_plan::{space_needed, CategorySource, DiskSpaceCheck, DownloadPlan};
 use crate::core::download_task::*;
+use crate::core::playlist::PlaylistMode;
+use crate::core::post_action::PostAction;
 use crate::core::resume_manager::{ResumeManager, ResumeData};
-use crate::core::retry::{RetryHandler, RetryConfig};
+use crate::core::retry::{RetryHandler, RetryConfig, RetryOverrides};
 use crate::core::segment_downloader::SegmentDownloader;
+use crate::core::segment_gate::{ContentionPolicy, SegmentGate};
+use crate::core::size_guard::{DownloadSource, SizeLimits};
 use crate::core::speed_limiter::SpeedLimiter;
-use crate::network::http_client::HttpClient;
-use crate::network::url_parser::UrlParser;
+use crate::core::waste::{WasteCounter, WasteReason, DEFAULT_WASTE_WARNING_FRACTION};
+use crate::network::http_client::{HttpClient, ProbeMode, RemoteFileInfo};
+use crate::network::file_info_cache::FileInfoCacheStats;
+use crate::network::resolvers::{ResolvedUrl, UrlResolvers};
+use crate::network::url_parser::{NormalizeOptions, UrlParser, DEFAULT_TRACKING_PARAMS};
+use crate::network::url_refresh::{UrlRefresh, UrlRefresher};
+use crate::utils::logging::{LogEntry, LogLevel, Logger};
+use crate::utils::permissions::{self, FilePermissions};
+use crate::utils::security::InputValidator;
 use crate::utils::constants::*;
 use crate::utils::error::DownloadError;
+use crate::utils::file_in_use;
+use crate::utils::file_utils;
 
 /// Main download engine - orchestrates all download operations
 pub struct DownloadEngine {
@@ -24,8 +44,67 @@ pub struct DownloadEngine {
     /// Global speed limiter (public for command access)
     pub speed_limiter: SpeedLimiter,
 
+    /// Unwrap shortened and redirecting links before they are probed
+    pub resolvers: Arc<UrlResolvers>,
+
+    /// Gets fresh URLs for downloads whose signed links expire
+    pub url_refresher: Arc<UrlRefresher>,
+
     /// Default download directory
     default_download_dir: PathBuf,
+
+    /// Detect a category for downloads added without one
+    auto_categorize: AtomicBool,
+
+    /// Save folders configured per category id
+    category_dirs: parking_lot::RwLock<HashMap<String, PathBuf>>,
+
+    /// Folders the user approved for saving, besides the default and
+    /// category folders
+    approved_save_roots: parking_lot::RwLock<Vec<PathBuf>>,
+
+    /// Folders approved until the app quits
+    session_save_roots: parking_lot::RwLock<Vec<PathBuf>>,
+
+    /// Folders the app itself saves into, such as the updates folder
+    app_save_roots: parking_lot::RwLock<Vec<PathBuf>>,
+
+    /// Mode and group for completed files, and per-category overrides
+    file_permissions: parking_lot::RwLock<FilePermissions>,
+    category_permissions: parking_lot::RwLock<HashMap<String, FilePermissions>>,
+
+    /// Segment, retry and checksum defaults per category id
+    category_defaults: parking_lot::RwLock<HashMap<String, CategoryDefaults>>,
+
+    /// Backoff policy used unless a download overrides it
+    retry_defaults: parking_lot::RwLock<RetryConfig>,
+
+    /// Size thresholds checked when downloads are added
+    size_limits: parking_lot::RwLock<SizeLimits>,
+
+    /// Segment parallelism of running multi-segment downloads
+    segment_gates: parking_lot::Mutex<HashMap<uuid::Uuid, Arc<SegmentGate>>>,
+    contention: parking_lot::RwLock<ContentionPolicy>,
+
+    /// URLs a batch add probes at once
+    batch_concurrency: AtomicUsize,
+
+    /// Entries a per-entry playlist downloads at once
+    playlist_concurrency: AtomicUsize,
+
+    /// Fraction of a file's size that may be wasted before the user is
+    /// warned
+    waste_warning_fraction: parking_lot::RwLock<f64>,
+
+    /// Refuse to add a download the destination or temp volume has no
+    /// room for, instead of only warning
+    refuse_without_space: AtomicBool,
+
+    /// Query parameters stripped when a batch of URLs is normalized
+    tracking_params: parking_lot::RwLock<Vec<String>>,
+
+    /// Receives per-download retry entries
+    logger: parking_lot::RwLock<Option<Arc<Logger>>>,
 }
 
 impl DownloadEngine {
@@ -38,6 +117,8 @@ impl DownloadEngine {
         let http_client = HttpClient::new(proxy)?;
 
         let speed_limiter = SpeedLimiter::new(speed_limit);
+        let resolvers = Arc::new(UrlResolvers::new(http_client.without_redirects()));
+        let url_refresher = Arc::new(UrlRefresher::new(http_client.client()));
 
         let default_download_dir = download_dir.unwrap_or_else(
             || {
@@ -66,7 +147,29 @@ impl DownloadEngine {
         Ok(Self {
             http_client,
             speed_limiter,
+            resolvers,
+            url_refresher,
             default_download_dir,
+            auto_categorize: AtomicBool::new(true),
+            category_dirs: parking_lot::RwLock::new(HashMap::new()),
+            approved_save_roots: parking_lot::RwLock::new(Vec::new()),
+            session_save_roots: parking_lot::RwLock::new(Vec::new()),
+            app_save_roots: parking_lot::RwLock::new(Vec::new()),
+            file_permissions: parking_lot::RwLock::new(FilePermissions::default()),
+            category_permissions: parking_lot::RwLock::new(HashMap::new()),
+            category_defaults: parking_lot::RwLock::new(HashMap::new()),
+            retry_defaults: parking_lot::RwLock::new(RetryConfig::default()),
+            size_limits: parking_lot::RwLock::new(SizeLimits::default()),
+            segment_gates: parking_lot::Mutex::new(HashMap::new()),
+            contention: parking_lot::RwLock::new(ContentionPolicy::default()),
+            batch_concurrency: AtomicUsize::new(DEFAULT_BATCH_CONCURRENCY),
+            playlist_concurrency: AtomicUsize::new(DEFAULT_PLAYLIST_CONCURRENCY),
+            waste_warning_fraction: parking_lot::RwLock::new(DEFAULT_WASTE_WARNING_FRACTION),
+            refuse_without_space: AtomicBool::new(false),
+            tracking_params: parking_lot::RwLock::new(
+                DEFAULT_TRACKING_PARAMS.iter().map(|p| p.to_string()).collect(),
+            ),
+            logger: parking_lot::RwLock::new(None),
         })
     }
 
@@ -75,95 +178,588 @@ impl DownloadEngine {
         &self.default_download_dir
     }
 
+    /// Shared HTTP client, without the global speed limiter
+    pub fn http_client(&self) -> &HttpClient {
+        &self.http_client
+    }
+
+    /// Set the backoff policy used by downloads without overrides
+    pub fn set_retry_defaults(&self, config: RetryConfig) {
+        info!("Retry defaults updated: {:?}", config);
+        *self.retry_defaults.write() = config;
+    }
+
+    /// Set the size thresholds checked when downloads are added
+    pub fn set_size_limits(&self, limits: SizeLimits) {
+        *self.size_limits.write() = limits;
+    }
+
+    pub fn size_limits(&self) -> SizeLimits {
+        *self.size_limits.read()
+    }
+
+    /// Overall time allowed for probing a URL before it is added
+    pub fn set_probe_timeout(&self, timeout: std::time::Duration) {
+        self.http_client.set_probe_timeout(timeout);
+    }
+
+    /// Run the URL resolvers within the probe timeout; their client only
+    /// has the much longer read timeout
+    async fn resolve_url(&self, url: &str) -> Result<ResolvedUrl, DownloadError> {
+        let budget = self.http_client.probe_timeout();
+        tokio::time::timeout(budget, self.resolvers.resolve(url))
+            .await
+            .unwrap_or(Err(DownloadError::Timeout { seconds: budget.as_secs() }))
+    }
+
+    /// How long a probed URL's file info is reused before asking again
+    pub fn set_file_info_ttl(&self, ttl: std::time::Duration) {
+        self.http_client.set_file_info_ttl(ttl);
+    }
+
+    pub fn file_info_cache_stats(&self) -> FileInfoCacheStats {
+        self.http_client.file_info_cache_stats()
+    }
+
+    /// How many URLs a batch add probes at once
+    pub fn set_batch_concurrency(&self, concurrency: usize) {
+        self.batch_concurrency.store(concurrency.max(1), Ordering::Relaxed);
+    }
+
+    pub fn batch_concurrency(&self) -> usize {
+        self.batch_concurrency.load(Ordering::Relaxed)
+    }
+
+    /// Query parameters batch normalization strips
+    pub fn set_tracking_params(&self, params: Vec<String>) {
+        *self.tracking_params.write() = params;
+    }
+
+    /// `options` with the configured tracking parameters filled in
+    pub fn normalize_options(&self, options: Option<NormalizeOptions>) -> NormalizeOptions {
+        let mut options = options.unwrap_or_default();
+        options.tracking_params = self.tracking_params.read().clone();
+        options
+    }
+
+    /// How many entries a per-entry playlist downloads at once
+    pub fn set_playlist_concurrency(&self, concurrency: usize) {
+        self.playlist_concurrency.store(concurrency.max(1), Ordering::Relaxed);
+    }
+
+    pub fn playlist_concurrency(&self) -> usize {
+        self.playlist_concurrency.load(Ordering::Relaxed)
+    }
+
+    /// Warn once a download has wasted this fraction of its size
+    pub fn set_waste_warning_fraction(&self, fraction: f64) {
+        *self.waste_warning_fraction.write() = fraction;
+    }
+
+    pub fn waste_warning_fraction(&self) -> f64 {
+        *self.waste_warning_fraction.read()
+    }
+
+    /// Refuse downloads there isn't room for when they are added
+    pub fn set_refuse_without_space(&self, refuse: bool) {
+        self.refuse_without_space.store(refuse, Ordering::Relaxed);
+    }
+
+    pub fn set_contention_policy(&self, policy: ContentionPolicy) {
+        *self.contention.write() = policy;
+    }
+
+    /// Segments a download about to start should use while
+    /// `active_downloads` others are running
+    pub fn segments_under_contention(&self, requested: u8, active_downloads: usize) -> u8 {
+        let in_use = self.segment_gates.lock().values().map(|gate| gate.limit()).sum();
+        self.contention.read().segments_for(requested, active_downloads, in_use)
+    }
+
+    /// Lower the parallelism of a running multi-segment download. Returns
+    /// the segments now allowed to run, or None if the download isn't
+    /// running segmented. Raising only takes effect on the next start.
+    pub fn reduce_live_segments(&self, id: uuid::Uuid, segments: u8) -> Option<usize> {
+        let gate = self.segment_gates.lock().get(&id).cloned()?;
+        let segments = segments.max(1) as usize;
+        if segments < gate.limit() {
+            gate.set_limit(segments);
+        }
+        Some(gate.limit())
+    }
+
+    /// Drop the connections of every running segment so each reconnects
+    /// at once, from where its partial file ends, instead of waiting for a
+    /// dead socket to time out. Like preemption, this doesn't count against
+    /// the download's retries. Returns how many segments were interrupted.
+    pub fn reconnect_segments(&self) -> usize {
+        self.segment_gates.lock().values().map(|gate| gate.interrupt_running()).sum()
+    }
+
+    /// Attach the app logger so retries show up in each download's log
+    pub fn set_logger(&self, logger: Arc<Logger>) {
+        *self.logger.write() = Some(logger);
+    }
+
+    /// Retry handler for a task: global defaults, the task's own overrides
+    /// and its retry limit. Each retry is written to the download's log.
+    fn retry_handler(&self, task: &DownloadTask, cancel: &CancellationToken) -> RetryHandler {
+        let mut config = self.retry_defaults.read().clone();
+        if let Some(overrides) = &task.retry_policy {
+            config = config.with_overrides(overrides);
+        }
+        config.max_retries = task.retry_count;
+
+        let handler = RetryHandler::new(config).with_cancel(cancel.clone());
+        let Some(logger) = self.logger.read().clone() else {
+            return handler;
+        };
+        let download_id = task.id;
+        handler.on_retry(move |retry| {
+            let entry = LogEntry::new(
+                LogLevel::Warn,
+                "download",
+                format!(
+                    "Attempt {}/{} failed ({}), retrying in {}ms",
+                    retry.attempt,
+                    retry.max_attempts,
+                    retry.error,
+                    retry.delay.as_millis()
+                ),
+            )
+            .with_metadata(serde_json::json!({
+                "download_id": download_id,
+                "attempt": retry.attempt,
+                "delay_ms": retry.delay.as_millis() as u64,
+            }));
+            let logger = logger.clone();
+            tokio::spawn(async move { logger.log(entry).await });
+        })
+    }
+
     /// Update global speed limit
     pub async fn set_speed_limit(&self, limit: Option<u64>) {
         self.speed_limiter.set_limit(limit).await;
         info!("Speed limit updated: {:?}", limit);
     }
 
+    /// Enable or disable automatic categorization of new downloads
+    pub fn set_auto_categorize(&self, enabled: bool) {
+        self.auto_categorize.store(enabled, Ordering::Relaxed);
+        info!("Auto-categorize on add: {}", enabled);
+    }
+
+    /// Replace the per-category save folders
+    pub fn set_category_dirs(&self, dirs: HashMap<String, PathBuf>) {
+        *self.category_dirs.write() = dirs;
+    }
+
+    /// Save folder configured for a category, if any
+    pub fn category_dir(&self, category: &str) -> Option<PathBuf> {
+        self.category_dirs.read().get(category).cloned()
+    }
+
+    /// Replace the folders the user approved for saving
+    pub fn set_approved_save_roots(&self, roots: Vec<PathBuf>) {
+        *self.approved_save_roots.write() = roots;
+    }
+
+    /// Approve a folder for saving until the app quits
+    pub fn add_session_save_root(&self, root: PathBuf) {
+        self.session_save_roots.write().push(root);
+    }
+
+    /// Let the app save into one of its own folders
+    pub fn add_app_save_root(&self, root: PathBuf) {
+        self.app_save_roots.write().push(root);
+    }
+
+    /// Every folder downloads may be saved under: the default folder,
+    /// category folders, the approved ones and the app's own
+    pub fn allowed_save_roots(&self) -> Vec<PathBuf> {
+        let mut roots = vec![self.default_download_dir.clone()];
+        roots.extend(self.category_dirs.read().values().cloned());
+        roots.extend(self.approved_save_roots.read().iter().cloned());
+        roots.extend(self.session_save_roots.read().iter().cloned());
+        roots.extend(self.app_save_roots.read().iter().cloned());
+        roots
+    }
+
+    /// Reject a save path outside the allowed folders, after resolving
+    /// `..` and symlinks
+    pub fn check_save_path(&self, path: &Path) -> Result<(), DownloadError> {
+        InputValidator::validate_file_path(&path.to_string_lossy(), &self.allowed_save_roots()).map(|_| ())
+    }
+
+    /// Set the mode and group given to completed files
+    pub fn set_file_permissions(&self, permissions: FilePermissions) {
+        *self.file_permissions.write() = permissions;
+    }
+
+    /// Replace the per-category mode and group overrides
+    pub fn set_category_permissions(&self, permissions: HashMap<String, FilePermissions>) {
+        *self.category_permissions.write() = permissions;
+    }
+
+    /// Replace the per-category add-time defaults
+    pub fn set_category_defaults(&self, defaults: HashMap<String, CategoryDefaults>) {
+        *self.category_defaults.write() = defaults;
+    }
+
+    /// Add-time defaults configured for a category
+    pub fn category_defaults(&self, category: &str) -> CategoryDefaults {
+        self.category_defaults.read().get(category).copied().unwrap_or_default()
+    }
+
+    /// Apply the configured mode and group to a completed download.
+    /// Failures are logged once for the download and never fail it.
+    pub fn apply_file_permissions(&self, task: &DownloadTask) {
+        let mut permissions = self.file_permissions.read().clone();
+        if let Some(category) = &task.category {
+            if let Some(overrides) = self.category_permissions.read().get(category) {
+                permissions = permissions.with_overrides(overrides);
+            }
+        }
+        if permissions.is_empty() {
+            return;
+        }
+
+        let Some(warning) = permissions::apply(&task.save_path, &permissions) else {
+            return;
+        };
+        warn!("Could not apply file permissions: {}", warning);
+        if let Some(logger) = self.logger.read().clone() {
+            let entry = LogEntry::new(LogLevel::Warn, "download", warning)
+                .with_metadata(serde_json::json!({ "download_id": task.id }));
+            tokio::spawn(async move { logger.log(entry).await });
+        }
+    }
+
+    /// Detect a category for a new download, or None when
+    /// automatic categorization is disabled
+    pub fn detect_category(
+        &self,
+        file_name: &str,
+        content_type: Option<&str>,
+    ) -> Option<String> {
+        if !self.auto_categorize.load(Ordering::Relaxed) {
+            return None;
+        }
+        Some(Category::detect(file_name, content_type))
+    }
+
     /// Fetch file information from URL
     pub async fn get_file_info(
         &self,
         url: &str,
+    ) -> Result<FileInfo, DownloadError> {
+        self.get_file_info_with_referrer(url, None).await
+    }
+
+    /// `get_file_info`, sending the page the file was found on as Referer
+    pub async fn get_file_info_with_referrer(
+        &self,
+        url: &str,
+        referrer: Option<&str>,
     ) -> Result<FileInfo, DownloadError> {
         // Parse and validate URL
         let _ = UrlParser::parse(url)?;
-        
+
+        // Resolve first, as adding would, so the redirects can be counted
+        let resolved = self.resolve_url(url).await.unwrap_or_else(|e| {
+            debug!("Could not resolve {}: {}", url, e);
+            ResolvedUrl::unchanged(url)
+        });
+
         // Get file info from HTTP client
-        let info = self.http_client.get_file_info(url).await?;
-        
+        let info = self.http_client.get_file_info_with_referrer(&resolved.url, referrer).await?;
+        let supports_resume = info.supports_resume();
+        let followed = usize::from(info.redirect_url.is_some());
+
         Ok(FileInfo {
+            capability_notes: SingleSegmentReason::for_file(info.supports_range, info.total_size)
+                .map(|reason| reason.to_string()),
             file_name: info.file_name,
             total_size: info.total_size,
             content_type: info.content_type,
             supports_range: info.supports_range,
+            etag: info.etag,
+            accept_ranges: info.accept_ranges,
+            server: info.server,
+            supports_resume,
+            redirect_count: resolved.chain.len() + followed,
+            https_downgraded: info.https_downgraded || resolved.downgrades_https(),
         })
     }
 
+    /// Probe one URL of a page's media list, sending the page as Referer.
+    /// Resolved the way adding it would be, so the add finds the result
+    /// in the file info cache.
+    pub async fn probe_media(
+        &self,
+        url: &str,
+        referrer: Option<&str>,
+    ) -> Result<RemoteFileInfo, DownloadError> {
+        UrlParser::parse(url)?;
+        let resolved = self.resolve_url(url).await.unwrap_or_else(|e| {
+            debug!("Could not resolve {}: {}", url, e);
+            ResolvedUrl::unchanged(url)
+        });
+        self.http_client.get_file_info_with_referrer(&resolved.url, referrer).await
+    }
+
+    /// Point `task` at `url`, keeping what it has downloaded. With
+    /// `validate` the link is probed first and refused with `UrlMismatch`
+    /// when its size or ETag shows a different file. `force` restarts the
+    /// download from zero under the new link instead.
+    pub async fn replace_url(
+        &self,
+        task: &mut DownloadTask,
+        url: &str,
+        validate: bool,
+        force: bool,
+    ) -> Result<(), DownloadError> {
+        UrlParser::parse(url)?;
+        let info = if validate || force {
+            Some(self.get_file_info_with_referrer(url, task.referrer()).await?)
+        } else {
+            None
+        };
+        if let Some(mismatch) = info.as_ref().and_then(|info| task.replacement_mismatch(info)) {
+            if !force {
+                return Err(DownloadError::UrlMismatch(format!(
+                    "the new link's {}; apply it with force to start over",
+                    mismatch
+                )));
+            }
+        }
+
+        if force {
+            self.discard_progress(task).await?;
+        }
+        task.url = url.to_string();
+        task.final_url = None;
+        task.redirect_chain.clear();
+        if let Some(info) = info {
+            if force || task.etag.is_none() {
+                task.etag = info.etag;
+            }
+            if force {
+                task.total_size = info.total_size;
+                task.supports_range = info.supports_range;
+            }
+        }
+        Ok(())
+    }
+
+    /// Throw away a download's partial data so it starts again from zero.
+    /// What was on disk counts as wasted.
+    pub async fn discard_progress(&self, task: &mut DownloadTask) -> Result<(), DownloadError> {
+        let temp_dir = self.get_temp_dir(task);
+        let discarded = Self::partial_bytes(&temp_dir, &task.save_path).await;
+        if discarded > 0 {
+            debug!("Discarded {} bytes: {}", discarded, WasteReason::InvalidatedResume);
+            task.wasted_bytes += discarded;
+        }
+        if temp_dir.exists() {
+            tokio::fs::remove_dir_all(&temp_dir)
+                .await
+                .map_err(|e| DownloadError::FileError(format!("Cannot remove partial data: {}", e)))?;
+        }
+        if task.save_path.exists() {
+            tokio::fs::remove_file(&task.save_path)
+                .await
+                .map_err(|e| DownloadError::FileError(format!("Cannot remove partial file: {}", e)))?;
+        }
+        task.downloaded_size = 0;
+        task.segment_progress.clear();
+        Ok(())
+    }
+
+    /// Bytes of partial data: the segment files and whatever is at the
+    /// save path
+    async fn partial_bytes(temp_dir: &Path, save_path: &Path) -> u64 {
+        let mut total = tokio::fs::metadata(save_path).await.map_or(0, |m| m.len());
+        if let Ok(mut entries) = tokio::fs::read_dir(temp_dir).await {
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let is_segment = entry.file_name().to_string_lossy().starts_with("segment_");
+                if let Ok(metadata) = entry.metadata().await {
+                    if is_segment && metadata.is_file() {
+                        total += metadata.len();
+                    }
+                }
+            }
+        }
+        total
+    }
+
     /// Create a new download task from a request
     pub async fn create_task(
         &self,
         request: &AddDownloadRequest,
     ) -> Result<DownloadTask, DownloadError> {
+        let plan = self.plan_download(request).await?;
+        for warning in &plan.warnings {
+            warn!("{}", warning);
+        }
+        if self.refuse_without_space.load(Ordering::Relaxed) && !plan.space_shortfalls().is_empty() {
+            return Err(DownloadError::InsufficientDiskSpace);
+        }
+        Ok(plan.into_task(request))
+    }
+
+    /// Decide everything about a new download short of transferring it:
+    /// where the URL leads, what the server says about the file, its name,
+    /// category, folder and segments. Adding a download follows the plan,
+    /// and a simulated add returns it.
+    pub async fn plan_download(
+        &self,
+        request: &AddDownloadRequest,
+    ) -> Result<DownloadPlan, DownloadError> {
         // Parse URL
         let parsed = UrlParser::parse(&request.url)?;
 
-        // Fetch file info from server
-        let file_info =
-            self.http_client.get_file_info(&request.url).await?;
+        // Follow shorteners and redirect pages to the file itself. A link
+        // that can't be resolved is added as given.
+        let resolved = match request.probe {
+            ProbeMode::None => ResolvedUrl::unchanged(&request.url),
+            _ => self.resolve_url(&request.url).await.unwrap_or_else(|e| {
+                warn!("Could not resolve {}: {}", request.url, e);
+                ResolvedUrl::unchanged(&request.url)
+            }),
+        };
+        let parsed = UrlParser::parse(&resolved.url).unwrap_or(parsed);
+
+        // Fetch file info from server, unless the request can do without.
+        // The page it came from goes along, as it will on the transfer.
+        let referrer = request.source_page_url.as_deref();
+        let file_info = match request.probe {
+            ProbeMode::None => RemoteFileInfo::unknown(&resolved.url),
+            ProbeMode::BestEffort => match self.http_client.get_file_info_with_referrer(&resolved.url, referrer).await {
+                Ok(info) => info,
+                Err(e) => {
+                    warn!("Adding {} without file info: {}", request.url, e);
+                    RemoteFileInfo::unknown(&resolved.url)
+                }
+            },
+            ProbeMode::Required => self.http_client.get_file_info_with_referrer(&resolved.url, referrer).await?,
+        };
+        self.size_limits().enforce_hard_limit(file_info.total_size)?;
+
+        // Determine file name: explicit file_name override, otherwise parsed filename
+        let file_name = request
+            .file_name
+            .clone()
+            .unwrap_or(parsed.filename);
 
-        // Determine save path
+        // Use the requested category, otherwise detect one
+        let (category, category_source) = match &request.category {
+            Some(category) => (Some(category.clone()), CategorySource::Requested),
+            None => match self.detect_category(&file_name, file_info.content_type.as_deref()) {
+                Some(category) => (Some(category), CategorySource::Detected),
+                None => (None, CategorySource::Unset),
+            },
+        };
+
+        // Determine save path: explicit path, then category folder, then default
         let save_dir = request
             .save_path
             .as_ref()
             .map(PathBuf::from)
+            .or_else(|| category.as_deref().and_then(|c| self.category_dir(c)))
             .unwrap_or_else(|| {
                 self.default_download_dir.clone()
             });
 
-        // Determine file name: explicit file_name override, otherwise parsed filename
-        let file_name = request
-            .file_name
-            .clone()
-            .unwrap_or(parsed.filename);
-
         // Generate unique filename if needed
         let unique_name = self.unique_filename(&save_dir, &file_name);
         let save_path = save_dir.join(&unique_name);
+        // Paths from deep links and the extension can't escape the
+        // approved folders
+        if !request.existing_location {
+            self.check_save_path(&save_path)?;
+        }
+
+        // Values left unset by the request come from the category
+        let defaults = category
+            .as_deref()
+            .map(|c| self.category_defaults(c))
+            .unwrap_or_default();
 
         // Determine number of segments
         let segments = request
             .segments
+            .or(defaults.segments)
             .unwrap_or(DEFAULT_SEGMENTS)
             .min(MAX_SEGMENTS);
+        let segmented = segments > 1
+            && SingleSegmentReason::for_file(file_info.supports_range, file_info.total_size).is_none();
+
+        // Segment data goes beside the file unless the request puts it
+        // elsewhere, e.g. on a faster or roomier volume
+        let temp_root = match &request.temp_dir {
+            Some(dir) => {
+                let dir = PathBuf::from(dir);
+                if !request.existing_location {
+                    self.check_save_path(&dir)?;
+                }
+                dir
+            }
+            None => save_dir.clone(),
+        };
 
-        let mut task =
-            DownloadTask::new(request.url.clone(), unique_name, save_path, segments);
-
-        task.total_size = file_info.total_size;
-        task.supports_range = file_info.supports_range;
-        task.content_type = file_info.content_type;
-        task.etag = file_info.etag;
-        task.retry_count = request
-            .max_retries
-            .unwrap_or(DEFAULT_MAX_RETRIES) as u32;
-        task.expected_checksum = request.expected_checksum.clone();
-        task.checksum_algorithm = request
-            .checksum_type
-            .as_ref()
-            .and_then(|s| ChecksumAlgorithm::from_str(s));
-
-        info!(
-            "Created download task: {} -> {:?} ({} segments, size: {})",
-            task.url,
-            task.save_path,
-            task.segments,
-            task.total_size
-                .map(|s| format_bytes(s))
-                .unwrap_or_else(|| "unknown".to_string())
-        );
+        let (disk_space, temp_space) = match file_info.total_size {
+            Some(size) => {
+                let same_volume = file_utils::same_volume(&save_dir, &temp_root);
+                let (dest_needed, temp_needed) = space_needed(size, segmented, same_volume);
+                let dest = file_utils::get_available_space(&save_dir)
+                    .await
+                    .ok()
+                    .map(|available| DiskSpaceCheck::new(available, dest_needed));
+                let temp = match temp_needed {
+                    0 => None,
+                    needed => file_utils::get_available_space(&temp_root)
+                        .await
+                        .ok()
+                        .map(|available| DiskSpaceCheck::new(available, needed)),
+                };
+                (dest, temp)
+            }
+            None => (None, None),
+        };
 
-        Ok(task)
+        let mut plan = DownloadPlan {
+            url: request.url.clone(),
+            resolved_url: (!resolved.is_unchanged()).then(|| resolved.url.clone()),
+            redirect_chain: resolved.chain,
+            probed: request.probe != ProbeMode::None,
+            total_size: file_info.total_size,
+            supports_range: file_info.supports_range,
+            content_type: file_info.content_type,
+            etag: file_info.etag,
+            requested_file_name: file_name,
+            file_name: unique_name,
+            save_path,
+            temp_root,
+            segmented,
+            category,
+            category_source,
+            segments,
+            max_retries: request
+                .max_retries
+                .or(defaults.max_retries)
+                .unwrap_or(DEFAULT_MAX_RETRIES),
+            checksum_required: defaults.require_checksum,
+            speed_limit: self.speed_limiter.effective_limit().await,
+            disk_space,
+            temp_space,
+            duplicates: Vec::new(),
+            confirmation: None,
+            held_for: Vec::new(),
+            warnings: Vec::new(),
+        };
+        plan.note_warnings(request);
+        Ok(plan)
     }
 
     // ==========================================================
@@ -186,55 +782,54 @@ impl DownloadEngine {
         Self::emit_progress(task, &progress_tx);
 
         // Check for existing resume state
-        let temp_dir = self.get_temp_dir(task);
-        let resume_data =
+        let mut temp_dir = self.get_temp_dir(task);
+        let mut resume_data =
             ResumeManager::load(&temp_dir).await?;
 
-        // Refresh file info (check if file changed on server)
-        let file_info = self
-            .http_client
-            .get_file_info(&task.url)
-            .await?;
-
-        task.total_size = file_info.total_size;
-        task.supports_range = file_info.supports_range;
-        task.etag = file_info.etag.clone();
-
-        // Determine download strategy
-        let use_multi_segment = self.should_use_multi_segment(task);
-
-        info!(
-            "Download strategy for '{}': {}",
-            task.file_name,
-            if use_multi_segment {
-                format!("multi-segment ({} segments)", task.segments)
-            } else {
-                "single-segment".to_string()
+        // A signed URL that expires mid-download is refreshed and the
+        // transfer picks up from the partial data. A 416 gets one re-probe:
+        // the file may be complete already, or have shrunk on the server.
+        let mut refreshes = 0;
+        let mut range_settled = false;
+        let result = loop {
+            let result = self
+                .probe_and_transfer(task, &mut temp_dir, resume_data, &cancel_token, &progress_tx)
+                .await;
+            match result {
+                Err(e) if Self::is_expired_url(&e) && self.has_partial_data(task, &temp_dir) => {
+                    if let Err(e) = self.refresh_expired_url(task, refreshes).await {
+                        break Err(e);
+                    }
+                    refreshes += 1;
+                    resume_data = ResumeManager::load(&temp_dir).await?;
+                }
+                Err(e) if Self::is_range_not_satisfiable(&e) && !range_settled => {
+                    range_settled = true;
+                    self.http_client.invalidate_file_info(task.transfer_url());
+                    match self.settle_unsatisfiable_range(task, &temp_dir).await {
+                        Ok(true) => break Ok(()),
+                        Ok(false) => resume_data = None,
+                        Err(e) => break Err(e),
+                    }
+                }
+                other => break other,
             }
-        );
-
-        task.status = DownloadStatus::Downloading;
-
-        let result = if use_multi_segment {
-            self.multi_segment_download(
-                task,
-                resume_data,
-                cancel_token.clone(),
-                progress_tx.clone(),
-            )
-            .await
-        } else {
-            self.single_segment_download(
-                task,
-                resume_data,
-                cancel_token.clone(),
-                progress_tx.clone(),
-            )
-            .await
         };
+        if result.as_ref().is_err_and(Self::is_content_changed) {
+            self.http_client.invalidate_file_info(task.transfer_url());
+        }
+        let result = result.map_err(|e| Self::destination_error(task, &temp_dir, e));
 
         match &result {
             Ok(()) => {
+                // The merged file may still be waiting beside the target
+                let replace_pending = task.status == DownloadStatus::CompletedPendingReplace;
+                let data_path = if replace_pending {
+                    file_in_use::pending_path(&task.save_path)
+                } else {
+                    task.save_path.clone()
+                };
+
                 // Verify checksum if provided
                 if let (Some(expected), Some(algorithm)) = (
                     &task.expected_checksum,
@@ -248,13 +843,32 @@ impl DownloadEngine {
                         task.file_name
                     );
 
-                    match ChecksumVerifier::verify(
-                        &task.save_path,
-                        expected,
-                        algorithm,
-                    )
-                    .await
-                    {
+                    let mut last_emit = std::time::Instant::now();
+                    let verify_result = {
+                        let task_ref = &*task;
+                        ChecksumVerifier::verify_with_progress(
+                            &data_path,
+                            expected,
+                            algorithm,
+                            |hashed, total| {
+                                if hashed >= total
+                                    || last_emit.elapsed().as_millis() as u64
+                                        >= PROGRESS_UPDATE_INTERVAL_MS
+                                {
+                                    last_emit = std::time::Instant::now();
+                                    Self::emit_phase_progress(
+                                        task_ref,
+                                        &progress_tx,
+                                        hashed,
+                                        total,
+                                    );
+                                }
+                            },
+                        )
+                        .await
+                    };
+
+                    match verify_result {
                         Ok(true) => {
                             info!(
                                 "Checksum verified for '{}'",
@@ -262,6 +876,13 @@ impl DownloadEngine {
                             );
                         }
                         Ok(false) | Err(_) => {
+                            // The whole file has to come again
+                            let size = tokio::fs::metadata(&data_path)
+                                .await
+                                .map_or(task.downloaded_size, |m| m.len());
+                            debug!("Discarded {} bytes: {}", size, WasteReason::ChecksumMismatch);
+                            task.wasted_bytes += size;
+                            self.http_client.invalidate_file_info(task.transfer_url());
                             task.status = DownloadStatus::Failed;
                             task.error_message = Some(
                                 "Checksum verification failed"
@@ -284,10 +905,18 @@ impl DownloadEngine {
                 // Clean up resume state
                 let _ = ResumeManager::delete(&temp_dir).await;
 
-                task.status = DownloadStatus::Completed;
                 task.completed_at =
                     Some(chrono::Local::now().naive_local());
                 task.speed = 0.0;
+                if replace_pending {
+                    task.status = DownloadStatus::CompletedPendingReplace;
+                    Self::emit_progress(task, &progress_tx);
+                    return result;
+                }
+
+                self.apply_file_permissions(task);
+
+                task.status = DownloadStatus::Completed;
                 Self::emit_progress(task, &progress_tx);
 
                 info!(
@@ -328,6 +957,191 @@ impl DownloadEngine {
         result
     }
 
+    /// Probe the server again and transfer the file, resuming from
+    /// `resume_data` when there is any
+    async fn probe_and_transfer(
+        &self,
+        task: &mut DownloadTask,
+        temp_dir: &mut PathBuf,
+        resume_data: Option<ResumeData>,
+        cancel_token: &CancellationToken,
+        progress_tx: &flume::Sender<DownloadProgress>,
+    ) -> Result<(), DownloadError> {
+        // Refresh file info (check if file changed on server). A download
+        // added without file info and nothing on disk can go ahead blind.
+        let file_info = match self.http_client.get_file_info_with_referrer(task.transfer_url(), task.referrer()).await {
+            Ok(info) => info,
+            Err(e) if task.total_size.is_none() && resume_data.is_none() => {
+                warn!("Starting '{}' without file info: {}", task.file_name, e);
+                RemoteFileInfo::unknown(task.transfer_url())
+            }
+            Err(e) => return Err(e),
+        };
+
+        task.total_size = file_info.total_size;
+        task.supports_range = file_info.supports_range;
+        task.etag = file_info.etag.clone();
+        task.note_capabilities();
+
+        // The server may report a different Content-Type than it did when
+        // the download was added; re-categorize while nothing is written yet
+        if file_info.content_type != task.content_type {
+            task.content_type = file_info.content_type.clone();
+            if resume_data.is_none()
+                && task.downloaded_size == 0
+                && self.recategorize(task).await
+            {
+                *temp_dir = self.get_temp_dir(task);
+                Self::emit_category(task, progress_tx);
+            }
+        }
+
+        // An empty file has nothing to transfer, and ranged requests for
+        // it could only come back 416
+        if task.total_size == Some(0) {
+            return self.create_empty_file(task).await;
+        }
+
+        // Determine download strategy
+        let use_multi_segment = self.should_use_multi_segment(task);
+
+        info!(
+            "Download strategy for '{}': {}",
+            task.file_name,
+            if use_multi_segment {
+                format!("multi-segment ({} segments)", task.segments)
+            } else {
+                "single-segment".to_string()
+            }
+        );
+
+        task.status = DownloadStatus::Downloading;
+
+        if use_multi_segment {
+            self.multi_segment_download(
+                task,
+                resume_data,
+                cancel_token.clone(),
+                progress_tx.clone(),
+            )
+            .await
+        } else {
+            self.single_segment_download(
+                task,
+                resume_data,
+                cancel_token.clone(),
+                progress_tx.clone(),
+            )
+            .await
+        }
+    }
+
+    /// A 403 from a URL that was working means a signed link expired
+    fn is_expired_url(error: &DownloadError) -> bool {
+        matches!(error, DownloadError::ServerError { status: 403, .. })
+    }
+
+    /// A 416: the ranges asked for lie past the end of the file
+    fn is_range_not_satisfiable(error: &DownloadError) -> bool {
+        matches!(error, DownloadError::ServerError { status: 416, .. })
+    }
+
+    /// The file on the server isn't the one that was probed, so its
+    /// cached file info can't be trusted either
+    fn is_content_changed(error: &DownloadError) -> bool {
+        matches!(
+            error,
+            DownloadError::ServerError { status: 412 | 416, .. } | DownloadError::UrlMismatch(_)
+        )
+    }
+
+    /// A file error because the drive being written to went away, so the
+    /// download can be resumed once it is back
+    fn destination_error(task: &DownloadTask, temp_dir: &Path, error: DownloadError) -> DownloadError {
+        match error {
+            DownloadError::FileError(message)
+                if task.save_path.parent().is_some_and(file_utils::volume_missing)
+                    || file_utils::volume_missing(temp_dir) =>
+            {
+                DownloadError::DestinationUnavailable(message)
+            }
+            other => other,
+        }
+    }
+
+    /// After a 416, probe the file again. True when the file on disk already
+    /// has every byte; otherwise the partial data can't belong to the file
+    /// the server has now, so it is thrown away for a restart from zero.
+    async fn settle_unsatisfiable_range(&self, task: &mut DownloadTask, temp_dir: &Path) -> Result<bool, DownloadError> {
+        let file_info = self.http_client.get_file_info_with_referrer(task.transfer_url(), task.referrer()).await?;
+        let on_disk = tokio::fs::metadata(&task.save_path).await.map(|m| m.len()).ok();
+
+        // Segments still in the temp dir mean the file was never merged
+        if let (Some(total), Some(len)) = (file_info.total_size, on_disk) {
+            if len == total && !temp_dir.exists() {
+                info!("'{}' already has all {} bytes", task.file_name, total);
+                task.total_size = Some(total);
+                task.downloaded_size = total;
+                return Ok(true);
+            }
+        }
+
+        warn!(
+            "'{}' is no longer the file that was partly downloaded (now {:?} bytes), starting over",
+            task.file_name, file_info.total_size
+        );
+        self.discard_progress(task).await?;
+        Ok(false)
+    }
+
+    /// Create a zero-length download's file without transferring anything
+    async fn create_empty_file(&self, task: &mut DownloadTask) -> Result<(), DownloadError> {
+        info!("'{}' is empty, creating it without a transfer", task.file_name);
+        if let Some(parent) = task.save_path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| DownloadError::FileError(format!("Cannot create folder: {}", e)))?;
+        }
+        tokio::fs::File::create(&task.save_path)
+            .await
+            .map_err(|e| DownloadError::FileError(format!("Failed to create file: {}", e)))?;
+        task.downloaded_size = 0;
+        task.segment_progress.clear();
+        Ok(())
+    }
+
+    /// Whether any of the file has been transferred, so the URL worked
+    fn has_partial_data(&self, task: &DownloadTask, temp_dir: &Path) -> bool {
+        task.downloaded_size > 0 || temp_dir.exists() || task.save_path.exists()
+    }
+
+    /// Swap the task's expired URL for a fresh one from its refresh hook.
+    /// Fails with `ExpiredUrl` when there is no hook or it keeps failing.
+    async fn refresh_expired_url(&self, task: &mut DownloadTask, attempts: u32) -> Result<(), DownloadError> {
+        let Some(refresh) = task.url_refresh.clone() else {
+            return Err(DownloadError::ExpiredUrl(format!(
+                "the link for '{}' has expired; paste a new one to resume",
+                task.file_name
+            )));
+        };
+        if attempts >= MAX_URL_REFRESHES {
+            return Err(DownloadError::ExpiredUrl(format!(
+                "the link for '{}' kept expiring after {} refreshes",
+                task.file_name, attempts
+            )));
+        }
+
+        info!("Link for '{}' expired, asking for a fresh one", task.file_name);
+        let fresh = self
+            .url_refresher
+            .refresh(&task.id.to_string(), task.transfer_url(), &refresh)
+            .await?;
+        task.url = fresh;
+        task.final_url = None;
+        task.redirect_chain.clear();
+        Ok(())
+    }
+
     /// Start a fresh download (no resume)
     #[allow(dead_code)]
     async fn start_fresh_download(
@@ -372,25 +1186,34 @@ impl DownloadEngine {
         _progress_tx: flume::Sender<DownloadProgress>,
     ) -> Result<(), DownloadError> {
         let client = self.http_client.clone();
-        let retry_handler = RetryHandler::new(RetryConfig::default());
+        let retry_handler = self.retry_handler(task, &cancel_token);
+        let limiter = self.speed_limiter.for_download(task.id, task.priority);
 
-        let url = task.url.clone();
+        let url = task.transfer_url().to_string();
+        let referrer = task.referrer().map(str::to_string);
         let save_path = task.save_path.clone();
+        let waste = WasteCounter::default();
 
         // Use retry handler for the actual download
         let result = retry_handler.execute(
             &format!("single-segment download '{}'", task.file_name),
             || {
                 let client = client.clone();
+                let limiter = limiter.clone();
                 let url = url.clone();
+                let referrer = referrer.clone();
                 let save_path = save_path.clone();
+                let waste = waste.clone();
                 let cancel = cancel_token.clone();
 
                 async move {
                     Self::do_single_download(
                         client,
+                        limiter,
                         &url,
+                        referrer.as_deref(),
                         &save_path,
+                        &waste,
                         cancel,
                     )
                     .await
@@ -398,10 +1221,12 @@ impl DownloadEngine {
             },
         )
         .await;
+        task.wasted_bytes += waste.total();
 
         match result {
             Ok(total_bytes) => {
                 task.downloaded_size = total_bytes;
+                task.total_size.get_or_insert(total_bytes);
                 Ok(())
             }
             Err(e) => {
@@ -410,15 +1235,18 @@ impl DownloadEngine {
         }
     }
 
-    /// Perform the actual single-segment HTTP download
+    /// Perform the actual single-segment HTTP download. Every attempt
+    /// starts the file over, so what a failed one wrote is wasted.
     async fn do_single_download(
         client: HttpClient,
+        speed_limiter: SpeedLimiter,
         url: &str,
+        referrer: Option<&str>,
         save_path: &PathBuf,
+        waste: &WasteCounter,
         cancel_token: CancellationToken,
     ) -> Result<u64, DownloadError> {
-        let response = client.get(url).await
-            .map_err(|e| DownloadError::NetworkError(e.to_string()))?;
+        let response = client.get(url, referrer).await?;
 
         let status = response.status();
         if !status.is_success() {
@@ -445,25 +1273,26 @@ impl DownloadEngine {
             tokio::select! {
                 _ = cancel_token.cancelled() => {
                     let _ = file.flush().await;
+                    waste.add(total_bytes, WasteReason::FailedAttempt);
                     return Err(DownloadError::Cancelled);
                 }
 
                 chunk = stream.next() => {
                     match chunk {
                         Some(Ok(data)) => {
-                            file.write_all(&data)
-                                .await
-                                .map_err(|e| {
-                                    DownloadError::FileError(
-                                        format!("Write error: {}", e)
-                                    )
-                                })?;
+                            speed_limiter.throttle(data.len()).await;
+
+                            if let Err(e) = file.write_all(&data).await {
+                                waste.add(total_bytes, WasteReason::FailedAttempt);
+                                return Err(DownloadError::FileError(format!("Write error: {}", e)));
+                            }
 
                             total_bytes += data.len() as u64;
                         }
 
                         Some(Err(e)) => {
                             let _ = file.flush().await;
+                            waste.add(total_bytes, WasteReason::FailedAttempt);
                             return Err(
                                 DownloadError::NetworkError(
                                     e.to_string()
@@ -494,9 +1323,9 @@ impl DownloadEngine {
     async fn multi_segment_download(
         &self,
         task: &mut DownloadTask,
-        _resume_data: Option<ResumeData>,
+        resume_data: Option<ResumeData>,
         cancel_token: CancellationToken,
-        _progress_tx: flume::Sender<DownloadProgress>,
+        progress_tx: flume::Sender<DownloadProgress>,
     ) -> Result<(), DownloadError> {
         let total_size = task.total_size.ok_or(
             DownloadError::Unknown(
@@ -505,8 +1334,34 @@ impl DownloadEngine {
             ),
         )?;
 
-        // Create chunks
-        let chunks = ChunkManager::split(total_size, task.segments);
+        let temp_dir = self.get_temp_dir(task);
+
+        // Segments written for a file of another size hold the wrong
+        // ranges; resuming them would ask for bytes past the end
+        let resume_data = match resume_data {
+            Some(data) if data.total_size.is_some_and(|size| size != total_size) => {
+                warn!(
+                    "'{}' changed size on the server ({} to {}), starting over",
+                    task.file_name,
+                    format_bytes(data.total_size.unwrap_or_default()),
+                    format_bytes(total_size)
+                );
+                self.discard_progress(task).await?;
+                None
+            }
+            other => other,
+        };
+
+        // Partially written segments must keep the ranges they were
+        // started with, so reuse the saved layout when resuming
+        let chunks = match resume_data.filter(|d| d.total_size == Some(total_size)) {
+            Some(data) => data.chunks(total_size, task.segments),
+            // Segments left by a version that did not save a layout
+            None if temp_dir.join("segment_0").exists() => {
+                ChunkManager::split_for_layout(LEGACY_LAYOUT_VERSION, total_size, task.segments)
+            }
+            None => ChunkManager::split(total_size, task.segments),
+        };
 
         let num_segments = chunks.len();
         info!(
@@ -516,7 +1371,6 @@ impl DownloadEngine {
         );
 
         // Create temp directory for segments
-        let temp_dir = self.get_temp_dir(task);
 
         tokio::fs::create_dir_all(&temp_dir)
             .await
@@ -527,30 +1381,80 @@ impl DownloadEngine {
                 ))
             })?;
 
-        // Spawn download tasks for each segment
+        // A crash can leave segment files ahead of what was recorded, or
+        // past their chunk; start from what is actually on disk
+        let waste = WasteCounter::default();
+        let segments = ResumeManager::reconcile_segments(&temp_dir, &chunks, &waste).await;
+        let on_disk: u64 = segments.iter().map(|s| s.downloaded_bytes).sum();
+        if on_disk > 0 {
+            info!("Resuming '{}' with {} already on disk", task.file_name, format_bytes(on_disk));
+            task.downloaded_size = on_disk;
+            Self::emit_progress(task, &progress_tx);
+        }
+
+        // Record the layout so a later resume uses the same geometry
+        let layout = ResumeData {
+            download_id: task.id,
+            url: task.url.clone(),
+            file_name: task.file_name.clone(),
+            save_path: task.save_path.clone(),
+            total_size: Some(total_size),
+            segments,
+            etag: task.etag.clone(),
+            created_at: chrono::Local::now().naive_local().to_string(),
+            layout_version: CURRENT_LAYOUT_VERSION,
+        };
+        if let Err(e) = ResumeManager::save(&temp_dir, &layout).await {
+            warn!("Failed to save segment layout: {}", e);
+        }
+
+        // Spawn download tasks for each segment. The gate lets at most
+        // `task.segments` of them transfer at once; the layout may have
+        // more when the count was lowered after the download started.
         let mut handles = Vec::with_capacity(num_segments);
+        let retry_handler = self.retry_handler(task, &cancel_token);
+        let gate = SegmentGate::new((task.segments as usize).min(num_segments));
+        self.segment_gates.lock().insert(task.id, gate.clone());
 
         for chunk in &chunks {
             let segment_dl = SegmentDownloader::new(
                 self.http_client.clone(),
-                self.speed_limiter.clone(),
-                RetryConfig::default(),
+                self.speed_limiter.for_download(task.id, task.priority),
+                retry_handler.clone(),
+                waste.clone(),
             );
 
-            let url = task.url.clone();
+            let url = task.transfer_url().to_string();
+            let referrer = task.referrer().map(str::to_string);
             let chunk_clone = chunk.clone();
             let temp_path =
                 temp_dir.join(format!("segment_{}", chunk.id));
             let cancel = cancel_token.clone();
+            let gate = gate.clone();
 
             let handle = tokio::spawn(async move {
-                segment_dl.download_segment(
-                    &url,
-                    &chunk_clone,
-                    &temp_path,
-                    cancel,
-                )
-                .await
+                loop {
+                    let slot = gate
+                        .acquire(chunk_clone.id, &cancel)
+                        .await
+                        .ok_or(DownloadError::Cancelled)?;
+                    let result = segment_dl.download_segment(
+                        &url,
+                        referrer.as_deref(),
+                        &chunk_clone,
+                        &temp_path,
+                        slot.token(),
+                    )
+                    .await;
+
+                    // Preempted segments, and ones interrupted by a network
+                    // change, wait for a slot and resume from what they wrote
+                    if result.is_err() && slot.was_preempted() {
+                        debug!("Segment {} interrupted, resuming from its partial file", chunk_clone.id);
+                        continue;
+                    }
+                    return result;
+                }
             });
 
             handles.push((chunk.id, handle));
@@ -587,6 +1491,14 @@ impl DownloadEngine {
                 }
             }
         }
+        task.wasted_bytes += waste.total();
+        {
+            // A resume may already have registered a new gate
+            let mut gates = self.segment_gates.lock();
+            if gates.get(&task.id).is_some_and(|g| Arc::ptr_eq(g, &gate)) {
+                gates.remove(&task.id);
+            }
+        }
 
         // Check for errors
         if !segment_errors.is_empty() {
@@ -597,6 +1509,15 @@ impl DownloadEngine {
                 }
             }
 
+            // An expired link or a shrunken file is the download's problem,
+            // not the segment's
+            if let Some(pos) = segment_errors
+                .iter()
+                .position(|(_, e)| Self::is_expired_url(e) || Self::is_range_not_satisfiable(e))
+            {
+                return Err(segment_errors.swap_remove(pos).1);
+            }
+
             let (seg_id, error) =
                 segment_errors.into_iter().next().unwrap();
             return Err(DownloadError::SegmentFailed { 
@@ -608,11 +1529,20 @@ impl DownloadEngine {
         // All segments complete - merge files
         info!("All segments complete. Merging...");
         task.status = DownloadStatus::Merging;
+        Self::emit_progress(task, &progress_tx);
 
+        // Merge beside the target, so a file already there is only
+        // replaced once no other program has it open
+        let pending = file_in_use::pending_path(&task.save_path);
+        if !file_utils::same_volume(&temp_dir, &pending) {
+            info!("Merging {} across volumes from {}", task.file_name, temp_dir.display());
+        }
         self.merge_segments(
+            task,
+            &pending,
             &temp_dir,
-            &task.save_path,
             &chunks,
+            &progress_tx,
         )
         .await?;
 
@@ -625,20 +1555,55 @@ impl DownloadEngine {
 
         task.downloaded_size = total_size;
 
+        if !Self::replace_when_free(&pending, &task.save_path).await? {
+            warn!(
+                "'{}' is open in another program; the download waits to replace it",
+                task.save_path.display()
+            );
+            task.status = DownloadStatus::CompletedPendingReplace;
+        }
+
         Ok(())
     }
 
+    /// Move a finished file from `pending` over `target`, unless another
+    /// program has `target` open. False when the swap has to wait.
+    pub async fn replace_when_free(pending: &Path, target: &Path) -> Result<bool, DownloadError> {
+        let check = target.to_path_buf();
+        let in_use = tokio::task::spawn_blocking(move || file_in_use::is_in_use(&check))
+            .await
+            .unwrap_or(false);
+        if in_use {
+            return Ok(false);
+        }
+        tokio::fs::rename(pending, target).await.map_err(|e| {
+            DownloadError::FileError(format!(
+                "Failed to move the finished file into place: {}",
+                e
+            ))
+        })?;
+        Ok(true)
+    }
+
     // ==========================================================
     //  MERGE SEGMENTS
     // ==========================================================
 
-    /// Merge downloaded segments into the final file
+    /// Merge downloaded segments into the final file,
+    /// emitting merge progress as bytes are copied
     async fn merge_segments(
         &self,
+        task: &DownloadTask,
+        output_path: &Path,
         temp_dir: &PathBuf,
-        output_path: &PathBuf,
         chunks: &[Chunk],
+        progress_tx: &flume::Sender<DownloadProgress>,
     ) -> Result<(), DownloadError> {
+        let total_bytes: u64 = chunks.iter().map(|c| c.size()).sum();
+        let mut merged_bytes: u64 = 0;
+        let mut buffer = vec![0u8; MERGE_BUFFER_SIZE];
+        let mut last_emit = std::time::Instant::now();
+
         let mut output =
             tokio::fs::File::create(output_path)
                 .await
@@ -674,16 +1639,49 @@ impl DownloadEngine {
                         ))
                     })?;
 
-            let bytes_copied =
-                tokio::io::copy(&mut segment_file, &mut output)
+            // Copy in buffered blocks so progress can be reported
+            let mut bytes_copied: u64 = 0;
+            loop {
+                let bytes_read = segment_file
+                    .read(&mut buffer)
+                    .await
+                    .map_err(|e| {
+                        DownloadError::MergeFailed(format!(
+                            "Read error for segment {}: {}",
+                            chunk.id, e
+                        ))
+                    })?;
+
+                if bytes_read == 0 {
+                    break;
+                }
+
+                output
+                    .write_all(&buffer[..bytes_read])
                     .await
                     .map_err(|e| {
                         DownloadError::MergeFailed(format!(
-                            "Copy error for segment {}: {}",
+                            "Write error for segment {}: {}",
                             chunk.id, e
                         ))
                     })?;
 
+                bytes_copied += bytes_read as u64;
+                merged_bytes += bytes_read as u64;
+
+                if last_emit.elapsed().as_millis() as u64
+                    >= PROGRESS_UPDATE_INTERVAL_MS
+                {
+                    last_emit = std::time::Instant::now();
+                    Self::emit_phase_progress(
+                        task,
+                        progress_tx,
+                        merged_bytes,
+                        total_bytes,
+                    );
+                }
+            }
+
             debug!(
                 "Merged segment {}: {} bytes",
                 chunk.id, bytes_copied
@@ -697,6 +1695,13 @@ impl DownloadEngine {
             ))
         })?;
 
+        Self::emit_phase_progress(
+            task,
+            progress_tx,
+            merged_bytes,
+            total_bytes,
+        );
+
         info!(
             "Successfully merged {} segments into {:?}",
             chunks.len(),
@@ -710,31 +1715,93 @@ impl DownloadEngine {
     //  HELPERS
     // ==========================================================
 
+    /// Clean up after a download whose task died without finishing, and
+    /// record from its segment files how far it got, so resuming continues
+    /// from there. False when it left no segment layout to read.
+    pub async fn recover_interrupted(&self, task: &mut DownloadTask) -> bool {
+        self.segment_gates.lock().remove(&task.id);
+        let temp_dir = self.get_temp_dir(task);
+        let data = match ResumeManager::load(&temp_dir).await {
+            Ok(Some(data)) => data,
+            Ok(None) => return false,
+            Err(e) => {
+                warn!("Failed to read resume data for {}: {}", task.id, e);
+                return false;
+            }
+        };
+        let Some(total_size) = data.total_size.or(task.total_size) else {
+            return false;
+        };
+
+        let chunks = data.chunks(total_size, task.segments);
+        let waste = WasteCounter::default();
+        let segments = ResumeManager::reconcile_segments(&temp_dir, &chunks, &waste).await;
+        task.downloaded_size = segments.iter().map(|s| s.downloaded_bytes).sum();
+        task.wasted_bytes += waste.total();
+        let layout = ResumeData { segments, ..data };
+        if let Err(e) = ResumeManager::save(&temp_dir, &layout).await {
+            warn!("Failed to save resume data for {}: {}", task.id, e);
+        }
+        true
+    }
+
     fn get_temp_dir(&self, task: &DownloadTask) -> PathBuf {
-        task.save_path
-            .parent()
+        task.temp_dir
+            .as_deref()
+            .or(task.save_path.parent())
             .unwrap_or(&self.default_download_dir)
             .join(format!(".sd_{}", task.id))
     }
 
-    /// Decide whether to use multi-segment download
-    fn should_use_multi_segment(
-        &self,
-        task: &DownloadTask,
-    ) -> bool {
-        // Must support range requests
-        if !task.supports_range {
+    /// Re-detect the category of a download that has not started writing.
+    /// Moves the pending save path into the category folder when one is
+    /// configured. Returns true if the category changed.
+    async fn recategorize(&self, task: &mut DownloadTask) -> bool {
+        if !matches!(task.category.as_deref(), None | Some("default")) {
             return false;
         }
 
-        // Must know the file size
-        let total_size = match task.total_size {
-            Some(size) => size,
-            None => return false,
+        let detected = match self
+            .detect_category(&task.file_name, task.content_type.as_deref())
+        {
+            Some(category) if category != "default" => category,
+            _ => return false,
         };
 
-        // Must be larger than minimum size
-        if total_size < MIN_SIZE_FOR_SEGMENTS {
+        if let Some(dir) = self.category_dir(&detected) {
+            if task.save_path.parent() != Some(dir.as_path())
+                && !task.save_path.exists()
+            {
+                match tokio::fs::create_dir_all(&dir).await {
+                    Ok(()) => {
+                        let unique_name =
+                            self.unique_filename(&dir, &task.file_name);
+                        task.save_path = dir.join(&unique_name);
+                        task.file_name = unique_name;
+                    }
+                    Err(e) => warn!(
+                        "Cannot create category dir {:?}: {}",
+                        dir, e
+                    ),
+                }
+            }
+        }
+
+        info!(
+            "Re-categorized '{}' as '{}' from Content-Type {:?}",
+            task.file_name, detected, task.content_type
+        );
+        task.category = Some(detected);
+        true
+    }
+
+    /// Decide whether to use multi-segment download
+    fn should_use_multi_segment(
+        &self,
+        task: &DownloadTask,
+    ) -> bool {
+        // Must support range requests, know the size and be big enough
+        if SingleSegmentReason::for_file(task.supports_range, task.total_size).is_some() {
             return false;
         }
 
@@ -793,6 +1860,54 @@ impl DownloadEngine {
             status: task.status.clone(),
             percent: task.percent(),
             error_message: task.error_message.clone(),
+            phase_progress: None,
+            category: None,
+        });
+    }
+
+    /// Emit a progress update announcing the task's category
+    fn emit_category(
+        task: &DownloadTask,
+        tx: &flume::Sender<DownloadProgress>,
+    ) {
+        let _ = tx.send(DownloadProgress {
+            id: task.id,
+            downloaded_size: task.downloaded_size,
+            total_size: task.total_size,
+            speed: task.speed,
+            eta: task.eta,
+            status: task.status.clone(),
+            percent: task.percent(),
+            error_message: None,
+            phase_progress: None,
+            category: task.category.clone(),
+        });
+    }
+
+    /// Emit a progress update for the Merging/Verifying phase
+    fn emit_phase_progress(
+        task: &DownloadTask,
+        tx: &flume::Sender<DownloadProgress>,
+        done: u64,
+        total: u64,
+    ) {
+        let fraction = if total > 0 {
+            (done as f64 / total as f64).min(1.0)
+        } else {
+            1.0
+        };
+
+        let _ = tx.send(DownloadProgress {
+            id: task.id,
+            downloaded_size: task.downloaded_size,
+            total_size: task.total_size,
+            speed: 0.0,
+            eta: None,
+            status: task.status.clone(),
+            percent: task.percent(),
+            error_message: None,
+            phase_progress: Some(fraction),
+            category: None,
         });
     }
 }
@@ -813,7 +1928,7 @@ fn format_bytes(bytes: u64) -> String {
 /// NOTE: This type is shared with the Tauri commands layer
 /// (`src-tauri/src/commands/download_commands.rs`). Keep the
 /// fields in sync with that struct.
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
 pub struct AddDownloadRequest {
     pub url: String,
     pub save_path: Option<String>,
@@ -823,7 +1938,43 @@ pub struct AddDownloadRequest {
     pub checksum_type: Option<String>,
     pub file_name: Option<String>,
     pub category: Option<String>,
-    pub priority: Option<u32>,
+    pub priority: Option<DownloadPriority>,
+    #[serde(default)]
+    pub notes: Option<PrivateText>,
+    #[serde(default)]
+    pub source_page_url: Option<String>,
+    #[serde(default)]
+    pub source_page_title: Option<String>,
+    /// Backoff overrides for this download
+    #[serde(default)]
+    pub retry: Option<RetryOverrides>,
+    /// Who asked for the download; automated sources may need confirmation
+    #[serde(default)]
+    pub source: DownloadSource,
+    /// Whether the add waits on, tolerates or skips the file info probe
+    #[serde(default)]
+    pub probe: ProbeMode,
+    /// Folder for the segment data instead of beside the file
+    #[serde(default)]
+    pub temp_dir: Option<String>,
+    /// Where to get a fresh URL if a signed one expires mid-download
+    #[serde(default)]
+    pub url_refresh: Option<UrlRefresh>,
+    /// Run on the file once it completes, e.g. a conversion
+    #[serde(default)]
+    pub post_action: Option<PostAction>,
+    /// Plan the download and report the plan without adding it
+    #[serde(default)]
+    pub simulate: bool,
+    /// For playlist URLs: one yt-dlp process for the whole playlist, or
+    /// one per entry. Unset downloads only the linked video.
+    #[serde(default)]
+    pub playlist_mode: Option<PlaylistMode>,
+    /// The folders come from a download already in the list, approved
+    /// when it was first added; the allowed folders may have changed
+    /// since. Never taken from a caller.
+    #[serde(skip)]
+    pub existing_location: bool,
 
     // YouTube-specific fields
     pub youtube_format: Option<String>,        // "video" or "audio"
@@ -832,3 +1983,208 @@ pub struct AddDownloadRequest {
     pub youtube_audio_format: Option<String>,  // "mp3", "aac", "flac"
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+    use tokio::net::TcpListener;
+
+    /// Answer every request with `respond(method)`
+    async fn mock_server(respond: impl Fn(&str) -> String + Send + Sync + 'static) -> String {
+        let respond = Arc::new(respond);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let respond = respond.clone();
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 1024];
+                    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                        match socket.read(&mut buf).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => request.extend_from_slice(&buf[..n]),
+                        }
+                    }
+                    let head = String::from_utf8_lossy(&request).to_string();
+                    let method = head.split(' ').next().unwrap_or_default();
+                    let _ = socket.write_all(respond(method).as_bytes()).await;
+                });
+            }
+        });
+        format!("http://{}/files/notes.txt", addr)
+    }
+
+    fn head_ok(length: u64) -> String {
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nAccept-Ranges: bytes\r\nConnection: close\r\n\r\n",
+            length
+        )
+    }
+
+    const UNSATISFIABLE: &str = "HTTP/1.1 416 Range Not Satisfiable\r\nContent-Range: bytes */5\r\n\
+        Content-Length: 0\r\nConnection: close\r\n\r\n";
+
+    fn engine_and_task(url: String) -> (DownloadEngine, DownloadTask, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("afk-dunld-engine-{}", uuid::Uuid::new_v4()));
+        let engine = DownloadEngine::new(None, None, Some(dir.clone())).unwrap();
+        let task = DownloadTask::new(url, "notes.txt".to_string(), dir.join("notes.txt"), 4);
+        (engine, task, dir)
+    }
+
+    async fn run(engine: &DownloadEngine, task: &mut DownloadTask) -> Result<(), DownloadError> {
+        let (tx, _rx) = flume::unbounded();
+        engine.start_download(task, CancellationToken::new(), tx).await
+    }
+
+    #[tokio::test]
+    async fn test_empty_file_completes_without_a_transfer() {
+        let url = mock_server(|method| match method {
+            "HEAD" => head_ok(0),
+            _ => "HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+        })
+        .await;
+        let (engine, mut task, dir) = engine_and_task(url);
+
+        run(&engine, &mut task).await.unwrap();
+        assert_eq!(task.status, DownloadStatus::Completed);
+        assert_eq!(std::fs::metadata(&task.save_path).unwrap().len(), 0);
+        assert_eq!(task.percent(), 100.0);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_416_with_every_byte_on_disk_completes() {
+        let url = mock_server(|method| match method {
+            "HEAD" => head_ok(5),
+            _ => UNSATISFIABLE.to_string(),
+        })
+        .await;
+        let (engine, mut task, dir) = engine_and_task(url);
+        std::fs::write(&task.save_path, b"hello").unwrap();
+
+        run(&engine, &mut task).await.unwrap();
+        assert_eq!(task.status, DownloadStatus::Completed);
+        assert_eq!(task.downloaded_size, 5);
+        assert_eq!(std::fs::read(&task.save_path).unwrap(), b"hello");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_416_for_a_shrunken_file_restarts_once() {
+        static GETS: AtomicU32 = AtomicU32::new(0);
+        let url = mock_server(|method| match method {
+            "HEAD" => head_ok(5),
+            // The first GET still sees the old resume point
+            _ if GETS.fetch_add(1, Ordering::SeqCst) == 0 => UNSATISFIABLE.to_string(),
+            _ => "HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello".to_string(),
+        })
+        .await;
+        let (engine, mut task, dir) = engine_and_task(url);
+        std::fs::write(&task.save_path, b"old partial data").unwrap();
+
+        run(&engine, &mut task).await.unwrap();
+        assert_eq!(task.status, DownloadStatus::Completed);
+        assert_eq!(std::fs::read(&task.save_path).unwrap(), b"hello");
+        // The 416 was settled, not retried
+        assert_eq!(GETS.load(Ordering::SeqCst), 2);
+        assert_eq!(task.wasted_bytes, 16);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    fn get_ok(body: &str) -> String {
+        format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body)
+    }
+
+    #[tokio::test]
+    async fn test_checksum_mismatch_wastes_the_whole_file() {
+        let url = mock_server(|method| match method {
+            "HEAD" => head_ok(5),
+            _ => get_ok("hello"),
+        })
+        .await;
+        let (engine, mut task, dir) = engine_and_task(url);
+        task.expected_checksum = Some("0".repeat(32));
+        task.checksum_algorithm = Some(crate::core::checksum::ChecksumAlgorithm::Md5);
+
+        assert!(run(&engine, &mut task).await.is_err());
+        assert_eq!(task.wasted_bytes, 5);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_failed_single_stream_attempt_is_wasted() {
+        let url = mock_server(|method| match method {
+            "HEAD" => "HTTP/1.1 200 OK\r\nContent-Length: 10\r\nConnection: close\r\n\r\n".to_string(),
+            // The connection closes five bytes short
+            _ => "HTTP/1.1 200 OK\r\nContent-Length: 10\r\nConnection: close\r\n\r\nhello".to_string(),
+        })
+        .await;
+        let (engine, mut task, dir) = engine_and_task(url);
+
+        assert!(run(&engine, &mut task).await.is_err());
+        assert_eq!(task.wasted_bytes, 5);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_segments_survive_a_server_ignoring_ranges() {
+        let body: String = (0..MIN_SIZE_FOR_SEGMENTS).map(|i| (b'a' + (i % 26) as u8) as char).collect();
+        let response = get_ok(&body);
+        let url = mock_server(move |method| match method {
+            "HEAD" => head_ok(MIN_SIZE_FOR_SEGMENTS),
+            // Every range request gets the whole file
+            _ => response.clone(),
+        })
+        .await;
+        let (engine, mut task, dir) = engine_and_task(url);
+
+        run(&engine, &mut task).await.unwrap();
+        assert_eq!(task.status, DownloadStatus::Completed);
+        assert_eq!(std::fs::read(&task.save_path).unwrap(), body.as_bytes());
+        // At least the bytes ahead of each segment's start were skipped
+        let segment = MIN_SIZE_FOR_SEGMENTS / 4;
+        assert!(task.wasted_bytes >= segment + 2 * segment + 3 * segment);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_preview_add_and_start_probe_once() {
+        static HEADS: AtomicU32 = AtomicU32::new(0);
+        let url = mock_server(|method| match method {
+            "HEAD" => {
+                HEADS.fetch_add(1, Ordering::SeqCst);
+                head_ok(5)
+            }
+            _ => get_ok("hello"),
+        })
+        .await;
+        let (engine, _, dir) = engine_and_task(url.clone());
+        let request: AddDownloadRequest = serde_json::from_value(serde_json::json!({
+            "url": url,
+            "save_path": null,
+            "segments": 1,
+            "max_retries": null,
+            "expected_checksum": null,
+            "checksum_type": null,
+            "file_name": null,
+            "category": null,
+            "priority": null,
+            "youtube_format": null,
+            "youtube_quality": null,
+            "youtube_video_format": null,
+            "youtube_audio_format": null,
+        }))
+        .unwrap();
+
+        let preview = engine.get_file_info(&url).await.unwrap();
+        assert_eq!(preview.total_size, Some(5));
+        let mut task = engine.create_task(&request).await.unwrap();
+        run(&engine, &mut task).await.unwrap();
+        assert_eq!(task.status, DownloadStatus::Completed);
+        assert_eq!(HEADS.load(Ordering::SeqCst), 1);
+        assert_eq!(engine.http_client.file_info_cache_stats().hits, 2);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
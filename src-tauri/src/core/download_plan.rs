@@ -0,0 +1,279 @@
+// src-tauri/src/core/download_plan.rs
+
+use serde::Serialize;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use crate::core::checksum::ChecksumAlgorithm;
+use crate::core::download_engine::AddDownloadRequest;
+use crate::core::download_task::DownloadTask;
+use crate::core::resource_guard::BlockingCondition;
+use crate::core::size_guard::ConfirmationReason;
+use crate::utils::format_utils::format_bytes;
+
+/// Where a download's category came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CategorySource {
+    Requested,
+    Detected,
+    /// Automatic categorization is off and none was asked for
+    Unset,
+}
+
+/// Free space where the file will be saved, against its size
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskSpaceCheck {
+    pub available: u64,
+    pub required: u64,
+    pub sufficient: bool,
+}
+
+impl DiskSpaceCheck {
+    pub fn new(available: u64, required: u64) -> Self {
+        Self { available, required, sufficient: required <= available }
+    }
+}
+
+/// Bytes a download of `size` needs at its destination and in its temp
+/// folder. Segments are written whole to the temp folder and merged into
+/// a new file at the destination, so with both on one volume a segmented
+/// download briefly needs twice its size there.
+pub fn space_needed(size: u64, segmented: bool, same_volume: bool) -> (u64, u64) {
+    match (segmented, same_volume) {
+        (false, _) => (size, 0),
+        (true, true) => (size.saturating_mul(2), 0),
+        (true, false) => (size, size),
+    }
+}
+
+/// Every decision adding a download makes before any data moves. The real
+/// add builds its task from the plan, so a simulated add reports exactly
+/// what would happen.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadPlan {
+    pub url: String,
+    /// Where shorteners and redirect pages led, when somewhere else
+    pub resolved_url: Option<String>,
+    pub redirect_chain: Vec<String>,
+    /// Whether the server was asked about the file
+    pub probed: bool,
+    pub total_size: Option<u64>,
+    pub supports_range: bool,
+    pub content_type: Option<String>,
+    pub etag: Option<String>,
+    /// Name before avoiding files already in the folder
+    pub requested_file_name: String,
+    pub file_name: String,
+    pub save_path: PathBuf,
+    /// Folder the segment data goes in: beside the file unless the
+    /// request names another
+    pub temp_root: PathBuf,
+    /// Whether the file downloads in segments, through the temp folder
+    pub segmented: bool,
+    pub category: Option<String>,
+    pub category_source: CategorySource,
+    pub segments: u8,
+    pub max_retries: u32,
+    pub checksum_required: bool,
+    /// Bytes per second all downloads share, if limited
+    pub speed_limit: Option<u64>,
+    /// Space at the destination, including the temp folder's share when
+    /// both are on one volume. Only checked when the size is known.
+    pub disk_space: Option<DiskSpaceCheck>,
+    /// Space in a temp folder on another volume
+    pub temp_space: Option<DiskSpaceCheck>,
+    /// Downloads already added for the same URL
+    pub duplicates: Vec<Uuid>,
+    /// Why the download would wait for the user
+    pub confirmation: Option<ConfirmationReason>,
+    /// Conditions the download would wait on before starting
+    pub held_for: Vec<BlockingCondition>,
+    pub warnings: Vec<String>,
+}
+
+impl DownloadPlan {
+    /// Collect warnings from the decisions made
+    pub fn note_warnings(&mut self, request: &AddDownloadRequest) {
+        if self.checksum_required && request.expected_checksum.is_none() {
+            self.warnings.push(format!(
+                "'{}' is in a category that requires a checksum but none was given",
+                self.file_name
+            ));
+        }
+        self.warnings.extend(self.space_shortfalls());
+        if self.file_name != self.requested_file_name {
+            self.warnings.push(format!(
+                "'{}' already exists, saving as '{}'",
+                self.requested_file_name, self.file_name
+            ));
+        }
+    }
+
+    /// What the destination and temp volumes are short of, if anything
+    pub fn space_shortfalls(&self) -> Vec<String> {
+        let mut shortfalls = Vec::new();
+        if let Some(check) = self.disk_space.filter(|c| !c.sufficient) {
+            shortfalls.push(format!(
+                "Only {} free where {} is needed",
+                format_bytes(check.available),
+                format_bytes(check.required)
+            ));
+        }
+        if let Some(check) = self.temp_space.filter(|c| !c.sufficient) {
+            shortfalls.push(format!(
+                "Only {} free in the temp folder {} where {} is needed",
+                format_bytes(check.available),
+                self.temp_root.display(),
+                format_bytes(check.required)
+            ));
+        }
+        shortfalls
+    }
+
+    /// The task the real add creates
+    pub fn into_task(self, request: &AddDownloadRequest) -> DownloadTask {
+        let mut task = DownloadTask::new(request.url.clone(), self.file_name, self.save_path, self.segments);
+
+        if let Some(resolved) = self.resolved_url {
+            task.final_url = Some(resolved);
+            task.redirect_chain = self.redirect_chain;
+        }
+        task.total_size = self.total_size;
+        task.supports_range = self.supports_range;
+        task.content_type = self.content_type;
+        task.etag = self.etag;
+        // Unknown until the transfer when the server wasn't asked
+        if self.probed {
+            task.note_capabilities();
+        }
+        task.retry_count = self.max_retries;
+        task.expected_checksum = request.expected_checksum.clone();
+        task.checksum_algorithm = request
+            .checksum_type
+            .as_ref()
+            .and_then(|s| ChecksumAlgorithm::from_str(s));
+        task.category = self.category;
+        task.temp_dir = request.temp_dir.as_ref().map(PathBuf::from);
+        task.notes = request.notes.clone();
+        task.source_page_url = request.source_page_url.clone();
+        task.source_page_title = request.source_page_title.clone();
+        task.retry_policy = request.retry.clone();
+        task.url_refresh = request.url_refresh.clone();
+        task.post_action = request.post_action.clone();
+        task.priority = request.priority.unwrap_or_default();
+        task.checksum_required = self.checksum_required;
+
+        tracing::info!(
+            "Created download task: {} -> {:?} ({} segments, size: {})",
+            task.url,
+            task.save_path,
+            task.segments,
+            task.total_size
+                .map(format_bytes)
+                .unwrap_or_else(|| "unknown".to_string())
+        );
+        task
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> AddDownloadRequest {
+        serde_json::from_value(serde_json::json!({
+            "url": "https://example.com/a.iso",
+            "save_path": null,
+            "segments": null,
+            "max_retries": null,
+            "expected_checksum": null,
+            "checksum_type": null,
+            "file_name": null,
+            "category": null,
+            "priority": null,
+            "youtube_format": null,
+            "youtube_quality": null,
+            "youtube_video_format": null,
+            "youtube_audio_format": null,
+        }))
+        .unwrap()
+    }
+
+    fn plan() -> DownloadPlan {
+        DownloadPlan {
+            url: "https://example.com/a.iso".to_string(),
+            resolved_url: Some("https://cdn.example.com/a.iso".to_string()),
+            redirect_chain: vec!["https://example.com/a.iso".to_string()],
+            probed: true,
+            total_size: Some(4096),
+            supports_range: true,
+            content_type: Some("application/octet-stream".to_string()),
+            etag: None,
+            requested_file_name: "a.iso".to_string(),
+            file_name: "a (1).iso".to_string(),
+            save_path: PathBuf::from("/dl/a (1).iso"),
+            temp_root: PathBuf::from("/scratch"),
+            segmented: true,
+            category: Some("software".to_string()),
+            category_source: CategorySource::Detected,
+            segments: 8,
+            max_retries: 3,
+            checksum_required: true,
+            speed_limit: None,
+            disk_space: Some(DiskSpaceCheck::new(1024, 4096)),
+            temp_space: Some(DiskSpaceCheck::new(8192, 4096)),
+            duplicates: Vec::new(),
+            confirmation: None,
+            held_for: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_task_follows_plan() {
+        let request = request();
+        assert!(!request.simulate);
+
+        let task = plan().into_task(&request);
+        assert_eq!(task.url, "https://example.com/a.iso");
+        assert_eq!(task.final_url.as_deref(), Some("https://cdn.example.com/a.iso"));
+        assert_eq!(task.file_name, "a (1).iso");
+        assert_eq!(task.save_path, PathBuf::from("/dl/a (1).iso"));
+        assert_eq!(task.segments, 8);
+        assert_eq!(task.retry_count, 3);
+        assert_eq!(task.category.as_deref(), Some("software"));
+    }
+
+    #[test]
+    fn test_plan_warnings() {
+        let mut plan = plan();
+        plan.note_warnings(&request());
+        assert_eq!(plan.warnings.len(), 3);
+        assert!(!plan.disk_space.unwrap().sufficient);
+
+        let json = serde_json::to_value(&plan).unwrap();
+        assert_eq!(json["categorySource"], "detected");
+        assert_eq!(json["diskSpace"]["sufficient"], false);
+        assert_eq!(json["tempSpace"]["sufficient"], true);
+    }
+
+    #[test]
+    fn test_space_needed_per_volume() {
+        // Written straight to the destination
+        assert_eq!(space_needed(100, false, false), (100, 0));
+        // Segments and the merged file side by side
+        assert_eq!(space_needed(100, true, true), (200, 0));
+        // Segments on the scratch volume, the merged file on the other
+        assert_eq!(space_needed(100, true, false), (100, 100));
+
+        let mut plan = plan();
+        plan.disk_space = Some(DiskSpaceCheck::new(4096, 4096));
+        plan.temp_space = Some(DiskSpaceCheck::new(1024, 4096));
+        let shortfalls = plan.space_shortfalls();
+        assert_eq!(shortfalls.len(), 1);
+        assert!(shortfalls[0].contains("/scratch"));
+    }
+}
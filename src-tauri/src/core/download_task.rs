@@ -2,11 +2,16 @@
 
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use std::path::PathBuf;
 use uuid::Uuid;
 
+use crate::core::retry::RetryOverrides;
+use crate::core::post_action::{PostAction, PostActionStatus};
+use crate::network::url_refresh::UrlRefresh;
+
 /// Status of a download
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum DownloadStatus {
     Queued,
     Connecting,
@@ -17,6 +22,13 @@ pub enum DownloadStatus {
     Cancelled,
     Merging,
     Verifying,
+    /// Held until power or network conditions allow it to start
+    WaitingForCondition,
+    /// Added by an automated source and too large to start unasked
+    NeedsConfirmation,
+    /// Finished, but the file it replaces is open in another program; the
+    /// data waits under a temporary name until the swap can be made
+    CompletedPendingReplace,
 }
 
 impl DownloadStatus {
@@ -49,6 +61,9 @@ impl DownloadStatus {
             DownloadStatus::Cancelled => "Cancelled",
             DownloadStatus::Merging => "Merging",
             DownloadStatus::Verifying => "Verifying",
+            DownloadStatus::WaitingForCondition => "WaitingForCondition",
+            DownloadStatus::NeedsConfirmation => "NeedsConfirmation",
+            DownloadStatus::CompletedPendingReplace => "CompletedPendingReplace",
         }
     }
 }
@@ -59,8 +74,74 @@ impl std::fmt::Display for DownloadStatus {
     }
 }
 
+/// How urgent a download is. Priority sets a download's share of the
+/// bandwidth when the speed limit is contended.
+///
+/// Stored as a number where lower is more urgent, the convention rows used
+/// before this enum existed; [`DownloadPriority::from_value`] and
+/// [`DownloadPriority::value`] map between the two.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, JsonSchema)]
+pub enum DownloadPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Critical,
+}
+
+impl DownloadPriority {
+    pub fn from_value(value: u32) -> Self {
+        match value {
+            0..=24 => DownloadPriority::Critical,
+            25..=74 => DownloadPriority::High,
+            75..=149 => DownloadPriority::Normal,
+            _ => DownloadPriority::Low,
+        }
+    }
+
+    pub fn value(&self) -> u32 {
+        match self {
+            DownloadPriority::Critical => 0,
+            DownloadPriority::High => 50,
+            DownloadPriority::Normal => 100,
+            DownloadPriority::Low => 200,
+        }
+    }
+}
+
+impl std::str::FromStr for DownloadPriority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "low" => Ok(DownloadPriority::Low),
+            "normal" => Ok(DownloadPriority::Normal),
+            "high" => Ok(DownloadPriority::High),
+            "critical" => Ok(DownloadPriority::Critical),
+            _ => Err(format!("Unknown priority: {}", s)),
+        }
+    }
+}
+
+/// Accepts a priority name or, from older clients, its numeric value
+impl<'de> Deserialize<'de> for DownloadPriority {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Numeric(u32),
+            Named(String),
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Numeric(value) => Ok(DownloadPriority::from_value(value)),
+            Repr::Named(name) => name.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
 /// A single download task
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct DownloadTask {
     /// Unique identifier
@@ -69,9 +150,14 @@ pub struct DownloadTask {
     /// Original URL
     pub url: String,
 
-    /// Final URL after redirects
+    /// URL the file is fetched from when the original one had to be
+    /// resolved (shortener, redirect page)
     pub final_url: Option<String>,
 
+    /// URLs passed through resolving `url` to `final_url`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redirect_chain: Vec<String>,
+
     /// File name
     pub file_name: String,
 
@@ -126,8 +212,8 @@ pub struct DownloadTask {
     /// When the download completed
     pub completed_at: Option<NaiveDateTime>,
 
-    /// Priority (lower = higher priority)
-    pub priority: u32,
+    /// Priority
+    pub priority: DownloadPriority,
 
     /// Category/group
     pub category: Option<String>,
@@ -135,10 +221,163 @@ pub struct DownloadTask {
     /// Segment progress details
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub segment_progress: Vec<SegmentProgress>,
+
+    /// Newer download that replaced this one: a re-download of the same
+    /// URL, or the yt-dlp download a refused link fell back to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub superseded_by: Option<Uuid>,
+
+    /// The refused direct download this yt-dlp download took over from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback_of: Option<Uuid>,
+
+    /// The per-entry playlist this row belongs to; the playlist's own row
+    /// names itself. These rows are run by the playlist, never the queue.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub playlist_id: Option<Uuid>,
+
+    /// Folder for segment data instead of beside the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temp_dir: Option<PathBuf>,
+
+    /// Free-text notes from the user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<PrivateText>,
+
+    /// Page the download was started from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_page_url: Option<String>,
+
+    /// Title of the page the download was started from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_page_title: Option<String>,
+
+    /// Result of the last on-disk check of a completed download
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub integrity_status: Option<IntegrityStatus>,
+
+    /// Backoff settings requested for this download
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_policy: Option<RetryOverrides>,
+
+    /// Added to a category that requires a checksum; unverified while
+    /// `expected_checksum` is unset
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub checksum_required: bool,
+
+    /// Why the download can't use several connections, when it can't
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capability_notes: Option<String>,
+
+    /// Where to get a fresh URL when a signed one expires mid-download
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url_refresh: Option<UrlRefresh>,
+
+    /// Run on the file once the download completes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_action: Option<PostAction>,
+
+    /// Outcome of `post_action`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_action_status: Option<PostActionStatus>,
+
+    /// Local time a "download tonight" is set to start
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduled_for: Option<chrono::NaiveDateTime>,
+
+    /// When the user needs it done by (UTC), for completion forecasts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline_at: Option<chrono::NaiveDateTime>,
+
+    /// Imported from a file found on disk with no record of where it came
+    /// from; `url` is then a file:// URL of the file itself
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unknown_origin: bool,
+
+    /// Bytes downloaded and then thrown away: restarted segments,
+    /// invalidated resume data, ignored ranges, failed checksums
+    #[serde(default)]
+    pub wasted_bytes: u64,
+}
+
+/// Whether a completed download's file was still found on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum IntegrityStatus {
+    Ok,
+    Missing,
+}
+
+impl IntegrityStatus {
+    pub fn as_str(&self) -> &str {
+        match self {
+            IntegrityStatus::Ok => "ok",
+            IntegrityStatus::Missing => "missing",
+        }
+    }
+}
+
+impl std::str::FromStr for IntegrityStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ok" => Ok(IntegrityStatus::Ok),
+            "missing" => Ok(IntegrityStatus::Missing),
+            _ => Err(format!("Unknown integrity status: {}", s)),
+        }
+    }
+}
+
+/// Personal free text that must not leak into logs or diagnostics.
+/// Serializes as a plain string, but its Debug output is redacted.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(transparent)]
+pub struct PrivateText(pub String);
+
+impl PrivateText {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for PrivateText {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<redacted>")
+    }
+}
+
+/// Editable descriptive fields of a download.
+/// `None` leaves a field unchanged; an empty string clears it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadMetadataPatch {
+    pub notes: Option<PrivateText>,
+    pub source_page_url: Option<String>,
+    pub source_page_title: Option<String>,
+}
+
+impl DownloadMetadataPatch {
+    pub fn apply(&self, task: &mut DownloadTask) {
+        fn non_empty(value: &str) -> Option<String> {
+            let trimmed = value.trim();
+            (!trimmed.is_empty()).then(|| trimmed.to_string())
+        }
+
+        if let Some(notes) = &self.notes {
+            task.notes = non_empty(notes.as_str()).map(PrivateText);
+        }
+        if let Some(url) = &self.source_page_url {
+            task.source_page_url = non_empty(url);
+        }
+        if let Some(title) = &self.source_page_title {
+            task.source_page_title = non_empty(title);
+        }
+    }
 }
 
 /// Progress of a single segment
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SegmentProgress {
     pub segment_id: u32,
@@ -148,7 +387,7 @@ pub struct SegmentProgress {
     pub status: SegmentStatus,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub enum SegmentStatus {
     Pending,
     Downloading,
@@ -172,7 +411,7 @@ pub struct ProgressEvent {
 }
 
 /// Download progress information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct DownloadProgress {
     pub id: Uuid,
@@ -183,6 +422,12 @@ pub struct DownloadProgress {
     pub status: DownloadStatus,
     pub percent: f64,
     pub error_message: Option<String>,
+    /// Fraction (0.0 - 1.0) of the current Merging/Verifying phase
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phase_progress: Option<f64>,
+    /// Set when the download has just been assigned a category
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
 }
 
 /// File information from URL
@@ -193,6 +438,60 @@ pub struct FileInfo {
     pub total_size: Option<u64>,
     pub content_type: Option<String>,
     pub supports_range: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    /// Raw `Accept-Ranges` header
+    #[serde(default)]
+    pub accept_ranges: Option<String>,
+    /// Raw `Server` header
+    #[serde(default)]
+    pub server: Option<String>,
+    /// Ranges are served and the file version can be checked, so a
+    /// partial download can be continued safely
+    #[serde(default)]
+    pub supports_resume: bool,
+    /// Redirects between the given URL and the file
+    #[serde(default)]
+    pub redirect_count: usize,
+    /// An HTTPS link ended up on plain HTTP
+    #[serde(default)]
+    pub https_downgraded: bool,
+    /// Why the file would download over a single connection
+    #[serde(default)]
+    pub capability_notes: Option<String>,
+}
+
+/// Why a file can't be split across several connections
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SingleSegmentReason {
+    NoRangeSupport,
+    UnknownSize,
+    TooSmall,
+}
+
+impl SingleSegmentReason {
+    /// What keeps a file with these properties to one segment, if anything
+    pub fn for_file(supports_range: bool, total_size: Option<u64>) -> Option<Self> {
+        if !supports_range {
+            return Some(Self::NoRangeSupport);
+        }
+        match total_size {
+            None => Some(Self::UnknownSize),
+            Some(size) if size < crate::utils::constants::MIN_SIZE_FOR_SEGMENTS => Some(Self::TooSmall),
+            Some(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for SingleSegmentReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoRangeSupport => write!(f, "This server doesn't support acceleration (no range requests)"),
+            Self::UnknownSize => write!(f, "The server didn't report a file size, so it can't be split"),
+            Self::TooSmall => write!(f, "The file is too small to split"),
+        }
+    }
 }
 
 /// Checksum type (alias for ChecksumAlgorithm)
@@ -223,9 +522,75 @@ impl DownloadTask {
             error_message: None,
             created_at: chrono::Local::now().naive_local(),
             completed_at: None,
-            priority: 100,
+            priority: DownloadPriority::Normal,
             category: None,
             segment_progress: Vec::new(),
+            superseded_by: None,
+            fallback_of: None,
+            playlist_id: None,
+            temp_dir: None,
+            notes: None,
+            source_page_url: None,
+            source_page_title: None,
+            integrity_status: None,
+            retry_policy: None,
+            checksum_required: false,
+            redirect_chain: Vec::new(),
+            capability_notes: None,
+            url_refresh: None,
+            post_action: None,
+            post_action_status: None,
+            scheduled_for: None,
+            deadline_at: None,
+            unknown_origin: false,
+            wasted_bytes: 0,
+        }
+    }
+
+    /// Record why this download is limited to one connection, if it is
+    pub fn note_capabilities(&mut self) {
+        self.capability_notes = SingleSegmentReason::for_file(self.supports_range, self.total_size)
+            .map(|reason| reason.to_string());
+    }
+
+    /// URL to transfer from: the resolved one when there is one
+    pub fn transfer_url(&self) -> &str {
+        self.final_url.as_deref().unwrap_or(&self.url)
+    }
+
+    /// Sent as Referer with every request for the file: the page it was
+    /// found on, for servers that refuse hotlinks
+    pub fn referrer(&self) -> Option<&str> {
+        self.source_page_url.as_deref()
+    }
+
+    /// Why `info`, probed from a replacement link, isn't the file this
+    /// download already holds part of. Only what both sides know is
+    /// compared.
+    pub fn replacement_mismatch(&self, info: &FileInfo) -> Option<String> {
+        if let (Some(old), Some(new)) = (self.total_size, info.total_size) {
+            if old != new {
+                return Some(format!("size is {} bytes, expected {}", new, old));
+            }
+        }
+        if let (Some(old), Some(new)) = (&self.etag, &info.etag) {
+            if old != new {
+                return Some(format!("ETag is {}, expected {}", new, old));
+            }
+        }
+        None
+    }
+
+    /// Its category requires a checksum and none was given
+    pub fn missing_required_checksum(&self) -> bool {
+        self.checksum_required && self.expected_checksum.is_none()
+    }
+
+    /// Copy of the task safe to broadcast in events (notes removed)
+    pub fn for_event(&self) -> Self {
+        Self {
+            notes: None,
+            ..self.clone()
         }
     }
 
@@ -233,6 +598,8 @@ impl DownloadTask {
     pub fn percent(&self) -> f64 {
         match self.total_size {
             Some(total) if total > 0 => (self.downloaded_size as f64 / total as f64) * 100.0,
+            // An empty file is all there once it's written
+            Some(_) if self.status == DownloadStatus::Completed => 100.0,
             _ => 0.0,
         }
     }
@@ -251,3 +618,75 @@ impl DownloadTask {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority_maps_both_ways() {
+        for priority in [
+            DownloadPriority::Low,
+            DownloadPriority::Normal,
+            DownloadPriority::High,
+            DownloadPriority::Critical,
+        ] {
+            assert_eq!(DownloadPriority::from_value(priority.value()), priority);
+        }
+        // Rows written before the enum default to 100
+        assert_eq!(DownloadPriority::from_value(100), DownloadPriority::Normal);
+        assert_eq!(DownloadPriority::from_value(1000), DownloadPriority::Low);
+    }
+
+    #[test]
+    fn test_priority_accepts_names_and_numbers() {
+        let named: DownloadPriority = serde_json::from_str("\"Critical\"").unwrap();
+        assert_eq!(named, DownloadPriority::Critical);
+        let lower: DownloadPriority = serde_json::from_str("\"high\"").unwrap();
+        assert_eq!(lower, DownloadPriority::High);
+        let numeric: DownloadPriority = serde_json::from_str("200").unwrap();
+        assert_eq!(numeric, DownloadPriority::Low);
+        assert!(serde_json::from_str::<DownloadPriority>("\"urgent\"").is_err());
+
+        assert_eq!(serde_json::to_string(&DownloadPriority::Normal).unwrap(), "\"Normal\"");
+    }
+
+    #[test]
+    fn test_single_segment_reasons() {
+        let big = Some(100 * 1024 * 1024);
+        assert_eq!(SingleSegmentReason::for_file(false, big), Some(SingleSegmentReason::NoRangeSupport));
+        assert_eq!(SingleSegmentReason::for_file(true, None), Some(SingleSegmentReason::UnknownSize));
+        assert_eq!(SingleSegmentReason::for_file(true, Some(1024)), Some(SingleSegmentReason::TooSmall));
+        assert_eq!(SingleSegmentReason::for_file(true, big), None);
+
+        let mut task = DownloadTask::new("https://example.com/a".into(), "a".into(), "a".into(), 8);
+        task.note_capabilities();
+        assert!(task.capability_notes.unwrap().contains("acceleration"));
+    }
+
+    #[test]
+    fn test_replacement_mismatch() {
+        let mut task = DownloadTask::new("https://cdn.example/a?sig=1".into(), "a".into(), "a".into(), 8);
+        task.total_size = Some(1000);
+        task.etag = Some("\"v1\"".into());
+        let info = |size: Option<u64>, etag: Option<&str>| FileInfo {
+            file_name: "a".into(),
+            total_size: size,
+            content_type: None,
+            supports_range: true,
+            etag: etag.map(String::from),
+            accept_ranges: None,
+            server: None,
+            supports_resume: true,
+            redirect_count: 0,
+            https_downgraded: false,
+            capability_notes: None,
+        };
+
+        assert_eq!(task.replacement_mismatch(&info(Some(1000), Some("\"v1\""))), None);
+        // Unknown on either side isn't held against the link
+        assert_eq!(task.replacement_mismatch(&info(None, None)), None);
+        assert!(task.replacement_mismatch(&info(Some(999), Some("\"v1\""))).unwrap().contains("size"));
+        assert!(task.replacement_mismatch(&info(Some(1000), Some("\"v2\""))).unwrap().contains("ETag"));
+    }
+}
@@ -0,0 +1,215 @@
+// src-tauri/src/core/forecast.rs
+// When the queue will be done: a simulation over a snapshot of the queue,
+// kept free of app state so it can be checked with made-up tasks.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// A download as the forecast sees it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForecastTask {
+    pub id: Uuid,
+    /// Bytes still to fetch; None when the size is unknown
+    pub remaining: Option<u64>,
+    /// Running now, so it holds a slot even past `max_concurrent`
+    pub active: bool,
+    /// Scheduled to start no earlier than this
+    pub not_before: Option<DateTime<Utc>>,
+    pub deadline_at: Option<DateTime<Utc>>,
+}
+
+/// Everything the forecast needs, taken at one moment
+#[derive(Debug, Clone)]
+pub struct ForecastSnapshot {
+    pub now: DateTime<Utc>,
+    pub max_concurrent: usize,
+    /// Bytes per second shared by the running downloads. The total is
+    /// assumed to hold however many run, as the line or the speed limit is
+    /// usually what bounds it. None when there is nothing to estimate from.
+    pub rate: Option<f64>,
+    /// Active downloads first, then the queue in order, then scheduled ones
+    pub tasks: Vec<ForecastTask>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskForecast {
+    pub id: Uuid,
+    /// None for downloads of unknown size, or with no rate to go on
+    pub completes_at: Option<DateTime<Utc>>,
+    pub deadline_at: Option<DateTime<Utc>>,
+    /// Forecast to finish after its deadline
+    pub at_risk: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueForecast {
+    pub generated_at: DateTime<Utc>,
+    pub rate: Option<f64>,
+    /// When the last download with a forecast completes
+    pub finish_at: Option<DateTime<Utc>>,
+    pub tasks: Vec<TaskForecast>,
+}
+
+/// Run the queue forward from `snapshot.now`: downloads start in order as
+/// slots free up and their scheduled time comes, and split the rate evenly
+/// while they run. Downloads of unknown size can't be placed and are left
+/// out, so they don't hold a slot either.
+pub fn forecast(snapshot: &ForecastSnapshot) -> QueueForecast {
+    let mut completes_at: Vec<Option<DateTime<Utc>>> = vec![None; snapshot.tasks.len()];
+
+    if let Some(rate) = snapshot.rate.filter(|rate| *rate > 0.0) {
+        simulate(snapshot, rate, &mut completes_at);
+    }
+
+    let tasks: Vec<TaskForecast> = snapshot
+        .tasks
+        .iter()
+        .zip(completes_at)
+        .map(|(task, completes_at)| TaskForecast {
+            id: task.id,
+            completes_at,
+            deadline_at: task.deadline_at,
+            at_risk: matches!((completes_at, task.deadline_at), (Some(done), Some(deadline)) if done > deadline),
+        })
+        .collect();
+
+    QueueForecast {
+        generated_at: snapshot.now,
+        rate: snapshot.rate,
+        finish_at: tasks.iter().filter_map(|t| t.completes_at).max(),
+        tasks,
+    }
+}
+
+/// Seconds from `now`, so the loop works in plain numbers
+fn offset(now: DateTime<Utc>, at: Option<DateTime<Utc>>) -> f64 {
+    at.map(|at| (at - now).num_milliseconds().max(0) as f64 / 1000.0).unwrap_or(0.0)
+}
+
+fn simulate(snapshot: &ForecastSnapshot, rate: f64, completes_at: &mut [Option<DateTime<Utc>>]) {
+    let max_concurrent = snapshot.max_concurrent.max(1);
+    // (index, bytes left, earliest start)
+    let mut waiting = Vec::new();
+    let mut running = Vec::new();
+    for (index, task) in snapshot.tasks.iter().enumerate() {
+        let Some(remaining) = task.remaining else { continue };
+        let entry = (index, remaining as f64, offset(snapshot.now, task.not_before));
+        if task.active {
+            running.push(entry);
+        } else {
+            waiting.push(entry);
+        }
+    }
+
+    let mut elapsed = 0.0;
+    loop {
+        // Fill free slots in queue order, skipping what isn't due yet
+        let mut i = 0;
+        while running.len() < max_concurrent && i < waiting.len() {
+            if waiting[i].2 <= elapsed {
+                running.push(waiting.remove(i));
+            } else {
+                i += 1;
+            }
+        }
+
+        if running.is_empty() {
+            match waiting.iter().map(|w| w.2).reduce(f64::min) {
+                Some(next_start) => {
+                    elapsed = next_start;
+                    continue;
+                }
+                None => break,
+            }
+        }
+
+        let share = rate / running.len() as f64;
+        let mut step = running.iter().map(|r| r.1 / share).fold(f64::INFINITY, f64::min);
+        if running.len() < max_concurrent {
+            if let Some(next_start) = waiting.iter().map(|w| w.2).filter(|s| *s > elapsed).reduce(f64::min) {
+                step = step.min(next_start - elapsed);
+            }
+        }
+
+        elapsed += step;
+        running.retain_mut(|(index, remaining, _)| {
+            *remaining -= share * step;
+            if *remaining > 0.5 {
+                return true;
+            }
+            completes_at[*index] = Some(snapshot.now + Duration::milliseconds((elapsed * 1000.0) as i64));
+            false
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(remaining: Option<u64>, active: bool) -> ForecastTask {
+        ForecastTask { id: Uuid::new_v4(), remaining, active, not_before: None, deadline_at: None }
+    }
+
+    fn snapshot(max_concurrent: usize, rate: f64, tasks: Vec<ForecastTask>) -> ForecastSnapshot {
+        ForecastSnapshot {
+            now: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            max_concurrent,
+            rate: Some(rate),
+            tasks,
+        }
+    }
+
+    fn seconds(forecast: &QueueForecast, index: usize) -> Option<i64> {
+        forecast.tasks[index].completes_at.map(|at| (at - forecast.generated_at).num_seconds())
+    }
+
+    #[test]
+    fn test_slots_and_shared_rate() {
+        // 100 B/s, two slots: a and b share until a is done at 20s, then b
+        // (1000 left) shares with c until 40s, and c ends alone at 50s
+        let forecast = forecast(&snapshot(
+            2,
+            100.0,
+            vec![task(Some(1000), true), task(Some(2000), true), task(Some(2000), false)],
+        ));
+        assert_eq!(seconds(&forecast, 0), Some(20));
+        assert_eq!(seconds(&forecast, 1), Some(40));
+        assert_eq!(seconds(&forecast, 2), Some(50));
+        assert_eq!(forecast.finish_at, forecast.tasks[2].completes_at);
+    }
+
+    #[test]
+    fn test_scheduled_start_and_deadline() {
+        let mut snap = snapshot(3, 100.0, vec![task(Some(1000), false), task(Some(1000), false)]);
+        let now = snap.now;
+        // Starts in an hour, so it can't make a deadline in 30 minutes
+        snap.tasks[0].not_before = Some(now + Duration::hours(1));
+        snap.tasks[0].deadline_at = Some(now + Duration::minutes(30));
+        snap.tasks[1].deadline_at = Some(now + Duration::minutes(30));
+
+        let forecast = forecast(&snap);
+        assert_eq!(seconds(&forecast, 0), Some(3600 + 10));
+        assert!(forecast.tasks[0].at_risk);
+        // The scheduled one doesn't hold up the rest of the queue
+        assert_eq!(seconds(&forecast, 1), Some(10));
+        assert!(!forecast.tasks[1].at_risk);
+    }
+
+    #[test]
+    fn test_unknown_sizes_and_no_rate() {
+        let forecast = forecast(&snapshot(1, 100.0, vec![task(None, true), task(Some(100), false)]));
+        assert_eq!(seconds(&forecast, 0), None);
+        // The unknown-size download doesn't hold the only slot
+        assert_eq!(seconds(&forecast, 1), Some(1));
+
+        let mut snap = snapshot(1, 0.0, vec![task(Some(100), true)]);
+        snap.rate = None;
+        let forecast = forecast(&snap);
+        assert_eq!(forecast.finish_at, None);
+        assert!(!forecast.tasks[0].at_risk);
+    }
+}
@@ -0,0 +1,302 @@
+// src-tauri/src/core/metrics.rs
+// Counters for Prometheus scraping. They only ever grow while the app runs
+// and start from zero with it, which Prometheus reads as a counter reset;
+// values derived from the database instead would drop whenever downloads
+// are removed from the list.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+use uuid::Uuid;
+
+use crate::utils::error::DownloadError;
+use crate::utils::privacy::{self, PrivacyLevel};
+
+/// Values read when scraped rather than counted
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricsGauges {
+    pub active_downloads: usize,
+    pub queued_downloads: usize,
+    /// Bytes per second across running downloads
+    pub download_speed: f64,
+    pub torrents_active: usize,
+    pub torrents_seeding: usize,
+    pub torrents_paused: usize,
+    pub torrent_download_rate: u64,
+    pub torrent_upload_rate: u64,
+    pub torrent_dht_nodes: Option<usize>,
+    pub database_size_bytes: Option<u64>,
+}
+
+#[derive(Default)]
+struct Counters {
+    started: u64,
+    resumed: u64,
+    completed: u64,
+    failed: u64,
+    bytes_by_protocol: BTreeMap<String, u64>,
+    errors_by_host: BTreeMap<String, u64>,
+    /// Bytes last reported per running transfer, so progress adds only what
+    /// is new, and what a resume starts with isn't counted again
+    seen: HashMap<Uuid, (String, u64)>,
+    /// Transfers begun since the app launched, so beginning one again
+    /// counts as a resume
+    begun: HashSet<Uuid>,
+}
+
+#[derive(Default)]
+pub struct MetricsRegistry {
+    counters: parking_lot::Mutex<Counters>,
+    /// Scrapes leave the process, so host labels follow the privacy level
+    privacy: parking_lot::RwLock<PrivacyLevel>,
+}
+
+/// Protocol label for a URL: its scheme, or "other"
+pub fn protocol_of(url: &str) -> String {
+    url::Url::parse(url)
+        .map(|u| u.scheme().to_string())
+        .unwrap_or_else(|_| "other".to_string())
+}
+
+impl MetricsRegistry {
+    pub fn set_privacy(&self, level: PrivacyLevel) {
+        *self.privacy.write() = level;
+    }
+
+    /// A transfer starts with `downloaded` bytes already on disk. It counts
+    /// as started the first time, and as resumed when it picks up bytes
+    /// from before or was begun earlier in this run.
+    pub fn begin(&self, id: Uuid, url: &str, downloaded: u64) {
+        let mut counters = self.counters.lock();
+        if counters.begun.insert(id) && downloaded == 0 {
+            counters.started += 1;
+        } else {
+            counters.resumed += 1;
+        }
+        counters.seen.insert(id, (protocol_of(url), downloaded));
+    }
+
+    pub fn record_progress(&self, id: Uuid, downloaded: u64) {
+        let mut counters = self.counters.lock();
+        let Some((protocol, last)) = counters.seen.get_mut(&id) else {
+            return;
+        };
+        let new_bytes = downloaded.saturating_sub(*last);
+        *last = (*last).max(downloaded);
+        let protocol = protocol.clone();
+        *counters.bytes_by_protocol.entry(protocol).or_default() += new_bytes;
+    }
+
+    pub fn record_completed(&self, id: Uuid) {
+        let mut counters = self.counters.lock();
+        counters.seen.remove(&id);
+        counters.completed += 1;
+    }
+
+    /// A transfer of `url` failed; counted against its host
+    pub fn record_failed(&self, id: Uuid, url: &str) {
+        let host = url::Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_lowercase))
+            .unwrap_or_else(|| "unknown".to_string());
+        let mut counters = self.counters.lock();
+        counters.seen.remove(&id);
+        counters.failed += 1;
+        *counters.errors_by_host.entry(host).or_default() += 1;
+    }
+
+    /// Paused or cancelled: neither a completion nor a failure
+    pub fn end(&self, id: Uuid) {
+        self.counters.lock().seen.remove(&id);
+    }
+
+    /// Count a transfer as its result says; pausing and cancelling count
+    /// as neither completed nor failed
+    pub fn finish<T>(&self, id: Uuid, url: &str, result: &Result<T, DownloadError>) {
+        match result {
+            Ok(_) => self.record_completed(id),
+            Err(DownloadError::Cancelled | DownloadError::Paused) => self.end(id),
+            Err(_) => self.record_failed(id, url),
+        }
+    }
+
+    /// Everything in the Prometheus text exposition format
+    pub fn render(&self, gauges: &MetricsGauges) -> String {
+        let counters = self.counters.lock();
+        let mut out = String::new();
+
+        metric(&mut out, "afk_downloads_started_total", "counter", "Downloads started since the app launched", &[(None, counters.started as f64)]);
+        metric(&mut out, "afk_downloads_resumed_total", "counter", "Downloads resumed since the app launched", &[(None, counters.resumed as f64)]);
+        metric(&mut out, "afk_downloads_completed_total", "counter", "Downloads completed since the app launched", &[(None, counters.completed as f64)]);
+        metric(&mut out, "afk_downloads_failed_total", "counter", "Downloads failed since the app launched", &[(None, counters.failed as f64)]);
+
+        let total_bytes: u64 = counters.bytes_by_protocol.values().sum();
+        metric(&mut out, "afk_downloaded_bytes_total", "counter", "Bytes downloaded since the app launched", &[(None, total_bytes as f64)]);
+        let by_protocol: Vec<_> = counters
+            .bytes_by_protocol
+            .iter()
+            .map(|(protocol, bytes)| (Some(("protocol", protocol.as_str())), *bytes as f64))
+            .collect();
+        metric(&mut out, "afk_downloaded_bytes_by_protocol_total", "counter", "Bytes downloaded per protocol", &by_protocol);
+        let privacy = *self.privacy.read();
+        let hosts: Vec<(String, u64)> = counters
+            .errors_by_host
+            .iter()
+            .filter_map(|(host, errors)| {
+                let mut label = serde_json::Value::String(host.clone());
+                privacy::filter_field(privacy, "host", &mut label)
+                    .then(|| (label.as_str().unwrap_or_default().to_string(), *errors))
+            })
+            .collect();
+        let by_host: Vec<_> = hosts
+            .iter()
+            .map(|(host, errors)| (Some(("host", host.as_str())), *errors as f64))
+            .collect();
+        metric(&mut out, "afk_download_errors_by_host_total", "counter", "Failed downloads per host", &by_host);
+
+        metric(&mut out, "afk_downloads_active", "gauge", "Downloads transferring now", &[(None, gauges.active_downloads as f64)]);
+        metric(&mut out, "afk_queue_depth", "gauge", "Downloads waiting in the queue", &[(None, gauges.queued_downloads as f64)]);
+        metric(&mut out, "afk_download_speed_bytes", "gauge", "Bytes per second across running downloads", &[(None, gauges.download_speed)]);
+
+        let torrents = [
+            (Some(("state", "active")), gauges.torrents_active as f64),
+            (Some(("state", "seeding")), gauges.torrents_seeding as f64),
+            (Some(("state", "paused")), gauges.torrents_paused as f64),
+        ];
+        metric(&mut out, "afk_torrents", "gauge", "Torrents in the session by state", &torrents);
+        let rates = [
+            (Some(("direction", "download")), gauges.torrent_download_rate as f64),
+            (Some(("direction", "upload")), gauges.torrent_upload_rate as f64),
+        ];
+        metric(&mut out, "afk_torrent_rate_bytes", "gauge", "Torrent session bytes per second", &rates);
+        if let Some(nodes) = gauges.torrent_dht_nodes {
+            metric(&mut out, "afk_torrent_dht_nodes", "gauge", "Nodes in the DHT routing table", &[(None, nodes as f64)]);
+        }
+        if let Some(size) = gauges.database_size_bytes {
+            metric(&mut out, "afk_database_size_bytes", "gauge", "Size of the downloads database", &[(None, size as f64)]);
+        }
+        out
+    }
+}
+
+type Sample<'a> = (Option<(&'a str, &'a str)>, f64);
+
+fn metric(out: &mut String, name: &str, kind: &str, help: &str, samples: &[Sample<'_>]) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    for (label, value) in samples {
+        match label {
+            Some((key, value_text)) => {
+                let _ = writeln!(out, "{}{{{}=\"{}\"}} {}", name, key, escape_label(value_text), value);
+            }
+            None => {
+                let _ = writeln!(out, "{} {}", name, value);
+            }
+        }
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A sample line as Prometheus reads it: name, optional labels, value
+    fn parse_sample(line: &str) -> Option<(String, Option<(String, String)>, f64)> {
+        let (series, value) = line.rsplit_once(' ')?;
+        let value: f64 = value.parse().ok()?;
+        let (name, labels) = match series.split_once('{') {
+            Some((name, rest)) => {
+                let inner = rest.strip_suffix('}')?;
+                let (key, quoted) = inner.split_once('=')?;
+                let text = quoted.strip_prefix('"')?.strip_suffix('"')?;
+                (name, Some((key.to_string(), text.replace("\\\"", "\"").replace("\\\\", "\\"))))
+            }
+            None => (series, None),
+        };
+        let valid_name = !name.is_empty()
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+            && !name.starts_with(|c: char| c.is_ascii_digit());
+        valid_name.then(|| (name.to_string(), labels, value))
+    }
+
+    #[test]
+    fn test_exposition_format_parses() {
+        let registry = MetricsRegistry::default();
+        let a = Uuid::new_v4();
+        let b = Uuid::new_v4();
+        registry.begin(a, "https://cdn.example/a.iso", 100);
+        registry.record_progress(a, 600);
+        registry.record_progress(a, 1100);
+        registry.record_completed(a);
+        registry.begin(b, "ftp://files.example/b.zip", 0);
+        registry.record_progress(b, 50);
+        registry.record_failed(b, "ftp://files.example/b.zip");
+        let c = Uuid::new_v4();
+        registry.begin(c, "https://cdn.example/c.iso", 0);
+        // Paused before any bytes arrived and resumed: started only once
+        registry.end(c);
+        registry.begin(c, "https://cdn.example/c.iso", 0);
+
+        let text = registry.render(&MetricsGauges {
+            active_downloads: 1,
+            database_size_bytes: Some(4096),
+            ..Default::default()
+        });
+
+        let mut samples = Vec::new();
+        let mut typed = Vec::new();
+        for line in text.lines() {
+            if let Some(rest) = line.strip_prefix("# TYPE ") {
+                let (name, kind) = rest.split_once(' ').unwrap();
+                assert!(matches!(kind, "counter" | "gauge"));
+                typed.push(name.to_string());
+            } else if !line.starts_with("# HELP ") {
+                let sample = parse_sample(line).unwrap_or_else(|| panic!("unparseable line: {}", line));
+                // Every sample belongs to a declared metric
+                assert!(typed.contains(&sample.0), "{} has no TYPE", sample.0);
+                samples.push(sample);
+            }
+        }
+        let value = |name: &str, label: Option<(&str, &str)>| {
+            samples
+                .iter()
+                .find(|(n, l, _)| n == name && l.as_ref().map(|(k, v)| (k.as_str(), v.as_str())) == label)
+                .map(|(_, _, v)| *v)
+        };
+
+        // The 100 bytes the first download resumed with aren't counted
+        assert_eq!(value("afk_downloaded_bytes_total", None), Some(1050.0));
+        assert_eq!(value("afk_downloaded_bytes_by_protocol_total", Some(("protocol", "https"))), Some(1000.0));
+        assert_eq!(value("afk_downloaded_bytes_by_protocol_total", Some(("protocol", "ftp"))), Some(50.0));
+        assert_eq!(value("afk_download_errors_by_host_total", Some(("host", "files.example"))), Some(1.0));
+        assert_eq!(value("afk_downloads_started_total", None), Some(2.0));
+        assert_eq!(value("afk_downloads_resumed_total", None), Some(2.0));
+        assert_eq!(value("afk_downloads_completed_total", None), Some(1.0));
+        assert_eq!(value("afk_database_size_bytes", None), Some(4096.0));
+        assert_eq!(value("afk_torrent_dht_nodes", None), None);
+
+        // Metadata only: the failure still counts, its host isn't named
+        registry.set_privacy(PrivacyLevel::MetadataOnly);
+        let text = registry.render(&MetricsGauges::default());
+        assert!(!text.contains("files.example"));
+        assert!(text.contains("afk_downloads_failed_total 1\n"));
+    }
+
+    #[test]
+    fn test_counters_never_go_back() {
+        let registry = MetricsRegistry::default();
+        let id = Uuid::new_v4();
+        registry.begin(id, "https://cdn.example/a", 0);
+        registry.record_progress(id, 500);
+        // A restarted segment reports less; nothing is taken back
+        registry.record_progress(id, 200);
+        registry.record_progress(id, 700);
+        registry.end(id);
+        // Progress after the transfer ended is ignored
+        registry.record_progress(id, 900);
+        assert!(registry.render(&MetricsGauges::default()).contains("afk_downloaded_bytes_total 700\n"));
+    }
+}
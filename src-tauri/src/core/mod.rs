@@ -1,13 +1,34 @@
+pub mod bandwidth;
 pub mod category;
 pub mod checksum;
+pub mod content_filter;
 pub mod chunk_manager;
+pub mod data_usage;
+pub mod dedup;
 pub mod download_engine;
+pub mod download_plan;
 pub mod download_task;
+pub mod forecast;
+pub mod metrics;
+pub mod page_media;
 pub mod queue_manager;
+pub mod rebuild;
+pub mod resource_guard;
+pub mod size_guard;
+pub mod soft_pause;
 pub mod resume_manager;
 pub mod retry;
 pub mod segment_downloader;
+pub mod segment_gate;
 pub mod speed_limiter;
 pub mod speed_tracker;
+pub mod transfer;
+pub mod undo;
+pub mod waste;
+pub mod ytdlp_env;
+pub mod ytdlp_fallback;
 pub mod merge_manager;
-pub mod scheduler;
\ No newline at end of file
+pub mod playlist;
+pub mod post_action;
+pub mod scheduler;
+pub mod task_launch;
\ No newline at end of file
@@ -0,0 +1,163 @@
+// src-tauri/src/core/page_media.rs
+// "Download all images/videos on this page": the extension sends the
+// page's media URLs, the app probes each one for its name, size and type
+// without adding anything, and the user picks what to keep. The picked
+// URLs are then added together, into a folder named after the page.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::core::download_task::{DownloadPriority, DownloadStatus};
+use crate::network::http_client::RemoteFileInfo;
+use crate::network::url_parser::{BatchUrlAction, NormalizedUrl};
+
+/// One URL from a page's media list, as probed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaProbe {
+    /// Position in the list the extension sent
+    pub index: usize,
+    pub url: String,
+    pub file_name: Option<String>,
+    pub total_size: Option<u64>,
+    pub content_type: Option<String>,
+    /// Listed earlier on the page, or already in the download list
+    pub duplicate: bool,
+    /// A download of this URL has completed before
+    pub already_downloaded: bool,
+    pub error: Option<String>,
+}
+
+impl MediaProbe {
+    /// An entry that isn't probed: a repeat of an earlier URL, or one that
+    /// couldn't be parsed
+    pub fn unprobed(index: usize, entry: &NormalizedUrl) -> Self {
+        Self {
+            index,
+            url: entry.normalized.clone().unwrap_or_else(|| entry.input.clone()),
+            file_name: None,
+            total_size: None,
+            content_type: None,
+            duplicate: entry.action == BatchUrlAction::SkippedDuplicate,
+            already_downloaded: false,
+            error: entry.error.clone(),
+        }
+    }
+
+    pub fn probed(index: usize, url: String, result: Result<RemoteFileInfo, String>) -> Self {
+        match result {
+            Ok(info) => Self {
+                index,
+                url,
+                file_name: Some(info.file_name),
+                total_size: info.total_size,
+                content_type: info.content_type,
+                duplicate: false,
+                already_downloaded: false,
+                error: None,
+            },
+            Err(error) => Self {
+                index,
+                url,
+                file_name: None,
+                total_size: None,
+                content_type: None,
+                duplicate: false,
+                already_downloaded: false,
+                error: Some(error),
+            },
+        }
+    }
+
+    /// Flag the entry from the statuses of existing downloads of its URL
+    pub fn mark_existing(&mut self, existing: &[DownloadStatus]) {
+        for status in existing {
+            match status {
+                DownloadStatus::Completed | DownloadStatus::CompletedPendingReplace => self.already_downloaded = true,
+                _ => self.duplicate = true,
+            }
+        }
+    }
+}
+
+/// Emitted as `probe-batch-progress` as each entry is probed
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProbeBatchProgress<'a> {
+    pub batch_id: Uuid,
+    pub completed: usize,
+    pub total: usize,
+    pub item: &'a MediaProbe,
+}
+
+/// Outcome of `probe_batch`, in the order the URLs were sent
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProbeBatchSummary {
+    pub batch_id: Uuid,
+    pub items: Vec<MediaProbe>,
+}
+
+/// Shared by every URL `add_selected` adds
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MediaAddOptions {
+    pub save_path: Option<String>,
+    pub category: Option<String>,
+    pub priority: Option<DownloadPriority>,
+    /// The page the media came from, kept on each download
+    pub referrer: Option<String>,
+    pub page_title: Option<String>,
+    /// Save into a folder named after the page, as playlists are
+    pub page_folder: bool,
+}
+
+impl MediaAddOptions {
+    /// Name of the page's folder: its title, else its host
+    pub fn folder_name(&self) -> Option<String> {
+        if !self.page_folder {
+            return None;
+        }
+        self.page_title
+            .as_deref()
+            .map(str::trim)
+            .filter(|title| !title.is_empty())
+            .map(str::to_string)
+            .or_else(|| {
+                self.referrer
+                    .as_deref()
+                    .and_then(|referrer| reqwest::Url::parse(referrer).ok())
+                    .and_then(|url| url.host_str().map(str::to_string))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_existing_downloads_set_the_flags() {
+        let info = RemoteFileInfo {
+            total_size: Some(2048),
+            content_type: Some("image/png".to_string()),
+            ..RemoteFileInfo::unknown("https://cdn.example.com/a.png")
+        };
+        let mut probe = MediaProbe::probed(0, "https://cdn.example.com/a.png".to_string(), Ok(info));
+        assert_eq!(probe.file_name.as_deref(), Some("a.png"));
+        probe.mark_existing(&[DownloadStatus::Completed]);
+        assert!(probe.already_downloaded && !probe.duplicate);
+        probe.mark_existing(&[DownloadStatus::Paused]);
+        assert!(probe.duplicate);
+
+        let options = MediaAddOptions {
+            referrer: Some("https://gallery.example.com/album/1".to_string()),
+            page_folder: true,
+            ..Default::default()
+        };
+        assert_eq!(options.folder_name().as_deref(), Some("gallery.example.com"));
+        let titled = MediaAddOptions { page_title: Some(" Holiday ".to_string()), ..options };
+        assert_eq!(titled.folder_name().as_deref(), Some("Holiday"));
+    }
+}
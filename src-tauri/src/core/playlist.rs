@@ -0,0 +1,269 @@
+// src-tauri/src/core/playlist.rs
+// Playlists run entry by entry: each entry is its own yt-dlp process, so a
+// slow or broken entry is retried or skipped without stalling the rest, and
+// a crash loses one entry rather than the batch.
+
+use futures::stream::{self, StreamExt};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::future::Future;
+use uuid::Uuid;
+
+/// File in the app data folder yt-dlp records finished entries in
+pub const YTDLP_ARCHIVE_FILE: &str = "ytdlp-archive.txt";
+
+/// How a playlist URL is downloaded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaylistMode {
+    /// One yt-dlp process works through the whole playlist
+    #[default]
+    SingleProcess,
+    /// The app runs a yt-dlp process per entry, a few at a time
+    PerEntry,
+}
+
+/// One entry of a flat playlist listing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistEntry {
+    pub id: String,
+    pub url: String,
+    pub title: Option<String>,
+    /// yt-dlp's extractor key, lowercased as the archive file writes it
+    pub extractor: String,
+}
+
+impl PlaylistEntry {
+    /// The line yt-dlp records in `--download-archive` for this entry
+    pub fn archive_key(&self) -> String {
+        format!("{} {}", self.extractor, self.id)
+    }
+}
+
+/// A playlist as `yt-dlp --flat-playlist --dump-single-json` lists it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlaylistListing {
+    pub title: Option<String>,
+    pub entries: Vec<PlaylistEntry>,
+}
+
+pub fn parse_flat_playlist(json: &serde_json::Value) -> PlaylistListing {
+    let playlist_extractor = json["extractor_key"].as_str().unwrap_or("generic");
+    let entries = json["entries"].as_array().map(Vec::as_slice).unwrap_or_default();
+
+    let entries = entries
+        .iter()
+        .filter_map(|entry| {
+            let id = entry["id"].as_str()?.to_string();
+            let extractor = entry["ie_key"]
+                .as_str()
+                .unwrap_or(playlist_extractor)
+                .to_ascii_lowercase();
+            // Flat YouTube listings give bare video ids as the url
+            let url = entry["url"]
+                .as_str()
+                .or_else(|| entry["webpage_url"].as_str())
+                .filter(|url| url.starts_with("http://") || url.starts_with("https://"))
+                .map(str::to_string)
+                .or_else(|| (extractor == "youtube").then(|| format!("https://www.youtube.com/watch?v={}", id)))?;
+            Some(PlaylistEntry {
+                id,
+                url,
+                title: entry["title"].as_str().map(str::to_string),
+                extractor,
+            })
+        })
+        .collect();
+    PlaylistListing {
+        title: json["title"].as_str().map(str::to_string),
+        entries,
+    }
+}
+
+/// Entries recorded in a `--download-archive` file
+pub fn parse_archive(contents: &str) -> HashSet<String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// How an entry ended
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum EntryOutcome {
+    Completed,
+    /// Already in the archive, or cancelled by the user
+    Skipped,
+    Failed { error: String },
+}
+
+/// Where a playlist group stands; emitted as `playlist-group-progress`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistGroupProgress {
+    pub group_id: Uuid,
+    pub total: usize,
+    pub completed: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+impl PlaylistGroupProgress {
+    pub fn new(group_id: Uuid, total: usize) -> Self {
+        Self { group_id, total, completed: 0, skipped: 0, failed: 0 }
+    }
+
+    pub fn finished(&self) -> usize {
+        self.completed + self.skipped + self.failed
+    }
+
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            return 100.0;
+        }
+        self.finished() as f64 * 100.0 / self.total as f64
+    }
+
+    fn record(&mut self, outcome: &EntryOutcome) {
+        match outcome {
+            EntryOutcome::Completed => self.completed += 1,
+            EntryOutcome::Skipped => self.skipped += 1,
+            EntryOutcome::Failed { .. } => self.failed += 1,
+        }
+    }
+}
+
+/// Run `run_entry` for every entry not in `archived`, `concurrency` at a
+/// time. A failed entry is tried again until it has had `attempts` tries;
+/// the rest carry on either way. `on_outcome` sees each entry as it ends.
+pub async fn run_entries<F, Fut, G>(
+    group_id: Uuid,
+    entries: Vec<PlaylistEntry>,
+    archived: &HashSet<String>,
+    concurrency: usize,
+    attempts: u32,
+    run_entry: F,
+    mut on_outcome: G,
+) -> PlaylistGroupProgress
+where
+    F: Fn(PlaylistEntry, u32) -> Fut,
+    Fut: Future<Output = EntryOutcome>,
+    G: FnMut(&PlaylistEntry, &EntryOutcome, &PlaylistGroupProgress),
+{
+    let mut progress = PlaylistGroupProgress::new(group_id, entries.len());
+    let run_entry = &run_entry;
+
+    let mut runs = stream::iter(entries.into_iter().map(|entry| async move {
+        if archived.contains(&entry.archive_key()) {
+            return (entry, EntryOutcome::Skipped);
+        }
+        let mut attempt = 1;
+        loop {
+            match run_entry(entry.clone(), attempt).await {
+                EntryOutcome::Failed { .. } if attempt < attempts => attempt += 1,
+                outcome => return (entry, outcome),
+            }
+        }
+    }))
+    .buffer_unordered(concurrency.max(1));
+
+    while let Some((entry, outcome)) = runs.next().await {
+        progress.record(&outcome);
+        on_outcome(&entry, &outcome, &progress);
+    }
+    progress
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    fn entry(id: &str) -> PlaylistEntry {
+        PlaylistEntry {
+            id: id.to_string(),
+            url: format!("https://www.youtube.com/watch?v={}", id),
+            title: None,
+            extractor: "youtube".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_flat_playlist() {
+        let json = serde_json::json!({
+            "_type": "playlist",
+            "title": "Mix",
+            "extractor_key": "YoutubeTab",
+            "entries": [
+                { "id": "aaa", "url": "aaa", "ie_key": "Youtube", "title": "First" },
+                { "id": "bbb", "url": "https://vimeo.com/bbb", "ie_key": "Vimeo" },
+                { "title": "no id" },
+            ]
+        });
+        let listing = parse_flat_playlist(&json);
+        assert_eq!(listing.title.as_deref(), Some("Mix"));
+        let entries = listing.entries;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].url, "https://www.youtube.com/watch?v=aaa");
+        assert_eq!(entries[0].archive_key(), "youtube aaa");
+        assert_eq!(entries[0].title.as_deref(), Some("First"));
+        assert_eq!(entries[1].archive_key(), "vimeo bbb");
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_retried_and_isolated() {
+        let archived = parse_archive("youtube done\n\n");
+        let entries = vec![entry("done"), entry("ok"), entry("flaky"), entry("broken")];
+        let calls = AtomicUsize::new(0);
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let seen = Mutex::new(Vec::new());
+
+        let progress = run_entries(
+            Uuid::new_v4(),
+            entries,
+            &archived,
+            2,
+            3,
+            |entry, attempt| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                let running = &running;
+                async move {
+                    tokio::task::yield_now().await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    match (entry.id.as_str(), attempt) {
+                        ("ok", _) | ("flaky", 2) => EntryOutcome::Completed,
+                        _ => EntryOutcome::Failed { error: "boom".to_string() },
+                    }
+                }
+            },
+            |entry, outcome, _| seen.lock().unwrap().push((entry.id.clone(), outcome.clone())),
+        )
+        .await;
+
+        assert_eq!((progress.completed, progress.skipped, progress.failed), (2, 1, 1));
+        assert_eq!(progress.percent(), 100.0);
+        // ok once, flaky twice, broken three times; the archived entry never runs
+        assert_eq!(calls.load(Ordering::SeqCst), 6);
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+        assert!(seen
+            .lock()
+            .unwrap()
+            .contains(&("done".to_string(), EntryOutcome::Skipped)));
+    }
+
+    #[test]
+    fn test_mode_names() {
+        let mode: PlaylistMode = serde_json::from_str("\"per_entry\"").unwrap();
+        assert_eq!(mode, PlaylistMode::PerEntry);
+        assert_eq!(PlaylistMode::default(), PlaylistMode::SingleProcess);
+    }
+}
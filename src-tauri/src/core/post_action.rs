@@ -0,0 +1,203 @@
+// src-tauri/src/core/post_action.rs
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::path::{Path, PathBuf};
+
+/// Work done on a download's file once it completes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum PostAction {
+    /// Run the file through a conversion profile from settings
+    Convert { profile: String },
+}
+
+impl std::fmt::Display for PostAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PostAction::Convert { profile } => write!(f, "convert {}", profile),
+        }
+    }
+}
+
+/// Where a download's post-action stands. It never changes the
+/// download's own status.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "state", rename_all = "camelCase")]
+pub enum PostActionStatus {
+    Queued,
+    Running,
+    Completed { output: PathBuf },
+    /// The original file was kept
+    Failed { error: String },
+}
+
+/// Sent with `post-action-progress`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostActionProgress {
+    pub id: uuid::Uuid,
+    pub action: PostAction,
+    pub status: PostActionStatus,
+    /// Share done while running, when the input's duration is known
+    pub percent: Option<f64>,
+}
+
+/// Where a conversion's output goes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ConversionOutput {
+    /// Next to the original, which is kept
+    #[default]
+    Alongside,
+    /// In place of the original, which is removed once the output is in place
+    Replace,
+}
+
+/// A named ffmpeg conversion
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversionProfile {
+    pub name: String,
+    /// ffmpeg options placed between the input and the output file
+    pub args: Vec<String>,
+    /// Extension of the converted file
+    pub extension: String,
+    #[serde(default)]
+    pub output: ConversionOutput,
+}
+
+impl ConversionProfile {
+    /// Profiles offered until the user defines their own
+    pub fn defaults() -> Vec<Self> {
+        let args = |args: &[&str]| args.iter().map(|a| a.to_string()).collect();
+        vec![
+            ConversionProfile {
+                name: "to_mp3_192k".to_string(),
+                args: args(&["-vn", "-c:a", "libmp3lame", "-b:a", "192k"]),
+                extension: "mp3".to_string(),
+                output: ConversionOutput::Alongside,
+            },
+            ConversionProfile {
+                name: "remux_to_mp4".to_string(),
+                args: args(&["-map", "0", "-c", "copy", "-movflags", "+faststart"]),
+                extension: "mp4".to_string(),
+                output: ConversionOutput::Replace,
+            },
+        ]
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Conversion profiles need a name".to_string());
+        }
+        if self.extension.is_empty() || !self.extension.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(format!("Profile {}: {:?} is not a file extension", self.name, self.extension));
+        }
+        // The worker picks the input and output files itself
+        if self.args.iter().any(|a| a == "-i" || a == "-y" || a == "-n") {
+            return Err(format!("Profile {}: -i, -y and -n are set by the converter", self.name));
+        }
+        Ok(())
+    }
+
+    /// Where converting `source` should end up, before avoiding existing
+    /// files. Alongside output never takes the original's name.
+    pub fn output_path(&self, source: &Path) -> PathBuf {
+        let target = source.with_extension(&self.extension);
+        if self.output == ConversionOutput::Alongside && target == source {
+            let stem = source.file_stem().unwrap_or_default().to_string_lossy();
+            return source.with_file_name(format!("{} (converted).{}", stem, self.extension));
+        }
+        target
+    }
+}
+
+/// File the conversion writes to, next to `output` so it can be renamed
+/// into place. Keeps the extension so ffmpeg picks the right format.
+pub fn temp_output_path(output: &Path) -> PathBuf {
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = output.extension().unwrap_or_default().to_string_lossy();
+    output.with_file_name(format!(".{}.converting.{}", stem, extension))
+}
+
+/// Follows ffmpeg's progress through its stderr
+#[derive(Debug, Default)]
+pub struct FfmpegProgress {
+    duration: Option<f64>,
+}
+
+impl FfmpegProgress {
+    /// Feed one line of stderr; returns the percent done when the line
+    /// reports progress and the input's duration is known
+    pub fn feed(&mut self, line: &str) -> Option<f64> {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("Duration:") {
+            if self.duration.is_none() {
+                self.duration = rest.split(',').next().and_then(|d| parse_timestamp(d.trim()));
+            }
+            return None;
+        }
+
+        let time = line.split_once("time=")?.1.split_whitespace().next()?;
+        let elapsed = parse_timestamp(time)?;
+        let duration = self.duration.filter(|d| *d > 0.0)?;
+        Some((elapsed / duration * 100.0).clamp(0.0, 100.0))
+    }
+}
+
+/// `HH:MM:SS.ss` in seconds
+fn parse_timestamp(s: &str) -> Option<f64> {
+    let mut parts = s.split(':');
+    let hours: f64 = parts.next()?.parse().ok()?;
+    let minutes: f64 = parts.next()?.parse().ok()?;
+    let seconds: f64 = parts.next()?.parse().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(hours * 3600.0 + minutes * 60.0 + seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ffmpeg_progress() {
+        let mut progress = FfmpegProgress::default();
+        assert_eq!(progress.feed("frame=  10 fps=0.0 size=0kB time=00:00:01.00 bitrate=N/A"), None);
+        assert_eq!(progress.feed("  Duration: 00:03:20.00, start: 0.000000, bitrate: 128 kb/s"), None);
+        assert_eq!(progress.feed("size=  1024kB time=00:01:40.00 bitrate= 83.9kbits/s speed=50x"), Some(50.0));
+        // Streams listed later don't replace the container duration
+        progress.feed("Duration: 00:00:10.00, start: 0.000000");
+        assert_eq!(progress.feed("size=N/A time=00:03:20.00 bitrate=N/A"), Some(100.0));
+        assert_eq!(progress.feed("size=N/A time=N/A bitrate=N/A"), None);
+    }
+
+    #[test]
+    fn test_output_paths() {
+        let profiles = ConversionProfile::defaults();
+        let (mp3, mp4) = (&profiles[0], &profiles[1]);
+        let video = Path::new("/dl/clip.mkv");
+
+        assert_eq!(mp3.output_path(video), Path::new("/dl/clip.mp3"));
+        assert_eq!(mp4.output_path(video), Path::new("/dl/clip.mp4"));
+        assert_eq!(mp4.output_path(Path::new("/dl/clip.mp4")), Path::new("/dl/clip.mp4"));
+        assert_eq!(mp3.output_path(Path::new("/dl/song.mp3")), Path::new("/dl/song (converted).mp3"));
+        assert_eq!(temp_output_path(Path::new("/dl/clip.mp4")), Path::new("/dl/.clip.converting.mp4"));
+    }
+
+    #[test]
+    fn test_profile_validation() {
+        assert!(ConversionProfile::defaults().iter().all(|p| p.validate().is_ok()));
+        let mut profile = ConversionProfile::defaults().remove(0);
+        profile.args.push("-y".to_string());
+        assert!(profile.validate().is_err());
+        profile.args.pop();
+        profile.extension = "mp3; rm".to_string();
+        assert!(profile.validate().is_err());
+
+        let action: PostAction = serde_json::from_str(r#"{"action":"convert","profile":"to_mp3_192k"}"#).unwrap();
+        assert_eq!(action.to_string(), "convert to_mp3_192k");
+    }
+}
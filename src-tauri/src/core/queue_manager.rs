@@ -1,9 +1,12 @@
 // src-tauri/src/core/queue_manager.rs
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
 use uuid::Uuid;
 use serde::{Serialize, Deserialize};
 
+use crate::core::download_task::DownloadStatus;
+use crate::utils::error::QueueError;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueueInfo {
     pub max_concurrent: u32,
@@ -12,6 +15,29 @@ pub struct QueueInfo {
     pub total_count: u32,
 }
 
+/// Divergence between the database, the queue and the running downloads.
+/// An empty report means all three agree.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueueConsistencyReport {
+    /// Rows with status Queued that the queue doesn't hold
+    pub queued_in_db_only: Vec<Uuid>,
+    /// Waiting entries whose row is missing or no longer Queued
+    pub stale_queue_entries: Vec<Uuid>,
+    /// Entries marked active in the queue with no running task
+    pub active_without_task: Vec<Uuid>,
+    /// Running tasks whose row isn't in an active status
+    pub running_with_stale_status: Vec<Uuid>,
+}
+
+impl QueueConsistencyReport {
+    pub fn is_consistent(&self) -> bool {
+        self.queued_in_db_only.is_empty()
+            && self.stale_queue_entries.is_empty()
+            && self.active_without_task.is_empty()
+            && self.running_with_stale_status.is_empty()
+    }
+}
+
 /// Manages download queue with concurrency control
 pub struct QueueManager {
     /// Queue of waiting download IDs
@@ -20,6 +46,8 @@ pub struct QueueManager {
     active: Vec<Uuid>,
     /// Maximum concurrent downloads
     max_concurrent: u32,
+    /// Nothing is promoted from the waiting queue while held
+    held: bool,
 }
 
 impl QueueManager {
@@ -28,13 +56,14 @@ impl QueueManager {
             queue: VecDeque::new(),
             active: Vec::new(),
             max_concurrent: max_concurrent.max(1),
+            held: false,
         }
     }
 
     /// Add a download to the queue
     /// Returns true if the download should start immediately
     pub fn enqueue(&mut self, id: Uuid) -> bool {
-        if self.active.len() < self.max_concurrent as usize {
+        if !self.held && self.active.len() < self.max_concurrent as usize {
             self.active.push(id);
             tracing::debug!(
                 "Download {} started immediately ({}/{} active)",
@@ -57,21 +86,28 @@ impl QueueManager {
     }
 
     /// Mark a download as complete and return next queued download
-    pub fn complete(&mut self, id: Uuid) -> Option<Uuid> {
+    pub fn complete(&mut self, id: Uuid) -> Result<Option<Uuid>, QueueError> {
+        if !self.is_active(&id) {
+            return Err(QueueError::NotFound(id));
+        }
         self.active.retain(|&active_id| active_id != id);
-        self.dequeue_next()
+        Ok(self.dequeue_next())
     }
 
-    /// Remove a download from queue or active list
-    pub fn remove(&mut self, id: Uuid) -> Option<Uuid> {
+    /// Remove a download from queue or active list and return the next
+    /// queued download if that freed a slot
+    pub fn remove(&mut self, id: Uuid) -> Result<Option<Uuid>, QueueError> {
+        if !self.is_active(&id) && !self.is_queued(&id) {
+            return Err(QueueError::NotFound(id));
+        }
         self.active.retain(|&active_id| active_id != id);
         self.queue.retain(|&queued_id| queued_id != id);
-        self.dequeue_next()
+        Ok(self.dequeue_next())
     }
 
     /// Get the next download from the queue if there's capacity
     fn dequeue_next(&mut self) -> Option<Uuid> {
-        if self.active.len() < self.max_concurrent as usize {
+        if !self.held && self.active.len() < self.max_concurrent as usize {
             if let Some(next_id) = self.queue.pop_front() {
                 self.active.push(next_id);
                 tracing::debug!(
@@ -91,16 +127,7 @@ impl QueueManager {
     /// Returns list of downloads that should start now
     pub fn set_max_concurrent(&mut self, max: u32) -> Vec<Uuid> {
         self.max_concurrent = max.max(1);
-        let mut to_start = Vec::new();
-
-        while self.active.len() < self.max_concurrent as usize {
-            if let Some(id) = self.queue.pop_front() {
-                self.active.push(id);
-                to_start.push(id);
-            } else {
-                break;
-            }
-        }
+        let to_start = self.fill();
 
         tracing::info!(
             "Max concurrent set to {}, starting {} queued downloads",
@@ -111,6 +138,22 @@ impl QueueManager {
         to_start
     }
 
+    /// Move waiting downloads into free slots, returning the ones that
+    /// should start now
+    pub fn fill(&mut self) -> Vec<Uuid> {
+        let mut to_start = Vec::new();
+        while let Some(id) = self.dequeue_next() {
+            to_start.push(id);
+        }
+        to_start
+    }
+
+    /// Stop or allow promoting waiting downloads into free slots. Callers
+    /// `fill` after lifting the hold.
+    pub fn set_held(&mut self, held: bool) {
+        self.held = held;
+    }
+
     /// Check if a download is currently active
     pub fn is_active(&self, id: &Uuid) -> bool {
         self.active.contains(id)
@@ -132,12 +175,21 @@ impl QueueManager {
     }
 
     /// Reorder queue - move download to position
-    pub fn reorder(&mut self, id: Uuid, position: usize) {
-        if let Some(pos) = self.queue.iter().position(|&qid| qid == id) {
-            self.queue.remove(pos);
-            let insert_pos = position.min(self.queue.len());
-            self.queue.insert(insert_pos, id);
-        }
+    pub fn reorder(&mut self, id: Uuid, position: usize) -> Result<(), QueueError> {
+        let pos = self
+            .queue
+            .iter()
+            .position(|&qid| qid == id)
+            .ok_or(QueueError::NotWaiting(id))?;
+        self.queue.remove(pos);
+        let insert_pos = position.min(self.queue.len());
+        self.queue.insert(insert_pos, id);
+        Ok(())
+    }
+
+    /// Move a waiting download to the front of the queue
+    pub fn promote(&mut self, id: Uuid) -> Result<(), QueueError> {
+        self.reorder(id, 0)
     }
 
     /// Get queue contents
@@ -149,6 +201,118 @@ impl QueueManager {
     pub fn get_active(&self) -> Vec<Uuid> {
         self.active.clone()
     }
+
+    /// Append a download to the waiting queue without starting it
+    pub fn push_waiting(&mut self, id: Uuid) {
+        if !self.active.contains(&id) && !self.queue.contains(&id) {
+            self.queue.push_back(id);
+        }
+    }
+
+    /// Rebuild the waiting queue from a persisted order.
+    /// Nothing is marked active; callers start downloads as capacity allows.
+    pub fn restore(&mut self, ids: Vec<Uuid>) {
+        for id in ids {
+            self.push_waiting(id);
+        }
+        tracing::info!("Restored {} queued downloads", self.queue.len());
+    }
+
+    /// Align a restored queue with the database statuses, given oldest first.
+    /// Entries that finished, were paused or no longer exist are dropped and
+    /// Queued rows missing from the saved order are appended.
+    /// Returns the entries that were running at shutdown; their rows should
+    /// be set back to Queued.
+    pub fn reconcile(&mut self, statuses: &[(Uuid, DownloadStatus)]) -> Vec<Uuid> {
+        let by_id: HashMap<Uuid, DownloadStatus> = statuses.iter().copied().collect();
+
+        let mut interrupted = Vec::new();
+        self.queue.retain(|id| match by_id.get(id) {
+            Some(DownloadStatus::Queued) => true,
+            Some(status) if status.is_active() => {
+                interrupted.push(*id);
+                true
+            }
+            _ => false,
+        });
+
+        for (id, status) in statuses {
+            if *status == DownloadStatus::Queued {
+                self.push_waiting(*id);
+            }
+        }
+
+        interrupted
+    }
+
+    /// Drop a finished download from the active list without promoting
+    /// the next waiting one. Downloads started outside the queue are
+    /// never in the active list, so an unknown id is not an error here.
+    pub fn release(&mut self, id: Uuid) {
+        self.active.retain(|&active_id| active_id != id);
+    }
+
+    /// Empty the queue in one step, returning the active entries followed
+    /// by the waiting ones in order. Nothing is promoted while draining.
+    pub fn drain_all(&mut self) -> Vec<Uuid> {
+        let mut drained: Vec<Uuid> = self.active.drain(..).collect();
+        drained.extend(self.queue.drain(..));
+        drained
+    }
+
+    /// Remove and return the waiting downloads matching `pred`, keeping
+    /// the order of the rest
+    pub fn drain_where<F>(&mut self, mut pred: F) -> Vec<Uuid>
+    where
+        F: FnMut(&Uuid) -> bool,
+    {
+        let mut drained = Vec::new();
+        self.queue.retain(|id| {
+            if pred(id) {
+                drained.push(*id);
+                false
+            } else {
+                true
+            }
+        });
+        drained
+    }
+
+    /// Compare the queue with the database statuses and the ids of the
+    /// downloads that currently have a running task
+    pub fn check_consistency(
+        &self,
+        statuses: &[(Uuid, DownloadStatus)],
+        running: &[Uuid],
+    ) -> QueueConsistencyReport {
+        let by_id: HashMap<Uuid, DownloadStatus> = statuses.iter().copied().collect();
+        let running: HashSet<Uuid> = running.iter().copied().collect();
+        let mut report = QueueConsistencyReport::default();
+
+        for (id, status) in statuses {
+            if *status == DownloadStatus::Queued && !self.is_queued(id) && !self.is_active(id) {
+                report.queued_in_db_only.push(*id);
+            }
+        }
+        for id in &self.queue {
+            if by_id.get(id) != Some(&DownloadStatus::Queued) {
+                report.stale_queue_entries.push(*id);
+            }
+        }
+        for id in &self.active {
+            if !running.contains(id) {
+                report.active_without_task.push(*id);
+            }
+        }
+        for id in &running {
+            if !by_id.get(id).is_some_and(|status| status.is_active()) {
+                report.running_with_stale_status.push(*id);
+            }
+        }
+        report.running_with_stale_status.sort();
+
+        report
+    }
 }
 
 #[cfg(test)]
@@ -172,8 +336,153 @@ mod tests {
 
         // Complete id1, id3 should start
         let next = queue.complete(id1);
-        assert_eq!(next, Some(id3));
+        assert_eq!(next, Ok(Some(id3)));
         assert_eq!(queue.info().active_count, 2);
         assert_eq!(queue.info().queued_count, 0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_queue_restore_preserves_order() {
+        let mut queue = QueueManager::new(1);
+
+        let ids: Vec<Uuid> = (0..4).map(|_| Uuid::new_v4()).collect();
+        queue.restore(vec![ids[0], ids[1], ids[1], ids[2], ids[3]]);
+
+        assert_eq!(queue.get_queue(), ids);
+        assert_eq!(queue.info().active_count, 0);
+
+        // Capacity frees up in the restored order
+        assert_eq!(queue.set_max_concurrent(2), vec![ids[0], ids[1]]);
+        assert_eq!(queue.get_queue(), vec![ids[2], ids[3]]);
+    }
+
+    #[test]
+    fn test_reconcile_with_database() {
+        let mut queue = QueueManager::new(2);
+
+        let queued = Uuid::new_v4();
+        let interrupted = Uuid::new_v4();
+        let paused = Uuid::new_v4();
+        let gone = Uuid::new_v4();
+        let unsaved = Uuid::new_v4();
+        queue.restore(vec![interrupted, paused, gone, queued]);
+
+        let statuses = vec![
+            (interrupted, DownloadStatus::Downloading),
+            (paused, DownloadStatus::Paused),
+            (queued, DownloadStatus::Queued),
+            (unsaved, DownloadStatus::Queued),
+        ];
+        assert_eq!(queue.reconcile(&statuses), vec![interrupted]);
+        assert_eq!(queue.get_queue(), vec![interrupted, queued, unsaved]);
+
+        // Once the interrupted row is requeued everything lines up
+        let statuses: Vec<_> = statuses
+            .into_iter()
+            .map(|(id, status)| if id == interrupted { (id, DownloadStatus::Queued) } else { (id, status) })
+            .collect();
+        assert!(queue.check_consistency(&statuses, &[]).is_consistent());
+        assert_eq!(queue.info().queued_count, 3);
+    }
+
+    #[test]
+    fn test_consistency_report_and_clear() {
+        let mut queue = QueueManager::new(1);
+
+        let running = Uuid::new_v4();
+        let waiting = Uuid::new_v4();
+        let cancelled = Uuid::new_v4();
+        let orphan = Uuid::new_v4();
+        queue.restore(vec![running, waiting, cancelled]);
+        queue.set_max_concurrent(1);
+
+        let statuses = vec![
+            (running, DownloadStatus::Downloading),
+            (waiting, DownloadStatus::Queued),
+            (cancelled, DownloadStatus::Cancelled),
+            (orphan, DownloadStatus::Queued),
+        ];
+        let report = queue.check_consistency(&statuses, &[]);
+        assert_eq!(report.queued_in_db_only, vec![orphan]);
+        assert_eq!(report.stale_queue_entries, vec![cancelled]);
+        assert_eq!(report.active_without_task, vec![running]);
+
+        let report = queue.check_consistency(&statuses, &[running, cancelled]);
+        assert!(report.active_without_task.is_empty());
+        assert_eq!(report.running_with_stale_status, vec![cancelled]);
+
+        // Draining never promotes a waiting entry into the active list
+        assert_eq!(queue.drain_all(), vec![running, waiting, cancelled]);
+        assert_eq!(queue.info().total_count, 0);
+    }
+
+    #[test]
+    fn test_drain_where_keeps_remaining_order() {
+        let mut queue = QueueManager::new(1);
+        let ids: Vec<Uuid> = (0..6).map(|_| Uuid::new_v4()).collect();
+        queue.restore(ids.clone());
+
+        let odd: Vec<Uuid> = ids.iter().copied().skip(1).step_by(2).collect();
+        assert_eq!(queue.drain_where(|id| odd.contains(id)), odd);
+        assert_eq!(queue.get_queue(), vec![ids[0], ids[2], ids[4]]);
+
+        assert!(queue.drain_where(|_| false).is_empty());
+        assert_eq!(queue.drain_all(), vec![ids[0], ids[2], ids[4]]);
+        assert!(queue.drain_all().is_empty());
+    }
+
+    #[test]
+    fn test_reorder_and_promote() {
+        let mut queue = QueueManager::new(1);
+        let ids: Vec<Uuid> = (0..4).map(|_| Uuid::new_v4()).collect();
+        assert!(queue.enqueue(ids[0]));
+        for id in &ids[1..] {
+            assert!(!queue.enqueue(*id));
+        }
+
+        queue.reorder(ids[1], 10).unwrap();
+        assert_eq!(queue.get_queue(), vec![ids[2], ids[3], ids[1]]);
+
+        queue.promote(ids[3]).unwrap();
+        assert_eq!(queue.get_queue(), vec![ids[3], ids[2], ids[1]]);
+
+        // The active download isn't waiting, so it can't be moved
+        assert_eq!(queue.promote(ids[0]), Err(QueueError::NotWaiting(ids[0])));
+        let unknown = Uuid::new_v4();
+        assert_eq!(queue.reorder(unknown, 0), Err(QueueError::NotWaiting(unknown)));
+    }
+
+    #[test]
+    fn test_unknown_ids_are_errors() {
+        let mut queue = QueueManager::new(1);
+        let id = Uuid::new_v4();
+        let next = Uuid::new_v4();
+        assert_eq!(queue.remove(id), Err(QueueError::NotFound(id)));
+        assert_eq!(queue.complete(id), Err(QueueError::NotFound(id)));
+
+        assert!(queue.enqueue(id));
+        assert!(!queue.enqueue(next));
+        assert_eq!(queue.remove(id), Ok(Some(next)));
+        assert_eq!(queue.complete(id), Err(QueueError::NotFound(id)));
+        assert_eq!(queue.complete(next), Ok(None));
+    }
+
+    #[test]
+    fn test_held_queue_promotes_nothing() {
+        let mut queue = QueueManager::new(1);
+        let running = Uuid::new_v4();
+        let waiting = Uuid::new_v4();
+        assert!(queue.enqueue(running));
+        assert!(!queue.enqueue(waiting));
+
+        queue.set_held(true);
+        assert_eq!(queue.remove(running), Ok(None));
+        let added = Uuid::new_v4();
+        assert!(!queue.enqueue(added));
+        assert!(queue.fill().is_empty());
+
+        queue.set_held(false);
+        assert_eq!(queue.fill(), vec![waiting]);
+        assert_eq!(queue.get_queue(), vec![added]);
+    }
+}
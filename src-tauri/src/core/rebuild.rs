@@ -0,0 +1,555 @@
+// src-tauri/src/core/rebuild.rs
+// Rebuilds the download list from files on disk after the database was
+// lost or damaged. Files are matched against whatever history survived:
+// rows whose file went missing, and an `export_history` file. Matches
+// become Completed downloads again. Files nothing knows about are only
+// imported when the user picks them, flagged `unknown_origin`. Segment
+// folders (`.sd_<id>`) no row owns become paused downloads again from
+// their resume.json. A scan always reports first; nothing is written
+// until it is run again with a selection.
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::core::category::Category;
+use crate::core::checksum::{ChecksumAlgorithm, ChecksumVerifier};
+use crate::core::download_task::{DownloadStatus, DownloadTask};
+use crate::core::resume_manager::{ResumeData, ResumeManager};
+use crate::database::db::Database;
+use crate::utils::error::DownloadError;
+
+/// Name prefix of the folder segments are downloaded into
+const SEGMENT_DIR_PREFIX: &str = ".sd_";
+
+/// Files still being written, by this app or a browser
+const PARTIAL_EXTENSIONS: &[&str] = &["part", "partial", "tmp", "crdownload", "download", "pending", "aria2", "!ut"];
+
+/// Which surviving record a file was matched against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistorySource {
+    /// A row still in the database whose file went missing
+    Database,
+    /// An entry of the history export
+    Export,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchedBy {
+    NameAndSize,
+    Checksum,
+}
+
+/// A file that history knows; becomes a Completed download
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecognizedFile {
+    pub path: PathBuf,
+    pub size: u64,
+    pub url: String,
+    pub source: HistorySource,
+    pub matched_by: MatchedBy,
+    /// The row or export entry it matched
+    pub history_id: Option<Uuid>,
+}
+
+/// A file nothing knows about; imported only when selected
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnknownFile {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// A segment folder without a download; can become a paused one
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumableDownload {
+    pub id: Uuid,
+    pub temp_dir: PathBuf,
+    pub url: String,
+    pub file_name: String,
+    pub save_path: PathBuf,
+    pub total_size: Option<u64>,
+    /// Bytes already in the segment files
+    pub downloaded: u64,
+}
+
+/// What to import besides the recognized files
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RebuildSelection {
+    #[serde(default)]
+    pub import_unknown: Vec<PathBuf>,
+    /// Ids of `ResumableDownload`s to turn back into downloads
+    #[serde(default)]
+    pub reconstruct: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RebuildReport {
+    pub recognized: Vec<RecognizedFile>,
+    pub unknown: Vec<UnknownFile>,
+    pub resumable: Vec<ResumableDownload>,
+    /// Partial and temporary files left alone
+    pub skipped: Vec<PathBuf>,
+    /// Files a download already points at
+    pub already_tracked: usize,
+    /// False for the dry run
+    pub applied: bool,
+    /// Downloads created or restored by applying
+    pub imported: usize,
+}
+
+/// One entry of an `export_history` file; only what matching needs
+#[derive(Debug, Deserialize)]
+struct ExportedItem {
+    id: String,
+    url: String,
+    file_name: String,
+    total_size: Option<u64>,
+    status: String,
+    #[serde(default)]
+    category: Option<String>,
+    #[serde(default)]
+    completed_at: Option<NaiveDateTime>,
+}
+
+/// A surviving record a file can be matched against
+#[derive(Debug, Clone)]
+struct HistoryEntry {
+    id: Option<Uuid>,
+    source: HistorySource,
+    url: String,
+    file_name: String,
+    size: Option<u64>,
+    checksum: Option<(ChecksumAlgorithm, String)>,
+    category: Option<String>,
+    completed_at: Option<NaiveDateTime>,
+}
+
+/// Files, segment folders and skipped entries under the scanned folders
+#[derive(Debug, Default)]
+struct Found {
+    files: Vec<(PathBuf, u64)>,
+    segment_dirs: Vec<(Uuid, PathBuf)>,
+    skipped: Vec<PathBuf>,
+}
+
+/// Scan `scan_dirs` and report what would be rebuilt. With a selection the
+/// report is applied too.
+pub async fn rebuild(
+    db: &Database,
+    scan_dirs: &[PathBuf],
+    history_file: Option<&Path>,
+    selection: Option<&RebuildSelection>,
+) -> Result<RebuildReport, DownloadError> {
+    let tasks = db.get_all_downloads().await?;
+    let known_ids: HashSet<Uuid> = tasks.iter().map(|t| t.id).collect();
+    let tracked: HashSet<PathBuf> = tasks.iter().map(|t| t.save_path.clone()).collect();
+
+    let mut history: Vec<HistoryEntry> = tasks
+        .iter()
+        .filter(|t| t.status == DownloadStatus::Completed && !t.save_path.exists())
+        .map(entry_from_task)
+        .collect();
+    if let Some(path) = history_file {
+        history.extend(load_export(path).await?.into_iter().filter(|e| match e.id {
+            Some(id) => !known_ids.contains(&id),
+            None => true,
+        }));
+    }
+
+    let dirs = scan_dirs.to_vec();
+    let found = tokio::task::spawn_blocking(move || walk(&dirs))
+        .await
+        .map_err(|e| DownloadError::Unknown(format!("Scan failed: {}", e)))?;
+
+    let mut report = RebuildReport {
+        skipped: found.skipped,
+        ..Default::default()
+    };
+    // What each recognized file matched, in the same order
+    let mut matched = Vec::new();
+    for (path, size) in found.files {
+        if tracked.contains(&path) {
+            report.already_tracked += 1;
+            continue;
+        }
+        match match_file(&mut history, &path, size).await {
+            Some((entry, matched_by)) => {
+                report.recognized.push(RecognizedFile {
+                    path,
+                    size,
+                    url: entry.url.clone(),
+                    source: entry.source,
+                    matched_by,
+                    history_id: entry.id,
+                });
+                matched.push(entry);
+            }
+            None => report.unknown.push(UnknownFile { path, size }),
+        }
+    }
+    for (id, temp_dir) in found.segment_dirs {
+        if known_ids.contains(&id) {
+            continue;
+        }
+        match ResumeManager::load(&temp_dir).await {
+            Ok(Some(data)) if data.download_id == id => {
+                let downloaded = segment_bytes(&temp_dir, &data).await;
+                report.resumable.push(ResumableDownload {
+                    id,
+                    temp_dir,
+                    url: data.url,
+                    file_name: data.file_name,
+                    save_path: data.save_path,
+                    total_size: data.total_size,
+                    downloaded,
+                });
+            }
+            Ok(_) => report.skipped.push(temp_dir),
+            Err(e) => {
+                tracing::warn!("Unreadable resume data in {:?}: {}", temp_dir, e);
+                report.skipped.push(temp_dir);
+            }
+        }
+    }
+
+    if let Some(selection) = selection {
+        report.imported = apply(db, &tasks, &matched, &report, selection).await?;
+        report.applied = true;
+    }
+    Ok(report)
+}
+
+async fn apply(
+    db: &Database,
+    tasks: &[DownloadTask],
+    matched: &[HistoryEntry],
+    report: &RebuildReport,
+    selection: &RebuildSelection,
+) -> Result<usize, DownloadError> {
+    let now = chrono::Local::now().naive_local();
+    let mut imported = 0;
+
+    for (file, entry) in report.recognized.iter().zip(matched) {
+        let existing = match entry.source {
+            HistorySource::Database => tasks.iter().find(|t| Some(t.id) == entry.id).cloned(),
+            HistorySource::Export => None,
+        };
+        let restored = existing.is_some();
+        let mut task = existing.unwrap_or_else(|| {
+            let mut task = completed_task(file.url.clone(), &file.path, file.size, now);
+            task.id = entry.id.unwrap_or(task.id);
+            task.category = entry.category.clone().or(task.category);
+            task.completed_at = entry.completed_at.or(task.completed_at);
+            task
+        });
+        // A row whose file moved now points at where it was found
+        task.save_path = file.path.clone();
+        task.file_name = file_name_of(&file.path);
+        task.total_size = Some(file.size);
+        task.downloaded_size = file.size;
+        task.status = DownloadStatus::Completed;
+        if restored {
+            db.update_download(&task).await?;
+        } else {
+            db.insert_download(&task).await?;
+        }
+        imported += 1;
+    }
+
+    for file in report.unknown.iter().filter(|f| selection.import_unknown.contains(&f.path)) {
+        let url = url::Url::from_file_path(&file.path)
+            .map(String::from)
+            .unwrap_or_else(|_| file.path.to_string_lossy().into_owned());
+        let mut task = completed_task(url, &file.path, file.size, now);
+        task.unknown_origin = true;
+        db.insert_download(&task).await?;
+        imported += 1;
+    }
+
+    for found in report.resumable.iter().filter(|r| selection.reconstruct.contains(&r.id)) {
+        let data = match ResumeManager::load(&found.temp_dir).await? {
+            Some(data) => data,
+            None => continue,
+        };
+        let segments = data.segments.len().clamp(1, u8::MAX as usize) as u8;
+        let mut task = DownloadTask::new(data.url, data.file_name, data.save_path, segments);
+        task.id = data.download_id;
+        task.total_size = data.total_size;
+        task.downloaded_size = found.downloaded;
+        task.etag = data.etag;
+        task.supports_range = true;
+        task.status = DownloadStatus::Paused;
+        task.category = Some(Category::detect(&task.file_name, None));
+        db.insert_download(&task).await?;
+        imported += 1;
+    }
+
+    Ok(imported)
+}
+
+fn completed_task(url: String, path: &Path, size: u64, now: NaiveDateTime) -> DownloadTask {
+    let file_name = file_name_of(path);
+    let mut task = DownloadTask::new(url, file_name.clone(), path.to_path_buf(), 1);
+    task.total_size = Some(size);
+    task.downloaded_size = size;
+    task.status = DownloadStatus::Completed;
+    task.completed_at = Some(now);
+    task.category = Some(Category::detect(&file_name, None));
+    task
+}
+
+fn file_name_of(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+fn entry_from_task(task: &DownloadTask) -> HistoryEntry {
+    let checksum = task.actual_checksum.clone().map(|sum| {
+        let algorithm = task.checksum_algorithm.clone().unwrap_or(ChecksumAlgorithm::Sha256);
+        (algorithm, sum)
+    });
+    HistoryEntry {
+        id: Some(task.id),
+        source: HistorySource::Database,
+        url: task.url.clone(),
+        file_name: task.file_name.clone(),
+        size: task.total_size,
+        checksum,
+        category: task.category.clone(),
+        completed_at: task.completed_at,
+    }
+}
+
+/// Completed entries of an `export_history` file
+async fn load_export(path: &Path) -> Result<Vec<HistoryEntry>, DownloadError> {
+    let json = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| DownloadError::FileError(format!("Failed to read history export: {}", e)))?;
+    let items: Vec<ExportedItem> = serde_json::from_str(&json)
+        .map_err(|e| DownloadError::FileError(format!("Failed to parse history export: {}", e)))?;
+    Ok(items
+        .into_iter()
+        .filter(|item| item.status == DownloadStatus::Completed.as_str())
+        .map(|item| HistoryEntry {
+            id: Uuid::parse_str(&item.id).ok(),
+            source: HistorySource::Export,
+            url: item.url,
+            file_name: item.file_name,
+            size: item.total_size,
+            checksum: None,
+            category: item.category,
+            completed_at: item.completed_at,
+        })
+        .collect())
+}
+
+/// Take the history entry `path` matches: same name and size, or failing
+/// that the same checksum. Each entry matches one file at most.
+async fn match_file(history: &mut Vec<HistoryEntry>, path: &Path, size: u64) -> Option<(HistoryEntry, MatchedBy)> {
+    let name = file_name_of(path);
+    if let Some(index) = history.iter().position(|e| e.file_name == name && e.size == Some(size)) {
+        return Some((history.remove(index), MatchedBy::NameAndSize));
+    }
+
+    // Hash only for entries that could be this file, once per algorithm
+    let mut sums: HashMap<String, Option<String>> = HashMap::new();
+    for index in 0..history.len() {
+        let Some((algorithm, expected)) = history[index].checksum.clone() else {
+            continue;
+        };
+        if history[index].size.is_some_and(|s| s != size) {
+            continue;
+        }
+        let key = algorithm.to_string();
+        if !sums.contains_key(&key) {
+            let sum = ChecksumVerifier::calculate(path, &algorithm).await.ok();
+            sums.insert(key.clone(), sum);
+        }
+        if sums[&key].as_deref().is_some_and(|sum| sum.eq_ignore_ascii_case(&expected)) {
+            return Some((history.remove(index), MatchedBy::Checksum));
+        }
+    }
+    None
+}
+
+/// Bytes in a segment folder's files, counting no more than each chunk
+/// holds. Read only: nothing is truncated until the download resumes.
+async fn segment_bytes(temp_dir: &Path, data: &ResumeData) -> u64 {
+    let mut total = 0;
+    for segment in &data.segments {
+        let chunk_size = segment.end_byte.saturating_sub(segment.start_byte) + 1;
+        let path = temp_dir.join(format!("segment_{}", segment.segment_id));
+        if let Ok(metadata) = tokio::fs::metadata(&path).await {
+            total += metadata.len().min(chunk_size);
+        }
+    }
+    total
+}
+
+fn is_partial(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| PARTIAL_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Walk the folders. Hidden entries are passed over, segment folders are
+/// collected without entering them, symlinks are not followed.
+fn walk(dirs: &[PathBuf]) -> Found {
+    let mut found = Found::default();
+    let mut pending: Vec<PathBuf> = dirs.to_vec();
+    let mut seen = HashSet::new();
+
+    while let Some(dir) = pending.pop() {
+        if !seen.insert(dir.clone()) {
+            continue;
+        }
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::warn!("Can't scan {:?}: {}", dir, e);
+                continue;
+            }
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                if let Some(id) = name.strip_prefix(SEGMENT_DIR_PREFIX) {
+                    match Uuid::parse_str(id) {
+                        Ok(id) => found.segment_dirs.push((id, path)),
+                        Err(_) => found.skipped.push(path),
+                    }
+                } else if !name.starts_with('.') {
+                    pending.push(path);
+                }
+            } else if file_type.is_file() {
+                if is_partial(&name) {
+                    found.skipped.push(path);
+                } else if !name.starts_with('.') {
+                    let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                    found.files.push((path, size));
+                }
+            }
+        }
+    }
+
+    found.files.sort();
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::resume_manager::SegmentResumeData;
+
+    async fn setup() -> (PathBuf, Database) {
+        let root = std::env::temp_dir().join(format!("afk-dunld-rebuild-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("data")).unwrap();
+        std::fs::create_dir_all(root.join("files/sub")).unwrap();
+        let db = Database::new(&root.join("data")).await.unwrap();
+        db.run_migrations().await.unwrap();
+        (root, db)
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_then_apply() {
+        let (root, db) = setup().await;
+        let files = root.join("files");
+        std::fs::write(files.join("report.pdf"), vec![1u8; 300]).unwrap();
+        std::fs::write(files.join("sub/moved.iso"), vec![2u8; 500]).unwrap();
+        std::fs::write(files.join("mystery.bin"), vec![3u8; 70]).unwrap();
+        std::fs::write(files.join("movie.mkv.part"), vec![4u8; 10]).unwrap();
+
+        // A row that survived but lost its file, known by checksum only
+        let mut survivor = DownloadTask::new("https://example.com/old-name.iso".into(), "old-name.iso".into(), root.join("gone/old-name.iso"), 1);
+        survivor.status = DownloadStatus::Completed;
+        survivor.actual_checksum = Some(ChecksumVerifier::calculate(&files.join("sub/moved.iso"), &ChecksumAlgorithm::Sha256).await.unwrap());
+        survivor.checksum_algorithm = Some(ChecksumAlgorithm::Sha256);
+        db.insert_download(&survivor).await.unwrap();
+
+        // An export entry matching by name and size
+        let exported_id = Uuid::new_v4();
+        let export = root.join("history.json");
+        let items = serde_json::json!([{
+            "id": exported_id.to_string(), "url": "https://example.com/report.pdf", "file_name": "report.pdf",
+            "total_size": 300, "status": "Completed", "completed_at": null, "created_at": "2024-01-01T00:00:00",
+            "category": "documents", "download_speed_avg": 0.0, "download_time": null,
+        }]);
+        std::fs::write(&export, items.to_string()).unwrap();
+
+        // A segment folder nothing owns
+        let orphan = Uuid::new_v4();
+        let temp_dir = files.join(format!(".sd_{}", orphan));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        std::fs::write(temp_dir.join("segment_0"), vec![5u8; 40]).unwrap();
+        let resume = ResumeData {
+            download_id: orphan,
+            url: "https://example.com/big.zip".into(),
+            file_name: "big.zip".into(),
+            save_path: files.join("big.zip"),
+            total_size: Some(200),
+            segments: vec![
+                SegmentResumeData { segment_id: 0, start_byte: 0, end_byte: 99, downloaded_bytes: 40, completed: false },
+                SegmentResumeData { segment_id: 1, start_byte: 100, end_byte: 199, downloaded_bytes: 0, completed: false },
+            ],
+            etag: None,
+            created_at: "2024-01-01 00:00:00".into(),
+            layout_version: crate::core::chunk_manager::CURRENT_LAYOUT_VERSION,
+        };
+        ResumeManager::save(&temp_dir, &resume).await.unwrap();
+
+        let dirs = vec![files.clone()];
+        let report = rebuild(&db, &dirs, Some(&export), None).await.unwrap();
+        assert!(!report.applied);
+        assert_eq!(report.recognized.len(), 2);
+        let by_name = |name: &str| report.recognized.iter().find(|f| f.path.ends_with(name)).unwrap();
+        assert_eq!(by_name("report.pdf").matched_by, MatchedBy::NameAndSize);
+        assert_eq!(by_name("report.pdf").history_id, Some(exported_id));
+        assert_eq!(by_name("moved.iso").matched_by, MatchedBy::Checksum);
+        assert_eq!(by_name("moved.iso").source, HistorySource::Database);
+        assert_eq!(report.unknown, vec![UnknownFile { path: files.join("mystery.bin"), size: 70 }]);
+        assert_eq!(report.skipped, vec![files.join("movie.mkv.part")]);
+        assert_eq!(report.resumable.len(), 1);
+        assert_eq!(report.resumable[0].downloaded, 40);
+        // The dry run wrote nothing
+        assert_eq!(db.get_all_downloads().await.unwrap().len(), 1);
+
+        let selection = RebuildSelection { import_unknown: vec![files.join("mystery.bin")], reconstruct: vec![orphan] };
+        let applied = rebuild(&db, &dirs, Some(&export), Some(&selection)).await.unwrap();
+        assert!(applied.applied);
+        assert_eq!(applied.imported, 4);
+
+        let tasks = db.get_all_downloads().await.unwrap();
+        assert_eq!(tasks.len(), 4);
+        let restored = tasks.iter().find(|t| t.id == survivor.id).unwrap();
+        assert_eq!(restored.save_path, files.join("sub/moved.iso"));
+        let report_task = tasks.iter().find(|t| t.id == exported_id).unwrap();
+        assert_eq!(report_task.status, DownloadStatus::Completed);
+        assert_eq!(report_task.category.as_deref(), Some("documents"));
+        let mystery = tasks.iter().find(|t| t.file_name == "mystery.bin").unwrap();
+        assert!(mystery.unknown_origin);
+        assert!(mystery.url.starts_with("file://"));
+        let paused = tasks.iter().find(|t| t.id == orphan).unwrap();
+        assert_eq!((paused.status.clone(), paused.downloaded_size, paused.segments), (DownloadStatus::Paused, 40, 2));
+
+        // Run again, everything is tracked and nothing new is found
+        let again = rebuild(&db, &dirs, Some(&export), None).await.unwrap();
+        assert_eq!(again.already_tracked, 3);
+        assert!(again.recognized.is_empty() && again.unknown.is_empty() && again.resumable.is_empty());
+
+        db.pool().close().await;
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,264 @@
+// src-tauri/src/core/resource_guard.rs
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::core::data_usage::DataCapPolicy;
+use crate::utils::network_info::{self, NetworkIdentity};
+
+/// Entry in the allowed networks list that matches any wired connection
+pub const ETHERNET: &str = "Ethernet";
+
+/// Conditions large downloads wait for before they start
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConditionPolicy {
+    /// Hold large downloads while the machine runs on battery
+    pub only_on_ac_power: bool,
+    /// Wi-Fi SSIDs (or [`ETHERNET`]) large downloads may run on; empty
+    /// allows any network
+    pub allowed_networks: Vec<String>,
+    /// Downloads at least this big are subject to the conditions; unknown
+    /// sizes are not held
+    pub large_download_bytes: u64,
+}
+
+impl Default for ConditionPolicy {
+    fn default() -> Self {
+        Self {
+            only_on_ac_power: false,
+            allowed_networks: Vec::new(),
+            large_download_bytes: 100 * 1024 * 1024,
+        }
+    }
+}
+
+/// Machine state the policy is checked against
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Environment {
+    /// None when there is no battery or its state can't be read
+    pub on_ac_power: Option<bool>,
+    pub network: NetworkIdentity,
+}
+
+impl Environment {
+    /// Probe the current machine. Blocking.
+    pub fn detect() -> Self {
+        Self {
+            on_ac_power: network_info::on_ac_power(),
+            network: network_info::current_network(),
+        }
+    }
+}
+
+/// Why a download is being held
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "condition", rename_all = "camelCase")]
+pub enum BlockingCondition {
+    OnBattery,
+    NetworkNotAllowed { network: NetworkIdentity },
+    /// The data budget for this period is used up
+    DataCapReached,
+}
+
+impl std::fmt::Display for BlockingCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlockingCondition::OnBattery => write!(f, "waiting for AC power"),
+            BlockingCondition::NetworkNotAllowed { network } => {
+                write!(f, "{} is not an allowed network", network)
+            }
+            BlockingCondition::DataCapReached => write!(f, "waiting for the data cap to reset"),
+        }
+    }
+}
+
+impl ConditionPolicy {
+    pub fn is_enabled(&self) -> bool {
+        self.only_on_ac_power || !self.allowed_networks.is_empty()
+    }
+
+    /// Every condition keeping a download of `total_size` from running
+    pub fn blocking(&self, total_size: Option<u64>, env: &Environment) -> Vec<BlockingCondition> {
+        let mut blocking = Vec::new();
+        if !total_size.is_some_and(|size| size >= self.large_download_bytes) {
+            return blocking;
+        }
+
+        if self.only_on_ac_power && env.on_ac_power == Some(false) {
+            blocking.push(BlockingCondition::OnBattery);
+        }
+        if !self.allowed_networks.is_empty() && !self.allows_network(&env.network) {
+            blocking.push(BlockingCondition::NetworkNotAllowed { network: env.network.clone() });
+        }
+        blocking
+    }
+
+    fn allows_network(&self, network: &NetworkIdentity) -> bool {
+        self.allowed_networks.iter().any(|allowed| match network {
+            NetworkIdentity::Ethernet => allowed.eq_ignore_ascii_case(ETHERNET),
+            NetworkIdentity::Wifi(ssid) => allowed == ssid,
+            NetworkIdentity::Unknown => false,
+        })
+    }
+}
+
+/// Holds the condition policy and checks downloads against the machine
+/// and the data budget
+pub struct ResourceGuard {
+    policy: parking_lot::RwLock<ConditionPolicy>,
+    data_cap: parking_lot::RwLock<DataCapPolicy>,
+    /// Set while the data budget is used up and downloads are held
+    data_capped: AtomicBool,
+}
+
+impl ResourceGuard {
+    pub fn new(policy: ConditionPolicy) -> Self {
+        Self {
+            policy: parking_lot::RwLock::new(policy),
+            data_cap: parking_lot::RwLock::new(DataCapPolicy::default()),
+            data_capped: AtomicBool::new(false),
+        }
+    }
+
+    pub fn policy(&self) -> ConditionPolicy {
+        self.policy.read().clone()
+    }
+
+    pub fn set_policy(&self, policy: ConditionPolicy) {
+        *self.policy.write() = policy;
+    }
+
+    pub fn data_cap_policy(&self) -> DataCapPolicy {
+        self.data_cap.read().clone()
+    }
+
+    pub fn set_data_cap_policy(&self, policy: DataCapPolicy) {
+        *self.data_cap.write() = policy;
+    }
+
+    pub fn is_data_capped(&self) -> bool {
+        self.data_capped.load(Ordering::Relaxed)
+    }
+
+    /// Hold every download until cleared; returns the previous value
+    pub fn set_data_capped(&self, capped: bool) -> bool {
+        self.data_capped.swap(capped, Ordering::Relaxed)
+    }
+
+    /// Probe the machine off the async runtime
+    pub async fn environment(&self) -> Environment {
+        tokio::task::spawn_blocking(Environment::detect)
+            .await
+            .unwrap_or(Environment { on_ac_power: None, network: NetworkIdentity::Unknown })
+    }
+
+    /// Conditions blocking a download of `total_size` right now, whatever
+    /// its size once the data cap is reached. Skips probing the machine
+    /// when no condition is configured.
+    pub async fn check(&self, total_size: Option<u64>) -> Vec<BlockingCondition> {
+        self.check_all(&[total_size]).await.pop().unwrap_or_default()
+    }
+
+    /// `check` for several downloads, probing the machine once
+    pub async fn check_all(&self, total_sizes: &[Option<u64>]) -> Vec<Vec<BlockingCondition>> {
+        let policy = self.policy();
+        let environment = if policy.is_enabled() {
+            Some(self.environment().await)
+        } else {
+            None
+        };
+        let capped = self.is_data_capped();
+
+        total_sizes
+            .iter()
+            .map(|total_size| {
+                let mut blocking = Vec::new();
+                if capped {
+                    blocking.push(BlockingCondition::DataCapReached);
+                }
+                if let Some(env) = &environment {
+                    blocking.extend(policy.blocking(*total_size, env));
+                }
+                blocking
+            })
+            .collect()
+    }
+}
+
+impl Default for ResourceGuard {
+    fn default() -> Self {
+        Self::new(ConditionPolicy::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LARGE: Option<u64> = Some(500 * 1024 * 1024);
+
+    fn env(on_ac_power: Option<bool>, network: NetworkIdentity) -> Environment {
+        Environment { on_ac_power, network }
+    }
+
+    #[test]
+    fn test_battery_holds_only_large_downloads() {
+        let policy = ConditionPolicy { only_on_ac_power: true, ..Default::default() };
+        let battery = env(Some(false), NetworkIdentity::Ethernet);
+
+        assert_eq!(policy.blocking(LARGE, &battery), vec![BlockingCondition::OnBattery]);
+        assert!(policy.blocking(Some(1024), &battery).is_empty());
+        assert!(policy.blocking(None, &battery).is_empty());
+        assert!(policy.blocking(LARGE, &env(Some(true), NetworkIdentity::Ethernet)).is_empty());
+        // Desktops report no battery at all
+        assert!(policy.blocking(LARGE, &env(None, NetworkIdentity::Ethernet)).is_empty());
+    }
+
+    #[test]
+    fn test_allowed_networks() {
+        let policy = ConditionPolicy {
+            allowed_networks: vec!["Home".to_string(), "ethernet".to_string()],
+            ..Default::default()
+        };
+        let wifi = |ssid: &str| env(Some(true), NetworkIdentity::Wifi(ssid.to_string()));
+
+        assert!(policy.blocking(LARGE, &wifi("Home")).is_empty());
+        assert!(policy.blocking(LARGE, &env(None, NetworkIdentity::Ethernet)).is_empty());
+        assert_eq!(
+            policy.blocking(LARGE, &wifi("Cafe")),
+            vec![BlockingCondition::NetworkNotAllowed {
+                network: NetworkIdentity::Wifi("Cafe".to_string())
+            }]
+        );
+        // An unidentified network isn't trusted
+        assert_eq!(policy.blocking(LARGE, &env(None, NetworkIdentity::Unknown)).len(), 1);
+    }
+
+    #[test]
+    fn test_both_conditions_reported() {
+        let policy = ConditionPolicy {
+            only_on_ac_power: true,
+            allowed_networks: vec![ETHERNET.to_string()],
+            ..Default::default()
+        };
+        let blocking = policy.blocking(LARGE, &env(Some(false), NetworkIdentity::Wifi("Cafe".into())));
+        assert_eq!(blocking.len(), 2);
+        assert_eq!(blocking[0].to_string(), "waiting for AC power");
+        assert_eq!(blocking[1].to_string(), "Wi-Fi \"Cafe\" is not an allowed network");
+    }
+
+    #[tokio::test]
+    async fn test_data_cap_holds_every_download() {
+        let guard = ResourceGuard::default();
+        assert!(guard.check_all(&[LARGE, None]).await.iter().all(Vec::is_empty));
+
+        guard.set_data_capped(true);
+        assert_eq!(
+            guard.check_all(&[LARGE, None]).await,
+            vec![vec![BlockingCondition::DataCapReached]; 2]
+        );
+        assert_eq!(guard.check(Some(1)).await, vec![BlockingCondition::DataCapReached]);
+    }
+}
@@ -3,6 +3,8 @@
 use std::path::{Path, PathBuf};
 use serde::{Serialize, Deserialize};
 use uuid::Uuid;
+use crate::core::chunk_manager::{Chunk, ChunkManager, LEGACY_LAYOUT_VERSION};
+use crate::core::waste::{WasteCounter, WasteReason};
 use crate::utils::error::DownloadError;
 
 /// Resume data saved to disk for crash recovery
@@ -16,6 +18,33 @@ pub struct ResumeData {
     pub segments: Vec<SegmentResumeData>,
     pub etag: Option<String>,
     pub created_at: String,
+    /// Chunk layout the segments were split with; files written before
+    /// versioning existed use the legacy even split
+    #[serde(default = "legacy_layout_version")]
+    pub layout_version: u32,
+}
+
+fn legacy_layout_version() -> u32 {
+    LEGACY_LAYOUT_VERSION
+}
+
+impl ResumeData {
+    /// Chunks to resume with: the saved ranges when present, otherwise
+    /// the geometry of the layout version the download was started with
+    pub fn chunks(&self, total_size: u64, requested_segments: u8) -> Vec<Chunk> {
+        if !self.segments.is_empty() {
+            return self
+                .segments
+                .iter()
+                .map(|s| Chunk {
+                    id: s.segment_id,
+                    start: s.start_byte,
+                    end: s.end_byte,
+                })
+                .collect();
+        }
+        ChunkManager::split_for_layout(self.layout_version, total_size, requested_segments)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -102,6 +131,93 @@ impl ResumeManager {
         progress
     }
 
+    /// Match segment files left by an interrupted download against their
+    /// chunks before resuming. The file is trusted over any recorded
+    /// count: one longer than its chunk is truncated to it, one that can't
+    /// be read is removed so the segment starts over. Bytes cut or removed
+    /// go to `waste`.
+    pub async fn reconcile_segments(temp_dir: &Path, chunks: &[Chunk], waste: &WasteCounter) -> Vec<SegmentResumeData> {
+        let mut segments = Vec::with_capacity(chunks.len());
+
+        for chunk in chunks {
+            let path = temp_dir.join(format!("segment_{}", chunk.id));
+            let downloaded = match Self::segment_file_len(&path).await {
+                Ok(None) => 0,
+                Ok(Some(len)) if len > chunk.size() => {
+                    tracing::warn!(
+                        "Segment {} has {} bytes for a {} byte chunk, truncating",
+                        chunk.id, len, chunk.size()
+                    );
+                    match Self::truncate(&path, chunk.size()).await {
+                        Ok(()) => {
+                            waste.add(len - chunk.size(), WasteReason::FailedSegment);
+                            chunk.size()
+                        }
+                        Err(e) => {
+                            waste.add(len, WasteReason::FailedSegment);
+                            Self::restart_segment(&path, chunk.id, &e).await
+                        }
+                    }
+                }
+                Ok(Some(len)) => len,
+                Err(e) => {
+                    // Only a file has bytes to lose; a directory in its place doesn't
+                    let len = tokio::fs::metadata(&path)
+                        .await
+                        .ok()
+                        .filter(|m| m.is_file())
+                        .map_or(0, |m| m.len());
+                    waste.add(len, WasteReason::FailedSegment);
+                    Self::restart_segment(&path, chunk.id, &e).await
+                }
+            };
+
+            segments.push(SegmentResumeData {
+                segment_id: chunk.id,
+                start_byte: chunk.start,
+                end_byte: chunk.end,
+                downloaded_bytes: downloaded,
+                completed: downloaded == chunk.size(),
+            });
+        }
+
+        segments
+    }
+
+    /// Length of a segment file, None when it doesn't exist. Errors when
+    /// it exists but isn't a readable file.
+    async fn segment_file_len(path: &Path) -> std::io::Result<Option<u64>> {
+        let metadata = match tokio::fs::metadata(path).await {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        if !metadata.is_file() {
+            return Err(std::io::Error::other("not a regular file"));
+        }
+        tokio::fs::File::open(path).await?;
+        Ok(Some(metadata.len()))
+    }
+
+    async fn truncate(path: &Path, len: u64) -> std::io::Result<()> {
+        let file = tokio::fs::OpenOptions::new().write(true).open(path).await?;
+        file.set_len(len).await?;
+        file.sync_all().await
+    }
+
+    async fn restart_segment(path: &Path, segment_id: u32, reason: &std::io::Error) -> u64 {
+        tracing::warn!("Segment {} file is unusable ({}), downloading it again", segment_id, reason);
+        let removed = if path.is_dir() {
+            tokio::fs::remove_dir_all(path).await
+        } else {
+            tokio::fs::remove_file(path).await
+        };
+        if let Err(e) = removed {
+            tracing::warn!("Failed to remove segment {} file: {}", segment_id, e);
+        }
+        0
+    }
+
     /// Delete resume data
     pub async fn delete(temp_dir: &Path) -> Result<(), DownloadError> {
         let resume_path = Self::resume_file_path(temp_dir);
@@ -116,4 +232,58 @@ impl ResumeManager {
     fn resume_file_path(temp_dir: &Path) -> PathBuf {
         temp_dir.join("resume.json")
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(id: u32, start: u64, end: u64) -> Chunk {
+        Chunk { id, start, end }
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_trusts_truncates_and_restarts_segments() {
+        let temp_dir = std::env::temp_dir().join(format!("afk-dunld-resume-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&temp_dir).await.unwrap();
+        let chunks = [chunk(0, 0, 99), chunk(1, 100, 199), chunk(2, 200, 299), chunk(3, 300, 399)];
+
+        // Partial file ahead of any recorded count, an oversized one, an
+        // unreadable one (a directory) and none at all
+        tokio::fs::write(temp_dir.join("segment_0"), vec![1u8; 60]).await.unwrap();
+        tokio::fs::write(temp_dir.join("segment_1"), vec![2u8; 150]).await.unwrap();
+        tokio::fs::create_dir_all(temp_dir.join("segment_2")).await.unwrap();
+
+        let waste = WasteCounter::default();
+        let segments = ResumeManager::reconcile_segments(&temp_dir, &chunks, &waste).await;
+        let downloaded: Vec<u64> = segments.iter().map(|s| s.downloaded_bytes).collect();
+        assert_eq!(downloaded, vec![60, 100, 0, 0]);
+        assert_eq!(segments.iter().map(|s| s.completed).collect::<Vec<_>>(), vec![false, true, false, false]);
+
+        assert_eq!(tokio::fs::metadata(temp_dir.join("segment_1")).await.unwrap().len(), 100);
+        assert!(!temp_dir.join("segment_2").exists());
+        assert!(!temp_dir.join("segment_3").exists());
+        // The 50 bytes cut from segment 1; the directory held none
+        assert_eq!(waste.total(), 50);
+        tokio::fs::remove_dir_all(&temp_dir).await.unwrap();
+    }
+
+    #[test]
+    fn test_unversioned_resume_data_uses_legacy_layout() {
+        let json = r#"{
+            "download_id": "7f1b3c1e-6a53-4d8e-9a57-3f0b6f2c8d11",
+            "url": "https://example.com/file.bin",
+            "file_name": "file.bin",
+            "save_path": "/tmp/file.bin",
+            "total_size": 100000000,
+            "segments": [],
+            "etag": null,
+            "created_at": "2024-01-01 00:00:00"
+        }"#;
+        let data: ResumeData = serde_json::from_str(json).unwrap();
+        assert_eq!(data.layout_version, LEGACY_LAYOUT_VERSION);
+
+        let chunks = data.chunks(100_000_000, 8);
+        assert_eq!(chunks, ChunkManager::split_even(100_000_000, 8));
+        assert_ne!(chunks, ChunkManager::split(100_000_000, 8));
+    }
+}
@@ -1,12 +1,17 @@
 // src-tauri/src/core/retry.rs
 
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
 use crate::utils::constants::*;
 use crate::utils::error::DownloadError;
 
 /// Retry configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct RetryConfig {
     /// Maximum number of retries
     pub max_retries: u32,
@@ -32,14 +37,90 @@ impl Default for RetryConfig {
     }
 }
 
+/// Per-download backoff settings; unset fields keep the global defaults
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RetryOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initial_delay_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_delay_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backoff_multiplier: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jitter: Option<bool>,
+}
+
+impl RetryConfig {
+    /// Apply per-download overrides on top of this config
+    pub fn with_overrides(&self, overrides: &RetryOverrides) -> Self {
+        Self {
+            max_retries: self.max_retries,
+            initial_delay_ms: overrides.initial_delay_ms.unwrap_or(self.initial_delay_ms),
+            max_delay_ms: overrides.max_delay_ms.unwrap_or(self.max_delay_ms),
+            backoff_multiplier: overrides.backoff_multiplier.unwrap_or(self.backoff_multiplier),
+            jitter: overrides.jitter.unwrap_or(self.jitter),
+        }
+    }
+
+    /// Backoff delay before the retry that follows failed attempt `attempt`
+    /// (1-based), without jitter. Large attempt counts and odd multipliers
+    /// saturate at `max_delay_ms` instead of overflowing.
+    pub fn base_delay(&self, attempt: u32) -> Duration {
+        let max_delay = self.max_delay_ms as f64;
+        let multiplier = if self.backoff_multiplier.is_finite() {
+            self.backoff_multiplier.max(1.0)
+        } else {
+            1.0
+        };
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let delay = self.initial_delay_ms as f64 * multiplier.powi(exponent);
+
+        let delay = if delay.is_finite() { delay.min(max_delay) } else { max_delay };
+        Duration::from_millis(delay as u64)
+    }
+}
+
+/// A failed attempt that is about to be retried
+#[derive(Debug, Clone)]
+pub struct RetryAttempt {
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub delay: Duration,
+    pub error: String,
+}
+
+type RetryObserver = Arc<dyn Fn(&RetryAttempt) + Send + Sync>;
+
 /// Retry handler with exponential backoff
+#[derive(Clone)]
 pub struct RetryHandler {
     config: RetryConfig,
+    cancel: Option<CancellationToken>,
+    observer: Option<RetryObserver>,
 }
 
 impl RetryHandler {
     pub fn new(config: RetryConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            cancel: None,
+            observer: None,
+        }
+    }
+
+    /// Abort the backoff sleep as soon as `token` is cancelled
+    pub fn with_cancel(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Called before each retry with the attempt and computed delay
+    pub fn on_retry<F>(mut self, observer: F) -> Self
+    where
+        F: Fn(&RetryAttempt) + Send + Sync + 'static,
+    {
+        self.observer = Some(Arc::new(observer));
+        self
     }
 
     /// Execute an async operation with retry logic
@@ -55,12 +136,12 @@ impl RetryHandler {
         let mut attempt = 0u32;
 
         loop {
-            attempt += 1;
+            attempt = attempt.saturating_add(1);
             tracing::debug!(
                 "{}: attempt {}/{}",
                 operation_name,
                 attempt,
-                self.config.max_retries + 1
+                self.config.max_retries.saturating_add(1)
             );
 
             match operation().await {
@@ -104,8 +185,24 @@ impl RetryHandler {
                         e,
                         delay.as_millis()
                     );
+                    if let Some(observer) = &self.observer {
+                        observer(&RetryAttempt {
+                            attempt,
+                            max_attempts: self.config.max_retries.saturating_add(1),
+                            delay,
+                            error: e.to_string(),
+                        });
+                    }
 
-                    sleep(delay).await;
+                    match &self.cancel {
+                        Some(cancel) => {
+                            tokio::select! {
+                                _ = cancel.cancelled() => return Err(DownloadError::Cancelled),
+                                _ = sleep(delay) => {}
+                            }
+                        }
+                        None => sleep(delay).await,
+                    }
                 }
             }
         }
@@ -113,15 +210,12 @@ impl RetryHandler {
 
     /// Calculate delay with exponential backoff and optional jitter
     fn calculate_delay(&self, attempt: u32) -> Duration {
-        let base_delay = self.config.initial_delay_ms as f64
-            * self.config.backoff_multiplier.powi(attempt as i32 - 1);
-
-        let delay_ms = base_delay.min(self.config.max_delay_ms as f64);
+        let delay_ms = self.config.base_delay(attempt).as_millis() as f64;
 
         let final_delay = if self.config.jitter {
             // Add random jitter: 50% to 150% of calculated delay
             let jitter_factor = 0.5 + rand_simple() * 1.0;
-            (delay_ms * jitter_factor) as u64
+            ((delay_ms * jitter_factor) as u64).min(self.config.max_delay_ms)
         } else {
             delay_ms as u64
         };
@@ -143,17 +237,97 @@ impl RetryHandler {
                 | DownloadError::ServerError { status: 403, .. }
                 | DownloadError::ServerError { status: 404, .. }
                 | DownloadError::ServerError { status: 410, .. }
+                | DownloadError::ServerError { status: 416, .. }
         )
     }
 }
 
 /// Simple pseudo-random number between 0.0 and 1.0
 /// (avoiding external dependency for a simple use case)
-fn rand_simple() -> f64 {
+pub(crate) fn rand_simple() -> f64 {
     use std::time::SystemTime;
     let nanos = SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
         .unwrap_or_default()
         .subsec_nanos();
     (nanos % 1000) as f64 / 1000.0
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Instant;
+
+    fn no_jitter() -> RetryConfig {
+        RetryConfig {
+            max_retries: 10,
+            initial_delay_ms: 100,
+            max_delay_ms: 1_000,
+            backoff_multiplier: 2.0,
+            jitter: false,
+        }
+    }
+
+    #[test]
+    fn test_delay_sequence_without_jitter() {
+        let handler = RetryHandler::new(no_jitter());
+        let delays: Vec<u128> = (1..=6).map(|a| handler.calculate_delay(a).as_millis()).collect();
+        assert_eq!(delays, vec![100, 200, 400, 800, 1_000, 1_000]);
+    }
+
+    #[test]
+    fn test_large_attempts_saturate() {
+        let config = no_jitter();
+        assert_eq!(config.base_delay(u32::MAX), Duration::from_millis(1_000));
+
+        let config = RetryConfig { backoff_multiplier: f64::INFINITY, ..no_jitter() };
+        assert_eq!(config.base_delay(3), Duration::from_millis(100));
+
+        let config = RetryConfig { max_delay_ms: u64::MAX, ..no_jitter() };
+        assert!(config.base_delay(u32::MAX) > Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_overrides_only_replace_set_fields() {
+        let overrides = RetryOverrides {
+            initial_delay_ms: Some(50),
+            jitter: Some(false),
+            ..Default::default()
+        };
+        let config = RetryConfig::default().with_overrides(&overrides);
+        assert_eq!(config.initial_delay_ms, 50);
+        assert!(!config.jitter);
+        assert_eq!(config.max_delay_ms, MAX_RETRY_DELAY_MS);
+        assert_eq!(config.backoff_multiplier, 2.0);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_during_backoff_returns_promptly() {
+        let config = RetryConfig { initial_delay_ms: 60_000, max_delay_ms: 60_000, ..no_jitter() };
+        let cancel = CancellationToken::new();
+        let attempts = Arc::new(AtomicU32::new(0));
+        let observed = attempts.clone();
+        let handler = RetryHandler::new(config)
+            .with_cancel(cancel.clone())
+            .on_retry(move |retry| {
+                assert_eq!(retry.delay, Duration::from_secs(60));
+                observed.fetch_add(1, Ordering::SeqCst);
+            });
+
+        let trigger = cancel.clone();
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(50)).await;
+            trigger.cancel();
+        });
+
+        let started = Instant::now();
+        let result: Result<(), _> = handler
+            .execute("test", || async { Err(DownloadError::NetworkError("reset".into())) })
+            .await;
+
+        assert!(matches!(result, Err(DownloadError::Cancelled)));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+}
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 use std::sync::Arc;
-use chrono::{DateTime, Utc, Duration};
+use chrono::{DateTime, Duration, LocalResult, NaiveTime, TimeZone, Utc};
 use tokio::sync::{RwLock, mpsc};
 use tokio::time::interval;
 use serde::{Deserialize, Serialize};
@@ -36,6 +36,34 @@ impl RepeatInterval {
     }
 }
 
+/// When "download tonight" starts unless the user picked another time
+pub const DEFAULT_NIGHT_START: &str = "01:00";
+
+/// Parse an `HH:MM` time of day
+pub fn parse_time_of_day(s: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(s.trim(), "%H:%M")
+        .map_err(|_| format!("Invalid time {:?}, expected HH:MM", s))
+}
+
+/// The next time the clock in `now`'s zone reads `at`: later today, or
+/// tomorrow once that time has passed. A time skipped by a daylight saving
+/// change runs an hour later instead.
+pub fn next_time_of_day<Tz: TimeZone>(now: &DateTime<Tz>, at: NaiveTime) -> DateTime<Tz> {
+    let zone = now.timezone();
+    let mut date = now.date_naive();
+    if now.time() >= at {
+        date = date.succ_opt().unwrap_or(date);
+    }
+    let local = date.and_time(at);
+    match zone.from_local_datetime(&local) {
+        LocalResult::Single(time) | LocalResult::Ambiguous(time, _) => time,
+        LocalResult::None => zone
+            .from_local_datetime(&(local + Duration::hours(1)))
+            .earliest()
+            .unwrap_or_else(|| now.clone() + Duration::days(1)),
+    }
+}
+
 pub struct Scheduler {
     tasks: Arc<RwLock<HashMap<String, ScheduledTask>>>,
     sender: mpsc::Sender<ScheduledTask>,
@@ -68,6 +96,17 @@ impl Scheduler {
         Ok(())
     }
 
+    /// Drop every entry that would start `download_id` and return them
+    pub async fn remove_for_download(&self, download_id: &str) -> Vec<ScheduledTask> {
+        let mut tasks = self.tasks.write().await;
+        let ids: Vec<String> = tasks
+            .values()
+            .filter(|task| task.download_id == download_id)
+            .map(|task| task.id.clone())
+            .collect();
+        ids.iter().filter_map(|id| tasks.remove(id)).collect()
+    }
+
     pub async fn update_task(&self, task: ScheduledTask) -> Result<(), AppError> {
         let mut tasks = self.tasks.write().await;
         tasks.insert(task.id.clone(), task);
@@ -232,4 +271,50 @@ mod tests {
 
         scheduler.stop().await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_remove_for_download() {
+        let (scheduler, _receiver) = Scheduler::new();
+        for (id, download_id) in [("a", "dl-1"), ("b", "dl-1"), ("c", "dl-2")] {
+            scheduler.add_task(ScheduledTask {
+                id: id.to_string(),
+                download_id: download_id.to_string(),
+                scheduled_time: Utc::now() + Duration::hours(1),
+                repeat_interval: None,
+                enabled: true,
+            }).await.unwrap();
+        }
+
+        assert_eq!(scheduler.remove_for_download("dl-1").await.len(), 2);
+        assert!(scheduler.remove_for_download("dl-1").await.is_empty());
+        assert_eq!(scheduler.get_all_tasks().await.len(), 1);
+    }
+
+    #[test]
+    fn test_next_time_of_day() {
+        let zone = chrono::FixedOffset::east_opt(2 * 3600).unwrap();
+        let at = parse_time_of_day("01:00").unwrap();
+
+        // Evening: tonight, after midnight
+        let evening = zone.with_ymd_and_hms(2024, 3, 9, 22, 30, 0).unwrap();
+        assert_eq!(next_time_of_day(&evening, at), zone.with_ymd_and_hms(2024, 3, 10, 1, 0, 0).unwrap());
+
+        // Just after midnight, before night start: later the same day
+        let early = zone.with_ymd_and_hms(2024, 3, 10, 0, 15, 0).unwrap();
+        assert_eq!(next_time_of_day(&early, at), zone.with_ymd_and_hms(2024, 3, 10, 1, 0, 0).unwrap());
+
+        // Already past night start, or exactly on it: tomorrow
+        let morning = zone.with_ymd_and_hms(2024, 3, 10, 9, 0, 0).unwrap();
+        assert_eq!(next_time_of_day(&morning, at), zone.with_ymd_and_hms(2024, 3, 11, 1, 0, 0).unwrap());
+        let on_time = zone.with_ymd_and_hms(2024, 3, 10, 1, 0, 0).unwrap();
+        assert_eq!(next_time_of_day(&on_time, at), zone.with_ymd_and_hms(2024, 3, 11, 1, 0, 0).unwrap());
+
+        // Month and year boundaries
+        let new_year = zone.with_ymd_and_hms(2024, 12, 31, 23, 0, 0).unwrap();
+        assert_eq!(next_time_of_day(&new_year, at), zone.with_ymd_and_hms(2025, 1, 1, 1, 0, 0).unwrap());
+
+        assert!(parse_time_of_day("25:00").is_err());
+        assert!(parse_time_of_day("tonight").is_err());
+        assert_eq!(parse_time_of_day(" 23:30 ").unwrap(), NaiveTime::from_hms_opt(23, 30, 0).unwrap());
+    }
 }
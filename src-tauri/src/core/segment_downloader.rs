@@ -7,7 +7,8 @@ use futures_util::StreamExt;
 
 use crate::core::chunk_manager::Chunk;
 use crate::core::speed_limiter::SpeedLimiter;
-use crate::core::retry::{RetryHandler, RetryConfig};
+use crate::core::retry::RetryHandler;
+use crate::core::waste::{WasteCounter, WasteReason};
 use crate::network::http_client::HttpClient;
 use crate::utils::error::DownloadError;
 
@@ -23,19 +24,23 @@ pub struct SegmentResult {
 pub struct SegmentDownloader {
     http_client: HttpClient,
     speed_limiter: SpeedLimiter,
-    retry_config: RetryConfig,
+    retry_handler: RetryHandler,
+    /// Shared by the download's segments
+    waste: WasteCounter,
 }
 
 impl SegmentDownloader {
     pub fn new(
         http_client: HttpClient,
         speed_limiter: SpeedLimiter,
-        retry_config: RetryConfig,
+        retry_handler: RetryHandler,
+        waste: WasteCounter,
     ) -> Self {
         Self {
             http_client,
             speed_limiter,
-            retry_config,
+            retry_handler,
+            waste,
         }
     }
 
@@ -43,36 +48,46 @@ impl SegmentDownloader {
     pub async fn download_segment(
         &self,
         url: &str,
+        referrer: Option<&str>,
         chunk: &Chunk,
         temp_path: &PathBuf,
         cancel_token: CancellationToken,
     ) -> Result<(), DownloadError> {
-        let retry_handler = RetryHandler::new(self.retry_config.clone());
         let url = url.to_string();
+        let referrer = referrer.map(str::to_string);
         let chunk = chunk.clone();
         let temp_path = temp_path.clone();
         let client = self.http_client.clone();
         let limiter = self.speed_limiter.clone();
+        let waste = self.waste.clone();
         let cancel = cancel_token.clone();
 
-        retry_handler
+        // Backoff ends early when the segment is interrupted too, not only
+        // when the whole download is
+        self.retry_handler
+            .clone()
+            .with_cancel(cancel_token)
             .execute(
                 &format!("segment_{}", chunk.id),
                 || {
                     let url = url.clone();
+                    let referrer = referrer.clone();
                     let chunk = chunk.clone();
                     let temp_path = temp_path.clone();
                     let client = client.clone();
                     let limiter = limiter.clone();
+                    let waste = waste.clone();
                     let cancel = cancel.clone();
 
                     async move {
                         Self::download_segment_inner(
                             &client,
                             &url,
+                            referrer.as_deref(),
                             &chunk,
                             &temp_path,
                             &limiter,
+                            &waste,
                             cancel,
                         )
                         .await
@@ -86,9 +101,11 @@ impl SegmentDownloader {
     async fn download_segment_inner(
         client: &HttpClient,
         url: &str,
+        referrer: Option<&str>,
         chunk: &Chunk,
         temp_path: &PathBuf,
         speed_limiter: &SpeedLimiter,
+        waste: &WasteCounter,
         cancel_token: CancellationToken,
     ) -> Result<(), DownloadError> {
         // Check for existing partial download (resume)
@@ -121,15 +138,33 @@ impl SegmentDownloader {
             existing_bytes
         );
 
-        // Request the range
-        let response = client
-            .get_range(url, actual_start, chunk.end)
-            .await?;
+        // Request the range; a connect on a dead route can take long to fail
+        let response = tokio::select! {
+            _ = cancel_token.cancelled() => return Err(DownloadError::Cancelled),
+            response = client.get_range(url, referrer, actual_start, chunk.end) => response?,
+        };
+
+        // A 200 is the whole file: the server ignored the range. The
+        // segment is written again from the stream, reading past the bytes
+        // before the chunk, and what the file held is lost.
+        let range_ignored = response.status() == reqwest::StatusCode::OK;
+        let (mut skip, mut total_written) = if range_ignored {
+            tracing::warn!("Server ignored the range for segment {}, rewriting it", chunk.id);
+            waste.add(existing_bytes, WasteReason::RangeIgnored);
+            (chunk.start, 0)
+        } else {
+            (0, existing_bytes)
+        };
 
-        // Open file for appending
-        let mut file = tokio::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
+        // Open file for appending, or from the start after a 200
+        let mut options = tokio::fs::OpenOptions::new();
+        options.create(true);
+        if range_ignored {
+            options.write(true).truncate(true);
+        } else {
+            options.append(true);
+        }
+        let mut file = options
             .open(temp_path)
             .await
             .map_err(|e| DownloadError::FileError(
@@ -137,7 +172,6 @@ impl SegmentDownloader {
             ))?;
 
         let mut stream = response.bytes_stream();
-        let mut total_written = existing_bytes;
 
         loop {
             tokio::select! {
@@ -160,14 +194,27 @@ impl SegmentDownloader {
                             // Apply speed limiting
                             speed_limiter.throttle(data.len()).await;
 
+                            let skipped = skip.min(data.len() as u64);
+                            skip -= skipped;
+                            let data = &data[skipped as usize..];
+
+                            // Only a server ignoring the range sends more
+                            // than the chunk
+                            let room = chunk.size().saturating_sub(total_written);
+                            let keep = data.len().min(room as usize);
+                            waste.add(skipped + (data.len() - keep) as u64, WasteReason::RangeIgnored);
+
                             // Write to file
-                            file.write_all(&data)
+                            file.write_all(&data[..keep])
                                 .await
                                 .map_err(|e| DownloadError::FileError(
                                     format!("Write error: {}", e)
                                 ))?;
 
-                            total_written += data.len() as u64;
+                            total_written += keep as u64;
+                            if range_ignored && total_written == chunk.size() {
+                                break;
+                            }
                         }
                         Some(Err(e)) => {
                             // Flush what we have so far (for resume)
@@ -0,0 +1,230 @@
+// src-tauri/src/core/segment_gate.rs
+
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+
+/// Bounds how many segments of one download transfer at once. The chunk
+/// layout never changes; lowering the limit stops the newest segments at
+/// their next network chunk and they resume from their partial file once a
+/// slot frees up.
+pub struct SegmentGate {
+    state: parking_lot::Mutex<GateState>,
+    changed: Notify,
+}
+
+struct GateState {
+    limit: usize,
+    running: Vec<(u32, CancellationToken)>,
+}
+
+/// A running segment's hold on the gate, released on drop
+pub struct SegmentSlot {
+    gate: Arc<SegmentGate>,
+    segment_id: u32,
+    token: CancellationToken,
+    parent: CancellationToken,
+}
+
+impl SegmentSlot {
+    /// Cancelled when the download is, or when the gate preempts this segment
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// The segment was stopped to lower parallelism, not by the download
+    pub fn was_preempted(&self) -> bool {
+        self.token.is_cancelled() && !self.parent.is_cancelled()
+    }
+}
+
+impl Drop for SegmentSlot {
+    fn drop(&mut self) {
+        self.gate.state.lock().running.retain(|(id, _)| *id != self.segment_id);
+        self.gate.changed.notify_waiters();
+    }
+}
+
+impl SegmentGate {
+    pub fn new(limit: usize) -> Arc<Self> {
+        Arc::new(Self {
+            state: parking_lot::Mutex::new(GateState { limit: limit.max(1), running: Vec::new() }),
+            changed: Notify::new(),
+        })
+    }
+
+    pub fn limit(&self) -> usize {
+        self.state.lock().limit
+    }
+
+    /// Wait for a free slot. None once `parent` is cancelled.
+    pub async fn acquire(self: &Arc<Self>, segment_id: u32, parent: &CancellationToken) -> Option<SegmentSlot> {
+        loop {
+            let changed = self.changed.notified();
+            {
+                let mut state = self.state.lock();
+                if state.running.len() < state.limit {
+                    let token = parent.child_token();
+                    state.running.push((segment_id, token.clone()));
+                    return Some(SegmentSlot {
+                        gate: self.clone(),
+                        segment_id,
+                        token,
+                        parent: parent.clone(),
+                    });
+                }
+            }
+            tokio::select! {
+                _ = changed => {}
+                _ = parent.cancelled() => return None,
+            }
+        }
+    }
+
+    /// Change how many segments may run, preempting the most recently
+    /// started ones when lowering below what is running
+    pub fn set_limit(&self, limit: usize) {
+        let mut state = self.state.lock();
+        state.limit = limit.max(1);
+
+        let live: Vec<&CancellationToken> = state.running
+            .iter()
+            .map(|(_, token)| token)
+            .filter(|token| !token.is_cancelled())
+            .collect();
+        for token in live.iter().skip(state.limit) {
+            token.cancel();
+        }
+        drop(state);
+        self.changed.notify_waiters();
+    }
+
+    /// Stop every running segment without lowering the limit. Each one
+    /// sees itself preempted, takes a slot again straight away and resumes
+    /// from its partial file over a new connection. Returns how many were
+    /// stopped.
+    pub fn interrupt_running(&self) -> usize {
+        let state = self.state.lock();
+        let live: Vec<&CancellationToken> = state.running
+            .iter()
+            .map(|(_, token)| token)
+            .filter(|token| !token.is_cancelled())
+            .collect();
+        for token in &live {
+            token.cancel();
+        }
+        live.len()
+    }
+}
+
+/// Gives new downloads fewer segments while many are running, keeping the
+/// total number of connections near `max_total_connections`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentionPolicy {
+    pub enabled: bool,
+    /// Reduction starts once this many downloads are already active
+    pub active_threshold: usize,
+    pub max_total_connections: usize,
+}
+
+impl Default for ContentionPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            active_threshold: 3,
+            max_total_connections: 16,
+        }
+    }
+}
+
+impl ContentionPolicy {
+    /// Segments a new download gets, given how many downloads are active
+    /// and how many connections they hold
+    pub fn segments_for(&self, requested: u8, active_downloads: usize, connections_in_use: usize) -> u8 {
+        if !self.enabled || active_downloads < self.active_threshold {
+            return requested;
+        }
+        let available = self.max_total_connections.saturating_sub(connections_in_use);
+        requested.min(available.clamp(1, u8::MAX as usize) as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_gate_bounds_running_segments() {
+        let gate = SegmentGate::new(2);
+        let cancel = CancellationToken::new();
+
+        let first = gate.acquire(0, &cancel).await.unwrap();
+        let _second = gate.acquire(1, &cancel).await.unwrap();
+        let waiting = {
+            let gate = gate.clone();
+            let cancel = cancel.clone();
+            tokio::spawn(async move { gate.acquire(2, &cancel).await.map(|slot| slot.segment_id) })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+
+        drop(first);
+        assert_eq!(waiting.await.unwrap(), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_lowering_limit_preempts_newest_segments() {
+        let gate = SegmentGate::new(3);
+        let cancel = CancellationToken::new();
+        let slots: Vec<SegmentSlot> = futures_util::future::join_all(
+            (0..3).map(|id| {
+                let gate = gate.clone();
+                let cancel = cancel.clone();
+                async move { gate.acquire(id, &cancel).await.unwrap() }
+            }),
+        )
+        .await;
+
+        gate.set_limit(1);
+        assert!(!slots[0].was_preempted());
+        assert!(slots[1].was_preempted() && slots[2].was_preempted());
+
+        // Download-level cancellation is not preemption
+        cancel.cancel();
+        assert!(!slots[0].was_preempted());
+        assert!(gate.acquire(3, &cancel).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_interrupt_keeps_the_limit() {
+        let gate = SegmentGate::new(2);
+        let cancel = CancellationToken::new();
+        let first = gate.acquire(0, &cancel).await.unwrap();
+        let second = gate.acquire(1, &cancel).await.unwrap();
+
+        assert_eq!(gate.interrupt_running(), 2);
+        assert!(first.was_preempted() && second.was_preempted());
+        assert_eq!(gate.limit(), 2);
+
+        // Both come straight back once they let go of their old slots
+        drop((first, second));
+        let first = gate.acquire(0, &cancel).await.unwrap();
+        let _second = gate.acquire(1, &cancel).await.unwrap();
+        assert!(!first.was_preempted());
+    }
+
+    #[test]
+    fn test_contention_policy() {
+        let policy = ContentionPolicy { enabled: true, active_threshold: 2, max_total_connections: 12 };
+
+        assert_eq!(policy.segments_for(8, 1, 8), 8);
+        assert_eq!(policy.segments_for(8, 2, 8), 4);
+        assert_eq!(policy.segments_for(2, 2, 8), 2);
+        // Budget exhausted: still one connection
+        assert_eq!(policy.segments_for(8, 5, 20), 1);
+        assert_eq!(ContentionPolicy::default().segments_for(8, 10, 100), 8);
+    }
+}
@@ -0,0 +1,140 @@
+// src-tauri/src/core/size_guard.rs
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::error::DownloadError;
+use crate::utils::format_utils;
+
+/// Where an add request came from. Automated sources can queue a file
+/// nobody looked at, so they are held to the confirmation threshold.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum DownloadSource {
+    #[default]
+    Manual,
+    Clipboard,
+    BrowserExtension,
+}
+
+impl DownloadSource {
+    pub fn is_automated(self) -> bool {
+        !matches!(self, DownloadSource::Manual)
+    }
+}
+
+/// Size thresholds checked when a download is added. A limit of 0 is off.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeLimits {
+    /// Automated downloads larger than this wait for confirmation
+    pub max_auto_download_size: u64,
+    /// Downloads larger than this are refused, whatever their source
+    pub hard_max_download_size: u64,
+    /// Automated downloads of unknown size also wait for confirmation
+    pub confirm_unknown_size: bool,
+}
+
+/// Why an automated download is waiting for the user
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "reason", rename_all = "camelCase")]
+pub enum ConfirmationReason {
+    #[serde(rename_all = "camelCase")]
+    OverThreshold { size: u64, limit: u64 },
+    UnknownSize,
+}
+
+impl std::fmt::Display for ConfirmationReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfirmationReason::OverThreshold { size, limit } => write!(
+                f,
+                "{} is larger than the {} limit for automatic downloads",
+                format_utils::format_bytes(*size),
+                format_utils::format_bytes(*limit)
+            ),
+            ConfirmationReason::UnknownSize => write!(f, "the server didn't report a file size"),
+        }
+    }
+}
+
+impl SizeLimits {
+    /// Refuse downloads over the hard limit. Unknown sizes pass.
+    pub fn enforce_hard_limit(&self, total_size: Option<u64>) -> Result<(), DownloadError> {
+        match total_size {
+            Some(size) if self.hard_max_download_size > 0 && size > self.hard_max_download_size => {
+                Err(DownloadError::FileTooLarge { size, limit: self.hard_max_download_size })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether a download must be confirmed before it is queued
+    pub fn confirmation_reason(
+        &self,
+        total_size: Option<u64>,
+        source: DownloadSource,
+    ) -> Option<ConfirmationReason> {
+        if !source.is_automated() {
+            return None;
+        }
+        match total_size {
+            Some(size) if self.max_auto_download_size > 0 && size > self.max_auto_download_size => {
+                Some(ConfirmationReason::OverThreshold { size, limit: self.max_auto_download_size })
+            }
+            Some(_) => None,
+            None => self.confirm_unknown_size.then_some(ConfirmationReason::UnknownSize),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GB: u64 = 1024 * 1024 * 1024;
+
+    fn limits() -> SizeLimits {
+        SizeLimits {
+            max_auto_download_size: 4 * GB,
+            hard_max_download_size: 100 * GB,
+            confirm_unknown_size: false,
+        }
+    }
+
+    #[test]
+    fn test_hard_limit_applies_to_every_source() {
+        let limits = limits();
+        assert!(limits.enforce_hard_limit(Some(50 * GB)).is_ok());
+        assert!(limits.enforce_hard_limit(None).is_ok());
+        assert!(matches!(
+            limits.enforce_hard_limit(Some(200 * GB)),
+            Err(DownloadError::FileTooLarge { size, limit }) if size == 200 * GB && limit == 100 * GB
+        ));
+        assert!(SizeLimits::default().enforce_hard_limit(Some(u64::MAX)).is_ok());
+    }
+
+    #[test]
+    fn test_only_automated_sources_need_confirmation() {
+        let limits = limits();
+        assert_eq!(limits.confirmation_reason(Some(200 * GB), DownloadSource::Manual), None);
+        assert_eq!(limits.confirmation_reason(Some(GB), DownloadSource::BrowserExtension), None);
+        assert_eq!(
+            limits.confirmation_reason(Some(5 * GB), DownloadSource::Clipboard),
+            Some(ConfirmationReason::OverThreshold { size: 5 * GB, limit: 4 * GB })
+        );
+    }
+
+    #[test]
+    fn test_unknown_size_confirmation_is_optional() {
+        let mut limits = limits();
+        assert_eq!(limits.confirmation_reason(None, DownloadSource::BrowserExtension), None);
+
+        limits.confirm_unknown_size = true;
+        assert_eq!(
+            limits.confirmation_reason(None, DownloadSource::BrowserExtension),
+            Some(ConfirmationReason::UnknownSize)
+        );
+        assert_eq!(limits.confirmation_reason(None, DownloadSource::Manual), None);
+    }
+}
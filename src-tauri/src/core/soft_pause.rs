@@ -0,0 +1,127 @@
+// src-tauri/src/core/soft_pause.rs
+// A soft pause stops a running download's segments reading from their
+// response bodies without closing the connections, so a resume a few
+// seconds later carries on without new TLS handshakes and range requests.
+// The download's bandwidth is held at zero through the speed limiter. A
+// soft pause that lasts past its limit becomes a real pause, so servers
+// aren't left holding idle connections for long.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::utils::constants::{DEFAULT_SOFT_PAUSE_SECS, MAX_SOFT_PAUSE_SECS};
+
+/// One soft pause of a download
+#[derive(Debug, Clone)]
+pub struct SoftPause {
+    /// Tells a later soft pause of the same download from this one
+    pub generation: u64,
+    /// Cancelled when the download is resumed
+    pub resumed: CancellationToken,
+}
+
+pub struct SoftPauses {
+    enabled: AtomicBool,
+    limit_secs: AtomicU64,
+    next_generation: AtomicU64,
+    paused: parking_lot::Mutex<HashMap<Uuid, SoftPause>>,
+}
+
+impl Default for SoftPauses {
+    fn default() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            limit_secs: AtomicU64::new(DEFAULT_SOFT_PAUSE_SECS),
+            next_generation: AtomicU64::new(0),
+            paused: parking_lot::Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl SoftPauses {
+    pub fn configure(&self, enabled: bool, limit: Duration) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        self.limit_secs.store(limit.as_secs().clamp(1, MAX_SOFT_PAUSE_SECS), Ordering::Relaxed);
+    }
+
+    /// How long a soft pause may last; None when soft pausing is off
+    pub fn limit(&self) -> Option<Duration> {
+        self.enabled
+            .load(Ordering::Relaxed)
+            .then(|| Duration::from_secs(self.limit_secs.load(Ordering::Relaxed)))
+    }
+
+    /// Start soft pausing `id`. None when it already is.
+    pub fn begin(&self, id: Uuid) -> Option<SoftPause> {
+        let mut paused = self.paused.lock();
+        if paused.contains_key(&id) {
+            return None;
+        }
+        let pause = SoftPause {
+            generation: self.next_generation.fetch_add(1, Ordering::Relaxed),
+            resumed: CancellationToken::new(),
+        };
+        paused.insert(id, pause.clone());
+        Some(pause)
+    }
+
+    /// Resume `id`. False when it wasn't soft paused, e.g. because the
+    /// pause already became a real one.
+    pub fn resume(&self, id: Uuid) -> bool {
+        match self.paused.lock().remove(&id) {
+            Some(pause) => {
+                pause.resumed.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// End the soft pause `generation` of `id` without resuming it. False
+    /// when a resume got there first or a newer soft pause replaced it.
+    pub fn finish(&self, id: Uuid, generation: u64) -> bool {
+        let mut paused = self.paused.lock();
+        match paused.get(&id) {
+            Some(pause) if pause.generation == generation => {
+                paused.remove(&id);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_paused(&self, id: Uuid) -> bool {
+        self.paused.lock().contains_key(&id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume_and_timeout_cannot_both_win() {
+        let pauses = SoftPauses::default();
+        assert_eq!(pauses.limit(), None);
+        pauses.configure(true, Duration::from_secs(3600));
+        assert_eq!(pauses.limit(), Some(Duration::from_secs(MAX_SOFT_PAUSE_SECS)));
+
+        let id = Uuid::new_v4();
+        let first = pauses.begin(id).unwrap();
+        assert!(pauses.begin(id).is_none());
+        assert!(pauses.resume(id));
+        assert!(first.resumed.is_cancelled());
+        // The timeout of the resumed pause lost
+        assert!(!pauses.finish(id, first.generation));
+
+        // Nor may it end a newer soft pause
+        let second = pauses.begin(id).unwrap();
+        assert!(!pauses.finish(id, first.generation));
+        assert!(pauses.is_paused(id));
+        assert!(pauses.finish(id, second.generation));
+        assert!(!pauses.resume(id));
+    }
+}
@@ -1,66 +1,182 @@
 // src-tauri/src/core/speed_limiter.rs
 
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
 use tokio::time::{sleep, Duration, Instant};
+use uuid::Uuid;
 
-/// Rate limiter using token bucket algorithm
+use crate::core::download_task::DownloadPriority;
+
+/// Bandwidth share of each priority under a contended limit, relative to
+/// each other: with the defaults Critical gets 3x the share of Low
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PriorityWeights {
+    pub low: f64,
+    pub normal: f64,
+    pub high: f64,
+    pub critical: f64,
+}
+
+impl Default for PriorityWeights {
+    fn default() -> Self {
+        Self {
+            low: 1.0,
+            normal: 1.5,
+            high: 2.0,
+            critical: 3.0,
+        }
+    }
+}
+
+impl PriorityWeights {
+    pub fn weight(&self, priority: DownloadPriority) -> f64 {
+        let weight = match priority {
+            DownloadPriority::Low => self.low,
+            DownloadPriority::Normal => self.normal,
+            DownloadPriority::High => self.high,
+            DownloadPriority::Critical => self.critical,
+        };
+        // A zero or negative weight would starve the download outright
+        if weight.is_finite() { weight.max(0.01) } else { 1.0 }
+    }
+}
+
+/// A download drawing from the shared budget
+#[derive(Debug, Clone, Copy)]
+struct Flow {
+    id: Uuid,
+    priority: DownloadPriority,
+}
+
+/// Usage of the current window, per download
+struct Window {
+    start: Instant,
+    consumed: HashMap<Uuid, u64>,
+    /// Priority and last throttle time of recently active downloads
+    active: HashMap<Uuid, (DownloadPriority, Instant)>,
+}
+
+/// Rate limiter using token bucket algorithm.
+///
+/// Downloads throttled through [`SpeedLimiter::for_download`] split each
+/// window's budget in proportion to their priority weights. A download
+/// that goes quiet for a couple of windows stops counting, so the others
+/// take over its share.
 pub struct SpeedLimiter {
     /// Max bytes per second (None = unlimited)
     limit: Arc<RwLock<Option<u64>>>,
-    /// Bytes consumed in current window
-    bytes_in_window: Arc<RwLock<u64>>,
-    /// Window start time
-    window_start: Arc<RwLock<Instant>>,
+    /// Upper bound on the rate imposed on top of `limit` (0 = none), e.g.
+    /// by a data cap
+    ceiling: Arc<AtomicU64>,
+    /// Bytes passed through since the last `take_transferred`
+    transferred: Arc<AtomicU64>,
+    weights: Arc<parking_lot::RwLock<PriorityWeights>>,
+    window: Arc<parking_lot::Mutex<Window>>,
     /// Window duration
     window_duration: Duration,
+    /// Download this handle throttles for; unbound handles share one flow
+    flow: Option<Flow>,
+    /// Downloads held at zero bandwidth, e.g. while soft paused
+    held: Arc<parking_lot::Mutex<HashSet<Uuid>>>,
+    released: Arc<Notify>,
 }
 
 impl SpeedLimiter {
     pub fn new(limit: Option<u64>) -> Self {
         Self {
             limit: Arc::new(RwLock::new(limit)),
-            bytes_in_window: Arc::new(RwLock::new(0)),
-            window_start: Arc::new(RwLock::new(Instant::now())),
+            ceiling: Arc::new(AtomicU64::new(0)),
+            transferred: Arc::new(AtomicU64::new(0)),
+            weights: Arc::new(parking_lot::RwLock::new(PriorityWeights::default())),
+            window: Arc::new(parking_lot::Mutex::new(Window {
+                start: Instant::now(),
+                consumed: HashMap::new(),
+                active: HashMap::new(),
+            })),
             window_duration: Duration::from_millis(100), // 100ms windows
+            flow: None,
+            held: Arc::new(parking_lot::Mutex::new(HashSet::new())),
+            released: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Handle sharing this limiter's budget on behalf of one download
+    pub fn for_download(&self, id: Uuid, priority: DownloadPriority) -> Self {
+        Self {
+            flow: Some(Flow { id, priority }),
+            ..self.clone()
         }
     }
 
     /// Throttle based on how many bytes are being written
     /// This should be called AFTER writing the bytes
     pub async fn throttle(&self, bytes: usize) {
-        let limit = *self.limit.read().await;
+        self.transferred.fetch_add(bytes as u64, Ordering::Relaxed);
+
+        if let Some(flow) = self.flow {
+            // A held download reads nothing more until it is released
+            loop {
+                let released = self.released.notified();
+                if !self.is_held(flow.id) {
+                    break;
+                }
+                released.await;
+            }
+        }
+
+        let limit = self.effective_limit().await;
         let limit = match limit {
             Some(l) if l > 0 => l,
             _ => return, // No limit
         };
 
         // Bytes allowed per window
-        let bytes_per_window = (limit as f64
-            * self.window_duration.as_secs_f64()) as u64;
+        let bytes_per_window = limit as f64 * self.window_duration.as_secs_f64();
+        let flow = self.flow.unwrap_or(Flow {
+            id: Uuid::nil(),
+            priority: DownloadPriority::Normal,
+        });
+        let weights = *self.weights.read();
 
-        let mut consumed = self.bytes_in_window.write().await;
-        let mut start = self.window_start.write().await;
+        let remaining = {
+            let mut window = self.window.lock();
+            let now = Instant::now();
 
-        // Check if we're in a new window
-        if start.elapsed() >= self.window_duration {
-            *consumed = 0;
-            *start = Instant::now();
-        }
+            // Check if we're in a new window
+            if now.duration_since(window.start) >= self.window_duration {
+                window.consumed.clear();
+                window.start = now;
+                let idle_after = self.window_duration * 2;
+                window.active.retain(|_, (_, seen)| now.duration_since(*seen) < idle_after);
+            }
 
-        *consumed += bytes as u64;
+            window.active.insert(flow.id, (flow.priority, now));
+            let consumed = window.consumed.entry(flow.id).or_insert(0);
+            *consumed += bytes as u64;
+            let consumed = *consumed;
 
-        // If we've exceeded the budget, sleep until the window ends
-        if *consumed >= bytes_per_window {
-            let remaining = self.window_duration
-                .checked_sub(start.elapsed())
-                .unwrap_or(Duration::ZERO);
-
-            if remaining > Duration::ZERO {
-                drop(consumed);
-                drop(start);
-                sleep(remaining).await;
+            // This download's slice of the window budget
+            let total_weight: f64 = window.active
+                .values()
+                .map(|(priority, _)| weights.weight(*priority))
+                .sum();
+            let share = bytes_per_window * weights.weight(flow.priority) / total_weight;
+
+            if consumed as f64 >= share {
+                self.window_duration.saturating_sub(now.duration_since(window.start))
+            } else {
+                Duration::ZERO
             }
+        };
+
+        // If we've exceeded the budget, sleep until the window ends
+        if remaining > Duration::ZERO {
+            sleep(remaining).await;
         }
     }
 
@@ -68,8 +184,11 @@ impl SpeedLimiter {
     #[allow(dead_code)]
     pub async fn set_limit(&self, limit: Option<u64>) {
         *self.limit.write().await = limit;
-        *self.bytes_in_window.write().await = 0;
-        *self.window_start.write().await = Instant::now();
+        {
+            let mut window = self.window.lock();
+            window.consumed.clear();
+            window.start = Instant::now();
+        }
 
         tracing::info!(
             "Speed limit set to: {}",
@@ -83,6 +202,60 @@ impl SpeedLimiter {
     pub async fn get_limit(&self) -> Option<u64> {
         *self.limit.read().await
     }
+
+    /// Cap the rate below whatever limit the user set, without touching it
+    pub fn set_ceiling(&self, ceiling: Option<u64>) {
+        let previous = self.ceiling.swap(ceiling.unwrap_or(0), Ordering::Relaxed);
+        if previous != ceiling.unwrap_or(0) {
+            tracing::info!(
+                "Speed ceiling set to: {}",
+                ceiling
+                    .map(|c| format_speed(c as f64))
+                    .unwrap_or_else(|| "None".to_string())
+            );
+        }
+    }
+
+    pub fn ceiling(&self) -> Option<u64> {
+        Some(self.ceiling.load(Ordering::Relaxed)).filter(|c| *c > 0)
+    }
+
+    /// The user's limit, lowered to the ceiling if one is set
+    pub async fn effective_limit(&self) -> Option<u64> {
+        let limit = (*self.limit.read().await).filter(|l| *l > 0);
+        match (limit, self.ceiling()) {
+            (Some(limit), Some(ceiling)) => Some(limit.min(ceiling)),
+            (limit, ceiling) => limit.or(ceiling),
+        }
+    }
+
+    /// Bytes throttled through any handle since the last call
+    pub fn take_transferred(&self) -> u64 {
+        self.transferred.swap(0, Ordering::Relaxed)
+    }
+
+    pub fn set_weights(&self, weights: PriorityWeights) {
+        *self.weights.write() = weights;
+    }
+
+    pub fn weights(&self) -> PriorityWeights {
+        *self.weights.read()
+    }
+
+    /// Stop `id`'s handles passing any more bytes until it is released
+    pub fn hold(&self, id: Uuid) {
+        self.held.lock().insert(id);
+    }
+
+    pub fn release(&self, id: Uuid) {
+        if self.held.lock().remove(&id) {
+            self.released.notify_waiters();
+        }
+    }
+
+    pub fn is_held(&self, id: Uuid) -> bool {
+        self.held.lock().contains(&id)
+    }
 }
 
 /// Clone the speed limiter
@@ -90,9 +263,14 @@ impl Clone for SpeedLimiter {
     fn clone(&self) -> Self {
         Self {
             limit: Arc::clone(&self.limit),
-            bytes_in_window: Arc::clone(&self.bytes_in_window),
-            window_start: Arc::clone(&self.window_start),
+            ceiling: Arc::clone(&self.ceiling),
+            transferred: Arc::clone(&self.transferred),
+            weights: Arc::clone(&self.weights),
+            window: Arc::clone(&self.window),
             window_duration: self.window_duration,
+            flow: self.flow,
+            held: Arc::clone(&self.held),
+            released: Arc::clone(&self.released),
         }
     }
 }
@@ -107,3 +285,105 @@ fn format_speed(bytes_per_sec: f64) -> String {
     let value = bytes_per_sec / 1024f64.powi(exp as i32);
     format!("{:.2} {}", value, UNITS[exp])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Throttle `limiter` in 1 KB steps until `deadline`, counting bytes
+    async fn simulate(limiter: SpeedLimiter, deadline: Instant, counted: Arc<AtomicU64>) {
+        while Instant::now() < deadline {
+            limiter.throttle(1024).await;
+            counted.fetch_add(1024, Ordering::Relaxed);
+            tokio::task::yield_now().await;
+        }
+    }
+
+    async fn measure_ratio(weights: PriorityWeights, fast: DownloadPriority, slow: DownloadPriority) -> f64 {
+        let limiter = SpeedLimiter::new(Some(2 * 1024 * 1024));
+        limiter.set_weights(weights);
+        let deadline = Instant::now() + Duration::from_millis(1500);
+
+        let fast_bytes = Arc::new(AtomicU64::new(0));
+        let slow_bytes = Arc::new(AtomicU64::new(0));
+        tokio::join!(
+            simulate(limiter.for_download(Uuid::new_v4(), fast), deadline, fast_bytes.clone()),
+            simulate(limiter.for_download(Uuid::new_v4(), slow), deadline, slow_bytes.clone()),
+        );
+
+        fast_bytes.load(Ordering::Relaxed) as f64 / slow_bytes.load(Ordering::Relaxed) as f64
+    }
+
+    #[tokio::test]
+    async fn test_contended_share_follows_default_weights() {
+        let ratio = measure_ratio(
+            PriorityWeights::default(),
+            DownloadPriority::Critical,
+            DownloadPriority::Low,
+        ).await;
+        assert!((2.5..3.5).contains(&ratio), "ratio was {}", ratio);
+    }
+
+    #[tokio::test]
+    async fn test_contended_share_follows_configured_weights() {
+        let weights = PriorityWeights {
+            normal: 1.0,
+            high: 2.0,
+            ..PriorityWeights::default()
+        };
+        let ratio = measure_ratio(weights, DownloadPriority::High, DownloadPriority::Normal).await;
+        assert!((1.6..2.4).contains(&ratio), "ratio was {}", ratio);
+    }
+
+    #[tokio::test]
+    async fn test_single_download_gets_whole_limit() {
+        let limiter = SpeedLimiter::new(Some(1024 * 1024));
+        let deadline = Instant::now() + Duration::from_millis(1000);
+        let bytes = Arc::new(AtomicU64::new(0));
+        simulate(limiter.for_download(Uuid::new_v4(), DownloadPriority::Low), deadline, bytes.clone()).await;
+
+        // About one second's worth, give or take a window
+        let total = bytes.load(Ordering::Relaxed);
+        assert!((900 * 1024..1200 * 1024).contains(&total), "got {} bytes", total);
+    }
+
+    #[tokio::test]
+    async fn test_ceiling_lowers_limit_and_bytes_are_counted() {
+        let limiter = SpeedLimiter::new(None);
+        limiter.set_ceiling(Some(512 * 1024));
+        let deadline = Instant::now() + Duration::from_millis(1000);
+        let bytes = Arc::new(AtomicU64::new(0));
+        simulate(limiter.clone(), deadline, bytes.clone()).await;
+
+        let total = bytes.load(Ordering::Relaxed);
+        assert!((400 * 1024..650 * 1024).contains(&total), "got {} bytes", total);
+        assert_eq!(limiter.take_transferred(), total);
+        assert_eq!(limiter.take_transferred(), 0);
+
+        // The lower of the user's limit and the ceiling wins
+        limiter.set_limit(Some(256 * 1024)).await;
+        assert_eq!(limiter.effective_limit().await, Some(256 * 1024));
+        limiter.set_ceiling(None);
+        limiter.set_limit(None).await;
+        assert_eq!(limiter.effective_limit().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_held_download_waits_until_released() {
+        let limiter = SpeedLimiter::new(None);
+        let id = Uuid::new_v4();
+        let held = limiter.for_download(id, DownloadPriority::Normal);
+        let other = limiter.for_download(Uuid::new_v4(), DownloadPriority::Normal);
+        limiter.hold(id);
+
+        assert!(tokio::time::timeout(Duration::from_millis(50), held.throttle(1024)).await.is_err());
+        // Other downloads carry on
+        assert!(tokio::time::timeout(Duration::from_millis(50), other.throttle(1024)).await.is_ok());
+
+        let waiting = tokio::spawn(async move { held.throttle(1024).await });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        limiter.release(id);
+        assert!(tokio::time::timeout(Duration::from_millis(200), waiting).await.is_ok());
+        assert!(!limiter.is_held(id));
+    }
+}
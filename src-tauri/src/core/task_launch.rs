@@ -0,0 +1,76 @@
+// src-tauri/src/core/task_launch.rs
+
+use std::future::Future;
+
+use crate::core::download_task::{DownloadStatus, DownloadTask};
+use crate::database::db::Database;
+use crate::utils::error::DownloadError;
+
+/// The persistence a newly added download needs before it may start
+pub trait TaskStore: Send + Sync {
+    /// Insert the row, renaming the task if another download already
+    /// claimed its save path
+    fn reserve_and_insert(
+        &self,
+        task: &mut DownloadTask,
+    ) -> impl Future<Output = Result<(), DownloadError>> + Send;
+
+    fn update_download(
+        &self,
+        task: &DownloadTask,
+    ) -> impl Future<Output = Result<(), DownloadError>> + Send;
+}
+
+impl TaskStore for Database {
+    fn reserve_and_insert(
+        &self,
+        task: &mut DownloadTask,
+    ) -> impl Future<Output = Result<(), DownloadError>> + Send {
+        Database::reserve_and_insert(self, task)
+    }
+
+    fn update_download(
+        &self,
+        task: &DownloadTask,
+    ) -> impl Future<Output = Result<(), DownloadError>> + Send {
+        Database::update_download(self, task)
+    }
+}
+
+/// Write the task ahead of starting it.
+///
+/// The row is inserted as Queued before anything runs, so a failed insert
+/// never leaves a download writing files without a row. Once it is marked
+/// Downloading, `start` is called; if either step fails the row is marked
+/// Failed with the error instead of being left behind for a download that
+/// never started.
+pub async fn launch<S, F, Fut>(
+    store: &S,
+    task: &mut DownloadTask,
+    start: F,
+) -> Result<(), DownloadError>
+where
+    S: TaskStore,
+    F: FnOnce(DownloadTask) -> Fut,
+    Fut: Future<Output = Result<(), String>>,
+{
+    task.status = DownloadStatus::Queued;
+    store.reserve_and_insert(task).await?;
+
+    task.status = DownloadStatus::Downloading;
+    let started = match store.update_download(task).await {
+        Ok(()) => start(task.clone()).await.map_err(DownloadError::Unknown),
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = started {
+        task.status = DownloadStatus::Failed;
+        task.error_message = Some(e.to_string());
+        if let Err(mark) = store.update_download(task).await {
+            tracing::error!("Failed to mark {} as failed: {}", task.id, mark);
+        }
+        return Err(e);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,300 @@
+// src-tauri/src/core/transfer.rs
+
+use chrono::TimeZone;
+use serde::{Deserialize, Serialize};
+
+use crate::core::download_task::DownloadTask;
+use crate::network::torrent_client_librqbit::TorrentHandle;
+use crate::network::torrent_helpers::TorrentMetadata;
+
+/// Kind of transfer in the combined view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferKind {
+    Download,
+    Torrent,
+}
+
+/// Fields shared by downloads and torrents in the "All transfers" view
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferFields {
+    /// Download UUID or torrent info hash
+    pub id: String,
+    pub name: String,
+    /// Percent complete (0-100)
+    pub progress: f64,
+    /// Bytes per second
+    pub speed: f64,
+    /// Lowercase status, e.g. "downloading", "paused", "seeding"
+    pub status: String,
+    pub category: Option<String>,
+    pub size: Option<u64>,
+    /// Unix timestamp (seconds) when the transfer was added
+    pub added_at: i64,
+}
+
+/// A download or torrent summary, tagged with `kind`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum TransferSummary {
+    Download(TransferFields),
+    Torrent(TransferFields),
+}
+
+impl TransferSummary {
+    pub fn from_download(task: &DownloadTask) -> Self {
+        let progress = task.percent().min(100.0);
+        let added_at = chrono::Local
+            .from_local_datetime(&task.created_at)
+            .earliest()
+            .map(|dt| dt.timestamp())
+            .unwrap_or_else(|| task.created_at.and_utc().timestamp());
+
+        TransferSummary::Download(TransferFields {
+            id: task.id.to_string(),
+            name: task.file_name.clone(),
+            progress,
+            speed: task.speed,
+            status: task.status.as_str().to_lowercase(),
+            category: task.category.clone(),
+            size: task.total_size,
+            added_at,
+        })
+    }
+
+    pub fn from_torrent(handle: &TorrentHandle, metadata: &TorrentMetadata) -> Self {
+        TransferSummary::Torrent(TransferFields {
+            id: handle.info.info_hash.clone(),
+            name: handle.info.name.clone(),
+            progress: (handle.stats.progress * 100.0).clamp(0.0, 100.0),
+            speed: handle.stats.download_rate as f64,
+            status: handle.state.as_str().to_lowercase(),
+            category: metadata.category.clone(),
+            size: Some(handle.info.total_size),
+            added_at: metadata.added_time.timestamp(),
+        })
+    }
+
+    pub fn kind(&self) -> TransferKind {
+        match self {
+            TransferSummary::Download(_) => TransferKind::Download,
+            TransferSummary::Torrent(_) => TransferKind::Torrent,
+        }
+    }
+
+    pub fn fields(&self) -> &TransferFields {
+        match self {
+            TransferSummary::Download(fields) | TransferSummary::Torrent(fields) => fields,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransferSort {
+    #[default]
+    AddedAt,
+    Name,
+    Progress,
+    Speed,
+    Size,
+}
+
+/// Filter, sort and page options for the combined view
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TransferFilter {
+    pub kind: Option<TransferKind>,
+    pub status: Option<String>,
+    pub category: Option<String>,
+    /// Case-insensitive substring of the name
+    pub search: Option<String>,
+    pub sort: TransferSort,
+    pub descending: bool,
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+impl Default for TransferFilter {
+    fn default() -> Self {
+        Self {
+            kind: None,
+            status: None,
+            category: None,
+            search: None,
+            sort: TransferSort::AddedAt,
+            descending: true,
+            offset: 0,
+            limit: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferPage {
+    pub items: Vec<TransferSummary>,
+    /// Number of transfers matching the filter before paging
+    pub total: usize,
+}
+
+impl TransferFilter {
+    pub fn matches(&self, transfer: &TransferSummary) -> bool {
+        let fields = transfer.fields();
+        if self.kind.is_some_and(|kind| kind != transfer.kind()) {
+            return false;
+        }
+        if let Some(status) = &self.status {
+            if !status.eq_ignore_ascii_case(&fields.status) {
+                return false;
+            }
+        }
+        if let Some(category) = &self.category {
+            if fields.category.as_deref() != Some(category.as_str()) {
+                return false;
+            }
+        }
+        if let Some(search) = &self.search {
+            if !fields.name.to_lowercase().contains(&search.to_lowercase()) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Filter, sort and paginate downloads and torrents together
+    pub fn apply(&self, transfers: Vec<TransferSummary>) -> TransferPage {
+        let mut items: Vec<TransferSummary> =
+            transfers.into_iter().filter(|t| self.matches(t)).collect();
+
+        items.sort_by(|a, b| {
+            let (a, b) = (a.fields(), b.fields());
+            let ordering = match self.sort {
+                TransferSort::AddedAt => a.added_at.cmp(&b.added_at),
+                TransferSort::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                TransferSort::Progress => a.progress.total_cmp(&b.progress),
+                TransferSort::Speed => a.speed.total_cmp(&b.speed),
+                TransferSort::Size => a.size.cmp(&b.size),
+            };
+            // Stable tie-break so pages don't shuffle between requests
+            let ordering = ordering.then_with(|| a.id.cmp(&b.id));
+            if self.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+
+        let total = items.len();
+        let items = items
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect();
+
+        TransferPage { items, total }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(id: &str, name: &str, added_at: i64) -> TransferFields {
+        TransferFields {
+            id: id.to_string(),
+            name: name.to_string(),
+            progress: 50.0,
+            speed: 1024.0,
+            status: "downloading".to_string(),
+            category: Some("videos".to_string()),
+            size: Some(2048),
+            added_at,
+        }
+    }
+
+    #[test]
+    fn test_unified_json_shape() {
+        let download = TransferSummary::Download(fields("d1", "movie.mp4", 100));
+        let torrent = TransferSummary::Torrent(TransferFields {
+            category: None,
+            size: None,
+            ..fields("abcdef", "ubuntu.iso", 200)
+        });
+
+        assert_eq!(
+            serde_json::to_value(&download).unwrap(),
+            serde_json::json!({
+                "kind": "download",
+                "id": "d1",
+                "name": "movie.mp4",
+                "progress": 50.0,
+                "speed": 1024.0,
+                "status": "downloading",
+                "category": "videos",
+                "size": 2048,
+                "addedAt": 100
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(&torrent).unwrap(),
+            serde_json::json!({
+                "kind": "torrent",
+                "id": "abcdef",
+                "name": "ubuntu.iso",
+                "progress": 50.0,
+                "speed": 1024.0,
+                "status": "downloading",
+                "category": null,
+                "size": null,
+                "addedAt": 200
+            })
+        );
+
+        let parsed: TransferSummary = serde_json::from_value(serde_json::to_value(&torrent).unwrap()).unwrap();
+        assert_eq!(parsed, torrent);
+    }
+
+    #[test]
+    fn test_filter_sort_and_paginate_together() {
+        let transfers = vec![
+            TransferSummary::Download(fields("d1", "Alpha", 1)),
+            TransferSummary::Torrent(fields("t1", "Bravo", 3)),
+            TransferSummary::Download(fields("d2", "Charlie", 2)),
+            TransferSummary::Torrent(TransferFields {
+                status: "seeding".to_string(),
+                ..fields("t2", "Delta", 4)
+            }),
+        ];
+
+        // Newest first across both kinds
+        let page = TransferFilter { limit: Some(2), ..Default::default() }.apply(transfers.clone());
+        assert_eq!(page.total, 4);
+        let ids: Vec<&str> = page.items.iter().map(|t| t.fields().id.as_str()).collect();
+        assert_eq!(ids, vec!["t2", "t1"]);
+
+        let page = TransferFilter { offset: 2, limit: Some(2), ..Default::default() }.apply(transfers.clone());
+        let ids: Vec<&str> = page.items.iter().map(|t| t.fields().id.as_str()).collect();
+        assert_eq!(ids, vec!["d2", "d1"]);
+
+        let page = TransferFilter {
+            status: Some("Downloading".to_string()),
+            kind: Some(TransferKind::Torrent),
+            ..Default::default()
+        }
+        .apply(transfers.clone());
+        assert_eq!(page.total, 1);
+        assert_eq!(page.items[0].fields().id, "t1");
+
+        let page = TransferFilter {
+            sort: TransferSort::Name,
+            descending: false,
+            search: Some("a".to_string()),
+            ..Default::default()
+        }
+        .apply(transfers);
+        let names: Vec<&str> = page.items.iter().map(|t| t.fields().name.as_str()).collect();
+        assert_eq!(names, vec!["Alpha", "Bravo", "Charlie", "Delta"]);
+    }
+}
@@ -0,0 +1,304 @@
+// src-tauri/src/core/undo.rs
+// Undo for destructive actions taken in this session. Removing, cancelling
+// and bulk-cancelling downloads, cancelling FTP and SFTP transfers and
+// deleting categories each record what it takes to put things back,
+// schedules included; the newest entry is undone first. Entries expire
+// after a while and the stack is capped, and nothing survives a restart.
+// Deleted files can only come back when they went to the trash.
+
+use serde::Serialize;
+use schemars::JsonSchema;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::core::category::Category;
+use crate::core::content_filter::ContentFilter;
+use crate::core::download_task::{DownloadStatus, DownloadTask};
+use crate::core::scheduler::ScheduledTask;
+use crate::utils::error::DownloadError;
+
+pub const DEFAULT_UNDO_MAX_ENTRIES: usize = 20;
+pub const DEFAULT_UNDO_EXPIRY_SECS: u64 = 300;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndoSettings {
+    pub max_entries: usize,
+    pub expiry: Duration,
+    /// Deleted files go to the trash, so removing a download with its file
+    /// can be undone
+    pub soft_delete: bool,
+}
+
+impl Default for UndoSettings {
+    fn default() -> Self {
+        Self {
+            max_entries: DEFAULT_UNDO_MAX_ENTRIES,
+            expiry: Duration::from_secs(DEFAULT_UNDO_EXPIRY_SECS),
+            soft_delete: false,
+        }
+    }
+}
+
+/// What undoing an action has to do
+#[derive(Debug, Clone)]
+pub enum UndoAction {
+    /// Insert the removed row again with its schedules, and restore its
+    /// file from the trash when it was moved there
+    RemoveDownload { task: Box<DownloadTask>, trashed_file: Option<PathBuf>, schedules: Vec<ScheduledTask> },
+    /// Put cancelled downloads back to the status each had, and their
+    /// schedules back on the scheduler
+    Cancel { previous: Vec<(Uuid, DownloadStatus)>, schedules: Vec<ScheduledTask> },
+    /// Start a cancelled FTP or SFTP transfer again under its id
+    CancelRemote { id: Uuid, restart: RemoteRestart },
+    /// Create the category again and move its downloads back into it
+    DeleteCategory { category: Category, download_ids: Vec<String> },
+}
+
+/// What an FTP or SFTP transfer was started with, kept while it runs so a
+/// cancel can be undone. A file picks up where it stopped; a folder starts
+/// over.
+#[derive(Debug, Clone)]
+pub enum RemoteRestart {
+    FtpFile { url: String, local_path: PathBuf },
+    SftpFile { url: String, local_path: PathBuf, password: Option<String>, key_path: Option<PathBuf> },
+    FtpFolder { url: String, local_dir: PathBuf, filter: Option<ContentFilter> },
+    SftpFolder {
+        url: String,
+        local_dir: PathBuf,
+        password: Option<String>,
+        key_path: Option<PathBuf>,
+        filter: Option<ContentFilter>,
+    },
+}
+
+impl RemoteRestart {
+    /// The file or folder the transfer writes, as the undo entry names it
+    pub fn name(&self) -> String {
+        let path = match self {
+            RemoteRestart::FtpFile { local_path, .. } | RemoteRestart::SftpFile { local_path, .. } => local_path,
+            RemoteRestart::FtpFolder { local_dir, .. } | RemoteRestart::SftpFolder { local_dir, .. } => local_dir,
+        };
+        path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_else(|| path.display().to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum UndoKind {
+    RemoveDownload,
+    CancelDownload,
+    CancelAll,
+    DeleteCategory,
+}
+
+/// An entry as the frontend sees it, and the payload of `action-undone`
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoableAction {
+    pub id: Uuid,
+    pub kind: UndoKind,
+    pub description: String,
+    pub recorded_at: chrono::NaiveDateTime,
+    pub expires_in_secs: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct UndoEntry {
+    pub id: Uuid,
+    pub kind: UndoKind,
+    pub description: String,
+    pub action: UndoAction,
+    recorded_at: chrono::NaiveDateTime,
+    recorded: Instant,
+}
+
+impl UndoEntry {
+    pub fn summary(&self, expiry: Duration, now: Instant) -> UndoableAction {
+        UndoableAction {
+            id: self.id,
+            kind: self.kind,
+            description: self.description.clone(),
+            recorded_at: self.recorded_at,
+            expires_in_secs: expiry.saturating_sub(now.duration_since(self.recorded)).as_secs(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct UndoStack {
+    entries: parking_lot::Mutex<VecDeque<UndoEntry>>,
+    settings: parking_lot::RwLock<UndoSettings>,
+}
+
+impl UndoStack {
+    pub fn set_settings(&self, settings: UndoSettings) {
+        *self.settings.write() = settings;
+        self.entries.lock().truncate(settings_cap(&self.settings.read()));
+    }
+
+    pub fn settings(&self) -> UndoSettings {
+        self.settings.read().clone()
+    }
+
+    /// Record an action that can be undone
+    pub fn record(&self, kind: UndoKind, description: impl Into<String>, action: UndoAction) {
+        self.record_at(kind, description.into(), action, Instant::now());
+    }
+
+    fn record_at(&self, kind: UndoKind, description: String, action: UndoAction, now: Instant) {
+        let cap = settings_cap(&self.settings.read());
+        let mut entries = self.entries.lock();
+        entries.push_front(UndoEntry {
+            id: Uuid::new_v4(),
+            kind,
+            description,
+            action,
+            recorded_at: chrono::Local::now().naive_local(),
+            recorded: now,
+        });
+        entries.truncate(cap);
+    }
+
+    /// Take the newest entry that hasn't expired
+    pub fn pop(&self) -> Option<UndoEntry> {
+        self.pop_at(Instant::now())
+    }
+
+    fn pop_at(&self, now: Instant) -> Option<UndoEntry> {
+        let mut entries = self.entries.lock();
+        self.expire(&mut entries, now);
+        entries.pop_front()
+    }
+
+    /// Undoable actions, newest first
+    pub fn list(&self) -> Vec<UndoableAction> {
+        self.list_at(Instant::now())
+    }
+
+    fn list_at(&self, now: Instant) -> Vec<UndoableAction> {
+        let expiry = self.settings.read().expiry;
+        let mut entries = self.entries.lock();
+        self.expire(&mut entries, now);
+        entries.iter().map(|entry| entry.summary(expiry, now)).collect()
+    }
+
+    fn expire(&self, entries: &mut VecDeque<UndoEntry>, now: Instant) {
+        let expiry = self.settings.read().expiry;
+        entries.retain(|entry| now.duration_since(entry.recorded) < expiry);
+    }
+}
+
+fn settings_cap(settings: &UndoSettings) -> usize {
+    settings.max_entries.max(1)
+}
+
+/// The status a cancelled download comes back with: one that was running
+/// or waiting its turn returns paused, to be resumed when wanted
+pub fn restored_status(previous: DownloadStatus) -> DownloadStatus {
+    match previous {
+        DownloadStatus::Queued | DownloadStatus::WaitingForCondition => DownloadStatus::Paused,
+        status if status.is_active() => DownloadStatus::Paused,
+        status => status,
+    }
+}
+
+/// Move `path` to the system trash
+pub async fn move_to_trash(path: &Path) -> Result<(), DownloadError> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || trash::delete(&path))
+        .await
+        .map_err(|e| DownloadError::Unknown(e.to_string()))?
+        .map_err(|e| DownloadError::FileError(format!("Cannot move to trash: {}", e)))
+}
+
+/// Put the most recently trashed file that was at `path` back there
+#[cfg(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+))]
+pub async fn restore_from_trash(path: &Path) -> Result<(), DownloadError> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let not_found = || DownloadError::NotFound(format!("{} is no longer in the trash", path.display()));
+        let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+            return Err(not_found());
+        };
+        let items = trash::os_limited::list()
+            .map_err(|e| DownloadError::FileError(format!("Cannot read the trash: {}", e)))?;
+        let item = items
+            .into_iter()
+            .filter(|item| item.original_parent == parent && Path::new(&item.name).as_os_str() == name)
+            .max_by_key(|item| item.time_deleted)
+            .ok_or_else(not_found)?;
+        trash::os_limited::restore_all([item])
+            .map_err(|e| DownloadError::FileError(format!("Cannot restore from the trash: {}", e)))
+    })
+    .await
+    .map_err(|e| DownloadError::Unknown(e.to_string()))?
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+)))]
+pub async fn restore_from_trash(path: &Path) -> Result<(), DownloadError> {
+    Err(DownloadError::Unknown(format!(
+        "Restoring from the trash is not supported here; {} is still in it",
+        path.display()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cancel(id: Uuid) -> UndoAction {
+        UndoAction::Cancel { previous: vec![(id, DownloadStatus::Downloading)], schedules: Vec::new() }
+    }
+
+    #[test]
+    fn test_newest_first_capped_and_expiring() {
+        let stack = UndoStack::default();
+        stack.set_settings(UndoSettings { max_entries: 2, expiry: Duration::from_secs(60), soft_delete: false });
+        let start = Instant::now();
+        stack.record_at(UndoKind::CancelDownload, "first".into(), cancel(Uuid::new_v4()), start);
+        stack.record_at(UndoKind::CancelDownload, "second".into(), cancel(Uuid::new_v4()), start);
+        stack.record_at(UndoKind::CancelAll, "third".into(), cancel(Uuid::new_v4()), start + Duration::from_secs(30));
+
+        // The oldest fell off the end
+        let listed: Vec<_> = stack.list_at(start + Duration::from_secs(40)).into_iter().map(|a| (a.description, a.expires_in_secs)).collect();
+        assert_eq!(listed, vec![("third".to_string(), 50), ("second".to_string(), 20)]);
+
+        // "second" expires before it is reached
+        let later = start + Duration::from_secs(61);
+        assert_eq!(stack.pop_at(later).map(|e| e.description), Some("third".to_string()));
+        assert!(stack.pop_at(later).is_none());
+    }
+
+    #[test]
+    fn test_remote_restart_names_its_target() {
+        let file = RemoteRestart::SftpFile {
+            url: "sftp://host.example/pub/a.iso".to_string(),
+            local_path: PathBuf::from("/data/a.iso"),
+            password: None,
+            key_path: None,
+        };
+        assert_eq!(file.name(), "a.iso");
+        let folder = RemoteRestart::FtpFolder {
+            url: "ftp://host.example/pub/".to_string(),
+            local_dir: PathBuf::from("/data/pub"),
+            filter: None,
+        };
+        assert_eq!(folder.name(), "pub");
+    }
+
+    #[test]
+    fn test_restored_status() {
+        assert_eq!(restored_status(DownloadStatus::Downloading), DownloadStatus::Paused);
+        assert_eq!(restored_status(DownloadStatus::Queued), DownloadStatus::Paused);
+        assert_eq!(restored_status(DownloadStatus::Failed), DownloadStatus::Failed);
+        assert_eq!(restored_status(DownloadStatus::Paused), DownloadStatus::Paused);
+    }
+}
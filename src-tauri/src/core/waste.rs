@@ -0,0 +1,118 @@
+// src-tauri/src/core/waste.rs
+// Accounting for bytes that were downloaded and then thrown away: segment
+// files that had to be cut back or restarted, partial data from a failed
+// attempt, a server ignoring a range request, a file that failed its
+// checksum. Each transfer shares one counter between its segments; the
+// total is added to the task's `wasted_bytes` when the transfer ends.
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Settings key: fraction of a file's size that may be wasted before the
+/// user is warned
+pub const WASTE_WARNING_FRACTION_KEY: &str = "wasted_bytes_warning_fraction";
+
+pub const DEFAULT_WASTE_WARNING_FRACTION: f64 = 0.25;
+
+/// Why bytes were thrown away
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WasteReason {
+    /// A segment file past its chunk or unreadable after a crash
+    FailedSegment,
+    /// Partial data that no longer matched the file on the server
+    InvalidatedResume,
+    /// The server answered a range request with the whole file
+    RangeIgnored,
+    /// A single-stream attempt that failed and starts over from zero
+    FailedAttempt,
+    /// A finished file whose checksum didn't match
+    ChecksumMismatch,
+}
+
+impl std::fmt::Display for WasteReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WasteReason::FailedSegment => write!(f, "failed segment"),
+            WasteReason::InvalidatedResume => write!(f, "invalidated resume data"),
+            WasteReason::RangeIgnored => write!(f, "range request ignored"),
+            WasteReason::FailedAttempt => write!(f, "failed attempt"),
+            WasteReason::ChecksumMismatch => write!(f, "checksum mismatch"),
+        }
+    }
+}
+
+/// Sent with `download-waste-warning`
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WasteWarning {
+    pub id: Uuid,
+    pub file_name: String,
+    pub wasted_bytes: u64,
+    pub total_size: Option<u64>,
+}
+
+/// Wasted bytes of one transfer; clones share the count
+#[derive(Debug, Clone, Default)]
+pub struct WasteCounter(Arc<AtomicU64>);
+
+impl WasteCounter {
+    pub fn add(&self, bytes: u64, reason: WasteReason) {
+        if bytes > 0 {
+            tracing::debug!("Discarded {} bytes: {}", bytes, reason);
+            self.0.fetch_add(bytes, Ordering::Relaxed);
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Check a warning fraction setting: above zero, and at most 10 (ten
+/// times the file's size)
+pub fn validate_fraction(fraction: f64) -> Result<f64, String> {
+    if fraction.is_finite() && fraction > 0.0 && fraction <= 10.0 {
+        Ok(fraction)
+    } else {
+        Err(format!("Wasted bytes warning fraction must be above 0 and at most 10, got {}", fraction))
+    }
+}
+
+/// True when going from `before` to `after` wasted bytes crossed
+/// `fraction` of `total_size`, so the warning goes out once per download
+pub fn crossed_threshold(before: u64, after: u64, total_size: Option<u64>, fraction: f64) -> bool {
+    let Some(total) = total_size.filter(|t| *t > 0) else {
+        return false;
+    };
+    let limit = total as f64 * fraction;
+    (before as f64) <= limit && (after as f64) > limit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter_is_shared_between_clones() {
+        let counter = WasteCounter::default();
+        let segment = counter.clone();
+        segment.add(100, WasteReason::FailedSegment);
+        counter.add(0, WasteReason::RangeIgnored);
+        counter.add(20, WasteReason::FailedAttempt);
+        assert_eq!(segment.total(), 120);
+    }
+
+    #[test]
+    fn test_threshold_is_crossed_once() {
+        assert!(!crossed_threshold(0, 250, Some(1000), 0.25));
+        assert!(crossed_threshold(0, 251, Some(1000), 0.25));
+        assert!(!crossed_threshold(300, 900, Some(1000), 0.25));
+        assert!(!crossed_threshold(0, 5000, None, 0.25));
+        assert!(validate_fraction(0.0).is_err());
+        assert!(validate_fraction(f64::NAN).is_err());
+        assert_eq!(validate_fraction(0.5), Ok(0.5));
+    }
+}
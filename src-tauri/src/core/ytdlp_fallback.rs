@@ -0,0 +1,75 @@
+// src-tauri/src/core/ytdlp_fallback.rs
+// Direct links to video site CDNs are signed and expire, so a link pasted
+// or caught from a page can be refused with 403 before or during its
+// download. The page it came from stays valid, and yt-dlp gets a fresh
+// link from it every time. When the page is one yt-dlp supports, the
+// download is retried through yt-dlp with the page, linked to the failed
+// direct download so history shows what happened.
+
+use serde::Serialize;
+use schemars::JsonSchema;
+use uuid::Uuid;
+
+use crate::network::youtube_downloader::YouTubeDownloader;
+use crate::utils::error::DownloadError;
+
+/// Settings key: retry refused media links through yt-dlp
+pub const AUTO_FALLBACK_KEY: &str = "auto_fallback_to_ytdlp";
+
+/// Sent with `download-fallback` when a refused direct download moves to
+/// yt-dlp
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FallbackNotice {
+    /// The failed direct download
+    pub original_id: Uuid,
+    /// The yt-dlp download that replaces it
+    pub fallback_id: Uuid,
+    pub file_name: String,
+    pub page_url: String,
+    /// Why the direct download failed
+    pub reason: String,
+}
+
+/// Whether `error` is how a server refuses an expired signed link
+pub fn is_refused(error: &DownloadError) -> bool {
+    matches!(
+        error,
+        DownloadError::ServerError { status: 403, .. } | DownloadError::ExpiredUrl(_)
+    )
+}
+
+/// The page to hand yt-dlp after `url` failed with `error`: the source
+/// page, when yt-dlp supports it and it isn't the link that failed. None
+/// for links yt-dlp already handled.
+pub fn fallback_page<'a>(url: &str, source_page_url: Option<&'a str>, error: &DownloadError) -> Option<&'a str> {
+    if !is_refused(error) || YouTubeDownloader::is_supported_url(url) {
+        return None;
+    }
+    source_page_url
+        .map(str::trim)
+        .filter(|page| !page.is_empty() && *page != url && YouTubeDownloader::is_supported_url(page))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fallback_page() {
+        let cdn = "https://rr3---sn-4g5e6nsz.googlevideo.com/videoplayback?expire=1700000000&sig=abc";
+        let page = Some("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+        let forbidden = DownloadError::ServerError { status: 403, message: "403 Forbidden".to_string() };
+
+        assert_eq!(fallback_page(cdn, page, &forbidden), page);
+        assert_eq!(fallback_page(cdn, page, &DownloadError::ExpiredUrl("expired".to_string())), page);
+
+        // Other failures, unknown or unsupported pages and links yt-dlp
+        // already had don't fall back
+        let not_found = DownloadError::ServerError { status: 404, message: "404 Not Found".to_string() };
+        assert_eq!(fallback_page(cdn, page, &not_found), None);
+        assert_eq!(fallback_page(cdn, None, &forbidden), None);
+        assert_eq!(fallback_page(cdn, Some("https://blog.example/post"), &forbidden), None);
+        assert_eq!(fallback_page(page.unwrap(), page, &forbidden), None);
+    }
+}
@@ -1,12 +1,17 @@
+use sqlx::sqlite::{SqliteAutoVacuum, SqliteConnectOptions, SqliteConnection};
 use sqlx::{Row, SqlitePool};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
 use crate::core::download_task::{
-    DownloadStatus, DownloadTask,
+    DownloadPriority, DownloadStatus, DownloadTask, IntegrityStatus, PrivateText,
 };
+use crate::core::post_action::{PostAction, PostActionStatus};
 use crate::database::models::DownloadRow;
+use crate::network::url_parser::UrlParser;
 use crate::utils::error::DownloadError;
+use crate::utils::file_utils;
+use crate::utils::permissions::FilePermissions;
 
 #[derive(Clone)]
 pub struct Database {
@@ -26,11 +31,15 @@ impl Database {
         })?;
 
         let db_path = app_data_dir.join("downloads.db");
-        let db_url =
-            format!("sqlite:{}?mode=rwc", db_path.display());
+        // Incremental auto-vacuum lets maintenance hand freed pages back
+        // without a full VACUUM. It only applies to newly created files.
+        let options = SqliteConnectOptions::new()
+            .filename(&db_path)
+            .create_if_missing(true)
+            .auto_vacuum(SqliteAutoVacuum::Incremental);
 
         let pool =
-            SqlitePool::connect(&db_url).await.map_err(|e| {
+            SqlitePool::connect_with(options).await.map_err(|e| {
                 DownloadError::Unknown(format!(
                     "DB connection failed: {}",
                     e
@@ -40,6 +49,18 @@ impl Database {
         Ok(Self { pool })
     }
 
+    /// Open an existing database without writing to it, for listing from
+    /// a process that doesn't own it
+    pub async fn open_read_only(app_data_dir: &Path) -> Result<Self, DownloadError> {
+        let options = SqliteConnectOptions::new()
+            .filename(app_data_dir.join("downloads.db"))
+            .read_only(true);
+        let pool = SqlitePool::connect_with(options)
+            .await
+            .map_err(|e| DownloadError::Unknown(format!("DB connection failed: {}", e)))?;
+        Ok(Self { pool })
+    }
+
     /// Run database migrations
     pub async fn run_migrations(
         &self,
@@ -107,9 +128,199 @@ impl Database {
             ))
         })?;
 
+        // Run category migrations
+        self.run_category_migrations().await?;
+
+        // Run domain migration
+        self.run_domain_migrations().await?;
+
+        // Link re-downloads to the entries they replaced
+        self.add_column_if_missing("downloads", "superseded_by", "TEXT").await?;
+
+        // User notes and the page a download came from
+        self.add_column_if_missing("downloads", "notes", "TEXT").await?;
+        self.add_column_if_missing("downloads", "source_page_url", "TEXT").await?;
+        self.add_column_if_missing("downloads", "source_page_title", "TEXT").await?;
+
+        // Outcome of the last missing-file sweep
+        self.add_column_if_missing("downloads", "integrity_status", "TEXT").await?;
+
+        // Per-download backoff overrides (JSON)
+        self.add_column_if_missing("downloads", "retry_policy", "TEXT").await?;
+
+        // Set when the download's category requires a checksum
+        self.add_column_if_missing("downloads", "checksum_required", "INTEGER NOT NULL DEFAULT 0").await?;
+
+        // Hops followed resolving the URL (JSON)
+        self.add_column_if_missing("downloads", "redirect_chain", "TEXT").await?;
+
+        // Why a download is limited to one connection
+        self.add_column_if_missing("downloads", "capability_notes", "TEXT").await?;
+
+        // Where an expired signed URL can be refreshed from (JSON)
+        self.add_column_if_missing("downloads", "url_refresh", "TEXT").await?;
+
+        // Post-completion action and its outcome (JSON)
+        self.add_column_if_missing("downloads", "post_action", "TEXT").await?;
+        self.add_column_if_missing("downloads", "post_action_status", "TEXT").await?;
+
+        // When a download scheduled for tonight starts
+        self.add_column_if_missing("downloads", "scheduled_for", "TEXT").await?;
+
+        // When the user needs a download finished by
+        self.add_column_if_missing("downloads", "deadline_at", "TEXT").await?;
+
+        // Imported from disk without a known source
+        self.add_column_if_missing("downloads", "unknown_origin", "INTEGER NOT NULL DEFAULT 0").await?;
+
+        // Bytes downloaded and thrown away by restarts and failed checks
+        self.add_column_if_missing("downloads", "wasted_bytes", "INTEGER NOT NULL DEFAULT 0").await?;
+
+        // The refused direct download a yt-dlp download took over from
+        self.add_column_if_missing("downloads", "fallback_of", "TEXT").await?;
+
+        // The per-entry playlist a row belongs to
+        self.add_column_if_missing("downloads", "playlist_id", "TEXT").await?;
+
+        // Folder for segment data when it isn't beside the file
+        self.add_column_if_missing("downloads", "temp_dir", "TEXT").await?;
+
+        // When the transfer started, so time spent queued isn't counted
+        // against its speed
+        self.add_column_if_missing("downloads", "started_at", "TEXT").await?;
+
         // Run torrent migrations
         self.run_torrent_migrations().await?;
 
+        // Run queue migrations
+        self.run_queue_migrations().await?;
+
+        // Per-day transfer totals
+        sqlx::query(include_str!("migrations/005_add_daily_usage.sql"))
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DownloadError::Unknown(format!("Usage migration failed: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Add a column to a table unless it already exists
+    async fn add_column_if_missing(
+        &self,
+        table: &str,
+        column: &str,
+        definition: &str,
+    ) -> Result<bool, DownloadError> {
+        let exists: Option<(String,)> = sqlx::query_as(
+            "SELECT name FROM pragma_table_info(?1) WHERE name = ?2"
+        )
+        .bind(table)
+        .bind(column)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| DownloadError::Unknown(format!("Failed to inspect {}: {}", table, e)))?;
+
+        if exists.is_some() {
+            return Ok(false);
+        }
+
+        sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DownloadError::Unknown(format!(
+                "Failed to add column {}.{}: {}",
+                table, column, e
+            )))?;
+
+        Ok(true)
+    }
+
+    /// Run category migrations
+    async fn run_category_migrations(&self) -> Result<(), DownloadError> {
+        let migration_sql = include_str!("migrations/006_add_category_table.sql");
+
+        sqlx::query(migration_sql)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                DownloadError::Unknown(format!(
+                    "Category migration failed: {}",
+                    e
+                ))
+            })?;
+
+        if self.add_column_if_missing("downloads", "category_id", "TEXT").await? {
+            // Carry over categories assigned before the column existed
+            sqlx::query("UPDATE downloads SET category_id = category WHERE category IS NOT NULL")
+                .execute(&self.pool)
+                .await
+                .map_err(|e| DownloadError::Unknown(format!("Category backfill failed: {}", e)))?;
+        }
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_downloads_category_id ON downloads(category_id)")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DownloadError::Unknown(format!("Category index failed: {}", e)))?;
+
+        // Per-category overrides of the completed file mode and group
+        self.add_column_if_missing("categories", "file_mode", "TEXT").await?;
+        self.add_column_if_missing("categories", "file_group", "TEXT").await?;
+
+        // Add-time defaults; software downloads are expected to carry a checksum
+        self.add_column_if_missing("categories", "default_segments", "INTEGER").await?;
+        self.add_column_if_missing("categories", "default_max_retries", "INTEGER").await?;
+        if self
+            .add_column_if_missing("categories", "require_checksum", "INTEGER NOT NULL DEFAULT 0")
+            .await?
+        {
+            sqlx::query("UPDATE categories SET require_checksum = 1 WHERE id = 'software'")
+                .execute(&self.pool)
+                .await
+                .map_err(|e| DownloadError::Unknown(format!("Category backfill failed: {}", e)))?;
+        }
+
+        Ok(())
+    }
+
+    /// Add the normalized downloads.domain column and backfill it from URLs
+    async fn run_domain_migrations(&self) -> Result<(), DownloadError> {
+        self.add_column_if_missing("downloads", "domain", "TEXT").await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_downloads_domain ON downloads(domain)")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DownloadError::Unknown(format!("Domain index failed: {}", e)))?;
+
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT id, url FROM downloads WHERE domain IS NULL"
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| DownloadError::Unknown(format!("Domain backfill failed: {}", e)))?;
+
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DownloadError::Unknown(format!("Domain backfill failed: {}", e)))?;
+
+        for (id, url) in rows {
+            // Unparseable URLs get an empty domain so they are not rescanned
+            let domain = UrlParser::domain(&url).unwrap_or_default();
+            sqlx::query("UPDATE downloads SET domain = ?1 WHERE id = ?2")
+                .bind(domain)
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| DownloadError::Unknown(format!("Domain backfill failed: {}", e)))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| DownloadError::Unknown(format!("Domain backfill failed: {}", e)))?;
+
         Ok(())
     }
 
@@ -128,6 +339,34 @@ impl Database {
                 ))
             })?;
 
+        self.add_column_if_missing(
+            "torrents",
+            "auto_paused",
+            "BOOLEAN NOT NULL DEFAULT FALSE",
+        ).await?;
+        self.add_column_if_missing("torrents", "metainfo", "BLOB").await?;
+        self.add_column_if_missing("torrents", "trackers", "TEXT").await?;
+        self.add_column_if_missing("torrents", "wasted_bytes", "INTEGER NOT NULL DEFAULT 0").await?;
+        self.add_column_if_missing("torrents", "super_seeding", "BOOLEAN NOT NULL DEFAULT FALSE").await?;
+        self.add_column_if_missing("torrents", "only_files", "TEXT").await?;
+
+        Ok(())
+    }
+
+    /// Run download queue migrations
+    async fn run_queue_migrations(&self) -> Result<(), DownloadError> {
+        let migration_sql = include_str!("migrations/004_add_download_queue.sql");
+
+        sqlx::query(migration_sql)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                DownloadError::Unknown(format!(
+                    "Queue migration failed: {}",
+                    e
+                ))
+            })?;
+
         Ok(())
     }
 
@@ -136,21 +375,109 @@ impl Database {
         &self,
         task: &DownloadTask,
     ) -> Result<(), DownloadError> {
+        let mut conn = self.connection().await?;
+        Self::insert_row(&mut conn, task, false).await.map(|_| ())
+    }
+
+    /// Insert a new download, claiming its save path. If another unfinished
+    /// download already holds the path the task is renamed `name (1).ext`,
+    /// `name (2).ext`, ... until a free one is found. Each attempt is a
+    /// single statement, so concurrent adds can't claim the same path.
+    pub async fn reserve_and_insert(
+        &self,
+        task: &mut DownloadTask,
+    ) -> Result<(), DownloadError> {
+        let mut conn = self.connection().await?;
+        Self::reserve_path_and_insert(&mut conn, task).await
+    }
+
+    /// Insert many new downloads in one transaction, claiming their save
+    /// paths as `reserve_and_insert` does. Either every row is written or
+    /// none is.
+    pub async fn reserve_and_insert_all(
+        &self,
+        tasks: &mut [DownloadTask],
+    ) -> Result<(), DownloadError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DownloadError::Unknown(format!("Failed to begin batch insert: {}", e)))?;
+
+        for task in tasks.iter_mut() {
+            Self::reserve_path_and_insert(&mut tx, task).await?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| DownloadError::Unknown(format!("Failed to commit batch insert: {}", e)))
+    }
+
+    async fn connection(&self) -> Result<sqlx::pool::PoolConnection<sqlx::Sqlite>, DownloadError> {
+        self.pool
+            .acquire()
+            .await
+            .map_err(|e| DownloadError::Unknown(format!("Failed to get a connection: {}", e)))
+    }
+
+    async fn reserve_path_and_insert(
+        conn: &mut SqliteConnection,
+        task: &mut DownloadTask,
+    ) -> Result<(), DownloadError> {
+        let requested = task.save_path.clone();
+
+        for n in 0..10000 {
+            let candidate = file_utils::numbered_path(&requested, n);
+            if n > 0 && candidate.exists() {
+                continue;
+            }
+            if let Some(name) = candidate.file_name() {
+                task.file_name = name.to_string_lossy().to_string();
+            }
+            task.save_path = candidate;
+
+            if Self::insert_row(conn, task, true).await? {
+                return Ok(());
+            }
+        }
+
+        Err(DownloadError::FileError(format!(
+            "No free file name for {}",
+            requested.display()
+        )))
+    }
+
+    /// Insert a download row. With `reserve_path` the insert is skipped,
+    /// returning false, when an unfinished download already uses the path.
+    async fn insert_row(
+        conn: &mut SqliteConnection,
+        task: &DownloadTask,
+        reserve_path: bool,
+    ) -> Result<bool, DownloadError> {
         let segment_progress_json = serde_json::to_string(&task.segment_progress)
             .unwrap_or_else(|_| "[]".to_string());
 
-        sqlx::query(
+        let result = sqlx::query(
             r#"
             INSERT INTO downloads (
                 id, url, final_url, file_name, save_path, total_size,
                 downloaded_size, status, segments, supports_range,
                 content_type, etag, expected_checksum, actual_checksum,
                 checksum_algorithm, retry_count, error_message, created_at,
-                completed_at, priority, category, segment_progress
-            ) VALUES (
+                completed_at, priority, category, segment_progress,
+                category_id, domain, notes, source_page_url,
+                source_page_title, retry_policy, checksum_required,
+                redirect_chain, capability_notes, url_refresh, post_action,
+                scheduled_for, deadline_at, unknown_origin, wasted_bytes,
+                fallback_of, temp_dir, playlist_id
+            )
+            SELECT
                 ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10,
                 ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19,
-                ?20, ?21, ?22
+                ?20, ?21, ?22, ?21, ?23, ?24, ?25, ?26, ?27, ?29,
+                ?30, ?31, ?32, ?33, ?34, ?35, ?36, ?37, ?38, ?39, ?40
+            WHERE NOT ?28 OR NOT EXISTS (
+                SELECT 1 FROM downloads
+                WHERE save_path = ?5
+                  AND status NOT IN ('Completed', 'Cancelled')
             )
             "#,
         )
@@ -173,10 +500,30 @@ impl Database {
         .bind(&task.error_message)
         .bind(task.created_at.to_string())
         .bind(task.completed_at.map(|c| c.to_string()))
-        .bind(task.priority as i32)
+        .bind(task.priority.value() as i32)
         .bind(&task.category)
         .bind(segment_progress_json)
-        .execute(&self.pool)
+        .bind(UrlParser::domain(&task.url).unwrap_or_default())
+        .bind(task.notes.as_ref().map(|n| n.as_str()))
+        .bind(&task.source_page_url)
+        .bind(&task.source_page_title)
+        .bind(task.retry_policy.as_ref().and_then(|p| serde_json::to_string(p).ok()))
+        .bind(reserve_path)
+        .bind(task.checksum_required)
+        .bind((!task.redirect_chain.is_empty())
+            .then(|| serde_json::to_string(&task.redirect_chain).ok())
+            .flatten())
+        .bind(&task.capability_notes)
+        .bind(task.url_refresh.as_ref().and_then(|r| serde_json::to_string(r).ok()))
+        .bind(task.post_action.as_ref().and_then(|a| serde_json::to_string(a).ok()))
+        .bind(task.scheduled_for.map(|t| t.to_string()))
+        .bind(task.deadline_at.map(|t| t.to_string()))
+        .bind(task.unknown_origin)
+        .bind(task.wasted_bytes as i64)
+        .bind(task.fallback_of.map(|id| id.to_string()))
+        .bind(task.temp_dir.as_ref().map(|p| p.to_string_lossy().to_string()))
+        .bind(task.playlist_id.map(|id| id.to_string()))
+        .execute(&mut *conn)
         .await
         .map_err(|e| {
             DownloadError::Unknown(format!(
@@ -185,7 +532,7 @@ impl Database {
             ))
         })?;
 
-        Ok(())
+        Ok(result.rows_affected() == 1)
     }
 
     /// Update download status and progress
@@ -208,8 +555,22 @@ impl Database {
                 error_message = ?7,
                 completed_at = ?8,
                 actual_checksum = ?9,
-                segment_progress = ?10
-            WHERE id = ?11
+                segment_progress = ?10,
+                category = ?11,
+                category_id = ?11,
+                supports_range = ?13,
+                capability_notes = ?14,
+                url = ?15,
+                final_url = ?16,
+                etag = ?17,
+                wasted_bytes = ?18,
+                -- Local time, as created_at and completed_at are
+                started_at = CASE
+                    WHEN ?5 = 'Downloading' AND (started_at IS NULL OR ?4 = 0)
+                    THEN datetime('now', 'localtime')
+                    ELSE started_at
+                END
+            WHERE id = ?12
             "#,
         )
         .bind(&task.file_name)
@@ -222,7 +583,14 @@ impl Database {
         .bind(task.completed_at.map(|c| c.to_string()))
         .bind(&task.actual_checksum)
         .bind(segment_progress_json)
+        .bind(&task.category)
         .bind(task.id.to_string())
+        .bind(task.supports_range)
+        .bind(&task.capability_notes)
+        .bind(&task.url)
+        .bind(&task.final_url)
+        .bind(&task.etag)
+        .bind(task.wasted_bytes as i64)
         .execute(&self.pool)
         .await
         .map_err(|e| {
@@ -242,7 +610,16 @@ impl Database {
         status: DownloadStatus,
     ) -> Result<(), DownloadError> {
         sqlx::query(
-            "UPDATE downloads SET status = ?1 WHERE id = ?2",
+            r#"
+            UPDATE downloads SET
+                status = ?1,
+                started_at = CASE
+                    WHEN ?1 = 'Downloading' AND (started_at IS NULL OR downloaded_size = 0)
+                    THEN datetime('now', 'localtime')
+                    ELSE started_at
+                END
+            WHERE id = ?2
+            "#,
         )
         .bind(status.as_str())
         .bind(id.to_string())
@@ -258,6 +635,27 @@ impl Database {
         Ok(())
     }
 
+    /// Set how many segments a download uses
+    pub async fn update_segments(
+        &self,
+        id: Uuid,
+        segments: u8,
+    ) -> Result<(), DownloadError> {
+        sqlx::query("UPDATE downloads SET segments = ?1 WHERE id = ?2")
+            .bind(segments as i32)
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                DownloadError::Unknown(format!(
+                    "Segment update failed: {}",
+                    e
+                ))
+            })?;
+
+        Ok(())
+    }
+
     /// Get a single download by ID
     pub async fn get_download(
         &self,
@@ -298,6 +696,184 @@ impl Database {
         Ok(rows.into_iter().map(Self::row_to_task).collect())
     }
 
+    /// Get every completed download, newest first
+    pub async fn get_completed_downloads(
+        &self,
+    ) -> Result<Vec<DownloadTask>, DownloadError> {
+        self.get_downloads_with_status(DownloadStatus::Completed).await
+    }
+
+    /// Get every download in `status`, newest first
+    pub async fn get_downloads_with_status(
+        &self,
+        status: DownloadStatus,
+    ) -> Result<Vec<DownloadTask>, DownloadError> {
+        let rows: Vec<DownloadRow> = sqlx::query_as::<_, DownloadRow>(
+            "SELECT * FROM downloads WHERE status = ?1 ORDER BY created_at DESC",
+        )
+        .bind(status.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            DownloadError::Unknown(format!(
+                "Query failed: {}",
+                e
+            ))
+        })?;
+
+        Ok(rows.into_iter().map(Self::row_to_task).collect())
+    }
+
+    /// Record (or clear) the result of the missing-file sweep
+    pub async fn set_integrity_status(
+        &self,
+        id: Uuid,
+        status: Option<IntegrityStatus>,
+    ) -> Result<(), DownloadError> {
+        sqlx::query("UPDATE downloads SET integrity_status = ?1 WHERE id = ?2")
+            .bind(status.map(|s| s.as_str().to_string()))
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                DownloadError::Unknown(format!(
+                    "Failed to update integrity status: {}",
+                    e
+                ))
+            })?;
+
+        Ok(())
+    }
+
+    /// Change what runs once a download completes
+    pub async fn set_post_action(
+        &self,
+        id: Uuid,
+        action: Option<&PostAction>,
+    ) -> Result<(), DownloadError> {
+        sqlx::query("UPDATE downloads SET post_action = ?1 WHERE id = ?2")
+            .bind(action.and_then(|a| serde_json::to_string(a).ok()))
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                DownloadError::Unknown(format!(
+                    "Failed to update post-action: {}",
+                    e
+                ))
+            })?;
+
+        Ok(())
+    }
+
+    /// Set or clear when a download needs to be finished by
+    pub async fn set_deadline_at(
+        &self,
+        id: Uuid,
+        deadline_at: Option<chrono::NaiveDateTime>,
+    ) -> Result<(), DownloadError> {
+        sqlx::query("UPDATE downloads SET deadline_at = ?1 WHERE id = ?2")
+            .bind(deadline_at.map(|t| t.to_string()))
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                DownloadError::Unknown(format!(
+                    "Failed to update deadline: {}",
+                    e
+                ))
+            })?;
+
+        Ok(())
+    }
+
+    /// Set or clear when a download scheduled for tonight starts
+    pub async fn set_scheduled_for(
+        &self,
+        id: Uuid,
+        scheduled_for: Option<chrono::NaiveDateTime>,
+    ) -> Result<(), DownloadError> {
+        sqlx::query("UPDATE downloads SET scheduled_for = ?1 WHERE id = ?2")
+            .bind(scheduled_for.map(|t| t.to_string()))
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                DownloadError::Unknown(format!(
+                    "Failed to update schedule: {}",
+                    e
+                ))
+            })?;
+
+        Ok(())
+    }
+
+    /// Store the checksum computed for a completed download's file
+    pub async fn set_actual_checksum(
+        &self,
+        id: Uuid,
+        checksum: &str,
+        algorithm: &crate::core::checksum::ChecksumAlgorithm,
+    ) -> Result<(), DownloadError> {
+        sqlx::query("UPDATE downloads SET actual_checksum = ?1, checksum_algorithm = ?2 WHERE id = ?3")
+            .bind(checksum)
+            .bind(format!("{:?}", algorithm))
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                DownloadError::Unknown(format!(
+                    "Failed to update checksum: {}",
+                    e
+                ))
+            })?;
+
+        Ok(())
+    }
+
+    /// Point a download at another file, e.g. an identical copy kept in
+    /// its place
+    pub async fn set_save_path(
+        &self,
+        id: Uuid,
+        save_path: &std::path::Path,
+    ) -> Result<(), DownloadError> {
+        sqlx::query("UPDATE downloads SET save_path = ?1 WHERE id = ?2")
+            .bind(save_path.to_string_lossy().to_string())
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                DownloadError::Unknown(format!(
+                    "Failed to update save path: {}",
+                    e
+                ))
+            })?;
+
+        Ok(())
+    }
+
+    /// Record how a download's post-action went
+    pub async fn set_post_action_status(
+        &self,
+        id: Uuid,
+        status: &PostActionStatus,
+    ) -> Result<(), DownloadError> {
+        sqlx::query("UPDATE downloads SET post_action_status = ?1 WHERE id = ?2")
+            .bind(serde_json::to_string(status).ok())
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                DownloadError::Unknown(format!(
+                    "Failed to update post-action status: {}",
+                    e
+                ))
+            })?;
+
+        Ok(())
+    }
+
     /// Delete a download record
     pub async fn delete_download(
         &self,
@@ -317,6 +893,75 @@ impl Database {
         Ok(())
     }
 
+    /// Update the user-editable descriptive fields of a download.
+    /// Kept separate from `update_download` so a running task's
+    /// snapshot never overwrites edits made while it downloads.
+    pub async fn update_download_metadata(
+        &self,
+        task: &DownloadTask,
+    ) -> Result<(), DownloadError> {
+        sqlx::query(
+            "UPDATE downloads SET notes = ?1, source_page_url = ?2, source_page_title = ?3 WHERE id = ?4",
+        )
+        .bind(task.notes.as_ref().map(|n| n.as_str()))
+        .bind(&task.source_page_url)
+        .bind(&task.source_page_title)
+        .bind(task.id.to_string())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            DownloadError::Unknown(format!(
+                "Failed to update download metadata: {}",
+                e
+            ))
+        })?;
+
+        Ok(())
+    }
+
+    /// Record that `id` was replaced by the newer download `newer_id`
+    pub async fn mark_superseded(
+        &self,
+        id: Uuid,
+        newer_id: Uuid,
+    ) -> Result<(), DownloadError> {
+        sqlx::query("UPDATE downloads SET superseded_by = ?1 WHERE id = ?2")
+            .bind(newer_id.to_string())
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                DownloadError::Unknown(format!(
+                    "Failed to mark download superseded: {}",
+                    e
+                ))
+            })?;
+
+        Ok(())
+    }
+
+    /// Downloads already added for `url`, or for the URL it resolved to
+    pub async fn find_by_url(
+        &self,
+        url: &str,
+        resolved_url: Option<&str>,
+    ) -> Result<Vec<Uuid>, DownloadError> {
+        let resolved_url = resolved_url.unwrap_or(url);
+        let rows: Vec<(String,)> = sqlx::query_as(
+            "SELECT id FROM downloads WHERE url IN (?1, ?2) OR final_url IN (?1, ?2)"
+        )
+        .bind(url)
+        .bind(resolved_url)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| DownloadError::Unknown(format!("Failed to look up downloads by URL: {}", e)))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(id,)| Uuid::parse_str(&id).ok())
+            .collect())
+    }
+
     /// Convert database row to DownloadTask
     pub fn row_to_task(row: DownloadRow) -> DownloadTask {
         let status = match row.status.as_str() {
@@ -326,6 +971,9 @@ impl Database {
             "Paused" => DownloadStatus::Paused,
             "Merging" => DownloadStatus::Merging,
             "Verifying" => DownloadStatus::Verifying,
+            "WaitingForCondition" => DownloadStatus::WaitingForCondition,
+            "NeedsConfirmation" => DownloadStatus::NeedsConfirmation,
+            "CompletedPendingReplace" => DownloadStatus::CompletedPendingReplace,
             "Completed" => DownloadStatus::Completed,
             "Failed" => DownloadStatus::Failed,
             "Cancelled" => DownloadStatus::Cancelled,
@@ -365,11 +1013,52 @@ impl Database {
                 )
                 .ok()
             }),
-            priority: row.priority as u32,
+            priority: DownloadPriority::from_value(row.priority.max(0) as u32),
             category: row.category,
             segment_progress: row.segment_progress
                 .and_then(|s| serde_json::from_str(&s).ok())
                 .unwrap_or_default(),
+            superseded_by: row.superseded_by
+                .and_then(|s| Uuid::parse_str(&s).ok()),
+            fallback_of: row.fallback_of
+                .and_then(|s| Uuid::parse_str(&s).ok()),
+            playlist_id: row.playlist_id
+                .and_then(|s| Uuid::parse_str(&s).ok()),
+            temp_dir: row.temp_dir.map(PathBuf::from),
+            notes: row.notes.map(PrivateText),
+            source_page_url: row.source_page_url,
+            source_page_title: row.source_page_title,
+            integrity_status: row.integrity_status
+                .and_then(|s| s.parse().ok()),
+            retry_policy: row.retry_policy
+                .and_then(|s| serde_json::from_str(&s).ok()),
+            checksum_required: row.checksum_required,
+            redirect_chain: row.redirect_chain
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default(),
+            capability_notes: row.capability_notes,
+            url_refresh: row.url_refresh
+                .and_then(|s| serde_json::from_str(&s).ok()),
+            post_action: row.post_action
+                .and_then(|s| serde_json::from_str(&s).ok()),
+            post_action_status: row.post_action_status
+                .and_then(|s| serde_json::from_str(&s).ok()),
+            scheduled_for: row.scheduled_for.and_then(|s| {
+                chrono::NaiveDateTime::parse_from_str(
+                    &s,
+                    "%Y-%m-%d %H:%M:%S%.f",
+                )
+                .ok()
+            }),
+            deadline_at: row.deadline_at.and_then(|s| {
+                chrono::NaiveDateTime::parse_from_str(
+                    &s,
+                    "%Y-%m-%d %H:%M:%S%.f",
+                )
+                .ok()
+            }),
+            unknown_origin: row.unknown_origin,
+            wasted_bytes: row.wasted_bytes.max(0) as u64,
         }
     }
 
@@ -435,53 +1124,38 @@ impl Database {
 
     /// Get all categories
     pub async fn get_all_categories(&self) -> Result<Vec<crate::core::category::Category>, DownloadError> {
-        let rows: Vec<(String, String, Option<String>, Option<String>, Option<String>, i64, i64)> = sqlx::query_as(
-            "SELECT id, name, color, icon, save_path, created_at, updated_at FROM categories ORDER BY name"
+        let rows: Vec<CategoryRow> = sqlx::query_as(
+            "SELECT id, name, color, icon, save_path, created_at, updated_at, file_mode, file_group, default_segments, default_max_retries, require_checksum FROM categories ORDER BY name"
         )
         .fetch_all(&self.pool)
         .await
         .map_err(|e| DownloadError::Unknown(format!("Failed to get categories: {}", e)))?;
 
-        Ok(rows.into_iter().map(|(id, name, color, icon, save_path, created_at, updated_at)| {
-            crate::core::category::Category {
-                id,
-                name,
-                color,
-                icon,
-                save_path: save_path.map(PathBuf::from),
-                created_at,
-                updated_at,
-            }
-        }).collect())
+        Ok(rows.into_iter().map(row_to_category).collect())
     }
 
     /// Get a single category by ID
     pub async fn get_category(&self, category_id: &str) -> Result<crate::core::category::Category, DownloadError> {
-        let row: (String, String, Option<String>, Option<String>, Option<String>, i64, i64) = sqlx::query_as(
-            "SELECT id, name, color, icon, save_path, created_at, updated_at FROM categories WHERE id = ?1"
+        let row: CategoryRow = sqlx::query_as(
+            "SELECT id, name, color, icon, save_path, created_at, updated_at, file_mode, file_group, default_segments, default_max_retries, require_checksum FROM categories WHERE id = ?1"
         )
         .bind(category_id)
         .fetch_one(&self.pool)
         .await
         .map_err(|e| DownloadError::NotFound(format!("Category not found: {}", e)))?;
 
-        Ok(crate::core::category::Category {
-            id: row.0,
-            name: row.1,
-            color: row.2,
-            icon: row.3,
-            save_path: row.4.map(PathBuf::from),
-            created_at: row.5,
-            updated_at: row.6,
-        })
+        Ok(row_to_category(row))
     }
 
     /// Create a new category
     pub async fn create_category(&self, category: &crate::core::category::Category) -> Result<(), DownloadError> {
         sqlx::query(
             r#"
-            INSERT INTO categories (id, name, color, icon, save_path, created_at, updated_at)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            INSERT INTO categories (
+                id, name, color, icon, save_path, created_at, updated_at, file_mode, file_group,
+                default_segments, default_max_retries, require_checksum
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
             "#
         )
         .bind(&category.id)
@@ -491,6 +1165,11 @@ impl Database {
         .bind(category.save_path.as_ref().map(|p| p.to_string_lossy().to_string()))
         .bind(category.created_at)
         .bind(category.updated_at)
+        .bind(&category.file_permissions.mode)
+        .bind(&category.file_permissions.group)
+        .bind(category.default_segments.map(|s| s as i64))
+        .bind(category.default_max_retries.map(|r| r as i64))
+        .bind(category.require_checksum)
         .execute(&self.pool)
         .await
         .map_err(|e| DownloadError::Unknown(format!("Failed to create category: {}", e)))?;
@@ -507,7 +1186,12 @@ impl Database {
                 color = ?2, 
                 icon = ?3, 
                 save_path = ?4, 
-                updated_at = ?5
+                updated_at = ?5,
+                file_mode = ?7,
+                file_group = ?8,
+                default_segments = ?9,
+                default_max_retries = ?10,
+                require_checksum = ?11
             WHERE id = ?6
             "#
         )
@@ -517,6 +1201,11 @@ impl Database {
         .bind(category.save_path.as_ref().map(|p| p.to_string_lossy().to_string()))
         .bind(category.updated_at)
         .bind(&category.id)
+        .bind(&category.file_permissions.mode)
+        .bind(&category.file_permissions.group)
+        .bind(category.default_segments.map(|s| s as i64))
+        .bind(category.default_max_retries.map(|r| r as i64))
+        .bind(category.require_checksum)
         .execute(&self.pool)
         .await
         .map_err(|e| DownloadError::Unknown(format!("Failed to update category: {}", e)))?;
@@ -524,10 +1213,21 @@ impl Database {
         Ok(())
     }
 
+    /// Ids of the downloads in a category
+    pub async fn download_ids_in_category(&self, category_id: &str) -> Result<Vec<String>, DownloadError> {
+        let rows: Vec<(String,)> = sqlx::query_as("SELECT id FROM downloads WHERE category_id = ?1")
+            .bind(category_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DownloadError::Unknown(format!("Failed to list category downloads: {}", e)))?;
+
+        Ok(rows.into_iter().map(|(id,)| id).collect())
+    }
+
     /// Delete a category
     pub async fn delete_category(&self, category_id: &str) -> Result<(), DownloadError> {
         // First, reassign downloads to default category
-        sqlx::query("UPDATE downloads SET category_id = 'default' WHERE category_id = ?1")
+        sqlx::query("UPDATE downloads SET category_id = 'default', category = 'default' WHERE category_id = ?1")
             .bind(category_id)
             .execute(&self.pool)
             .await
@@ -572,7 +1272,7 @@ impl Database {
 
     /// Assign a download to a category
     pub async fn assign_download_category(&self, download_id: &str, category_id: &str) -> Result<(), DownloadError> {
-        sqlx::query("UPDATE downloads SET category_id = ?1 WHERE id = ?2")
+        sqlx::query("UPDATE downloads SET category_id = ?1, category = ?1 WHERE id = ?2")
             .bind(category_id)
             .bind(download_id)
             .execute(&self.pool)
@@ -582,12 +1282,168 @@ impl Database {
         Ok(())
     }
 
+    // ========== Queue Operations ==========
+
+    /// Replace the persisted queue with the given active and queued IDs.
+    /// Active downloads are stored first so they resume ahead of the
+    /// waiting queue after a restart.
+    pub async fn save_queue(&self, active: &[Uuid], queued: &[Uuid]) -> Result<(), DownloadError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DownloadError::Unknown(format!("Failed to begin queue transaction: {}", e)))?;
+
+        sqlx::query("DELETE FROM download_queue")
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| DownloadError::Unknown(format!("Failed to clear queue: {}", e)))?;
+
+        let entries = active.iter().map(|id| (id, "active"))
+            .chain(queued.iter().map(|id| (id, "queued")));
+
+        for (position, (id, state)) in entries.enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO download_queue (download_id, position, state, updated_at)
+                VALUES (?1, ?2, ?3, datetime('now'))
+                "#
+            )
+            .bind(id.to_string())
+            .bind(position as i64)
+            .bind(state)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| DownloadError::Unknown(format!("Failed to save queue entry: {}", e)))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| DownloadError::Unknown(format!("Failed to commit queue: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Load the persisted queue in saved order.
+    /// Entries whose download no longer exists are skipped.
+    pub async fn load_queue(&self) -> Result<Vec<Uuid>, DownloadError> {
+        let rows: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT q.download_id FROM download_queue q
+            INNER JOIN downloads d ON d.id = q.download_id
+            ORDER BY q.position ASC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| DownloadError::Unknown(format!("Failed to load queue: {}", e)))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(id,)| Uuid::parse_str(&id).ok())
+            .collect())
+    }
+
+    // ========== Data Usage ==========
+
+    /// Add transferred bytes to a day's totals
+    pub async fn add_daily_usage(
+        &self,
+        day: chrono::NaiveDate,
+        downloaded: u64,
+        uploaded: u64,
+    ) -> Result<(), DownloadError> {
+        sqlx::query(
+            r#"
+            INSERT INTO daily_usage (day, downloaded_bytes, uploaded_bytes)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT(day) DO UPDATE SET
+                downloaded_bytes = downloaded_bytes + excluded.downloaded_bytes,
+                uploaded_bytes = uploaded_bytes + excluded.uploaded_bytes
+            "#
+        )
+        .bind(day.to_string())
+        .bind(downloaded as i64)
+        .bind(uploaded as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DownloadError::Unknown(format!("Failed to record usage: {}", e)))?;
+        Ok(())
+    }
+
+    /// Downloaded and uploaded bytes from `start` up to, not including, `end`
+    pub async fn get_usage_between(
+        &self,
+        start: chrono::NaiveDate,
+        end: chrono::NaiveDate,
+    ) -> Result<(u64, u64), DownloadError> {
+        let (downloaded, uploaded): (i64, i64) = sqlx::query_as(
+            r#"
+            SELECT COALESCE(SUM(downloaded_bytes), 0), COALESCE(SUM(uploaded_bytes), 0)
+            FROM daily_usage WHERE day >= ?1 AND day < ?2
+            "#
+        )
+        .bind(start.to_string())
+        .bind(end.to_string())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| DownloadError::Unknown(format!("Failed to read usage: {}", e)))?;
+        Ok((downloaded.max(0) as u64, uploaded.max(0) as u64))
+    }
+
+    // ========== Maintenance ==========
+
+    /// Return free pages to the filesystem. A no-op on databases created
+    /// before incremental auto-vacuum was enabled.
+    pub async fn incremental_vacuum(&self) -> Result<(), DownloadError> {
+        sqlx::query("PRAGMA incremental_vacuum")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DownloadError::Unknown(format!("Incremental vacuum failed: {}", e)))?;
+        Ok(())
+    }
+
+    /// Size of the database file in bytes, from its page count
+    pub async fn size_bytes(&self) -> Result<u64, DownloadError> {
+        let (size,): (i64,) = sqlx::query_as(
+            "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()"
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| DownloadError::Unknown(format!("Failed to read database size: {}", e)))?;
+        Ok(size.max(0) as u64)
+    }
+
     /// Get the underlying pool for torrent queries
     pub fn pool(&self) -> &SqlitePool {
         &self.pool
     }
 }
 
+/// id, name, color, icon, save_path, created_at, updated_at, file_mode, file_group
+type CategoryRow = (
+    String, String, Option<String>, Option<String>, Option<String>, i64, i64,
+    Option<String>, Option<String>, Option<i64>, Option<i64>, bool,
+);
+
+fn row_to_category(row: CategoryRow) -> crate::core::category::Category {
+    let (
+        id, name, color, icon, save_path, created_at, updated_at, file_mode, file_group,
+        default_segments, default_max_retries, require_checksum,
+    ) = row;
+    crate::core::category::Category {
+        id,
+        name,
+        color,
+        icon,
+        save_path: save_path.map(PathBuf::from),
+        created_at,
+        updated_at,
+        file_permissions: FilePermissions::new(file_mode, file_group),
+        default_segments: default_segments.and_then(|s| u8::try_from(s).ok()),
+        default_max_retries: default_max_retries.and_then(|r| u32::try_from(r).ok()),
+        require_checksum,
+    }
+}
+
 // Implement sqlx::FromRow for DownloadRow
 impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow>
     for DownloadRow
@@ -621,6 +1477,25 @@ impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow>
             priority: row.try_get("priority")?,
             category: row.try_get("category")?,
             segment_progress: row.try_get("segment_progress")?,
+            superseded_by: row.try_get("superseded_by")?,
+            fallback_of: row.try_get("fallback_of")?,
+            playlist_id: row.try_get("playlist_id")?,
+            temp_dir: row.try_get("temp_dir")?,
+            notes: row.try_get("notes")?,
+            source_page_url: row.try_get("source_page_url")?,
+            source_page_title: row.try_get("source_page_title")?,
+            integrity_status: row.try_get("integrity_status")?,
+            retry_policy: row.try_get("retry_policy")?,
+            checksum_required: row.try_get("checksum_required")?,
+            redirect_chain: row.try_get("redirect_chain")?,
+            capability_notes: row.try_get("capability_notes")?,
+            url_refresh: row.try_get("url_refresh")?,
+            post_action: row.try_get("post_action")?,
+            post_action_status: row.try_get("post_action_status")?,
+            scheduled_for: row.try_get("scheduled_for")?,
+            deadline_at: row.try_get("deadline_at")?,
+            unknown_origin: row.try_get("unknown_origin")?,
+            wasted_bytes: row.try_get("wasted_bytes")?,
         })
     }
 }
@@ -0,0 +1,11 @@
+-- Migration to persist download queue membership and ordering
+
+CREATE TABLE IF NOT EXISTS download_queue (
+    download_id TEXT PRIMARY KEY,
+    position INTEGER NOT NULL,
+    state TEXT NOT NULL DEFAULT 'queued',
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_download_queue_position
+    ON download_queue(position);
@@ -0,0 +1,7 @@
+-- Migration to record bytes transferred per calendar day, for data caps
+
+CREATE TABLE IF NOT EXISTS daily_usage (
+    day TEXT PRIMARY KEY,
+    downloaded_bytes INTEGER NOT NULL DEFAULT 0,
+    uploaded_bytes INTEGER NOT NULL DEFAULT 0
+);
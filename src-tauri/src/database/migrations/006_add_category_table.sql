@@ -0,0 +1,24 @@
+-- Migration to create the categories table on every startup. Unlike 002 it
+-- leaves downloads alone; Database::run_category_migrations adds
+-- downloads.category_id when it is missing.
+
+CREATE TABLE IF NOT EXISTS categories (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    color TEXT,
+    icon TEXT,
+    save_path TEXT,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+-- Insert default categories
+INSERT OR IGNORE INTO categories (id, name, color, icon, save_path, created_at, updated_at)
+VALUES 
+    ('default', 'Default', '#6B7280', 'folder', NULL, strftime('%s', 'now'), strftime('%s', 'now')),
+    ('documents', 'Documents', '#3B82F6', 'file-text', NULL, strftime('%s', 'now'), strftime('%s', 'now')),
+    ('videos', 'Videos', '#EF4444', 'video', NULL, strftime('%s', 'now'), strftime('%s', 'now')),
+    ('music', 'Music', '#8B5CF6', 'music', NULL, strftime('%s', 'now'), strftime('%s', 'now')),
+    ('images', 'Images', '#10B981', 'image', NULL, strftime('%s', 'now'), strftime('%s', 'now')),
+    ('software', 'Software', '#F59E0B', 'package', NULL, strftime('%s', 'now'), strftime('%s', 'now')),
+    ('compressed', 'Archives', '#6366F1', 'archive', NULL, strftime('%s', 'now'), strftime('%s', 'now'));
@@ -1,4 +1,5 @@
 pub mod db;
 pub mod models;
 pub mod queries;
-pub mod torrent_queries;
\ No newline at end of file
+pub mod torrent_queries;
+pub mod writer_lock;
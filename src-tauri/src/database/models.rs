@@ -25,6 +25,25 @@ pub struct DownloadRow {
     pub priority: i32,
     pub category: Option<String>,
     pub segment_progress: Option<String>,
+    pub superseded_by: Option<String>,
+    pub fallback_of: Option<String>,
+    pub playlist_id: Option<String>,
+    pub temp_dir: Option<String>,
+    pub notes: Option<String>,
+    pub source_page_url: Option<String>,
+    pub source_page_title: Option<String>,
+    pub integrity_status: Option<String>,
+    pub retry_policy: Option<String>,
+    pub checksum_required: bool,
+    pub redirect_chain: Option<String>,
+    pub capability_notes: Option<String>,
+    pub url_refresh: Option<String>,
+    pub post_action: Option<String>,
+    pub post_action_status: Option<String>,
+    pub scheduled_for: Option<String>,
+    pub deadline_at: Option<String>,
+    pub unknown_origin: bool,
+    pub wasted_bytes: i64,
 }
 
 /// Database row for a torrent
@@ -49,6 +68,15 @@ pub struct TorrentRow {
     pub seeders: i32,
     pub progress: f64,
     pub eta: Option<i64>,
+    #[sqlx(default)]
+    pub auto_paused: bool,
+    #[sqlx(default)]
+    pub wasted_bytes: i64,
+    #[sqlx(default)]
+    pub super_seeding: bool,
+    /// JSON list of the file indexes to download
+    #[sqlx(default)]
+    pub only_files: Option<String>,
 }
 
 /// Database row for a torrent file
@@ -1,5 +1,6 @@
 // src-tauri/src/database/queries.rs
 
+use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
@@ -97,7 +98,7 @@ impl DownloadQuery {
 
         // Search filter
         if self.search_term.is_some() {
-            where_clauses.push(" AND (file_name LIKE ? OR url LIKE ?)".to_string());
+            where_clauses.push(" AND (file_name LIKE ? OR url LIKE ? OR notes LIKE ?)".to_string());
         }
 
         // Append WHERE clauses
@@ -146,6 +147,7 @@ impl DownloadQuery {
         if let Some(ref term) = self.search_term {
             let search_pattern = format!("%{}%", term);
             query = query.bind(search_pattern.clone());
+            query = query.bind(search_pattern.clone());
             query = query.bind(search_pattern);
         }
 
@@ -166,8 +168,104 @@ impl Default for DownloadQuery {
     }
 }
 
+/// Dimension used to bucket download statistics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsGroupBy {
+    Category,
+    Domain,
+    Month,
+    Status,
+}
+
+impl StatsGroupBy {
+    /// SQL expression producing the bucket key
+    fn key_expr(&self) -> &'static str {
+        match self {
+            StatsGroupBy::Category => "COALESCE(NULLIF(category_id, ''), NULLIF(category, ''), 'default')",
+            StatsGroupBy::Domain => "COALESCE(NULLIF(domain, ''), 'unknown')",
+            StatsGroupBy::Month => "substr(created_at, 1, 7)",
+            StatsGroupBy::Status => "status",
+        }
+    }
+}
+
+/// Aggregates for one bucket of downloads
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsBucket {
+    pub key: String,
+    pub count: u64,
+    pub completed: u64,
+    pub failed: u64,
+    /// Sum of bytes downloaded in this bucket
+    pub total_bytes: u64,
+    /// Average speed (bytes/sec) of completed downloads with a known
+    /// duration, timed from when their transfer started. Rows from before
+    /// that was recorded are timed from when they were added.
+    pub average_speed: Option<f64>,
+    /// Failed downloads as a fraction (0.0 - 1.0) of all downloads
+    pub failure_rate: f64,
+}
+
 /// Helper functions for common queries
 impl Database {
+    /// Aggregate downloads grouped by `group_by`, optionally limited to
+    /// downloads created within [from, to)
+    pub async fn get_stats_breakdown(
+        &self,
+        group_by: StatsGroupBy,
+        from: Option<chrono::NaiveDateTime>,
+        to: Option<chrono::NaiveDateTime>,
+    ) -> Result<Vec<StatsBucket>, DownloadError> {
+        let query_str = format!(
+            r#"
+            SELECT
+                {key} AS bucket,
+                COUNT(*) AS count,
+                SUM(CASE WHEN status = 'Completed' THEN 1 ELSE 0 END) AS completed,
+                SUM(CASE WHEN status = 'Failed' THEN 1 ELSE 0 END) AS failed,
+                COALESCE(SUM(downloaded_size), 0) AS total_bytes,
+                AVG(CASE
+                    WHEN status = 'Completed'
+                        AND completed_at IS NOT NULL
+                        AND julianday(completed_at) > julianday(COALESCE(started_at, created_at))
+                    THEN downloaded_size
+                        / ((julianday(completed_at) - julianday(COALESCE(started_at, created_at))) * 86400.0)
+                END) AS average_speed
+            FROM downloads
+            WHERE (?1 IS NULL OR created_at >= ?1)
+              AND (?2 IS NULL OR created_at < ?2)
+            GROUP BY bucket
+            ORDER BY bucket
+            "#,
+            key = group_by.key_expr()
+        );
+
+        let rows: Vec<(String, i64, i64, i64, i64, Option<f64>)> = sqlx::query_as(&query_str)
+            .bind(from.map(|d| d.to_string()))
+            .bind(to.map(|d| d.to_string()))
+            .fetch_all(self.pool())
+            .await
+            .map_err(|e| DownloadError::Unknown(format!("Stats query failed: {}", e)))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(key, count, completed, failed, total_bytes, average_speed)| StatsBucket {
+                key,
+                count: count as u64,
+                completed: completed as u64,
+                failed: failed as u64,
+                total_bytes: total_bytes.max(0) as u64,
+                average_speed,
+                failure_rate: if count > 0 {
+                    failed as f64 / count as f64
+                } else {
+                    0.0
+                },
+            })
+            .collect())
+    }
+
     /// Get downloads by status
     pub async fn get_downloads_by_status(
         &self,
@@ -227,6 +325,9 @@ impl Database {
             .filter(|t| {
                 t.file_name.to_lowercase().contains(&query_lower)
                     || t.url.to_lowercase().contains(&query_lower)
+                    || t.notes.as_ref()
+                        .map(|n| n.as_str().to_lowercase().contains(&query_lower))
+                        .unwrap_or(false)
             })
             .collect())
     }
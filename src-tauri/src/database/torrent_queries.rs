@@ -4,8 +4,8 @@
 use sqlx::{SqlitePool, Row};
 use crate::database::models::{TorrentRow, TorrentFileRow, TorrentBandwidthRow, TorrentScheduleRow};
 use crate::utils::error::AppError;
-use crate::network::torrent_client_librqbit::{TorrentInfo, TorrentStats, TorrentFile};
-use crate::network::torrent_helpers::{TorrentMetadata, BandwidthLimit, TorrentSchedule};
+use crate::network::torrent_client_librqbit::{TorrentInfo, TorrentStats, TorrentFile, TorrentState, TorrentHandle};
+use crate::network::torrent_helpers::{TorrentMetadata, TorrentPriority, BandwidthLimit, TorrentSchedule};
 use crate::network::torrent_advanced::{WebSeed, WebSeedType, EncryptionConfig, EncryptionMode};
 
 /// Save or update torrent metadata in database
@@ -14,8 +14,9 @@ pub async fn save_torrent(
     info: &TorrentInfo,
     stats: &TorrentStats,
     metadata: &TorrentMetadata,
+    state: &TorrentState,
 ) -> Result<(), AppError> {
-    let state = "Downloading"; // Convert TorrentState to string
+    let state = state.as_str();
 
     sqlx::query(
         r#"
@@ -23,11 +24,13 @@ pub async fn save_torrent(
             info_hash, name, total_size, piece_length, num_pieces, save_path,
             priority, category, added_time, completed_time, state,
             downloaded_size, uploaded_size, download_rate, upload_rate,
-            peers, seeders, progress, eta
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            peers, seeders, progress, eta, auto_paused, wasted_bytes,
+            super_seeding, only_files
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         ON CONFLICT(info_hash) DO UPDATE SET
             name = excluded.name,
             total_size = excluded.total_size,
+            save_path = excluded.save_path,
             priority = excluded.priority,
             category = excluded.category,
             completed_time = excluded.completed_time,
@@ -39,7 +42,11 @@ pub async fn save_torrent(
             peers = excluded.peers,
             seeders = excluded.seeders,
             progress = excluded.progress,
-            eta = excluded.eta
+            eta = excluded.eta,
+            auto_paused = excluded.auto_paused,
+            wasted_bytes = excluded.wasted_bytes,
+            super_seeding = excluded.super_seeding,
+            only_files = excluded.only_files
         "#,
     )
     .bind(&info.info_hash)
@@ -61,6 +68,10 @@ pub async fn save_torrent(
     .bind(stats.seeders as i32)
     .bind(stats.progress)
     .bind(stats.eta.map(|e| e as i64))
+    .bind(metadata.auto_paused)
+    .bind(stats.wasted_bytes as i64)
+    .bind(metadata.super_seeding)
+    .bind(metadata.only_files.as_ref().and_then(|files| serde_json::to_string(files).ok()))
     .execute(pool)
     .await
     .map_err(|e| AppError::DatabaseError(format!("Failed to save torrent: {}", e)))?;
@@ -80,6 +91,50 @@ pub async fn save_torrent(
     Ok(())
 }
 
+/// Save the .torrent file and trackers a torrent was added with, so its
+/// metadata can be exported after a restart
+pub async fn save_torrent_source(
+    pool: &SqlitePool,
+    info_hash: &str,
+    metainfo: Option<&[u8]>,
+    trackers: &[String],
+) -> Result<(), AppError> {
+    let trackers = serde_json::to_string(trackers)
+        .map_err(|e| AppError::DatabaseError(format!("Failed to encode trackers: {}", e)))?;
+
+    sqlx::query("UPDATE torrents SET metainfo = ?, trackers = ? WHERE info_hash = ?")
+        .bind(metainfo)
+        .bind(trackers)
+        .bind(info_hash)
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to save torrent source: {}", e)))?;
+
+    Ok(())
+}
+
+/// Load the .torrent file and trackers saved for a torrent
+pub async fn load_torrent_source(
+    pool: &SqlitePool,
+    info_hash: &str,
+) -> Result<(Option<Vec<u8>>, Vec<String>), AppError> {
+    let row = sqlx::query("SELECT metainfo, trackers FROM torrents WHERE info_hash = ?")
+        .bind(info_hash)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to load torrent source: {}", e)))?;
+
+    Ok(match row {
+        Some(row) => {
+            let trackers = row.get::<Option<String>, _>("trackers")
+                .and_then(|t| serde_json::from_str(&t).ok())
+                .unwrap_or_default();
+            (row.get("metainfo"), trackers)
+        }
+        None => (None, Vec::new()),
+    })
+}
+
 /// Save web seeds for a torrent
 pub async fn save_web_seeds(
     pool: &SqlitePool,
@@ -411,7 +466,7 @@ pub async fn load_torrent(
 
     // Load files
     let files = sqlx::query_as::<_, TorrentFileRow>(
-        "SELECT id, info_hash, path, size FROM torrent_files WHERE info_hash = ?"
+        "SELECT id, info_hash, path, size FROM torrent_files WHERE info_hash = ? ORDER BY id"
     )
     .bind(info_hash)
     .fetch_all(pool)
@@ -492,6 +547,136 @@ pub async fn delete_torrent(
     Ok(())
 }
 
+/// Update the persisted state of a torrent (e.g. Paused/Downloading)
+pub async fn update_torrent_state(
+    pool: &SqlitePool,
+    info_hash: &str,
+    state: &TorrentState,
+) -> Result<(), AppError> {
+    sqlx::query("UPDATE torrents SET state = ? WHERE info_hash = ?")
+        .bind(state.as_str())
+        .bind(info_hash)
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to update torrent state: {}", e)))?;
+
+    Ok(())
+}
+
+/// Persist whether a torrent was paused by its schedule
+pub async fn update_torrent_auto_paused(
+    pool: &SqlitePool,
+    info_hash: &str,
+    auto_paused: bool,
+) -> Result<(), AppError> {
+    sqlx::query("UPDATE torrents SET auto_paused = ? WHERE info_hash = ?")
+        .bind(auto_paused)
+        .bind(info_hash)
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to update torrent auto-pause: {}", e)))?;
+
+    Ok(())
+}
+
+/// Persist whether a torrent super-seeds
+pub async fn update_torrent_super_seeding(
+    pool: &SqlitePool,
+    info_hash: &str,
+    super_seeding: bool,
+) -> Result<(), AppError> {
+    sqlx::query("UPDATE torrents SET super_seeding = ? WHERE info_hash = ?")
+        .bind(super_seeding)
+        .bind(info_hash)
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to update torrent super-seeding: {}", e)))?;
+
+    Ok(())
+}
+
+/// Load every persisted torrent as a client handle plus metadata,
+/// ready to be re-registered with the torrent client on startup
+pub async fn load_torrent_handles(
+    pool: &SqlitePool,
+) -> Result<Vec<(TorrentHandle, TorrentMetadata)>, AppError> {
+    let mut handles = Vec::new();
+
+    for info_hash in load_all_torrents(pool).await? {
+        let (row, files, tags, bandwidth, schedule) = match load_torrent(pool, &info_hash).await? {
+            Some(loaded) => loaded,
+            None => continue,
+        };
+
+        let info = TorrentInfo {
+            info_hash: row.info_hash.clone(),
+            name: row.name.clone(),
+            total_size: row.total_size as u64,
+            piece_length: row.piece_length as u64,
+            num_pieces: row.num_pieces as u64,
+            files: files.into_iter().map(|f| TorrentFile {
+                path: f.path.into(),
+                size: f.size as u64,
+            }).collect(),
+        };
+
+        let stats = TorrentStats {
+            downloaded: row.downloaded_size as u64,
+            uploaded: row.uploaded_size as u64,
+            download_rate: 0,
+            upload_rate: 0,
+            peers: 0,
+            seeders: 0,
+            progress: row.progress,
+            eta: None,
+            wasted_bytes: row.wasted_bytes as u64,
+        };
+
+        let mut metadata = TorrentMetadata::new(row.info_hash.clone(), row.save_path.clone().into());
+        metadata.priority = TorrentPriority::from_i32(row.priority);
+        metadata.category = row.category.clone();
+        metadata.auto_paused = row.auto_paused;
+        metadata.super_seeding = row.super_seeding;
+        metadata.only_files = row.only_files.as_deref().and_then(|files| serde_json::from_str(files).ok());
+        metadata.tags = tags;
+        metadata.bandwidth_limit = BandwidthLimit {
+            download_limit: bandwidth.download_limit.map(|l| l as u64),
+            upload_limit: bandwidth.upload_limit.map(|l| l as u64),
+            enabled: bandwidth.enabled,
+        };
+        metadata.schedule = TorrentSchedule {
+            start_time: schedule.start_time,
+            end_time: schedule.end_time,
+            days_of_week: schedule.days_of_week
+                .and_then(|d| serde_json::from_str(&d).ok())
+                .unwrap_or_default(),
+            enabled: schedule.enabled,
+        };
+        if let Ok(added) = chrono::DateTime::parse_from_rfc3339(&row.added_time) {
+            metadata.added_time = added.with_timezone(&chrono::Utc);
+        }
+        metadata.completed_time = row.completed_time
+            .as_deref()
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&chrono::Utc));
+
+        let (metainfo, trackers) = load_torrent_source(pool, &info_hash).await?;
+        let Ok(state) = row.state.parse::<TorrentState>();
+
+        let handle = TorrentHandle {
+            info,
+            state,
+            stats,
+            metainfo: metainfo.map(Into::into),
+            trackers,
+        };
+
+        handles.push((handle, metadata));
+    }
+
+    Ok(handles)
+}
+
 /// Update torrent statistics
 pub async fn update_torrent_stats(
     pool: &SqlitePool,
@@ -508,7 +693,8 @@ pub async fn update_torrent_stats(
             peers = ?,
             seeders = ?,
             progress = ?,
-            eta = ?
+            eta = ?,
+            wasted_bytes = ?
         WHERE info_hash = ?
         "#,
     )
@@ -520,6 +706,7 @@ pub async fn update_torrent_stats(
     .bind(stats.seeders as i32)
     .bind(stats.progress)
     .bind(stats.eta.map(|e| e as i64))
+    .bind(stats.wasted_bytes as i64)
     .bind(info_hash)
     .execute(pool)
     .await
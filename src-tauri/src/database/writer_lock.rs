@@ -0,0 +1,253 @@
+// src-tauri/src/database/writer_lock.rs
+// Only one process writes to downloads.db. The owner holds an OS lock on a
+// lock file naming its pid and the local port other entry points reach it
+// on; they send their requests there instead of opening the database
+// themselves. The OS drops the lock when its holder exits, however it exits.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::utils::error::DownloadError;
+
+pub const LOCK_FILE_NAME: &str = "downloads.db.lock";
+
+/// What the lock file says about the process that owns the database
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockOwner {
+    pub pid: u32,
+    /// Shared secret for the owner's IPC endpoint
+    pub token: String,
+    /// Local port the owner accepts requests on, once it is listening
+    #[serde(default)]
+    pub port: Option<u16>,
+}
+
+/// Held by the process that writes to the database; released on drop
+#[derive(Debug)]
+pub struct WriterLock {
+    /// Kept open for as long as the lock is held
+    file: fs::File,
+    owner: parking_lot::Mutex<LockOwner>,
+    /// Pid of a crashed owner whose lock was taken over
+    took_over_from: Option<u32>,
+}
+
+impl WriterLock {
+    /// Take the lock in `app_data_dir`. A lock file left by a process that
+    /// exited without clearing it is taken over; one whose lock is still
+    /// held is an error naming its owner.
+    pub fn acquire(app_data_dir: &Path) -> Result<Self, DownloadError> {
+        let path = app_data_dir.join(LOCK_FILE_NAME);
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .map_err(|e| DownloadError::FileError(format!("Cannot open {}: {}", path.display(), e)))?;
+
+        let locked = try_lock(&file)
+            .map_err(|e| DownloadError::FileError(format!("Cannot lock {}: {}", path.display(), e)))?;
+        if !locked {
+            let holder = match read_lock(&path) {
+                Some(existing) => format!("another AFK-Dunld process (pid {})", existing.pid),
+                None => "another AFK-Dunld process".to_string(),
+            };
+            return Err(DownloadError::DatabaseLocked(format!(
+                "the downloads database is in use by {}. \
+                 Close it, or end that process if it is stuck, then try again.",
+                holder
+            )));
+        }
+
+        // A clean exit clears the file, so an owner still named in it crashed
+        let took_over_from = read_lock(&path).map(|o| o.pid);
+        if let Some(pid) = took_over_from {
+            tracing::warn!("Taking over database lock left by process {}, which is no longer running", pid);
+        }
+
+        let owner = LockOwner {
+            pid: std::process::id(),
+            token: uuid::Uuid::new_v4().simple().to_string(),
+            port: None,
+        };
+        write_owner(&file, &owner)?;
+        Ok(Self {
+            file,
+            owner: parking_lot::Mutex::new(owner),
+            took_over_from,
+        })
+    }
+
+    pub fn took_over_from(&self) -> Option<u32> {
+        self.took_over_from
+    }
+
+    pub fn token(&self) -> String {
+        self.owner.lock().token.clone()
+    }
+
+    /// Publish the port other entry points can reach this process on
+    pub fn set_port(&self, port: u16) -> Result<(), DownloadError> {
+        let mut owner = self.owner.lock();
+        owner.port = Some(port);
+        write_owner(&self.file, &owner)
+    }
+}
+
+impl Drop for WriterLock {
+    fn drop(&mut self) {
+        // Closing the file releases the lock; clearing it first tells the
+        // next owner this one exited cleanly
+        let _ = self.file.set_len(0);
+    }
+}
+
+/// The process that owns the database in `app_data_dir`, if it is running
+pub fn live_owner(app_data_dir: &Path) -> Option<LockOwner> {
+    let path = app_data_dir.join(LOCK_FILE_NAME);
+    let file = fs::File::open(&path).ok()?;
+    // Taking the lock means nobody holds it; it is let go with `file`
+    if try_lock(&file).unwrap_or(true) {
+        return None;
+    }
+    read_lock(&path)
+}
+
+fn read_lock(path: &Path) -> Option<LockOwner> {
+    let contents = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Replace the lock file's contents in place; it can't be swapped for
+/// another file without losing the lock
+fn write_owner(mut file: &fs::File, owner: &LockOwner) -> Result<(), DownloadError> {
+    let json = serde_json::to_vec(owner).map_err(|e| DownloadError::Unknown(e.to_string()))?;
+    file.set_len(0)
+        .and_then(|_| file.seek(SeekFrom::Start(0)))
+        .and_then(|_| file.write_all(&json))
+        .and_then(|_| file.sync_all())
+        .map_err(|e| DownloadError::FileError(format!("Cannot write database lock: {}", e)))?;
+    // The token guards the IPC endpoint, so only the user may read it
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = file.set_permissions(fs::Permissions::from_mode(0o600));
+    }
+    Ok(())
+}
+
+/// Take an exclusive lock on `file` without waiting. False when another
+/// open handle, in this process or another, holds it.
+#[cfg(unix)]
+fn try_lock(file: &fs::File) -> std::io::Result<bool> {
+    use std::os::unix::io::AsRawFd;
+
+    // SAFETY: the descriptor stays open for the duration of the call
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+        return Ok(true);
+    }
+    let error = std::io::Error::last_os_error();
+    if error.raw_os_error() == Some(libc::EWOULDBLOCK) {
+        Ok(false)
+    } else {
+        Err(error)
+    }
+}
+
+/// Take an exclusive lock on `file` without waiting. False when another
+/// open handle, in this process or another, holds it.
+#[cfg(windows)]
+fn try_lock(file: &fs::File) -> std::io::Result<bool> {
+    use std::os::windows::io::AsRawHandle;
+    use winapi::shared::winerror::ERROR_LOCK_VIOLATION;
+    use winapi::um::fileapi::LockFileEx;
+    use winapi::um::minwinbase::{LOCKFILE_EXCLUSIVE_LOCK, LOCKFILE_FAIL_IMMEDIATELY, OVERLAPPED};
+
+    // Locked bytes can't be read, so the lock covers a byte far past the
+    // owner's details
+    // SAFETY: the handle stays open for the duration of the call and the
+    // OVERLAPPED only sets the offset
+    unsafe {
+        let mut overlapped: OVERLAPPED = std::mem::zeroed();
+        overlapped.u.s_mut().OffsetHigh = u32::MAX;
+        let flags = LOCKFILE_EXCLUSIVE_LOCK | LOCKFILE_FAIL_IMMEDIATELY;
+        if LockFileEx(file.as_raw_handle() as _, flags, 0, 1, 0, &mut overlapped) != 0 {
+            return Ok(true);
+        }
+    }
+    let error = std::io::Error::last_os_error();
+    if error.raw_os_error() == Some(ERROR_LOCK_VIOLATION as i32) {
+        Ok(false)
+    } else {
+        Err(error)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn try_lock(_file: &fs::File) -> std::io::Result<bool> {
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("afk-dunld-lock-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_acquire_and_release() {
+        let dir = temp_dir();
+        let lock = WriterLock::acquire(&dir).unwrap();
+        assert_eq!(lock.took_over_from(), None);
+        lock.set_port(4242).unwrap();
+
+        let owner = live_owner(&dir).unwrap();
+        assert_eq!(owner.pid, std::process::id());
+        assert_eq!(owner.port, Some(4242));
+        assert_eq!(owner.token, lock.token());
+
+        drop(lock);
+        assert_eq!(live_owner(&dir), None);
+        // Released cleanly, so the next owner takes over from nobody
+        assert_eq!(WriterLock::acquire(&dir).unwrap().took_over_from(), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_held_lock_refuses() {
+        let dir = temp_dir();
+        let lock = WriterLock::acquire(&dir).unwrap();
+
+        assert!(matches!(WriterLock::acquire(&dir), Err(DownloadError::DatabaseLocked(_))));
+        assert_eq!(live_owner(&dir).map(|o| o.token), Some(lock.token()));
+        drop(lock);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_stale_lock_taken_over() {
+        // Names a pid that may well be running again; only the OS lock counts
+        let dir = temp_dir();
+        let stale = LockOwner { pid: 1, token: "t".to_string(), port: Some(1) };
+        fs::write(dir.join(LOCK_FILE_NAME), serde_json::to_vec(&stale).unwrap()).unwrap();
+        assert_eq!(live_owner(&dir), None);
+
+        let lock = WriterLock::acquire(&dir).unwrap();
+        assert_eq!(lock.took_over_from(), Some(1));
+        assert_eq!(live_owner(&dir).unwrap().pid, std::process::id());
+
+        // An unreadable lock is taken over too
+        drop(lock);
+        fs::write(dir.join(LOCK_FILE_NAME), b"{").unwrap();
+        assert_eq!(WriterLock::acquire(&dir).unwrap().took_over_from(), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
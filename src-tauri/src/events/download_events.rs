@@ -1,9 +1,43 @@
+// Events are emitted on the app handle, which delivers them to every
+// window: the main window and the mini progress widget both listen.
+
+use schemars::JsonSchema;
+use serde::Serialize;
 use tauri::{AppHandle, Emitter};
 use tracing::error;
 
 use crate::core::download_task::{
-    DownloadProgress, DownloadTask,
+    DownloadProgress, DownloadStatus, DownloadTask,
 };
+use crate::network::remote_progress::RemoteProgress;
+use crate::utils::enhanced_error::FailureSuggestion;
+use crate::utils::error::DownloadError;
+
+/// Payload of `download-failed`: the task plus what the user could do
+/// about the failure
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadFailedEvent {
+    #[serde(flatten)]
+    pub download: DownloadTask,
+    #[serde(flatten)]
+    pub suggestion: FailureSuggestion,
+}
+
+impl DownloadFailedEvent {
+    /// Classifies `error` when the caller still has it, otherwise the
+    /// task's saved error message
+    pub fn new(task: &DownloadTask, error: Option<&DownloadError>) -> Self {
+        let suggestion = match error {
+            Some(error) => FailureSuggestion::for_error(error, &task.url),
+            None => FailureSuggestion::for_message(task.error_message.as_deref().unwrap_or_default(), &task.url),
+        };
+        Self {
+            download: task.for_event(),
+            suggestion,
+        }
+    }
+}
 
 /// Emit download progress to the frontend
 pub fn emit_progress(
@@ -17,12 +51,35 @@ pub fn emit_progress(
     }
 }
 
+/// Emit the final progress of an FTP or SFTP transfer
+pub fn emit_remote_finished(
+    app_handle: &AppHandle,
+    id: uuid::Uuid,
+    last: Option<RemoteProgress>,
+    result: &Result<u64, DownloadError>,
+) {
+    let last = last.unwrap_or(RemoteProgress { transferred: 0, total: None, speed: 0.0, eta: None });
+    let mut progress = match result {
+        Ok(bytes) => RemoteProgress { transferred: *bytes, speed: 0.0, eta: None, ..last }
+            .to_download_progress(id, DownloadStatus::Completed),
+        Err(DownloadError::Cancelled) => last.to_download_progress(id, DownloadStatus::Cancelled),
+        Err(e) => {
+            let mut progress = last.to_download_progress(id, DownloadStatus::Failed);
+            progress.error_message = Some(e.to_string());
+            progress
+        }
+    };
+    progress.speed = 0.0;
+    progress.eta = None;
+    emit_progress(app_handle, &progress);
+}
+
 /// Emit download completed event
 pub fn emit_completed(
     app_handle: &AppHandle,
     task: &DownloadTask,
 ) {
-    if let Err(e) = app_handle.emit("download-complete", task)
+    if let Err(e) = app_handle.emit("download-complete", &task.for_event())
     {
         error!("Failed to emit complete: {}", e);
     }
@@ -32,8 +89,9 @@ pub fn emit_completed(
 pub fn emit_failed(
     app_handle: &AppHandle,
     task: &DownloadTask,
+    error: Option<&DownloadError>,
 ) {
-    if let Err(e) = app_handle.emit("download-failed", task) {
+    if let Err(e) = app_handle.emit("download-failed", &DownloadFailedEvent::new(task, error)) {
         error!("Failed to emit failed: {}", e);
     }
 }
@@ -43,7 +101,7 @@ pub fn emit_paused(
     app_handle: &AppHandle,
     task: &DownloadTask,
 ) {
-    if let Err(e) = app_handle.emit("download-paused", task) {
+    if let Err(e) = app_handle.emit("download-paused", &task.for_event()) {
         error!("Failed to emit paused: {}", e);
     }
 }
@@ -21,6 +21,7 @@ pub fn run() {
         )
         .with_target(true)
         .with_thread_ids(true)
+        .with_writer(utils::logging::PrivateStdout)
         .init();
 
     tracing::info!("Starting Super Downloader...");
@@ -35,13 +36,15 @@ pub fn run() {
             tauri_plugin_autostart::MacosLauncher::LaunchAgent,
             None,
         ))
-        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+        .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+            services::recent_files_service::handle_launch_args(app, &args);
             tracing::info!("Another instance tried to start, focusing existing window");
             if let Some(window) = app.get_webview_window("main") {
                 let _ = window.show();
                 let _ = window.set_focus();
             }
         }))
+        .on_window_event(services::mini_window_service::on_window_event)
         .setup(|app| {
             tracing::info!("Setting up application...");
 
@@ -60,6 +63,8 @@ pub fn run() {
             });
 
             app.manage(app_state.clone());
+            let args: Vec<String> = std::env::args().collect();
+            services::recent_files_service::handle_launch_args(app.handle(), &args);
 
             // Setup system tray
             services::tray_service::setup_tray(app)?;
@@ -70,6 +75,75 @@ pub fn run() {
                 services::clipboard_service::start_monitoring(handle).await;
             });
 
+            // Start time-based rules (torrent schedules, log maintenance, update checks)
+            app_state.cron.register(std::sync::Arc::new(
+                services::cron_service::TorrentScheduleRule::new(
+                    app.handle().clone(),
+                    app_state.torrent_client.clone(),
+                    app_state.db.clone(),
+                ),
+            ));
+            app_state.cron.register(std::sync::Arc::new(
+                services::cron_service::LogMaintenanceRule::new(
+                    app_state.logger.clone(),
+                    app_state.db.clone(),
+                ),
+            ));
+            app_state.cron.register(std::sync::Arc::new(
+                services::cron_service::UpdateCheckRule::new(
+                    app.handle().clone(),
+                    app_state.updates.clone(),
+                ),
+            ));
+            app_state.cron.register(std::sync::Arc::new(
+                services::cron_service::DownloadConditionRule::new(app.handle().clone()),
+            ));
+            app_state.cron.register(std::sync::Arc::new(
+                services::cron_service::PendingReplaceRule::new(app.handle().clone()),
+            ));
+            app_state.cron.register(std::sync::Arc::new(
+                services::cron_service::DataUsageRule::new(app.handle().clone()),
+            ));
+            app_state.cron.register(std::sync::Arc::new(
+                services::cron_service::DeadlineRiskRule::new(app.handle().clone()),
+            ));
+            app_state.cron.register(std::sync::Arc::new(
+                services::cron_service::TorrentCompletionRule::new(app.handle().clone()),
+            ));
+            app_state.cron.clone().start();
+
+            // Mirror aggregate progress on the tray, taskbar and dock
+            app_state.taskbar.clone().start(app.handle().clone());
+
+            // Let the native messaging host and other entry points reach
+            // this process instead of opening the database themselves, and
+            // answer Prometheus scrapes when a metrics address is set
+            tauri::async_runtime::spawn(services::ipc_service::serve(
+                app.handle().clone(),
+                app_state.writer_lock.clone(),
+            ));
+
+            // Pause transfers before the system sleeps, resume them after wake
+            app_state.sleep_guard.clone().start(app.handle().clone());
+
+            // Reconnect stalled transfers when the default route changes
+            tauri::async_runtime::spawn(services::network_monitor::watch(app.handle().clone()));
+
+            // Fail downloads whose task died or stalled, so they can be resumed
+            tauri::async_runtime::spawn(services::watchdog_service::watch(app.handle().clone()));
+
+            // Live bandwidth by category and domain
+            tauri::async_runtime::spawn(services::bandwidth_service::run(app.handle().clone()));
+
+            // Finish conversions the last run was killed in the middle of
+            tauri::async_runtime::spawn(services::conversion_service::resume_interrupted(app.handle().clone()));
+
+            // Flag completed downloads whose files have gone missing
+            tauri::async_runtime::spawn(services::integrity_service::run_startup_sweep(
+                app.handle().clone(),
+                app_state.clone(),
+            ));
+
             // Start scheduler and listen for scheduled tasks
             let state_for_scheduler = app_state.clone();
             tauri::async_runtime::spawn(async move {
@@ -107,24 +181,43 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             // Download commands
             commands::download_commands::add_download,
+            commands::download_commands::confirm_download,
+            commands::download_commands::set_download_segments,
+            commands::download_commands::get_data_usage,
+            commands::download_commands::resolve_url,
+            commands::download_commands::update_download_url,
+            commands::download_commands::set_post_action,
             commands::download_commands::pause_download,
             commands::download_commands::resume_download,
             commands::download_commands::cancel_download,
             commands::download_commands::remove_download,
             commands::download_commands::retry_download,
+            commands::download_commands::finalize_download,
+            commands::download_commands::redownload,
+            commands::download_commands::update_download_metadata,
+            commands::download_commands::check_missing_files,
+            commands::download_commands::redownload_missing,
             commands::download_commands::get_all_downloads,
             commands::download_commands::get_download_progress,
             commands::download_commands::get_file_info,
             commands::download_commands::add_batch_downloads,
+            commands::download_commands::normalize_urls,
+            commands::download_commands::probe_batch,
+            commands::download_commands::add_selected,
             commands::download_commands::pause_all,
             commands::download_commands::resume_all,
             commands::download_commands::cancel_all,
             commands::download_commands::open_file,
             commands::download_commands::open_file_location,
             commands::download_commands::get_global_stats,
+            commands::download_commands::get_live_bandwidth_breakdown,
             commands::download_commands::set_speed_limit,
             commands::download_commands::get_queue_info,
+            commands::download_commands::get_queue_completion_forecast,
+            commands::download_commands::set_download_deadline,
+            commands::download_commands::validate_queue_consistency,
             commands::download_commands::set_max_concurrent,
+            commands::download_commands::reorder_queue,
             commands::download_commands::check_file_exists,
             commands::download_commands::get_file_size,
             // YouTube/video download commands
@@ -136,21 +229,39 @@ pub fn run() {
             commands::ytdlp_commands::update_ytdlp,
             commands::ytdlp_commands::get_ytdlp_version,
             commands::ytdlp_commands::get_bundled_ytdlp_version,
+            commands::ytdlp_commands::get_tool_status,
+            commands::ytdlp_commands::set_tool_path,
             // History commands
             commands::history_commands::get_download_history,
             commands::history_commands::get_history_stats,
+            commands::history_commands::get_stats_breakdown,
+            // Combined downloads + torrents view
+            commands::transfer_commands::get_unified_transfers,
+            commands::clipboard_commands::copy_to_clipboard,
+            commands::clipboard_commands::copy_all_urls,
             commands::history_commands::clear_download_history,
             commands::history_commands::export_history,
+            commands::history_commands::find_duplicate_files,
+            commands::history_commands::deduplicate,
+            commands::history_commands::rebuild_from_disk,
             // Settings commands
             commands::settings_commands::get_settings,
             commands::settings_commands::get_setting,
             commands::settings_commands::update_settings,
             commands::settings_commands::reset_settings,
+            commands::settings_commands::allow_save_root,
+            commands::settings_commands::test_content_filter,
             // System commands
             commands::system_commands::get_system_info,
             commands::system_commands::check_disk_space,
+            commands::system_commands::export_api_schema,
+            commands::system_commands::get_directory_sizes,
+            commands::system_commands::cancel_directory_sizes,
+            commands::system_commands::run_speed_test,
+            commands::system_commands::cancel_speed_test,
             // Scheduler commands
             commands::scheduler_commands::schedule_download,
+            commands::scheduler_commands::schedule_for_tonight,
             commands::scheduler_commands::cancel_scheduled_download,
             commands::scheduler_commands::update_scheduled_download,
             commands::scheduler_commands::get_scheduled_downloads,
@@ -159,6 +270,7 @@ pub fn run() {
             commands::scheduler_commands::is_scheduler_running,
             // FTP commands
             commands::ftp_commands::ftp_connect,
+            commands::ftp_commands::ftp_download_directory,
             commands::ftp_commands::ftp_disconnect,
             commands::ftp_commands::ftp_list_files,
             commands::ftp_commands::ftp_download_file,
@@ -166,6 +278,11 @@ pub fn run() {
             commands::ftp_commands::ftp_upload_file,
             // SFTP commands
             commands::sftp_commands::sftp_connect,
+            commands::sftp_commands::sftp_download_directory,
+            commands::remote_commands::validate_remote_url,
+            commands::remote_commands::test_remote_connection,
+            commands::remote_commands::save_remote_credential,
+            commands::remote_commands::forget_remote_credential,
             commands::sftp_commands::sftp_disconnect,
             commands::sftp_commands::sftp_list_files,
             commands::sftp_commands::sftp_download_file,
@@ -177,7 +294,10 @@ pub fn run() {
             commands::category_commands::get_category,
             commands::category_commands::create_category,
             commands::category_commands::update_category,
+            commands::category_commands::get_default_folder,
             commands::category_commands::delete_category,
+            commands::undo_commands::get_undoable_actions,
+            commands::undo_commands::undo_last_action,
             commands::category_commands::get_category_stats,
             commands::category_commands::assign_download_category,
             commands::category_commands::auto_categorize_download,
@@ -185,8 +305,11 @@ pub fn run() {
             commands::logging_commands::get_logs,
             commands::logging_commands::get_logs_by_level,
             commands::logging_commands::get_logs_by_category,
+            commands::logging_commands::get_download_logs,
             commands::logging_commands::get_logger_download_history,
             commands::logging_commands::get_performance_metrics,
+            commands::logging_commands::get_log_retention,
+            commands::logging_commands::set_log_retention,
             commands::logging_commands::clear_logs,
             commands::logging_commands::clear_logger_download_history,
             // Security commands
@@ -200,18 +323,39 @@ pub fn run() {
             commands::security_commands::check_rate_limit,
             // Torrent commands
             commands::torrent_commands::add_torrent_file,
+            commands::torrent_commands::add_torrent_url,
             commands::torrent_commands::add_magnet_link,
             commands::torrent_commands::get_torrent_stats,
             commands::torrent_commands::get_torrent_state,
             commands::torrent_commands::pause_torrent,
             commands::torrent_commands::resume_torrent,
             commands::torrent_commands::remove_torrent,
+            commands::torrent_commands::export_torrent_file,
+            commands::torrent_commands::get_magnet_uri,
+            commands::torrent_commands::get_torrent_session_info,
+            commands::torrent_commands::apply_torrent_settings,
+            commands::torrent_commands::open_torrent_content,
+            commands::update_commands::check_app_update,
+            commands::update_commands::download_app_update,
+            commands::update_commands::launch_installer,
             // Service commands
             services::clipboard_service::set_clipboard_monitoring,
             services::notification_service::set_notifications_enabled,
             services::notification_service::test_notification,
+            services::notification_service::preview_notification_sound,
             services::tray_service::handle_tray_menu_click,
+            services::mini_window_service::create_mini_window,
+            services::mini_window_service::close_mini_window,
+            services::mini_window_service::get_mini_summary,
         ])
-        .run(tauri::generate_context!())
-        .expect("Error while running Super Downloader");
+        .build(tauri::generate_context!())
+        .expect("Error while running Super Downloader")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Don't leave a stale progress bar on the taskbar or dock
+                if let Some(state) = app.try_state::<AppState>() {
+                    state.taskbar.clear(app);
+                }
+            }
+        });
 }
\ No newline at end of file
@@ -29,6 +29,7 @@ fn setup_crash_logging() {
         } else {
             format!("PANIC at {:?}:\nUnknown panic\n", panic_info.location())
         };
+        let msg = utils::privacy::redact_text(utils::privacy::logging_privacy(), &msg);
         let _ = std::fs::write(&log_file, &msg);
         eprintln!("{}", msg);
     }));
@@ -59,6 +60,25 @@ async fn handle_deep_link(
             
             let referrer = query_pairs.get("referrer").cloned();
             let filename = query_pairs.get("filename").cloned();
+            let page_title = query_pairs.get("title").cloned();
+            
+            // Links to a .torrent are added as torrents, not saved as files
+            if network::torrent_fetch::is_torrent_url(download_url) {
+                let info_hash = commands::torrent_commands::add_torrent_url_internal(
+                    &app_handle,
+                    &state,
+                    download_url,
+                    None,
+                    None,
+                    None,
+                ).await?;
+                tracing::info!("Torrent added from deep link: {}", info_hash);
+                if let Some(window) = app_handle.get_webview_window("main") {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+                return Ok(());
+            }
             
             // Add download using internal helper
             match commands::download_commands::add_download_internal(
@@ -66,6 +86,8 @@ async fn handle_deep_link(
                 None,
                 filename,
                 referrer,
+                page_title,
+                None,
                 state.clone(),
             ).await {
                 Ok(download_id) => {
@@ -105,6 +127,7 @@ fn main() {
 
     tracing_subscriber::fmt()
         .with_env_filter("afk_dunld=debug")
+        .with_writer(utils::logging::PrivateStdout)
         .init();
 
     // Check if running in native messaging mode
@@ -113,78 +136,17 @@ fn main() {
         // Run as native messaging host for browser extension
         tracing::info!("Starting in native messaging mode");
         
-        // We need to run the native messaging host synchronously
-        // For this, we'll use tokio runtime directly
+        // The running app owns the database; this process reaches it over
+        // IPC, or reads the database when the app isn't running
+        let Some(app_data_dir) = dirs::data_dir().map(|dir| dir.join(utils::constants::APP_IDENTIFIER)) else {
+            tracing::error!("No data directory for native messaging");
+            return;
+        };
         let runtime = tokio::runtime::Runtime::new().expect("Failed to create runtime");
-        runtime.block_on(async {
-            // Create a minimal app handle for native messaging
-            // This is a simplified version that doesn't need the full Tauri app
-            use std::io::{self, Read, Write};
-            use serde_json::json;
-            
-            let mut stdin = io::stdin();
-            let mut stdout = io::stdout();
-            
-            loop {
-                let mut length_bytes = [0u8; 4];
-                if let Err(e) = stdin.read_exact(&mut length_bytes) {
-                    if e.kind() == io::ErrorKind::UnexpectedEof {
-                        break;
-                    }
-                    tracing::error!("Failed to read message length: {}", e);
-                    break;
-                }
-                
-                let length = u32::from_le_bytes(length_bytes) as usize;
-                if length == 0 || length > 1024 * 1024 {
-                    break;
-                }
-                
-                let mut buffer = vec![0u8; length];
-                if let Err(e) = stdin.read_exact(&mut buffer) {
-                    tracing::error!("Failed to read message: {}", e);
-                    break;
-                }
-                
-                // Parse the message
-                if let Ok(msg) = serde_json::from_slice::<serde_json::Value>(&buffer) {
-                    tracing::debug!("Received message: {:?}", msg);
-                    
-                    // Simple response for now - in production, this would integrate with the app
-                    let response = match msg.get("type").and_then(|t| t.as_str()) {
-                        Some("ping") => json!({
-                            "type": "pong",
-                            "version": env!("CARGO_PKG_VERSION"),
-                            "app_name": "AFK-Dunld"
-                        }),
-                        Some("add_download") => {
-                            // TODO: Queue the download to be added when app starts
-                            json!({
-                                "type": "download_added",
-                                "success": true,
-                                "message": "Download queued"
-                            })
-                        },
-                        _ => json!({
-                            "type": "error",
-                            "message": "Unknown message type"
-                        })
-                    };
-                    
-                    // Send response
-                    if let Ok(response_str) = serde_json::to_string(&response) {
-                        let response_len = (response_str.len() as u32).to_le_bytes();
-                        let _ = stdout.write_all(&response_len);
-                        let _ = stdout.write_all(response_str.as_bytes());
-                        let _ = stdout.flush();
-                    }
-                } else {
-                    tracing::error!("Failed to parse message");
-                    break;
-                }
-            }
-        });
-        
+        if let Err(e) = runtime.block_on(services::native_messaging::run_detached_host(app_data_dir)) {
+            tracing::error!("Native messaging host failed: {}", e);
+        }
+
         return;
     }
 
@@ -198,7 +160,9 @@ fn main() {
             tauri_plugin_autostart::MacosLauncher::LaunchAgent,
             None,
         ))
-        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+        .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+            // A Jump List click starts another instance to open a download
+            services::recent_files_service::handle_launch_args(app, &args);
             // When another instance tries to start, bring the existing window to front
             tracing::info!("Another instance tried to start, focusing existing window");
             if let Some(window) = app.get_webview_window("main") {
@@ -207,6 +171,7 @@ fn main() {
             }
         }))
         .plugin(tauri_plugin_deep_link::init())
+        .on_window_event(services::mini_window_service::on_window_event)
         .setup(|app| {
             // Get app data directory
             let app_data_dir = app
@@ -251,6 +216,10 @@ fn main() {
                 }
             }
 
+            // Started from the Jump List while the app wasn't running
+            let args: Vec<String> = std::env::args().collect();
+            services::recent_files_service::handle_launch_args(app.handle(), &args);
+
             // Listen for deep links while app is running
             let app_handle_for_deep_links = app.handle().clone();
             app.deep_link().on_open_url(move |event| {
@@ -288,8 +257,78 @@ fn main() {
             let state_for_watcher = app_state.clone();
             services::file_watcher::FileWatcher::start(handle, state_for_watcher);
 
+            // Start time-based rules (torrent schedules, log maintenance, update checks)
+            app_state.cron.register(std::sync::Arc::new(
+                services::cron_service::TorrentScheduleRule::new(
+                    app.handle().clone(),
+                    app_state.torrent_client.clone(),
+                    app_state.db.clone(),
+                ),
+            ));
+            app_state.cron.register(std::sync::Arc::new(
+                services::cron_service::LogMaintenanceRule::new(
+                    app_state.logger.clone(),
+                    app_state.db.clone(),
+                ),
+            ));
+            app_state.cron.register(std::sync::Arc::new(
+                services::cron_service::UpdateCheckRule::new(
+                    app.handle().clone(),
+                    app_state.updates.clone(),
+                ),
+            ));
+            app_state.cron.register(std::sync::Arc::new(
+                services::cron_service::DownloadConditionRule::new(app.handle().clone()),
+            ));
+            app_state.cron.register(std::sync::Arc::new(
+                services::cron_service::PendingReplaceRule::new(app.handle().clone()),
+            ));
+            app_state.cron.register(std::sync::Arc::new(
+                services::cron_service::DataUsageRule::new(app.handle().clone()),
+            ));
+            app_state.cron.register(std::sync::Arc::new(
+                services::cron_service::DeadlineRiskRule::new(app.handle().clone()),
+            ));
+            app_state.cron.register(std::sync::Arc::new(
+                services::cron_service::TorrentCompletionRule::new(app.handle().clone()),
+            ));
+            app_state.cron.clone().start();
+
+            // Mirror aggregate progress on the tray, taskbar and dock
+            app_state.taskbar.clone().start(app.handle().clone());
+
+            // Let the native messaging host and other entry points reach
+            // this process instead of opening the database themselves, and
+            // answer Prometheus scrapes when a metrics address is set
+            tauri::async_runtime::spawn(services::ipc_service::serve(
+                app.handle().clone(),
+                app_state.writer_lock.clone(),
+            ));
+
+            // Pause transfers before the system sleeps, resume them after wake
+            app_state.sleep_guard.clone().start(app.handle().clone());
+
+            // Reconnect stalled transfers when the default route changes
+            tauri::async_runtime::spawn(services::network_monitor::watch(app.handle().clone()));
+
+            // Fail downloads whose task died or stalled, so they can be resumed
+            tauri::async_runtime::spawn(services::watchdog_service::watch(app.handle().clone()));
+
+            // Live bandwidth by category and domain
+            tauri::async_runtime::spawn(services::bandwidth_service::run(app.handle().clone()));
+
+            // Finish conversions the last run was killed in the middle of
+            tauri::async_runtime::spawn(services::conversion_service::resume_interrupted(app.handle().clone()));
+
+            // Flag completed downloads whose files have gone missing
+            tauri::async_runtime::spawn(services::integrity_service::run_startup_sweep(
+                app.handle().clone(),
+                app_state.clone(),
+            ));
+
             // Start scheduler and listen for scheduled tasks
             let state_for_scheduler = app_state.clone();
+            let scheduler_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
                 // Start the scheduler
                 if let Err(e) = state_for_scheduler.scheduler.start().await {
@@ -308,6 +347,7 @@ fn main() {
                         
                         // Get the download from database and start it
                         let state_clone = state_for_scheduler.clone();
+                        let app_handle = scheduler_handle.clone();
                         tokio::spawn(async move {
                             // Parse download_id from string to Uuid
                             let download_id = match uuid::Uuid::parse_str(&task.download_id) {
@@ -323,21 +363,14 @@ fn main() {
                                 Ok(Some(download_task)) => {
                                     tracing::info!("Loaded scheduled download: {}", download_task.id);
                                     
-                                    // Check if download is already active
-                                    let active_downloads = state_clone.active_downloads.read().await;
-                                    if active_downloads.contains_key(&download_task.id) {
-                                        tracing::warn!("Download {} is already active, skipping", download_task.id);
-                                        return;
-                                    }
-                                    drop(active_downloads);
-                                    
-                                    // Resume or restart the download based on status
+                                    // Resume or restart the download based on status. A
+                                    // download the user already started is left running.
                                     match download_task.status {
                                         core::download_task::DownloadStatus::Paused => {
                                             // Resume paused download
                                             if let Err(e) = commands::download_commands::resume_download_internal(
                                                 download_task.id,
-                                                state_clone.clone()
+                                                &app_handle
                                             ).await {
                                                 tracing::error!("Failed to resume scheduled download {}: {}", download_task.id, e);
                                             } else {
@@ -349,7 +382,7 @@ fn main() {
                                             // Retry failed/cancelled downloads
                                             if let Err(e) = commands::download_commands::retry_download_internal(
                                                 download_task.id,
-                                                state_clone.clone()
+                                                &app_handle
                                             ).await {
                                                 tracing::error!("Failed to retry scheduled download {}: {}", download_task.id, e);
                                             } else {
@@ -362,8 +395,10 @@ fn main() {
                                                 download_task.url.clone(),
                                                 Some(download_task.save_path.to_string_lossy().to_string()),
                                                 Some(download_task.file_name.clone()),
-                                                None,
-                                                state_clone.clone()
+                                                download_task.source_page_url.clone(),
+                                                download_task.source_page_title.clone(),
+                                                download_task.url_refresh.clone(),
+                                                &app_handle
                                             ).await {
                                                 tracing::error!("Failed to start scheduled download {}: {}", download_task.id, e);
                                             } else {
@@ -393,14 +428,28 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             // Download commands
             commands::download_commands::add_download,
+            commands::download_commands::confirm_download,
+            commands::download_commands::set_download_segments,
+            commands::download_commands::get_data_usage,
+            commands::download_commands::resolve_url,
+            commands::download_commands::update_download_url,
+            commands::download_commands::set_post_action,
             commands::download_commands::pause_download,
             commands::download_commands::resume_download,
             commands::download_commands::cancel_download,
             commands::download_commands::remove_download,
             commands::download_commands::retry_download,
+            commands::download_commands::finalize_download,
+            commands::download_commands::redownload,
+            commands::download_commands::update_download_metadata,
+            commands::download_commands::check_missing_files,
+            commands::download_commands::redownload_missing,
             commands::download_commands::get_all_downloads,
             commands::download_commands::get_file_info,
             commands::download_commands::add_batch_downloads,
+            commands::download_commands::normalize_urls,
+            commands::download_commands::probe_batch,
+            commands::download_commands::add_selected,
             commands::download_commands::get_download_progress,
             commands::download_commands::pause_all,
             commands::download_commands::resume_all,
@@ -408,9 +457,14 @@ fn main() {
             commands::download_commands::open_file,
             commands::download_commands::open_file_location,
             commands::download_commands::get_global_stats,
+            commands::download_commands::get_live_bandwidth_breakdown,
             commands::download_commands::set_speed_limit,
             commands::download_commands::get_queue_info,
+            commands::download_commands::get_queue_completion_forecast,
+            commands::download_commands::set_download_deadline,
+            commands::download_commands::validate_queue_consistency,
             commands::download_commands::set_max_concurrent,
+            commands::download_commands::reorder_queue,
             commands::download_commands::check_file_exists,
             commands::download_commands::get_file_size,
             // YouTube/video download commands
@@ -422,25 +476,43 @@ fn main() {
             commands::ytdlp_commands::update_ytdlp,
             commands::ytdlp_commands::get_ytdlp_version,
             commands::ytdlp_commands::get_bundled_ytdlp_version,
+            commands::ytdlp_commands::get_tool_status,
+            commands::ytdlp_commands::set_tool_path,
             // History commands
             commands::history_commands::get_download_history,
             commands::history_commands::get_history_stats,
+            commands::history_commands::get_stats_breakdown,
+            // Combined downloads + torrents view
+            commands::transfer_commands::get_unified_transfers,
+            commands::clipboard_commands::copy_to_clipboard,
+            commands::clipboard_commands::copy_all_urls,
             commands::history_commands::clear_download_history,
             commands::history_commands::delete_download_from_history,
             commands::history_commands::delete_downloads_bulk,
             commands::history_commands::clear_old_history,
             commands::history_commands::export_history,
+            commands::history_commands::find_duplicate_files,
+            commands::history_commands::deduplicate,
+            commands::history_commands::rebuild_from_disk,
             // Settings commands
             commands::settings_commands::get_settings,
             commands::settings_commands::get_setting,
             commands::settings_commands::update_settings,
             commands::settings_commands::reset_settings,
+            commands::settings_commands::allow_save_root,
+            commands::settings_commands::test_content_filter,
             // System commands
             commands::system_commands::get_system_info,
             commands::system_commands::open_download_folder,
             commands::system_commands::check_disk_space,
+            commands::system_commands::export_api_schema,
+            commands::system_commands::get_directory_sizes,
+            commands::system_commands::cancel_directory_sizes,
+            commands::system_commands::run_speed_test,
+            commands::system_commands::cancel_speed_test,
             // Scheduler commands
             commands::scheduler_commands::schedule_download,
+            commands::scheduler_commands::schedule_for_tonight,
             commands::scheduler_commands::cancel_scheduled_download,
             commands::scheduler_commands::update_scheduled_download,
             commands::scheduler_commands::get_scheduled_downloads,
@@ -449,6 +521,7 @@ fn main() {
             commands::scheduler_commands::is_scheduler_running,
             // FTP commands
             commands::ftp_commands::ftp_connect,
+            commands::ftp_commands::ftp_download_directory,
             commands::ftp_commands::ftp_disconnect,
             commands::ftp_commands::ftp_list_files,
             commands::ftp_commands::ftp_download_file,
@@ -456,6 +529,11 @@ fn main() {
             commands::ftp_commands::ftp_upload_file,
             // SFTP commands
             commands::sftp_commands::sftp_connect,
+            commands::sftp_commands::sftp_download_directory,
+            commands::remote_commands::validate_remote_url,
+            commands::remote_commands::test_remote_connection,
+            commands::remote_commands::save_remote_credential,
+            commands::remote_commands::forget_remote_credential,
             commands::sftp_commands::sftp_disconnect,
             commands::sftp_commands::sftp_list_files,
             commands::sftp_commands::sftp_download_file,
@@ -467,7 +545,10 @@ fn main() {
             commands::category_commands::get_category,
             commands::category_commands::create_category,
             commands::category_commands::update_category,
+            commands::category_commands::get_default_folder,
             commands::category_commands::delete_category,
+            commands::undo_commands::get_undoable_actions,
+            commands::undo_commands::undo_last_action,
             commands::category_commands::get_category_stats,
             commands::category_commands::assign_download_category,
             commands::category_commands::auto_categorize_download,
@@ -475,8 +556,11 @@ fn main() {
             commands::logging_commands::get_logs,
             commands::logging_commands::get_logs_by_level,
             commands::logging_commands::get_logs_by_category,
+            commands::logging_commands::get_download_logs,
             commands::logging_commands::get_logger_download_history,
             commands::logging_commands::get_performance_metrics,
+            commands::logging_commands::get_log_retention,
+            commands::logging_commands::set_log_retention,
             commands::logging_commands::clear_logs,
             commands::logging_commands::clear_logger_download_history,
             // Security commands
@@ -490,6 +574,7 @@ fn main() {
             commands::security_commands::check_rate_limit,
             // Torrent commands
             commands::torrent_commands::add_torrent_file,
+            commands::torrent_commands::add_torrent_url,
             commands::torrent_commands::add_magnet_link,
             commands::torrent_commands::get_torrent_stats,
             commands::torrent_commands::get_torrent_state,
@@ -509,9 +594,11 @@ fn main() {
             commands::torrent_commands::remove_torrent_tag,
             commands::torrent_commands::set_torrent_category,
             commands::torrent_commands::get_torrent_metadata,
+            commands::torrent_commands::set_torrent_super_seeding,
             commands::torrent_commands::add_web_seed,
             commands::torrent_commands::remove_web_seed,
             commands::torrent_commands::get_web_seeds,
+            commands::torrent_commands::reset_web_seed_health,
             commands::torrent_commands::set_encryption_config,
             commands::torrent_commands::get_encryption_config,
             commands::torrent_commands::add_blocked_ip,
@@ -522,17 +609,37 @@ fn main() {
             commands::torrent_commands::set_advanced_config,
             commands::torrent_commands::set_seed_ratio_limit,
             commands::torrent_commands::set_max_connections,
+            commands::torrent_commands::export_torrent_file,
+            commands::torrent_commands::get_magnet_uri,
+            commands::torrent_commands::get_torrent_session_info,
+            commands::torrent_commands::apply_torrent_settings,
+            commands::torrent_commands::open_torrent_content,
+            commands::update_commands::check_app_update,
+            commands::update_commands::download_app_update,
+            commands::update_commands::launch_installer,
             // Service commands
             services::clipboard_service::set_clipboard_monitoring,
             services::notification_service::set_notifications_enabled,
             services::notification_service::test_notification,
+            services::notification_service::preview_notification_sound,
             services::tray_service::handle_tray_menu_click,
+            services::mini_window_service::create_mini_window,
+            services::mini_window_service::close_mini_window,
+            services::mini_window_service::get_mini_summary,
             // Browser extension commands
             commands::browser_commands::add_download_from_browser,
             commands::browser_commands::is_browser_extension_available,
             commands::browser_commands::install_browser_extension_support,
             commands::browser_commands::uninstall_browser_extension_support,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running application");
+        .build(tauri::generate_context!())
+        .expect("error while running application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Don't leave a stale progress bar on the taskbar or dock
+                if let Some(state) = app.try_state::<AppState>() {
+                    state.taskbar.clear(app);
+                }
+            }
+        });
 }
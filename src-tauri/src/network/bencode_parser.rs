@@ -3,7 +3,9 @@
 
 use serde::{Deserialize, Serialize};
 use serde_bencode;
+use serde_bencode::value::Value;
 use sha1::{Digest, Sha1};
+use sha2::Sha256;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use crate::utils::error::AppError;
@@ -24,6 +26,10 @@ pub struct TorrentFile {
     #[serde(rename = "creation date")]
     #[serde(default)]
     pub creation_date: Option<i64>,
+    /// The info dictionary exactly as it appeared in the file, so hashes
+    /// cover keys this struct doesn't model
+    #[serde(skip)]
+    raw_info: Option<Vec<u8>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -31,6 +37,8 @@ pub struct TorrentInfo {
     pub name: String,
     #[serde(rename = "piece length")]
     pub piece_length: i64,
+    /// v1 piece hashes; absent in v2-only torrents
+    #[serde(default)]
     pub pieces: Vec<u8>,
     #[serde(default)]
     pub length: Option<i64>,
@@ -38,6 +46,14 @@ pub struct TorrentInfo {
     pub files: Option<Vec<FileInfo>>,
     #[serde(default)]
     pub private: Option<i64>,
+    /// 2 for BitTorrent v2 and hybrid torrents
+    #[serde(rename = "meta version")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta_version: Option<i64>,
+    /// v2 file listing: nested directories ending in `"" -> {length}`
+    #[serde(rename = "file tree")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_tree: Option<Value>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -49,8 +65,61 @@ pub struct FileInfo {
 impl TorrentFile {
     /// Parse a torrent file from bytes
     pub fn from_bytes(data: &[u8]) -> Result<Self, AppError> {
-        serde_bencode::from_bytes::<TorrentFile>(data)
-            .map_err(|e| AppError::TorrentError(format!("Failed to parse torrent file: {}", e)))
+        let mut torrent = serde_bencode::from_bytes::<TorrentFile>(data)
+            .map_err(|e| AppError::TorrentError(format!("Failed to parse torrent file: {}", e)))?;
+
+        if let Ok(Value::Dict(root)) = serde_bencode::from_bytes::<Value>(data) {
+            torrent.raw_info = root.get(b"info".as_slice())
+                .and_then(|info| serde_bencode::to_bytes(info).ok());
+        }
+
+        Ok(torrent)
+    }
+
+    /// Encode as a .torrent file. The info dictionary is written exactly as
+    /// it was read, so the info hash doesn't change.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, AppError> {
+        let info = serde_bencode::from_bytes::<Value>(&self.info_bytes()?)
+            .map_err(|e| AppError::TorrentError(format!("Failed to decode info dict: {}", e)))?;
+        let text = |s: &String| Value::Bytes(s.clone().into_bytes());
+
+        let mut root = HashMap::new();
+        root.insert(b"info".to_vec(), info);
+        if let Some(ref announce) = self.announce {
+            root.insert(b"announce".to_vec(), text(announce));
+        }
+        if let Some(ref announce_list) = self.announce_list {
+            let tiers = announce_list.iter()
+                .map(|tier| Value::List(tier.iter().map(text).collect()))
+                .collect();
+            root.insert(b"announce-list".to_vec(), Value::List(tiers));
+        }
+        if let Some(ref comment) = self.comment {
+            root.insert(b"comment".to_vec(), text(comment));
+        }
+        if let Some(ref created_by) = self.created_by {
+            root.insert(b"created by".to_vec(), text(created_by));
+        }
+        if let Some(creation_date) = self.creation_date {
+            root.insert(b"creation date".to_vec(), Value::Int(creation_date));
+        }
+
+        // Dictionary keys come out sorted, as bencode requires
+        serde_bencode::to_bytes(&Value::Dict(root))
+            .map_err(|e| AppError::TorrentError(format!("Failed to encode torrent file: {}", e)))
+    }
+
+    /// Magnet link with the torrent's hashes, name, size and trackers
+    pub fn magnet_link(&self) -> Result<MagnetLink, AppError> {
+        let info_hash_v1 = if self.has_v1() { Some(self.info_hash()?) } else { None };
+        Ok(MagnetLink {
+            info_hash: self.preferred_info_hash()?,
+            info_hash_v1,
+            info_hash_v2: self.info_hash_v2()?,
+            display_name: Some(self.info.name.clone()),
+            trackers: self.trackers(),
+            exact_length: Some(self.total_size()),
+        })
     }
 
     /// Parse a torrent file from a path
@@ -60,10 +129,17 @@ impl TorrentFile {
         Self::from_bytes(&data)
     }
 
+    fn info_bytes(&self) -> Result<Vec<u8>, AppError> {
+        match &self.raw_info {
+            Some(raw) => Ok(raw.clone()),
+            None => serde_bencode::to_bytes(&self.info)
+                .map_err(|e| AppError::TorrentError(format!("Failed to encode info dict: {}", e))),
+        }
+    }
+
     /// Calculate the info hash (SHA1 hash of the bencoded info dictionary)
     pub fn info_hash(&self) -> Result<String, AppError> {
-        let info_bytes = serde_bencode::to_bytes(&self.info)
-            .map_err(|e| AppError::TorrentError(format!("Failed to encode info dict: {}", e)))?;
+        let info_bytes = self.info_bytes()?;
         
         let mut hasher = Sha1::new();
         hasher.update(&info_bytes);
@@ -72,6 +148,36 @@ impl TorrentFile {
         Ok(hex::encode(hash))
     }
 
+    /// SHA-256 info hash of v2 and hybrid torrents
+    pub fn info_hash_v2(&self) -> Result<Option<String>, AppError> {
+        if !self.is_v2() {
+            return Ok(None);
+        }
+        let mut hasher = Sha256::new();
+        hasher.update(self.info_bytes()?);
+        Ok(Some(hex::encode(hasher.finalize())))
+    }
+
+    /// Whether the torrent carries v2 metadata (v2-only or hybrid)
+    pub fn is_v2(&self) -> bool {
+        self.info.meta_version == Some(2)
+    }
+
+    /// Whether v1 peers can download it (v1-only or hybrid)
+    pub fn has_v1(&self) -> bool {
+        !self.info.pieces.is_empty()
+    }
+
+    /// Key used by the torrent client: the v1 hash when the torrent has one,
+    /// otherwise the v2 hash
+    pub fn preferred_info_hash(&self) -> Result<String, AppError> {
+        if self.has_v1() {
+            return self.info_hash();
+        }
+        self.info_hash_v2()?
+            .ok_or_else(|| AppError::TorrentError("Torrent has neither v1 pieces nor v2 metadata".to_string()))
+    }
+
     /// Get total size of the torrent
     pub fn total_size(&self) -> u64 {
         if let Some(length) = self.info.length {
@@ -79,7 +185,7 @@ impl TorrentFile {
         } else if let Some(ref files) = self.info.files {
             files.iter().map(|f| f.length as u64).sum()
         } else {
-            0
+            self.file_list().iter().map(|(_, size)| size).sum()
         }
     }
 
@@ -106,6 +212,11 @@ impl TorrentFile {
                 let path: PathBuf = f.path.iter().collect();
                 (path, f.length as u64)
             }).collect()
+        } else if let (None, Some(tree)) = (self.info.length, &self.info.file_tree) {
+            let mut files = Vec::new();
+            walk_file_tree(tree, PathBuf::new(), &mut files);
+            files.sort();
+            files
         } else {
             vec![(PathBuf::from(&self.info.name), self.info.length.unwrap_or(0) as u64)]
         }
@@ -138,53 +249,189 @@ impl TorrentFile {
     }
 }
 
+/// Collect `(path, length)` pairs from a v2 `file tree`
+fn walk_file_tree(node: &Value, path: PathBuf, files: &mut Vec<(PathBuf, u64)>) {
+    let Value::Dict(entries) = node else {
+        return;
+    };
+    for (name, child) in entries {
+        if name.is_empty() {
+            let length = match child {
+                Value::Dict(leaf) => match leaf.get(b"length".as_slice()) {
+                    Some(Value::Int(length)) => *length as u64,
+                    _ => 0,
+                },
+                _ => 0,
+            };
+            files.push((path.clone(), length));
+        } else {
+            let name = String::from_utf8_lossy(name);
+            walk_file_tree(child, path.join(name.as_ref()), files);
+        }
+    }
+}
+
+/// Multihash prefix of a v2 `urn:btmh:` hash: SHA-256 (0x12), 32 bytes (0x20)
+const BTMH_SHA256_PREFIX: &str = "1220";
+
 /// Parse a magnet link into its components
 #[derive(Debug, Clone)]
 pub struct MagnetLink {
+    /// Key used by the torrent client: the v1 hash when present, else v2
     pub info_hash: String,
+    /// 40-hex SHA-1 hash from `urn:btih:`
+    pub info_hash_v1: Option<String>,
+    /// 64-hex SHA-256 hash from `urn:btmh:1220...`
+    pub info_hash_v2: Option<String>,
     pub display_name: Option<String>,
     pub trackers: Vec<String>,
     pub exact_length: Option<u64>,
 }
 
 impl MagnetLink {
-    /// Parse a magnet URI
+    /// Parse a magnet URI. v1 (`btih`), v2 (`btmh`) and hybrid links that
+    /// carry both are accepted.
     pub fn parse(magnet_uri: &str) -> Result<Self, AppError> {
         if !magnet_uri.starts_with("magnet:?") {
             return Err(AppError::TorrentError("Invalid magnet link".to_string()));
         }
 
-        let params: HashMap<String, String> = magnet_uri[8..]
+        // Keys such as xt and tr may repeat
+        let params: Vec<(&str, &str)> = magnet_uri[8..]
             .split('&')
-            .filter_map(|param| {
-                let mut parts = param.splitn(2, '=');
-                Some((parts.next()?.to_string(), parts.next()?.to_string()))
-            })
+            .filter_map(|param| param.split_once('='))
             .collect();
 
-        let info_hash = params.get("xt")
-            .and_then(|xt| xt.strip_prefix("urn:btih:"))
-            .ok_or_else(|| AppError::TorrentError("Missing info hash in magnet link".to_string()))?
-            .to_string();
+        let mut info_hash_v1 = None;
+        let mut info_hash_v2 = None;
+        for (key, value) in &params {
+            if !key.starts_with("xt") {
+                continue;
+            }
+            if let Some(hash) = value.strip_prefix("urn:btih:") {
+                info_hash_v1 = Some(parse_btih(hash)?);
+            } else if let Some(hash) = value.strip_prefix("urn:btmh:") {
+                info_hash_v2 = Some(parse_btmh(hash)?);
+            }
+        }
+
+        let info_hash = info_hash_v1.clone()
+            .or_else(|| info_hash_v2.clone())
+            .ok_or_else(|| AppError::TorrentError("Missing info hash in magnet link".to_string()))?;
 
-        let display_name = params.get("dn")
-            .map(|dn| urlencoding::decode(dn).unwrap_or_default().to_string());
+        let display_name = params.iter()
+            .find(|(k, _)| *k == "dn")
+            .map(|(_, dn)| urlencoding::decode(dn).unwrap_or_default().to_string());
 
         let trackers = params.iter()
-            .filter(|(k, _)| k == &"tr")
+            .filter(|(k, _)| *k == "tr")
             .filter_map(|(_, v)| urlencoding::decode(v).ok().map(|s| s.to_string()))
             .collect();
 
-        let exact_length = params.get("xl")
-            .and_then(|xl| xl.parse::<u64>().ok());
+        let exact_length = params.iter()
+            .find(|(k, _)| *k == "xl")
+            .and_then(|(_, xl)| xl.parse::<u64>().ok());
 
         Ok(MagnetLink {
             info_hash,
+            info_hash_v1,
+            info_hash_v2,
             display_name,
             trackers,
             exact_length,
         })
     }
+
+    /// Link for a bare info hash: 40 hex characters are taken as v1,
+    /// 64 as v2
+    pub fn from_info_hash(info_hash: &str) -> Self {
+        let info_hash = info_hash.to_ascii_lowercase();
+        let (info_hash_v1, info_hash_v2) = if info_hash.len() == 64 {
+            (None, Some(info_hash.clone()))
+        } else {
+            (Some(info_hash.clone()), None)
+        };
+        MagnetLink {
+            info_hash,
+            info_hash_v1,
+            info_hash_v2,
+            display_name: None,
+            trackers: Vec::new(),
+            exact_length: None,
+        }
+    }
+
+    /// Format as a `magnet:?` URI. Hybrid links carry both hashes.
+    pub fn to_uri(&self) -> String {
+        let mut params = Vec::new();
+        if let Some(ref v1) = self.info_hash_v1 {
+            params.push(format!("xt=urn:btih:{}", v1));
+        }
+        if let Some(ref v2) = self.info_hash_v2 {
+            params.push(format!("xt=urn:btmh:{}{}", BTMH_SHA256_PREFIX, v2));
+        }
+        if let Some(ref name) = self.display_name {
+            params.push(format!("dn={}", urlencoding::encode(name)));
+        }
+        if let Some(length) = self.exact_length {
+            params.push(format!("xl={}", length));
+        }
+        for tracker in &self.trackers {
+            params.push(format!("tr={}", urlencoding::encode(tracker)));
+        }
+        format!("magnet:?{}", params.join("&"))
+    }
+
+    /// Whether the link carries both a v1 and a v2 hash
+    pub fn is_hybrid(&self) -> bool {
+        self.info_hash_v1.is_some() && self.info_hash_v2.is_some()
+    }
+}
+
+/// v1 hashes are 40 hex characters or 32 base32 characters
+fn parse_btih(hash: &str) -> Result<String, AppError> {
+    if hash.len() == 40 && hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Ok(hash.to_ascii_lowercase());
+    }
+    if hash.len() == 32 {
+        if let Some(bytes) = decode_base32(hash) {
+            return Ok(hex::encode(bytes));
+        }
+    }
+    Err(AppError::TorrentError(format!("Invalid btih info hash: {}", hash)))
+}
+
+/// v2 hashes are SHA-256 multihashes: `1220` followed by 64 hex characters
+fn parse_btmh(hash: &str) -> Result<String, AppError> {
+    let hash = hash.to_ascii_lowercase();
+    match hash.strip_prefix(BTMH_SHA256_PREFIX) {
+        Some(digest) if digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit()) => {
+            Ok(digest.to_string())
+        }
+        _ => Err(AppError::TorrentError(format!("Invalid btmh info hash: {}", hash))),
+    }
+}
+
+/// RFC 4648 base32 without padding
+fn decode_base32(input: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(input.len() * 5 / 8);
+    let mut buffer = 0u64;
+    let mut bits = 0;
+    for c in input.chars() {
+        let value = match c.to_ascii_uppercase() {
+            c @ 'A'..='Z' => c as u64 - 'A' as u64,
+            c @ '2'..='7' => c as u64 - '2' as u64 + 26,
+            _ => return None,
+        };
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(bytes)
 }
 
 #[cfg(test)]
@@ -200,4 +447,129 @@ mod tests {
         assert_eq!(parsed.display_name, Some("test file".to_string()));
         assert_eq!(parsed.trackers.len(), 1);
     }
+
+    const V1_HASH: &str = "1234567890abcdef1234567890abcdef12345678";
+    const V2_HASH: &str = "cafebabecafebabecafebabecafebabecafebabecafebabecafebabecafebabe";
+
+    #[test]
+    fn test_magnet_v1_only() {
+        let magnet = format!("magnet:?xt=urn:btih:{}&tr=udp://a&tr=udp://b", V1_HASH.to_uppercase());
+        let parsed = MagnetLink::parse(&magnet).unwrap();
+
+        assert_eq!(parsed.info_hash, V1_HASH);
+        assert_eq!(parsed.info_hash_v1.as_deref(), Some(V1_HASH));
+        assert_eq!(parsed.info_hash_v2, None);
+        assert_eq!(parsed.trackers, vec!["udp://a", "udp://b"]);
+
+        // Base32 form of the same hash
+        let base32 = MagnetLink::parse("magnet:?xt=urn:btih:CI2FM6EQVPG66ERUKZ4JBK6N54JDIVTY").unwrap();
+        assert_eq!(base32.info_hash, V1_HASH);
+    }
+
+    #[test]
+    fn test_magnet_v2_only() {
+        let magnet = format!("magnet:?xt=urn:btmh:1220{}&dn=v2", V2_HASH);
+        let parsed = MagnetLink::parse(&magnet).unwrap();
+
+        assert_eq!(parsed.info_hash, V2_HASH);
+        assert_eq!(parsed.info_hash_v1, None);
+        assert_eq!(parsed.info_hash_v2.as_deref(), Some(V2_HASH));
+        assert!(!parsed.is_hybrid());
+
+        // Only SHA-256 multihashes are valid
+        assert!(MagnetLink::parse(&format!("magnet:?xt=urn:btmh:1120{}", V2_HASH)).is_err());
+    }
+
+    #[test]
+    fn test_magnet_hybrid_prefers_v1() {
+        let magnet = format!(
+            "magnet:?xt=urn:btih:{}&xt=urn:btmh:1220{}&dn=hybrid",
+            V1_HASH, V2_HASH
+        );
+        let parsed = MagnetLink::parse(&magnet).unwrap();
+
+        assert!(parsed.is_hybrid());
+        assert_eq!(parsed.info_hash, V1_HASH);
+        assert_eq!(parsed.info_hash_v2.as_deref(), Some(V2_HASH));
+    }
+
+    #[test]
+    fn test_magnet_uri_round_trip() {
+        let mut magnet = MagnetLink::from_info_hash(V1_HASH);
+        magnet.info_hash_v2 = Some(V2_HASH.to_string());
+        magnet.display_name = Some("a & b.iso".to_string());
+        magnet.exact_length = Some(4096);
+        magnet.trackers = vec!["udp://tracker.example:80/announce?x=1".to_string()];
+
+        let parsed = MagnetLink::parse(&magnet.to_uri()).unwrap();
+        assert!(parsed.is_hybrid());
+        assert_eq!(parsed.info_hash, V1_HASH);
+        assert_eq!(parsed.display_name, magnet.display_name);
+        assert_eq!(parsed.exact_length, Some(4096));
+        assert_eq!(parsed.trackers, magnet.trackers);
+    }
+
+    /// Single-file v1 torrent with keys the struct doesn't model
+    fn v1_fixture() -> Vec<u8> {
+        [
+            b"d8:announce17:udp://a.example:113:announce-listll17:udp://a.example:1el17:udp://b.example:2ee".as_slice(),
+            b"7:comment3:hey13:creation datei1700000000e".as_slice(),
+            b"4:infod6:lengthi12345e4:name8:file.bin12:piece lengthi16384e6:pieces20:".as_slice(),
+            &[b'p'; 20],
+            b"7:privatei1e6:sourcei9eee".as_slice(),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_torrent_export_round_trip() {
+        let torrent = TorrentFile::from_bytes(&v1_fixture()).unwrap();
+        let exported = TorrentFile::from_bytes(&torrent.to_bytes().unwrap()).unwrap();
+
+        assert_eq!(exported.info_hash().unwrap(), torrent.info_hash().unwrap());
+        assert_eq!(exported.trackers(), vec!["udp://a.example:1", "udp://b.example:2"]);
+        assert_eq!(exported.comment.as_deref(), Some("hey"));
+        assert_eq!(exported.creation_date, Some(1_700_000_000));
+
+        let magnet = MagnetLink::parse(&torrent.magnet_link().unwrap().to_uri()).unwrap();
+        assert_eq!(magnet.info_hash, torrent.info_hash().unwrap());
+        assert_eq!(magnet.display_name.as_deref(), Some("file.bin"));
+        assert_eq!(magnet.exact_length, Some(12345));
+        assert_eq!(magnet.trackers.len(), 2);
+    }
+
+    /// v2-only torrent with a nested file tree and no v1 `pieces`
+    fn v2_fixture() -> (Vec<u8>, std::ops::Range<usize>) {
+        let info: Vec<u8> = [
+            b"d9:file treed".as_slice(),
+            b"4:docsd5:a.txtd0:d6:lengthi5e11:pieces root32:".as_slice(),
+            &[b'r'; 32],
+            b"eee".as_slice(),
+            b"5:b.bind0:d6:lengthi7eee".as_slice(),
+            b"e12:meta versioni2e4:name4:test12:piece lengthi16384ee".as_slice(),
+        ]
+        .concat();
+        let prefix = b"d8:announce9:udp://x:14:info".as_slice();
+        let data = [prefix, &info, b"e".as_slice()].concat();
+        (data, prefix.len()..prefix.len() + info.len())
+    }
+
+    #[test]
+    fn test_v2_torrent_file_tree() {
+        let (data, info_range) = v2_fixture();
+        let torrent = TorrentFile::from_bytes(&data).unwrap();
+
+        assert!(torrent.is_v2());
+        assert!(!torrent.has_v1());
+        assert_eq!(
+            torrent.file_list(),
+            vec![(PathBuf::from("b.bin"), 7), (PathBuf::from("docs").join("a.txt"), 5)]
+        );
+        assert_eq!(torrent.total_size(), 12);
+
+        // Hashes cover the info dictionary exactly as stored
+        let expected = hex::encode(Sha256::digest(&data[info_range]));
+        assert_eq!(torrent.info_hash_v2().unwrap(), Some(expected.clone()));
+        assert_eq!(torrent.preferred_info_hash().unwrap(), expected);
+    }
 }
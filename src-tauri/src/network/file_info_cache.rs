@@ -0,0 +1,199 @@
+// src-tauri/src/network/file_info_cache.rs
+// Adding a download probes its URL up to three times: the add dialog's
+// preview, creating the task, and starting it. Probes that succeed are
+// kept for a short while so the later ones are answered from memory. Once
+// an entry goes stale it is revalidated with its ETag instead of being
+// dropped, and a download that finds the file changed on the server
+// removes it outright.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::network::http_client::RemoteFileInfo;
+use crate::utils::constants::DEFAULT_FILE_INFO_TTL;
+
+/// Entries kept before the oldest are dropped
+const MAX_ENTRIES: usize = 512;
+
+/// Lookups since the app started, for the performance report
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileInfoCacheStats {
+    pub entries: usize,
+    /// Answered from a fresh entry
+    pub hits: u64,
+    /// Probed because nothing usable was cached
+    pub misses: u64,
+    /// Stale entries the server confirmed with 304 Not Modified
+    pub revalidated: u64,
+    /// Entries dropped because a download found the file changed
+    pub invalidated: u64,
+}
+
+/// What the cache knows about a URL
+#[derive(Debug, Clone, PartialEq)]
+pub enum CachedFileInfo {
+    Fresh(RemoteFileInfo),
+    /// Past the TTL, but has an ETag to revalidate with
+    Stale(RemoteFileInfo),
+    Missing,
+}
+
+pub struct FileInfoCache {
+    /// Zero turns the cache off
+    ttl_ms: AtomicU64,
+    entries: parking_lot::Mutex<HashMap<String, (RemoteFileInfo, Instant)>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    revalidated: AtomicU64,
+    invalidated: AtomicU64,
+}
+
+impl Default for FileInfoCache {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(DEFAULT_FILE_INFO_TTL))
+    }
+}
+
+impl FileInfoCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl_ms: AtomicU64::new(ttl.as_millis() as u64),
+            entries: parking_lot::Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            revalidated: AtomicU64::new(0),
+            invalidated: AtomicU64::new(0),
+        }
+    }
+
+    pub fn set_ttl(&self, ttl: Duration) {
+        self.ttl_ms.store(ttl.as_millis() as u64, Ordering::Relaxed);
+        if ttl.is_zero() {
+            self.entries.lock().clear();
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        Duration::from_millis(self.ttl_ms.load(Ordering::Relaxed))
+    }
+
+    /// Look `url` up as of `now`, counting a hit for a fresh entry. Stale
+    /// entries without an ETag can't be revalidated and are dropped.
+    pub fn lookup(&self, url: &str, now: Instant) -> CachedFileInfo {
+        let ttl = self.ttl();
+        if ttl.is_zero() {
+            return CachedFileInfo::Missing;
+        }
+        let mut entries = self.entries.lock();
+        let Some((info, stored)) = entries.get(url) else {
+            return CachedFileInfo::Missing;
+        };
+        if now.saturating_duration_since(*stored) <= ttl {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            CachedFileInfo::Fresh(info.clone())
+        } else if info.etag.is_some() {
+            CachedFileInfo::Stale(info.clone())
+        } else {
+            entries.remove(url);
+            CachedFileInfo::Missing
+        }
+    }
+
+    /// Keep a probe's result, fresh as of `now`
+    pub fn store(&self, url: &str, info: RemoteFileInfo, now: Instant) {
+        if self.ttl().is_zero() {
+            return;
+        }
+        let mut entries = self.entries.lock();
+        if entries.len() >= MAX_ENTRIES && !entries.contains_key(url) {
+            if let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, (_, stored))| *stored)
+                .map(|(url, _)| url.clone())
+            {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(url.to_string(), (info, now));
+    }
+
+    /// The server confirmed a stale entry is unchanged
+    pub fn refresh(&self, url: &str, now: Instant) {
+        if let Some((_, stored)) = self.entries.lock().get_mut(url) {
+            *stored = now;
+            self.revalidated.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn record_miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Forget `url`, e.g. after the file changed under a download
+    pub fn invalidate(&self, url: &str) {
+        if self.entries.lock().remove(url).is_some() {
+            self.invalidated.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn stats(&self) -> FileInfoCacheStats {
+        FileInfoCacheStats {
+            entries: self.entries.lock().len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            revalidated: self.revalidated.load(Ordering::Relaxed),
+            invalidated: self.invalidated.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(etag: Option<&str>) -> RemoteFileInfo {
+        RemoteFileInfo {
+            etag: etag.map(String::from),
+            total_size: Some(10),
+            ..RemoteFileInfo::unknown("https://example.com/a.bin")
+        }
+    }
+
+    #[test]
+    fn test_entries_go_stale_after_the_ttl() {
+        let cache = FileInfoCache::new(Duration::from_secs(60));
+        let start = Instant::now();
+        let url = "https://example.com/a.bin";
+        cache.store(url, info(Some("\"v1\"")), start);
+        cache.store("https://example.com/b.bin", info(None), start);
+
+        assert!(matches!(cache.lookup(url, start + Duration::from_secs(60)), CachedFileInfo::Fresh(_)));
+        let later = start + Duration::from_secs(61);
+        assert!(matches!(cache.lookup(url, later), CachedFileInfo::Stale(_)));
+        // Nothing to revalidate with
+        assert_eq!(cache.lookup("https://example.com/b.bin", later), CachedFileInfo::Missing);
+
+        cache.refresh(url, later);
+        assert!(matches!(cache.lookup(url, later), CachedFileInfo::Fresh(_)));
+        cache.invalidate(url);
+        assert_eq!(cache.lookup(url, later), CachedFileInfo::Missing);
+        assert_eq!(
+            cache.stats(),
+            FileInfoCacheStats { entries: 0, hits: 2, misses: 0, revalidated: 1, invalidated: 1 }
+        );
+    }
+
+    #[test]
+    fn test_zero_ttl_disables_the_cache() {
+        let cache = FileInfoCache::new(Duration::from_secs(60));
+        let now = Instant::now();
+        cache.store("https://example.com/a.bin", info(Some("\"v1\"")), now);
+        cache.set_ttl(Duration::ZERO);
+        assert_eq!(cache.lookup("https://example.com/a.bin", now), CachedFileInfo::Missing);
+        cache.store("https://example.com/a.bin", info(None), now);
+        assert_eq!(cache.stats().entries, 0);
+    }
+}
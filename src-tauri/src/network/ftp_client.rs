@@ -6,6 +6,7 @@ use suppaftp::types::FileType;
 use tokio::io::AsyncWriteExt;
 use tracing::{debug, info};
 
+use crate::network::remote_progress::{ProgressReporter, RemoteProgress, TransferControl};
 use crate::utils::error::DownloadError;
 
 /// FTP client for downloading files via FTP/FTPS
@@ -84,6 +85,51 @@ impl FtpClient {
         ))
     }
 
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn username(&self) -> Option<&str> {
+        self.username.as_deref()
+    }
+
+    pub fn uses_tls(&self) -> bool {
+        self.use_tls
+    }
+
+    /// Log in with `password` when the URL carried none
+    pub fn with_password(mut self, password: Option<String>) -> Self {
+        if self.password.is_none() {
+            self.password = password;
+        }
+        self
+    }
+
+    /// Connect, log in and send NOOP. Returns the server's welcome message.
+    pub async fn test_connection(&self) -> Result<Option<String>, DownloadError> {
+        if self.use_tls {
+            let mut ftp = self.connect_tls().await?;
+            let banner = ftp.get_welcome_msg().map(str::to_string);
+            ftp.noop()
+                .await
+                .map_err(|e| DownloadError::NetworkError(format!("NOOP failed: {}", e)))?;
+            let _ = ftp.quit().await;
+            Ok(banner)
+        } else {
+            let mut ftp = self.connect_plain().await?;
+            let banner = ftp.get_welcome_msg().map(str::to_string);
+            ftp.noop()
+                .await
+                .map_err(|e| DownloadError::NetworkError(format!("NOOP failed: {}", e)))?;
+            let _ = ftp.quit().await;
+            Ok(banner)
+        }
+    }
+
     /// Get file information from FTP server
     pub async fn get_file_info(&self, remote_path: &str) -> Result<FtpFileInfo, DownloadError> {
         if self.use_tls {
@@ -248,24 +294,30 @@ impl FtpClient {
 
     /// Download a file from FTP server
     /// Returns the number of bytes downloaded
-    pub async fn download_file(
+    /// `on_progress` is called as data arrives with the speed and ETA;
+    /// cancelling `control.cancel` stops the transfer mid-stream
+    pub async fn download_file<F: FnMut(RemoteProgress)>(
         &self,
         remote_path: &str,
         local_path: &PathBuf,
         resume_from: Option<u64>,
+        control: &TransferControl,
+        on_progress: F,
     ) -> Result<u64, DownloadError> {
         if self.use_tls {
-            self.download_file_tls(remote_path, local_path, resume_from).await
+            self.download_file_tls(remote_path, local_path, resume_from, control, on_progress).await
         } else {
-            self.download_file_plain(remote_path, local_path, resume_from).await
+            self.download_file_plain(remote_path, local_path, resume_from, control, on_progress).await
         }
     }
 
-    async fn download_file_plain(
+    async fn download_file_plain<F: FnMut(RemoteProgress)>(
         &self,
         remote_path: &str,
         local_path: &PathBuf,
         resume_from: Option<u64>,
+        control: &TransferControl,
+        on_progress: F,
     ) -> Result<u64, DownloadError> {
         let mut ftp = self.connect_plain().await?;
 
@@ -274,6 +326,9 @@ impl FtpClient {
             .await
             .map_err(|e| DownloadError::NetworkError(format!("Failed to set binary mode: {}", e)))?;
 
+        // SIZE is optional; without it there is no percentage or ETA
+        let total = ftp.size(remote_path).await.ok().map(|size| size as u64);
+
         // Open local file for writing (append if resuming)
         let mut file = if let Some(offset) = resume_from {
             info!("Resuming FTP download from byte {}", offset);
@@ -300,26 +355,9 @@ impl FtpClient {
             .map_err(|e| DownloadError::NetworkError(format!("Failed to retrieve file: {}", e)))?;
 
         // Read from stream and write to file
-        let mut total_bytes = resume_from.unwrap_or(0);
-        let mut buffer = vec![0u8; 8192];
-
-        loop {
-            match futures::io::AsyncReadExt::read(&mut stream, &mut buffer).await {
-                Ok(0) => break, // EOF
-                Ok(n) => {
-                    file.write_all(&buffer[..n])
-                        .await
-                        .map_err(|e| DownloadError::FileError(format!("Write error: {}", e)))?;
-                    total_bytes += n as u64;
-                }
-                Err(e) => {
-                    return Err(DownloadError::NetworkError(format!("Read error: {}", e)));
-                }
-            }
-        }
-
-        file.flush().await
-            .map_err(|e| DownloadError::FileError(format!("Flush error: {}", e)))?;
+        let mut reporter = ProgressReporter::new(resume_from.unwrap_or(0), total, on_progress);
+        copy_stream(&mut stream, &mut file, &mut reporter, control).await?;
+        let total_bytes = reporter.transferred();
 
         // Finalize transfer
         let _ = ftp.finalize_retr_stream(stream).await;
@@ -329,11 +367,13 @@ impl FtpClient {
         Ok(total_bytes)
     }
 
-    async fn download_file_tls(
+    async fn download_file_tls<F: FnMut(RemoteProgress)>(
         &self,
         remote_path: &str,
         local_path: &PathBuf,
         resume_from: Option<u64>,
+        control: &TransferControl,
+        on_progress: F,
     ) -> Result<u64, DownloadError> {
         let mut ftp = self.connect_tls().await?;
 
@@ -342,6 +382,9 @@ impl FtpClient {
             .await
             .map_err(|e| DownloadError::NetworkError(format!("Failed to set binary mode: {}", e)))?;
 
+        // SIZE is optional; without it there is no percentage or ETA
+        let total = ftp.size(remote_path).await.ok().map(|size| size as u64);
+
         // Open local file for writing (append if resuming)
         let mut file = if let Some(offset) = resume_from {
             info!("Resuming FTPS download from byte {}", offset);
@@ -368,26 +411,9 @@ impl FtpClient {
             .map_err(|e| DownloadError::NetworkError(format!("Failed to retrieve file: {}", e)))?;
 
         // Read from stream and write to file
-        let mut total_bytes = resume_from.unwrap_or(0);
-        let mut buffer = vec![0u8; 8192];
-
-        loop {
-            match futures::io::AsyncReadExt::read(&mut stream, &mut buffer).await {
-                Ok(0) => break, // EOF
-                Ok(n) => {
-                    file.write_all(&buffer[..n])
-                        .await
-                        .map_err(|e| DownloadError::FileError(format!("Write error: {}", e)))?;
-                    total_bytes += n as u64;
-                }
-                Err(e) => {
-                    return Err(DownloadError::NetworkError(format!("Read error: {}", e)));
-                }
-            }
-        }
-
-        file.flush().await
-            .map_err(|e| DownloadError::FileError(format!("Flush error: {}", e)))?;
+        let mut reporter = ProgressReporter::new(resume_from.unwrap_or(0), total, on_progress);
+        copy_stream(&mut stream, &mut file, &mut reporter, control).await?;
+        let total_bytes = reporter.transferred();
 
         // Finalize transfer
         let _ = ftp.finalize_retr_stream(stream).await;
@@ -443,6 +469,49 @@ impl FtpClient {
     }
 }
 
+/// Copy a RETR data stream into `file`, reporting progress, honoring the
+/// speed limit and stopping as soon as the transfer is cancelled
+async fn copy_stream<R, F>(
+    stream: &mut R,
+    file: &mut tokio::fs::File,
+    reporter: &mut ProgressReporter<F>,
+    control: &TransferControl,
+) -> Result<(), DownloadError>
+where
+    R: futures::io::AsyncRead + Unpin,
+    F: FnMut(RemoteProgress),
+{
+    let mut buffer = vec![0u8; 8192];
+
+    loop {
+        let read = tokio::select! {
+            _ = control.cancel.cancelled() => {
+                let _ = file.flush().await;
+                return Err(DownloadError::Cancelled);
+            }
+            read = futures::io::AsyncReadExt::read(stream, &mut buffer) => read,
+        };
+        match read {
+            Ok(0) => break, // EOF
+            Ok(n) => {
+                file.write_all(&buffer[..n])
+                    .await
+                    .map_err(|e| DownloadError::FileError(format!("Write error: {}", e)))?;
+                reporter.record(n as u64);
+                control.throttle(n).await;
+            }
+            Err(e) => {
+                return Err(DownloadError::NetworkError(format!("Read error: {}", e)));
+            }
+        }
+    }
+
+    file.flush().await
+        .map_err(|e| DownloadError::FileError(format!("Flush error: {}", e)))?;
+    reporter.report();
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -483,3 +552,4 @@ mod tests {
         assert_eq!(path, "/pub/file.tar.gz");
     }
 }
+
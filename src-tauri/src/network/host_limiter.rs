@@ -0,0 +1,78 @@
+// src-tauri/src/network/host_limiter.rs
+// Caps how many file info probes run against one host at a time. Probes
+// come from the add dialog, batch adds and page media lists alike; a page
+// with hundreds of images on one CDN would otherwise open a connection per
+// image at once.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::utils::constants::MAX_PROBES_PER_HOST;
+
+pub struct HostLimiter {
+    per_host: usize,
+    hosts: parking_lot::Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl Default for HostLimiter {
+    fn default() -> Self {
+        Self::new(MAX_PROBES_PER_HOST)
+    }
+}
+
+impl HostLimiter {
+    pub fn new(per_host: usize) -> Self {
+        Self {
+            per_host: per_host.max(1),
+            hosts: parking_lot::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wait for a free slot on `url`'s host; held until the permit drops.
+    /// URLs without a host share one slot pool.
+    pub async fn acquire(&self, url: &str) -> OwnedSemaphorePermit {
+        let host = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_ascii_lowercase))
+            .unwrap_or_default();
+        let semaphore = {
+            let mut hosts = self.hosts.lock();
+            // Hosts nobody is waiting on or holding are forgotten
+            hosts.retain(|_, semaphore| Arc::strong_count(semaphore) > 1);
+            hosts
+                .entry(host)
+                .or_insert_with(|| Arc::new(Semaphore::new(self.per_host)))
+                .clone()
+        };
+        semaphore.acquire_owned().await.expect("host semaphores are never closed")
+    }
+
+    /// Hosts with probes running or waiting
+    pub fn busy_hosts(&self) -> usize {
+        self.hosts.lock().values().filter(|semaphore| Arc::strong_count(semaphore) > 1).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_one_host_is_capped_others_are_not() {
+        let limiter = HostLimiter::new(2);
+        let a = limiter.acquire("https://cdn.example.com/1.jpg").await;
+        let _b = limiter.acquire("https://CDN.example.com/2.jpg").await;
+
+        // A third probe of the same host waits, another host doesn't
+        let third = tokio::time::timeout(Duration::from_millis(50), limiter.acquire("https://cdn.example.com/3.jpg"));
+        assert!(third.await.is_err());
+        let _other = limiter.acquire("https://img.example.org/1.jpg").await;
+        assert_eq!(limiter.busy_hosts(), 2);
+
+        drop(a);
+        let third = tokio::time::timeout(Duration::from_millis(50), limiter.acquire("https://cdn.example.com/3.jpg"));
+        assert!(third.await.is_ok());
+    }
+}
@@ -1,14 +1,18 @@
 // src-tauri/src/network/http_client.rs
 
-use reqwest::{Client, Response, header};
-use std::time::Duration;
+use reqwest::{Client, Response, StatusCode, header};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use crate::utils::constants::*;
 use crate::utils::error::DownloadError;
 use crate::network::url_parser::UrlParser;
 use crate::network::proxy_manager::ProxyConfig;
+use crate::network::file_info_cache::{CachedFileInfo, FileInfoCache, FileInfoCacheStats};
+use crate::network::host_limiter::HostLimiter;
 
 /// Information about a remote file
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct RemoteFileInfo {
     pub url: String,
     pub file_name: String,
@@ -18,12 +22,69 @@ pub struct RemoteFileInfo {
     pub etag: Option<String>,
     pub last_modified: Option<String>,
     pub redirect_url: Option<String>,
+    /// Raw `Accept-Ranges` and `Server` headers
+    #[serde(default)]
+    pub accept_ranges: Option<String>,
+    #[serde(default)]
+    pub server: Option<String>,
+    /// Requested over HTTPS but redirected to plain HTTP
+    #[serde(default)]
+    pub https_downgraded: bool,
+}
+
+impl RemoteFileInfo {
+    /// Placeholder for a download added without probing the server; the
+    /// transfer itself finds out the size
+    pub fn unknown(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            file_name: UrlParser::parse(url)
+                .map(|p| p.filename)
+                .unwrap_or_else(|_| "download".to_string()),
+            total_size: None,
+            supports_range: false,
+            content_type: None,
+            etag: None,
+            last_modified: None,
+            redirect_url: None,
+            accept_ranges: None,
+            server: None,
+            https_downgraded: false,
+        }
+    }
+
+    /// Ranges are served and the file version can be checked, so a
+    /// partial download can be continued safely
+    pub fn supports_resume(&self) -> bool {
+        self.supports_range && (self.etag.is_some() || self.last_modified.is_some())
+    }
+}
+
+/// How much an add request depends on probing the server first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeMode {
+    /// Add without contacting the server
+    None,
+    /// Probe, but add with unknown size if the probe fails
+    BestEffort,
+    /// Fail the add when the probe fails
+    #[default]
+    Required,
 }
 
 /// HTTP client wrapper with retry and proxy support
 #[derive(Clone)]
 pub struct HttpClient {
     client: Client,
+    /// Same settings, but redirects are returned instead of followed
+    no_redirect_client: Client,
+    /// Overall budget for a file info probe, in milliseconds
+    probe_timeout_ms: Arc<AtomicU64>,
+    /// Recent probe results, shared by every clone
+    file_info_cache: Arc<FileInfoCache>,
+    /// Probes allowed per host at once, shared by every clone
+    host_limiter: Arc<HostLimiter>,
 }
 
 impl HttpClient {
@@ -31,13 +92,26 @@ impl HttpClient {
     pub fn new(
         proxy_config: Option<&ProxyConfig>,
     ) -> Result<Self, DownloadError> {
+        Ok(Self {
+            client: Self::build(proxy_config, reqwest::redirect::Policy::limited(10))?,
+            no_redirect_client: Self::build(proxy_config, reqwest::redirect::Policy::none())?,
+            probe_timeout_ms: Arc::new(AtomicU64::new(DEFAULT_PROBE_TIMEOUT * 1000)),
+            file_info_cache: Arc::new(FileInfoCache::default()),
+            host_limiter: Arc::new(HostLimiter::default()),
+        })
+    }
+
+    fn build(
+        proxy_config: Option<&ProxyConfig>,
+        redirect: reqwest::redirect::Policy,
+    ) -> Result<Client, DownloadError> {
         let mut builder = Client::builder()
             .user_agent(USER_AGENT)
             .connect_timeout(Duration::from_secs(DEFAULT_CONNECT_TIMEOUT))
             .timeout(Duration::from_secs(DEFAULT_READ_TIMEOUT))
             .pool_max_idle_per_host(10)
             .pool_idle_timeout(Duration::from_secs(90))
-            .redirect(reqwest::redirect::Policy::limited(10))
+            .redirect(redirect)
             .gzip(true);
 
         // Configure proxy
@@ -54,52 +128,208 @@ impl HttpClient {
             }
         }
 
-        let client = builder.build()
+        builder.build()
             .map_err(|e| DownloadError::NetworkError(
                 format!("Failed to build HTTP client: {}", e)
-            ))?;
+            ))
+    }
+
+    /// The underlying client, for requests that aren't downloads
+    pub fn client(&self) -> Client {
+        self.client.clone()
+    }
+
+    /// Client that hands back redirects instead of following them
+    pub fn without_redirects(&self) -> Client {
+        self.no_redirect_client.clone()
+    }
+
+    /// Overall time allowed for `get_file_info`, shared by every clone
+    pub fn set_probe_timeout(&self, timeout: Duration) {
+        self.probe_timeout_ms.store(timeout.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    pub fn probe_timeout(&self) -> Duration {
+        Duration::from_millis(self.probe_timeout_ms.load(Ordering::Relaxed))
+    }
+
+    /// How long a probe's result is reused; zero probes every time
+    pub fn set_file_info_ttl(&self, ttl: Duration) {
+        self.file_info_cache.set_ttl(ttl);
+    }
 
-        Ok(Self { client })
+    /// Drop the cached file info for `url`, so the next probe asks the
+    /// server
+    pub fn invalidate_file_info(&self, url: &str) {
+        self.file_info_cache.invalidate(url);
     }
 
-    /// Get file information using HEAD request
+    pub fn file_info_cache_stats(&self) -> FileInfoCacheStats {
+        self.file_info_cache.stats()
+    }
+
+    /// Get file information, from the cache while it is fresh. A stale
+    /// entry is revalidated with its ETag and kept on 304 Not Modified;
+    /// anything else probes the server again.
     pub async fn get_file_info(
         &self,
         url: &str,
     ) -> Result<RemoteFileInfo, DownloadError> {
+        self.get_file_info_with_referrer(url, None).await
+    }
+
+    /// `get_file_info`, sending `referrer` for servers that refuse
+    /// requests from outside their own pages
+    pub async fn get_file_info_with_referrer(
+        &self,
+        url: &str,
+        referrer: Option<&str>,
+    ) -> Result<RemoteFileInfo, DownloadError> {
+        match self.file_info_cache.lookup(url, Instant::now()) {
+            CachedFileInfo::Fresh(info) => {
+                tracing::debug!("File info for {} from cache", url);
+                return Ok(info);
+            }
+            CachedFileInfo::Stale(info) => {
+                if self.still_current(url, &info, referrer).await {
+                    self.file_info_cache.refresh(url, Instant::now());
+                    return Ok(info);
+                }
+            }
+            CachedFileInfo::Missing => {}
+        }
+
+        self.file_info_cache.record_miss();
+        let info = self.probe_file_info(url, referrer).await?;
+        self.file_info_cache.store(url, info.clone(), Instant::now());
+        Ok(info)
+    }
+
+    /// Ask the server with a conditional HEAD whether `info` still
+    /// describes the file. Any answer but 304 means probing again.
+    async fn still_current(&self, url: &str, info: &RemoteFileInfo, referrer: Option<&str>) -> bool {
+        let Some(etag) = &info.etag else {
+            return false;
+        };
+        let _permit = self.host_limiter.acquire(url).await;
+        let request = self
+            .request(reqwest::Method::HEAD, url, referrer)
+            .header(header::IF_NONE_MATCH, etag)
+            .send();
+        match tokio::time::timeout(self.probe_timeout() / 2, request).await {
+            Ok(Ok(response)) => response.status() == StatusCode::NOT_MODIFIED,
+            _ => false,
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, url: &str, referrer: Option<&str>) -> reqwest::RequestBuilder {
+        let request = self.client.request(method, url);
+        match referrer {
+            Some(referrer) => request.header(header::REFERER, referrer),
+            None => request,
+        }
+    }
+
+    /// Get file information with a HEAD request, falling back to a
+    /// one-byte ranged GET when HEAD is refused, fails or is too slow.
+    /// The whole probe is bounded by the probe timeout, which starts once
+    /// the host has a free probe slot.
+    async fn probe_file_info(
+        &self,
+        url: &str,
+        referrer: Option<&str>,
+    ) -> Result<RemoteFileInfo, DownloadError> {
+        let _permit = self.host_limiter.acquire(url).await;
         tracing::debug!("Fetching file info: {}", url);
 
-        // First try HEAD request
-        let response = self.client
-            .head(url)
-            .send()
-            .await
-            .map_err(|e| DownloadError::NetworkError(e.to_string()))?;
+        let budget = self.probe_timeout();
+        let started = Instant::now();
+        let timed_out = || DownloadError::Timeout { seconds: budget.as_secs() };
+
+        // HEAD gets half the budget so a hanging server leaves time for GET
+        let head = tokio::time::timeout(budget / 2, self.request(reqwest::Method::HEAD, url, referrer).send()).await;
+        let response = match head {
+            Ok(Ok(response)) if !matches!(
+                response.status(),
+                StatusCode::METHOD_NOT_ALLOWED | StatusCode::FORBIDDEN
+            ) => response,
+            head => {
+                match &head {
+                    Ok(Ok(response)) => tracing::debug!("HEAD {} returned {}, trying GET", url, response.status()),
+                    Ok(Err(e)) => tracing::debug!("HEAD {} failed ({}), trying GET", url, e),
+                    Err(_) => tracing::debug!("HEAD {} timed out, trying GET", url),
+                }
+                let remaining = budget.saturating_sub(started.elapsed());
+                let get = self
+                    .request(reqwest::Method::GET, url, referrer)
+                    .header(header::RANGE, "bytes=0-0")
+                    .send();
+                tokio::time::timeout(remaining, get)
+                    .await
+                    .map_err(|_| timed_out())?
+                    .map_err(|e| DownloadError::from_request(&e))?
+            }
+        };
 
-        // Check for HTTP errors
-        if !response.status().is_success() && !response.status().is_redirection() {
+        // Check for HTTP errors. The ranged GET for an empty file is
+        // refused with 416 and "Content-Range: bytes */0", which still
+        // answers the question.
+        let empty_file = response.status() == StatusCode::RANGE_NOT_SATISFIABLE
+            && Self::content_range_total(&response) == Some(0);
+        if !response.status().is_success() && !response.status().is_redirection() && !empty_file {
             return Err(DownloadError::ServerError {
                 status: response.status().as_u16(),
                 message: response.status().to_string(),
             });
         }
 
-        let headers = response.headers().clone();
+        let info = Self::file_info_from_response(url, &response);
+
+        tracing::info!(
+            "File info: name={}, size={:?}, range_support={}, type={:?}",
+            info.file_name,
+            info.total_size,
+            info.supports_range,
+            info.content_type,
+        );
+
+        Ok(info)
+    }
+
+    /// Read file info from the headers of a HEAD or ranged GET response.
+    /// The body is never read.
+    fn file_info_from_response(url: &str, response: &Response) -> RemoteFileInfo {
+        let headers = response.headers();
         let final_url = response.url().to_string();
+        let partial = response.status() == StatusCode::PARTIAL_CONTENT;
 
-        // Extract file size
-        let total_size = headers
-            .get(header::CONTENT_LENGTH)
-            .and_then(|v| v.to_str().ok())
-            .and_then(|v| v.parse::<u64>().ok());
+        // Extract file size: a 206 carries it in Content-Range
+        // ("bytes 0-0/12345"), as does a 416 ("bytes */0");
+        // Content-Length is just the range or the error body then
+        let total_size = if partial || response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+            Self::content_range_total(response)
+        } else {
+            headers
+                .get(header::CONTENT_LENGTH)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok())
+        };
 
         // Check range support
-        let supports_range = headers
+        let accept_ranges = headers
             .get(header::ACCEPT_RANGES)
             .and_then(|v| v.to_str().ok())
+            .map(String::from);
+        let supports_range = partial || accept_ranges
+            .as_deref()
             .map(|v| v.contains("bytes"))
             .unwrap_or(false);
 
+        let server = headers
+            .get(header::SERVER)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+
         // Extract filename
         let file_name = headers
             .get(header::CONTENT_DISPOSITION)
@@ -129,13 +359,14 @@ impl HttpClient {
             .and_then(|v| v.to_str().ok())
             .map(String::from);
 
+        let https_downgraded = url.starts_with("https://") && final_url.starts_with("http://");
         let redirect_url = if final_url != url {
             Some(final_url)
         } else {
             None
         };
 
-        let info = RemoteFileInfo {
+        RemoteFileInfo {
             url: redirect_url.clone().unwrap_or_else(|| url.to_string()),
             file_name,
             total_size,
@@ -144,35 +375,39 @@ impl HttpClient {
             etag,
             last_modified,
             redirect_url,
-        };
-
-        tracing::info!(
-            "File info: name={}, size={:?}, range_support={}, type={:?}",
-            info.file_name,
-            info.total_size,
-            info.supports_range,
-            info.content_type,
-        );
+            accept_ranges,
+            server,
+            https_downgraded,
+        }
+    }
 
-        Ok(info)
+    /// The complete length from a Content-Range header
+    fn content_range_total(response: &Response) -> Option<u64> {
+        response
+            .headers()
+            .get(header::CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.rsplit_once('/'))
+            .and_then(|(_, total)| total.trim().parse::<u64>().ok())
     }
 
     /// Start a GET request with optional range header
     pub async fn get_range(
         &self,
         url: &str,
+        referrer: Option<&str>,
         start: u64,
         end: u64,
     ) -> Result<Response, DownloadError> {
         let range = format!("bytes={}-{}", start, end);
         tracing::debug!("GET {} Range: {}", url, range);
 
-        let response = self.client
-            .get(url)
+        let response = self
+            .request(reqwest::Method::GET, url, referrer)
             .header(header::RANGE, range)
             .send()
             .await
-            .map_err(|e| DownloadError::NetworkError(e.to_string()))?;
+            .map_err(|e| DownloadError::from_request(&e))?;
 
         if !response.status().is_success() {
             return Err(DownloadError::ServerError {
@@ -188,14 +423,15 @@ impl HttpClient {
     pub async fn get_full(
         &self,
         url: &str,
+        referrer: Option<&str>,
     ) -> Result<Response, DownloadError> {
         tracing::debug!("GET (full) {}", url);
 
-        let response = self.client
-            .get(url)
+        let response = self
+            .request(reqwest::Method::GET, url, referrer)
             .send()
             .await
-            .map_err(|e| DownloadError::NetworkError(e.to_string()))?;
+            .map_err(|e| DownloadError::from_request(&e))?;
 
         if !response.status().is_success() {
             return Err(DownloadError::ServerError {
@@ -211,17 +447,18 @@ impl HttpClient {
     pub async fn get_resume(
         &self,
         url: &str,
+        referrer: Option<&str>,
         from_byte: u64,
     ) -> Result<Response, DownloadError> {
         let range = format!("bytes={}-", from_byte);
         tracing::debug!("GET (resume) {} Range: {}", url, range);
 
-        let response = self.client
-            .get(url)
+        let response = self
+            .request(reqwest::Method::GET, url, referrer)
             .header(header::RANGE, range)
             .send()
             .await
-            .map_err(|e| DownloadError::NetworkError(e.to_string()))?;
+            .map_err(|e| DownloadError::from_request(&e))?;
 
         // 206 Partial Content = resume successful
         // 200 OK = server doesn't support resume, sending full file
@@ -238,22 +475,211 @@ impl HttpClient {
     }
 
     /// Perform a simple GET request
-    pub async fn get(&self, url: &str) -> Result<Response, DownloadError> {
-        self.client
-            .get(url)
+    pub async fn get(&self, url: &str, referrer: Option<&str>) -> Result<Response, DownloadError> {
+        self.request(reqwest::Method::GET, url, referrer)
             .send()
             .await
-            .map_err(|e| DownloadError::NetworkError(e.to_string()))
+            .map_err(|e| DownloadError::from_request(&e))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve `respond(method)` for every request; None never answers
+    async fn mock_server(respond: fn(&str) -> Option<&'static str>) -> String {
+        mock_server_with(move |method, _| respond(method)).await
+    }
+
+    /// Like `mock_server`, but `respond` also sees the request's headers
+    async fn mock_server_with(
+        respond: impl Fn(&str, &str) -> Option<&'static str> + Copy + Send + Sync + 'static,
+    ) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 1024];
+                    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                        match socket.read(&mut buf).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => request.extend_from_slice(&buf[..n]),
+                        }
+                    }
+                    let head = String::from_utf8_lossy(&request).to_ascii_lowercase();
+                    let method = head.split(' ').next().unwrap_or_default().to_ascii_uppercase();
+                    match respond(&method, &head) {
+                        Some(response) => {
+                            let _ = socket.write_all(response.as_bytes()).await;
+                        }
+                        None => tokio::time::sleep(Duration::from_secs(60)).await,
+                    }
+                });
+            }
+        });
+        format!("http://{}/files/archive.zip", addr)
+    }
+
+    const RANGED: &str = "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 0-0/12345\r\n\
+        Content-Length: 1\r\nContent-Type: application/zip\r\nConnection: close\r\n\r\nP";
+
+    const HEAD_OK: &str = "HTTP/1.1 200 OK\r\nContent-Length: 12345\r\nAccept-Ranges: none\r\n\
+        Server: nginx/1.25\r\nETag: \"abc\"\r\nConnection: close\r\n\r\n";
 
     #[tokio::test]
     async fn test_client_creation() {
         let client = HttpClient::new(None);
         assert!(client.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_head_405_falls_back_to_ranged_get() {
+        let url = mock_server(|method| match method {
+            "HEAD" => Some("HTTP/1.1 405 Method Not Allowed\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"),
+            _ => Some(RANGED),
+        })
+        .await;
+
+        let info = HttpClient::new(None).unwrap().get_file_info(&url).await.unwrap();
+        assert_eq!(info.total_size, Some(12345));
+        assert!(info.supports_range);
+        assert_eq!(info.file_name, "archive.zip");
+        assert_eq!(info.content_type.as_deref(), Some("application/zip"));
+    }
+
+    #[tokio::test]
+    async fn test_capability_headers_are_captured() {
+        let url = mock_server(|_| Some(HEAD_OK)).await;
+
+        let info = HttpClient::new(None).unwrap().get_file_info(&url).await.unwrap();
+        assert_eq!(info.accept_ranges.as_deref(), Some("none"));
+        assert_eq!(info.server.as_deref(), Some("nginx/1.25"));
+        assert!(!info.supports_range);
+        assert!(!info.supports_resume());
+        assert!(!info.https_downgraded);
+    }
+
+    #[tokio::test]
+    async fn test_slow_head_falls_back_within_budget() {
+        let url = mock_server(|method| (method != "HEAD").then_some(RANGED)).await;
+        let client = HttpClient::new(None).unwrap();
+        client.set_probe_timeout(Duration::from_millis(400));
+
+        let started = Instant::now();
+        let info = client.get_file_info(&url).await.unwrap();
+        assert_eq!(info.total_size, Some(12345));
+        assert!(started.elapsed() < Duration::from_millis(400));
+    }
+
+    #[tokio::test]
+    async fn test_unresponsive_server_times_out() {
+        let url = mock_server(|_| None).await;
+        let client = HttpClient::new(None).unwrap();
+        client.set_probe_timeout(Duration::from_millis(300));
+
+        let started = Instant::now();
+        let result = client.get_file_info(&url).await;
+        assert!(matches!(result, Err(DownloadError::Timeout { .. })));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_empty_file_probe_is_not_an_error() {
+        // HEAD refused, and the ranged GET can't be satisfied by an empty file
+        let url = mock_server(|method| match method {
+            "HEAD" => Some("HTTP/1.1 405 Method Not Allowed\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"),
+            _ => Some("HTTP/1.1 416 Range Not Satisfiable\r\nContent-Range: bytes */0\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"),
+        })
+        .await;
+        let info = HttpClient::new(None).unwrap().get_file_info(&url).await.unwrap();
+        assert_eq!(info.total_size, Some(0));
+
+        // A 416 for a file that has bytes is still an error
+        let url = mock_server(|method| match method {
+            "HEAD" => Some("HTTP/1.1 405 Method Not Allowed\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"),
+            _ => Some("HTTP/1.1 416 Range Not Satisfiable\r\nContent-Range: bytes */500\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"),
+        })
+        .await;
+        let result = HttpClient::new(None).unwrap().get_file_info(&url).await;
+        assert!(matches!(result, Err(DownloadError::ServerError { status: 416, .. })));
+    }
+
+    #[tokio::test]
+    async fn test_repeated_probes_hit_the_cache() {
+        static HEADS: AtomicU64 = AtomicU64::new(0);
+        let url = mock_server(|_| {
+            HEADS.fetch_add(1, Ordering::SeqCst);
+            Some(HEAD_OK)
+        })
+        .await;
+        let client = HttpClient::new(None).unwrap();
+
+        let first = client.get_file_info(&url).await.unwrap();
+        assert_eq!(client.clone().get_file_info(&url).await.unwrap(), first);
+        assert_eq!(HEADS.load(Ordering::SeqCst), 1);
+
+        client.invalidate_file_info(&url);
+        client.get_file_info(&url).await.unwrap();
+        assert_eq!(HEADS.load(Ordering::SeqCst), 2);
+        let stats = client.file_info_cache_stats();
+        assert_eq!((stats.hits, stats.misses, stats.invalidated), (1, 2, 1));
+    }
+
+    #[tokio::test]
+    async fn test_stale_entry_is_revalidated_with_its_etag() {
+        static HEADS: AtomicU64 = AtomicU64::new(0);
+        let url = mock_server_with(|_, head| {
+            HEADS.fetch_add(1, Ordering::SeqCst);
+            if head.contains("if-none-match: \"abc\"") {
+                Some("HTTP/1.1 304 Not Modified\r\nETag: \"abc\"\r\nConnection: close\r\n\r\n")
+            } else {
+                Some(HEAD_OK)
+            }
+        })
+        .await;
+        let client = HttpClient::new(None).unwrap();
+        client.set_file_info_ttl(Duration::from_millis(20));
+
+        let first = client.get_file_info(&url).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert_eq!(client.get_file_info(&url).await.unwrap(), first);
+        assert_eq!(HEADS.load(Ordering::SeqCst), 2);
+        let stats = client.file_info_cache_stats();
+        assert_eq!((stats.misses, stats.revalidated), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_referrer_is_sent_with_the_probe_and_transfers() {
+        // Hotlink protection: only requests from the gallery are served
+        let url = mock_server_with(|_, head| {
+            if head.contains("referer: https://gallery.example.com/album") {
+                Some(HEAD_OK)
+            } else {
+                Some("HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+            }
+        })
+        .await;
+        let client = HttpClient::new(None).unwrap();
+        client.set_file_info_ttl(Duration::ZERO);
+
+        assert!(client.get_file_info(&url).await.is_err());
+        let info = client
+            .get_file_info_with_referrer(&url, Some("https://gallery.example.com/album"))
+            .await
+            .unwrap();
+        assert_eq!(info.total_size, Some(12345));
+
+        // The transfer needs it as much as the probe did
+        let page = Some("https://gallery.example.com/album");
+        assert!(client.get_range(&url, None, 0, 99).await.is_err());
+        assert!(client.get_range(&url, page, 0, 99).await.is_ok());
+        assert!(client.get_full(&url, None).await.is_err());
+        assert!(client.get_full(&url, page).await.is_ok());
+        assert!(client.get(&url, page).await.is_ok());
+    }
 }
@@ -1,4 +1,6 @@
 pub mod http_client;
+pub mod file_info_cache;
+pub mod host_limiter;
 pub mod ftp_client;
 pub mod sftp_client;
 pub mod torrent_client;
@@ -6,7 +8,13 @@ pub mod torrent_client_librqbit;
 pub mod bencode_parser;
 pub mod torrent_helpers;
 pub mod torrent_advanced;
+pub mod torrent_fetch;
 pub mod proxy_manager;
 pub mod url_parser;
+pub mod resolvers;
+pub mod url_refresh;
 pub mod connection;
-pub mod youtube_downloader;
\ No newline at end of file
+pub mod remote_progress;
+pub mod remote_url;
+pub mod youtube_downloader;
+pub mod speed_test;
\ No newline at end of file
@@ -0,0 +1,191 @@
+// src-tauri/src/network/remote_progress.rs
+// Progress, throttling and cancellation shared by the FTP and SFTP read
+// loops, which stream a single file rather than going through segments.
+
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::core::content_filter::SkippedFile;
+use crate::core::download_task::{DownloadProgress, DownloadStatus};
+use crate::core::speed_limiter::SpeedLimiter;
+use crate::core::speed_tracker::SpeedTracker;
+
+/// Report at least this often while data flows
+pub const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// ... or after this many bytes, whichever comes first
+pub const PROGRESS_BYTES: u64 = 4 * 1024 * 1024;
+
+/// A progress report from an FTP or SFTP transfer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RemoteProgress {
+    /// Bytes in the local file, including any resumed prefix
+    pub transferred: u64,
+    /// From SIZE or stat, when the server reports it
+    pub total: Option<u64>,
+    /// Bytes per second over the recent window
+    pub speed: f64,
+    /// Seconds left at the current speed
+    pub eta: Option<u64>,
+}
+
+impl RemoteProgress {
+    pub fn percent(&self) -> f64 {
+        match self.total {
+            Some(total) if total > 0 => (self.transferred as f64 * 100.0 / total as f64).min(100.0),
+            _ => 0.0,
+        }
+    }
+
+    /// The standard progress event for the transfer `id`
+    pub fn to_download_progress(&self, id: uuid::Uuid, status: DownloadStatus) -> DownloadProgress {
+        DownloadProgress {
+            id,
+            downloaded_size: self.transferred,
+            total_size: self.total,
+            speed: self.speed,
+            eta: self.eta,
+            status,
+            percent: self.percent(),
+            error_message: None,
+            phase_progress: None,
+            category: None,
+        }
+    }
+}
+
+/// Outcome of downloading a remote folder
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryDownloadSummary {
+    pub downloaded: Vec<String>,
+    /// Left out by the content filter
+    pub skipped: Vec<SkippedFile>,
+    pub failed: Vec<DirectoryDownloadFailure>,
+    pub bytes: u64,
+    /// Stopped by `cancel_download` before every file was tried
+    pub cancelled: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryDownloadFailure {
+    pub name: String,
+    pub reason: String,
+}
+
+/// The local name for a remote directory entry; None for names that
+/// would leave the target folder, like `..`
+pub fn local_file_name(remote_name: &str) -> Option<&str> {
+    let name = std::path::Path::new(remote_name).file_name()?.to_str()?;
+    (name == remote_name.trim_end_matches('/')).then_some(name)
+}
+
+/// How a transfer is interrupted and throttled
+#[derive(Clone, Default)]
+pub struct TransferControl {
+    pub cancel: CancellationToken,
+    /// Shares the global speed limit with other downloads when set
+    pub limiter: Option<SpeedLimiter>,
+}
+
+impl TransferControl {
+    /// Wait out the speed limit for `bytes` just written
+    pub async fn throttle(&self, bytes: usize) {
+        if let Some(limiter) = &self.limiter {
+            limiter.throttle(bytes).await;
+        }
+    }
+}
+
+/// Turns byte counts from a read loop into throttled progress reports
+pub struct ProgressReporter<F: FnMut(RemoteProgress)> {
+    tracker: SpeedTracker,
+    transferred: u64,
+    total: Option<u64>,
+    last_report: Instant,
+    unreported: u64,
+    on_progress: F,
+}
+
+impl<F: FnMut(RemoteProgress)> ProgressReporter<F> {
+    pub fn new(resumed_from: u64, total: Option<u64>, on_progress: F) -> Self {
+        Self {
+            tracker: SpeedTracker::new(),
+            transferred: resumed_from,
+            total,
+            last_report: Instant::now(),
+            unreported: 0,
+            on_progress,
+        }
+    }
+
+    pub fn transferred(&self) -> u64 {
+        self.transferred
+    }
+
+    /// Count bytes written, reporting when enough time or data has passed
+    pub fn record(&mut self, bytes: u64) {
+        self.transferred += bytes;
+        self.unreported += bytes;
+        self.tracker.add_bytes(bytes);
+        if self.unreported >= PROGRESS_BYTES || self.last_report.elapsed() >= PROGRESS_INTERVAL {
+            self.report();
+        }
+    }
+
+    /// Report where the transfer stands now
+    pub fn report(&mut self) {
+        let speed = self.tracker.speed();
+        let eta = self
+            .total
+            .and_then(|total| self.tracker.eta(total.saturating_sub(self.transferred)));
+        (self.on_progress)(RemoteProgress {
+            transferred: self.transferred,
+            total: self.total,
+            speed,
+            eta,
+        });
+        self.last_report = Instant::now();
+        self.unreported = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports_by_bytes_and_at_the_end() {
+        let mut reports = Vec::new();
+        let mut reporter = ProgressReporter::new(100, Some(100 + PROGRESS_BYTES * 2), |p| reports.push(p));
+
+        reporter.record(1024);
+        reporter.record(PROGRESS_BYTES);
+        reporter.record(PROGRESS_BYTES - 1024);
+        reporter.report();
+        assert_eq!(reporter.transferred(), 100 + PROGRESS_BYTES * 2);
+        drop(reporter);
+
+        // The small first read waits; crossing the byte threshold reports
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].transferred, 100 + 1024 + PROGRESS_BYTES);
+        let last = reports[1];
+        assert_eq!(last.percent(), 100.0);
+        assert_eq!(last.eta, Some(0).filter(|_| last.speed > 0.0));
+    }
+
+    #[test]
+    fn test_local_file_name() {
+        assert_eq!(local_file_name("movie.mkv"), Some("movie.mkv"));
+        assert_eq!(local_file_name("../etc/passwd"), None);
+        assert_eq!(local_file_name(".."), None);
+    }
+
+    #[test]
+    fn test_percent_without_total() {
+        let progress = RemoteProgress { transferred: 10, total: None, speed: 0.0, eta: None };
+        assert_eq!(progress.percent(), 0.0);
+    }
+}
@@ -0,0 +1,379 @@
+// src-tauri/src/network/resolvers.rs
+
+use regex::Regex;
+use reqwest::{header, Client, Response, StatusCode};
+use serde::Serialize;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use url::Url;
+
+use crate::utils::constants::DEFAULT_MAX_RESOLVE_HOPS;
+use crate::utils::error::DownloadError;
+
+/// How much of an HTML page is searched for a meta refresh
+const META_REFRESH_SCAN_BYTES: usize = 64 * 1024;
+
+pub type ResolveFuture<'a> = Pin<Box<dyn Future<Output = Result<ResolvedUrl, DownloadError>> + Send + 'a>>;
+
+/// Where a link leads, and the URLs passed through on the way
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedUrl {
+    pub url: String,
+    /// URLs visited before `url`, starting with the one resolved
+    pub chain: Vec<String>,
+}
+
+impl ResolvedUrl {
+    /// Nothing to resolve
+    pub fn unchanged(url: &str) -> Self {
+        Self { url: url.to_string(), chain: Vec::new() }
+    }
+
+    /// One step from `from` to `to`
+    pub fn hop(from: &str, to: String) -> Self {
+        Self { url: to, chain: vec![from.to_string()] }
+    }
+
+    pub fn is_unchanged(&self) -> bool {
+        self.chain.is_empty()
+    }
+
+    /// Some hop moved from HTTPS to plain HTTP
+    pub fn downgrades_https(&self) -> bool {
+        let mut hops = self.chain.iter().chain(std::iter::once(&self.url));
+        hops.by_ref().any(|hop| hop.starts_with("https://"))
+            && hops.any(|hop| hop.starts_with("http://"))
+    }
+}
+
+/// Turns a link into the URL of the file behind it (shorteners, redirect
+/// pages, file host landing pages)
+pub trait UrlResolver: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Whether this resolver handles `url` at all
+    fn matches(&self, url: &Url) -> bool;
+
+    /// Take one or more steps from `url`, or return it unchanged. `client`
+    /// does not follow redirects.
+    fn resolve<'a>(&'a self, url: &'a str, client: &'a Client) -> ResolveFuture<'a>;
+}
+
+fn is_http(url: &Url) -> bool {
+    matches!(url.scheme(), "http" | "https")
+}
+
+fn network_error(e: reqwest::Error) -> DownloadError {
+    DownloadError::NetworkError(e.to_string())
+}
+
+/// `Location` of a redirect response, made absolute
+fn redirect_target(url: &str, response: &Response) -> Option<String> {
+    if !response.status().is_redirection() {
+        return None;
+    }
+    let location = response.headers().get(header::LOCATION)?.to_str().ok()?;
+    Url::parse(url).ok()?.join(location.trim()).ok().map(String::from)
+}
+
+/// Follows HTTP redirects one hop at a time so each hop is recorded.
+/// Servers refusing HEAD are asked with GET; the body is never read.
+pub struct RedirectResolver;
+
+impl UrlResolver for RedirectResolver {
+    fn name(&self) -> &str {
+        "redirect"
+    }
+
+    fn matches(&self, url: &Url) -> bool {
+        is_http(url)
+    }
+
+    fn resolve<'a>(&'a self, url: &'a str, client: &'a Client) -> ResolveFuture<'a> {
+        Box::pin(async move {
+            let head = client.head(url).send().await;
+            let response = match head {
+                Ok(response) if !matches!(
+                    response.status(),
+                    StatusCode::METHOD_NOT_ALLOWED | StatusCode::FORBIDDEN | StatusCode::NOT_IMPLEMENTED
+                ) => response,
+                _ => client.get(url).send().await.map_err(network_error)?,
+            };
+
+            Ok(match redirect_target(url, &response) {
+                Some(next) => ResolvedUrl::hop(url, next),
+                None => ResolvedUrl::unchanged(url),
+            })
+        })
+    }
+}
+
+/// Follows `<meta http-equiv="refresh">` pages, such as file hosts that
+/// show a countdown before sending the browser on to the file
+pub struct MetaRefreshResolver;
+
+impl UrlResolver for MetaRefreshResolver {
+    fn name(&self) -> &str {
+        "meta-refresh"
+    }
+
+    fn matches(&self, url: &Url) -> bool {
+        is_http(url)
+    }
+
+    fn resolve<'a>(&'a self, url: &'a str, client: &'a Client) -> ResolveFuture<'a> {
+        Box::pin(async move {
+            // Ask for the scanned prefix only, so a link straight to a
+            // large file isn't streamed from the start
+            let mut response = client
+                .get(url)
+                .header(header::RANGE, format!("bytes=0-{}", META_REFRESH_SCAN_BYTES - 1))
+                .send()
+                .await
+                .map_err(network_error)?;
+
+            let is_html = response
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|v| v.to_ascii_lowercase().contains("text/html"));
+            if !response.status().is_success() || !is_html {
+                return Ok(ResolvedUrl::unchanged(url));
+            }
+
+            // Only the head of the page matters; don't pull in a large body
+            let mut page = Vec::new();
+            while page.len() < META_REFRESH_SCAN_BYTES {
+                match response.chunk().await.map_err(network_error)? {
+                    Some(chunk) => page.extend_from_slice(&chunk),
+                    None => break,
+                }
+            }
+
+            let target = meta_refresh_target(&String::from_utf8_lossy(&page))
+                .and_then(|target| Url::parse(url).ok()?.join(&target).ok())
+                .map(String::from);
+            Ok(match target {
+                Some(next) if next != url => ResolvedUrl::hop(url, next),
+                _ => ResolvedUrl::unchanged(url),
+            })
+        })
+    }
+}
+
+/// URL of the first meta refresh tag in `html`, as written (possibly
+/// relative)
+fn meta_refresh_target(html: &str) -> Option<String> {
+    let meta = Regex::new(r"(?is)<meta\s[^>]*>").ok()?;
+    let refresh = Regex::new(r#"(?i)http-equiv\s*=\s*["']?refresh\b"#).ok()?;
+    let content = Regex::new(r#"(?is)\bcontent\s*=\s*(?:"([^"]*)"|'([^']*)')"#).ok()?;
+    let url = Regex::new(r#"(?is)^\s*\d*(?:\.\d*)?\s*[;,]\s*(?:url\s*=\s*)?["']?([^"']+)["']?\s*$"#).ok()?;
+
+    meta.find_iter(html)
+        .map(|tag| tag.as_str())
+        .filter(|tag| refresh.is_match(tag))
+        .find_map(|tag| {
+            let captures = content.captures(tag)?;
+            let value = captures.get(1).or_else(|| captures.get(2))?.as_str();
+            let target = url.captures(value)?.get(1)?.as_str().trim();
+            (!target.is_empty()).then(|| target.replace("&amp;", "&"))
+        })
+}
+
+/// Ordered set of resolvers run before a download is probed. At each hop
+/// the first matching resolver that changes the URL wins, and resolution
+/// carries on from its result until nothing changes.
+pub struct UrlResolvers {
+    resolvers: parking_lot::RwLock<Vec<Arc<dyn UrlResolver>>>,
+    /// Does not follow redirects, so resolvers see every hop
+    client: Client,
+    enabled: AtomicBool,
+    max_hops: AtomicUsize,
+}
+
+impl UrlResolvers {
+    /// The built-in redirect and meta refresh resolvers
+    pub fn new(client: Client) -> Self {
+        Self::with_resolvers(client, vec![Arc::new(RedirectResolver), Arc::new(MetaRefreshResolver)])
+    }
+
+    pub fn with_resolvers(client: Client, resolvers: Vec<Arc<dyn UrlResolver>>) -> Self {
+        Self {
+            resolvers: parking_lot::RwLock::new(resolvers),
+            client,
+            enabled: AtomicBool::new(true),
+            max_hops: AtomicUsize::new(DEFAULT_MAX_RESOLVE_HOPS),
+        }
+    }
+
+    /// Add a resolver ahead of the built-in ones, so host-specific
+    /// resolvers get the first look at their links
+    pub fn register(&self, resolver: Arc<dyn UrlResolver>) {
+        tracing::info!("Registered URL resolver '{}'", resolver.name());
+        self.resolvers.write().insert(0, resolver);
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_max_hops(&self, max_hops: usize) {
+        self.max_hops.store(max_hops.max(1), Ordering::Relaxed);
+    }
+
+    pub fn max_hops(&self) -> usize {
+        self.max_hops.load(Ordering::Relaxed)
+    }
+
+    /// Follow `url` to the file it leads to. Returns it unchanged when
+    /// resolution is turned off.
+    pub async fn resolve(&self, url: &str) -> Result<ResolvedUrl, DownloadError> {
+        let mut resolved = ResolvedUrl::unchanged(url);
+        if !self.is_enabled() {
+            return Ok(resolved);
+        }
+        let max_hops = self.max_hops();
+
+        while let Some(step) = self.step(&resolved.url).await? {
+            if resolved.chain.len() + step.chain.len() > max_hops {
+                return Err(DownloadError::InvalidUrl(format!(
+                    "{} takes more than {} hops to resolve",
+                    url, max_hops
+                )));
+            }
+            if step.url == url || resolved.chain.contains(&step.url) {
+                return Err(DownloadError::InvalidUrl(format!("{} redirects in a loop", url)));
+            }
+            resolved.chain.extend(step.chain);
+            resolved.url = step.url;
+        }
+
+        if !resolved.is_unchanged() {
+            tracing::debug!("Resolved {} -> {} in {} hops", url, resolved.url, resolved.chain.len());
+        }
+        Ok(resolved)
+    }
+
+    /// Result of the first resolver that changes `url`
+    async fn step(&self, url: &str) -> Result<Option<ResolvedUrl>, DownloadError> {
+        let Ok(parsed) = Url::parse(url) else {
+            return Ok(None);
+        };
+        let matching: Vec<Arc<dyn UrlResolver>> = self.resolvers
+            .read()
+            .iter()
+            .filter(|resolver| resolver.matches(&parsed))
+            .cloned()
+            .collect();
+
+        for resolver in matching {
+            let step = resolver.resolve(url, &self.client).await?;
+            if !step.is_unchanged() {
+                tracing::debug!("Resolver '{}': {} -> {}", resolver.name(), url, step.url);
+                return Ok(Some(step));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Resolves from a fixed table of hops
+    struct TableResolver(&'static [(&'static str, &'static str)]);
+
+    impl UrlResolver for TableResolver {
+        fn name(&self) -> &str {
+            "table"
+        }
+
+        fn matches(&self, url: &Url) -> bool {
+            self.0.iter().any(|(from, _)| url.as_str() == *from)
+        }
+
+        fn resolve<'a>(&'a self, url: &'a str, _client: &'a Client) -> ResolveFuture<'a> {
+            Box::pin(async move {
+                let to = self.0.iter().find(|(from, _)| *from == url).map(|(_, to)| to.to_string());
+                Ok(to.map_or_else(|| ResolvedUrl::unchanged(url), |to| ResolvedUrl::hop(url, to)))
+            })
+        }
+    }
+
+    fn resolvers(table: &'static [(&'static str, &'static str)]) -> UrlResolvers {
+        UrlResolvers::with_resolvers(Client::new(), vec![Arc::new(TableResolver(table))])
+    }
+
+    #[test]
+    fn test_meta_refresh_target() {
+        let page = r#"<html><head>
+            <META HTTP-EQUIV="Refresh" CONTENT="5; URL=/files/app.zip?a=1&amp;b=2">
+            </head></html>"#;
+        assert_eq!(meta_refresh_target(page).as_deref(), Some("/files/app.zip?a=1&b=2"));
+
+        let reversed = r#"<meta content='0;url="https://cdn.example.com/f.bin"' http-equiv=refresh>"#;
+        assert_eq!(meta_refresh_target(reversed).as_deref(), Some("https://cdn.example.com/f.bin"));
+
+        // A plain reload has no target
+        assert_eq!(meta_refresh_target(r#"<meta http-equiv="refresh" content="30">"#), None);
+        assert_eq!(meta_refresh_target(r#"<meta name="viewport" content="0; url=x">"#), None);
+    }
+
+    #[tokio::test]
+    async fn test_chain_is_recorded() {
+        let resolvers = resolvers(&[
+            ("https://short.example/a", "https://landing.example/page"),
+            ("https://landing.example/page", "https://cdn.example/file.zip"),
+        ]);
+
+        let resolved = resolvers.resolve("https://short.example/a").await.unwrap();
+        assert_eq!(resolved.url, "https://cdn.example/file.zip");
+        assert_eq!(resolved.chain, vec!["https://short.example/a", "https://landing.example/page"]);
+        assert!(!resolved.downgrades_https());
+
+        resolvers.set_enabled(false);
+        assert!(resolvers.resolve("https://short.example/a").await.unwrap().is_unchanged());
+    }
+
+    #[test]
+    fn test_https_downgrade() {
+        let hops = |chain: &[&str], url: &str| ResolvedUrl {
+            url: url.to_string(),
+            chain: chain.iter().map(|s| s.to_string()).collect(),
+        };
+        assert!(hops(&["https://a.example/"], "http://b.example/").downgrades_https());
+        assert!(hops(&["https://a.example/", "http://b.example/"], "https://c.example/").downgrades_https());
+        // Upgrading is fine
+        assert!(!hops(&["http://a.example/"], "https://b.example/").downgrades_https());
+    }
+
+    #[tokio::test]
+    async fn test_hop_cap_and_loops() {
+        let resolvers = resolvers(&[
+            ("https://a.example/", "https://b.example/"),
+            ("https://b.example/", "https://c.example/"),
+            ("https://c.example/", "https://a.example/"),
+        ]);
+        assert!(resolvers.resolve("https://a.example/").await.unwrap_err().to_string().contains("loop"));
+
+        resolvers.set_max_hops(1);
+        assert!(resolvers.resolve("https://a.example/").await.unwrap_err().to_string().contains("1 hops"));
+    }
+
+    #[tokio::test]
+    async fn test_registered_resolvers_go_first() {
+        let resolvers = resolvers(&[("https://host.example/f", "https://generic.example/f")]);
+        resolvers.register(Arc::new(TableResolver(&[("https://host.example/f", "https://direct.example/f")])));
+
+        let resolved = resolvers.resolve("https://host.example/f").await.unwrap();
+        assert_eq!(resolved.url, "https://direct.example/f");
+    }
+}
@@ -1,14 +1,27 @@
 // src-tauri/src/network/sftp_client.rs
 
 use std::path::PathBuf;
-use std::net::TcpStream;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
 use ssh2::Session;
 use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, oneshot};
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, info};
 
+use crate::network::remote_progress::{ProgressReporter, RemoteProgress, TransferControl};
+use crate::utils::constants::{DEFAULT_CONNECT_TIMEOUT, DEFAULT_READ_TIMEOUT};
 use crate::utils::error::DownloadError;
 
-/// SFTP client for secure file transfers over SSH
+/// Size of each read from the remote file
+const READ_CHUNK_SIZE: usize = 32768;
+
+/// Chunks read ahead of the local file before the reader waits
+const READ_AHEAD_CHUNKS: usize = 8;
+
+/// SFTP client for secure file transfers over SSH. ssh2 blocks, so its
+/// calls run on the blocking thread pool.
+#[derive(Clone)]
 pub struct SftpClient {
     host: String,
     port: u16,
@@ -82,8 +95,35 @@ impl SftpClient {
         ))
     }
 
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    /// Connect, authenticate and open the SFTP subsystem. Returns the
+    /// server's SSH banner. Blocking.
+    pub fn test_connection(&self) -> Result<Option<String>, DownloadError> {
+        let session = self.connect()?;
+        session.sftp()
+            .map_err(|e| DownloadError::NetworkError(format!("SFTP init failed: {}", e)))?;
+        Ok(session.banner().map(str::to_string))
+    }
+
     /// Get file information from SFTP server
     pub async fn get_file_info(&self, remote_path: &str) -> Result<SftpFileInfo, DownloadError> {
+        let client = self.clone();
+        let remote_path = remote_path.to_string();
+        run_blocking(move || client.file_info_blocking(&remote_path)).await
+    }
+
+    fn file_info_blocking(&self, remote_path: &str) -> Result<SftpFileInfo, DownloadError> {
         let session = self.connect()?;
         let sftp = session.sftp()
             .map_err(|e| DownloadError::NetworkError(format!("SFTP init failed: {}", e)))?;
@@ -107,6 +147,12 @@ impl SftpClient {
 
     /// List directory contents
     pub async fn list_directory(&self, remote_path: &str) -> Result<Vec<SftpFileInfo>, DownloadError> {
+        let client = self.clone();
+        let remote_path = remote_path.to_string();
+        run_blocking(move || client.list_directory_blocking(&remote_path)).await
+    }
+
+    fn list_directory_blocking(&self, remote_path: &str) -> Result<Vec<SftpFileInfo>, DownloadError> {
         let session = self.connect()?;
         let sftp = session.sftp()
             .map_err(|e| DownloadError::NetworkError(format!("SFTP init failed: {}", e)))?;
@@ -130,35 +176,36 @@ impl SftpClient {
     }
 
     /// Download a file from SFTP server with resume support
-    pub async fn download_file(
+    /// `on_progress` is called as data arrives with the speed and ETA;
+    /// cancelling `control.cancel` stops the transfer between reads
+    pub async fn download_file<F: FnMut(RemoteProgress)>(
         &self,
         remote_path: &str,
         local_path: &PathBuf,
         resume_from: Option<u64>,
+        control: &TransferControl,
+        on_progress: F,
     ) -> Result<u64, DownloadError> {
-        let session = self.connect()?;
-        let sftp = session.sftp()
-            .map_err(|e| DownloadError::NetworkError(format!("SFTP init failed: {}", e)))?;
-
-        // Get file size
-        let stat = sftp.stat(std::path::Path::new(remote_path))
-            .map_err(|e| DownloadError::NetworkError(format!("Failed to stat file: {}", e)))?;
-
-        let total_size = stat.size.unwrap_or(0);
-
-        // Open remote file
-        let mut remote_file = sftp.open(std::path::Path::new(remote_path))
-            .map_err(|e| DownloadError::NetworkError(format!("Failed to open remote file: {}", e)))?;
+        // The reader thread sends the file's size once it is open, then
+        // its contents; a full channel holds it until the writes catch up
+        let (opened_tx, opened_rx) = oneshot::channel();
+        let (chunks_tx, mut chunks_rx) = mpsc::channel(READ_AHEAD_CHUNKS);
+        let client = self.clone();
+        let path = remote_path.to_string();
+        let cancel = control.cancel.clone();
+        let reader = tokio::task::spawn_blocking(move || {
+            client.read_blocking(&path, resume_from, &cancel, opened_tx, &chunks_tx)
+        });
+
+        let total_size = match opened_rx.await {
+            Ok(total_size) => total_size,
+            // The reader failed before opening the file
+            Err(_) => return Err(join_reader(reader).await.err().unwrap_or(DownloadError::Cancelled)),
+        };
 
         // Open local file for writing (append if resuming)
         let mut local_file = if let Some(offset) = resume_from {
             info!("Resuming SFTP download from byte {}", offset);
-            
-            // Seek remote file to resume position
-            use std::io::Seek;
-            remote_file.seek(std::io::SeekFrom::Start(offset))
-                .map_err(|e| DownloadError::NetworkError(format!("Failed to seek: {}", e)))?;
-            
             tokio::fs::OpenOptions::new()
                 .append(true)
                 .open(local_path)
@@ -170,31 +217,80 @@ impl SftpClient {
                 .map_err(|e| DownloadError::FileError(format!("Cannot create file: {}", e)))?
         };
 
-        // Read from remote and write to local
-        let mut total_bytes = resume_from.unwrap_or(0);
-        let mut buffer = vec![0u8; 32768]; // 32KB buffer
+        let mut reporter = ProgressReporter::new(resume_from.unwrap_or(0), total_size, on_progress);
+        while let Some(chunk) = chunks_rx.recv().await {
+            local_file.write_all(&chunk)
+                .await
+                .map_err(|e| DownloadError::FileError(format!("Write error: {}", e)))?;
+            reporter.record(chunk.len() as u64);
+            control.throttle(chunk.len()).await;
+        }
+        join_reader(reader).await?;
+        if control.cancel.is_cancelled() {
+            let _ = local_file.flush().await;
+            return Err(DownloadError::Cancelled);
+        }
+
+        local_file.flush().await
+            .map_err(|e| DownloadError::FileError(format!("Flush error: {}", e)))?;
+        reporter.report();
+        let total_bytes = reporter.transferred();
+
+        info!("SFTP download completed: {} bytes", total_bytes);
+        Ok(total_bytes)
+    }
+
+    /// Open `remote_path` at `resume_from`, report its size on `opened`
+    /// and send its contents on `chunks`. Stops early, without an error,
+    /// when cancelled or when the receiving side goes away.
+    fn read_blocking(
+        &self,
+        remote_path: &str,
+        resume_from: Option<u64>,
+        cancel: &CancellationToken,
+        opened: oneshot::Sender<Option<u64>>,
+        chunks: &mpsc::Sender<Vec<u8>>,
+    ) -> Result<(), DownloadError> {
+        use std::io::{Read, Seek};
+
+        let session = self.connect()?;
+        let sftp = session.sftp()
+            .map_err(|e| DownloadError::NetworkError(format!("SFTP init failed: {}", e)))?;
+
+        // Get file size
+        let stat = sftp.stat(std::path::Path::new(remote_path))
+            .map_err(|e| DownloadError::NetworkError(format!("Failed to stat file: {}", e)))?;
+
+        // Open remote file
+        let mut remote_file = sftp.open(std::path::Path::new(remote_path))
+            .map_err(|e| DownloadError::NetworkError(format!("Failed to open remote file: {}", e)))?;
+        if let Some(offset) = resume_from {
+            remote_file.seek(std::io::SeekFrom::Start(offset))
+                .map_err(|e| DownloadError::NetworkError(format!("Failed to seek: {}", e)))?;
+        }
+        if opened.send(stat.size).is_err() {
+            return Ok(());
+        }
 
         loop {
-            use std::io::Read;
+            // ssh2 reads block, so cancellation is noticed between them
+            if cancel.is_cancelled() {
+                return Ok(());
+            }
+            let mut buffer = vec![0u8; READ_CHUNK_SIZE];
             match remote_file.read(&mut buffer) {
-                Ok(0) => break, // EOF
+                Ok(0) => return Ok(()), // EOF
                 Ok(n) => {
-                    local_file.write_all(&buffer[..n])
-                        .await
-                        .map_err(|e| DownloadError::FileError(format!("Write error: {}", e)))?;
-                    total_bytes += n as u64;
+                    buffer.truncate(n);
+                    if chunks.blocking_send(buffer).is_err() {
+                        return Ok(());
+                    }
                 }
                 Err(e) => {
                     return Err(DownloadError::NetworkError(format!("Read error: {}", e)));
                 }
             }
         }
-
-        local_file.flush().await
-            .map_err(|e| DownloadError::FileError(format!("Flush error: {}", e)))?;
-
-        info!("SFTP download completed: {} bytes", total_bytes);
-        Ok(total_bytes)
     }
 
     /// Upload a file to SFTP server
@@ -246,12 +342,13 @@ impl SftpClient {
         
         debug!("Connecting to SFTP server: {}", addr);
         
-        let tcp = TcpStream::connect(&addr)
-            .map_err(|e| DownloadError::NetworkError(format!("TCP connection failed: {}", e)))?;
+        let tcp = connect_tcp(&addr)?;
 
         let mut session = Session::new()
             .map_err(|e| DownloadError::NetworkError(format!("Session creation failed: {}", e)))?;
 
+        // Every blocking libssh2 call, reads included, gives up after this
+        session.set_timeout((DEFAULT_READ_TIMEOUT * 1000) as u32);
         session.set_tcp_stream(tcp);
         session.handshake()
             .map_err(|e| DownloadError::NetworkError(format!("SSH handshake failed: {}", e)))?;
@@ -286,6 +383,46 @@ impl SftpClient {
     }
 }
 
+/// Connect to the first of `addr`'s addresses that answers within
+/// `DEFAULT_CONNECT_TIMEOUT`
+fn connect_tcp(addr: &str) -> Result<TcpStream, DownloadError> {
+    let timeout = Duration::from_secs(DEFAULT_CONNECT_TIMEOUT);
+    let addresses = addr
+        .to_socket_addrs()
+        .map_err(|e| DownloadError::NetworkError(format!("Cannot resolve {}: {}", addr, e)))?;
+    let mut last_error = None;
+    for address in addresses {
+        match TcpStream::connect_timeout(&address, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(match last_error {
+        Some(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+            DownloadError::Timeout { seconds: DEFAULT_CONNECT_TIMEOUT }
+        }
+        Some(e) => DownloadError::NetworkError(format!("TCP connection failed: {}", e)),
+        None => DownloadError::NetworkError(format!("{} has no addresses", addr)),
+    })
+}
+
+/// Run blocking ssh2 work off the async runtime
+async fn run_blocking<T, F>(work: F) -> Result<T, DownloadError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, DownloadError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|e| DownloadError::Unknown(format!("SFTP task failed: {}", e)))?
+}
+
+async fn join_reader(reader: tokio::task::JoinHandle<Result<(), DownloadError>>) -> Result<(), DownloadError> {
+    reader
+        .await
+        .map_err(|e| DownloadError::Unknown(format!("SFTP task failed: {}", e)))?
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
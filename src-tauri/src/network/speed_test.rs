@@ -0,0 +1,311 @@
+// src-tauri/src/network/speed_test.rs
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::network::http_client::HttpClient;
+use crate::utils::error::DownloadError;
+
+/// Large file served by Cloudflare's speed test endpoint
+pub const DEFAULT_SPEED_TEST_URL: &str = "https://speed.cloudflare.com/__down?bytes=1000000000";
+
+/// Settings key: custom speed test URL
+pub const SPEED_TEST_URL_KEY: &str = "speed_test_url";
+
+/// Settings key: segment count suggested by the last speed test
+pub const RECOMMENDED_SEGMENTS_KEY: &str = "speed_test_recommended_segments";
+
+pub const MIN_TEST_SECS: u64 = 2;
+pub const MAX_TEST_SECS: u64 = 30;
+
+/// How often a live sample is reported
+pub const SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Connections used for the multi-connection pass
+pub const MULTI_CONNECTIONS: u32 = 4;
+
+/// Multi-connection throughput must beat a single connection by this
+/// factor before extra segments are worth recommending
+const MULTI_SEGMENT_GAIN: f64 = 1.25;
+
+/// Live throughput sample, emitted as `speed-test-sample`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeedSample {
+    pub connections: u32,
+    pub elapsed_ms: u64,
+    pub bytes_per_sec: f64,
+}
+
+/// Result of one pass with a fixed number of connections
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PassResult {
+    pub connections: u32,
+    pub bytes: u64,
+    pub average: f64,
+    pub median: f64,
+    pub peak: f64,
+    /// Time to the first body byte on the fastest connection
+    pub latency_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeedTestResult {
+    pub url: String,
+    pub single: PassResult,
+    pub multi: PassResult,
+    pub multi_segment_helps: bool,
+    pub recommended_segments: u8,
+}
+
+/// Average, median and peak of per-interval throughput samples
+pub fn summarize(samples: &[f64]) -> (f64, f64, f64) {
+    if samples.is_empty() {
+        return (0.0, 0.0, 0.0);
+    }
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(f64::total_cmp);
+
+    let average = sorted.iter().sum::<f64>() / sorted.len() as f64;
+    let mid = sorted.len() / 2;
+    let median = if sorted.len() % 2 == 0 {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    };
+    (average, median, sorted[sorted.len() - 1])
+}
+
+/// Whether splitting downloads into segments is worth it on this connection
+pub fn multi_segment_helps(single: &PassResult, multi: &PassResult) -> bool {
+    single.median > 0.0 && multi.median >= single.median * MULTI_SEGMENT_GAIN
+}
+
+/// Runs throwaway downloads to measure the connection. Nothing is written
+/// to disk or persisted as a task, and the global speed limiter is bypassed.
+#[derive(Default)]
+pub struct SpeedTester {
+    current: parking_lot::Mutex<Option<CancellationToken>>,
+}
+
+impl SpeedTester {
+    /// Start a new test, cancelling one already running
+    pub fn begin(&self) -> CancellationToken {
+        let token = CancellationToken::new();
+        if let Some(previous) = self.current.lock().replace(token.clone()) {
+            previous.cancel();
+        }
+        token
+    }
+
+    /// Cancel the test in progress, if any
+    pub fn cancel(&self) {
+        if let Some(token) = self.current.lock().take() {
+            token.cancel();
+        }
+    }
+
+    /// Measure with one connection and then with [`MULTI_CONNECTIONS`],
+    /// splitting `duration` between the two passes
+    pub async fn run<F>(
+        &self,
+        client: &HttpClient,
+        url: &str,
+        duration: Duration,
+        cancel: CancellationToken,
+        on_sample: F,
+    ) -> Result<SpeedTestResult, DownloadError>
+    where
+        F: Fn(SpeedSample),
+    {
+        let duration = duration.clamp(
+            Duration::from_secs(MIN_TEST_SECS),
+            Duration::from_secs(MAX_TEST_SECS),
+        );
+        let pass_duration = duration / 2;
+
+        let single = run_pass(client, url, 1, pass_duration, &cancel, &on_sample).await?;
+        let multi = run_pass(client, url, MULTI_CONNECTIONS, pass_duration, &cancel, &on_sample).await?;
+
+        let helps = multi_segment_helps(&single, &multi);
+        Ok(SpeedTestResult {
+            url: url.to_string(),
+            recommended_segments: if helps { MULTI_CONNECTIONS as u8 } else { 1 },
+            multi_segment_helps: helps,
+            single,
+            multi,
+        })
+    }
+}
+
+async fn run_pass<F>(
+    client: &HttpClient,
+    url: &str,
+    connections: u32,
+    duration: Duration,
+    cancel: &CancellationToken,
+    on_sample: &F,
+) -> Result<PassResult, DownloadError>
+where
+    F: Fn(SpeedSample),
+{
+    let bytes = Arc::new(AtomicU64::new(0));
+    let latency_ms = Arc::new(AtomicU64::new(u64::MAX));
+    let pass_cancel = cancel.child_token();
+    let started = Instant::now();
+    let deadline = started + duration;
+
+    let workers: Vec<_> = (0..connections)
+        .map(|_| {
+            let client = client.clone();
+            let url = url.to_string();
+            let bytes = bytes.clone();
+            let latency_ms = latency_ms.clone();
+            let cancel = pass_cancel.clone();
+            tokio::spawn(async move {
+                drain_body(&client, &url, &bytes, &latency_ms, &cancel).await
+            })
+        })
+        .collect();
+
+    let mut samples = Vec::new();
+    let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+    interval.tick().await;
+    let mut last = (Instant::now(), 0u64);
+
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = interval.tick() => {}
+        }
+        if workers.iter().all(|w| w.is_finished()) {
+            break;
+        }
+
+        let now = Instant::now();
+        let total = bytes.load(Ordering::Relaxed);
+        let elapsed = now.duration_since(last.0).as_secs_f64();
+        if elapsed > 0.0 {
+            let bytes_per_sec = (total - last.1) as f64 / elapsed;
+            samples.push(bytes_per_sec);
+            on_sample(SpeedSample {
+                connections,
+                elapsed_ms: now.duration_since(started).as_millis() as u64,
+                bytes_per_sec,
+            });
+        }
+        last = (now, total);
+
+        if now >= deadline {
+            break;
+        }
+    }
+
+    pass_cancel.cancel();
+    let mut first_error = None;
+    for worker in workers {
+        if let Ok(Err(e)) = worker.await {
+            first_error.get_or_insert(e);
+        }
+    }
+
+    if cancel.is_cancelled() {
+        return Err(DownloadError::Cancelled);
+    }
+    let total = bytes.load(Ordering::Relaxed);
+    if total == 0 {
+        return Err(first_error.unwrap_or_else(|| {
+            DownloadError::NetworkError("Speed test received no data".to_string())
+        }));
+    }
+
+    let (average, median, peak) = summarize(&samples);
+    let latency = latency_ms.load(Ordering::Relaxed);
+    Ok(PassResult {
+        connections,
+        bytes: total,
+        average,
+        median,
+        peak,
+        latency_ms: (latency != u64::MAX).then_some(latency),
+    })
+}
+
+/// Read and discard response bodies until cancelled, re-requesting when
+/// the test file runs out
+async fn drain_body(
+    client: &HttpClient,
+    url: &str,
+    bytes: &AtomicU64,
+    latency_ms: &AtomicU64,
+    cancel: &CancellationToken,
+) -> Result<(), DownloadError> {
+    while !cancel.is_cancelled() {
+        let requested = Instant::now();
+        let mut response = tokio::select! {
+            _ = cancel.cancelled() => return Ok(()),
+            response = client.get_full(url, None) => response?,
+        };
+
+        let mut first = true;
+        loop {
+            let chunk = tokio::select! {
+                _ = cancel.cancelled() => return Ok(()),
+                chunk = response.chunk() => chunk
+                    .map_err(|e| DownloadError::NetworkError(e.to_string()))?,
+            };
+            let Some(chunk) = chunk else {
+                break;
+            };
+            if first {
+                latency_ms.fetch_min(requested.elapsed().as_millis() as u64, Ordering::Relaxed);
+                first = false;
+            }
+            bytes.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pass(median: f64) -> PassResult {
+        PassResult {
+            median,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_summarize_samples() {
+        assert_eq!(summarize(&[]), (0.0, 0.0, 0.0));
+        assert_eq!(summarize(&[4.0, 1.0, 7.0]), (4.0, 4.0, 7.0));
+        assert_eq!(summarize(&[1.0, 2.0, 3.0, 10.0]), (4.0, 2.5, 10.0));
+    }
+
+    #[test]
+    fn test_multi_segment_recommendation() {
+        assert!(multi_segment_helps(&pass(10.0), &pass(20.0)));
+        assert!(!multi_segment_helps(&pass(10.0), &pass(11.0)));
+        assert!(!multi_segment_helps(&pass(0.0), &pass(5.0)));
+    }
+
+    #[test]
+    fn test_begin_cancels_previous_run() {
+        let tester = SpeedTester::default();
+        let first = tester.begin();
+        let second = tester.begin();
+        assert!(first.is_cancelled());
+        assert!(!second.is_cancelled());
+
+        tester.cancel();
+        assert!(second.is_cancelled());
+    }
+}
@@ -38,6 +38,72 @@ impl WebSeed {
     }
 }
 
+/// Wait before retrying a source after its first failure; doubles with
+/// each further failure up to `MAX_SOURCE_BACKOFF_SECS`
+pub const SOURCE_BACKOFF_BASE_SECS: i64 = 30;
+pub const MAX_SOURCE_BACKOFF_SECS: i64 = 60 * 60;
+
+/// How a web seed or tracker has been responding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SourceStatus {
+    Healthy,
+    /// Failed last time; tried again on the next request
+    Failing,
+    /// Skipped until its backoff runs out
+    BackedOff,
+}
+
+/// Consecutive failures of a web seed or tracker and when it may be tried
+/// again. A success clears it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceHealth {
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    /// Skipped until then
+    pub retry_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl SourceHealth {
+    pub fn status(&self, now: chrono::DateTime<chrono::Utc>) -> SourceStatus {
+        match self.retry_at {
+            _ if self.consecutive_failures == 0 => SourceStatus::Healthy,
+            Some(retry_at) if retry_at > now => SourceStatus::BackedOff,
+            _ => SourceStatus::Failing,
+        }
+    }
+
+    /// Whether the source should be tried at `now`
+    pub fn is_available(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.status(now) != SourceStatus::BackedOff
+    }
+
+    pub fn record_success(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Back the source off for twice as long as last time
+    pub fn record_failure(&mut self, error: String, now: chrono::DateTime<chrono::Utc>) {
+        self.consecutive_failures += 1;
+        self.last_error = Some(error);
+        let doublings = (self.consecutive_failures - 1).min(16);
+        let secs = (SOURCE_BACKOFF_BASE_SECS << doublings).min(MAX_SOURCE_BACKOFF_SECS);
+        self.retry_at = Some(now + chrono::Duration::seconds(secs));
+    }
+}
+
+/// A web seed with how it has been responding, as returned by
+/// `get_web_seeds`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSeedStatus {
+    #[serde(flatten)]
+    pub seed: WebSeed,
+    pub status: SourceStatus,
+    pub health: SourceHealth,
+}
+
 /// Protocol encryption settings (BEP 3, MSE/PE)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EncryptionConfig {
@@ -379,6 +445,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_source_backoff_grows_and_resets() {
+        let start = chrono::Utc::now();
+        let secs = |s: i64| start + chrono::Duration::seconds(s);
+        let mut health = SourceHealth::default();
+        assert_eq!(health.status(start), SourceStatus::Healthy);
+
+        health.record_failure("connection refused".to_string(), start);
+        assert!(!health.is_available(secs(29)));
+        assert_eq!(health.status(secs(30)), SourceStatus::Failing);
+
+        // The skip doubles with each consecutive failure
+        health.record_failure("connection refused".to_string(), secs(30));
+        assert!(!health.is_available(secs(89)));
+        assert!(health.is_available(secs(90)));
+        health.record_failure("timed out".to_string(), secs(90));
+        assert_eq!(health.retry_at, Some(secs(210)));
+        assert_eq!(health.last_error.as_deref(), Some("timed out"));
+
+        // ...up to the cap
+        for _ in 0..20 {
+            health.record_failure("gone".to_string(), start);
+        }
+        assert_eq!(health.retry_at, Some(secs(MAX_SOURCE_BACKOFF_SECS)));
+
+        health.record_success();
+        assert_eq!(health, SourceHealth::default());
+        assert!(health.is_available(start));
+    }
+
     #[test]
     fn test_encryption_config() {
         let disabled = EncryptionConfig::disabled();
@@ -74,7 +74,7 @@ impl TorrentClient {
     /// Add a torrent from a .torrent file
     pub async fn add_torrent_file(&self, path: &PathBuf) -> Result<String, AppError> {
         // Use librqbit backend to add torrent
-        let info_hash = self.backend.add_torrent_file(path).await?;
+        let info_hash = self.backend.add_torrent_file(path, false, None).await?;
         
         // Get info from backend
         let info = self.backend.get_torrent_info(&info_hash).await?;
@@ -97,7 +97,7 @@ impl TorrentClient {
     /// Add a torrent from a magnet link
     pub async fn add_magnet(&self, magnet_link: &str) -> Result<String, AppError> {
         // Use librqbit backend to add magnet
-        let info_hash = self.backend.add_magnet(magnet_link).await?;
+        let info_hash = self.backend.add_magnet(magnet_link, false).await?;
         
         // Get info from backend
         let info = self.backend.get_torrent_info(&info_hash).await?;
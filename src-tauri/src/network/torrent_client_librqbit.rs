@@ -1,17 +1,22 @@
 // src-tauri/src/network/torrent_client_librqbit.rs
 // Complete BitTorrent implementation using librqbit
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::core::content_filter::CompiledFilter;
 use crate::utils::error::AppError;
+use crate::utils::file_utils;
 use std::collections::HashMap;
 use crate::network::bencode_parser::{TorrentFile as BencodeTorrentFile, MagnetLink};
 use crate::network::torrent_helpers::{TorrentMetadata, TorrentPriority, BandwidthLimit, TorrentSchedule};
 use crate::network::torrent_advanced::{
     AdvancedTorrentOptions, WebSeed, EncryptionConfig, IpFilter, 
-    TorrentAdvancedConfig, WebSeedDownloader
+    TorrentAdvancedConfig, WebSeedDownloader, SourceHealth, WebSeedStatus
 };
 
 // Stub types for librqbit while it's disabled
@@ -49,14 +54,16 @@ mod librqbit_stub {
     
     pub struct AddTorrentOptions {
         pub overwrite: bool,
-        pub only_files: Option<()>,
-        pub output_folder: Option<()>,
+        pub paused: bool,
+        pub only_files: Option<Vec<usize>>,
+        pub output_folder: Option<String>,
     }
     
     impl Default for AddTorrentOptions {
         fn default() -> Self {
             Self {
                 overwrite: false,
+                paused: false,
                 only_files: None,
                 output_folder: None,
             }
@@ -66,8 +73,8 @@ mod librqbit_stub {
     pub enum AddTorrent {}
     
     impl AddTorrent {
-        pub fn from_file(_path: &PathBuf) -> Self {
-            panic!("librqbit stub - cannot add torrent from file")
+        pub fn from_bytes(_bytes: Vec<u8>) -> Self {
+            panic!("librqbit stub - cannot add torrent from bytes")
         }
         
         pub fn from_url(_url: &str) -> Self {
@@ -80,13 +87,29 @@ mod librqbit_stub {
         pub fn new_disabled() -> Self {
             Session
         }
+
+        pub fn tcp_listen_port(&self) -> Option<u16> {
+            None
+        }
+
+        pub fn dht_routing_table_size(&self) -> Option<usize> {
+            None
+        }
+
+        pub fn reannounce(&self, _info_hash: &str) {}
+
+        /// Connected peers of a torrent that dialled our listen port,
+        /// rather than being dialled by us
+        pub fn incoming_peer_count(&self, _info_hash: &str) -> usize {
+            0
+        }
     }
 }
 
 #[cfg(not(feature = "librqbit-enabled"))]
 use librqbit_stub as librqbit;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TorrentInfo {
     pub info_hash: String,
     pub name: String,
@@ -96,13 +119,13 @@ pub struct TorrentInfo {
     pub files: Vec<TorrentFile>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TorrentFile {
     pub path: PathBuf,
     pub size: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TorrentStats {
     pub downloaded: u64,
     pub uploaded: u64,
@@ -112,6 +135,9 @@ pub struct TorrentStats {
     pub seeders: usize,
     pub progress: f64,
     pub eta: Option<u64>,
+    /// Bytes of pieces that failed their hash check and came again
+    #[serde(default)]
+    pub wasted_bytes: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -123,6 +149,64 @@ pub enum TorrentState {
     Error(String),
 }
 
+impl TorrentState {
+    /// String form used for database persistence
+    pub fn as_str(&self) -> &str {
+        match self {
+            TorrentState::Downloading => "Downloading",
+            TorrentState::Seeding => "Seeding",
+            TorrentState::Paused => "Paused",
+            TorrentState::Checking => "Checking",
+            TorrentState::Error(_) => "Error",
+        }
+    }
+}
+
+/// Parses a persisted state string; anything unknown becomes an error state
+impl std::str::FromStr for TorrentState {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "Downloading" => TorrentState::Downloading,
+            "Seeding" => TorrentState::Seeding,
+            "Paused" => TorrentState::Paused,
+            "Checking" => TorrentState::Checking,
+            other => TorrentState::Error(other.to_string()),
+        })
+    }
+}
+
+/// Ports the session listens on for incoming peers
+const LISTEN_PORTS: std::ops::RangeInclusive<u16> = 6881..=6889;
+
+/// How long [`TorrentSessionInfo`] is served from cache
+const SESSION_INFO_TTL: Duration = Duration::from_secs(1);
+
+/// Session-wide torrent health for the network widget and diagnostics
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TorrentSessionInfo {
+    /// False when the torrent session failed to start
+    pub session_running: bool,
+    pub dht_enabled: bool,
+    /// None when the session doesn't report its routing table
+    pub dht_nodes: Option<usize>,
+    pub download_rate: u64,
+    pub upload_rate: u64,
+    /// Bytes transferred by the torrents currently in the session
+    pub total_downloaded: u64,
+    pub total_uploaded: u64,
+    pub active_torrents: usize,
+    pub paused_torrents: usize,
+    pub seeding_torrents: usize,
+    pub listen_port: Option<u16>,
+    /// A peer has connected to us since the session started, so the
+    /// listen port is reachable
+    pub incoming_connections_seen: bool,
+    pub uptime_secs: u64,
+}
+
 pub struct LibrqbitTorrentClient {
     #[allow(dead_code)]
     session: Option<Arc<librqbit::Session>>,
@@ -130,7 +214,15 @@ pub struct LibrqbitTorrentClient {
     metadata: Arc<RwLock<HashMap<String, TorrentMetadata>>>,
     advanced_config: Arc<RwLock<HashMap<String, TorrentAdvancedConfig>>>,
     web_seed_downloader: Arc<WebSeedDownloader>,
-    config: TorrentConfig,
+    /// How each torrent's web seeds have been responding, by info hash
+    /// then URL
+    web_seed_health: parking_lot::Mutex<HashMap<String, HashMap<String, SourceHealth>>>,
+    /// Live session settings; `download_dir` can change while running
+    config: parking_lot::RwLock<TorrentConfig>,
+    started_at: Instant,
+    /// Sticky: inbound peers come and go, reachability doesn't
+    incoming_seen: AtomicBool,
+    session_info: parking_lot::Mutex<Option<(Instant, TorrentSessionInfo)>>,
 }
 
 #[derive(Debug, Clone)]
@@ -163,6 +255,10 @@ pub struct TorrentHandle {
     pub info: TorrentInfo,
     pub state: TorrentState,
     pub stats: TorrentStats,
+    /// The .torrent file as added; None for magnets until metadata resolves
+    pub metainfo: Option<Arc<[u8]>>,
+    /// Trackers from the .torrent file or magnet link
+    pub trackers: Vec<String>,
 }
 
 impl LibrqbitTorrentClient {
@@ -183,7 +279,11 @@ impl LibrqbitTorrentClient {
             metadata: Arc::new(RwLock::new(HashMap::new())),
             advanced_config: Arc::new(RwLock::new(HashMap::new())),
             web_seed_downloader: Arc::new(WebSeedDownloader::new()),
-            config,
+            web_seed_health: parking_lot::Mutex::new(HashMap::new()),
+            config: parking_lot::RwLock::new(config),
+            started_at: Instant::now(),
+            incoming_seen: AtomicBool::new(false),
+            session_info: parking_lot::Mutex::new(None),
         })
     }
 
@@ -195,14 +295,23 @@ impl LibrqbitTorrentClient {
             metadata: Arc::new(RwLock::new(HashMap::new())),
             advanced_config: Arc::new(RwLock::new(HashMap::new())),
             web_seed_downloader: Arc::new(WebSeedDownloader::new()),
-            config: TorrentConfig::default(),
+            web_seed_health: parking_lot::Mutex::new(HashMap::new()),
+            config: parking_lot::RwLock::new(TorrentConfig::default()),
+            started_at: Instant::now(),
+            incoming_seen: AtomicBool::new(false),
+            session_info: parking_lot::Mutex::new(None),
         }
     }
 
+    /// Start the librqbit session
+    ///
+    /// The session opens, writes and flushes torrent data files itself and
+    /// exposes no storage hook, so piece writes can't be cached or fsync'd
+    /// on our side.
     async fn create_session(config: &TorrentConfig) -> Result<librqbit::Session, AppError> {
         // Create librqbit session configuration
         let opts = librqbit::SessionOptions {
-            listen_port_range: Some(6881..=6889),
+            listen_port_range: Some(LISTEN_PORTS),
             enable_dht: config.dht_enabled,
             enable_dht_persistence: config.dht_enabled,
             dht_config: None,
@@ -220,25 +329,75 @@ impl LibrqbitTorrentClient {
     }
 
     /// Add a torrent from a .torrent file
-    pub async fn add_torrent_file(&self, path: &PathBuf) -> Result<String, AppError> {
+    ///
+    /// When `add_paused` is set the torrent is registered with the session
+    /// but no data is transferred until `resume` is called.
+    pub async fn add_torrent_file(
+        &self,
+        path: &PathBuf,
+        add_paused: bool,
+        filter: Option<&CompiledFilter>,
+    ) -> Result<String, AppError> {
+        let metainfo = tokio::fs::read(path).await
+            .map_err(|e| AppError::TorrentError(format!("Failed to read torrent file: {}", e)))?;
+        self.add_torrent_bytes(metainfo, add_paused, filter).await
+    }
+
+    /// Add a torrent from .torrent contents, such as a file fetched from a URL
+    ///
+    /// With a `filter`, only the files it takes are downloaded.
+    pub async fn add_torrent_bytes(
+        &self,
+        metainfo: Vec<u8>,
+        add_paused: bool,
+        filter: Option<&CompiledFilter>,
+    ) -> Result<String, AppError> {
         let session = self.session.as_ref()
             .ok_or_else(|| AppError::TorrentError("Torrent session not initialized".to_string()))?;
 
-        // Parse the torrent file first to get info
-        let torrent_file = BencodeTorrentFile::from_file(path).await?;
-        let info_hash = torrent_file.info_hash()?;
+        // Parse the torrent first to get info
+        let torrent_file = BencodeTorrentFile::from_bytes(&metainfo)?;
+        let info_hash = torrent_file.preferred_info_hash()?;
+        if self.torrents.read().await.contains_key(&info_hash) {
+            return Err(AppError::TorrentError(format!("Torrent {} is already added", info_hash)));
+        }
+
+        let only_files = match filter {
+            Some(filter) => {
+                let selected: Vec<usize> = torrent_file
+                    .file_list()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (path, size))| filter.matches(&path.to_string_lossy(), Some(*size)))
+                    .map(|(index, _)| index)
+                    .collect();
+                if selected.is_empty() {
+                    return Err(AppError::TorrentError("No files in the torrent match the filter".to_string()));
+                }
+                tracing::info!(
+                    "Filter selected {} of {} files in torrent {}",
+                    selected.len(),
+                    torrent_file.file_list().len(),
+                    info_hash
+                );
+                Some(selected)
+            }
+            None => None,
+        };
         
         // Add to librqbit session
         let add_opts = librqbit::AddTorrentOptions {
             overwrite: false,
-            only_files: None,
-            output_folder: None,
+            paused: add_paused,
+            only_files: only_files.clone(),
+            // The session's folder is fixed at startup; follow the setting
+            output_folder: Some(self.download_dir().to_string_lossy().to_string()),
             ..Default::default()
         };
 
         let _handle = session
             .add_torrent(
-                librqbit::AddTorrent::from_file(path),
+                librqbit::AddTorrent::from_bytes(metainfo.clone()),
                 Some(add_opts),
             )
             .await
@@ -259,7 +418,7 @@ impl LibrqbitTorrentClient {
 
         let torrent_handle = TorrentHandle {
             info: torrent_info,
-            state: TorrentState::Downloading,
+            state: if add_paused { TorrentState::Paused } else { TorrentState::Downloading },
             stats: TorrentStats {
                 downloaded: 0,
                 uploaded: 0,
@@ -269,21 +428,28 @@ impl LibrqbitTorrentClient {
                 seeders: 0,
                 progress: 0.0,
                 eta: None,
+                wasted_bytes: 0,
             },
+            metainfo: Some(metainfo.into()),
+            trackers: torrent_file.trackers(),
         };
 
         // Store in our map
         self.torrents.write().await.insert(info_hash.clone(), torrent_handle);
 
         // Create metadata
-        let metadata = TorrentMetadata::new(info_hash.clone(), self.config.download_dir.clone());
+        let mut metadata = TorrentMetadata::new(info_hash.clone(), self.download_dir());
+        metadata.only_files = only_files;
         self.metadata.write().await.insert(info_hash.clone(), metadata);
 
         Ok(info_hash)
     }
 
     /// Add a torrent from a magnet link
-    pub async fn add_magnet(&self, magnet_link: &str) -> Result<String, AppError> {
+    ///
+    /// With `add_paused` the session still resolves metadata from peers,
+    /// but piece downloads wait for `resume`.
+    pub async fn add_magnet(&self, magnet_link: &str, add_paused: bool) -> Result<String, AppError> {
         let session = self.session.as_ref()
             .ok_or_else(|| AppError::TorrentError("Torrent session not initialized".to_string()))?;
 
@@ -294,8 +460,10 @@ impl LibrqbitTorrentClient {
         // Add to librqbit session
         let add_opts = librqbit::AddTorrentOptions {
             overwrite: false,
+            paused: add_paused,
             only_files: None,
-            output_folder: None,
+            // The session's folder is fixed at startup; follow the setting
+            output_folder: Some(self.download_dir().to_string_lossy().to_string()),
             ..Default::default()
         };
 
@@ -319,7 +487,7 @@ impl LibrqbitTorrentClient {
 
         let torrent_handle = TorrentHandle {
             info: torrent_info,
-            state: TorrentState::Downloading,
+            state: if add_paused { TorrentState::Paused } else { TorrentState::Downloading },
             stats: TorrentStats {
                 downloaded: 0,
                 uploaded: 0,
@@ -329,14 +497,17 @@ impl LibrqbitTorrentClient {
                 seeders: 0,
                 progress: 0.0,
                 eta: None,
+                wasted_bytes: 0,
             },
+            metainfo: None,
+            trackers: magnet.trackers,
         };
 
         // Store in our map
         self.torrents.write().await.insert(info_hash.clone(), torrent_handle);
 
         // Create metadata
-        let metadata = TorrentMetadata::new(info_hash.clone(), self.config.download_dir.clone());
+        let metadata = TorrentMetadata::new(info_hash.clone(), self.download_dir());
         self.metadata.write().await.insert(info_hash.clone(), metadata);
 
         Ok(info_hash)
@@ -440,6 +611,39 @@ impl LibrqbitTorrentClient {
         }
     }
 
+    /// Record whether a torrent is paused by its schedule
+    pub async fn set_auto_paused(&self, info_hash: &str, auto_paused: bool) -> Result<(), AppError> {
+        let mut metadata = self.metadata.write().await;
+        if let Some(meta) = metadata.get_mut(info_hash) {
+            meta.auto_paused = auto_paused;
+            Ok(())
+        } else {
+            Err(AppError::TorrentError("Torrent not found".to_string()))
+        }
+    }
+
+    /// Whether this backend can super-seed (BEP 16). librqbit has no
+    /// super-seeding mode, and its session gives no say over which pieces
+    /// are announced to which peer, so it can't be emulated either.
+    pub fn supports_super_seeding(&self) -> bool {
+        false
+    }
+
+    /// Turn super-seeding on or off for a torrent. Turning it on fails with
+    /// `UNSUPPORTED` where the backend can't do it.
+    pub async fn set_super_seeding(&self, info_hash: &str, enabled: bool) -> Result<(), AppError> {
+        if enabled && !self.supports_super_seeding() {
+            return Err(unsupported_super_seeding());
+        }
+        let mut metadata = self.metadata.write().await;
+        if let Some(meta) = metadata.get_mut(info_hash) {
+            meta.super_seeding = enabled;
+            Ok(())
+        } else {
+            Err(AppError::TorrentError("Torrent not found".to_string()))
+        }
+    }
+
     /// Get torrent metadata
     pub async fn get_metadata(&self, info_hash: &str) -> Result<TorrentMetadata, AppError> {
         let metadata = self.metadata.read().await;
@@ -457,6 +661,234 @@ impl LibrqbitTorrentClient {
         Ok(handle.stats.clone())
     }
 
+    /// Get the current state of a torrent
+    pub async fn get_state(&self, info_hash: &str) -> Result<TorrentState, AppError> {
+        let torrents = self.torrents.read().await;
+        let handle = torrents.get(info_hash)
+            .ok_or_else(|| AppError::TorrentError("Torrent not found".to_string()))?;
+        
+        Ok(handle.state.clone())
+    }
+
+    /// Get a snapshot of a torrent's info, state, stats and metadata for persistence
+    pub async fn snapshot(&self, info_hash: &str) -> Result<(TorrentHandle, TorrentMetadata), AppError> {
+        let handle = self.torrents.read().await
+            .get(info_hash)
+            .cloned()
+            .ok_or_else(|| AppError::TorrentError("Torrent not found".to_string()))?;
+        let metadata = self.get_metadata(info_hash).await?;
+        
+        Ok((handle, metadata))
+    }
+
+    /// Snapshots of every torrent, for listing with filters
+    pub async fn snapshot_all(&self) -> Vec<(TorrentHandle, TorrentMetadata)> {
+        let torrents = self.torrents.read().await;
+        let metadata = self.metadata.read().await;
+        torrents
+            .iter()
+            .map(|(info_hash, handle)| {
+                let meta = metadata
+                    .get(info_hash)
+                    .cloned()
+                    .unwrap_or_else(|| TorrentMetadata::new(info_hash.clone(), PathBuf::new()));
+                (handle.clone(), meta)
+            })
+            .collect()
+    }
+
+    /// Record the torrents that finished downloading since the last call:
+    /// each gets its completion time and, if it was downloading, moves on
+    /// to seeding. Returns their snapshots.
+    pub async fn take_newly_completed(&self) -> Vec<(TorrentHandle, TorrentMetadata)> {
+        let mut torrents = self.torrents.write().await;
+        let mut metadata = self.metadata.write().await;
+        let mut finished = Vec::new();
+        for (info_hash, handle) in torrents.iter_mut() {
+            let Some(meta) = metadata.get_mut(info_hash) else {
+                continue;
+            };
+            let done = handle.info.total_size > 0 && handle.stats.downloaded >= handle.info.total_size;
+            if !done || meta.completed_time.is_some() {
+                continue;
+            }
+            meta.mark_completed();
+            if matches!(handle.state, TorrentState::Downloading) {
+                handle.state = TorrentState::Seeding;
+            }
+            finished.push((handle.clone(), meta.clone()));
+        }
+        finished
+    }
+
+    /// Re-register a torrent loaded from the database after a restart
+    pub async fn restore(&self, handle: TorrentHandle, metadata: TorrentMetadata) {
+        let info_hash = handle.info.info_hash.clone();
+        if metadata.super_seeding && !self.supports_super_seeding() {
+            tracing::warn!("Torrent {} asks for super-seeding, which this backend can't do; seeding normally", info_hash);
+        }
+        self.metadata.write().await.insert(info_hash.clone(), metadata);
+        self.torrents.write().await.insert(info_hash, handle);
+    }
+
+    /// Remember that peers have reached us on the listen port
+    fn note_incoming_peers(&self, count: usize) {
+        if count > 0 {
+            self.incoming_seen.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Session health and aggregate rates. Cheap to poll: the result is
+    /// reused for [`SESSION_INFO_TTL`].
+    pub async fn session_info(&self) -> TorrentSessionInfo {
+        if let Some((at, info)) = self.session_info.lock().as_ref() {
+            if at.elapsed() < SESSION_INFO_TTL {
+                return info.clone();
+            }
+        }
+
+        let mut info = TorrentSessionInfo {
+            session_running: self.session.is_some(),
+            dht_enabled: self.config.read().dht_enabled,
+            dht_nodes: self.session.as_ref().and_then(|s| s.dht_routing_table_size()),
+            listen_port: self.session.as_ref().and_then(|s| s.tcp_listen_port()),
+            uptime_secs: self.started_at.elapsed().as_secs(),
+            ..Default::default()
+        };
+        for (info_hash, handle) in self.torrents.read().await.iter() {
+            if let (Some(session), TorrentState::Downloading | TorrentState::Seeding) = (&self.session, &handle.state) {
+                self.note_incoming_peers(session.incoming_peer_count(info_hash));
+            }
+            match handle.state {
+                TorrentState::Paused => info.paused_torrents += 1,
+                TorrentState::Seeding => info.seeding_torrents += 1,
+                TorrentState::Downloading | TorrentState::Checking => info.active_torrents += 1,
+                TorrentState::Error(_) => {}
+            }
+            info.total_downloaded += handle.stats.downloaded;
+            info.total_uploaded += handle.stats.uploaded;
+            if !matches!(handle.state, TorrentState::Paused) {
+                info.download_rate += handle.stats.download_rate;
+                info.upload_rate += handle.stats.upload_rate;
+            }
+        }
+
+        info.incoming_connections_seen = self.incoming_seen.load(Ordering::Relaxed);
+
+        *self.session_info.lock() = Some((Instant::now(), info.clone()));
+        info
+    }
+
+    /// Folder new torrents are saved to
+    pub fn download_dir(&self) -> PathBuf {
+        self.config.read().download_dir.clone()
+    }
+
+    /// Save torrents added from now on to `dir`. Existing torrents stay
+    /// where they are unless moved with `set_location`.
+    pub fn set_download_dir(&self, dir: PathBuf) {
+        tracing::info!("Torrent download folder set to {:?}", dir);
+        self.config.write().download_dir = dir;
+    }
+
+    /// Move a torrent's data to `dest_dir`. The torrent is paused and shows
+    /// as checking while its files move, and is resumed afterwards if it
+    /// was running; on failure its data and save folder are left as they
+    /// were.
+    pub async fn set_location(&self, info_hash: &str, dest_dir: &Path) -> Result<(), AppError> {
+        let (handle, metadata) = self.snapshot(info_hash).await?;
+        if metadata.save_path == dest_dir {
+            return Ok(());
+        }
+
+        let source = metadata.save_path.join(&handle.info.name);
+        let target = dest_dir.join(&handle.info.name);
+        if source.exists() && target.exists() {
+            return Err(AppError::TorrentError(format!("{} already exists", target.display())));
+        }
+
+        // No piece may be written to the old files while they move
+        let running = matches!(handle.state, TorrentState::Downloading | TorrentState::Seeding);
+        if running {
+            self.pause(info_hash).await?;
+        }
+        self.set_state(info_hash, TorrentState::Checking).await;
+        let moved = if source.exists() {
+            file_utils::move_path(&source, &target).await
+        } else {
+            // Nothing written yet
+            Ok(())
+        };
+        if moved.is_ok() {
+            if let Some(meta) = self.metadata.write().await.get_mut(info_hash) {
+                meta.save_path = dest_dir.to_path_buf();
+            }
+        }
+        if running {
+            self.set_state(info_hash, TorrentState::Paused).await;
+            self.resume(info_hash).await?;
+        } else {
+            self.set_state(info_hash, handle.state).await;
+        }
+
+        moved.map_err(|e| AppError::TorrentError(format!(
+            "Failed to move {} to {}: {}", source.display(), dest_dir.display(), e
+        )))
+    }
+
+    async fn set_state(&self, info_hash: &str, state: TorrentState) {
+        if let Some(handle) = self.torrents.write().await.get_mut(info_hash) {
+            handle.state = state;
+        }
+    }
+
+    /// Write a torrent's metadata as a .torrent file at `dest`
+    pub async fn export_torrent_file(&self, info_hash: &str, dest: &PathBuf) -> Result<(), AppError> {
+        let torrent_file = self.parsed_metainfo(info_hash).await?
+            .ok_or_else(|| AppError::TorrentError(format!(
+                "Metadata for {} has not been resolved yet", info_hash
+            )))?;
+        let bytes = torrent_file.to_bytes()?;
+
+        tokio::fs::write(dest, bytes).await
+            .map_err(|e| AppError::TorrentError(format!("Failed to write torrent file: {}", e)))
+    }
+
+    /// Magnet link for a torrent, with its name, size and known trackers
+    pub async fn get_magnet_uri(&self, info_hash: &str) -> Result<String, AppError> {
+        let handle = self.torrents.read().await
+            .get(info_hash)
+            .cloned()
+            .ok_or_else(|| AppError::TorrentError("Torrent not found".to_string()))?;
+
+        let mut magnet = match handle.metainfo {
+            Some(ref bytes) => BencodeTorrentFile::from_bytes(bytes)?.magnet_link()?,
+            // Not resolved yet: rebuild from what the magnet told us
+            None => {
+                let mut magnet = MagnetLink::from_info_hash(info_hash);
+                magnet.display_name = Some(handle.info.name.clone()).filter(|n| n != "Unknown");
+                magnet.exact_length = Some(handle.info.total_size).filter(|size| *size > 0);
+                magnet
+            }
+        };
+        for tracker in handle.trackers {
+            if !magnet.trackers.contains(&tracker) {
+                magnet.trackers.push(tracker);
+            }
+        }
+        Ok(magnet.to_uri())
+    }
+
+    /// The torrent's .torrent file, parsed, if its metadata is known
+    async fn parsed_metainfo(&self, info_hash: &str) -> Result<Option<BencodeTorrentFile>, AppError> {
+        let metainfo = self.torrents.read().await
+            .get(info_hash)
+            .ok_or_else(|| AppError::TorrentError("Torrent not found".to_string()))?
+            .metainfo
+            .clone();
+        metainfo.map(|bytes| BencodeTorrentFile::from_bytes(&bytes)).transpose()
+    }
+
     /// Pause a torrent
     pub async fn pause(&self, info_hash: &str) -> Result<(), AppError> {
         // Librqbit doesn't have a direct pause, but we can track state
@@ -469,17 +901,43 @@ impl LibrqbitTorrentClient {
         }
     }
 
-    /// Resume a torrent
-    pub async fn resume(&self, info_hash: &str) -> Result<(), AppError> {
+    /// Resume a torrent, back to seeding when it had finished. Returns the
+    /// state it resumed into.
+    pub async fn resume(&self, info_hash: &str) -> Result<TorrentState, AppError> {
+        let completed = self.metadata
+            .read()
+            .await
+            .get(info_hash)
+            .is_some_and(|meta| meta.completed_time.is_some());
         let mut torrents = self.torrents.write().await;
         if let Some(handle) = torrents.get_mut(info_hash) {
-            handle.state = TorrentState::Downloading;
-            Ok(())
+            let finished = completed
+                || (handle.info.total_size > 0 && handle.stats.downloaded >= handle.info.total_size);
+            handle.state = if finished { TorrentState::Seeding } else { TorrentState::Downloading };
+            Ok(handle.state.clone())
         } else {
             Err(AppError::TorrentError("Torrent not found".to_string()))
         }
     }
 
+    /// Announce running torrents to their trackers again, so peers learn
+    /// the new address after the network changed. Returns how many were
+    /// announced.
+    pub async fn reannounce_all(&self) -> usize {
+        let Some(session) = &self.session else {
+            return 0;
+        };
+        let torrents = self.torrents.read().await;
+        let mut announced = 0;
+        for (info_hash, handle) in torrents.iter() {
+            if matches!(handle.state, TorrentState::Downloading | TorrentState::Seeding) {
+                session.reannounce(info_hash);
+                announced += 1;
+            }
+        }
+        announced
+    }
+
     /// Remove a torrent
     pub async fn remove(&self, info_hash: &str, delete_files: bool) -> Result<(), AppError> {
         // Remove from our tracking
@@ -536,18 +994,42 @@ impl LibrqbitTorrentClient {
         let mut advanced = self.advanced_config.write().await;
         if let Some(config) = advanced.get_mut(info_hash) {
             config.options.web_seeds.retain(|ws| ws.url != url);
+            if let Some(health) = self.web_seed_health.lock().get_mut(info_hash) {
+                health.remove(url);
+            }
             Ok(())
         } else {
             Err(AppError::TorrentError("Torrent not found".to_string()))
         }
     }
 
-    /// Get all web seeds for a torrent
-    pub async fn get_web_seeds(&self, info_hash: &str) -> Result<Vec<WebSeed>, AppError> {
-        let advanced = self.advanced_config.read().await;
-        Ok(advanced.get(info_hash)
+    /// Get all web seeds for a torrent, with how each has been responding
+    pub async fn get_web_seeds(&self, info_hash: &str) -> Result<Vec<WebSeedStatus>, AppError> {
+        let seeds = self.advanced_config.read().await
+            .get(info_hash)
             .map(|c| c.options.web_seeds.clone())
-            .unwrap_or_default())
+            .unwrap_or_default();
+
+        let now = chrono::Utc::now();
+        let health = self.web_seed_health.lock();
+        Ok(seeds
+            .into_iter()
+            .map(|seed| {
+                let health = health
+                    .get(info_hash)
+                    .and_then(|h| h.get(&seed.url))
+                    .cloned()
+                    .unwrap_or_default();
+                WebSeedStatus { status: health.status(now), seed, health }
+            })
+            .collect())
+    }
+
+    /// Forget a web seed's failures so the next request tries it again
+    pub fn reset_web_seed_health(&self, info_hash: &str, url: &str) {
+        if let Some(health) = self.web_seed_health.lock().get_mut(info_hash) {
+            health.remove(url);
+        }
     }
 
     /// Set encryption configuration for a torrent
@@ -618,6 +1100,9 @@ impl LibrqbitTorrentClient {
 
     /// Set complete advanced configuration for a torrent
     pub async fn set_advanced_config(&self, info_hash: &str, config: TorrentAdvancedConfig) -> Result<(), AppError> {
+        if config.super_seeding.enabled && !self.supports_super_seeding() {
+            return Err(unsupported_super_seeding());
+        }
         let mut advanced = self.advanced_config.write().await;
         advanced.insert(info_hash.to_string(), config);
         Ok(())
@@ -672,24 +1157,279 @@ impl LibrqbitTorrentClient {
         offset: u64,
         length: u64,
     ) -> Result<Vec<u8>, AppError> {
-        let advanced = self.advanced_config.read().await;
-        
-        if let Some(config) = advanced.get(info_hash) {
-            for web_seed in &config.options.web_seeds {
-                match self.web_seed_downloader
-                    .download_piece(web_seed, file_path, offset, length)
-                    .await
-                {
-                    Ok(data) => return Ok(data),
-                    Err(e) => {
+        let web_seeds = match self.advanced_config.read().await.get(info_hash) {
+            Some(config) => config.options.web_seeds.clone(),
+            None => return Err(AppError::TorrentError("No web seeds configured".to_string())),
+        };
+
+        // Seeds that keep failing are skipped for longer and longer
+        let mut tried = 0;
+        for web_seed in &web_seeds {
+            let available = self.web_seed_health.lock()
+                .get(info_hash)
+                .and_then(|h| h.get(&web_seed.url))
+                .map_or(true, |h| h.is_available(chrono::Utc::now()));
+            if !available {
+                continue;
+            }
+            tried += 1;
+
+            let result = self.web_seed_downloader
+                .download_piece(web_seed, file_path, offset, length)
+                .await;
+            let mut health = self.web_seed_health.lock();
+            let seed_health = health
+                .entry(info_hash.to_string())
+                .or_default()
+                .entry(web_seed.url.clone())
+                .or_default();
+            match result {
+                Ok(data) => {
+                    seed_health.record_success();
+                    return Ok(data);
+                }
+                Err(e) => {
+                    // Only the first failure in a row is worth a warning
+                    if seed_health.consecutive_failures == 0 {
                         tracing::warn!("Web seed {} failed: {}", web_seed.url, e);
-                        continue; // Try next web seed
+                    } else {
+                        tracing::debug!("Web seed {} failed again: {}", web_seed.url, e);
                     }
+                    seed_health.record_failure(e.to_string(), chrono::Utc::now());
                 }
             }
-            Err(AppError::TorrentError("All web seeds failed".to_string()))
+        }
+
+        if tried == 0 && !web_seeds.is_empty() {
+            Err(AppError::TorrentError("All web seeds are backed off after failing".to_string()))
         } else {
-            Err(AppError::TorrentError("No web seeds configured".to_string()))
+            Err(AppError::TorrentError("All web seeds failed".to_string()))
         }
     }
 }
+
+fn unsupported_super_seeding() -> AppError {
+    AppError::Unsupported("Super-seeding is not available with this torrent backend".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handle(info_hash: &str, metainfo: Option<Vec<u8>>, trackers: Vec<String>) -> TorrentHandle {
+        TorrentHandle {
+            info: TorrentInfo {
+                info_hash: info_hash.to_string(),
+                name: "file.bin".to_string(),
+                total_size: 12345,
+                piece_length: 16384,
+                num_pieces: 1,
+                files: vec![],
+            },
+            state: TorrentState::Paused,
+            stats: TorrentStats {
+                downloaded: 0,
+                uploaded: 0,
+                download_rate: 0,
+                upload_rate: 0,
+                peers: 0,
+                seeders: 0,
+                progress: 0.0,
+                eta: None,
+                wasted_bytes: 0,
+            },
+            metainfo: metainfo.map(Into::into),
+            trackers,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dead_web_seed_is_backed_off() {
+        use crate::network::torrent_advanced::{SourceStatus, WebSeedType};
+
+        let client = LibrqbitTorrentClient::new_disabled();
+        // Nothing listens on port 1, so the seed fails straight away
+        client
+            .add_web_seed("abc", WebSeed::new("http://127.0.0.1:1/file.bin".to_string(), WebSeedType::WebSeed))
+            .await
+            .unwrap();
+
+        assert!(client.download_from_web_seed("abc", "file.bin", 0, 16).await.is_err());
+        let seeds = client.get_web_seeds("abc").await.unwrap();
+        assert_eq!(seeds[0].status, SourceStatus::BackedOff);
+        assert_eq!(seeds[0].health.consecutive_failures, 1);
+        assert!(seeds[0].health.last_error.is_some());
+
+        // The next request skips it rather than trying again
+        let skipped = client.download_from_web_seed("abc", "file.bin", 0, 16).await.unwrap_err();
+        assert!(skipped.to_string().contains("backed off"));
+        assert_eq!(client.get_web_seeds("abc").await.unwrap()[0].health.consecutive_failures, 1);
+
+        client.reset_web_seed_health("abc", "http://127.0.0.1:1/file.bin");
+        assert_eq!(client.get_web_seeds("abc").await.unwrap()[0].status, SourceStatus::Healthy);
+    }
+
+    #[tokio::test]
+    async fn test_export_round_trips_info_hash() {
+        let data: Vec<u8> = [
+            b"d8:announce17:udp://a.example:14:infod6:lengthi12345e4:name8:file.bin".as_slice(),
+            b"12:piece lengthi16384e6:pieces20:".as_slice(),
+            &[b'p'; 20],
+            b"ee".as_slice(),
+        ]
+        .concat();
+        let info_hash = BencodeTorrentFile::from_bytes(&data).unwrap().info_hash().unwrap();
+
+        let client = LibrqbitTorrentClient::new_disabled();
+        let trackers = vec!["udp://a.example:1".to_string(), "udp://added.example:2".to_string()];
+        client.restore(
+            handle(&info_hash, Some(data), trackers),
+            TorrentMetadata::new(info_hash.clone(), PathBuf::new()),
+        ).await;
+
+        let dest = std::env::temp_dir().join(format!("afk-dunld-export-{}.torrent", uuid::Uuid::new_v4()));
+        client.export_torrent_file(&info_hash, &dest).await.unwrap();
+        let exported = BencodeTorrentFile::from_file(&dest).await.unwrap();
+        let _ = std::fs::remove_file(&dest);
+        assert_eq!(exported.info_hash().unwrap(), info_hash);
+
+        let magnet = MagnetLink::parse(&client.get_magnet_uri(&info_hash).await.unwrap()).unwrap();
+        assert_eq!(magnet.info_hash, info_hash);
+        assert_eq!(magnet.display_name.as_deref(), Some("file.bin"));
+        assert_eq!(magnet.exact_length, Some(12345));
+        assert_eq!(magnet.trackers, vec!["udp://a.example:1", "udp://added.example:2"]);
+    }
+
+    #[tokio::test]
+    async fn test_completion_is_reported_once() {
+        let info_hash = format!("{:040x}", 9);
+        let client = LibrqbitTorrentClient::new_disabled();
+        let mut downloading = handle(&info_hash, None, vec![]);
+        downloading.state = TorrentState::Downloading;
+        client.restore(downloading, TorrentMetadata::new(info_hash.clone(), PathBuf::new())).await;
+        assert!(client.take_newly_completed().await.is_empty());
+
+        client.torrents.write().await.get_mut(&info_hash).unwrap().stats.downloaded = 12345;
+        let finished = client.take_newly_completed().await;
+        assert_eq!(finished.len(), 1);
+        assert!(finished[0].1.completed_time.is_some());
+        assert!(matches!(client.get_state(&info_hash).await.unwrap(), TorrentState::Seeding));
+        assert!(client.take_newly_completed().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unresolved_magnet_exports_link_only() {
+        let info_hash = "1234567890abcdef1234567890abcdef12345678";
+        let client = LibrqbitTorrentClient::new_disabled();
+        client.restore(
+            handle(info_hash, None, vec!["udp://t.example:1".to_string()]),
+            TorrentMetadata::new(info_hash.to_string(), PathBuf::new()),
+        ).await;
+
+        let dest = std::env::temp_dir().join("afk-dunld-unresolved.torrent");
+        assert!(client.export_torrent_file(info_hash, &dest).await.is_err());
+
+        let magnet = MagnetLink::parse(&client.get_magnet_uri(info_hash).await.unwrap()).unwrap();
+        assert_eq!(magnet.info_hash, info_hash);
+        assert_eq!(magnet.trackers, vec!["udp://t.example:1"]);
+    }
+
+    #[tokio::test]
+    async fn test_set_location_moves_data_and_restores_state() {
+        let root = std::env::temp_dir().join(format!("afk-dunld-relocate-{}", uuid::Uuid::new_v4()));
+        let (from, to) = (root.join("old"), root.join("new"));
+        std::fs::create_dir_all(&from).unwrap();
+        std::fs::write(from.join("file.bin"), b"data").unwrap();
+
+        let info_hash = format!("{:040x}", 7);
+        let client = LibrqbitTorrentClient::new_disabled();
+        let mut seeding = handle(&info_hash, None, vec![]);
+        seeding.state = TorrentState::Seeding;
+        seeding.stats.downloaded = seeding.info.total_size;
+        client.restore(seeding, TorrentMetadata::new(info_hash.clone(), from.clone())).await;
+
+        client.set_location(&info_hash, &to).await.unwrap();
+        assert_eq!(std::fs::read(to.join("file.bin")).unwrap(), b"data");
+        assert!(!from.join("file.bin").exists());
+        assert_eq!(client.get_metadata(&info_hash).await.unwrap().save_path, to);
+        assert!(matches!(client.get_state(&info_hash).await.unwrap(), TorrentState::Seeding));
+
+        // Pausing and resuming a finished torrent keeps it seeding
+        client.pause(&info_hash).await.unwrap();
+        assert!(matches!(client.resume(&info_hash).await.unwrap(), TorrentState::Seeding));
+
+        // Never overwrites data already at the destination
+        std::fs::write(from.join("file.bin"), b"other").unwrap();
+        client.restore(handle(&info_hash, None, vec![]), TorrentMetadata::new(info_hash.clone(), from.clone())).await;
+        assert!(client.set_location(&info_hash, &to).await.is_err());
+        assert_eq!(client.get_metadata(&info_hash).await.unwrap().save_path, from);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_super_seeding_unsupported_and_persisted() {
+        use crate::database::{db::Database, torrent_queries};
+        use crate::utils::enhanced_error::UserError;
+
+        let info_hash = format!("{:040x}", 9);
+        let client = LibrqbitTorrentClient::new_disabled();
+        client.restore(handle(&info_hash, None, vec![]), TorrentMetadata::new(info_hash.clone(), PathBuf::new())).await;
+
+        // Refused with a code the UI can key on, leaving the flag alone
+        let error = client.set_super_seeding(&info_hash, true).await.unwrap_err();
+        assert!(error.to_string().starts_with("UNSUPPORTED: "));
+        assert_eq!(UserError::from_app_error(&error).error_code, "UNSUPPORTED");
+        assert!(!client.get_metadata(&info_hash).await.unwrap().super_seeding);
+        client.set_super_seeding(&info_hash, false).await.unwrap();
+
+        // The flag is saved and restored with the torrent
+        let dir = std::env::temp_dir().join(format!("afk-dunld-superseed-{}", uuid::Uuid::new_v4()));
+        let db = Database::new(&dir).await.unwrap();
+        db.run_migrations().await.unwrap();
+        let (saved, mut metadata) = client.snapshot(&info_hash).await.unwrap();
+        metadata.super_seeding = true;
+        torrent_queries::save_torrent(db.pool(), &saved.info, &saved.stats, &metadata, &saved.state).await.unwrap();
+        let super_seeding = |loaded: Vec<(TorrentHandle, TorrentMetadata)>| {
+            loaded.into_iter().find(|(h, _)| h.info.info_hash == info_hash).map(|(_, m)| m.super_seeding)
+        };
+        assert_eq!(super_seeding(torrent_queries::load_torrent_handles(db.pool()).await.unwrap()), Some(true));
+
+        torrent_queries::update_torrent_super_seeding(db.pool(), &info_hash, false).await.unwrap();
+        assert_eq!(super_seeding(torrent_queries::load_torrent_handles(db.pool()).await.unwrap()), Some(false));
+        db.pool().close().await;
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_session_info_aggregates_and_caches() {
+        let client = LibrqbitTorrentClient::new_disabled();
+        let states = [TorrentState::Downloading, TorrentState::Seeding, TorrentState::Paused];
+        for (i, state) in states.into_iter().enumerate() {
+            let info_hash = format!("{:040x}", i);
+            let mut handle = handle(&info_hash, None, vec![]);
+            handle.state = state;
+            handle.stats.download_rate = 1000;
+            handle.stats.upload_rate = 100;
+            client.restore(handle, TorrentMetadata::new(info_hash, PathBuf::new())).await;
+        }
+
+        let info = client.session_info().await;
+        assert!(!info.session_running);
+        assert_eq!((info.active_torrents, info.seeding_torrents, info.paused_torrents), (1, 1, 1));
+        // Paused torrents don't count towards the rates
+        assert_eq!((info.download_rate, info.upload_rate), (2000, 200));
+        assert!(!info.incoming_connections_seen);
+
+        // Served from cache within the refresh window
+        client.remove(&format!("{:040x}", 0), false).await.unwrap();
+        client.note_incoming_peers(3);
+        let cached = client.session_info().await;
+        assert_eq!(cached.active_torrents, 1);
+        assert!(!cached.incoming_connections_seen);
+
+        // Still reported once the inbound peers have gone
+        *client.session_info.lock() = None;
+        client.note_incoming_peers(0);
+        assert!(client.session_info().await.incoming_connections_seen);
+    }
+}
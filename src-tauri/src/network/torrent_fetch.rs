@@ -0,0 +1,134 @@
+// src-tauri/src/network/torrent_fetch.rs
+// Fetching .torrent files linked from the web, so a link to one can be added
+// like a magnet instead of being saved as a plain download first.
+
+use thiserror::Error;
+use url::Url;
+
+use crate::network::bencode_parser::BencodeTorrentFile;
+
+/// Metainfo for very large torrents runs to a few MB; anything bigger is
+/// not a torrent
+pub const MAX_TORRENT_FILE_SIZE: usize = 10 * 1024 * 1024;
+
+/// Fetching and parsing fail differently so the UI can say which it was
+#[derive(Debug, Error)]
+pub enum TorrentFetchError {
+    #[error("TORRENT_FETCH_FAILED: {0}")]
+    Fetch(String),
+
+    #[error("TORRENT_PARSE_FAILED: {0}")]
+    Parse(String),
+}
+
+/// Whether `text` is an http(s) link to a .torrent file
+pub fn is_torrent_url(text: &str) -> bool {
+    match Url::parse(text.trim()) {
+        Ok(url) => {
+            matches!(url.scheme(), "http" | "https")
+                && url.path().to_ascii_lowercase().ends_with(".torrent")
+        }
+        Err(_) => false,
+    }
+}
+
+/// Reject responses that are clearly not a torrent, like the HTML login or
+/// error page a tracker serves in its place
+fn check_content_type(content_type: Option<&str>) -> Result<(), TorrentFetchError> {
+    let Some(content_type) = content_type else {
+        return Ok(());
+    };
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    if essence == "text/html" || essence == "application/xhtml+xml" {
+        return Err(TorrentFetchError::Fetch(format!(
+            "server returned a web page ({}) instead of a torrent",
+            essence
+        )));
+    }
+    Ok(())
+}
+
+/// Download the .torrent at `url`, up to `MAX_TORRENT_FILE_SIZE`
+pub async fn fetch_torrent(client: &reqwest::Client, url: &str) -> Result<Vec<u8>, TorrentFetchError> {
+    let parsed = Url::parse(url.trim()).map_err(|e| TorrentFetchError::Fetch(format!("invalid URL: {}", e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(TorrentFetchError::Fetch(format!("unsupported scheme '{}'", parsed.scheme())));
+    }
+
+    let mut response = client
+        .get(parsed)
+        .send()
+        .await
+        .map_err(|e| TorrentFetchError::Fetch(e.to_string()))?;
+    if !response.status().is_success() {
+        return Err(TorrentFetchError::Fetch(format!("server returned {}", response.status())));
+    }
+    if response.content_length().is_some_and(|len| len > MAX_TORRENT_FILE_SIZE as u64) {
+        return Err(TorrentFetchError::Fetch("file is too large to be a torrent".to_string()));
+    }
+    check_content_type(
+        response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok()),
+    )?;
+
+    // Content-Length can be missing or wrong, so cap what is read too
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| TorrentFetchError::Fetch(e.to_string()))?
+    {
+        if bytes.len() + chunk.len() > MAX_TORRENT_FILE_SIZE {
+            return Err(TorrentFetchError::Fetch("file is too large to be a torrent".to_string()));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+/// Check fetched bytes are torrent metainfo, returning its info hash
+pub fn parse_info_hash(metainfo: &[u8]) -> Result<String, TorrentFetchError> {
+    BencodeTorrentFile::from_bytes(metainfo)
+        .and_then(|torrent| torrent.preferred_info_hash())
+        .map_err(|e| TorrentFetchError::Parse(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_torrent_url() {
+        assert!(is_torrent_url("https://example.com/files/ubuntu.iso.torrent"));
+        assert!(is_torrent_url(" http://example.com/a.TORRENT?key=abc "));
+        assert!(!is_torrent_url("https://example.com/download.php?id=12"));
+        assert!(!is_torrent_url("ftp://example.com/a.torrent"));
+        assert!(!is_torrent_url("magnet:?xt=urn:btih:abc"));
+        assert!(!is_torrent_url("a.torrent"));
+    }
+
+    #[test]
+    fn test_content_type_rejects_pages() {
+        assert!(check_content_type(None).is_ok());
+        assert!(check_content_type(Some("application/x-bittorrent")).is_ok());
+        assert!(check_content_type(Some("application/octet-stream")).is_ok());
+        assert!(matches!(
+            check_content_type(Some("text/html; charset=utf-8")),
+            Err(TorrentFetchError::Fetch(_))
+        ));
+    }
+
+    #[test]
+    fn test_garbage_is_a_parse_error() {
+        let err = parse_info_hash(b"<html>not a torrent</html>").unwrap_err();
+        assert!(matches!(err, TorrentFetchError::Parse(_)));
+        assert!(err.to_string().starts_with("TORRENT_PARSE_FAILED"));
+    }
+}
@@ -2,7 +2,7 @@
 // Helper utilities for torrent management
 
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Torrent priority levels
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
@@ -124,15 +124,20 @@ impl TorrentSchedule {
     }
 
     pub fn is_active_now(&self) -> bool {
+        self.is_active_at(chrono::Local::now().naive_local())
+    }
+
+    /// Whether the schedule window is open at the given local time.
+    /// Both ends of the window are inclusive.
+    pub fn is_active_at(&self, at: chrono::NaiveDateTime) -> bool {
         if !self.enabled {
             return true; // No schedule = always active
         }
 
-        use chrono::{Local, Timelike, Datelike};
-        let now = Local::now();
-        let current_hour = now.hour();
-        let current_minute = now.minute();
-        let current_day = now.weekday().num_days_from_sunday() as u8;
+        use chrono::{Timelike, Datelike};
+        let current_hour = at.hour();
+        let current_minute = at.minute();
+        let current_day = at.weekday().num_days_from_sunday() as u8;
 
         // Check day of week
         if !self.days_of_week.is_empty() && !self.days_of_week.contains(&current_day) {
@@ -161,6 +166,37 @@ impl TorrentSchedule {
     }
 }
 
+/// Change the schedule enforcer should apply to a torrent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScheduleAction {
+    Pause,
+    Resume,
+}
+
+/// Decide whether a torrent must be paused or resumed at `at`, given the
+/// previous check at `last` (None on the first check after startup).
+///
+/// Torrents are only paused when the window closes, so one the user
+/// resumed outside it keeps running; on the first check there is no
+/// earlier state, so one running outside its window is paused. Only
+/// torrents paused by the schedule itself (`auto_paused`) are resumed,
+/// so a manual pause is never overridden.
+pub fn schedule_action(
+    schedule: &TorrentSchedule,
+    is_paused: bool,
+    auto_paused: bool,
+    last: Option<chrono::NaiveDateTime>,
+    at: chrono::NaiveDateTime,
+) -> Option<ScheduleAction> {
+    let inside = schedule.is_active_at(at);
+    let closed = last.is_none_or(|last| schedule.is_active_at(last));
+    match (inside, is_paused) {
+        (false, false) if closed => Some(ScheduleAction::Pause),
+        (true, true) if auto_paused => Some(ScheduleAction::Resume),
+        _ => None,
+    }
+}
+
 fn parse_time(time_str: &str) -> Option<u32> {
     let parts: Vec<&str> = time_str.split(':').collect();
     if parts.len() != 2 {
@@ -189,6 +225,15 @@ pub struct TorrentMetadata {
     pub added_time: chrono::DateTime<chrono::Utc>,
     pub completed_time: Option<chrono::DateTime<chrono::Utc>>,
     pub save_path: PathBuf,
+    /// Paused by the schedule enforcer rather than by the user
+    #[serde(default)]
+    pub auto_paused: bool,
+    /// Super-seeding (BEP 16) requested for this torrent
+    #[serde(default)]
+    pub super_seeding: bool,
+    /// Indexes of the files an add filter selected; None downloads all
+    #[serde(default)]
+    pub only_files: Option<Vec<usize>>,
 }
 
 impl TorrentMetadata {
@@ -203,6 +248,9 @@ impl TorrentMetadata {
             added_time: chrono::Utc::now(),
             completed_time: None,
             save_path,
+            auto_paused: false,
+            super_seeding: false,
+            only_files: None,
         }
     }
 
@@ -286,10 +334,115 @@ impl TorrentFilter {
     }
 }
 
+/// Filters for listing torrents; unset fields match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TorrentFilter {
+    /// State name, e.g. "Downloading" or "Seeding" (case-insensitive)
+    pub state: Option<String>,
+    pub category: Option<String>,
+    pub tag: Option<String>,
+    /// Case-insensitive substring of the torrent name
+    pub name: Option<String>,
+}
+
+impl TorrentFilter {
+    pub fn matches(&self, name: &str, state: &str, metadata: &TorrentMetadata) -> bool {
+        if let Some(wanted) = &self.state {
+            if !wanted.eq_ignore_ascii_case(state) {
+                return false;
+            }
+        }
+        if let Some(category) = &self.category {
+            if metadata.category.as_deref() != Some(category.as_str()) {
+                return false;
+            }
+        }
+        if let Some(tag) = &self.tag {
+            if !metadata.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                return false;
+            }
+        }
+        if let Some(needle) = &self.name {
+            if !name.to_lowercase().contains(&needle.to_lowercase()) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Where file `file_index` of a torrent named `name` lives under its save
+/// directory, or its largest file when no index is given. `files` are the
+/// torrent's (path, size) entries; a single-file torrent's only entry is
+/// its name, multi-file entries sit in a folder named after the torrent.
+pub fn content_path(
+    save_path: &Path,
+    name: &str,
+    files: &[(PathBuf, u64)],
+    file_index: Option<usize>,
+) -> Option<PathBuf> {
+    let (path, _) = match file_index {
+        Some(index) => files.get(index)?,
+        None => files.iter().max_by_key(|(_, size)| *size)?,
+    };
+    let single_file = files.len() == 1 && path == Path::new(name);
+    Some(if single_file {
+        save_path.join(path)
+    } else {
+        save_path.join(name).join(path)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_content_path() {
+        let save = Path::new("/downloads");
+        let single = [(PathBuf::from("movie.mkv"), 700)];
+        assert_eq!(content_path(save, "movie.mkv", &single, None), Some(PathBuf::from("/downloads/movie.mkv")));
+
+        let multi = [
+            (PathBuf::from("readme.txt"), 1),
+            (PathBuf::from("disc/image.iso"), 4000),
+            (PathBuf::from("extras.zip"), 20),
+        ];
+        assert_eq!(
+            content_path(save, "Distro", &multi, None),
+            Some(PathBuf::from("/downloads/Distro/disc/image.iso"))
+        );
+        assert_eq!(
+            content_path(save, "Distro", &multi, Some(0)),
+            Some(PathBuf::from("/downloads/Distro/readme.txt"))
+        );
+        assert_eq!(content_path(save, "Distro", &multi, Some(3)), None);
+        assert_eq!(content_path(save, "Distro", &[], None), None);
+    }
+
+    #[test]
+    fn test_torrent_filter() {
+        let mut metadata = TorrentMetadata::new("abc".to_string(), PathBuf::from("/tmp"));
+        metadata.category = Some("videos".to_string());
+        metadata.add_tag("Linux".to_string());
+
+        assert!(TorrentFilter::default().matches("Ubuntu ISO", "Seeding", &metadata));
+
+        let filter = TorrentFilter {
+            state: Some("seeding".to_string()),
+            category: Some("videos".to_string()),
+            tag: Some("linux".to_string()),
+            name: Some("ubuntu".to_string()),
+        };
+        assert!(filter.matches("Ubuntu ISO", "Seeding", &metadata));
+        assert!(!filter.matches("Ubuntu ISO", "Paused", &metadata));
+        assert!(!filter.matches("Fedora ISO", "Seeding", &metadata));
+
+        let other_tag = TorrentFilter { tag: Some("games".to_string()), ..Default::default() };
+        assert!(!other_tag.matches("Ubuntu ISO", "Seeding", &metadata));
+    }
+
     #[test]
     fn test_priority_conversion() {
         assert_eq!(TorrentPriority::from_i32(0), TorrentPriority::Low);
@@ -316,6 +469,64 @@ mod tests {
         assert_eq!(invalid, None);
     }
 
+    fn at(date: (i32, u32, u32), time: (u32, u32)) -> chrono::NaiveDateTime {
+        chrono::NaiveDate::from_ymd_opt(date.0, date.1, date.2)
+            .unwrap()
+            .and_hms_opt(time.0, time.1, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_schedule_window_boundaries() {
+        let mut schedule = TorrentSchedule::new();
+        schedule.set_time_range("08:00".to_string(), "18:00".to_string());
+
+        assert!(!schedule.is_active_at(at((2024, 1, 3), (7, 59))));
+        assert!(schedule.is_active_at(at((2024, 1, 3), (8, 0))));
+        assert!(schedule.is_active_at(at((2024, 1, 3), (18, 0))));
+        assert!(!schedule.is_active_at(at((2024, 1, 3), (18, 1))));
+    }
+
+    #[test]
+    fn test_schedule_window_crossing_midnight() {
+        let mut schedule = TorrentSchedule::new();
+        schedule.set_time_range("22:00".to_string(), "06:00".to_string());
+
+        assert!(!schedule.is_active_at(at((2024, 1, 3), (21, 59))));
+        assert!(schedule.is_active_at(at((2024, 1, 3), (22, 0))));
+        assert!(schedule.is_active_at(at((2024, 1, 3), (23, 59))));
+        assert!(schedule.is_active_at(at((2024, 1, 4), (0, 0))));
+        assert!(schedule.is_active_at(at((2024, 1, 4), (6, 0))));
+        assert!(!schedule.is_active_at(at((2024, 1, 4), (6, 1))));
+    }
+
+    #[test]
+    fn test_schedule_action_transitions() {
+        let mut schedule = TorrentSchedule::new();
+        schedule.set_time_range("22:00".to_string(), "06:00".to_string());
+        let opening = at((2024, 1, 3), (22, 0));
+        let last_open = at((2024, 1, 4), (6, 0));
+        let closed = at((2024, 1, 4), (6, 1));
+        let later = at((2024, 1, 4), (6, 2));
+
+        // Window closes on a running torrent
+        assert_eq!(schedule_action(&schedule, false, false, Some(last_open), closed), Some(ScheduleAction::Pause));
+        // First check after startup, outside the window
+        assert_eq!(schedule_action(&schedule, false, false, None, closed), Some(ScheduleAction::Pause));
+        // A torrent the user resumed outside the window is left running
+        assert_eq!(schedule_action(&schedule, false, false, Some(closed), later), None);
+        // Window opens on a torrent the schedule paused
+        assert_eq!(schedule_action(&schedule, true, true, Some(closed), opening), Some(ScheduleAction::Resume));
+        // A manual pause is respected inside the window
+        assert_eq!(schedule_action(&schedule, true, false, Some(closed), opening), None);
+        // Nothing to do when already in the right state
+        assert_eq!(schedule_action(&schedule, false, false, Some(closed), opening), None);
+        assert_eq!(schedule_action(&schedule, true, true, Some(last_open), closed), None);
+
+        // Disabled schedules never pause
+        assert_eq!(schedule_action(&TorrentSchedule::new(), false, false, None, closed), None);
+    }
+
     #[test]
     fn test_metadata_operations() {
         let mut metadata = TorrentMetadata::new(
@@ -2,8 +2,74 @@
 
 use url::Url;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use crate::utils::error::DownloadError;
 
+/// Query parameters stripped from batch URLs unless settings say
+/// otherwise. A trailing `*` matches any parameter with that prefix.
+pub const DEFAULT_TRACKING_PARAMS: &[&str] = &["utm_*", "fbclid", "gclid"];
+
+/// Query parameters that mark a signed URL. Its query is part of the
+/// signature, so nothing is stripped from it.
+const SIGNATURE_PARAMS: &[&str] = &[
+    "signature",
+    "sig",
+    "x-amz-signature",
+    "x-amz-credential",
+    "x-goog-signature",
+    "x-goog-credential",
+    "key-pair-id",
+    "policy",
+    "hmac",
+    "token",
+];
+
+/// How a batch of URLs is normalized
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NormalizeOptions {
+    /// Strip the tracking parameters; off for URLs whose parameters matter
+    pub strip_tracking: bool,
+    /// From settings rather than each call
+    #[serde(skip)]
+    pub tracking_params: Vec<String>,
+    /// Relative URLs are resolved against this, such as the page they
+    /// were copied from
+    pub base_url: Option<String>,
+}
+
+impl Default for NormalizeOptions {
+    fn default() -> Self {
+        Self {
+            strip_tracking: true,
+            tracking_params: DEFAULT_TRACKING_PARAMS.iter().map(|p| p.to_string()).collect(),
+            base_url: None,
+        }
+    }
+}
+
+/// What a batch does with one of its URLs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchUrlAction {
+    Added,
+    /// Normalizes to a URL earlier in the batch
+    SkippedDuplicate,
+    Invalid,
+}
+
+/// One input of a batch, what it normalized to and what is done with it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizedUrl {
+    pub input: String,
+    pub normalized: Option<String>,
+    pub action: BatchUrlAction,
+    /// Why an invalid URL was rejected
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ParsedUrl {
     pub url: String,
@@ -117,6 +183,92 @@ impl UrlParser {
         None
     }
 
+    /// Normalized domain of a URL for grouping: lowercase host
+    /// without a leading "www."
+    pub fn domain(raw_url: &str) -> Option<String> {
+        let parsed = Url::parse(raw_url.trim()).ok()?;
+        let host = parsed.host_str()?.to_lowercase();
+        let host = host.strip_prefix("www.").unwrap_or(&host);
+        if host.is_empty() {
+            None
+        } else {
+            Some(host.to_string())
+        }
+    }
+
+    /// Canonical form of a URL for de-duplication: resolved against the
+    /// base, scheme and host lowercased, fragment removed and tracking
+    /// parameters stripped. Signed URLs keep their query as it is.
+    pub fn normalize(raw_url: &str, options: &NormalizeOptions) -> Result<String, DownloadError> {
+        let trimmed = raw_url.trim();
+        if trimmed.is_empty() {
+            return Err(DownloadError::InvalidUrl("Empty URL".to_string()));
+        }
+
+        let mut url = match (Url::parse(trimmed), options.base_url.as_deref()) {
+            (Ok(url), _) => url,
+            (Err(url::ParseError::RelativeUrlWithoutBase), Some(base)) => Url::parse(base.trim())
+                .and_then(|base| base.join(trimmed))
+                .map_err(|e| DownloadError::InvalidUrl(format!("{}: {}", raw_url, e)))?,
+            (Err(url::ParseError::RelativeUrlWithoutBase), None) => Url::parse(&Self::parse(trimmed)?.url)
+                .map_err(|e| DownloadError::InvalidUrl(format!("{}: {}", raw_url, e)))?,
+            (Err(e), _) => return Err(DownloadError::InvalidUrl(format!("{}: {}", raw_url, e))),
+        };
+
+        // The url crate lowercases the scheme and host of these itself
+        if !["http", "https", "ftp", "ftps"].contains(&url.scheme()) {
+            return Err(DownloadError::InvalidUrl(format!("Unsupported scheme: {}", url.scheme())));
+        }
+        if url.host_str().map_or(true, str::is_empty) {
+            return Err(DownloadError::InvalidUrl("No host in URL".to_string()));
+        }
+
+        url.set_fragment(None);
+        if options.strip_tracking {
+            if let Some(query) = url.query().map(str::to_string) {
+                let pairs: Vec<&str> = query.split('&').filter(|pair| !pair.is_empty()).collect();
+                let names: Vec<String> = pairs.iter().map(|pair| query_name(pair)).collect();
+                if !names.iter().any(|name| SIGNATURE_PARAMS.contains(&name.as_str())) {
+                    // Kept as written, so the remaining values aren't re-encoded
+                    let kept: Vec<&str> = pairs
+                        .iter()
+                        .zip(&names)
+                        .filter(|(_, name)| !is_tracking_param(name, &options.tracking_params))
+                        .map(|(pair, _)| *pair)
+                        .collect();
+                    url.set_query((!kept.is_empty()).then(|| kept.join("&")).as_deref());
+                }
+            }
+        }
+
+        Ok(url.to_string())
+    }
+
+    /// Normalize a batch and drop URLs that normalize to one seen earlier.
+    /// Results keep the order of `inputs`.
+    pub fn normalize_batch(inputs: &[String], options: &NormalizeOptions) -> Vec<NormalizedUrl> {
+        let mut seen = HashSet::new();
+        inputs
+            .iter()
+            .map(|input| match Self::normalize(input, options) {
+                Ok(normalized) => {
+                    let action = if seen.insert(normalized.clone()) {
+                        BatchUrlAction::Added
+                    } else {
+                        BatchUrlAction::SkippedDuplicate
+                    };
+                    NormalizedUrl { input: input.clone(), normalized: Some(normalized), action, error: None }
+                }
+                Err(e) => NormalizedUrl {
+                    input: input.clone(),
+                    normalized: None,
+                    action: BatchUrlAction::Invalid,
+                    error: Some(e.to_string()),
+                },
+            })
+            .collect()
+    }
+
     /// Check if a string looks like a downloadable URL
     pub fn is_downloadable_url(text: &str) -> bool {
         let trimmed = text.trim();
@@ -157,6 +309,24 @@ impl UrlParser {
     }
 }
 
+/// Lowercased, decoded name of one `name=value` query pair
+fn query_name(pair: &str) -> String {
+    let name = pair.split_once('=').map_or(pair, |(name, _)| name);
+    urlencoding_decode(name).to_lowercase()
+}
+
+/// Whether `name` matches one of `patterns`, where a trailing `*`
+/// matches a prefix
+fn is_tracking_param(name: &str, patterns: &[String]) -> bool {
+    patterns.iter().any(|pattern| {
+        let pattern = pattern.trim().to_lowercase();
+        match pattern.strip_suffix('*') {
+            Some(prefix) => !prefix.is_empty() && name.starts_with(prefix),
+            None => !pattern.is_empty() && name == pattern,
+        }
+    })
+}
+
 /// Simple URL decoding
 fn urlencoding_decode(s: &str) -> String {
     let mut result = String::with_capacity(s.len());
@@ -208,6 +378,129 @@ mod tests {
         assert_eq!(name, Some("my file.zip".to_string()));
     }
 
+    #[test]
+    fn test_domain_normalized() {
+        assert_eq!(
+            UrlParser::domain("https://WWW.Example.com/a.zip"),
+            Some("example.com".to_string())
+        );
+        assert_eq!(
+            UrlParser::domain("ftp://files.example.org:21/pub/x.iso"),
+            Some("files.example.org".to_string())
+        );
+        assert_eq!(UrlParser::domain("not a url"), None);
+    }
+
+    fn normalize(url: &str) -> Result<String, DownloadError> {
+        UrlParser::normalize(url, &NormalizeOptions::default())
+    }
+
+    #[test]
+    fn test_normalize_strips_tracking_and_fragment() {
+        assert_eq!(
+            normalize("HTTPS://Example.COM/a.zip?utm_source=x&id=3&UTM_Medium=y&fbclid=z#top").unwrap(),
+            "https://example.com/a.zip?id=3"
+        );
+        assert_eq!(normalize("https://example.com/a.zip?gclid=1").unwrap(), "https://example.com/a.zip");
+        // Other parameters keep their encoding and order
+        assert_eq!(
+            normalize("https://example.com/get?b=a%20b&a=1+2&utm_id=9").unwrap(),
+            "https://example.com/get?b=a%20b&a=1+2"
+        );
+        // Default ports go, other ports stay
+        assert_eq!(normalize("http://example.com:80/x").unwrap(), "http://example.com/x");
+        assert_eq!(normalize("http://example.com:8080/x").unwrap(), "http://example.com:8080/x");
+        assert_eq!(normalize("  example.com/file.zip ").unwrap(), "https://example.com/file.zip");
+    }
+
+    #[test]
+    fn test_normalize_options() {
+        let custom = NormalizeOptions {
+            tracking_params: vec!["ref".to_string(), "mc_*".to_string()],
+            ..NormalizeOptions::default()
+        };
+        assert_eq!(
+            UrlParser::normalize("https://example.com/a?ref=tw&mc_cid=1&utm_source=x", &custom).unwrap(),
+            "https://example.com/a?utm_source=x"
+        );
+
+        let keep = NormalizeOptions { strip_tracking: false, ..NormalizeOptions::default() };
+        assert_eq!(
+            UrlParser::normalize("https://example.com/a?utm_source=x#frag", &keep).unwrap(),
+            "https://example.com/a?utm_source=x"
+        );
+
+        // An empty or bare `*` pattern matches nothing
+        let empty = NormalizeOptions {
+            tracking_params: vec!["".to_string(), "*".to_string()],
+            ..NormalizeOptions::default()
+        };
+        assert_eq!(
+            UrlParser::normalize("https://example.com/a?x=1", &empty).unwrap(),
+            "https://example.com/a?x=1"
+        );
+    }
+
+    #[test]
+    fn test_normalize_leaves_signed_urls() {
+        let signed = "https://bucket.s3.amazonaws.com/f.bin?X-Amz-Credential=abc&utm_source=x&X-Amz-Signature=def";
+        assert_eq!(normalize(signed).unwrap(), signed);
+        let cdn = "https://cdn.example.com/v.mp4?expires=1&sig=abc&fbclid=1#t=10";
+        assert_eq!(normalize(cdn).unwrap(), "https://cdn.example.com/v.mp4?expires=1&sig=abc&fbclid=1");
+    }
+
+    #[test]
+    fn test_normalize_relative_and_invalid() {
+        let base = NormalizeOptions {
+            base_url: Some("https://example.com/downloads/index.html".to_string()),
+            ..NormalizeOptions::default()
+        };
+        assert_eq!(
+            UrlParser::normalize("files/a.zip", &base).unwrap(),
+            "https://example.com/downloads/files/a.zip"
+        );
+        assert_eq!(UrlParser::normalize("/b.zip?utm_x=1", &base).unwrap(), "https://example.com/b.zip");
+        assert_eq!(
+            UrlParser::normalize("//mirror.example.org/c.zip", &base).unwrap(),
+            "https://mirror.example.org/c.zip"
+        );
+        assert_eq!(UrlParser::normalize("https://other.org/d", &base).unwrap(), "https://other.org/d");
+
+        assert!(normalize("").is_err());
+        assert!(normalize("javascript:alert(1)").is_err());
+        assert!(normalize("mailto:me@example.com").is_err());
+        assert!(normalize("https://").is_err());
+    }
+
+    #[test]
+    fn test_normalize_batch() {
+        let inputs: Vec<String> = [
+            "https://example.com/a.zip?utm_source=news",
+            "https://EXAMPLE.com/a.zip#download",
+            "not a url at all",
+            "https://example.com/b.zip",
+            "https://example.com/a.zip?fbclid=1",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let results = UrlParser::normalize_batch(&inputs, &NormalizeOptions::default());
+        let actions: Vec<_> = results.iter().map(|r| r.action).collect();
+        assert_eq!(
+            actions,
+            vec![
+                BatchUrlAction::Added,
+                BatchUrlAction::SkippedDuplicate,
+                BatchUrlAction::Invalid,
+                BatchUrlAction::Added,
+                BatchUrlAction::SkippedDuplicate,
+            ]
+        );
+        assert_eq!(results[1].input, inputs[1]);
+        assert_eq!(results[1].normalized.as_deref(), Some("https://example.com/a.zip"));
+        assert!(results[2].normalized.is_none() && results[2].error.is_some());
+    }
+
     #[test]
     fn test_downloadable_url() {
         assert!(UrlParser::is_downloadable_url(
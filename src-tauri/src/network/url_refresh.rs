@@ -0,0 +1,207 @@
+// src-tauri/src/network/url_refresh.rs
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+use crate::utils::error::DownloadError;
+use crate::utils::privacy::{self, PrivacyLevel};
+
+/// How long the extension or a webhook gets to hand back a fresh URL
+const REFRESH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Where a download whose signed URL expired can get a new one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum UrlRefresh {
+    /// Ask the browser extension that added the download; `request_id`
+    /// identifies the resource on its side
+    #[serde(rename_all = "camelCase")]
+    Extension { request_id: String },
+    /// POST to `endpoint`, which answers with `{"url": ...}`
+    Webhook { endpoint: String },
+}
+
+/// A refresh sent to the browser extension over native messaging
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshRequest {
+    pub request_id: String,
+    pub download_id: String,
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct WebhookReply {
+    url: String,
+}
+
+/// Gets fresh URLs for downloads whose signed links expired mid-transfer
+pub struct UrlRefresher {
+    client: Client,
+    /// Forwards requests to the connected extension, if any
+    extension: parking_lot::RwLock<Option<flume::Sender<RefreshRequest>>>,
+    /// Requests waiting on the extension, by request id
+    pending: parking_lot::Mutex<HashMap<String, oneshot::Sender<String>>>,
+    /// Applied to webhook bodies; below `Full` the webhook has to find
+    /// the resource by download id
+    privacy: parking_lot::RwLock<PrivacyLevel>,
+}
+
+impl UrlRefresher {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            extension: parking_lot::RwLock::new(None),
+            pending: parking_lot::Mutex::new(HashMap::new()),
+            privacy: parking_lot::RwLock::new(PrivacyLevel::default()),
+        }
+    }
+
+    pub fn set_privacy(&self, level: PrivacyLevel) {
+        *self.privacy.write() = level;
+    }
+
+    /// Route extension refreshes through `tx`; the native messaging host
+    /// registers one while it is connected, replacing any earlier host
+    pub fn connect_extension(&self, tx: flume::Sender<RefreshRequest>) {
+        *self.extension.write() = Some(tx);
+    }
+
+    /// Stop routing refreshes through `tx`, unless another host has taken
+    /// over since
+    pub fn disconnect_extension(&self, tx: &flume::Sender<RefreshRequest>) {
+        let mut extension = self.extension.write();
+        if extension.as_ref().is_some_and(|current| current.same_channel(tx)) {
+            *extension = None;
+            self.pending.lock().clear();
+        }
+    }
+
+    /// Hand a fresh URL from the extension to the download waiting on it.
+    /// False when nothing is waiting for `request_id`.
+    pub fn complete(&self, request_id: &str, url: String) -> bool {
+        match self.pending.lock().remove(request_id) {
+            Some(tx) => tx.send(url).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Ask for a fresh URL for the resource `url` pointed to
+    pub async fn refresh(
+        &self,
+        download_id: &str,
+        url: &str,
+        refresh: &UrlRefresh,
+    ) -> Result<String, DownloadError> {
+        let fresh = match refresh {
+            UrlRefresh::Extension { request_id } => {
+                self.ask_extension(download_id, url, request_id).await?
+            }
+            UrlRefresh::Webhook { endpoint } => self.ask_webhook(download_id, url, endpoint).await?,
+        };
+        url::Url::parse(&fresh).map_err(|e| DownloadError::InvalidUrl(format!("Refreshed URL: {}", e)))?;
+        Ok(fresh)
+    }
+
+    async fn ask_extension(&self, download_id: &str, url: &str, request_id: &str) -> Result<String, DownloadError> {
+        let tx = self.extension.read().clone().ok_or_else(|| {
+            DownloadError::ExpiredUrl("the browser extension is not connected to refresh it".to_string())
+        })?;
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.pending.lock().insert(request_id.to_string(), reply_tx);
+        let request = RefreshRequest {
+            request_id: request_id.to_string(),
+            download_id: download_id.to_string(),
+            url: url.to_string(),
+        };
+        if tx.send_async(request).await.is_err() {
+            self.pending.lock().remove(request_id);
+            return Err(DownloadError::ExpiredUrl("the browser extension disconnected".to_string()));
+        }
+
+        let reply = tokio::time::timeout(REFRESH_TIMEOUT, reply_rx).await;
+        self.pending.lock().remove(request_id);
+        match reply {
+            Ok(Ok(url)) => Ok(url),
+            Ok(Err(_)) => Err(DownloadError::ExpiredUrl("the browser extension disconnected".to_string())),
+            Err(_) => Err(DownloadError::ExpiredUrl("the browser extension did not send a new link".to_string())),
+        }
+    }
+
+    async fn ask_webhook(&self, download_id: &str, url: &str, endpoint: &str) -> Result<String, DownloadError> {
+        let mut body = serde_json::json!({ "downloadId": download_id, "url": url });
+        privacy::filter_payload(*self.privacy.read(), &mut body);
+        let response = self
+            .client
+            .post(endpoint)
+            .timeout(REFRESH_TIMEOUT)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| DownloadError::ExpiredUrl(format!("refresh webhook failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(DownloadError::ExpiredUrl(format!(
+                "refresh webhook returned {}",
+                response.status()
+            )));
+        }
+        let reply: WebhookReply = response
+            .json()
+            .await
+            .map_err(|e| DownloadError::ExpiredUrl(format!("refresh webhook sent no URL: {}", e)))?;
+        Ok(reply.url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_descriptor_format() {
+        let extension: UrlRefresh = serde_json::from_str(r#"{"kind":"extension","requestId":"r1"}"#).unwrap();
+        assert_eq!(extension, UrlRefresh::Extension { request_id: "r1".to_string() });
+        let webhook: UrlRefresh =
+            serde_json::from_str(r#"{"kind":"webhook","endpoint":"https://hooks.example/refresh"}"#).unwrap();
+        assert_eq!(webhook, UrlRefresh::Webhook { endpoint: "https://hooks.example/refresh".to_string() });
+    }
+
+    #[tokio::test]
+    async fn test_extension_round_trip() {
+        let refresher = std::sync::Arc::new(UrlRefresher::new(Client::new()));
+        let refresh = UrlRefresh::Extension { request_id: "r1".to_string() };
+        assert!(matches!(
+            refresher.refresh("d1", "https://cdn.example/a?sig=old", &refresh).await,
+            Err(DownloadError::ExpiredUrl(_))
+        ));
+
+        let (tx, rx) = flume::unbounded();
+        refresher.connect_extension(tx.clone());
+        let extension = {
+            let refresher = refresher.clone();
+            tokio::spawn(async move {
+                let request = rx.recv_async().await.unwrap();
+                assert_eq!(request.download_id, "d1");
+                assert!(refresher.complete(&request.request_id, "https://cdn.example/a?sig=new".to_string()));
+            })
+        };
+
+        let fresh = refresher.refresh("d1", "https://cdn.example/a?sig=old", &refresh).await.unwrap();
+        assert_eq!(fresh, "https://cdn.example/a?sig=new");
+        extension.await.unwrap();
+        assert!(!refresher.complete("r1", "https://late.example".to_string()));
+
+        // A host that was replaced doesn't disconnect its successor
+        let (newer, _newer_rx) = flume::unbounded();
+        refresher.connect_extension(newer.clone());
+        refresher.disconnect_extension(&tx);
+        assert!(refresher.extension.read().is_some());
+        refresher.disconnect_extension(&newer);
+        assert!(refresher.extension.read().is_none());
+    }
+}
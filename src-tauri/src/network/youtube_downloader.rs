@@ -4,14 +4,18 @@ use std::path::PathBuf;
 use anyhow::{Result, Context, bail};
 use regex::Regex;
 use tokio::io::{AsyncBufReadExt, BufReader};
+use schemars::JsonSchema;
 use serde::{Serialize, Deserialize};
 use tracing::{debug, error, info, warn};
 
+use crate::core::playlist::{parse_flat_playlist, PlaylistListing};
+
 pub struct YouTubeDownloader {
     ytdlp_path: Option<PathBuf>,
+    ffmpeg_path: Option<PathBuf>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct YouTubeDownloadOptions {
     pub url: String,
     pub format_type: String,      // "video" or "audio"
@@ -21,6 +25,9 @@ pub struct YouTubeDownloadOptions {
     pub save_path: PathBuf,
     pub is_playlist: bool,         // Whether to download entire playlist
     pub output_filename: Option<String>, // Optional specific filename to use
+    /// Record finished entries here and skip those already recorded
+    #[serde(default)]
+    pub download_archive: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,10 +63,46 @@ pub struct QualityOption {
     pub has_audio: bool,
 }
 
+/// A yt-dlp run that exited with an error. Shows as the user-facing
+/// message and keeps what went into the run for bug reports.
+#[derive(Debug, Clone)]
+pub struct YtDlpFailure {
+    pub message: String,
+    pub args: Vec<String>,
+    pub exit_code: Option<i32>,
+    pub stderr: String,
+}
+
+impl YtDlpFailure {
+    pub fn new(message: String, args: &[&str], exit_code: Option<i32>, stderr: &str) -> Self {
+        Self {
+            message,
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            exit_code,
+            stderr: stderr.to_string(),
+        }
+    }
+
+    /// The browser whose cookies the run used, if any
+    pub fn cookie_browser(&self) -> Option<&str> {
+        let flag = self.args.iter().position(|arg| arg == "--cookies-from-browser")?;
+        self.args.get(flag + 1).map(String::as_str)
+    }
+}
+
+impl std::fmt::Display for YtDlpFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for YtDlpFailure {}
+
 impl YouTubeDownloader {
     pub fn new() -> Self {
         Self {
             ytdlp_path: None,
+            ffmpeg_path: None,
         }
     }
 
@@ -67,6 +110,15 @@ impl YouTubeDownloader {
     pub fn with_binary_path(ytdlp_path: PathBuf) -> Self {
         Self {
             ytdlp_path: Some(ytdlp_path),
+            ffmpeg_path: None,
+        }
+    }
+
+    /// Use the resolved yt-dlp binary and, when available, the resolved ffmpeg
+    pub fn with_binaries(ytdlp_path: PathBuf, ffmpeg_path: Option<PathBuf>) -> Self {
+        Self {
+            ytdlp_path: Some(ytdlp_path),
+            ffmpeg_path,
         }
     }
 
@@ -227,6 +279,31 @@ impl YouTubeDownloader {
         Ok(info.is_playlist)
     }
 
+    /// List a playlist's entries without downloading or resolving them
+    pub async fn get_playlist_entries(&self, url: &str) -> Result<PlaylistListing> {
+        if !Self::is_supported_url(url) {
+            bail!("Unsupported URL: {}", url);
+        }
+
+        let output = Command::new(self.get_ytdlp_command())
+            .args(["--flat-playlist", "--dump-single-json", "--yes-playlist", "--no-warnings", url])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .await
+            .context("Failed to execute yt-dlp")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("Failed to list playlist: {}", stderr.lines().next().unwrap_or("Unknown error"));
+        }
+
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout)
+            .context("Failed to parse playlist listing")?;
+        let listing = parse_flat_playlist(&json);
+        info!("Playlist {} has {} entries", url, listing.entries.len());
+        Ok(listing)
+    }
+
     /// Download a video or audio from YouTube or other supported platforms
     pub async fn download(&self, options: YouTubeDownloadOptions) -> Result<PathBuf> {
         // Validate URL
@@ -245,7 +322,18 @@ impl YouTubeDownloader {
                 .context("Failed to create output directory")?;
         }
 
+        // Point yt-dlp at the resolved ffmpeg for merging and audio extraction
+        let ffmpeg_location = self.ffmpeg_path.as_ref().map(|p| p.to_string_lossy().to_string());
+
+        let download_archive = options.download_archive.as_ref().map(|p| p.to_string_lossy().to_string());
+
         let mut args = vec![];
+        if let Some(ref location) = ffmpeg_location {
+            args.extend_from_slice(&["--ffmpeg-location", location.as_str()]);
+        }
+        if let Some(ref archive) = download_archive {
+            args.extend_from_slice(&["--download-archive", archive.as_str()]);
+        }
 
         if options.format_type == "audio" {
             // Audio-only download
@@ -409,7 +497,8 @@ impl YouTubeDownloader {
                 "Download failed"
             };
             
-            bail!("{}: {}", error_msg, stderr.lines().next().unwrap_or("Unknown error"));
+            let message = format!("{}: {}", error_msg, stderr.lines().next().unwrap_or("Unknown error"));
+            return Err(YtDlpFailure::new(message, &args, output.status.code(), &stderr).into());
         }
 
         // Find the actual downloaded file
@@ -471,7 +560,18 @@ impl YouTubeDownloader {
                 .context("Failed to create output directory")?;
         }
 
+        // Point yt-dlp at the resolved ffmpeg for merging and audio extraction
+        let ffmpeg_location = self.ffmpeg_path.as_ref().map(|p| p.to_string_lossy().to_string());
+
+        let download_archive = options.download_archive.as_ref().map(|p| p.to_string_lossy().to_string());
+
         let mut args = vec![];
+        if let Some(ref location) = ffmpeg_location {
+            args.extend_from_slice(&["--ffmpeg-location", location.as_str()]);
+        }
+        if let Some(ref archive) = download_archive {
+            args.extend_from_slice(&["--download-archive", archive.as_str()]);
+        }
 
         if options.format_type == "audio" {
             args.extend_from_slice(&[
@@ -591,6 +691,8 @@ impl YouTubeDownloader {
             .args(&args)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
+            // Cancelling drops the download and must not leave yt-dlp running
+            .kill_on_drop(true)
             .spawn()
             .context("Failed to spawn yt-dlp process")?;
 
@@ -627,7 +729,8 @@ impl YouTubeDownloader {
                 "Download failed"
             };
             
-            bail!("{}: {}", error_msg, stderr.lines().next().unwrap_or("Unknown error"));
+            let message = format!("{}: {}", error_msg, stderr.lines().next().unwrap_or("Unknown error"));
+            return Err(YtDlpFailure::new(message, &args, output.status.code(), &stderr).into());
         }
 
         // Send final progress
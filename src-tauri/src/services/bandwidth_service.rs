@@ -0,0 +1,38 @@
+// src-tauri/src/services/bandwidth_service.rs
+// Drives the bandwidth tracker: closes each second's counters and feeds
+// in torrents, which report totals when asked rather than as they move.
+
+use std::collections::HashSet;
+use tauri::{AppHandle, Manager};
+
+use crate::core::bandwidth::{BANDWIDTH_TICK, TORRENT_DOMAIN};
+use crate::state::app_state::AppState;
+
+/// Prefix keeping torrent sources apart from download ids
+const TORRENT_SOURCE_PREFIX: &str = "torrent:";
+
+pub async fn run(app: AppHandle) {
+    let mut interval = tokio::time::interval(BANDWIDTH_TICK);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    loop {
+        interval.tick().await;
+        let state = app.state::<AppState>();
+        let tracker = &state.bandwidth;
+
+        // Uploads use the connection as much as downloads do
+        let mut seen = HashSet::new();
+        for (handle, metadata) in state.torrent_client.snapshot_all().await {
+            let source = format!("{}{}", TORRENT_SOURCE_PREFIX, metadata.info_hash);
+            let total = handle.stats.downloaded + handle.stats.uploaded;
+            tracker.observe(&source, metadata.category.as_deref(), TORRENT_DOMAIN, total);
+            seen.insert(source);
+        }
+        for source in tracker.sources() {
+            if source.starts_with(TORRENT_SOURCE_PREFIX) && !seen.contains(&source) {
+                tracker.end(&source);
+            }
+        }
+
+        tracker.tick();
+    }
+}
@@ -6,8 +6,12 @@ use regex::Regex;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use crate::network::torrent_fetch::is_torrent_url;
+
 /// URL patterns to detect download links
 const URL_PATTERNS: &[&str] = &[
+    // Before the extensions below so "x.iso.torrent" isn't cut at ".iso"
+    r"https?://[^\s<>]+\.torrent(?:\?[^\s<>]*)?",
     r"https?://[^\s<>]+\.(zip|rar|7z|tar|gz|exe|msi|dmg|deb|rpm|apk)",
     r"https?://[^\s<>]+\.(mp4|mkv|avi|mov|webm|mp3|flac|wav|aac)",
     r"https?://[^\s<>]+\.(pdf|doc|docx|xls|xlsx|ppt|pptx)",
@@ -49,6 +53,12 @@ impl ClipboardMonitor {
         tracing::info!("Clipboard monitoring {}", if enabled { "enabled" } else { "disabled" });
     }
 
+    /// Remember text the app copied itself, so it isn't offered back as a
+    /// new download
+    pub async fn note_own_copy(&self, text: &str) {
+        *self.last_content.write().await = text.to_string();
+    }
+
     /// Check clipboard for download URLs
     pub async fn check_clipboard(&self, app: &AppHandle) -> Result<(), String> {
         if !self.is_enabled().await {
@@ -78,8 +88,15 @@ impl ClipboardMonitor {
         if let Some(url) = self.extract_url(&clipboard_text) {
             tracing::info!("Detected download URL in clipboard: {}", url);
             
+            // Links to a .torrent are offered as torrents
+            let event = if is_torrent_url(&url) {
+                "clipboard-torrent-detected"
+            } else {
+                "clipboard-url-detected"
+            };
+
             // Emit event to frontend
-            if let Err(e) = app.emit("clipboard-url-detected", url) {
+            if let Err(e) = app.emit(event, url) {
                 tracing::error!("Failed to emit clipboard event: {}", e);
             }
         }
@@ -113,7 +130,9 @@ pub async fn start_monitoring(app_handle: AppHandle) {
     tracing::info!("Starting clipboard monitoring service...");
 
     let monitor = Arc::new(ClipboardMonitor::new());
-    
+    // Copy commands tell it what they put on the clipboard
+    app_handle.manage(monitor.clone());
+
     // Check if monitoring should be enabled from settings
     if let Some(state) = app_handle.try_state::<crate::state::app_state::AppState>() {
         match state.db.get_setting("monitor_clipboard").await {
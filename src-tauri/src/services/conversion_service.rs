@@ -0,0 +1,252 @@
+// src-tauri/src/services/conversion_service.rs
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::AsyncBufReadExt;
+use tokio::sync::Semaphore;
+use uuid::Uuid;
+
+use crate::core::download_task::DownloadTask;
+use crate::core::post_action::{
+    temp_output_path, ConversionOutput, ConversionProfile, FfmpegProgress, PostAction, PostActionProgress,
+    PostActionStatus,
+};
+use crate::state::app_state::AppState;
+use crate::utils::binaries::Tool;
+use crate::utils::constants::MAX_CONCURRENT_CONVERSIONS;
+use crate::utils::file_utils;
+use crate::utils::sandbox;
+
+/// Runs downloads' post-actions through the managed ffmpeg, apart from
+/// the download queue and only a few at a time
+pub struct ConversionWorker {
+    profiles: parking_lot::RwLock<Vec<ConversionProfile>>,
+    permits: Arc<Semaphore>,
+}
+
+impl ConversionWorker {
+    pub fn new(profiles: Vec<ConversionProfile>) -> Self {
+        Self {
+            profiles: parking_lot::RwLock::new(profiles),
+            permits: Arc::new(Semaphore::new(MAX_CONCURRENT_CONVERSIONS)),
+        }
+    }
+
+    pub fn set_profiles(&self, profiles: Vec<ConversionProfile>) {
+        *self.profiles.write() = profiles;
+    }
+
+    pub fn profile(&self, name: &str) -> Option<ConversionProfile> {
+        self.profiles.read().iter().find(|p| p.name == name).cloned()
+    }
+
+    /// Run `task`'s post-action once the conversions ahead of it finish.
+    /// Failures keep the original file and leave the download Completed.
+    pub fn enqueue(self: &Arc<Self>, app: AppHandle, task: DownloadTask) {
+        let Some(action) = task.post_action.clone() else { return };
+        let worker = self.clone();
+        tokio::spawn(async move {
+            let state = app.state::<AppState>().inner().clone();
+            report(&app, &state, task.id, &action, PostActionStatus::Queued, None).await;
+            let Ok(_permit) = worker.permits.clone().acquire_owned().await else { return };
+
+            report(&app, &state, task.id, &action, PostActionStatus::Running, Some(0.0)).await;
+            let result = match &action {
+                PostAction::Convert { profile } => worker.convert(&app, &state, &task, &action, profile).await,
+            };
+            let status = match result {
+                Ok(output) => {
+                    tracing::info!("{} for {} wrote {:?}", action, task.file_name, output);
+                    PostActionStatus::Completed { output }
+                }
+                Err(error) => {
+                    tracing::warn!("{} failed for {}: {}", action, task.file_name, error);
+                    PostActionStatus::Failed { error }
+                }
+            };
+            report(&app, &state, task.id, &action, status, None).await;
+        });
+    }
+
+    async fn convert(
+        &self,
+        app: &AppHandle,
+        state: &AppState,
+        task: &DownloadTask,
+        action: &PostAction,
+        profile: &str,
+    ) -> Result<PathBuf, String> {
+        let profile = self
+            .profile(profile)
+            .ok_or_else(|| format!("No conversion profile named {}", profile))?;
+        let source = task.save_path.clone();
+        if tokio::fs::metadata(&source).await.is_err() {
+            return Err(format!("{} is missing", source.display()));
+        }
+        let ffmpeg = state
+            .binaries
+            .resolve(Tool::Ffmpeg)
+            .ok_or_else(|| "ffmpeg is not installed".to_string())?
+            .path;
+
+        let mut output = profile.output_path(&source);
+        if output != source {
+            output = file_utils::get_unique_filename(&output).await;
+        }
+        let temp = temp_output_path(&output);
+
+        let converted = run_ffmpeg(&ffmpeg, &source, &profile.args, &temp, |percent| {
+            let _ = app.emit(
+                "post-action-progress",
+                &PostActionProgress {
+                    id: task.id,
+                    action: action.clone(),
+                    status: PostActionStatus::Running,
+                    percent: Some(percent),
+                },
+            );
+        })
+        .await;
+        // The original stays untouched until the output is complete
+        let placed = match converted {
+            Ok(()) => tokio::fs::rename(&temp, &output)
+                .await
+                .map_err(|e| format!("Failed to move the converted file into place: {}", e)),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = placed {
+            let _ = tokio::fs::remove_file(&temp).await;
+            return Err(e);
+        }
+
+        if profile.output == ConversionOutput::Replace && output != source {
+            if let Err(e) = tokio::fs::remove_file(&source).await {
+                tracing::warn!("Failed to remove {:?} after converting it: {}", source, e);
+            }
+            record_new_location(state, task.id, &output).await;
+        }
+        Ok(output)
+    }
+}
+
+/// Queue again the post-actions a restart cut off while they were
+/// Queued or Running. Their temp output is overwritten and the
+/// original is only removed once the new file is in place.
+pub async fn resume_interrupted(app: AppHandle) {
+    let state = app.state::<AppState>().inner().clone();
+    let tasks = match state.db.get_completed_downloads().await {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            tracing::warn!("Failed to look for interrupted post-actions: {}", e);
+            return;
+        }
+    };
+    for task in tasks {
+        if matches!(
+            task.post_action_status,
+            Some(PostActionStatus::Queued | PostActionStatus::Running)
+        ) {
+            tracing::info!("Resuming the post-action for {} after a restart", task.file_name);
+            state.conversions.enqueue(app.clone(), task);
+        }
+    }
+}
+
+impl Default for ConversionWorker {
+    fn default() -> Self {
+        Self::new(ConversionProfile::defaults())
+    }
+}
+
+/// Point the download at the file that replaced its original
+async fn record_new_location(state: &AppState, id: Uuid, output: &Path) {
+    let mut task = match state.db.get_download(id).await {
+        Ok(Some(task)) => task,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::warn!("Failed to load {} to record its converted file: {}", id, e);
+            return;
+        }
+    };
+    task.save_path = output.to_path_buf();
+    task.file_name = output
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or(task.file_name);
+    if let Ok(metadata) = tokio::fs::metadata(output).await {
+        task.total_size = Some(metadata.len());
+        task.downloaded_size = metadata.len();
+    }
+    // The checksum was of the original file
+    task.actual_checksum = None;
+    if let Err(e) = state.db.update_download(&task).await {
+        tracing::warn!("Failed to record converted file for {}: {}", id, e);
+    }
+}
+
+async fn report(
+    app: &AppHandle,
+    state: &AppState,
+    id: Uuid,
+    action: &PostAction,
+    status: PostActionStatus,
+    percent: Option<f64>,
+) {
+    if let Err(e) = state.db.set_post_action_status(id, &status).await {
+        tracing::warn!("Failed to save post-action status for {}: {}", id, e);
+    }
+    let progress = PostActionProgress { id, action: action.clone(), status, percent };
+    let _ = app.emit("post-action-progress", &progress);
+}
+
+/// Convert `source` into `output`, reporting each whole percent done
+async fn run_ffmpeg(
+    ffmpeg: &Path,
+    source: &Path,
+    args: &[String],
+    output: &Path,
+    mut on_progress: impl FnMut(f64),
+) -> Result<(), String> {
+    let mut command = sandbox::command(ffmpeg);
+    command
+        .args(["-hide_banner", "-nostdin", "-y", "-i"])
+        .arg(source)
+        .args(args)
+        .arg(output)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+
+    let mut child = command.spawn().map_err(|e| format!("Failed to start ffmpeg: {}", e))?;
+    let stderr = child
+        .stderr
+        .take()
+        .ok_or_else(|| "ffmpeg's output is unavailable".to_string())?;
+
+    // Progress lines end in \r, everything else in \n
+    let mut segments = tokio::io::BufReader::new(stderr).split(b'\r');
+    let mut progress = FfmpegProgress::default();
+    let mut last_percent = -1.0;
+    let mut last_message = String::new();
+    while let Ok(Some(segment)) = segments.next_segment().await {
+        for line in String::from_utf8_lossy(&segment).lines() {
+            match progress.feed(line) {
+                Some(percent) if percent.floor() > last_percent => {
+                    last_percent = percent.floor();
+                    on_progress(percent);
+                }
+                Some(_) => {}
+                None if !line.trim().is_empty() => last_message = line.trim().to_string(),
+                None => {}
+            }
+        }
+    }
+
+    let status = child.wait().await.map_err(|e| format!("ffmpeg did not finish: {}", e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("ffmpeg exited with {}: {}", status, last_message))
+    }
+}
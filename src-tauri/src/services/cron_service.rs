@@ -0,0 +1,643 @@
+// src-tauri/src/services/cron_service.rs
+
+use chrono::NaiveDateTime;
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use uuid::Uuid;
+
+use crate::commands::download_commands;
+use crate::core::data_usage::CapState;
+use crate::core::forecast::{self, QueueForecast, TaskForecast};
+use crate::database::torrent_queries;
+use crate::network::torrent_client_librqbit::{LibrqbitTorrentClient, TorrentState};
+use crate::network::torrent_helpers::{schedule_action, ScheduleAction};
+use crate::services::hook_service::{self, HookEvent, HookSubject};
+use crate::services::update_service::UpdateService;
+use crate::state::app_state::AppState;
+use crate::utils::logging::Logger;
+
+/// How often time-based rules are evaluated
+pub const CRON_TICK: Duration = Duration::from_secs(60);
+
+/// Source of the current local time, replaceable in tests
+pub trait Clock: Send + Sync {
+    fn now(&self) -> NaiveDateTime;
+}
+
+/// Wall-clock local time
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> NaiveDateTime {
+        chrono::Local::now().naive_local()
+    }
+}
+
+pub type RuleFuture<'a> = Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
+
+/// A time-based rule evaluated on every cron tick
+/// (torrent schedules, quiet hours, bandwidth schedules, ...)
+pub trait CronRule: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn evaluate(&self, now: NaiveDateTime) -> RuleFuture<'_>;
+}
+
+/// Single periodic service that evaluates every registered rule
+pub struct CronService {
+    rules: parking_lot::RwLock<Vec<Arc<dyn CronRule>>>,
+    clock: Arc<dyn Clock>,
+    tick: Duration,
+}
+
+impl CronService {
+    pub fn new(clock: Arc<dyn Clock>, tick: Duration) -> Self {
+        Self {
+            rules: parking_lot::RwLock::new(Vec::new()),
+            clock,
+            tick,
+        }
+    }
+
+    /// Register a rule to be evaluated on every tick
+    pub fn register(&self, rule: Arc<dyn CronRule>) {
+        tracing::info!("Registered cron rule: {}", rule.name());
+        self.rules.write().push(rule);
+    }
+
+    /// Evaluate every rule once against the clock's current time
+    pub async fn run_once(&self) {
+        let now = self.clock.now();
+        let rules = self.rules.read().clone();
+        for rule in rules {
+            tracing::debug!("Evaluating cron rule {} at {}", rule.name(), now);
+            rule.evaluate(now).await;
+        }
+    }
+
+    /// Start ticking in the background
+    pub fn start(self: Arc<Self>) {
+        tauri::async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(self.tick);
+            loop {
+                interval.tick().await;
+                self.run_once().await;
+            }
+        });
+    }
+}
+
+impl Default for CronService {
+    fn default() -> Self {
+        Self::new(Arc::new(SystemClock), CRON_TICK)
+    }
+}
+
+/// Pauses torrents when their schedule window closes and resumes the
+/// ones the schedule paused once it opens again
+pub struct TorrentScheduleRule {
+    app_handle: AppHandle,
+    torrent_client: Arc<LibrqbitTorrentClient>,
+    db: crate::database::db::Database,
+    /// When the schedules were last checked
+    last_run: parking_lot::Mutex<Option<NaiveDateTime>>,
+}
+
+impl TorrentScheduleRule {
+    pub fn new(
+        app_handle: AppHandle,
+        torrent_client: Arc<LibrqbitTorrentClient>,
+        db: crate::database::db::Database,
+    ) -> Self {
+        Self { app_handle, torrent_client, db, last_run: parking_lot::Mutex::new(None) }
+    }
+
+    async fn apply(
+        &self,
+        info_hash: &str,
+        last: Option<NaiveDateTime>,
+        now: NaiveDateTime,
+    ) -> Result<(), String> {
+        let metadata = self.torrent_client.get_metadata(info_hash)
+            .await
+            .map_err(|e| e.to_string())?;
+        let state = self.torrent_client.get_state(info_hash)
+            .await
+            .map_err(|e| e.to_string())?;
+        if matches!(state, TorrentState::Error(_)) {
+            return Ok(());
+        }
+
+        let is_paused = matches!(state, TorrentState::Paused);
+        let action = match schedule_action(&metadata.schedule, is_paused, metadata.auto_paused, last, now) {
+            Some(action) => action,
+            None => return Ok(()),
+        };
+
+        let (new_state, auto_paused) = match action {
+            ScheduleAction::Pause => {
+                self.torrent_client.pause(info_hash).await.map_err(|e| e.to_string())?;
+                (TorrentState::Paused, true)
+            }
+            ScheduleAction::Resume => {
+                let state = self.torrent_client.resume(info_hash).await.map_err(|e| e.to_string())?;
+                (state, false)
+            }
+        };
+        self.torrent_client.set_auto_paused(info_hash, auto_paused)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let pool = self.db.pool();
+        torrent_queries::update_torrent_state(pool, info_hash, &new_state)
+            .await
+            .map_err(|e| e.to_string())?;
+        torrent_queries::update_torrent_auto_paused(pool, info_hash, auto_paused)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        tracing::info!("Torrent schedule applied to {}: {:?}", info_hash, action);
+        let _ = self.app_handle.emit(
+            "torrent-schedule-applied",
+            serde_json::json!({ "infoHash": info_hash, "action": action }),
+        );
+
+        Ok(())
+    }
+}
+
+impl CronRule for TorrentScheduleRule {
+    fn name(&self) -> &str {
+        "torrent-schedule"
+    }
+
+    fn evaluate(&self, now: NaiveDateTime) -> RuleFuture<'_> {
+        Box::pin(async move {
+            let torrents = match self.torrent_client.list_torrents().await {
+                Ok(torrents) => torrents,
+                Err(e) => {
+                    tracing::warn!("Torrent schedule check failed: {}", e);
+                    return;
+                }
+            };
+
+            let last = self.last_run.lock().replace(now);
+            for info in torrents {
+                if let Err(e) = self.apply(&info.info_hash, last, now).await {
+                    tracing::warn!("Failed to apply schedule to {}: {}", info.info_hash, e);
+                }
+            }
+        })
+    }
+}
+
+/// Notices torrents that finished downloading, records when, and runs the
+/// completion hooks for them
+pub struct TorrentCompletionRule {
+    app_handle: AppHandle,
+}
+
+impl TorrentCompletionRule {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self { app_handle }
+    }
+}
+
+impl CronRule for TorrentCompletionRule {
+    fn name(&self) -> &str {
+        "torrent-completion"
+    }
+
+    fn evaluate(&self, _now: NaiveDateTime) -> RuleFuture<'_> {
+        Box::pin(async move {
+            let state = self.app_handle.state::<AppState>();
+            let finished = state.torrent_client.take_newly_completed().await;
+            if finished.is_empty() {
+                return;
+            }
+            for (handle, metadata) in &finished {
+                let info_hash = &metadata.info_hash;
+                if let Err(e) =
+                    torrent_queries::save_torrent(state.db.pool(), &handle.info, &handle.stats, metadata, &handle.state).await
+                {
+                    tracing::warn!("Failed to save finished torrent {}: {}", info_hash, e);
+                }
+                tracing::info!("Torrent {} finished downloading", info_hash);
+                let magnet = state.torrent_client.get_magnet_uri(info_hash).await.unwrap_or_default();
+                hook_service::run_for(
+                    &self.app_handle,
+                    HookEvent::DownloadComplete,
+                    HookSubject::torrent(handle, metadata, magnet),
+                );
+            }
+            hook_service::run_if_all_complete(&self.app_handle).await;
+        })
+    }
+}
+
+/// How often logs are pruned and the database vacuumed
+pub const LOG_MAINTENANCE_INTERVAL: chrono::Duration = chrono::Duration::hours(1);
+
+/// Prunes logs past their retention, then returns freed database pages
+pub struct LogMaintenanceRule {
+    logger: Arc<Logger>,
+    db: crate::database::db::Database,
+    last_run: parking_lot::Mutex<Option<NaiveDateTime>>,
+}
+
+impl LogMaintenanceRule {
+    pub fn new(logger: Arc<Logger>, db: crate::database::db::Database) -> Self {
+        Self { logger, db, last_run: parking_lot::Mutex::new(None) }
+    }
+
+    fn is_due(&self, now: NaiveDateTime) -> bool {
+        let mut last_run = self.last_run.lock();
+        let due = last_run.is_none_or(|last| now - last >= LOG_MAINTENANCE_INTERVAL);
+        if due {
+            *last_run = Some(now);
+        }
+        due
+    }
+}
+
+impl CronRule for LogMaintenanceRule {
+    fn name(&self) -> &str {
+        "log-maintenance"
+    }
+
+    fn evaluate(&self, now: NaiveDateTime) -> RuleFuture<'_> {
+        Box::pin(async move {
+            if !self.is_due(now) {
+                return;
+            }
+
+            let summary = self.logger.prune(chrono::Utc::now()).await;
+            tracing::debug!(
+                "Pruned {} log entries, {} remain",
+                summary.removed,
+                summary.remaining
+            );
+
+            if let Err(e) = self.db.incremental_vacuum().await {
+                tracing::warn!("Log maintenance vacuum failed: {}", e);
+            }
+        })
+    }
+}
+
+/// Checks for a new app release at the configured interval and tells the
+/// UI when one is available; nothing is downloaded automatically
+pub struct UpdateCheckRule {
+    app_handle: AppHandle,
+    updates: Arc<UpdateService>,
+}
+
+impl UpdateCheckRule {
+    pub fn new(app_handle: AppHandle, updates: Arc<UpdateService>) -> Self {
+        Self { app_handle, updates }
+    }
+}
+
+impl CronRule for UpdateCheckRule {
+    fn name(&self) -> &str {
+        "update-check"
+    }
+
+    fn evaluate(&self, now: NaiveDateTime) -> RuleFuture<'_> {
+        Box::pin(async move {
+            if !self.updates.is_check_due(now) {
+                return;
+            }
+
+            match self.updates.check().await {
+                Ok(update) if update.update_available => {
+                    tracing::info!("Update available: {}", update.latest_version);
+                    let _ = self.app_handle.emit("app-update-available", &update);
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Update check failed: {}", e),
+            }
+        })
+    }
+}
+
+/// Starts downloads held for AC power or an allowed network once the
+/// machine meets their conditions again
+pub struct DownloadConditionRule {
+    app_handle: AppHandle,
+}
+
+impl DownloadConditionRule {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self { app_handle }
+    }
+}
+
+impl CronRule for DownloadConditionRule {
+    fn name(&self) -> &str {
+        "download-conditions"
+    }
+
+    fn evaluate(&self, _now: NaiveDateTime) -> RuleFuture<'_> {
+        Box::pin(async move {
+            if let Err(e) = download_commands::promote_held_downloads(&self.app_handle).await {
+                tracing::warn!("Failed to check held downloads: {}", e);
+            }
+        })
+    }
+}
+
+/// Moves finished downloads into place once the files they replace are no
+/// longer open in another program
+pub struct PendingReplaceRule {
+    app_handle: AppHandle,
+}
+
+impl PendingReplaceRule {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self { app_handle }
+    }
+}
+
+impl CronRule for PendingReplaceRule {
+    fn name(&self) -> &str {
+        "pending-replace"
+    }
+
+    fn evaluate(&self, _now: NaiveDateTime) -> RuleFuture<'_> {
+        Box::pin(async move {
+            match download_commands::finalize_pending_replaces(&self.app_handle).await {
+                Ok(0) => {}
+                Ok(moved) => tracing::info!("Moved {} finished downloads into place", moved),
+                Err(e) => tracing::warn!("Failed to check downloads waiting on files in use: {}", e),
+            }
+        })
+    }
+}
+
+/// Records transferred bytes per day and enforces the data cap: slows
+/// transfers past the soft threshold, holds everything once the budget is
+/// used up and lets it go again when the period resets or the cap changes
+pub struct DataUsageRule {
+    app_handle: AppHandle,
+    /// Torrent session totals at the previous tick
+    torrent_totals: parking_lot::Mutex<Option<(u64, u64)>>,
+    /// Period start and bytes used at the previous tick, for warnings
+    last_used: parking_lot::Mutex<Option<(chrono::NaiveDate, u64)>>,
+    /// Torrents paused when the cap was reached
+    capped_torrents: parking_lot::Mutex<Vec<String>>,
+}
+
+impl DataUsageRule {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self {
+            app_handle,
+            torrent_totals: parking_lot::Mutex::new(None),
+            last_used: parking_lot::Mutex::new(None),
+            capped_torrents: parking_lot::Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Torrent bytes since the previous tick. Totals drop when a torrent is
+    /// removed; that tick counts nothing.
+    fn torrent_delta(&self, downloaded: u64, uploaded: u64) -> (u64, u64) {
+        match self.torrent_totals.lock().replace((downloaded, uploaded)) {
+            Some((last_down, last_up)) => {
+                (downloaded.saturating_sub(last_down), uploaded.saturating_sub(last_up))
+            }
+            None => (0, 0),
+        }
+    }
+
+    async fn record(&self, state: &AppState, now: NaiveDateTime) {
+        let http = state.engine.speed_limiter.take_transferred();
+        let session = state.torrent_client.session_info().await;
+        let (torrent_down, torrent_up) = self.torrent_delta(session.total_downloaded, session.total_uploaded);
+
+        if http + torrent_down + torrent_up == 0 {
+            return;
+        }
+        if let Err(e) = state.db.add_daily_usage(now.date(), http + torrent_down, torrent_up).await {
+            tracing::warn!("Failed to record data usage: {}", e);
+        }
+    }
+
+    async fn enforce(&self, state: &AppState, now: NaiveDateTime) -> Result<(), String> {
+        let policy = state.resource_guard.data_cap_policy();
+        if !policy.is_enabled() {
+            state.engine.speed_limiter.set_ceiling(None);
+            self.lift(state).await;
+            return Ok(());
+        }
+
+        let (start, end) = policy.bounds(policy.period, now.date());
+        let (downloaded, uploaded) = state.db
+            .get_usage_between(start, end)
+            .await
+            .map_err(|e| e.to_string())?;
+        let usage = policy.usage(policy.period, now, downloaded, uploaded);
+
+        let previous = self.last_used.lock().replace((start, usage.used_bytes));
+        if let Some((_, previous_used)) = previous.filter(|(previous_start, _)| *previous_start == start) {
+            for percent in policy.crossed_warnings(previous_used, usage.used_bytes) {
+                tracing::info!("Data usage passed {}% of the budget", percent);
+                let _ = self.app_handle.emit(
+                    "data-usage-warning",
+                    serde_json::json!({ "percent": percent, "usage": usage }),
+                );
+            }
+        }
+
+        let throttle = match usage.state {
+            CapState::Normal => None,
+            CapState::Throttled | CapState::Capped => policy.throttle_to,
+        };
+        state.engine.speed_limiter.set_ceiling(throttle);
+
+        if usage.state == CapState::Capped && policy.pause_at_cap {
+            if !state.resource_guard.set_data_capped(true) {
+                let held = download_commands::hold_active_for_data_cap(&self.app_handle).await;
+                let torrents = self.pause_torrents(state).await;
+                tracing::info!("Data cap reached: held {} downloads and {} torrents", held, torrents);
+                let _ = self.app_handle.emit("data-cap-reached", &usage);
+            }
+        } else {
+            self.lift(state).await;
+        }
+        Ok(())
+    }
+
+    async fn pause_torrents(&self, state: &AppState) -> usize {
+        let torrents = match state.torrent_client.list_torrents().await {
+            Ok(torrents) => torrents,
+            Err(e) => {
+                tracing::warn!("Failed to list torrents for the data cap: {}", e);
+                return 0;
+            }
+        };
+
+        let mut paused = Vec::new();
+        for info in torrents {
+            let state_now = state.torrent_client.get_state(&info.info_hash).await;
+            if matches!(state_now, Ok(TorrentState::Paused | TorrentState::Error(_)) | Err(_)) {
+                continue;
+            }
+            match state.torrent_client.pause(&info.info_hash).await {
+                Ok(()) => paused.push(info.info_hash),
+                Err(e) => tracing::warn!("Failed to pause torrent {}: {}", info.info_hash, e),
+            }
+        }
+        let count = paused.len();
+        self.capped_torrents.lock().extend(paused);
+        count
+    }
+
+    /// Release whatever the cap was holding
+    async fn lift(&self, state: &AppState) {
+        if !state.resource_guard.set_data_capped(false) {
+            return;
+        }
+
+        let torrents = std::mem::take(&mut *self.capped_torrents.lock());
+        for info_hash in torrents {
+            if let Err(e) = state.torrent_client.resume(&info_hash).await {
+                tracing::warn!("Failed to resume torrent {}: {}", info_hash, e);
+            }
+        }
+        match download_commands::promote_held_downloads(&self.app_handle).await {
+            Ok(resumed) => tracing::info!("Data cap lifted, {} downloads released", resumed),
+            Err(e) => tracing::warn!("Failed to release held downloads: {}", e),
+        }
+        let _ = self.app_handle.emit("data-cap-lifted", ());
+    }
+}
+
+impl CronRule for DataUsageRule {
+    fn name(&self) -> &str {
+        "data-usage"
+    }
+
+    fn evaluate(&self, now: NaiveDateTime) -> RuleFuture<'_> {
+        Box::pin(async move {
+            let state = self.app_handle.state::<AppState>();
+            self.record(&state, now).await;
+            if let Err(e) = self.enforce(&state, now).await {
+                tracing::warn!("Data cap check failed: {}", e);
+            }
+        })
+    }
+}
+
+/// Warns with `deadline-at-risk` when the forecast puts a download past its
+/// deadline; once per download until it is back on track
+pub struct DeadlineRiskRule {
+    app_handle: AppHandle,
+    warned: parking_lot::Mutex<HashSet<Uuid>>,
+}
+
+impl DeadlineRiskRule {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self { app_handle, warned: parking_lot::Mutex::new(HashSet::new()) }
+    }
+
+    /// Forecasts that newly went past their deadline
+    fn newly_at_risk(&self, forecast: QueueForecast) -> Vec<TaskForecast> {
+        let mut warned = self.warned.lock();
+        let at_risk: HashSet<Uuid> = forecast.tasks.iter().filter(|t| t.at_risk).map(|t| t.id).collect();
+        let fresh = forecast
+            .tasks
+            .into_iter()
+            .filter(|t| t.at_risk && !warned.contains(&t.id))
+            .collect();
+        *warned = at_risk;
+        fresh
+    }
+}
+
+impl CronRule for DeadlineRiskRule {
+    fn name(&self) -> &str {
+        "deadline-risk"
+    }
+
+    fn evaluate(&self, _now: NaiveDateTime) -> RuleFuture<'_> {
+        Box::pin(async move {
+            let state = self.app_handle.state::<AppState>();
+            let snapshot = match download_commands::forecast_snapshot(&state).await {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    tracing::warn!("Completion forecast failed: {}", e);
+                    return;
+                }
+            };
+            if snapshot.tasks.iter().all(|t| t.deadline_at.is_none()) {
+                self.warned.lock().clear();
+                return;
+            }
+
+            for task in self.newly_at_risk(forecast::forecast(&snapshot)) {
+                tracing::info!(
+                    "Download {} is forecast to miss its deadline ({:?} after {:?})",
+                    task.id,
+                    task.completes_at,
+                    task.deadline_at
+                );
+                let _ = self.app_handle.emit("deadline-at-risk", &task);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    struct FixedClock(Mutex<NaiveDateTime>);
+
+    impl Clock for FixedClock {
+        fn now(&self) -> NaiveDateTime {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    struct RecordingRule(Mutex<Vec<NaiveDateTime>>);
+
+    impl CronRule for RecordingRule {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        fn evaluate(&self, now: NaiveDateTime) -> RuleFuture<'_> {
+            Box::pin(async move {
+                self.0.lock().unwrap().push(now);
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rules_receive_injected_time() {
+        let start = chrono::NaiveDate::from_ymd_opt(2024, 1, 3)
+            .unwrap()
+            .and_hms_opt(23, 59, 0)
+            .unwrap();
+        let clock = Arc::new(FixedClock(Mutex::new(start)));
+        let service = CronService::new(clock.clone(), CRON_TICK);
+
+        let first = Arc::new(RecordingRule(Mutex::new(Vec::new())));
+        let second = Arc::new(RecordingRule(Mutex::new(Vec::new())));
+        service.register(first.clone());
+        service.register(second.clone());
+
+        service.run_once().await;
+        *clock.0.lock().unwrap() = start + chrono::Duration::minutes(1);
+        service.run_once().await;
+
+        let expected = vec![start, start + chrono::Duration::minutes(1)];
+        assert_eq!(*first.0.lock().unwrap(), expected);
+        assert_eq!(*second.0.lock().unwrap(), expected);
+    }
+}
@@ -3,7 +3,7 @@
 use std::time::Duration;
 use tracing::{debug, error, info};
 use crate::state::app_state::AppState;
-use crate::core::download_task::DownloadStatus;
+use crate::services::integrity_service;
 use tauri::Emitter;
 
 /// File watcher service that monitors downloaded files and syncs with database
@@ -31,51 +31,31 @@ impl FileWatcher {
     async fn sync_files(
         app_handle: &tauri::AppHandle,
         state: &AppState,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Get all completed downloads from database
-        let downloads = state.db.get_all_downloads().await?;
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let mounts = integrity_service::configured_mounts(&state.db).await;
+        let summary = integrity_service::sweep(&state.db, &mounts).await?;
         
-        let mut deleted_count = 0;
-        
-        for download in downloads {
-            // Only check completed downloads
-            if download.status != DownloadStatus::Completed {
-                continue;
-            }
-            
-            // Check if file exists on disk
-            let file_exists = tokio::fs::metadata(&download.save_path).await.is_ok();
+        for download in &summary.newly_missing {
+            // File was deleted from disk - it stays Completed but is flagged missing
+            debug!(
+                "File not found on disk, marking as missing: {} (ID: {})",
+                download.file_name,
+                download.id
+            );
             
-            if !file_exists {
-                // File was deleted from disk - mark as missing in database
-                debug!(
-                    "File not found on disk, marking as missing: {} (ID: {})",
-                    download.file_name,
-                    download.id
-                );
-                
-                // Update status to Failed with appropriate message
-                state.db.update_status(
-                    download.id,
-                    DownloadStatus::Failed,
-                ).await?;
-                
-                // Emit event to notify frontend
-                let _ = app_handle.emit("file-deleted", serde_json::json!({
-                    "id": download.id.to_string(),
-                    "file_name": download.file_name,
-                    "message": "File was deleted from download folder"
-                }));
-                
-                deleted_count += 1;
-            }
+            // Emit event to notify frontend
+            let _ = app_handle.emit("file-deleted", serde_json::json!({
+                "id": download.id.to_string(),
+                "file_name": download.file_name,
+                "message": "File was deleted from download folder"
+            }));
         }
         
-        if deleted_count > 0 {
-            info!("File watcher: Marked {} downloads as missing", deleted_count);
+        if !summary.newly_missing.is_empty() {
+            info!("File watcher: Marked {} downloads as missing", summary.newly_missing.len());
         }
         
-        Ok(())
+        Ok(summary.missing)
     }
     
     /// Manually trigger a sync (useful for user-initiated checks)
@@ -85,18 +65,6 @@ impl FileWatcher {
     ) -> Result<usize, String> {
         Self::sync_files(app_handle, state)
             .await
-            .map_err(|e| e.to_string())?;
-        
-        // Count missing files
-        let downloads = state.db.get_all_downloads()
-            .await
-            .map_err(|e| e.to_string())?;
-        
-        let missing_count = downloads
-            .iter()
-            .filter(|d| d.status == DownloadStatus::Failed)
-            .count();
-        
-        Ok(missing_count)
+            .map_err(|e| e.to_string())
     }
 }
@@ -0,0 +1,403 @@
+// src-tauri/src/services/hook_service.rs
+// Global hook scripts, run when any download, FTP or SFTP transfer,
+// playlist entry or torrent completes or fails, and when the last one
+// finishes. Scripts learn about the download from AFK_* variables rather
+// than arguments, so nothing in a URL or file name is ever parsed by a
+// shell. A hook's outcome never changes the download's.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use uuid::Uuid;
+
+use crate::core::download_task::DownloadTask;
+use crate::network::torrent_client_librqbit::TorrentHandle;
+use crate::network::torrent_helpers::TorrentMetadata;
+use crate::state::app_state::AppState;
+use crate::utils::error::DownloadError;
+use crate::utils::logging::{LogEntry, LogLevel, Logger};
+use crate::utils::sandbox;
+
+/// How long a hook may run when settings don't say
+pub const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HookEvent {
+    DownloadComplete,
+    DownloadFailed,
+    AllComplete,
+}
+
+impl HookEvent {
+    /// The hook a transfer's result calls for; none when it was paused or
+    /// cancelled
+    pub fn for_result<T>(result: &Result<T, DownloadError>) -> Option<Self> {
+        match result {
+            Ok(_) => Some(HookEvent::DownloadComplete),
+            Err(DownloadError::Cancelled | DownloadError::Paused) => None,
+            Err(_) => Some(HookEvent::DownloadFailed),
+        }
+    }
+}
+
+impl std::fmt::Display for HookEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HookEvent::DownloadComplete => write!(f, "download complete"),
+            HookEvent::DownloadFailed => write!(f, "download failed"),
+            HookEvent::AllComplete => write!(f, "all complete"),
+        }
+    }
+}
+
+/// The scripts configured in settings; None runs nothing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookScripts {
+    pub on_download_complete: Option<PathBuf>,
+    pub on_download_failed: Option<PathBuf>,
+    pub on_all_complete: Option<PathBuf>,
+    pub timeout: Duration,
+}
+
+impl Default for HookScripts {
+    fn default() -> Self {
+        Self {
+            on_download_complete: None,
+            on_download_failed: None,
+            on_all_complete: None,
+            timeout: Duration::from_secs(DEFAULT_HOOK_TIMEOUT_SECS),
+        }
+    }
+}
+
+impl HookScripts {
+    pub fn script_for(&self, event: HookEvent) -> Option<&Path> {
+        match event {
+            HookEvent::DownloadComplete => self.on_download_complete.as_deref(),
+            HookEvent::DownloadFailed => self.on_download_failed.as_deref(),
+            HookEvent::AllComplete => self.on_all_complete.as_deref(),
+        }
+    }
+}
+
+/// Sent with `hook-failed` when a script exits non-zero, times out or
+/// can't start
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookFailure {
+    pub hook: HookEvent,
+    pub script: PathBuf,
+    pub download_id: Option<Uuid>,
+    /// None when the script didn't exit on its own
+    pub exit_code: Option<i32>,
+    pub error: String,
+}
+
+#[derive(Default)]
+pub struct HookRunner {
+    scripts: parking_lot::RwLock<HookScripts>,
+}
+
+impl HookRunner {
+    pub fn set_scripts(&self, scripts: HookScripts) {
+        *self.scripts.write() = scripts;
+    }
+
+    pub fn scripts(&self) -> HookScripts {
+        self.scripts.read().clone()
+    }
+}
+
+/// Check a script setting when saved: empty is allowed and means none
+pub fn validate_script(value: &str) -> Result<Option<PathBuf>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    let path = PathBuf::from(value);
+    if !path.is_absolute() {
+        return Err(format!("Hook script {} must be an absolute path", value));
+    }
+    let metadata = std::fs::metadata(&path).map_err(|e| format!("Hook script {}: {}", value, e))?;
+    if !metadata.is_file() {
+        return Err(format!("Hook script {} is not a file", value));
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if metadata.permissions().mode() & 0o111 == 0 {
+            return Err(format!("Hook script {} is not executable", value));
+        }
+    }
+    Ok(Some(path))
+}
+
+/// What a hook's AFK_* variables describe: a download, an FTP or SFTP
+/// transfer, or a torrent
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookSubject {
+    /// The download or transfer id; a torrent's info hash
+    pub id: String,
+    /// The id hook output is logged against
+    pub download_id: Option<Uuid>,
+    pub url: String,
+    pub path: PathBuf,
+    pub size: u64,
+    pub status: String,
+    pub category: Option<String>,
+    pub checksum: Option<String>,
+}
+
+impl From<&DownloadTask> for HookSubject {
+    fn from(task: &DownloadTask) -> Self {
+        Self {
+            id: task.id.to_string(),
+            download_id: Some(task.id),
+            url: task.url.clone(),
+            path: task.save_path.clone(),
+            size: task.total_size.unwrap_or(task.downloaded_size),
+            status: task.status.as_str().to_string(),
+            category: task.category.clone(),
+            checksum: task.actual_checksum.clone().or_else(|| task.expected_checksum.clone()),
+        }
+    }
+}
+
+impl HookSubject {
+    /// An FTP or SFTP transfer of `bytes` into `path`, which may be a folder
+    pub fn remote(id: Uuid, url: &str, path: &Path, bytes: u64, category: Option<String>) -> Self {
+        Self {
+            id: id.to_string(),
+            download_id: Some(id),
+            url: url.to_string(),
+            path: path.to_path_buf(),
+            size: bytes,
+            status: "Completed".to_string(),
+            category,
+            checksum: None,
+        }
+    }
+
+    /// A torrent that finished downloading, known by its magnet link
+    pub fn torrent(handle: &TorrentHandle, metadata: &TorrentMetadata, magnet: String) -> Self {
+        Self {
+            id: metadata.info_hash.clone(),
+            download_id: None,
+            url: magnet,
+            path: metadata.save_path.join(&handle.info.name),
+            size: handle.info.total_size,
+            status: "Completed".to_string(),
+            category: metadata.category.clone(),
+            checksum: None,
+        }
+    }
+}
+
+/// The variables a hook gets for `subject`; AFK_STATUS alone for `AllComplete`
+pub fn hook_env(event: HookEvent, subject: Option<&HookSubject>) -> Vec<(&'static str, String)> {
+    let Some(subject) = subject else {
+        return vec![("AFK_STATUS", "AllComplete".to_string())];
+    };
+    let status = match event {
+        HookEvent::DownloadFailed => "Failed",
+        _ => subject.status.as_str(),
+    };
+    vec![
+        ("AFK_ID", subject.id.clone()),
+        ("AFK_URL", subject.url.clone()),
+        ("AFK_PATH", subject.path.to_string_lossy().into_owned()),
+        ("AFK_SIZE", subject.size.to_string()),
+        ("AFK_STATUS", status.to_string()),
+        ("AFK_CATEGORY", subject.category.clone().unwrap_or_default()),
+        ("AFK_CHECKSUM", subject.checksum.clone().unwrap_or_default()),
+    ]
+}
+
+/// Run the script for `event`, if one is set, in the background
+pub fn run(app: &AppHandle, event: HookEvent, task: Option<&DownloadTask>) {
+    spawn_script(app, event, task.map(HookSubject::from));
+}
+
+/// Run the script for `event` about something other than a download row
+pub fn run_for(app: &AppHandle, event: HookEvent, subject: HookSubject) {
+    spawn_script(app, event, Some(subject));
+}
+
+/// Run the `AllComplete` script when nothing is left running or waiting:
+/// no downloads active or queued, and no FTP, SFTP or playlist transfers
+pub async fn run_if_all_complete(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let idle = state.active_downloads.read().await.is_empty()
+        && state.queue.read().await.info().queued_count == 0
+        && state.remote_transfers.lock().is_empty();
+    if idle {
+        run(app, HookEvent::AllComplete, None);
+    }
+}
+
+/// Run the hooks for a finished FTP or SFTP transfer: its own unless it
+/// was cancelled, then `AllComplete` if it was the last thing running
+pub async fn run_remote(app: &AppHandle, event: Option<HookEvent>, subject: HookSubject) {
+    let Some(event) = event else {
+        return;
+    };
+    run_for(app, event, subject);
+    run_if_all_complete(app).await;
+}
+
+fn spawn_script(app: &AppHandle, event: HookEvent, subject: Option<HookSubject>) {
+    let state = app.state::<AppState>();
+    let scripts = state.hooks.scripts();
+    let Some(script) = scripts.script_for(event).map(Path::to_path_buf) else {
+        return;
+    };
+    let env = hook_env(event, subject.as_ref());
+    let download_id = subject.and_then(|s| s.download_id);
+    let logger = state.logger.clone();
+    let app = app.clone();
+    tokio::spawn(async move {
+        let failure = execute(&script, &env, scripts.timeout, event, download_id, &logger).await;
+        if let Some(failure) = failure {
+            tracing::warn!("{} hook {:?} failed: {}", event, failure.script, failure.error);
+            let _ = app.emit("hook-failed", &failure);
+        }
+    });
+}
+
+/// Run one hook and log its output against the download; the failure to
+/// report, if it failed
+async fn execute(
+    script: &Path,
+    env: &[(&'static str, String)],
+    timeout: Duration,
+    event: HookEvent,
+    download_id: Option<Uuid>,
+    logger: &Arc<Logger>,
+) -> Option<HookFailure> {
+    let mut command = sandbox::command(script);
+    command.envs(env.iter().map(|(name, value)| (*name, value)));
+    if let Some(dir) = script.parent() {
+        command.current_dir(dir);
+    }
+
+    let result = sandbox::run_captured(command, timeout).await;
+    let (level, message, exit_code, error) = match &result {
+        Ok(output) if output.success() => (LogLevel::Info, format!("{} hook finished", event), output.exit_code, None),
+        Ok(output) => {
+            let error = match output.exit_code {
+                Some(code) => format!("exited with {}", code),
+                None => "was killed by a signal".to_string(),
+            };
+            (LogLevel::Warn, format!("{} hook {}", event, error), output.exit_code, Some(error))
+        }
+        Err(e) => (LogLevel::Warn, format!("{} hook {}", event, e), None, Some(e.clone())),
+    };
+
+    let (stdout, stderr) = result
+        .as_ref()
+        .map(|output| (output.stdout.as_str(), output.stderr.as_str()))
+        .unwrap_or_default();
+    let entry = LogEntry::new(level, "hook", message).with_metadata(serde_json::json!({
+        "download_id": download_id,
+        "script": script,
+        "exit_code": exit_code,
+        "stdout": stdout,
+        "stderr": stderr,
+    }));
+    logger.log(entry).await;
+
+    error.map(|error| HookFailure {
+        hook: event,
+        script: script.to_path_buf(),
+        download_id,
+        exit_code,
+        error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::download_task::DownloadStatus;
+
+    fn finished_task() -> DownloadTask {
+        let mut task = DownloadTask::new(
+            "https://cdn.example/a b.iso?x=1&y=$(rm -rf)".to_string(),
+            "a b.iso".to_string(),
+            "/data/a b.iso".into(),
+            4,
+        );
+        task.total_size = Some(4096);
+        task.status = DownloadStatus::Completed;
+        task.category = Some("Software".to_string());
+        task.actual_checksum = Some("abc123".to_string());
+        task
+    }
+
+    #[test]
+    fn test_hook_env() {
+        let task = finished_task();
+        let subject = HookSubject::from(&task);
+        let env: std::collections::HashMap<_, _> = hook_env(HookEvent::DownloadComplete, Some(&subject)).into_iter().collect();
+        assert_eq!(env["AFK_URL"], task.url);
+        assert_eq!(env["AFK_PATH"], "/data/a b.iso");
+        assert_eq!(env["AFK_SIZE"], "4096");
+        assert_eq!(env["AFK_STATUS"], "Completed");
+        assert_eq!(env["AFK_CATEGORY"], "Software");
+        assert_eq!(env["AFK_CHECKSUM"], "abc123");
+
+        assert_eq!(hook_env(HookEvent::AllComplete, None), vec![("AFK_STATUS", "AllComplete".to_string())]);
+
+        let id = Uuid::new_v4();
+        let remote = HookSubject::remote(id, "sftp://host.example/pub/a.iso", Path::new("/data/a.iso"), 512, None);
+        let env: std::collections::HashMap<_, _> = hook_env(HookEvent::DownloadFailed, Some(&remote)).into_iter().collect();
+        assert_eq!(env["AFK_ID"], id.to_string());
+        assert_eq!(env["AFK_SIZE"], "512");
+        assert_eq!(env["AFK_STATUS"], "Failed");
+        assert_eq!(env["AFK_CHECKSUM"], "");
+    }
+
+    #[test]
+    fn test_hook_for_result() {
+        assert_eq!(HookEvent::for_result(&Ok::<_, DownloadError>(1)), Some(HookEvent::DownloadComplete));
+        assert_eq!(HookEvent::for_result::<u64>(&Err(DownloadError::Cancelled)), None);
+        assert_eq!(HookEvent::for_result::<u64>(&Err(DownloadError::Paused)), None);
+        assert_eq!(
+            HookEvent::for_result::<u64>(&Err(DownloadError::NetworkError("reset".to_string()))),
+            Some(HookEvent::DownloadFailed)
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_hook_output_is_logged_and_failures_reported() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("afk-dunld-hook-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("hook.sh");
+        std::fs::write(&script, "#!/bin/sh\necho \"got $AFK_URL\"\necho nope >&2\nexit 2\n").unwrap();
+        assert!(validate_script(script.to_str().unwrap()).is_err());
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(validate_script(script.to_str().unwrap()), Ok(Some(script.clone())));
+        assert!(validate_script("relative/hook.sh").is_err());
+
+        let task = finished_task();
+        let logger = Arc::new(Logger::new());
+        let env = hook_env(HookEvent::DownloadComplete, Some(&HookSubject::from(&task)));
+        let failure = execute(&script, &env, Duration::from_secs(5), HookEvent::DownloadComplete, Some(task.id), &logger)
+            .await
+            .unwrap();
+        assert_eq!(failure.exit_code, Some(2));
+
+        // The URL reached the script intact, as data
+        let logs = logger.get_logs_for_download(&task.id.to_string()).await;
+        let metadata = logs[0].metadata.as_ref().unwrap();
+        assert_eq!(metadata["stdout"], format!("got {}\n", task.url));
+        assert_eq!(metadata["stderr"], "nope\n");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
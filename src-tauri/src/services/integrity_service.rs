@@ -0,0 +1,161 @@
+// src-tauri/src/services/integrity_service.rs
+
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::core::download_task::{DownloadTask, IntegrityStatus};
+use crate::database::db::Database;
+use crate::state::app_state::AppState;
+use crate::utils::error::DownloadError;
+
+/// Maximum number of files checked at the same time
+pub const SWEEP_CONCURRENCY: usize = 8;
+
+/// A single stat (or mount probe) slower than this counts as unreachable
+pub const STAT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Settings key: run the sweep when the app starts
+pub const SWEEP_ON_STARTUP_KEY: &str = "integrity_sweep_on_startup";
+
+/// Settings key: newline separated path prefixes of network mounts
+pub const NETWORK_MOUNTS_KEY: &str = "network_mount_prefixes";
+
+/// Outcome of a missing-file sweep over completed downloads
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SweepSummary {
+    pub checked: usize,
+    pub missing: usize,
+    pub restored: usize,
+    /// Downloads on network mounts that were offline during the sweep
+    pub skipped: usize,
+    pub missing_by_category: HashMap<String, usize>,
+    #[serde(skip)]
+    pub newly_missing: Vec<DownloadTask>,
+}
+
+/// Parse the network mount setting into path prefixes
+pub fn parse_network_mounts(value: &str) -> Vec<PathBuf> {
+    value
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+async fn is_reachable(path: &Path) -> bool {
+    matches!(
+        tokio::time::timeout(STAT_TIMEOUT, tokio::fs::metadata(path)).await,
+        Ok(Ok(_))
+    )
+}
+
+/// Check that every completed download still exists on disk (a cheap stat,
+/// no hashing) and record transitions in `integrity_status`.
+/// Files under an offline network mount are skipped rather than flagged.
+pub async fn sweep(db: &Database, network_mounts: &[PathBuf]) -> Result<SweepSummary, DownloadError> {
+    let mut offline = Vec::new();
+    for mount in network_mounts {
+        if !is_reachable(mount).await {
+            tracing::info!("Network mount {:?} is offline, skipping its downloads", mount);
+            offline.push(mount.clone());
+        }
+    }
+
+    let mut summary = SweepSummary::default();
+    let mut tasks = Vec::new();
+    for task in db.get_completed_downloads().await? {
+        if offline.iter().any(|mount| task.save_path.starts_with(mount)) {
+            summary.skipped += 1;
+        } else {
+            tasks.push(task);
+        }
+    }
+
+    let mut results = stream::iter(tasks)
+        .map(|task| async move {
+            let exists = is_reachable(&task.save_path).await;
+            (task, exists)
+        })
+        .buffer_unordered(SWEEP_CONCURRENCY);
+
+    while let Some((task, exists)) = results.next().await {
+        summary.checked += 1;
+        let was_missing = task.integrity_status == Some(IntegrityStatus::Missing);
+
+        if exists {
+            if was_missing {
+                db.set_integrity_status(task.id, Some(IntegrityStatus::Ok)).await?;
+                summary.restored += 1;
+            }
+            continue;
+        }
+
+        summary.missing += 1;
+        *summary
+            .missing_by_category
+            .entry(task.category.clone().unwrap_or_else(|| "default".to_string()))
+            .or_insert(0) += 1;
+        if !was_missing {
+            db.set_integrity_status(task.id, Some(IntegrityStatus::Missing)).await?;
+            summary.newly_missing.push(task);
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Network mount prefixes from settings
+pub async fn configured_mounts(db: &Database) -> Vec<PathBuf> {
+    db.get_setting(NETWORK_MOUNTS_KEY)
+        .await
+        .ok()
+        .flatten()
+        .map(|value| parse_network_mounts(&value))
+        .unwrap_or_default()
+}
+
+/// Run a sweep with the configured mounts and emit one `files-missing`
+/// event summarizing the result
+pub async fn run_sweep(app_handle: &AppHandle, state: &AppState) -> Result<SweepSummary, String> {
+    let mounts = configured_mounts(&state.db).await;
+    let summary = sweep(&state.db, &mounts).await.map_err(|e| e.to_string())?;
+    tracing::info!(
+        "Integrity sweep: {} checked, {} missing, {} restored, {} skipped",
+        summary.checked,
+        summary.missing,
+        summary.restored,
+        summary.skipped
+    );
+
+    if summary.missing > 0 {
+        let _ = app_handle.emit("files-missing", &summary);
+    }
+
+    Ok(summary)
+}
+
+/// Startup entry point, honouring the opt-out setting
+pub async fn run_startup_sweep(app_handle: AppHandle, state: AppState) {
+    let enabled = state
+        .db
+        .get_setting(SWEEP_ON_STARTUP_KEY)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(true);
+    if !enabled {
+        tracing::debug!("Startup integrity sweep disabled");
+        return;
+    }
+
+    if let Err(e) = run_sweep(&app_handle, &state).await {
+        tracing::warn!("Startup integrity sweep failed: {}", e);
+    }
+}
@@ -0,0 +1,228 @@
+// src-tauri/src/services/ipc_service.rs
+// Local endpoint through which secondary entry points (the native messaging
+// host the browser starts, command line invocations) reach the process that
+// owns the downloads database. Requests are native messages, one JSON line
+// each, carrying the token from the owner's lock file. A connection can also
+// be turned into a feed of the URL refresh requests meant for the extension.
+// When metrics are on, the same server answers Prometheus scrapes, on this
+// port and on the configured metrics address, where nothing else is served.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::database::writer_lock::{self, WriterLock};
+use crate::network::url_refresh::RefreshRequest;
+use crate::services::metrics_service;
+use crate::services::native_messaging::{self, NativeMessage, NativeResponse};
+use crate::state::app_state::AppState;
+
+/// How long a secondary waits on the owner before giving up on it
+const IPC_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum IpcCall {
+    /// Answered with one response line
+    Message(NativeMessage),
+    /// From then on the connection only carries `refresh_url` responses
+    /// pushed by the owner, until the caller hangs up
+    UrlRefreshes,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct IpcRequest {
+    token: String,
+    call: IpcCall,
+}
+
+/// Listen on a loopback port and publish it in the lock file
+pub async fn serve(app: AppHandle, lock: Arc<WriterLock>) {
+    let listener = match TcpListener::bind("127.0.0.1:0").await {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::warn!("IPC endpoint unavailable, other entry points will read only: {}", e);
+            return;
+        }
+    };
+    let port = match listener.local_addr() {
+        Ok(addr) => addr.port(),
+        Err(e) => {
+            tracing::warn!("IPC endpoint unavailable: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = lock.set_port(port) {
+        tracing::warn!("Failed to publish IPC endpoint: {}", e);
+        return;
+    }
+    tracing::info!("IPC endpoint listening on 127.0.0.1:{}", port);
+
+    let metrics_address = metrics_service::configured_address(&app).await;
+    if let Some(addr) = metrics_address {
+        match TcpListener::bind(addr).await {
+            Ok(metrics_listener) => {
+                tracing::info!("Serving metrics on http://{}/metrics", addr);
+                let endpoint = Endpoint { token: None, metrics: true };
+                tauri::async_runtime::spawn(accept(metrics_listener, app.clone(), endpoint));
+            }
+            Err(e) => tracing::warn!("Metrics endpoint unavailable on {}: {}", addr, e),
+        }
+    }
+
+    let endpoint = Endpoint { token: Some(lock.token()), metrics: metrics_address.is_some() };
+    accept(listener, app, endpoint).await
+}
+
+/// What a listener answers
+#[derive(Clone)]
+struct Endpoint {
+    /// IPC calls carrying this token; None answers no IPC calls
+    token: Option<String>,
+    /// Prometheus scrapes
+    metrics: bool,
+}
+
+async fn accept(listener: TcpListener, app: AppHandle, endpoint: Endpoint) {
+    loop {
+        let Ok((stream, _)) = listener.accept().await else {
+            continue;
+        };
+        let app = app.clone();
+        let endpoint = endpoint.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = handle_connection(stream, &app, &endpoint).await {
+                tracing::debug!("IPC connection ended: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_connection(stream: TcpStream, app: &AppHandle, endpoint: &Endpoint) -> std::io::Result<()> {
+    let mut first_bytes = [0u8; 4];
+    let peeked = stream.peek(&mut first_bytes).await?;
+    if metrics_service::is_scrape(&first_bytes[..peeked]) {
+        return if endpoint.metrics { metrics_service::answer(stream, app).await } else { Ok(()) };
+    }
+    let Some(token) = endpoint.token.as_deref() else {
+        return Ok(());
+    };
+
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        let response = match serde_json::from_str::<IpcRequest>(&line) {
+            Ok(request) if request.token != token => {
+                write_line(&mut writer, &NativeResponse::Error { message: "Invalid IPC token".to_string() }).await?;
+                return Ok(());
+            }
+            Ok(IpcRequest { call: IpcCall::Message(message), .. }) => {
+                native_messaging::handle_message(message, app).await
+            }
+            Ok(IpcRequest { call: IpcCall::UrlRefreshes, .. }) => {
+                return feed_url_refreshes(lines, writer, app).await;
+            }
+            Err(e) => NativeResponse::Error { message: format!("Invalid IPC request: {}", e) },
+        };
+        write_line(&mut writer, &response).await?;
+    }
+    Ok(())
+}
+
+/// Send the extension's URL refresh requests to the native messaging host
+/// on the other end until it hangs up
+async fn feed_url_refreshes(
+    mut lines: Lines<BufReader<OwnedReadHalf>>,
+    mut writer: OwnedWriteHalf,
+    app: &AppHandle,
+) -> std::io::Result<()> {
+    let refresher = app.state::<AppState>().engine.url_refresher.clone();
+    let (tx, rx) = flume::unbounded::<RefreshRequest>();
+    refresher.connect_extension(tx.clone());
+    tracing::info!("Browser extension connected for URL refreshes");
+
+    let result = loop {
+        tokio::select! {
+            request = rx.recv_async() => {
+                let Ok(request) = request else { break Ok(()) };
+                if let Err(e) = write_line(&mut writer, &NativeResponse::from(request)).await {
+                    break Err(e);
+                }
+            }
+            // Nothing more is expected from the host; this only notices
+            // it going away
+            line = lines.next_line() => match line {
+                Ok(Some(_)) => {}
+                Ok(None) => break Ok(()),
+                Err(e) => break Err(e),
+            },
+        }
+    };
+    refresher.disconnect_extension(&tx);
+    result
+}
+
+async fn write_line<W: AsyncWriteExt + Unpin, T: Serialize>(writer: &mut W, value: &T) -> std::io::Result<()> {
+    let mut json = serde_json::to_vec(value).map_err(std::io::Error::other)?;
+    json.push(b'\n');
+    writer.write_all(&json).await?;
+    writer.flush().await
+}
+
+/// Connection from a secondary entry point to the database owner
+pub struct IpcClient {
+    token: String,
+    lines: tokio::io::Lines<BufReader<tokio::net::tcp::OwnedReadHalf>>,
+    writer: tokio::net::tcp::OwnedWriteHalf,
+}
+
+impl IpcClient {
+    /// Connect to the owner of the database in `app_data_dir`. None when
+    /// no live process owns it or it can't be reached.
+    pub async fn connect(app_data_dir: &Path) -> Option<Self> {
+        let owner = writer_lock::live_owner(app_data_dir)?;
+        let port = owner.port?;
+        let stream = tokio::time::timeout(IPC_TIMEOUT, TcpStream::connect(("127.0.0.1", port)))
+            .await
+            .ok()?
+            .ok()?;
+        let (reader, writer) = stream.into_split();
+        Some(Self {
+            token: owner.token,
+            lines: BufReader::new(reader).lines(),
+            writer,
+        })
+    }
+
+    pub async fn send(&mut self, message: NativeMessage) -> std::io::Result<NativeResponse> {
+        let request = IpcRequest { token: self.token.clone(), call: IpcCall::Message(message) };
+        write_line(&mut self.writer, &request).await?;
+        let line = tokio::time::timeout(IPC_TIMEOUT, self.lines.next_line())
+            .await
+            .map_err(|_| std::io::Error::from(std::io::ErrorKind::TimedOut))??
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::UnexpectedEof))?;
+        serde_json::from_str(&line).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    /// Turn this connection into the owner's feed of URL refresh requests
+    /// for the extension, read with `next_pushed`
+    pub async fn subscribe_url_refreshes(&mut self) -> std::io::Result<()> {
+        let request = IpcRequest { token: self.token.clone(), call: IpcCall::UrlRefreshes };
+        write_line(&mut self.writer, &request).await
+    }
+
+    /// Wait for the next response the owner pushes. None once it hangs up.
+    pub async fn next_pushed(&mut self) -> std::io::Result<Option<NativeResponse>> {
+        match self.lines.next_line().await? {
+            Some(line) => serde_json::from_str(&line)
+                .map(Some)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+// src-tauri/src/services/metrics_service.rs
+// Answers GET /metrics in the Prometheus text format for headless setups.
+// The IPC server accepts the connections, on its own port and on
+// `metrics_address`; with that setting empty no scrape is answered.
+// Nothing here can change app state.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::core::download_task::DownloadStatus;
+use crate::core::metrics::MetricsGauges;
+use crate::state::app_state::AppState;
+
+/// Settings key: address to serve metrics on, e.g. `0.0.0.0:9464`; empty
+/// keeps the endpoint off
+pub const METRICS_ADDRESS_KEY: &str = "metrics_address";
+
+/// A scraper gets this long to send its request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Largest request head read before giving up on a connection
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// Check a `metrics_address` setting; empty is allowed and means off
+pub fn parse_address(value: &str) -> Result<Option<SocketAddr>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    value
+        .parse()
+        .map(Some)
+        .map_err(|_| format!("Invalid metrics address {} (expected host:port, like 127.0.0.1:9464)", value))
+}
+
+/// The address the setting asks for; None keeps metrics off. Read once,
+/// when the IPC server starts.
+pub async fn configured_address(app: &AppHandle) -> Option<SocketAddr> {
+    let state = app.state::<AppState>();
+    let setting = state.db.get_setting(METRICS_ADDRESS_KEY).await.ok().flatten().unwrap_or_default();
+    match parse_address(&setting) {
+        Ok(addr) => addr,
+        Err(e) => {
+            tracing::warn!("Metrics endpoint off: {}", e);
+            None
+        }
+    }
+}
+
+/// Whether a connection opens with an HTTP GET rather than an IPC line
+pub fn is_scrape(first_bytes: &[u8]) -> bool {
+    first_bytes.starts_with(b"GET ")
+}
+
+/// Answer one scrape on a connection the IPC server accepted
+pub async fn answer(mut stream: TcpStream, app: &AppHandle) -> std::io::Result<()> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        let read = tokio::time::timeout(REQUEST_TIMEOUT, stream.read(&mut buf))
+            .await
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "request timed out"))??;
+        if read == 0 || head.len() + read > MAX_REQUEST_BYTES {
+            return Ok(());
+        }
+        head.extend_from_slice(&buf[..read]);
+    }
+
+    let request_line = String::from_utf8_lossy(&head);
+    let mut parts = request_line.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => {
+            let body = render(app).await;
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        }
+        _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+    };
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// The registry's counters with gauges read now
+pub async fn render(app: &AppHandle) -> String {
+    let state = app.state::<AppState>();
+
+    let mut gauges = MetricsGauges {
+        queued_downloads: state.queue.read().await.info().queued_count as usize,
+        database_size_bytes: state.db.size_bytes().await.ok(),
+        ..Default::default()
+    };
+    // Counted as get_global_stats counts them
+    if let Ok(downloads) = state.db.get_all_downloads().await {
+        let active = downloads
+            .iter()
+            .filter(|task| matches!(task.status, DownloadStatus::Downloading | DownloadStatus::Connecting));
+        for task in active {
+            gauges.active_downloads += 1;
+            gauges.download_speed += task.speed;
+        }
+    }
+    let session = state.torrent_client.session_info().await;
+    gauges.torrents_active = session.active_torrents;
+    gauges.torrents_seeding = session.seeding_torrents;
+    gauges.torrents_paused = session.paused_torrents;
+    gauges.torrent_download_rate = session.download_rate;
+    gauges.torrent_upload_rate = session.upload_rate;
+    gauges.torrent_dht_nodes = session.dht_nodes;
+
+    state.metrics.render(&gauges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(parse_address("  "), Ok(None));
+        assert_eq!(parse_address("127.0.0.1:9464"), Ok(Some("127.0.0.1:9464".parse().unwrap())));
+        assert!(parse_address("localhost").is_err());
+    }
+
+    #[test]
+    fn test_is_scrape() {
+        assert!(is_scrape(b"GET /metrics HTTP/1.1"));
+        assert!(!is_scrape(b"{\"token\":\"abc\"}"));
+        assert!(!is_scrape(b"GE"));
+    }
+}
@@ -0,0 +1,170 @@
+// src-tauri/src/services/mini_window_service.rs
+// Small always-on-top progress widget. It is a second webview of the same
+// frontend; events reach it like the main window because they are emitted
+// on the app handle, and it polls `get_mini_summary` for the rest.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State, WebviewUrl, WebviewWindow, WebviewWindowBuilder, Window, WindowEvent};
+use uuid::Uuid;
+
+use crate::state::app_state::AppState;
+
+pub const MINI_WINDOW_LABEL: &str = "mini";
+
+/// Setting holding the widget's last position and size
+pub const MINI_WINDOW_GEOMETRY_KEY: &str = "mini_window_geometry";
+
+const DEFAULT_WIDTH: f64 = 320.0;
+const DEFAULT_HEIGHT: f64 = 96.0;
+const MIN_WIDTH: f64 = 200.0;
+const MIN_HEIGHT: f64 = 64.0;
+
+/// Logical position and size of the widget
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MiniWindowGeometry {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl MiniWindowGeometry {
+    fn of(window: &WebviewWindow) -> Option<Self> {
+        let scale = window.scale_factor().ok()?;
+        let position = window.outer_position().ok()?.to_logical::<f64>(scale);
+        let size = window.inner_size().ok()?.to_logical::<f64>(scale);
+        Some(Self {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        })
+    }
+}
+
+/// What the widget shows; small enough to poll every second
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MiniSummary {
+    pub active: usize,
+    pub paused: usize,
+    /// Bytes per second across running downloads
+    pub speed: f64,
+    pub top: Option<MiniDownload>,
+}
+
+/// The running download moving the most data
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MiniDownload {
+    pub id: Uuid,
+    pub file_name: String,
+    /// None while the size is unknown
+    pub percent: Option<f64>,
+}
+
+/// Open the widget, or bring it forward if it is already open
+#[tauri::command]
+pub async fn create_mini_window(app_handle: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    if let Some(window) = app_handle.get_webview_window(MINI_WINDOW_LABEL) {
+        window.show().map_err(|e| e.to_string())?;
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+
+    let geometry = state
+        .db
+        .get_setting(MINI_WINDOW_GEOMETRY_KEY)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str::<MiniWindowGeometry>(&json).ok());
+
+    let mut builder = WebviewWindowBuilder::new(
+        &app_handle,
+        MINI_WINDOW_LABEL,
+        WebviewUrl::App("index.html?view=mini".into()),
+    )
+    .title("AFK-Dunld")
+    .always_on_top(true)
+    .decorations(false)
+    .skip_taskbar(true)
+    .resizable(true)
+    .min_inner_size(MIN_WIDTH, MIN_HEIGHT);
+    builder = match geometry {
+        Some(g) => builder
+            .position(g.x, g.y)
+            .inner_size(g.width.max(MIN_WIDTH), g.height.max(MIN_HEIGHT)),
+        None => builder.inner_size(DEFAULT_WIDTH, DEFAULT_HEIGHT),
+    };
+    let window = builder.build().map_err(|e| e.to_string())?;
+
+    // The widget has no title bar, but the OS can still close it
+    let handle = window.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::CloseRequested { .. } = event {
+            remember_geometry(&handle);
+        }
+    });
+
+    tracing::info!("Mini window opened");
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn close_mini_window(app_handle: AppHandle) -> Result<(), String> {
+    let Some(window) = app_handle.get_webview_window(MINI_WINDOW_LABEL) else {
+        return Ok(());
+    };
+    remember_geometry(&window);
+    window.destroy().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_mini_summary(state: State<'_, AppState>) -> Result<MiniSummary, String> {
+    let progress = state.taskbar.summary();
+    let mut summary = MiniSummary {
+        active: progress.active,
+        paused: progress.paused,
+        speed: progress.speed,
+        top: None,
+    };
+
+    if let Some((id, percent)) = state.taskbar.fastest() {
+        let file_name = match state.active_downloads.read().await.get(&id) {
+            Some(handle) => handle.task.read().await.file_name.clone(),
+            None => return Ok(summary),
+        };
+        summary.top = Some(MiniDownload { id, file_name, percent });
+    }
+    Ok(summary)
+}
+
+/// Hide the main window instead of closing it while the widget is open,
+/// so the app keeps running and the main window can be shown again
+pub fn on_window_event(window: &Window, event: &WindowEvent) {
+    if window.label() != "main" {
+        return;
+    }
+    if let WindowEvent::CloseRequested { api, .. } = event {
+        if window.app_handle().get_webview_window(MINI_WINDOW_LABEL).is_some() {
+            api.prevent_close();
+            let _ = window.hide();
+        }
+    }
+}
+
+fn remember_geometry(window: &WebviewWindow) {
+    let Some(geometry) = MiniWindowGeometry::of(window) else {
+        return;
+    };
+    let Ok(json) = serde_json::to_string(&geometry) else {
+        return;
+    };
+    let app = window.app_handle().clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        if let Err(e) = state.db.set_setting(MINI_WINDOW_GEOMETRY_KEY, &json).await {
+            tracing::warn!("Failed to save mini window position: {}", e);
+        }
+    });
+}
@@ -1,10 +1,25 @@
+pub mod bandwidth_service;
 pub mod browser_service;
 pub mod clipboard_service;
 pub mod config_service;
+pub mod conversion_service;
+pub mod cron_service;
 pub mod file_watcher;
+pub mod hook_service;
+pub mod integrity_service;
+pub mod ipc_service;
+pub mod metrics_service;
+pub mod mini_window_service;
+pub mod native_codec;
 pub mod native_messaging;
+pub mod network_monitor;
 pub mod notification_service;
+pub mod power_service;
+pub mod recent_files_service;
+pub mod taskbar_service;
 pub mod tray_service;
+pub mod update_service;
+pub mod watchdog_service;
 
 // Re-export notification types for easier access
 #[allow(unused_imports)]
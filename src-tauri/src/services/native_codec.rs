@@ -0,0 +1,226 @@
+// src-tauri/src/services/native_codec.rs
+// Framing and envelopes for the native messaging protocol, kept free of
+// I/O beyond the reader and writer handed in so it can be tested against
+// byte fixtures.
+//
+// A frame is a 4-byte little-endian length followed by that many bytes
+// of JSON. Protocol v1 is one request, one response, no ids. In v2 the
+// extension opens with `hello`, every request may carry an `id` that its
+// response echoes, and the host pushes `event` frames for downloads the
+// extension added. An extension that never says hello gets v1.
+
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read, Write};
+use thiserror::Error;
+
+use crate::services::native_messaging::{NativeMessage, NativeResponse};
+
+/// Largest frame either side sends; browsers refuse bigger host messages
+pub const MAX_FRAME_LEN: usize = 1024 * 1024;
+
+pub const PROTOCOL_V1: u32 = 1;
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Capability: the host pushes `event` frames for downloads the
+/// extension added
+pub const CAP_PROGRESS_EVENTS: &str = "progress_events";
+
+/// Capabilities this host offers in v2
+pub const HOST_CAPABILITIES: &[&str] = &[CAP_PROGRESS_EVENTS];
+
+#[derive(Debug, Error)]
+pub enum FrameError {
+    /// The browser closed the pipe between frames
+    #[error("connection closed")]
+    Closed,
+    /// The frame was skipped, so the next one can still be read
+    #[error("message of {0} bytes is over the {max} byte limit", max = MAX_FRAME_LEN)]
+    Oversized(usize),
+    #[error("empty message")]
+    Empty,
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// Read one frame's body. An oversized frame is read past and reported,
+/// leaving the stream at the start of the next frame.
+pub fn read_frame<R: Read>(reader: &mut R) -> Result<Vec<u8>, FrameError> {
+    let mut length_bytes = [0u8; 4];
+    match reader.read_exact(&mut length_bytes) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Err(FrameError::Closed),
+        Err(e) => return Err(e.into()),
+    }
+    let length = u32::from_le_bytes(length_bytes) as usize;
+
+    if length == 0 {
+        return Err(FrameError::Empty);
+    }
+    if length > MAX_FRAME_LEN {
+        let skipped = io::copy(&mut reader.take(length as u64), &mut io::sink())?;
+        if skipped < length as u64 {
+            return Err(FrameError::Closed);
+        }
+        return Err(FrameError::Oversized(length));
+    }
+
+    let mut body = vec![0u8; length];
+    reader.read_exact(&mut body)?;
+    Ok(body)
+}
+
+/// Write one frame
+pub fn write_frame<W: Write>(writer: &mut W, body: &[u8]) -> io::Result<()> {
+    if body.len() > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("message of {} bytes is over the {} byte limit", body.len(), MAX_FRAME_LEN),
+        ));
+    }
+    writer.write_all(&(body.len() as u32).to_le_bytes())?;
+    writer.write_all(body)?;
+    writer.flush()
+}
+
+/// A request's correlation id, echoed on its response
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RequestId {
+    Number(u64),
+    Text(String),
+}
+
+/// Parse a request body. A request that can't be understood still hands
+/// back its id, so the error can be correlated.
+pub fn decode_request(body: &[u8]) -> Result<(Option<RequestId>, NativeMessage), (Option<RequestId>, String)> {
+    let value: serde_json::Value = serde_json::from_slice(body).map_err(|e| (None, e.to_string()))?;
+    let id = value.get("id").and_then(|id| RequestId::deserialize(id).ok());
+    match serde_json::from_value(value) {
+        Ok(message) => Ok((id, message)),
+        Err(e) => Err((id, e.to_string())),
+    }
+}
+
+#[derive(Serialize)]
+struct Reply<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<&'a RequestId>,
+    #[serde(flatten)]
+    response: &'a NativeResponse,
+}
+
+/// Serialize a response, echoing `id` when the request had one. A
+/// response too big to send becomes an error with the same id.
+pub fn encode_reply(id: Option<&RequestId>, response: &NativeResponse) -> Vec<u8> {
+    let encode = |response: &NativeResponse| serde_json::to_vec(&Reply { id, response }).unwrap_or_default();
+    let body = encode(response);
+    if body.len() <= MAX_FRAME_LEN {
+        return body;
+    }
+    encode(&NativeResponse::Error {
+        message: format!("Response of {} bytes is over the {} byte limit", body.len(), MAX_FRAME_LEN),
+    })
+}
+
+/// What a connection agreed on in its hello; v1 until then
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Negotiated {
+    pub protocol: u32,
+    pub capabilities: Vec<String>,
+}
+
+impl Default for Negotiated {
+    fn default() -> Self {
+        Self { protocol: PROTOCOL_V1, capabilities: Vec::new() }
+    }
+}
+
+impl Negotiated {
+    pub fn has(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|c| c == capability)
+    }
+}
+
+/// The highest protocol both sides speak, and the capabilities both
+/// asked for. Capabilities need v2.
+pub fn negotiate(protocol: u32, requested: &[String]) -> Negotiated {
+    let protocol = protocol.clamp(PROTOCOL_V1, PROTOCOL_VERSION);
+    let capabilities = if protocol >= PROTOCOL_VERSION {
+        requested
+            .iter()
+            .filter(|c| HOST_CAPABILITIES.contains(&c.as_str()))
+            .cloned()
+            .collect()
+    } else {
+        Vec::new()
+    };
+    Negotiated { protocol, capabilities }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(body: &[u8]) -> Vec<u8> {
+        let mut bytes = (body.len() as u32).to_le_bytes().to_vec();
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    #[test]
+    fn test_frames_round_trip_and_oversized_is_skipped() {
+        let ping = b"\x0f\x00\x00\x00{\"type\":\"ping\"}";
+        assert_eq!(frame(b"{\"type\":\"ping\"}"), ping.to_vec());
+
+        // An oversized frame, then a ping that must still be readable
+        let mut input = ((MAX_FRAME_LEN + 1) as u32).to_le_bytes().to_vec();
+        input.extend(std::iter::repeat(b' ').take(MAX_FRAME_LEN + 1));
+        input.extend_from_slice(ping);
+        input.extend_from_slice(b"\x00\x00\x00\x00");
+        let mut reader = io::Cursor::new(input);
+
+        assert!(matches!(read_frame(&mut reader), Err(FrameError::Oversized(n)) if n == MAX_FRAME_LEN + 1));
+        assert_eq!(read_frame(&mut reader).unwrap(), b"{\"type\":\"ping\"}");
+        assert!(matches!(read_frame(&mut reader), Err(FrameError::Empty)));
+        assert!(matches!(read_frame(&mut reader), Err(FrameError::Closed)));
+
+        let mut written = Vec::new();
+        write_frame(&mut written, b"{\"type\":\"ping\"}").unwrap();
+        assert_eq!(written, ping.to_vec());
+        assert!(write_frame(&mut Vec::new(), &vec![b' '; MAX_FRAME_LEN + 1]).is_err());
+    }
+
+    #[test]
+    fn test_ids_are_echoed() {
+        let (id, message) = decode_request(br#"{"id":7,"type":"get_status"}"#).unwrap();
+        assert_eq!(id, Some(RequestId::Number(7)));
+        assert!(matches!(message, NativeMessage::GetStatus));
+
+        // v1 requests have no id and their replies carry none
+        let (id, _) = decode_request(br#"{"type":"ping"}"#).unwrap();
+        assert_eq!(id, None);
+        let reply = encode_reply(None, &NativeResponse::UrlRefreshed { accepted: true });
+        assert_eq!(reply, br#"{"type":"url_refreshed","accepted":true}"#.to_vec());
+
+        let id = RequestId::Text("a1".to_string());
+        let reply = encode_reply(Some(&id), &NativeResponse::UrlRefreshed { accepted: false });
+        assert_eq!(reply, br#"{"id":"a1","type":"url_refreshed","accepted":false}"#.to_vec());
+
+        // An unknown request still gives its id back for the error
+        let (id, _) = decode_request(br#"{"id":"b2","type":"fly"}"#).unwrap_err();
+        assert_eq!(id, Some(RequestId::Text("b2".to_string())));
+    }
+
+    #[test]
+    fn test_negotiation() {
+        let asked = vec![CAP_PROGRESS_EVENTS.to_string(), "telepathy".to_string()];
+        let v2 = negotiate(3, &asked);
+        assert_eq!(v2.protocol, PROTOCOL_VERSION);
+        assert_eq!(v2.capabilities, vec![CAP_PROGRESS_EVENTS.to_string()]);
+
+        // An old extension keeps v1 and gets no pushes
+        let v1 = negotiate(1, &asked);
+        assert_eq!(v1, Negotiated::default());
+        assert!(!v1.has(CAP_PROGRESS_EVENTS));
+    }
+}
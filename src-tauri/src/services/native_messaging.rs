@@ -1,28 +1,79 @@
 // Native Messaging Host for Browser Extension Communication
-// Implements Chrome/Firefox Native Messaging protocol
+// Implements Chrome/Firefox Native Messaging protocol; framing, ids and
+// version negotiation are in native_codec
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::io::{self, Read, Write};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tauri::{AppHandle, Manager, Emitter};
+use crate::core::download_task::{DownloadStatus, DownloadTask};
+use crate::database::db::Database;
+use crate::network::url_refresh::{RefreshRequest, UrlRefresh};
+use crate::services::ipc_service::IpcClient;
+use crate::services::native_codec::{
+    self, FrameError, Negotiated, RequestId, CAP_PROGRESS_EVENTS,
+};
 use crate::state::app_state::AppState;
+use crate::utils::error::DownloadError;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// How often downloads the extension added are checked for events
+const EVENT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long a detached host waits before looking for the app again
+const OWNER_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type")]
 pub enum NativeMessage {
     #[serde(rename = "ping")]
     Ping,
+    /// Opens a v2 session; without it the connection speaks v1
+    #[serde(rename = "hello")]
+    Hello {
+        protocol: u32,
+        #[serde(default)]
+        capabilities: Vec<String>,
+    },
     #[serde(rename = "add_download")]
     AddDownload {
         url: String,
         referrer: Option<String>,
         filename: Option<String>,
         timestamp: Option<i64>,
+        #[serde(default)]
+        page_title: Option<String>,
+        /// Lets the extension hand back a fresh link if this one expires
+        #[serde(default)]
+        request_id: Option<String>,
     },
     #[serde(rename = "get_status")]
     GetStatus,
+    /// Where the given downloads stand
+    #[serde(rename = "get_progress")]
+    GetProgress {
+        download_ids: Vec<String>,
+    },
+    /// A page's images and videos, for the app to probe and let the user
+    /// pick from
+    #[serde(rename = "page_media")]
+    PageMedia {
+        urls: Vec<String>,
+        referrer: Option<String>,
+        #[serde(default)]
+        page_title: Option<String>,
+    },
+    /// Answer to a `refresh_url` request
+    #[serde(rename = "refreshed_url")]
+    RefreshedUrl {
+        request_id: String,
+        url: String,
+    },
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type")]
 pub enum NativeResponse {
     #[serde(rename = "pong")]
@@ -30,6 +81,14 @@ pub enum NativeResponse {
         version: String,
         app_name: String,
     },
+    /// What the hello settled on
+    #[serde(rename = "hello")]
+    Hello {
+        protocol: u32,
+        capabilities: Vec<String>,
+        version: String,
+        app_name: String,
+    },
     #[serde(rename = "download_added")]
     DownloadAdded {
         success: bool,
@@ -45,45 +104,209 @@ pub enum NativeResponse {
     Error {
         message: String,
     },
+    /// Asks the extension for a fresh link to a download's resource
+    #[serde(rename = "refresh_url")]
+    RefreshUrl {
+        request_id: String,
+        download_id: String,
+        url: String,
+    },
+    #[serde(rename = "url_refreshed")]
+    UrlRefreshed {
+        accepted: bool,
+    },
+    #[serde(rename = "progress")]
+    Progress {
+        downloads: Vec<DownloadSnapshot>,
+    },
+    /// The media list was handed to the selection dialog
+    #[serde(rename = "media_received")]
+    MediaReceived {
+        count: usize,
+    },
+    /// Pushed without a request, in v2 sessions that asked for events
+    #[serde(rename = "event")]
+    Event(NativeEvent),
 }
 
-/// Read a message from stdin using Chrome Native Messaging protocol
-/// Format: 4-byte message length (little-endian) followed by JSON message
-pub fn read_message() -> io::Result<NativeMessage> {
-    let mut length_bytes = [0u8; 4];
-    io::stdin().read_exact(&mut length_bytes)?;
-    
-    let length = u32::from_le_bytes(length_bytes) as usize;
-    
-    if length == 0 || length > 1024 * 1024 {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            "Invalid message length",
-        ));
+/// A download as the extension sees it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct DownloadSnapshot {
+    pub download_id: String,
+    pub file_name: String,
+    pub save_path: PathBuf,
+    pub status: DownloadStatus,
+    pub downloaded_size: u64,
+    pub total_size: Option<u64>,
+    pub speed: f64,
+    pub error: Option<String>,
+}
+
+impl From<&DownloadTask> for DownloadSnapshot {
+    fn from(task: &DownloadTask) -> Self {
+        Self {
+            download_id: task.id.to_string(),
+            file_name: task.file_name.clone(),
+            save_path: task.save_path.clone(),
+            status: task.status,
+            downloaded_size: task.downloaded_size,
+            total_size: task.total_size,
+            speed: task.speed,
+            error: task.error_message.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum NativeEvent {
+    Progress {
+        download_id: String,
+        status: DownloadStatus,
+        downloaded_size: u64,
+        total_size: Option<u64>,
+        speed: f64,
+    },
+    Completed {
+        download_id: String,
+        file_name: String,
+        save_path: PathBuf,
+    },
+    Failed {
+        download_id: String,
+        error: Option<String>,
+    },
+    Cancelled {
+        download_id: String,
+    },
+}
+
+/// Downloads the extension added this session, and what it was last told
+/// about each. Finished downloads get a last event and are let go.
+#[derive(Debug, Default)]
+struct ProgressWatch {
+    last: HashMap<String, (DownloadStatus, u64)>,
+}
+
+impl ProgressWatch {
+    fn watch(&mut self, download_id: String) {
+        self.last.insert(download_id, (DownloadStatus::Queued, 0));
+    }
+
+    fn is_empty(&self) -> bool {
+        self.last.is_empty()
+    }
+
+    fn ids(&self) -> Vec<String> {
+        self.last.keys().cloned().collect()
+    }
+
+    /// Events for what changed. Downloads missing from `snapshots` were
+    /// removed and are dropped.
+    fn update(&mut self, snapshots: Vec<DownloadSnapshot>) -> Vec<NativeEvent> {
+        let present: HashSet<&str> = snapshots.iter().map(|s| s.download_id.as_str()).collect();
+        self.last.retain(|id, _| present.contains(id.as_str()));
+
+        let mut events = Vec::new();
+        for snapshot in snapshots {
+            let Some(last) = self.last.get_mut(&snapshot.download_id) else {
+                continue;
+            };
+            let event = match snapshot.status {
+                DownloadStatus::Completed => NativeEvent::Completed {
+                    download_id: snapshot.download_id.clone(),
+                    file_name: snapshot.file_name,
+                    save_path: snapshot.save_path,
+                },
+                DownloadStatus::Failed => NativeEvent::Failed {
+                    download_id: snapshot.download_id.clone(),
+                    error: snapshot.error,
+                },
+                DownloadStatus::Cancelled => NativeEvent::Cancelled {
+                    download_id: snapshot.download_id.clone(),
+                },
+                status => {
+                    if *last != (status, snapshot.downloaded_size) {
+                        *last = (status, snapshot.downloaded_size);
+                        events.push(NativeEvent::Progress {
+                            download_id: snapshot.download_id,
+                            status,
+                            downloaded_size: snapshot.downloaded_size,
+                            total_size: snapshot.total_size,
+                            speed: snapshot.speed,
+                        });
+                    }
+                    continue;
+                }
+            };
+            self.last.remove(&snapshot.download_id);
+            events.push(event);
+        }
+        events
+    }
+}
+
+impl From<RefreshRequest> for NativeResponse {
+    fn from(request: RefreshRequest) -> Self {
+        NativeResponse::RefreshUrl {
+            request_id: request.request_id,
+            download_id: request.download_id,
+            url: request.url,
+        }
+    }
+}
+
+/// Read frames from stdin on a thread of their own, so the host can push
+/// events while it waits for the next request
+fn spawn_stdin_reader() -> flume::Receiver<Result<Vec<u8>, FrameError>> {
+    let (tx, rx) = flume::unbounded();
+    std::thread::spawn(move || {
+        let mut stdin = io::stdin().lock();
+        loop {
+            let frame = native_codec::read_frame(&mut stdin);
+            let closed = matches!(frame, Err(FrameError::Closed) | Err(FrameError::Io(_)));
+            if tx.send(frame).is_err() || closed {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+/// Write a response to stdout, echoing the request's id if it had one
+pub fn write_response(id: Option<&RequestId>, response: &NativeResponse) -> io::Result<()> {
+    let body = native_codec::encode_reply(id, response);
+    // Refresh requests are written from another task; keep frames whole
+    native_codec::write_frame(&mut io::stdout().lock(), &body)
+}
+
+fn hello_response(negotiated: Negotiated) -> NativeResponse {
+    NativeResponse::Hello {
+        protocol: negotiated.protocol,
+        capabilities: negotiated.capabilities,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        app_name: "AFK-Dunld".to_string(),
+    }
+}
+
+async fn snapshots(db: &Database, download_ids: &[String]) -> Result<Vec<DownloadSnapshot>, DownloadError> {
+    let mut snapshots = Vec::new();
+    for id in download_ids {
+        let Ok(uuid) = uuid::Uuid::parse_str(id) else {
+            continue;
+        };
+        if let Some(task) = db.get_download(uuid).await? {
+            snapshots.push(DownloadSnapshot::from(&task));
+        }
+    }
+    Ok(snapshots)
+}
+
+fn progress_response(result: Result<Vec<DownloadSnapshot>, DownloadError>) -> NativeResponse {
+    match result {
+        Ok(downloads) => NativeResponse::Progress { downloads },
+        Err(e) => NativeResponse::Error { message: e.to_string() },
     }
-    
-    let mut buffer = vec![0u8; length];
-    io::stdin().read_exact(&mut buffer)?;
-    
-    let message: NativeMessage = serde_json::from_slice(&buffer)
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-    
-    Ok(message)
-}
-
-/// Write a response to stdout using Chrome Native Messaging protocol
-pub fn write_response(response: &NativeResponse) -> io::Result<()> {
-    let json = serde_json::to_string(response)
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-    
-    let length = json.len() as u32;
-    let length_bytes = length.to_le_bytes();
-    
-    io::stdout().write_all(&length_bytes)?;
-    io::stdout().write_all(json.as_bytes())?;
-    io::stdout().flush()?;
-    
-    Ok(())
 }
 
 /// Handle a native messaging message
@@ -96,24 +319,28 @@ pub async fn handle_message(
             version: env!("CARGO_PKG_VERSION").to_string(),
             app_name: "AFK-Dunld".to_string(),
         },
+
+        NativeMessage::Hello { protocol, capabilities } => {
+            hello_response(native_codec::negotiate(protocol, &capabilities))
+        }
         
         NativeMessage::AddDownload {
             url,
             referrer,
             filename,
+            page_title,
+            request_id,
             ..
         } => {
-            // Get app state
-            let state = app_handle.state::<AppState>();
-            let state_clone = state.inner().clone();
-            
             // Add download
             match crate::commands::download_commands::add_download_internal(
                 url.clone(),
                 None, // save_path - use default
                 filename,
                 referrer,
-                state_clone,
+                page_title,
+                request_id.map(|request_id| UrlRefresh::Extension { request_id }),
+                app_handle,
             ).await {
                 Ok(download_id) => {
                     // Send notification
@@ -136,56 +363,298 @@ pub async fn handle_message(
         NativeMessage::GetStatus => {
             let state = app_handle.state::<AppState>();
             let downloads = state.db.get_all_downloads().await.unwrap_or_default();
-            
-            use crate::core::download_task::DownloadStatus;
-            
-            let active_downloads = downloads.iter()
-                .filter(|d| matches!(d.status, DownloadStatus::Downloading | DownloadStatus::Queued))
-                .count();
-            
-            let total_speed: f64 = downloads.iter()
-                .filter(|d| d.status == DownloadStatus::Downloading)
-                .map(|d| d.speed)
-                .sum();
-            
-            NativeResponse::Status {
-                active_downloads,
-                total_speed,
+            status_of(&downloads)
+        }
+
+        NativeMessage::GetProgress { download_ids } => {
+            let state = app_handle.state::<AppState>();
+            progress_response(snapshots(&state.db, &download_ids).await)
+        }
+
+        NativeMessage::PageMedia { urls, referrer, page_title } => {
+            // The dialog probes them with `probe_batch` and adds the
+            // picked ones with `add_selected`
+            let count = urls.len();
+            let _ = app_handle.emit(
+                "page-media-received",
+                serde_json::json!({ "urls": urls, "referrer": referrer, "pageTitle": page_title }),
+            );
+            NativeResponse::MediaReceived { count }
+        }
+
+        NativeMessage::RefreshedUrl { request_id, url } => {
+            let state = app_handle.state::<AppState>();
+            NativeResponse::UrlRefreshed {
+                accepted: state.engine.url_refresher.complete(&request_id, url),
             }
         }
     }
 }
 
-/// Run the native messaging host (stdio mode)
-pub async fn run_native_messaging_host(app_handle: AppHandle) -> io::Result<()> {
-    tracing::info!("Native messaging host started");
-    
+fn status_of(downloads: &[DownloadTask]) -> NativeResponse {
+    let active_downloads = downloads.iter()
+        .filter(|d| matches!(d.status, DownloadStatus::Downloading | DownloadStatus::Queued))
+        .count();
+
+    let total_speed: f64 = downloads.iter()
+        .filter(|d| d.status == DownloadStatus::Downloading)
+        .map(|d| d.speed)
+        .sum();
+
+    NativeResponse::Status {
+        active_downloads,
+        total_speed,
+    }
+}
+
+/// Answer a message when the app isn't running: status comes from the
+/// database read-only, anything that would change it is refused
+async fn offline_response(message: NativeMessage, app_data_dir: &Path) -> NativeResponse {
+    match message {
+        NativeMessage::Ping => NativeResponse::Pong {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            app_name: "AFK-Dunld".to_string(),
+        },
+        NativeMessage::Hello { protocol, capabilities } => {
+            hello_response(native_codec::negotiate(protocol, &capabilities))
+        }
+        NativeMessage::GetStatus => match Database::open_read_only(app_data_dir).await {
+            Ok(db) => match db.get_all_downloads().await {
+                Ok(downloads) => status_of(&downloads),
+                Err(e) => NativeResponse::Error { message: e.to_string() },
+            },
+            Err(e) => NativeResponse::Error { message: e.to_string() },
+        },
+        NativeMessage::AddDownload { .. } => NativeResponse::DownloadAdded {
+            success: false,
+            download_id: None,
+            error: Some("AFK-Dunld is not running; open it to add downloads".to_string()),
+        },
+        NativeMessage::GetProgress { download_ids } => match Database::open_read_only(app_data_dir).await {
+            Ok(db) => progress_response(snapshots(&db, &download_ids).await),
+            Err(e) => NativeResponse::Error { message: e.to_string() },
+        },
+        NativeMessage::PageMedia { .. } => NativeResponse::Error {
+            message: "AFK-Dunld is not running; open it to download from this page".to_string(),
+        },
+        NativeMessage::RefreshedUrl { .. } => NativeResponse::UrlRefreshed { accepted: false },
+    }
+}
+
+/// Where a stdio host sends the requests it doesn't answer itself
+enum Backend {
+    /// The host runs inside the app
+    App(AppHandle),
+    /// The host was started by the browser and forwards to the app over
+    /// IPC, answering read-only when it can't be reached
+    Detached {
+        app_data_dir: PathBuf,
+        owner: Option<IpcClient>,
+    },
+}
+
+impl Backend {
+    async fn dispatch(&mut self, message: NativeMessage) -> NativeResponse {
+        match self {
+            Backend::App(app_handle) => handle_message(message, app_handle).await,
+            Backend::Detached { app_data_dir, owner } => {
+                // The app may have started or restarted since the last message
+                if owner.is_none() {
+                    *owner = IpcClient::connect(app_data_dir).await;
+                }
+                match owner.as_mut() {
+                    Some(client) => match client.send(message.clone()).await {
+                        Ok(response) => response,
+                        Err(e) => {
+                            tracing::warn!("Lost the app's IPC endpoint: {}", e);
+                            *owner = None;
+                            offline_response(message, app_data_dir).await
+                        }
+                    },
+                    None => offline_response(message, app_data_dir).await,
+                }
+            }
+        }
+    }
+}
+
+/// Answer requests on stdin until the browser closes it. A v2 session
+/// that asked for events also gets them for the downloads it adds.
+async fn serve_stdio(mut backend: Backend) -> io::Result<()> {
+    let frames = spawn_stdin_reader();
+    let mut session = Negotiated::default();
+    let mut watch = ProgressWatch::default();
+    let mut ticker = tokio::time::interval(EVENT_POLL_INTERVAL);
+
     loop {
-        match read_message() {
-            Ok(message) => {
+        tokio::select! {
+            frame = frames.recv_async() => {
+                let body = match frame {
+                    Ok(Ok(body)) => body,
+                    Ok(Err(FrameError::Closed)) | Err(_) => {
+                        tracing::info!("Native messaging host connection closed");
+                        return Ok(());
+                    }
+                    Ok(Err(e)) => {
+                        // Oversized and empty frames were skipped; the
+                        // connection carries on
+                        tracing::error!("Failed to read message: {}", e);
+                        write_response(None, &NativeResponse::Error { message: e.to_string() })?;
+                        continue;
+                    }
+                };
+                let (id, message) = match native_codec::decode_request(&body) {
+                    Ok(request) => request,
+                    Err((id, error)) => {
+                        let message = format!("Invalid message: {}", error);
+                        write_response(id.as_ref(), &NativeResponse::Error { message })?;
+                        continue;
+                    }
+                };
                 tracing::debug!("Received message: {:?}", message);
-                
-                let response = handle_message(message, &app_handle).await;
-                
-                if let Err(e) = write_response(&response) {
-                    tracing::error!("Failed to write response: {}", e);
-                    break;
+
+                let response = match message {
+                    NativeMessage::Hello { protocol, capabilities } => {
+                        session = native_codec::negotiate(protocol, &capabilities);
+                        tracing::info!("Native messaging protocol v{} with {:?}", session.protocol, session.capabilities);
+                        hello_response(session.clone())
+                    }
+                    message => backend.dispatch(message).await,
+                };
+                if let NativeResponse::DownloadAdded { download_id: Some(download_id), .. } = &response {
+                    if session.has(CAP_PROGRESS_EVENTS) {
+                        watch.watch(download_id.clone());
+                    }
                 }
+                write_response(id.as_ref(), &response)?;
             }
-            Err(e) => {
-                if e.kind() == io::ErrorKind::UnexpectedEof {
-                    tracing::info!("Native messaging host connection closed");
-                    break;
+            _ = ticker.tick(), if !watch.is_empty() => {
+                let request = NativeMessage::GetProgress { download_ids: watch.ids() };
+                if let NativeResponse::Progress { downloads } = backend.dispatch(request).await {
+                    for event in watch.update(downloads) {
+                        write_response(None, &NativeResponse::Event(event))?;
+                    }
                 }
-                
-                tracing::error!("Failed to read message: {}", e);
-                let error_response = NativeResponse::Error {
-                    message: e.to_string(),
-                };
-                let _ = write_response(&error_response);
             }
         }
     }
-    
-    Ok(())
+}
+
+/// Run the native messaging host in a process started by the browser. The
+/// running app owns the database, so messages are forwarded to it; when it
+/// can't be reached they are answered read-only.
+pub async fn run_detached_host(app_data_dir: PathBuf) -> io::Result<()> {
+    tracing::info!("Native messaging host started (detached)");
+    tokio::spawn(forward_url_refreshes(app_data_dir.clone()));
+    serve_stdio(Backend::Detached { app_data_dir, owner: None }).await
+}
+
+/// Pass the running app's URL refresh requests on to the extension,
+/// following the app across restarts. Its answers come back as
+/// `refreshed_url` messages through the usual IPC connection.
+async fn forward_url_refreshes(app_data_dir: PathBuf) {
+    loop {
+        if let Some(mut owner) = IpcClient::connect(&app_data_dir).await {
+            if owner.subscribe_url_refreshes().await.is_ok() {
+                while let Ok(Some(response)) = owner.next_pushed().await {
+                    if let Err(e) = write_response(None, &response) {
+                        tracing::error!("Failed to send URL refresh request: {}", e);
+                        return;
+                    }
+                }
+            }
+        }
+        tokio::time::sleep(OWNER_RETRY_INTERVAL).await;
+    }
+}
+
+/// Run the native messaging host (stdio mode)
+pub async fn run_native_messaging_host(app_handle: AppHandle) -> io::Result<()> {
+    tracing::info!("Native messaging host started");
+
+    // Downloads whose links expire ask the extension for new ones while
+    // it is connected
+    let (refresh_tx, refresh_rx) = flume::unbounded::<RefreshRequest>();
+    let refresher = app_handle.state::<AppState>().engine.url_refresher.clone();
+    refresher.connect_extension(refresh_tx.clone());
+    tokio::spawn(async move {
+        while let Ok(request) = refresh_rx.recv_async().await {
+            if let Err(e) = write_response(None, &request.into()) {
+                tracing::error!("Failed to send URL refresh request: {}", e);
+            }
+        }
+    });
+
+    let result = serve_stdio(Backend::App(app_handle)).await;
+    refresher.disconnect_extension(&refresh_tx);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_offline_responses_read_only() {
+        let dir = std::env::temp_dir().join(format!("afk-dunld-nm-{}", uuid::Uuid::new_v4()));
+        let db = Database::new(&dir).await.unwrap();
+        db.run_migrations().await.unwrap();
+        db.pool().close().await;
+
+        assert!(matches!(
+            offline_response(NativeMessage::GetStatus, &dir).await,
+            NativeResponse::Status { active_downloads: 0, .. }
+        ));
+        let add = NativeMessage::AddDownload {
+            url: "https://example.com/a.zip".to_string(),
+            referrer: None,
+            filename: None,
+            timestamp: None,
+            page_title: None,
+            request_id: None,
+        };
+        assert!(matches!(
+            offline_response(add, &dir).await,
+            NativeResponse::DownloadAdded { success: false, .. }
+        ));
+
+        let read_only = Database::open_read_only(&dir).await.unwrap();
+        assert!(sqlx::query("DELETE FROM downloads").execute(read_only.pool()).await.is_err());
+        read_only.pool().close().await;
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    fn snapshot(id: &str, status: DownloadStatus, downloaded_size: u64) -> DownloadSnapshot {
+        DownloadSnapshot {
+            download_id: id.to_string(),
+            file_name: "a.zip".to_string(),
+            save_path: PathBuf::from("/dl/a.zip"),
+            status,
+            downloaded_size,
+            total_size: Some(100),
+            speed: 0.0,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_progress_watch_events() {
+        let mut watch = ProgressWatch::default();
+        watch.watch("a".to_string());
+        watch.watch("b".to_string());
+
+        let events = watch.update(vec![snapshot("a", DownloadStatus::Downloading, 40), snapshot("b", DownloadStatus::Queued, 0)]);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(&events[0], NativeEvent::Progress { download_id, downloaded_size: 40, .. } if download_id == "a"));
+
+        // Nothing new, then a finish that ends the watch; "b" was removed
+        assert!(watch.update(vec![snapshot("a", DownloadStatus::Downloading, 40), snapshot("b", DownloadStatus::Queued, 0)]).is_empty());
+        let events = watch.update(vec![snapshot("a", DownloadStatus::Completed, 100)]);
+        assert!(matches!(&events[0], NativeEvent::Completed { download_id, .. } if download_id == "a"));
+        assert!(watch.is_empty());
+
+        let json = serde_json::to_value(NativeResponse::Event(events[0].clone())).unwrap();
+        assert_eq!(json["type"], "event");
+        assert_eq!(json["event"], "completed");
+    }
 }
@@ -0,0 +1,111 @@
+// src-tauri/src/services/network_monitor.rs
+// Watches the default route so a switch from Wi-Fi to Ethernet, or a VPN
+// reconnecting, doesn't leave transfers stalled on sockets that went with
+// the old route. Their TCP timeouts can take minutes; reconnecting right
+// away picks up from the partial files instead.
+
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::state::app_state::AppState;
+use crate::utils::network_info::{self, NetworkIdentity, RouteIdentity};
+
+/// How often the route is checked
+pub const NETWORK_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Emitted as `network-changed`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkChange {
+    pub previous: RouteIdentity,
+    pub current: RouteIdentity,
+    pub network: NetworkIdentity,
+    /// Segments dropped and reconnected on the new route
+    pub reconnected_segments: usize,
+    pub reannounced_torrents: usize,
+}
+
+/// Whether connections made on `previous` are likely dead on `current`
+/// and worth reconnecting. Going offline isn't: a reconnect would only
+/// fail, so transfers are left alone until a route comes back.
+pub fn needs_reconnect(previous: &RouteIdentity, current: &RouteIdentity) -> bool {
+    previous != current && current.is_online()
+}
+
+async fn current_route() -> RouteIdentity {
+    tokio::task::spawn_blocking(network_info::default_route)
+        .await
+        .unwrap_or_default()
+}
+
+pub async fn watch(app: AppHandle) {
+    let mut previous = current_route().await;
+    let mut ticker = tokio::time::interval(NETWORK_CHECK_INTERVAL);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        ticker.tick().await;
+        let current = current_route().await;
+        if current == previous {
+            continue;
+        }
+
+        let (reconnected_segments, reannounced_torrents) = if needs_reconnect(&previous, &current) {
+            let state = app.state::<AppState>();
+            (state.engine.reconnect_segments(), state.torrent_client.reannounce_all().await)
+        } else {
+            (0, 0)
+        };
+        let network = tokio::task::spawn_blocking(network_info::current_network)
+            .await
+            .unwrap_or(NetworkIdentity::Unknown);
+
+        tracing::info!(
+            "Network changed from {:?} to {:?} ({}): reconnected {} segments, reannounced {} torrents",
+            previous,
+            current,
+            network,
+            reconnected_segments,
+            reannounced_torrents
+        );
+        let _ = app.emit(
+            "network-changed",
+            NetworkChange {
+                previous: previous.clone(),
+                current: current.clone(),
+                network,
+                reconnected_segments,
+                reannounced_torrents,
+            },
+        );
+        previous = current;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(interface: &str, addr: Option<&str>) -> RouteIdentity {
+        RouteIdentity {
+            interface: Some(interface.to_string()),
+            local_addr: addr.map(|a| a.parse().unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_needs_reconnect() {
+        let wifi = route("wlan0", Some("192.168.1.20"));
+        let ethernet = route("eth0", Some("192.168.1.21"));
+        let vpn = route("wlan0", Some("10.8.0.2"));
+
+        assert!(needs_reconnect(&wifi, &ethernet));
+        assert!(needs_reconnect(&wifi, &vpn));
+        assert!(!needs_reconnect(&wifi, &wifi.clone()));
+        // Offline: nothing to reconnect to yet; back online: reconnect
+        let offline = RouteIdentity::default();
+        assert!(!needs_reconnect(&wifi, &offline));
+        assert!(needs_reconnect(&offline, &wifi));
+    }
+}
@@ -1,8 +1,16 @@
 // src-tauri/src/services/notification_service.rs
 
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Manager};
 use tauri_plugin_notification::NotificationExt;
 
+use crate::utils::focus_mode;
+
+const COMPLETE_SOUND: &[u8] = include_bytes!("../../resources/sounds/complete.wav");
+const FAILURE_SOUND: &[u8] = include_bytes!("../../resources/sounds/failure.wav");
+
 /// Notification types
 #[derive(Debug, Clone)]
 pub enum NotificationType {
@@ -13,16 +21,201 @@ pub enum NotificationType {
     ClipboardUrlDetected { url: String },
 }
 
+impl NotificationType {
+    /// Critical notifications still show during do-not-disturb
+    pub fn is_critical(&self) -> bool {
+        matches!(
+            self,
+            NotificationType::DownloadFailed { .. } | NotificationType::LowDiskSpace { .. }
+        )
+    }
+
+    pub fn sound(&self) -> Option<SoundEvent> {
+        match self {
+            NotificationType::DownloadComplete { .. } | NotificationType::AllDownloadsComplete { .. } => {
+                Some(SoundEvent::Complete)
+            }
+            NotificationType::DownloadFailed { .. } => Some(SoundEvent::Failure),
+            _ => None,
+        }
+    }
+}
+
+/// Events that can play a sound
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SoundEvent {
+    Complete,
+    Failure,
+}
+
+/// Which sounds play, and whether do-not-disturb quiets them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SoundSettings {
+    pub enabled: bool,
+    /// Files played instead of the bundled chime and buzz
+    pub completion_sound: Option<PathBuf>,
+    pub failure_sound: Option<PathBuf>,
+    /// Silence sounds and non-critical notifications while the OS is in
+    /// do-not-disturb or focus mode
+    pub respect_do_not_disturb: bool,
+}
+
+enum Sound {
+    Bundled(&'static [u8]),
+    File(PathBuf),
+}
+
+impl SoundSettings {
+    fn sound_for(&self, event: SoundEvent) -> Sound {
+        let (custom, bundled) = match event {
+            SoundEvent::Complete => (&self.completion_sound, COMPLETE_SOUND),
+            SoundEvent::Failure => (&self.failure_sound, FAILURE_SOUND),
+        };
+        custom.clone().map(Sound::File).unwrap_or(Sound::Bundled(bundled))
+    }
+}
+
+/// Check that `path` is an audio file that can be played
+pub fn validate_sound_file(path: &Path) -> Result<(), String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("Can't open {}: {}", path.display(), e))?;
+    rodio::Decoder::new(std::io::BufReader::new(file))
+        .map(|_| ())
+        .map_err(|e| format!("{} is not a supported sound file: {}", path.display(), e))
+}
+
+/// Plays notification sounds on a dedicated thread, which owns the audio
+/// output and waits for each sound to finish. Failures are logged.
+pub struct SoundPlayer {
+    settings: parking_lot::RwLock<SoundSettings>,
+    /// Started on the first sound
+    thread: parking_lot::Mutex<Option<flume::Sender<Sound>>>,
+}
+
+impl SoundPlayer {
+    pub fn new(settings: SoundSettings) -> Self {
+        Self {
+            settings: parking_lot::RwLock::new(settings),
+            thread: parking_lot::Mutex::new(None),
+        }
+    }
+
+    pub fn settings(&self) -> SoundSettings {
+        self.settings.read().clone()
+    }
+
+    pub fn set_settings(&self, settings: SoundSettings) {
+        *self.settings.write() = settings;
+    }
+
+    /// Play the sound for `event`, if sounds are on
+    pub fn play(&self, event: SoundEvent) {
+        let settings = self.settings();
+        if settings.enabled {
+            self.queue(settings.sound_for(event));
+        }
+    }
+
+    /// Play `path`, or the bundled sound for `event`, whether or not
+    /// sounds are on
+    pub fn preview(&self, path: Option<PathBuf>, event: SoundEvent) {
+        let sound = match path {
+            Some(path) => Sound::File(path),
+            None => SoundSettings::default().sound_for(event),
+        };
+        self.queue(sound);
+    }
+
+    fn queue(&self, sound: Sound) {
+        let mut thread = self.thread.lock();
+        let tx = thread.get_or_insert_with(|| {
+            let (tx, rx) = flume::unbounded();
+            if let Err(e) = std::thread::Builder::new()
+                .name("notification-sounds".to_string())
+                .spawn(move || play_sounds(rx))
+            {
+                tracing::warn!("Failed to start the sound thread: {}", e);
+            }
+            tx
+        });
+        if tx.send(sound).is_err() {
+            // The thread is gone; start a new one for the next sound
+            *thread = None;
+        }
+    }
+}
+
+impl Default for SoundPlayer {
+    fn default() -> Self {
+        Self::new(SoundSettings::default())
+    }
+}
+
+fn play_sounds(rx: flume::Receiver<Sound>) {
+    // Opened on the first sound and kept; retried if no device was available
+    let mut output: Option<(rodio::OutputStream, rodio::OutputStreamHandle)> = None;
+    for sound in rx.iter() {
+        if output.is_none() {
+            match rodio::OutputStream::try_default() {
+                Ok(stream) => output = Some(stream),
+                Err(e) => {
+                    tracing::warn!("No audio output for notification sound: {}", e);
+                    continue;
+                }
+            }
+        }
+        let Some((_, handle)) = &output else { continue };
+        if let Err(e) = play_one(handle, sound) {
+            tracing::warn!("Failed to play notification sound: {}", e);
+        }
+    }
+}
+
+fn play_one(handle: &rodio::OutputStreamHandle, sound: Sound) -> Result<(), String> {
+    let sink = rodio::Sink::try_new(handle).map_err(|e| e.to_string())?;
+    match sound {
+        Sound::Bundled(bytes) => {
+            sink.append(rodio::Decoder::new(Cursor::new(bytes)).map_err(|e| e.to_string())?)
+        }
+        Sound::File(path) => {
+            let file = std::fs::File::open(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            let source = rodio::Decoder::new(std::io::BufReader::new(file))
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
+            sink.append(source);
+        }
+    }
+    sink.sleep_until_end();
+    Ok(())
+}
+
 /// Notification service
 pub struct NotificationService;
 
 impl NotificationService {
-    /// Send a notification
+    /// Send a notification and play its sound. During do-not-disturb only
+    /// critical notifications show and no sound plays, unless the user
+    /// chose to ignore it.
     pub async fn send(app: &AppHandle, notification_type: NotificationType) -> Result<(), String> {
+        let sounds = app.try_state::<crate::state::app_state::AppState>().map(|s| s.sounds.clone());
+        let quiet = match &sounds {
+            Some(sounds) if sounds.settings().respect_do_not_disturb => Self::do_not_disturb().await,
+            _ => false,
+        };
+
+        if let (Some(sounds), Some(event)) = (&sounds, notification_type.sound()) {
+            if !quiet {
+                sounds.play(event);
+            }
+        }
+
         // Check if notifications are enabled
         if !Self::is_enabled(app).await {
             return Ok(());
         }
+        if quiet && !notification_type.is_critical() {
+            tracing::debug!("Do not disturb is on, not showing {:?}", notification_type);
+            return Ok(());
+        }
 
         match notification_type {
             NotificationType::DownloadComplete { file_name, size } => {
@@ -43,6 +236,21 @@ impl NotificationService {
         }
     }
 
+    /// `send`, logging failures instead of returning them
+    pub async fn notify(app: &AppHandle, notification_type: NotificationType) {
+        if let Err(e) = Self::send(app, notification_type).await {
+            tracing::warn!("Failed to show notification: {}", e);
+        }
+    }
+
+    async fn do_not_disturb() -> bool {
+        tokio::task::spawn_blocking(focus_mode::do_not_disturb_active)
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or(false)
+    }
+
     /// Check if notifications are enabled
     async fn is_enabled(app: &AppHandle) -> bool {
         if let Some(state) = app.try_state::<crate::state::app_state::AppState>() {
@@ -174,3 +382,56 @@ pub async fn test_notification(app_handle: AppHandle) -> Result<(), String> {
     )
     .await
 }
+
+/// Play a sound file so the settings page can try it; without a path the
+/// bundled sound for `event` plays
+#[tauri::command]
+pub async fn preview_notification_sound(
+    app_handle: AppHandle,
+    path: Option<String>,
+    event: Option<SoundEvent>,
+) -> Result<(), String> {
+    let path = path.filter(|p| !p.trim().is_empty()).map(PathBuf::from);
+    if let Some(path) = &path {
+        validate_sound_file(path)?;
+    }
+    let state = app_handle
+        .try_state::<crate::state::app_state::AppState>()
+        .ok_or_else(|| "App is still starting".to_string())?;
+    state.sounds.preview(path, event.unwrap_or(SoundEvent::Complete));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sound_selection() {
+        let mut settings = SoundSettings::default();
+        assert!(matches!(settings.sound_for(SoundEvent::Complete), Sound::Bundled(b) if b == COMPLETE_SOUND));
+        assert!(matches!(settings.sound_for(SoundEvent::Failure), Sound::Bundled(b) if b == FAILURE_SOUND));
+
+        settings.failure_sound = Some(PathBuf::from("/sounds/buzz.ogg"));
+        assert!(matches!(settings.sound_for(SoundEvent::Failure), Sound::File(p) if p == Path::new("/sounds/buzz.ogg")));
+        assert!(matches!(settings.sound_for(SoundEvent::Complete), Sound::Bundled(_)));
+    }
+
+    #[test]
+    fn test_bundled_sounds_decode() {
+        for bytes in [COMPLETE_SOUND, FAILURE_SOUND] {
+            assert!(rodio::Decoder::new(Cursor::new(bytes)).is_ok());
+        }
+        assert!(validate_sound_file(Path::new("/nonexistent/chime.wav")).is_err());
+    }
+
+    #[test]
+    fn test_do_not_disturb_keeps_critical_notifications() {
+        let failed = NotificationType::DownloadFailed { file_name: "a.zip".into(), error: "gone".into() };
+        let complete = NotificationType::DownloadComplete { file_name: "a.zip".into(), size: 1 };
+        assert!(failed.is_critical());
+        assert!(!complete.is_critical());
+        assert_eq!(complete.sound(), Some(SoundEvent::Complete));
+        assert_eq!(NotificationType::ClipboardUrlDetected { url: "https://a".into() }.sound(), None);
+    }
+}
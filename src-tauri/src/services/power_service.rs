@@ -0,0 +1,580 @@
+// src-tauri/src/services/power_service.rs
+// Pauses transfers when the system is about to sleep and resumes them after
+// wake, so downloads don't come back to sockets the OS silently dropped.
+// Windows and macOS announce sleep and wake, Linux does through logind;
+// elsewhere a jump between the wall clock and the monotonic clock is taken
+// as a wake that wasn't announced.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use tauri::{AppHandle, Emitter, Manager};
+use uuid::Uuid;
+
+use crate::commands::download_commands::{lift_queue_hold, pause_download, resume_download};
+use crate::core::retry::rand_simple;
+use crate::database::db::Database;
+use crate::network::torrent_client_librqbit::TorrentState;
+use crate::state::app_state::AppState;
+
+/// Wait after wake before the first transfer resumes, for the network to
+/// come back
+const WAKE_RESUME_DELAY: Duration = Duration::from_secs(5);
+
+/// Up to this much more, picked per transfer, so they don't all reconnect
+/// at once
+const WAKE_RESUME_JITTER: Duration = Duration::from_secs(10);
+
+/// How long the OS is held off while transfers pause
+const SUSPEND_ACK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How often the clocks are compared where the OS doesn't announce sleep
+const WAKE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Wall clock time beyond the monotonic clock that counts as a sleep
+const WAKE_JUMP_THRESHOLD: Duration = Duration::from_secs(30);
+
+/// Settings key: transfers paused for sleep, so a restart while asleep
+/// still resumes them
+const PAUSED_FOR_SLEEP_KEY: &str = "paused_for_sleep";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerEvent {
+    /// The system is about to sleep
+    Suspending,
+    /// The system woke from an announced sleep
+    Resumed,
+    /// The clocks show the system slept without saying so; connections
+    /// are likely dead already
+    WokeUnannounced,
+}
+
+/// A power event from a platform listener. `done` is answered once
+/// transfers are paused, so the listener can let the system sleep.
+pub struct PowerSignal {
+    pub event: PowerEvent,
+    pub done: Option<flume::Sender<()>>,
+}
+
+/// What `SleepGuard` keeps under `PAUSED_FOR_SLEEP_KEY`
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct PausedForSleep {
+    downloads: Vec<Uuid>,
+    torrents: Vec<String>,
+}
+
+/// Transfers paused for sleep, to be resumed on wake. Only these are
+/// resumed: anything the user paused stays paused.
+#[derive(Default)]
+pub struct SleepGuard {
+    downloads: parking_lot::Mutex<HashSet<Uuid>>,
+    torrents: parking_lot::Mutex<HashSet<String>>,
+    asleep: AtomicBool,
+}
+
+impl SleepGuard {
+    /// The user paused or removed a download; don't resume it on wake.
+    /// True when it was waiting for one, and the set needs saving.
+    pub fn forget(&self, id: Uuid) -> bool {
+        self.downloads.lock().remove(&id)
+    }
+
+    /// The user paused or removed a torrent; don't resume it on wake
+    pub fn forget_torrent(&self, info_hash: &str) -> bool {
+        self.torrents.lock().remove(info_hash)
+    }
+
+    /// Save the transfers waiting for wake, or clear the key when none are
+    pub async fn save(&self, db: &Database) {
+        let paused = PausedForSleep {
+            downloads: self.downloads.lock().iter().copied().collect(),
+            torrents: self.torrents.lock().iter().cloned().collect(),
+        };
+        let result = if paused == PausedForSleep::default() {
+            db.delete_setting(PAUSED_FOR_SLEEP_KEY).await
+        } else {
+            match serde_json::to_string(&paused) {
+                Ok(value) => db.set_setting(PAUSED_FOR_SLEEP_KEY, &value).await,
+                Err(e) => {
+                    tracing::warn!("Failed to serialize the transfers paused for sleep: {}", e);
+                    return;
+                }
+            }
+        };
+        if let Err(e) = result {
+            tracing::warn!("Failed to save the transfers paused for sleep: {}", e);
+        }
+    }
+
+    /// Load what the last run paused for a sleep it never woke from
+    async fn restore(&self, db: &Database) -> bool {
+        let Ok(Some(value)) = db.get_setting(PAUSED_FOR_SLEEP_KEY).await else {
+            return false;
+        };
+        let paused: PausedForSleep = match serde_json::from_str(&value) {
+            Ok(paused) => paused,
+            Err(e) => {
+                tracing::warn!("Ignoring unreadable transfers paused for sleep: {}", e);
+                return false;
+            }
+        };
+        self.downloads.lock().extend(paused.downloads);
+        self.torrents.lock().extend(paused.torrents);
+        true
+    }
+
+    pub fn start(self: Arc<Self>, app: AppHandle) {
+        let (tx, rx) = flume::unbounded::<PowerSignal>();
+        if platform::listen(tx.clone()) {
+            tracing::info!("Listening for system sleep and wake");
+        } else {
+            tracing::info!("System sleep isn't announced here, watching the clock for wakes");
+            tauri::async_runtime::spawn(watch_clock(tx));
+        }
+
+        tauri::async_runtime::spawn(async move {
+            let db = app.state::<AppState>().db.clone();
+            if self.restore(&db).await {
+                tracing::info!("Resuming transfers the last run paused for sleep");
+                self.resume(&app);
+            }
+
+            while let Ok(signal) = rx.recv_async().await {
+                match signal.event {
+                    PowerEvent::Suspending => self.suspend(&app).await,
+                    PowerEvent::Resumed => self.resume(&app),
+                    PowerEvent::WokeUnannounced => {
+                        self.suspend(&app).await;
+                        self.resume(&app);
+                    }
+                }
+                if let Some(done) = signal.done {
+                    let _ = done.send(());
+                }
+            }
+        });
+    }
+
+    async fn suspend(&self, app: &AppHandle) {
+        self.asleep.store(true, Ordering::SeqCst);
+        let state = app.state::<AppState>();
+        // Pausing frees queue slots; nothing should start in them now
+        state.queue.write().await.set_held(true);
+
+        let active: Vec<Uuid> = state.active_downloads.read().await.keys().copied().collect();
+        let mut downloads = 0;
+        for id in active {
+            match pause_download(app.clone(), app.state::<AppState>(), id.to_string(), None).await {
+                Ok(()) => {
+                    // pause_download forgets the id, so mark it afterwards
+                    self.downloads.lock().insert(id);
+                    downloads += 1;
+                }
+                Err(e) => tracing::warn!("Failed to pause {} for sleep: {}", id, e),
+            }
+        }
+
+        let mut torrents = 0;
+        for info in state.torrent_client.list_torrents().await.unwrap_or_default() {
+            let running = matches!(
+                state.torrent_client.get_state(&info.info_hash).await,
+                Ok(TorrentState::Downloading | TorrentState::Seeding)
+            );
+            if !running {
+                continue;
+            }
+            match state.torrent_client.pause(&info.info_hash).await {
+                Ok(()) => {
+                    self.torrents.lock().insert(info.info_hash);
+                    torrents += 1;
+                }
+                Err(e) => tracing::warn!("Failed to pause torrent {} for sleep: {}", info.info_hash, e),
+            }
+        }
+
+        self.save(&state.db).await;
+        tracing::info!("Paused {} downloads and {} torrents for sleep", downloads, torrents);
+        let _ = app.emit(
+            "system-suspending",
+            serde_json::json!({ "downloads": downloads, "torrents": torrents }),
+        );
+    }
+
+    fn resume(self: &Arc<Self>, app: &AppHandle) {
+        self.asleep.store(false, Ordering::SeqCst);
+        let downloads: Vec<Uuid> = self.downloads.lock().iter().copied().collect();
+        let torrents: Vec<String> = self.torrents.lock().iter().cloned().collect();
+
+        tracing::info!("Woke from sleep, resuming {} downloads and {} torrents", downloads.len(), torrents.len());
+        let _ = app.emit(
+            "system-resumed",
+            serde_json::json!({ "downloads": downloads.len(), "torrents": torrents.len() }),
+        );
+
+        // The queue waits for the paused transfers to take their slots back
+        let guard = self.clone();
+        let queue_app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(WAKE_RESUME_DELAY + WAKE_RESUME_JITTER).await;
+            if !guard.asleep.load(Ordering::SeqCst) {
+                lift_queue_hold(&queue_app).await;
+            }
+        });
+
+        for id in downloads {
+            let guard = self.clone();
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(resume_delay()).await;
+                // Asleep again, or paused by hand while waiting
+                if guard.asleep.load(Ordering::SeqCst) || !guard.downloads.lock().remove(&id) {
+                    return;
+                }
+                guard.save(&app.state::<AppState>().db).await;
+                if let Err(e) = resume_download(app.clone(), app.state::<AppState>(), id.to_string()).await {
+                    tracing::warn!("Failed to resume {} after wake: {}", id, e);
+                }
+            });
+        }
+
+        for info_hash in torrents {
+            let guard = self.clone();
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(resume_delay()).await;
+                if guard.asleep.load(Ordering::SeqCst) || !guard.torrents.lock().remove(&info_hash) {
+                    return;
+                }
+                let state = app.state::<AppState>();
+                guard.save(&state.db).await;
+                if let Err(e) = state.torrent_client.resume(&info_hash).await {
+                    tracing::warn!("Failed to resume torrent {} after wake: {}", info_hash, e);
+                }
+            });
+        }
+    }
+}
+
+fn resume_delay() -> Duration {
+    WAKE_RESUME_DELAY + WAKE_RESUME_JITTER.mul_f64(rand_simple())
+}
+
+/// Notices sleeps from the wall clock running ahead of the monotonic
+/// clock, which stops while the system is suspended
+struct WakeDetector {
+    wall: SystemTime,
+    monotonic: Instant,
+}
+
+impl WakeDetector {
+    fn new(wall: SystemTime, monotonic: Instant) -> Self {
+        Self { wall, monotonic }
+    }
+
+    /// Whether the system slept since the last check
+    fn check(&mut self, wall: SystemTime, monotonic: Instant) -> bool {
+        let wall_elapsed = wall.duration_since(self.wall).unwrap_or_default();
+        let monotonic_elapsed = monotonic.saturating_duration_since(self.monotonic);
+        self.wall = wall;
+        self.monotonic = monotonic;
+        wall_elapsed > monotonic_elapsed + WAKE_JUMP_THRESHOLD
+    }
+}
+
+async fn watch_clock(tx: flume::Sender<PowerSignal>) {
+    let mut detector = WakeDetector::new(SystemTime::now(), Instant::now());
+    let mut interval = tokio::time::interval(WAKE_CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        if detector.check(SystemTime::now(), Instant::now()) {
+            let signal = PowerSignal { event: PowerEvent::WokeUnannounced, done: None };
+            if tx.send_async(signal).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Send `event` and wait, up to `SUSPEND_ACK_TIMEOUT`, for it to be handled
+#[cfg_attr(not(any(target_os = "linux", target_os = "macos", windows)), allow(dead_code))]
+fn send_and_wait(tx: &flume::Sender<PowerSignal>, event: PowerEvent) {
+    let (done, handled) = flume::bounded(1);
+    if tx.send(PowerSignal { event, done: Some(done) }).is_ok() {
+        let _ = handled.recv_timeout(SUSPEND_ACK_TIMEOUT);
+    }
+}
+
+/// logind's PrepareForSleep signal as printed by `gdbus monitor`:
+/// true before sleeping, false after waking
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_prepare_for_sleep(line: &str) -> Option<PowerEvent> {
+    let args = line.split_once("PrepareForSleep")?.1.trim();
+    match args {
+        "(true,)" => Some(PowerEvent::Suspending),
+        "(false,)" => Some(PowerEvent::Resumed),
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::os::unix::process::CommandExt;
+    use std::process::{Child, Command, Stdio};
+
+    /// logind only waits for apps holding a delay inhibitor. The command
+    /// it holds the lock for exits with the app, and gets its own process
+    /// group so `release` stops it along with systemd-inhibit.
+    fn take_inhibitor() -> Option<Child> {
+        Command::new("systemd-inhibit")
+            .args([
+                "--what=sleep",
+                "--mode=delay",
+                "--who=AFK-Dunld",
+                "--why=Pausing downloads",
+                "tail",
+                &format!("--pid={}", std::process::id()),
+                "-f",
+                "/dev/null",
+            ])
+            .process_group(0)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()
+    }
+
+    fn release(inhibitor: &mut Option<Child>) {
+        if let Some(mut child) = inhibitor.take() {
+            if let Ok(group) = libc::pid_t::try_from(child.id()) {
+                // SAFETY: signals only the group the inhibitor leads
+                unsafe { libc::kill(-group, libc::SIGTERM) };
+            }
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+
+    pub fn listen(tx: flume::Sender<PowerSignal>) -> bool {
+        let child = Command::new("gdbus")
+            .args([
+                "monitor",
+                "--system",
+                "--dest",
+                "org.freedesktop.login1",
+                "--object-path",
+                "/org/freedesktop/login1",
+            ])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn();
+        let Ok(mut child) = child else {
+            return false;
+        };
+        let Some(stdout) = child.stdout.take() else {
+            return false;
+        };
+
+        std::thread::spawn(move || {
+            let mut inhibitor = take_inhibitor();
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                match parse_prepare_for_sleep(&line) {
+                    Some(PowerEvent::Suspending) => {
+                        send_and_wait(&tx, PowerEvent::Suspending);
+                        release(&mut inhibitor);
+                    }
+                    Some(event) => {
+                        inhibitor = take_inhibitor();
+                        if tx.send(PowerSignal { event, done: None }).is_err() {
+                            break;
+                        }
+                    }
+                    None => {}
+                }
+            }
+            release(&mut inhibitor);
+            let _ = child.kill();
+            let _ = child.wait();
+            tracing::warn!("Stopped listening for system sleep, watching the clock for wakes instead");
+            tauri::async_runtime::spawn(watch_clock(tx));
+        });
+        true
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::*;
+    use block2::RcBlock;
+    use objc2_app_kit::{NSWorkspace, NSWorkspaceDidWakeNotification, NSWorkspaceWillSleepNotification};
+    use objc2_foundation::{NSNotification, NSNotificationName};
+    use std::ptr::NonNull;
+
+    pub fn listen(tx: flume::Sender<PowerSignal>) -> bool {
+        // SAFETY: the notification names are static NSStrings from AppKit,
+        // and the observers live as long as the app
+        unsafe {
+            let center = NSWorkspace::sharedWorkspace().notificationCenter();
+            let observe = |name: &NSNotificationName, event: PowerEvent| {
+                let tx = tx.clone();
+                // Posted on the main thread; the system sleeps once the
+                // handlers return
+                let block = RcBlock::new(move |_: NonNull<NSNotification>| match event {
+                    PowerEvent::Suspending => send_and_wait(&tx, event),
+                    _ => {
+                        let _ = tx.send(PowerSignal { event, done: None });
+                    }
+                });
+                let observer = center.addObserverForName_object_queue_usingBlock(Some(name), None, None, &block);
+                std::mem::forget(observer);
+            };
+            observe(NSWorkspaceWillSleepNotification, PowerEvent::Suspending);
+            observe(NSWorkspaceDidWakeNotification, PowerEvent::Resumed);
+        }
+        true
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::*;
+    use std::sync::OnceLock;
+    use winapi::shared::minwindef::{LPARAM, LRESULT, TRUE, UINT, WPARAM};
+    use winapi::shared::windef::HWND;
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    use winapi::um::winuser::{
+        CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, RegisterClassW, TranslateMessage, MSG,
+        PBT_APMRESUMEAUTOMATIC, PBT_APMSUSPEND, WM_POWERBROADCAST, WNDCLASSW,
+    };
+
+    static SIGNALS: OnceLock<flume::Sender<PowerSignal>> = OnceLock::new();
+
+    unsafe extern "system" fn window_proc(hwnd: HWND, msg: UINT, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if msg == WM_POWERBROADCAST {
+            if let Some(tx) = SIGNALS.get() {
+                match wparam {
+                    // Windows allows about two seconds before it sleeps
+                    PBT_APMSUSPEND => send_and_wait(tx, PowerEvent::Suspending),
+                    PBT_APMRESUMEAUTOMATIC => {
+                        let _ = tx.send(PowerSignal { event: PowerEvent::Resumed, done: None });
+                    }
+                    _ => {}
+                }
+            }
+            return TRUE as LRESULT;
+        }
+        DefWindowProcW(hwnd, msg, wparam, lparam)
+    }
+
+    pub fn listen(tx: flume::Sender<PowerSignal>) -> bool {
+        if SIGNALS.set(tx).is_err() {
+            return true;
+        }
+
+        std::thread::spawn(|| {
+            let class_name: Vec<u16> = "AfkDunldPowerMonitor\0".encode_utf16().collect();
+            // SAFETY: the class and window are created and pumped on this
+            // thread, which lives as long as the app
+            unsafe {
+                let instance = GetModuleHandleW(std::ptr::null());
+                let mut class: WNDCLASSW = std::mem::zeroed();
+                class.lpfnWndProc = Some(window_proc);
+                class.hInstance = instance;
+                class.lpszClassName = class_name.as_ptr();
+                if RegisterClassW(&class) == 0 {
+                    tracing::warn!("Failed to register the power monitor window class");
+                    return;
+                }
+
+                // Message-only windows don't get broadcasts, so this is a
+                // top-level window that is never shown
+                let hwnd = CreateWindowExW(
+                    0,
+                    class_name.as_ptr(),
+                    class_name.as_ptr(),
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                    instance,
+                    std::ptr::null_mut(),
+                );
+                if hwnd.is_null() {
+                    tracing::warn!("Failed to create the power monitor window");
+                    return;
+                }
+
+                let mut msg: MSG = std::mem::zeroed();
+                while GetMessageW(&mut msg, std::ptr::null_mut(), 0, 0) > 0 {
+                    TranslateMessage(&msg);
+                    DispatchMessageW(&msg);
+                }
+            }
+        });
+        true
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    use super::*;
+
+    pub fn listen(_tx: flume::Sender<PowerSignal>) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wake_detection() {
+        let wall = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let monotonic = Instant::now();
+        let mut detector = WakeDetector::new(wall, monotonic);
+
+        let tick = Duration::from_secs(5);
+        assert!(!detector.check(wall + tick, monotonic + tick));
+        // An hour asleep: the wall clock moved, the monotonic clock didn't
+        assert!(detector.check(wall + tick + Duration::from_secs(3600), monotonic + tick * 2));
+        // The wall clock going back is not a wake
+        assert!(!detector.check(wall, monotonic + tick * 3));
+    }
+
+    #[test]
+    fn test_logind_signal_parsing() {
+        let line = "/org/freedesktop/login1: org.freedesktop.login1.Manager.PrepareForSleep (true,)";
+        assert_eq!(parse_prepare_for_sleep(line), Some(PowerEvent::Suspending));
+        let line = "/org/freedesktop/login1: org.freedesktop.login1.Manager.PrepareForSleep (false,)";
+        assert_eq!(parse_prepare_for_sleep(line), Some(PowerEvent::Resumed));
+        let line = "/org/freedesktop/login1: org.freedesktop.login1.Manager.PrepareForShutdown (true,)";
+        assert_eq!(parse_prepare_for_sleep(line), None);
+    }
+
+    #[test]
+    fn test_paused_for_sleep_round_trip() {
+        let paused = PausedForSleep {
+            downloads: vec![Uuid::new_v4()],
+            torrents: vec!["c12fe1c06bba254a9dc9f519b335aa7c1367a88a".to_string()],
+        };
+        let value = serde_json::to_string(&paused).unwrap();
+        assert_eq!(serde_json::from_str::<PausedForSleep>(&value).unwrap(), paused);
+    }
+
+    #[test]
+    fn test_resume_delay_bounds() {
+        let delay = resume_delay();
+        assert!(delay >= WAKE_RESUME_DELAY);
+        assert!(delay <= WAKE_RESUME_DELAY + WAKE_RESUME_JITTER);
+    }
+}
@@ -0,0 +1,618 @@
+// src-tauri/src/services/recent_files_service.rs
+// Lists finished downloads where the OS shows recently used files: the
+// recent documents list on Windows, recent items on macOS and GTK's
+// recently-used.xbel on Linux. On Windows the taskbar Jump List also gets a
+// section with the last few downloads. Clicking one starts the app with
+// `--open-download <id>`, which the running instance routes through
+// `open_file`. Files with a sensitive extension are never listed.
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tauri::{AppHandle, Manager};
+use uuid::Uuid;
+
+use crate::commands::download_commands::open_file;
+use crate::core::download_task::{DownloadStatus, DownloadTask};
+use crate::state::app_state::AppState;
+
+/// Launch argument a Jump List entry starts the app with, followed by the
+/// download's id
+pub const OPEN_DOWNLOAD_ARG: &str = "--open-download";
+
+/// Downloads the Jump List section shows
+pub const JUMP_LIST_SIZE: usize = 5;
+
+/// Extensions of files that are never listed: keys, certificates and
+/// password vaults
+pub const DEFAULT_SENSITIVE_EXTENSIONS: &[&str] =
+    &["kdbx", "kdb", "key", "pem", "p12", "pfx", "gpg", "pgp", "asc", "ovpn", "keychain"];
+
+/// A finished download as the Jump List shows it
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecentFile {
+    pub id: Uuid,
+    pub path: PathBuf,
+    pub title: String,
+}
+
+/// What the OS offers for recently used files. The platform's
+/// implementation is picked at build time; tests record the calls instead.
+pub trait ShellRecents: Send + Sync {
+    fn add_recent_document(&self, path: &Path) -> Result<(), String>;
+
+    /// Replace the app's Jump List section, newest first. Empty removes it.
+    fn set_jump_list(&self, _files: &[RecentFile]) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+pub struct RecentFiles {
+    shell: Box<dyn ShellRecents>,
+    enabled: AtomicBool,
+    /// Lowercase, without the dot
+    sensitive_extensions: parking_lot::RwLock<Vec<String>>,
+    /// Newest first
+    jump_list: parking_lot::Mutex<VecDeque<RecentFile>>,
+}
+
+impl RecentFiles {
+    pub fn new(shell: Box<dyn ShellRecents>) -> Self {
+        Self {
+            shell,
+            enabled: AtomicBool::new(true),
+            sensitive_extensions: parking_lot::RwLock::new(
+                DEFAULT_SENSITIVE_EXTENSIONS.iter().map(|e| e.to_string()).collect(),
+            ),
+            jump_list: parking_lot::Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn for_platform(app: &AppHandle) -> Self {
+        Self::new(Box::new(platform::Shell::new(app.clone())))
+    }
+
+    /// Apply the settings and bring the Jump List in line with them
+    pub fn configure(&self, enabled: bool, sensitive_extensions: &[String]) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        *self.sensitive_extensions.write() = sensitive_extensions
+            .iter()
+            .map(|e| e.trim().trim_start_matches('.').to_ascii_lowercase())
+            .filter(|e| !e.is_empty())
+            .collect();
+
+        let files: Vec<RecentFile> = {
+            let mut jump_list = self.jump_list.lock();
+            jump_list.retain(|file| !self.is_sensitive(&file.path));
+            jump_list.iter().cloned().collect()
+        };
+        let shown = if enabled { files.as_slice() } else { &[] };
+        if let Err(e) = self.shell.set_jump_list(shown) {
+            tracing::warn!("Failed to update the Jump List: {}", e);
+        }
+    }
+
+    /// Fill the Jump List from the download history without touching the
+    /// OS; `configure` shows it
+    pub fn seed(&self, downloads: &[DownloadTask]) {
+        let mut completed: Vec<&DownloadTask> = downloads
+            .iter()
+            .filter(|d| d.status == DownloadStatus::Completed && d.completed_at.is_some())
+            .collect();
+        completed.sort_by_key(|d| std::cmp::Reverse(d.completed_at));
+        *self.jump_list.lock() = completed
+            .into_iter()
+            .take(JUMP_LIST_SIZE)
+            .map(RecentFile::from)
+            .collect();
+    }
+
+    pub fn is_sensitive(&self, path: &Path) -> bool {
+        let Some(extension) = path.extension().and_then(|e| e.to_str()) else {
+            return false;
+        };
+        let extension = extension.to_ascii_lowercase();
+        self.sensitive_extensions.read().iter().any(|e| *e == extension)
+    }
+
+    /// List a download that just completed
+    pub fn record(&self, task: &DownloadTask) {
+        if !self.enabled.load(Ordering::Relaxed) {
+            return;
+        }
+        if self.is_sensitive(&task.save_path) {
+            tracing::debug!("Not listing {} as a recent file: sensitive extension", task.id);
+            return;
+        }
+
+        if let Err(e) = self.shell.add_recent_document(&task.save_path) {
+            tracing::warn!("Failed to add {} to recent files: {}", task.save_path.display(), e);
+        }
+        let files: Vec<RecentFile> = {
+            let mut jump_list = self.jump_list.lock();
+            jump_list.retain(|file| file.id != task.id);
+            jump_list.push_front(RecentFile::from(task));
+            jump_list.truncate(JUMP_LIST_SIZE);
+            jump_list.iter().cloned().collect()
+        };
+        if let Err(e) = self.shell.set_jump_list(&files) {
+            tracing::warn!("Failed to update the Jump List: {}", e);
+        }
+    }
+}
+
+impl From<&DownloadTask> for RecentFile {
+    fn from(task: &DownloadTask) -> Self {
+        Self {
+            id: task.id,
+            path: task.save_path.clone(),
+            title: task.file_name.clone(),
+        }
+    }
+}
+
+/// The download a launch asks to open, from a Jump List click
+pub fn requested_download(args: &[String]) -> Option<Uuid> {
+    let position = args.iter().position(|arg| arg == OPEN_DOWNLOAD_ARG)?;
+    Uuid::parse_str(args.get(position + 1)?).ok()
+}
+
+/// Open the download a launch asks for, if any. Called with the arguments
+/// of the first instance and of every later one forwarded to it.
+pub fn handle_launch_args(app: &AppHandle, args: &[String]) {
+    let Some(id) = requested_download(args) else {
+        return;
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = open_file(app.state::<AppState>(), id.to_string()).await {
+            tracing::warn!("Failed to open download {} from the Jump List: {}", id, e);
+        }
+    });
+}
+
+#[cfg(windows)]
+mod platform {
+    // winapi has no bindings for the Jump List interfaces, so the few
+    // methods used are declared here
+    use super::*;
+    use std::ffi::c_void;
+    use std::os::windows::ffi::OsStrExt;
+
+    type HResult = i32;
+
+    #[repr(C)]
+    struct Guid(u32, u16, u16, [u8; 8]);
+
+    const CLSID_DESTINATION_LIST: Guid =
+        Guid(0x77f10cf0, 0x3db5, 0x4966, [0xb5, 0x20, 0xb7, 0xc5, 0x4f, 0xd3, 0x5e, 0xd6]);
+    const IID_CUSTOM_DESTINATION_LIST: Guid =
+        Guid(0x6332debf, 0x87b5, 0x4670, [0x90, 0xc0, 0x5e, 0x57, 0xb4, 0x08, 0xa4, 0x9e]);
+    const CLSID_ENUMERABLE_OBJECT_COLLECTION: Guid =
+        Guid(0x2d3468c1, 0x36a7, 0x43b6, [0xac, 0x24, 0xd3, 0xf0, 0x2f, 0xd9, 0x60, 0x7a]);
+    const IID_OBJECT_COLLECTION: Guid =
+        Guid(0x5632b1a4, 0xe38a, 0x400a, [0x92, 0x8a, 0xd4, 0xcd, 0x63, 0x23, 0x02, 0x95]);
+    const IID_OBJECT_ARRAY: Guid =
+        Guid(0x92ca9dcd, 0x5622, 0x4bba, [0xa8, 0x05, 0x5e, 0x9f, 0x54, 0x1b, 0xd8, 0xc9]);
+    const CLSID_SHELL_LINK: Guid = Guid(0x00021401, 0, 0, [0xc0, 0, 0, 0, 0, 0, 0, 0x46]);
+    const IID_SHELL_LINK_W: Guid = Guid(0x000214f9, 0, 0, [0xc0, 0, 0, 0, 0, 0, 0, 0x46]);
+    const IID_PROPERTY_STORE: Guid =
+        Guid(0x886d8eeb, 0x8cf2, 0x4446, [0x8d, 0x02, 0xcd, 0xba, 0x1d, 0xbd, 0xcf, 0x99]);
+
+    #[repr(C)]
+    struct PropertyKey {
+        fmtid: Guid,
+        pid: u32,
+    }
+
+    /// PKEY_Title, the entry's label
+    const PKEY_TITLE: PropertyKey = PropertyKey {
+        fmtid: Guid(0xf29f85e0, 0x4ff9, 0x1068, [0xab, 0x91, 0x08, 0x00, 0x2b, 0x27, 0xb3, 0xd9]),
+        pid: 2,
+    };
+
+    /// A PROPVARIANT holding a VT_LPWSTR
+    #[repr(C)]
+    struct PropVariant {
+        vt: u16,
+        reserved: [u16; 3],
+        value: *const u16,
+        padding: usize,
+    }
+
+    const VT_LPWSTR: u16 = 31;
+    const COINIT_APARTMENTTHREADED: u32 = 0x2;
+    const CLSCTX_INPROC_SERVER: u32 = 0x1;
+    const SHARD_PATHW: u32 = 0x3;
+
+    #[link(name = "ole32")]
+    extern "system" {
+        fn CoInitializeEx(reserved: *mut c_void, coinit: u32) -> HResult;
+        fn CoUninitialize();
+        fn CoCreateInstance(
+            clsid: *const Guid,
+            outer: *mut c_void,
+            context: u32,
+            iid: *const Guid,
+            out: *mut *mut c_void,
+        ) -> HResult;
+    }
+
+    #[link(name = "shell32")]
+    extern "system" {
+        fn SHAddToRecentDocs(flags: u32, pv: *const c_void);
+    }
+
+    type Method = usize;
+
+    #[repr(C)]
+    struct UnknownVtbl {
+        query_interface: unsafe extern "system" fn(*mut c_void, *const Guid, *mut *mut c_void) -> HResult,
+        add_ref: Method,
+        release: unsafe extern "system" fn(*mut c_void) -> u32,
+    }
+
+    #[repr(C)]
+    struct DestinationListVtbl {
+        unknown: UnknownVtbl,
+        set_app_id: Method,
+        begin_list: unsafe extern "system" fn(*mut c_void, *mut u32, *const Guid, *mut *mut c_void) -> HResult,
+        append_category: unsafe extern "system" fn(*mut c_void, *const u16, *mut c_void) -> HResult,
+        append_known_category: Method,
+        add_user_tasks: Method,
+        commit_list: unsafe extern "system" fn(*mut c_void) -> HResult,
+        get_removed_destinations: Method,
+        delete_list: unsafe extern "system" fn(*mut c_void, *const u16) -> HResult,
+        abort_list: unsafe extern "system" fn(*mut c_void) -> HResult,
+    }
+
+    #[repr(C)]
+    struct ObjectCollectionVtbl {
+        unknown: UnknownVtbl,
+        get_count: Method,
+        get_at: Method,
+        add_object: unsafe extern "system" fn(*mut c_void, *mut c_void) -> HResult,
+    }
+
+    #[repr(C)]
+    struct ShellLinkVtbl {
+        unknown: UnknownVtbl,
+        get_path: Method,
+        get_id_list: Method,
+        set_id_list: Method,
+        get_description: Method,
+        set_description: unsafe extern "system" fn(*mut c_void, *const u16) -> HResult,
+        get_working_directory: Method,
+        set_working_directory: Method,
+        get_arguments: Method,
+        set_arguments: unsafe extern "system" fn(*mut c_void, *const u16) -> HResult,
+        get_hotkey: Method,
+        set_hotkey: Method,
+        get_show_cmd: Method,
+        set_show_cmd: Method,
+        get_icon_location: Method,
+        set_icon_location: Method,
+        set_relative_path: Method,
+        resolve: Method,
+        set_path: unsafe extern "system" fn(*mut c_void, *const u16) -> HResult,
+    }
+
+    #[repr(C)]
+    struct PropertyStoreVtbl {
+        unknown: UnknownVtbl,
+        get_count: Method,
+        get_at: Method,
+        get_value: Method,
+        set_value: unsafe extern "system" fn(*mut c_void, *const PropertyKey, *const PropVariant) -> HResult,
+        commit: unsafe extern "system" fn(*mut c_void) -> HResult,
+    }
+
+    /// An owned COM interface pointer, released on drop
+    struct Com<V> {
+        ptr: *mut c_void,
+        _vtbl: std::marker::PhantomData<V>,
+    }
+
+    impl<V> Com<V> {
+        unsafe fn create(clsid: &Guid, iid: &Guid) -> Result<Self, String> {
+            let mut ptr = std::ptr::null_mut();
+            check(CoCreateInstance(clsid, std::ptr::null_mut(), CLSCTX_INPROC_SERVER, iid, &mut ptr))?;
+            Ok(Self { ptr, _vtbl: std::marker::PhantomData })
+        }
+
+        unsafe fn cast<T>(&self, iid: &Guid) -> Result<Com<T>, String> {
+            let mut ptr = std::ptr::null_mut();
+            check((self.unknown().query_interface)(self.ptr, iid, &mut ptr))?;
+            Ok(Com { ptr, _vtbl: std::marker::PhantomData })
+        }
+
+        unsafe fn vtbl(&self) -> &V {
+            &**(self.ptr as *const *const V)
+        }
+
+        unsafe fn unknown(&self) -> &UnknownVtbl {
+            &**(self.ptr as *const *const UnknownVtbl)
+        }
+    }
+
+    impl<V> Drop for Com<V> {
+        fn drop(&mut self) {
+            // SAFETY: `ptr` is a live interface this wrapper holds one
+            // reference to
+            unsafe {
+                (self.unknown().release)(self.ptr);
+            }
+        }
+    }
+
+    fn check(result: HResult) -> Result<(), String> {
+        if result >= 0 {
+            Ok(())
+        } else {
+            Err(format!("HRESULT 0x{:08x}", result as u32))
+        }
+    }
+
+    fn wide(text: impl AsRef<std::ffi::OsStr>) -> Vec<u16> {
+        text.as_ref().encode_wide().chain(std::iter::once(0)).collect()
+    }
+
+    unsafe fn shell_link(exe: &Path, file: &RecentFile) -> Result<Com<ShellLinkVtbl>, String> {
+        let link: Com<ShellLinkVtbl> = Com::create(&CLSID_SHELL_LINK, &IID_SHELL_LINK_W)?;
+        check((link.vtbl().set_path)(link.ptr, wide(exe).as_ptr()))?;
+        let arguments = format!("{} {}", OPEN_DOWNLOAD_ARG, file.id);
+        check((link.vtbl().set_arguments)(link.ptr, wide(arguments).as_ptr()))?;
+        check((link.vtbl().set_description)(link.ptr, wide(&file.path).as_ptr()))?;
+
+        let title = wide(&file.title);
+        let value = PropVariant { vt: VT_LPWSTR, reserved: [0; 3], value: title.as_ptr(), padding: 0 };
+        let store: Com<PropertyStoreVtbl> = link.cast(&IID_PROPERTY_STORE)?;
+        check((store.vtbl().set_value)(store.ptr, &PKEY_TITLE, &value))?;
+        check((store.vtbl().commit)(store.ptr))?;
+        Ok(link)
+    }
+
+    unsafe fn replace_jump_list(files: &[RecentFile]) -> Result<(), String> {
+        let list: Com<DestinationListVtbl> = Com::create(&CLSID_DESTINATION_LIST, &IID_CUSTOM_DESTINATION_LIST)?;
+        if files.is_empty() {
+            return check((list.vtbl().delete_list)(list.ptr, std::ptr::null()));
+        }
+
+        let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+        let mut slots = 0u32;
+        let mut removed = std::ptr::null_mut();
+        check((list.vtbl().begin_list)(list.ptr, &mut slots, &IID_OBJECT_ARRAY, &mut removed))?;
+        if !removed.is_null() {
+            drop(Com::<UnknownVtbl> { ptr: removed, _vtbl: std::marker::PhantomData });
+        }
+
+        let build = || -> Result<(), String> {
+            let items: Com<ObjectCollectionVtbl> =
+                Com::create(&CLSID_ENUMERABLE_OBJECT_COLLECTION, &IID_OBJECT_COLLECTION)?;
+            for file in files.iter().take(slots.max(1) as usize) {
+                let link = shell_link(&exe, file)?;
+                check((items.vtbl().add_object)(items.ptr, link.ptr))?;
+            }
+            // An IObjectCollection is an IObjectArray
+            check((list.vtbl().append_category)(list.ptr, wide("Recent downloads").as_ptr(), items.ptr))?;
+            check((list.vtbl().commit_list)(list.ptr))
+        };
+        build().inspect_err(|_| {
+            (list.vtbl().abort_list)(list.ptr);
+        })
+    }
+
+    pub struct Shell;
+
+    impl Shell {
+        pub fn new(_app: AppHandle) -> Self {
+            Self
+        }
+    }
+
+    impl ShellRecents for Shell {
+        fn add_recent_document(&self, path: &Path) -> Result<(), String> {
+            let path = wide(path);
+            // SAFETY: SHARD_PATHW takes a null-terminated wide path
+            unsafe { SHAddToRecentDocs(SHARD_PATHW, path.as_ptr() as *const c_void) };
+            Ok(())
+        }
+
+        fn set_jump_list(&self, files: &[RecentFile]) -> Result<(), String> {
+            let files = files.to_vec();
+            // COM wants a thread of its own rather than a runtime worker
+            std::thread::spawn(move || {
+                // SAFETY: COM is initialized on this thread for the
+                // duration of the calls, and every interface is released
+                // before it is uninitialized
+                unsafe {
+                    let initialized = CoInitializeEx(std::ptr::null_mut(), COINIT_APARTMENTTHREADED) >= 0;
+                    if let Err(e) = replace_jump_list(&files) {
+                        tracing::warn!("Failed to update the Jump List: {}", e);
+                    }
+                    if initialized {
+                        CoUninitialize();
+                    }
+                }
+            });
+            Ok(())
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::*;
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+    use objc2_foundation::NSString;
+
+    pub struct Shell {
+        app: AppHandle,
+    }
+
+    impl Shell {
+        pub fn new(app: AppHandle) -> Self {
+            Self { app }
+        }
+    }
+
+    impl ShellRecents for Shell {
+        fn add_recent_document(&self, path: &Path) -> Result<(), String> {
+            let path = path.to_string_lossy().into_owned();
+            self.app
+                .run_on_main_thread(move || {
+                    // SAFETY: AppKit is called on the main thread, and
+                    // noteNewRecentDocumentURL: is given a file NSURL
+                    unsafe {
+                        let path = NSString::from_str(&path);
+                        let url: *mut AnyObject = msg_send![class!(NSURL), fileURLWithPath: &*path];
+                        let controller: *mut AnyObject =
+                            msg_send![class!(NSDocumentController), sharedDocumentController];
+                        if !url.is_null() && !controller.is_null() {
+                            let _: () = msg_send![controller, noteNewRecentDocumentURL: url];
+                        }
+                    }
+                })
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::*;
+    use gtk::prelude::RecentManagerExt;
+
+    pub struct Shell {
+        app: AppHandle,
+    }
+
+    impl Shell {
+        pub fn new(app: AppHandle) -> Self {
+            Self { app }
+        }
+    }
+
+    impl ShellRecents for Shell {
+        fn add_recent_document(&self, path: &Path) -> Result<(), String> {
+            let uri = gtk::glib::filename_to_uri(path, None).map_err(|e| e.to_string())?;
+            // GTK writes recently-used.xbel from its main thread
+            self.app
+                .run_on_main_thread(move || {
+                    if !gtk::RecentManager::default().add_item(&uri) {
+                        tracing::warn!("GTK refused to add {} to recent files", uri);
+                    }
+                })
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    use super::*;
+
+    pub struct Shell;
+
+    impl Shell {
+        pub fn new(_app: AppHandle) -> Self {
+            Self
+        }
+    }
+
+    impl ShellRecents for Shell {
+        fn add_recent_document(&self, _path: &Path) -> Result<(), String> {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[derive(Default)]
+    struct Calls {
+        documents: Vec<PathBuf>,
+        jump_lists: Vec<Vec<RecentFile>>,
+    }
+
+    struct RecordingShell(Arc<parking_lot::Mutex<Calls>>);
+
+    impl ShellRecents for RecordingShell {
+        fn add_recent_document(&self, path: &Path) -> Result<(), String> {
+            self.0.lock().documents.push(path.to_path_buf());
+            Ok(())
+        }
+
+        fn set_jump_list(&self, files: &[RecentFile]) -> Result<(), String> {
+            self.0.lock().jump_lists.push(files.to_vec());
+            Ok(())
+        }
+    }
+
+    fn recording() -> (RecentFiles, Arc<parking_lot::Mutex<Calls>>) {
+        let calls = Arc::new(parking_lot::Mutex::new(Calls::default()));
+        (RecentFiles::new(Box::new(RecordingShell(calls.clone()))), calls)
+    }
+
+    fn completed(name: &str) -> DownloadTask {
+        let mut task = DownloadTask::new(
+            format!("https://example.com/{}", name),
+            name.to_string(),
+            PathBuf::from("/downloads").join(name),
+            1,
+        );
+        task.status = DownloadStatus::Completed;
+        task
+    }
+
+    #[test]
+    fn test_completed_files_are_listed_newest_first() {
+        let (recent, calls) = recording();
+        let tasks: Vec<DownloadTask> = (0..7).map(|i| completed(&format!("file{}.zip", i))).collect();
+        for task in &tasks {
+            recent.record(task);
+        }
+        // Finishing again moves it to the top instead of listing it twice
+        recent.record(&tasks[3]);
+
+        let calls = calls.lock();
+        assert_eq!(calls.documents.len(), 8);
+        assert_eq!(calls.documents[0], PathBuf::from("/downloads/file0.zip"));
+        let shown: Vec<&str> = calls.jump_lists.last().unwrap().iter().map(|f| f.title.as_str()).collect();
+        assert_eq!(shown, ["file3.zip", "file6.zip", "file5.zip", "file4.zip", "file2.zip"]);
+    }
+
+    #[test]
+    fn test_sensitive_files_and_disabled_setting_are_skipped() {
+        let (recent, calls) = recording();
+        recent.record(&completed("vault.KDBX"));
+        recent.record(&completed("movie.mkv"));
+        assert_eq!(calls.lock().documents, [PathBuf::from("/downloads/movie.mkv")]);
+
+        // A newly sensitive extension drops what is already listed
+        recent.configure(true, &["kdbx".to_string(), ".MKV".to_string()]);
+        assert!(calls.lock().jump_lists.last().unwrap().is_empty());
+        recent.record(&completed("vault.kdbx"));
+        recent.record(&completed("notes.txt"));
+        assert_eq!(calls.lock().documents.len(), 2);
+
+        recent.configure(false, &[]);
+        assert!(calls.lock().jump_lists.last().unwrap().is_empty());
+        recent.record(&completed("report.pdf"));
+        assert_eq!(calls.lock().documents.len(), 2);
+    }
+
+    #[test]
+    fn test_open_download_argument() {
+        let id = Uuid::new_v4();
+        let args = vec!["afk-dunld.exe".to_string(), OPEN_DOWNLOAD_ARG.to_string(), id.to_string()];
+        assert_eq!(requested_download(&args), Some(id));
+        assert_eq!(requested_download(&args[..2]), None);
+        assert_eq!(requested_download(&["afk-dunld.exe".to_string()]), None);
+    }
+}
@@ -0,0 +1,358 @@
+// src-tauri/src/services/taskbar_service.rs
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::window::{ProgressBarState, ProgressBarStatus};
+use tauri::{AppHandle, Manager};
+use uuid::Uuid;
+
+use crate::core::download_task::{DownloadProgress, DownloadStatus};
+
+/// How often the aggregated progress is pushed to the OS
+pub const TASKBAR_TICK: Duration = Duration::from_secs(1);
+
+/// How long the error state stays visible after a download fails
+pub const ERROR_FLASH: Duration = Duration::from_secs(3);
+
+/// What the taskbar / dock / launcher entry should show
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskbarState {
+    Hidden,
+    Indeterminate,
+    Normal(u64),
+    Paused(u64),
+    Error(u64),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    downloaded: u64,
+    total: Option<u64>,
+    speed: f64,
+    paused: bool,
+}
+
+/// Totals across every download the aggregator knows about
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProgressSummary {
+    pub active: usize,
+    pub paused: usize,
+    pub downloaded: u64,
+    pub total: u64,
+    /// Bytes per second across running downloads
+    pub speed: f64,
+    pub failed_recently: bool,
+}
+
+impl ProgressSummary {
+    /// Aggregate percentage over downloads with a known size
+    pub fn percent(&self) -> Option<u64> {
+        if self.total == 0 {
+            return None;
+        }
+        Some((self.downloaded.min(self.total) * 100 / self.total).min(100))
+    }
+
+    pub fn taskbar_state(&self) -> TaskbarState {
+        let percent = self.percent();
+        if self.failed_recently {
+            return TaskbarState::Error(percent.unwrap_or(100));
+        }
+        if self.active == 0 && self.paused == 0 {
+            return TaskbarState::Hidden;
+        }
+        if self.active == 0 {
+            return TaskbarState::Paused(percent.unwrap_or(0));
+        }
+        match percent {
+            Some(p) => TaskbarState::Normal(p),
+            None => TaskbarState::Indeterminate,
+        }
+    }
+}
+
+/// Throttled aggregation of download progress shared by the tray tooltip
+/// and the taskbar / dock integration
+pub struct TaskbarProgress {
+    entries: parking_lot::Mutex<HashMap<Uuid, Entry>>,
+    error_until: parking_lot::Mutex<Option<Instant>>,
+    completed: AtomicUsize,
+    enabled: AtomicBool,
+}
+
+impl Default for TaskbarProgress {
+    fn default() -> Self {
+        Self {
+            entries: parking_lot::Mutex::new(HashMap::new()),
+            error_until: parking_lot::Mutex::new(None),
+            completed: AtomicUsize::new(0),
+            enabled: AtomicBool::new(true),
+        }
+    }
+}
+
+impl TaskbarProgress {
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Start tracking a download that has just been spawned
+    pub fn track(&self, id: Uuid) {
+        self.entries.lock().insert(id, Entry {
+            downloaded: 0,
+            total: None,
+            speed: 0.0,
+            paused: false,
+        });
+    }
+
+    /// Record a progress update from a tracked download. Updates that arrive
+    /// after the download was paused, cancelled or finished are ignored.
+    pub fn record(&self, progress: &DownloadProgress) {
+        if matches!(
+            progress.status,
+            DownloadStatus::Completed | DownloadStatus::Failed | DownloadStatus::Cancelled
+        ) {
+            return;
+        }
+        if let Some(entry) = self.entries.lock().get_mut(&progress.id) {
+            if entry.paused {
+                return;
+            }
+            entry.downloaded = progress.downloaded_size;
+            entry.total = progress.total_size;
+            entry.paused = progress.status == DownloadStatus::Paused;
+            entry.speed = if entry.paused { 0.0 } else { progress.speed };
+        }
+    }
+
+    /// Keep a paused download in the totals so the paused state can be shown
+    pub fn mark_paused(&self, id: Uuid) {
+        if let Some(entry) = self.entries.lock().get_mut(&id) {
+            entry.paused = true;
+            entry.speed = 0.0;
+        }
+    }
+
+    /// A soft paused download picked up where it stopped
+    pub fn mark_resumed(&self, id: Uuid) {
+        if let Some(entry) = self.entries.lock().get_mut(&id) {
+            entry.paused = false;
+        }
+    }
+
+    /// Drop a download that was cancelled or removed
+    pub fn forget(&self, id: Uuid) {
+        self.entries.lock().remove(&id);
+    }
+
+    pub fn complete(&self, id: Uuid) {
+        self.entries.lock().remove(&id);
+        self.completed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn fail(&self, id: Uuid) {
+        self.entries.lock().remove(&id);
+        *self.error_until.lock() = Some(Instant::now() + ERROR_FLASH);
+    }
+
+    pub fn summary(&self) -> ProgressSummary {
+        let mut summary = ProgressSummary {
+            failed_recently: self
+                .error_until
+                .lock()
+                .map(|until| Instant::now() < until)
+                .unwrap_or(false),
+            ..Default::default()
+        };
+        for entry in self.entries.lock().values() {
+            if entry.paused {
+                summary.paused += 1;
+            } else {
+                summary.active += 1;
+                summary.speed += entry.speed;
+            }
+            if let Some(total) = entry.total {
+                summary.downloaded += entry.downloaded;
+                summary.total += total;
+            }
+        }
+        summary
+    }
+
+    /// The running download moving the most data, with its percentage when
+    /// the size is known
+    pub fn fastest(&self) -> Option<(Uuid, Option<f64>)> {
+        self.entries
+            .lock()
+            .iter()
+            .filter(|(_, entry)| !entry.paused)
+            .max_by(|(_, a), (_, b)| a.speed.total_cmp(&b.speed))
+            .map(|(id, entry)| {
+                let percent = entry
+                    .total
+                    .filter(|total| *total > 0)
+                    .map(|total| (entry.downloaded.min(total) as f64 * 100.0 / total as f64));
+                (*id, percent)
+            })
+    }
+
+    /// Number of downloads completed since the last call
+    fn take_completed(&self) -> usize {
+        self.completed.swap(0, Ordering::Relaxed)
+    }
+
+    /// Push the aggregate to the OS once per tick, only when it changed
+    pub fn start(self: Arc<Self>, app: AppHandle) {
+        tauri::async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(TASKBAR_TICK);
+            let mut last_state = (TaskbarState::Hidden, 0);
+            let mut last_counts = (0, 0);
+            let mut completed_total = 0;
+
+            loop {
+                interval.tick().await;
+
+                let summary = self.summary();
+                let completed = self.take_completed();
+                completed_total += completed;
+
+                let counts = (summary.active, completed_total);
+                if counts != last_counts {
+                    let _ = super::tray_service::update_tray_stats(&app, counts.0, counts.1).await;
+                    last_counts = counts;
+                }
+
+                let state = if self.is_enabled() {
+                    summary.taskbar_state()
+                } else {
+                    TaskbarState::Hidden
+                };
+                if (state, summary.active) != last_state {
+                    apply_state(&app, state, summary.active);
+                    last_state = (state, summary.active);
+                }
+
+                if completed > 0 && self.is_enabled() {
+                    notify_completion(&app);
+                }
+            }
+        });
+    }
+
+    /// Remove any progress indicator, used on exit
+    pub fn clear(&self, app: &AppHandle) {
+        apply_state(app, TaskbarState::Hidden, 0);
+    }
+}
+
+/// Tauri maps the progress bar to ITaskbarList3 on Windows, the dock tile
+/// on macOS and the Unity LauncherEntry API on Linux
+fn apply_state(app: &AppHandle, state: TaskbarState, active: usize) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+
+    let (status, progress) = match state {
+        TaskbarState::Hidden => (ProgressBarStatus::None, None),
+        TaskbarState::Indeterminate => (ProgressBarStatus::Indeterminate, None),
+        TaskbarState::Normal(p) => (ProgressBarStatus::Normal, Some(p)),
+        TaskbarState::Paused(p) => (ProgressBarStatus::Paused, Some(p)),
+        TaskbarState::Error(p) => (ProgressBarStatus::Error, Some(p)),
+    };
+    if let Err(e) = window.set_progress_bar(ProgressBarState {
+        status: Some(status),
+        progress,
+    }) {
+        tracing::debug!("Failed to update taskbar progress: {}", e);
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let badge = match state {
+            TaskbarState::Hidden => None,
+            _ if active > 0 => Some(active as i64),
+            _ => None,
+        };
+        if let Err(e) = window.set_badge_count(badge) {
+            tracing::debug!("Failed to update dock badge: {}", e);
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = active;
+}
+
+/// Bounce the dock icon when a download finishes
+fn notify_completion(app: &AppHandle) {
+    #[cfg(target_os = "macos")]
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.request_user_attention(Some(tauri::UserAttentionType::Informational));
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = app;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(id: Uuid, downloaded: u64, total: Option<u64>, status: DownloadStatus) -> DownloadProgress {
+        DownloadProgress {
+            id,
+            downloaded_size: downloaded,
+            total_size: total,
+            speed: downloaded as f64,
+            eta: None,
+            status,
+            percent: 0.0,
+            error_message: None,
+            phase_progress: None,
+            category: None,
+        }
+    }
+
+    #[test]
+    fn test_taskbar_state_transitions() {
+        let taskbar = TaskbarProgress::default();
+        assert_eq!(taskbar.summary().taskbar_state(), TaskbarState::Hidden);
+
+        let a = Uuid::new_v4();
+        let b = Uuid::new_v4();
+        taskbar.track(a);
+        taskbar.track(b);
+        taskbar.record(&progress(a, 25, Some(100), DownloadStatus::Downloading));
+        taskbar.record(&progress(b, 75, Some(100), DownloadStatus::Downloading));
+        assert_eq!(taskbar.summary().taskbar_state(), TaskbarState::Normal(50));
+        assert_eq!(taskbar.summary().speed, 100.0);
+        assert_eq!(taskbar.fastest(), Some((b, Some(75.0))));
+
+        taskbar.mark_paused(a);
+        taskbar.mark_paused(b);
+        assert_eq!(taskbar.summary().taskbar_state(), TaskbarState::Paused(50));
+        assert_eq!(taskbar.summary().speed, 0.0);
+        assert_eq!(taskbar.fastest(), None);
+
+        taskbar.forget(a);
+        taskbar.complete(b);
+        assert_eq!(taskbar.summary().taskbar_state(), TaskbarState::Hidden);
+        assert_eq!(taskbar.take_completed(), 1);
+
+        // Late updates for a finished download are ignored
+        taskbar.record(&progress(b, 80, Some(100), DownloadStatus::Downloading));
+        assert_eq!(taskbar.summary().taskbar_state(), TaskbarState::Hidden);
+
+        let c = Uuid::new_v4();
+        taskbar.track(c);
+        taskbar.record(&progress(c, 10, None, DownloadStatus::Downloading));
+        assert_eq!(taskbar.summary().taskbar_state(), TaskbarState::Indeterminate);
+
+        taskbar.fail(c);
+        assert_eq!(taskbar.summary().taskbar_state(), TaskbarState::Error(100));
+    }
+}
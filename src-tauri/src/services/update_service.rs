@@ -0,0 +1,376 @@
+// src-tauri/src/services/update_service.rs
+// Checks for new releases of the app itself. Nothing is installed unless
+// the UI asks: the installer is fetched through the download engine and
+// only launched on request.
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::path::{Path, PathBuf};
+
+/// GitHub releases listing checked by default
+pub const DEFAULT_RELEASES_URL: &str = "https://api.github.com/repos/DemiAnkit/AFK-Dunld/releases";
+
+/// Version of the running build
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Hours between automatic checks by default
+pub const DEFAULT_CHECK_INTERVAL_HOURS: u32 = 24;
+
+/// Which releases are offered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    /// Stable releases plus pre-releases
+    Beta,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateSettings {
+    pub channel: UpdateChannel,
+    /// 0 disables automatic checks
+    pub check_interval_hours: u32,
+    pub releases_url: String,
+}
+
+impl Default for UpdateSettings {
+    fn default() -> Self {
+        Self {
+            channel: UpdateChannel::Stable,
+            check_interval_hours: DEFAULT_CHECK_INTERVAL_HOURS,
+            releases_url: DEFAULT_RELEASES_URL.to_string(),
+        }
+    }
+}
+
+/// A release as listed by the GitHub releases API
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub published_at: Option<String>,
+    #[serde(default)]
+    pub html_url: Option<String>,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+    #[serde(default)]
+    pub size: u64,
+}
+
+/// Installer for this platform in the latest release
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateAsset {
+    pub name: String,
+    pub url: String,
+    pub size: u64,
+    /// From the release's checksum file; downloads are refused without it
+    pub sha256: Option<String>,
+}
+
+/// Result of an update check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppUpdate {
+    pub current_version: String,
+    pub latest_version: String,
+    pub update_available: bool,
+    pub release_notes: Option<String>,
+    pub release_url: Option<String>,
+    pub published_at: Option<String>,
+    pub asset: Option<UpdateAsset>,
+}
+
+/// Version from a release tag such as "v1.2.0" or "1.3.0-beta.1"
+pub fn parse_version(tag: &str) -> Option<semver::Version> {
+    semver::Version::parse(tag.trim().trim_start_matches('v')).ok()
+}
+
+/// Newest release the channel offers. Drafts and unparseable tags are
+/// skipped; stable ignores pre-releases.
+pub fn select_release(releases: &[Release], channel: UpdateChannel) -> Option<(&Release, semver::Version)> {
+    releases
+        .iter()
+        .filter(|r| !r.draft)
+        .filter_map(|r| parse_version(&r.tag_name).map(|v| (r, v)))
+        .filter(|(r, v)| channel == UpdateChannel::Beta || (!r.prerelease && v.pre.is_empty()))
+        .max_by(|(_, a), (_, b)| a.cmp(b))
+}
+
+/// Installer asset for `os`/`arch` (as in `std::env::consts`), preferring
+/// the first matching installer format for the platform
+pub fn platform_asset<'a>(assets: &'a [ReleaseAsset], os: &str, arch: &str) -> Option<&'a ReleaseAsset> {
+    let extensions: &[&str] = match os {
+        "windows" => &[".msi", "-setup.exe", ".exe"],
+        "macos" => &[".dmg"],
+        "linux" => &[".appimage", ".deb", ".rpm"],
+        _ => &[],
+    };
+    let x86_64 = ["x86_64", "x64", "amd64"];
+    let aarch64 = ["aarch64", "arm64"];
+    let other_arches: &[&str] = match arch {
+        "x86_64" => &aarch64,
+        "aarch64" => &x86_64,
+        _ => &[],
+    };
+
+    extensions.iter().find_map(|ext| {
+        assets.iter().find(|asset| {
+            let name = asset.name.to_ascii_lowercase();
+            name.ends_with(ext) && !other_arches.iter().any(|tag| name.contains(tag))
+        })
+    })
+}
+
+/// SHA-256 for `file_name` from a checksum file: `sha256sum` output, or a
+/// per-asset `.sha256` file holding just the digest
+pub fn parse_checksums(text: &str, file_name: &str) -> Option<String> {
+    let is_digest = |s: &str| s.len() == 64 && s.chars().all(|c| c.is_ascii_hexdigit());
+
+    for line in text.lines() {
+        let mut parts = line.split_whitespace();
+        let Some(digest) = parts.next().filter(|d| is_digest(d)) else {
+            continue;
+        };
+        match parts.next() {
+            Some(name) if name.trim_start_matches('*') == file_name => {
+                return Some(digest.to_ascii_lowercase());
+            }
+            None if text.lines().filter(|l| !l.trim().is_empty()).count() == 1 => {
+                return Some(digest.to_ascii_lowercase());
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Checksum file in the release covering `asset_name`
+fn checksum_asset<'a>(assets: &'a [ReleaseAsset], asset_name: &str) -> Option<&'a ReleaseAsset> {
+    let own = format!("{}.sha256", asset_name);
+    assets.iter().find(|a| a.name == own).or_else(|| {
+        assets.iter().find(|a| {
+            let name = a.name.to_ascii_lowercase();
+            name.starts_with("sha256sums") || name == "checksums.txt"
+        })
+    })
+}
+
+/// Checks the releases endpoint and remembers the latest result
+pub struct UpdateService {
+    settings: parking_lot::RwLock<UpdateSettings>,
+    latest: parking_lot::RwLock<Option<AppUpdate>>,
+    last_check: parking_lot::Mutex<Option<NaiveDateTime>>,
+    /// Managed folder installers are downloaded into
+    updates_dir: PathBuf,
+    client: reqwest::Client,
+}
+
+impl UpdateService {
+    pub fn new(updates_dir: PathBuf) -> Self {
+        let client = reqwest::Client::builder()
+            .user_agent(format!("AFK-Dunld/{}", CURRENT_VERSION))
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .unwrap_or_default();
+        Self {
+            settings: parking_lot::RwLock::new(UpdateSettings::default()),
+            latest: parking_lot::RwLock::new(None),
+            last_check: parking_lot::Mutex::new(None),
+            updates_dir,
+            client,
+        }
+    }
+
+    pub fn settings(&self) -> UpdateSettings {
+        self.settings.read().clone()
+    }
+
+    pub fn set_settings(&self, settings: UpdateSettings) {
+        *self.settings.write() = settings;
+    }
+
+    pub fn updates_dir(&self) -> &Path {
+        &self.updates_dir
+    }
+
+    /// Result of the most recent successful check
+    pub fn latest(&self) -> Option<AppUpdate> {
+        self.latest.read().clone()
+    }
+
+    /// Whether an automatic check is due at `now`; marks it as started
+    pub fn is_check_due(&self, now: NaiveDateTime) -> bool {
+        let hours = self.settings.read().check_interval_hours;
+        if hours == 0 {
+            return false;
+        }
+        let mut last_check = self.last_check.lock();
+        let due = last_check.is_none_or(|last| now - last >= chrono::Duration::hours(hours as i64));
+        if due {
+            *last_check = Some(now);
+        }
+        due
+    }
+
+    /// Query the releases endpoint and compare against this build
+    pub async fn check(&self) -> Result<AppUpdate, String> {
+        let settings = self.settings();
+        let releases: Vec<Release> = self.client
+            .get(&settings.releases_url)
+            .header("Accept", "application/vnd.github+json")
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("Failed to fetch releases: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Invalid releases response: {}", e))?;
+
+        let current = parse_version(CURRENT_VERSION)
+            .ok_or_else(|| format!("Invalid build version {}", CURRENT_VERSION))?;
+
+        let update = match select_release(&releases, settings.channel) {
+            Some((release, version)) => {
+                let update_available = version > current;
+                let asset = match platform_asset(&release.assets, std::env::consts::OS, std::env::consts::ARCH) {
+                    Some(asset) if update_available => Some(self.describe_asset(release, asset).await),
+                    _ => None,
+                };
+                AppUpdate {
+                    current_version: CURRENT_VERSION.to_string(),
+                    latest_version: version.to_string(),
+                    update_available,
+                    release_notes: release.body.clone(),
+                    release_url: release.html_url.clone(),
+                    published_at: release.published_at.clone(),
+                    asset,
+                }
+            }
+            None => AppUpdate {
+                current_version: CURRENT_VERSION.to_string(),
+                latest_version: CURRENT_VERSION.to_string(),
+                update_available: false,
+                release_notes: None,
+                release_url: None,
+                published_at: None,
+                asset: None,
+            },
+        };
+
+        *self.latest.write() = Some(update.clone());
+        Ok(update)
+    }
+
+    async fn describe_asset(&self, release: &Release, asset: &ReleaseAsset) -> UpdateAsset {
+        let sha256 = match checksum_asset(&release.assets, &asset.name) {
+            Some(sums) => match self.fetch_text(&sums.browser_download_url).await {
+                Ok(text) => parse_checksums(&text, &asset.name),
+                Err(e) => {
+                    tracing::warn!("Failed to fetch release checksums: {}", e);
+                    None
+                }
+            },
+            None => None,
+        };
+        UpdateAsset {
+            name: asset.name.clone(),
+            url: asset.browser_download_url.clone(),
+            size: asset.size,
+            sha256,
+        }
+    }
+
+    async fn fetch_text(&self, url: &str) -> Result<String, reqwest::Error> {
+        self.client.get(url).send().await?.error_for_status()?.text().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(tag: &str, prerelease: bool, draft: bool) -> Release {
+        Release {
+            tag_name: tag.to_string(),
+            body: None,
+            prerelease,
+            draft,
+            published_at: None,
+            html_url: None,
+            assets: vec![],
+        }
+    }
+
+    fn asset(name: &str) -> ReleaseAsset {
+        ReleaseAsset {
+            name: name.to_string(),
+            browser_download_url: format!("https://example.com/{}", name),
+            size: 0,
+        }
+    }
+
+    #[test]
+    fn test_channel_selects_release() {
+        let releases = vec![
+            release("v1.2.0", false, false),
+            release("v1.3.0-beta.1", true, false),
+            release("v2.0.0", false, true),
+            release("nightly", true, false),
+        ];
+
+        let (stable, version) = select_release(&releases, UpdateChannel::Stable).unwrap();
+        assert_eq!(stable.tag_name, "v1.2.0");
+        assert!(version > parse_version("1.2.0-rc.1").unwrap());
+
+        let (beta, _) = select_release(&releases, UpdateChannel::Beta).unwrap();
+        assert_eq!(beta.tag_name, "v1.3.0-beta.1");
+    }
+
+    #[test]
+    fn test_platform_asset_matches_os_and_arch() {
+        let assets = vec![
+            asset("AFK-Dunld_1.2.0_x64_en-US.msi.sig"),
+            asset("AFK-Dunld_1.2.0_arm64_en-US.msi"),
+            asset("AFK-Dunld_1.2.0_x64_en-US.msi"),
+            asset("AFK-Dunld_1.2.0_x64-setup.exe"),
+            asset("AFK-Dunld_1.2.0_aarch64.dmg"),
+            asset("afk-dunld_1.2.0_amd64.AppImage"),
+            asset("afk-dunld_1.2.0_amd64.deb"),
+        ];
+
+        let name = |os, arch| platform_asset(&assets, os, arch).map(|a| a.name.as_str());
+        assert_eq!(name("windows", "x86_64"), Some("AFK-Dunld_1.2.0_x64_en-US.msi"));
+        assert_eq!(name("windows", "aarch64"), Some("AFK-Dunld_1.2.0_arm64_en-US.msi"));
+        assert_eq!(name("macos", "aarch64"), Some("AFK-Dunld_1.2.0_aarch64.dmg"));
+        assert_eq!(name("macos", "x86_64"), None);
+        assert_eq!(name("linux", "x86_64"), Some("afk-dunld_1.2.0_amd64.AppImage"));
+    }
+
+    #[test]
+    fn test_parse_checksums() {
+        let digest = "ab".repeat(32);
+        let other = "cd".repeat(32);
+        let sums = format!("{}  app.msi\n{} *app.dmg\n", digest, other);
+        assert_eq!(parse_checksums(&sums, "app.msi"), Some(digest.clone()));
+        assert_eq!(parse_checksums(&sums, "app.dmg"), Some(other));
+        assert_eq!(parse_checksums(&sums, "app.deb"), None);
+
+        // Per-asset file with only the digest
+        assert_eq!(parse_checksums(&format!("{}\n", digest.to_uppercase()), "app.msi"), Some(digest));
+    }
+}
@@ -0,0 +1,340 @@
+// src-tauri/src/services/watchdog_service.rs
+// Finds downloads that died without saying so. A task that panics never
+// reaches the code that reports its end and takes it off the running list,
+// and one that deadlocks never gets there either; both would otherwise
+// show "Downloading" at zero speed until the app restarts. Every so often
+// the running list is checked for tasks that ended and for tasks whose
+// progress hasn't moved in a long time, and those are failed so they can
+// be resumed.
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::core::download_engine::DownloadEngine;
+use crate::core::download_task::{DownloadStatus, DownloadTask};
+use crate::database::db::Database;
+use crate::state::app_state::{ActiveDownload, AppState};
+use crate::utils::error::DownloadError;
+
+/// How often running downloads are checked
+pub const WATCHDOG_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long a running download may go without progress. Longer than any
+/// retry backoff, so a download waiting to retry isn't taken for dead.
+pub const STALL_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Why the watchdog stepped in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchdogReason {
+    /// The task ended, by panicking, without reporting how
+    TaskEnded,
+    /// No progress for longer than the stall timeout
+    Stalled,
+}
+
+impl std::fmt::Display for WatchdogReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WatchdogReason::TaskEnded => write!(f, "the download task ended without reporting"),
+            WatchdogReason::Stalled => write!(f, "no progress for {} minutes", STALL_TIMEOUT.as_secs() / 60),
+        }
+    }
+}
+
+/// Sent with `download-watchdog-recovered`
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchdogRecovery {
+    pub id: Uuid,
+    pub file_name: String,
+    pub reason: WatchdogReason,
+    /// Failed so it can be resumed; false when it had already finished
+    /// and was only taken off the running list
+    pub failed: bool,
+}
+
+/// Interventions since the app started, for the performance report
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchdogCounters {
+    pub ended_tasks: u64,
+    pub stalled: u64,
+}
+
+/// A download taken off the running list
+pub struct DeadDownload {
+    pub id: Uuid,
+    pub handle: ActiveDownload,
+    pub reason: WatchdogReason,
+    /// Bytes it last reported
+    pub last_downloaded: Option<u64>,
+}
+
+pub struct Watchdog {
+    stall_timeout: Duration,
+    /// Bytes and merge/verify fraction last reported per running download,
+    /// and when either changed
+    progress: parking_lot::Mutex<HashMap<Uuid, (u64, Option<f64>, Instant)>>,
+    ended_tasks: AtomicU64,
+    stalled: AtomicU64,
+}
+
+impl Default for Watchdog {
+    fn default() -> Self {
+        Self::new(STALL_TIMEOUT)
+    }
+}
+
+impl Watchdog {
+    pub fn new(stall_timeout: Duration) -> Self {
+        Self {
+            stall_timeout,
+            progress: parking_lot::Mutex::new(HashMap::new()),
+            ended_tasks: AtomicU64::new(0),
+            stalled: AtomicU64::new(0),
+        }
+    }
+
+    /// A running download reported `downloaded` bytes. Merging and
+    /// verifying don't move the byte count, so their `phase_progress`
+    /// counts as progress too.
+    pub fn record_progress(&self, id: Uuid, downloaded: u64, phase_progress: Option<f64>) {
+        let mut progress = self.progress.lock();
+        match progress.get_mut(&id) {
+            Some((last, phase, _)) if *last == downloaded && *phase == phase_progress => {}
+            _ => {
+                progress.insert(id, (downloaded, phase_progress, Instant::now()));
+            }
+        }
+    }
+
+    pub fn counters(&self) -> WatchdogCounters {
+        WatchdogCounters {
+            ended_tasks: self.ended_tasks.load(Ordering::Relaxed),
+            stalled: self.stalled.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Take the downloads that ended or stalled as of `now` off the
+    /// running list. Paused downloads never count as stalled.
+    pub async fn collect(&self, active: &RwLock<HashMap<Uuid, ActiveDownload>>, now: Instant) -> Vec<DeadDownload> {
+        let mut active = active.write().await;
+        let mut dead = Vec::new();
+
+        let ids: Vec<Uuid> = active.keys().copied().collect();
+        for id in ids {
+            let entry = &active[&id];
+            let reason = if entry.task_handle.is_finished() {
+                Some(WatchdogReason::TaskEnded)
+            } else if entry.task.read().await.status == DownloadStatus::Paused {
+                None
+            } else {
+                // A download that never reported is timed from when it was
+                // first seen
+                let (_, _, since) = *self.progress.lock().entry(id).or_insert((0, None, now));
+                (now.saturating_duration_since(since) > self.stall_timeout).then_some(WatchdogReason::Stalled)
+            };
+            let Some(reason) = reason else {
+                continue;
+            };
+
+            match reason {
+                WatchdogReason::TaskEnded => self.ended_tasks.fetch_add(1, Ordering::Relaxed),
+                WatchdogReason::Stalled => self.stalled.fetch_add(1, Ordering::Relaxed),
+            };
+            let handle = active.remove(&id).expect("listed above");
+            dead.push(DeadDownload {
+                id,
+                handle,
+                reason,
+                last_downloaded: self.progress.lock().remove(&id).map(|(bytes, _, _)| bytes).filter(|bytes| *bytes > 0),
+            });
+        }
+
+        // Only what is still running is tracked
+        self.progress.lock().retain(|id, _| active.contains_key(id));
+        dead
+    }
+}
+
+/// Fail a download taken off the running list, keeping what its segment
+/// files hold, and save it. `None` when it had already completed, failed,
+/// been cancelled or paused, and is left as it was.
+pub async fn fail_dead_download(
+    engine: &DownloadEngine,
+    db: &Database,
+    task: &mut DownloadTask,
+    reason: WatchdogReason,
+    last_downloaded: Option<u64>,
+) -> Option<DownloadError> {
+    if matches!(
+        task.status,
+        DownloadStatus::Completed
+            | DownloadStatus::CompletedPendingReplace
+            | DownloadStatus::Failed
+            | DownloadStatus::Cancelled
+            | DownloadStatus::Paused
+    ) {
+        return None;
+    }
+
+    if !engine.recover_interrupted(task).await {
+        if let Some(downloaded) = last_downloaded {
+            task.downloaded_size = task.downloaded_size.max(downloaded);
+        }
+    }
+    task.status = DownloadStatus::Failed;
+    task.speed = 0.0;
+    task.eta = None;
+    let error = DownloadError::WatchdogRecovered(reason.to_string());
+    task.error_message = Some(error.to_string());
+    if let Err(e) = db.update_download(task).await {
+        tracing::error!("Failed to save recovered download {}: {}", task.id, e);
+    }
+    Some(error)
+}
+
+pub async fn watch(app: AppHandle) {
+    let mut ticker = tokio::time::interval(WATCHDOG_INTERVAL);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        ticker.tick().await;
+        let state = app.state::<AppState>();
+        let dead = state.watchdog.collect(&state.active_downloads, Instant::now()).await;
+        for download in dead {
+            tracing::warn!("Watchdog recovering download {}: {}", download.id, download.reason);
+            crate::commands::download_commands::recover_dead_download(&app, &state, download).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::app_state::{spawn_active, IfActive};
+
+    fn task() -> DownloadTask {
+        let mut task = DownloadTask::new(
+            "https://example.com/file.bin".to_string(),
+            "file.bin".to_string(),
+            std::env::temp_dir().join("file.bin"),
+            1,
+        );
+        task.status = DownloadStatus::Downloading;
+        task
+    }
+
+    #[tokio::test]
+    async fn test_panicked_and_stalled_downloads_are_collected() {
+        let watchdog = Watchdog::new(Duration::from_secs(60));
+        let active = RwLock::new(HashMap::new());
+
+        // An engine run that panics before reporting anything
+        let panicked = task();
+        spawn_active(&active, panicked.clone(), IfActive::Fail, |_| {
+            tokio::spawn(async { panic!("engine blew up") })
+        })
+        .await
+        .unwrap();
+        // One that makes progress, one that stops, one paused
+        let (healthy, stalled, mut paused) = (task(), task(), task());
+        paused.status = DownloadStatus::Paused;
+        for task in [&healthy, &stalled, &paused] {
+            spawn_active(&active, task.clone(), IfActive::Fail, |cancel| {
+                tokio::spawn(async move { cancel.cancelled().await })
+            })
+            .await
+            .unwrap();
+        }
+        while !active.read().await[&panicked.id].task_handle.is_finished() {
+            tokio::task::yield_now().await;
+        }
+
+        let start = Instant::now();
+        watchdog.record_progress(stalled.id, 4096, None);
+        watchdog.record_progress(healthy.id, 100, None);
+        let first = watchdog.collect(&active, start).await;
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].id, panicked.id);
+        assert_eq!(first[0].reason, WatchdogReason::TaskEnded);
+        assert_eq!(active.read().await.len(), 3);
+
+        // Progress that repeats the same count doesn't reset the clock
+        watchdog.record_progress(stalled.id, 4096, None);
+        std::thread::sleep(Duration::from_millis(5));
+        watchdog.record_progress(healthy.id, 200, None);
+        let later = Instant::now() + Duration::from_secs(60) - Duration::from_millis(1);
+        let second = watchdog.collect(&active, later).await;
+        assert_eq!(second.iter().map(|d| d.id).collect::<Vec<_>>(), vec![stalled.id]);
+        assert_eq!(second[0].reason, WatchdogReason::Stalled);
+        assert_eq!(second[0].last_downloaded, Some(4096));
+
+        let remaining = active.read().await;
+        assert!(remaining.contains_key(&healthy.id) && remaining.contains_key(&paused.id));
+        assert_eq!(watchdog.counters(), WatchdogCounters { ended_tasks: 1, stalled: 1 });
+    }
+
+    #[tokio::test]
+    async fn test_merging_progress_keeps_a_download_alive() {
+        let watchdog = Watchdog::new(Duration::from_secs(60));
+        let active = RwLock::new(HashMap::new());
+        let mut merging = task();
+        merging.status = DownloadStatus::Merging;
+        spawn_active(&active, merging.clone(), IfActive::Fail, |cancel| {
+            tokio::spawn(async move { cancel.cancelled().await })
+        })
+        .await
+        .unwrap();
+
+        // All bytes are in; only the merge fraction moves
+        watchdog.record_progress(merging.id, 8192, Some(0.1));
+        std::thread::sleep(Duration::from_millis(5));
+        watchdog.record_progress(merging.id, 8192, Some(0.2));
+        let later = Instant::now() + Duration::from_secs(60) - Duration::from_millis(1);
+        assert!(watchdog.collect(&active, later).await.is_empty());
+
+        // A merge that stops moving is still caught
+        let dead = watchdog.collect(&active, later + Duration::from_secs(1)).await;
+        assert_eq!(dead.iter().map(|d| d.id).collect::<Vec<_>>(), vec![merging.id]);
+        assert_eq!(dead[0].reason, WatchdogReason::Stalled);
+    }
+
+    #[tokio::test]
+    async fn test_dead_download_is_saved_as_failed() {
+        let dir = std::env::temp_dir().join(format!("afk-dunld-watchdog-{}", Uuid::new_v4()));
+        let db = Database::new(&dir).await.unwrap();
+        db.run_migrations().await.unwrap();
+        let engine = DownloadEngine::new(None, None, Some(dir.clone())).unwrap();
+
+        let mut stalled = task();
+        stalled.save_path = dir.join("stalled.bin");
+        db.insert_download(&stalled).await.unwrap();
+        let error = fail_dead_download(&engine, &db, &mut stalled, WatchdogReason::Stalled, Some(4096)).await;
+        assert!(matches!(error, Some(DownloadError::WatchdogRecovered(_))));
+
+        let saved = db.get_download(stalled.id).await.unwrap().unwrap();
+        assert_eq!(saved.status, DownloadStatus::Failed);
+        assert_eq!(saved.downloaded_size, 4096);
+        assert!(saved.error_message.unwrap().starts_with("WATCHDOG_RECOVERED"));
+
+        // A download that finished before it was collected is left alone
+        let mut finished = task();
+        finished.status = DownloadStatus::Completed;
+        db.insert_download(&finished).await.unwrap();
+        assert!(fail_dead_download(&engine, &db, &mut finished, WatchdogReason::TaskEnded, None).await.is_none());
+        let saved = db.get_download(finished.id).await.unwrap().unwrap();
+        assert_eq!(saved.status, DownloadStatus::Completed);
+        assert!(saved.error_message.is_none());
+
+        db.pool().close().await;
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+}
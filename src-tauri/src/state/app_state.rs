@@ -4,16 +4,45 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+use crate::core::bandwidth::BandwidthTracker;
+use crate::core::category::MissingCategoryFolder;
 use crate::core::download_engine::DownloadEngine;
-use crate::core::download_task::DownloadTask;
+use crate::core::metrics::MetricsRegistry;
+use crate::services::hook_service::HookRunner;
+use crate::core::download_task::{DownloadPriority, DownloadStatus, DownloadTask};
+use crate::core::playlist::YTDLP_ARCHIVE_FILE;
 use crate::core::queue_manager::QueueManager;
+use crate::core::resource_guard::ResourceGuard;
 use crate::core::scheduler::{Scheduler, ScheduledTask};
+use crate::core::soft_pause::SoftPauses;
+use crate::core::undo::{RemoteRestart, UndoStack};
 use crate::database::db::Database;
+use crate::database::writer_lock::WriterLock;
+use crate::network::remote_progress::TransferControl;
+use crate::network::speed_test::SpeedTester;
 use crate::network::torrent_client_librqbit::{LibrqbitTorrentClient, TorrentConfig};
-use crate::utils::logging::Logger;
+use crate::services::conversion_service::ConversionWorker;
+use crate::services::cron_service::CronService;
+use crate::services::notification_service::SoundPlayer;
+use crate::services::power_service::SleepGuard;
+use crate::services::recent_files_service::RecentFiles;
+use crate::services::taskbar_service::TaskbarProgress;
+use crate::services::update_service::UpdateService;
+use crate::services::watchdog_service::Watchdog;
+use crate::utils::binaries::{BinaryResolver, Tool};
+use crate::utils::dir_size::DirSizeCache;
+use crate::utils::logging::{Logger, LOG_RETENTION_KEY};
+use crate::utils::error::DownloadError;
+use crate::utils::file_utils::{self, FolderKind};
 use crate::utils::security::{CredentialVault, RateLimiter};
+use crate::utils::privacy;
 use crate::utils::ytdlp_manager::YtdlpManager;
 use std::time::Duration;
+use chrono::TimeZone;
+use tauri::{Emitter, Manager};
+
+/// Set once the built-in categories were given their standard folders
+const CATEGORY_FOLDERS_SEEDED_KEY: &str = "category_folders_seeded";
 
 /// Handle for an active download (used for cancellation)
 pub struct ActiveDownload {
@@ -25,6 +54,65 @@ pub struct ActiveDownload {
 /// Download handle type alias for backwards compatibility
 pub type DownloadHandle = ActiveDownload;
 
+/// What starting a download that is already running does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IfActive {
+    /// Fail with `AlreadyActive`
+    Fail,
+    /// Leave the running download alone
+    Skip,
+}
+
+/// Start `task` with `spawn` and record it as running, unless it already
+/// is. The map stays locked from the check until the handle is stored, so
+/// two starts of the same download can't both get through. Returns
+/// whether it was started.
+pub async fn spawn_active<F>(
+    active: &RwLock<HashMap<Uuid, ActiveDownload>>,
+    task: DownloadTask,
+    if_active: IfActive,
+    spawn: F,
+) -> Result<bool, DownloadError>
+where
+    F: FnOnce(tokio_util::sync::CancellationToken) -> tokio::task::JoinHandle<()>,
+{
+    let mut active = active.write().await;
+    if active.contains_key(&task.id) {
+        return match if_active {
+            IfActive::Fail => Err(DownloadError::AlreadyActive(format!("download {} is already running", task.id))),
+            IfActive::Skip => Ok(false),
+        };
+    }
+
+    let cancel_token = tokio_util::sync::CancellationToken::new();
+    let task_handle = spawn(cancel_token.clone());
+    active.insert(task.id, ActiveDownload {
+        cancel_token,
+        task_handle,
+        task: Arc::new(RwLock::new(task)),
+    });
+    Ok(true)
+}
+
+/// Fail the playlist rows a restart cut off. Nothing runs them after the
+/// process that did is gone, and their entries would otherwise sit Queued
+/// forever; each can be retried on its own.
+async fn fail_interrupted_playlists(db: &Database, downloads: &[DownloadTask]) {
+    for task in downloads {
+        if task.playlist_id.is_none()
+            || !(task.status == DownloadStatus::Queued || task.status.is_active())
+        {
+            continue;
+        }
+        let mut task = task.clone();
+        task.status = DownloadStatus::Failed;
+        task.error_message = Some("The playlist was interrupted when the app closed".to_string());
+        if let Err(e) = db.update_download(&task).await {
+            tracing::warn!("Failed to fail interrupted playlist download {}: {}", task.id, e);
+        }
+    }
+}
+
 /// Global application state managed by Tauri
 #[derive(Clone)]
 pub struct AppState {
@@ -40,7 +128,46 @@ pub struct AppState {
     pub logger: Arc<Logger>,
     pub credential_vault: Arc<CredentialVault>,
     pub rate_limiter: Arc<RateLimiter>,
+    pub binaries: Arc<BinaryResolver>,
     pub ytdlp_manager: Arc<YtdlpManager>,
+    pub dir_sizes: Arc<DirSizeCache>,
+    pub cron: Arc<CronService>,
+    pub taskbar: Arc<TaskbarProgress>,
+    pub speed_test: Arc<SpeedTester>,
+    pub updates: Arc<UpdateService>,
+    pub resource_guard: Arc<ResourceGuard>,
+    pub sounds: Arc<SoundPlayer>,
+    pub conversions: Arc<ConversionWorker>,
+    pub sleep_guard: Arc<SleepGuard>,
+    /// Counters scraped from the metrics endpoint
+    pub metrics: Arc<MetricsRegistry>,
+    /// Scripts run when downloads complete or fail
+    pub hooks: Arc<HookRunner>,
+    /// Live bandwidth by category and domain
+    pub bandwidth: Arc<BandwidthTracker>,
+    /// Destructive actions of this session that can be undone
+    pub undo: Arc<UndoStack>,
+    /// Finds running downloads whose task died or stalled
+    pub watchdog: Arc<Watchdog>,
+    /// Downloads paused with their connections kept open
+    pub soft_pauses: Arc<SoftPauses>,
+    /// Finished downloads listed in the OS's recent files
+    pub recent_files: Arc<RecentFiles>,
+    pub writer_lock: Arc<WriterLock>,
+    /// FTP and SFTP transfers and playlist entries, which run outside the
+    /// engine
+    pub remote_transfers: Arc<parking_lot::Mutex<HashMap<Uuid, RemoteTransfer>>>,
+    /// yt-dlp's record of finished playlist entries, shared by both
+    /// playlist modes so neither downloads what the other already has
+    pub ytdlp_archive: PathBuf,
+}
+
+/// A transfer running outside the engine
+#[derive(Clone)]
+pub struct RemoteTransfer {
+    pub cancel: tokio_util::sync::CancellationToken,
+    /// How to start it again when its cancel is undone; None for playlists
+    pub restart: Option<RemoteRestart>,
 }
 
 impl AppState {
@@ -48,6 +175,33 @@ impl AppState {
         app_data_dir: PathBuf,
         app_handle: &tauri::AppHandle,
     ) -> Result<Self, crate::utils::error::DownloadError> {
+        let state = Self::load(
+            app_data_dir,
+            app_handle.path().resource_dir().ok(),
+            RecentFiles::for_platform(app_handle),
+        )
+        .await?;
+        for missing in state.refresh_category_dirs().await {
+            let _ = app_handle.emit("category-folder-missing", &missing);
+        }
+        if let Some(pid) = state.writer_lock.took_over_from() {
+            let _ = app_handle.emit("database-lock-recovered", serde_json::json!({ "pid": pid }));
+        }
+        Ok(state)
+    }
+
+    /// Open everything under `app_data_dir` the way a launch does, without
+    /// a window to report to. Tests use it to simulate a restart.
+    pub async fn load(
+        app_data_dir: PathBuf,
+        resource_dir: Option<PathBuf>,
+        recent_files: RecentFiles,
+    ) -> Result<Self, crate::utils::error::DownloadError> {
+        // Only this process writes to the database from here on
+        std::fs::create_dir_all(&app_data_dir)
+            .map_err(|e| DownloadError::FileError(format!("Cannot create data dir: {}", e)))?;
+        let writer_lock = Arc::new(WriterLock::acquire(&app_data_dir)?);
+
         // Initialize database
         let db = Database::new(&app_data_dir).await?;
         db.run_migrations().await?;
@@ -66,8 +220,58 @@ impl AppState {
             Some(download_dir.clone()),
         )?);
 
+        let auto_categorize = db
+            .get_setting("auto_categorize_on_add")
+            .await
+            .ok()
+            .flatten()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(true);
+        engine.set_auto_categorize(auto_categorize);
+
+        let taskbar = Arc::new(TaskbarProgress::default());
+        let show_taskbar_progress = db
+            .get_setting("show_taskbar_progress")
+            .await
+            .ok()
+            .flatten()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(true);
+        taskbar.set_enabled(show_taskbar_progress);
+
+        // Rebuild the queue in the order it had before shutdown
+        let mut queue_manager = QueueManager::new(5);
+        match db.load_queue().await {
+            Ok(saved) => queue_manager.restore(saved),
+            Err(e) => tracing::warn!("Failed to restore download queue: {}", e),
+        }
+        let recent_files = Arc::new(recent_files);
+        // The database is the source of truth for statuses, so the stats
+        // and the queue report the same counts after a restart
+        match db.get_all_downloads().await {
+            Ok(downloads) => {
+                recent_files.seed(&downloads);
+                fail_interrupted_playlists(&db, &downloads).await;
+                // Playlist rows are run by their playlist, not the queue
+                let statuses: Vec<_> = downloads
+                    .iter()
+                    .rev()
+                    .filter(|d| d.playlist_id.is_none())
+                    .map(|d| (d.id, d.status))
+                    .collect();
+                for id in queue_manager.reconcile(&statuses) {
+                    if let Err(e) = db.update_status(id, DownloadStatus::Queued).await {
+                        tracing::warn!("Failed to requeue interrupted download {}: {}", id, e);
+                    }
+                }
+                if let Err(e) = db.save_queue(&queue_manager.get_active(), &queue_manager.get_queue()).await {
+                    tracing::warn!("Failed to save reconciled queue: {}", e);
+                }
+            }
+            Err(e) => tracing::warn!("Failed to reconcile download queue: {}", e),
+        }
         let queue =
-            Arc::new(RwLock::new(QueueManager::new(5)));
+            Arc::new(RwLock::new(queue_manager));
 
         // Initialize scheduler
         let (scheduler, receiver) = Scheduler::new();
@@ -92,32 +296,108 @@ impl AppState {
             LibrqbitTorrentClient::new_disabled()
         );
 
+        // Restore persisted torrents (including ones added paused)
+        match crate::database::torrent_queries::load_torrent_handles(db.pool()).await {
+            Ok(handles) => {
+                for (handle, metadata) in handles {
+                    torrent_client.restore(handle, metadata).await;
+                }
+            }
+            Err(e) => tracing::warn!("Failed to restore torrents: {}", e),
+        }
+
         // Initialize logger
         let logger = Arc::new(Logger::new());
+        if let Ok(Some(json)) = db.get_setting(LOG_RETENTION_KEY).await {
+            match serde_json::from_str(&json) {
+                Ok(retention) => logger.set_retention(retention),
+                Err(e) => tracing::warn!("Ignoring invalid log retention setting: {}", e),
+            }
+        }
+        engine.set_logger(logger.clone());
+
+        let updates = Arc::new(UpdateService::new(app_data_dir.join("updates")));
+        engine.add_app_save_root(updates.updates_dir().to_path_buf());
+        let resource_guard = Arc::new(ResourceGuard::default());
+        let sounds = Arc::new(SoundPlayer::default());
+        let conversions = Arc::new(ConversionWorker::default());
+        let metrics = Arc::new(MetricsRegistry::default());
+        let hooks = Arc::new(HookRunner::default());
+        let undo = Arc::new(UndoStack::default());
+        let soft_pauses = Arc::new(SoftPauses::default());
+
+        match db.get_all_settings().await {
+            Ok(map) => {
+                let settings = crate::commands::settings_commands::map_to_settings(&map);
+                engine.set_retry_defaults(settings.retry_config());
+                engine.speed_limiter.set_weights(settings.priority_weights);
+                engine.set_file_permissions(settings.file_permissions());
+                updates.set_settings(settings.update_settings());
+                resource_guard.set_policy(settings.condition_policy());
+                engine.set_size_limits(settings.size_limits());
+                engine.set_contention_policy(settings.contention_policy());
+                engine.set_probe_timeout(settings.probe_timeout());
+                engine.set_file_info_ttl(settings.file_info_ttl());
+                soft_pauses.configure(settings.soft_pause, settings.soft_pause_limit());
+                resource_guard.set_data_cap_policy(settings.data_cap_policy());
+                engine.resolvers.set_enabled(settings.resolve_urls);
+                engine.resolvers.set_max_hops(settings.max_resolve_hops as usize);
+                engine.set_batch_concurrency(settings.batch_concurrency as usize);
+                engine.set_playlist_concurrency(settings.playlist_concurrency as usize);
+                engine.set_waste_warning_fraction(settings.wasted_bytes_warning_fraction);
+                engine.set_refuse_without_space(settings.refuse_without_space);
+                engine.set_tracking_params(settings.tracking_params.clone());
+                recent_files.configure(settings.add_to_recent_files, &settings.recent_files_excluded_extensions);
+                engine.set_approved_save_roots(settings.approved_save_roots());
+                if let Some(dir) = settings.torrent_download_dir() {
+                    torrent_client.set_download_dir(dir);
+                }
+                sounds.set_settings(settings.sound_settings());
+                hooks.set_scripts(settings.hook_scripts());
+                undo.set_settings(settings.undo_settings());
+                conversions.set_profiles(settings.conversion_profiles);
+                engine.url_refresher.set_privacy(settings.privacy_level);
+                metrics.set_privacy(settings.privacy_level);
+                privacy::set_logging_privacy(settings.privacy_level);
+            }
+            Err(e) => tracing::warn!("Failed to load download settings: {}", e),
+        }
 
-        // Initialize credential vault with a master password
-        // In production, this should be stored securely or derived from user input
+        // Saved remote passwords, encrypted with a key kept beside them
         let credential_vault = Arc::new(
-            CredentialVault::new("default_master_password")
+            CredentialVault::open(&app_data_dir)
                 .map_err(|e| crate::utils::error::DownloadError::Unknown(e))?
         );
 
         // Initialize rate limiter (10 requests per 60 seconds per key)
         let rate_limiter = Arc::new(RateLimiter::new(10, Duration::from_secs(60)));
 
+        // Resolve external tools (yt-dlp, ffmpeg, ...), honouring configured paths
+        let binaries = Arc::new(BinaryResolver::for_app(
+            &app_data_dir,
+            resource_dir,
+        ));
+        for tool in Tool::ALL {
+            if let Ok(Some(path)) = db.get_setting(tool.setting_key()).await {
+                if !path.is_empty() {
+                    binaries.set_override(tool, Some(PathBuf::from(path)));
+                }
+            }
+        }
+
         // Initialize yt-dlp manager
-        let ytdlp_manager = YtdlpManager::new(app_handle)
+        let ytdlp_manager = YtdlpManager::new(binaries.clone())
             .map_err(|e| crate::utils::error::DownloadError::Unknown(e.to_string()))?;
         
         // Initialize yt-dlp (extract bundled binary) - non-fatal, YouTube downloads will be unavailable if this fails
-        if let Err(e) = ytdlp_manager.initialize(app_handle).await {
+        if let Err(e) = ytdlp_manager.initialize().await {
             tracing::warn!("yt-dlp initialization failed (YouTube downloads will be unavailable): {}", e);
             // Continue without yt-dlp instead of crashing the entire app
         }
         
         let ytdlp_manager = Arc::new(ytdlp_manager);
 
-        Ok(Self {
+        let state = Self {
             db,
             engine,
             queue,
@@ -131,7 +411,223 @@ impl AppState {
             logger,
             credential_vault,
             rate_limiter,
+            binaries,
             ytdlp_manager,
-        })
+            dir_sizes: Arc::new(DirSizeCache::default()),
+            cron: Arc::new(CronService::default()),
+            taskbar,
+            speed_test: Arc::new(SpeedTester::default()),
+            updates,
+            resource_guard,
+            sounds,
+            conversions,
+            sleep_guard: Arc::new(SleepGuard::default()),
+            metrics,
+            hooks,
+            bandwidth: Arc::new(BandwidthTracker::default()),
+            undo,
+            watchdog: Arc::new(Watchdog::default()),
+            soft_pauses,
+            recent_files,
+            writer_lock,
+            remote_transfers: Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            ytdlp_archive: app_data_dir.join(YTDLP_ARCHIVE_FILE),
+        };
+
+        state.seed_category_folders().await;
+        state.restore_night_schedule().await;
+
+        Ok(state)
+    }
+
+    /// Register an FTP or SFTP transfer so `cancel_download` can stop it,
+    /// and undo can start it again with `restart`. It shares the global
+    /// speed limit with other downloads.
+    pub fn begin_remote_transfer(&self, id: Uuid, restart: RemoteRestart) -> TransferControl {
+        let control = TransferControl {
+            cancel: tokio_util::sync::CancellationToken::new(),
+            limiter: Some(self.engine.speed_limiter.for_download(id, DownloadPriority::Normal)),
+        };
+        let transfer = RemoteTransfer { cancel: control.cancel.clone(), restart: Some(restart) };
+        self.remote_transfers.lock().insert(id, transfer);
+        control
+    }
+
+    /// Let `cancel_download` stop a transfer that cancels itself through
+    /// `cancel`, like a playlist entry
+    pub fn track_remote_transfer(&self, id: Uuid, cancel: tokio_util::sync::CancellationToken) {
+        self.remote_transfers.lock().insert(id, RemoteTransfer { cancel, restart: None });
+    }
+
+    pub fn end_remote_transfer(&self, id: Uuid) {
+        self.remote_transfers.lock().remove(&id);
+    }
+
+    /// Stop a transfer registered above; None if none is running as `id`
+    pub fn cancel_remote_transfer(&self, id: Uuid) -> Option<RemoteTransfer> {
+        let transfer = self.remote_transfers.lock().remove(&id)?;
+        transfer.cancel.cancel();
+        Some(transfer)
+    }
+
+    /// Put downloads waiting for the night back on the scheduler, which
+    /// doesn't persist its entries. Times missed while the app was closed
+    /// fire as soon as it starts.
+    async fn restore_night_schedule(&self) {
+        let downloads = match self.db.get_all_downloads().await {
+            Ok(downloads) => downloads,
+            Err(e) => {
+                tracing::warn!("Failed to load scheduled downloads: {}", e);
+                return;
+            }
+        };
+        for task in downloads.into_iter().filter(|t| t.status == DownloadStatus::Paused) {
+            let Some(when) = task.scheduled_for else { continue };
+            let scheduled_time = match chrono::Local.from_local_datetime(&when).earliest() {
+                Some(time) => time.with_timezone(&chrono::Utc),
+                None => chrono::Utc::now(),
+            };
+            let entry = ScheduledTask {
+                id: uuid::Uuid::new_v4().to_string(),
+                download_id: task.id.to_string(),
+                scheduled_time,
+                repeat_interval: None,
+                enabled: true,
+            };
+            if let Err(e) = self.scheduler.add_task(entry).await {
+                tracing::warn!("Failed to reschedule download {}: {}", task.id, e);
+            }
+        }
+    }
+
+    /// On first run, point the built-in categories without a folder at
+    /// the platform's standard folders
+    async fn seed_category_folders(&self) {
+        if matches!(self.db.get_setting(CATEGORY_FOLDERS_SEEDED_KEY).await, Ok(Some(_))) {
+            return;
+        }
+        let categories = match self.db.get_all_categories().await {
+            Ok(categories) => categories,
+            Err(e) => {
+                tracing::warn!("Failed to load categories to seed folders: {}", e);
+                return;
+            }
+        };
+
+        for mut category in categories.into_iter().filter(|c| c.save_path.is_none()) {
+            let Some(kind) = FolderKind::for_category(&category.id) else { continue };
+            let folder = file_utils::get_default_folder(kind);
+            if let Err(e) = file_utils::ensure_dir(&folder).await {
+                tracing::warn!("Not seeding {} with {:?}: {}", category.id, folder, e);
+                continue;
+            }
+            category.save_path = Some(folder);
+            category.updated_at = chrono::Utc::now().timestamp();
+            if let Err(e) = self.db.update_category(&category).await {
+                tracing::warn!("Failed to seed folder for {}: {}", category.id, e);
+            }
+        }
+        if let Err(e) = self.db.set_setting(CATEGORY_FOLDERS_SEEDED_KEY, "true").await {
+            tracing::warn!("Failed to record seeded category folders: {}", e);
+        }
+    }
+
+    /// Reload the per-category save folders used when adding downloads,
+    /// and the file permission overrides applied when they complete.
+    /// Folders that no longer exist are replaced with Downloads, keeping
+    /// the configured path so it applies again once the folder is back;
+    /// those are returned.
+    pub async fn refresh_category_dirs(&self) -> Vec<MissingCategoryFolder> {
+        let mut missing = Vec::new();
+        match self.db.get_all_categories().await {
+            Ok(categories) => {
+                let permissions = categories
+                    .iter()
+                    .filter(|c| !c.file_permissions.is_empty())
+                    .map(|c| (c.id.clone(), c.file_permissions.clone()))
+                    .collect();
+                self.engine.set_category_permissions(permissions);
+
+                let defaults = categories
+                    .iter()
+                    .map(|c| (c.id.clone(), c.download_defaults()))
+                    .filter(|(_, d)| !d.is_empty())
+                    .collect();
+                self.engine.set_category_defaults(defaults);
+
+                let fallback = file_utils::get_default_download_dir();
+                let dirs = categories
+                    .into_iter()
+                    .filter_map(|c| {
+                        let path = c.save_path?;
+                        if path.is_dir() {
+                            return Some((c.id, path));
+                        }
+                        tracing::warn!("Folder for category {} is missing: {:?}", c.name, path);
+                        missing.push(MissingCategoryFolder {
+                            category_id: c.id.clone(),
+                            name: c.name,
+                            path,
+                            fallback: fallback.clone(),
+                        });
+                        Some((c.id, fallback.clone()))
+                    })
+                    .collect();
+                self.engine.set_category_dirs(dirs);
+            }
+            Err(e) => tracing::warn!("Failed to load category folders: {}", e),
+        }
+        missing
+    }
+
+    /// Stop running downloads so they resume on the next start, before
+    /// the app exits on its own (e.g. to run an installer)
+    pub async fn pause_active_for_shutdown(&self) {
+        let active: Vec<(Uuid, ActiveDownload)> = self.active_downloads.write().await.drain().collect();
+        for (id, download) in active {
+            download.cancel_token.cancel();
+            if let Err(e) = self.db.update_status(id, DownloadStatus::Paused).await {
+                tracing::warn!("Failed to pause {} before shutdown: {}", id, e);
+            }
+        }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_concurrent_starts_run_once() {
+        let active = Arc::new(RwLock::new(HashMap::new()));
+        let task = DownloadTask::new("https://example.com/a".into(), "a".into(), "a".into(), 1);
+        let (events_tx, events_rx) = flume::unbounded::<u64>();
+
+        let start = |if_active| {
+            let active = active.clone();
+            let task = task.clone();
+            let events_tx = events_tx.clone();
+            async move {
+                spawn_active(&active, task, if_active, move |_cancel| {
+                    tokio::spawn(async move {
+                        for downloaded in [10, 20, 30] {
+                            let _ = events_tx.send_async(downloaded).await;
+                        }
+                    })
+                })
+                .await
+            }
+        };
+
+        let (first, second) = tokio::join!(start(IfActive::Fail), start(IfActive::Fail));
+        assert_eq!(first.is_ok() as u8 + second.is_ok() as u8, 1);
+        assert!(matches!(first.and(second), Err(DownloadError::AlreadyActive(_))));
+        assert_eq!(active.read().await.len(), 1);
+        assert!(!start(IfActive::Skip).await.unwrap());
+
+        let handle = active.write().await.remove(&task.id).unwrap().task_handle;
+        handle.await.unwrap();
+        drop(events_tx);
+        assert_eq!(events_rx.drain().collect::<Vec<_>>(), vec![10, 20, 30]);
+    }
+}
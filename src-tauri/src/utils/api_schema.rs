@@ -0,0 +1,144 @@
+// src-tauri/src/utils/api_schema.rs
+// JSON Schemas for the payloads that cross from Rust to the frontend, the
+// browser extension and remote clients: one per type, one per event name
+// and one per native messaging direction. The snapshots checked in under `src-tauri/schemas` are compared
+// with what the structs produce, so a field that changes without the
+// schemas being regenerated fails `cargo test`.
+
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+use std::path::{Path, PathBuf};
+
+use crate::commands::browser_commands::BrowserDownloadRequest;
+use crate::commands::download_commands::GlobalStats;
+use crate::commands::settings_commands::AppSettings;
+use crate::core::download_engine::AddDownloadRequest;
+use crate::core::download_task::{DownloadProgress, DownloadTask};
+use crate::core::page_media::MediaAddOptions;
+use crate::core::undo::UndoableAction;
+use crate::core::waste::WasteWarning;
+use crate::core::ytdlp_fallback::FallbackNotice;
+use crate::events::download_events::DownloadFailedEvent;
+use crate::network::torrent_client_librqbit::{TorrentInfo, TorrentStats};
+use crate::network::youtube_downloader::YouTubeDownloadOptions;
+use crate::services::native_messaging::{NativeMessage, NativeResponse};
+use crate::services::watchdog_service::WatchdogRecovery;
+use crate::utils::enhanced_error::UserError;
+
+/// Set to write the schemas the snapshot test compares against
+pub const UPDATE_SCHEMAS_ENV: &str = "UPDATE_API_SCHEMA";
+
+/// Request, response and settings types, by name
+pub fn type_schemas() -> Vec<(&'static str, RootSchema)> {
+    vec![
+        ("AddDownloadRequest", schema_for!(AddDownloadRequest)),
+        ("BrowserDownloadRequest", schema_for!(BrowserDownloadRequest)),
+        ("MediaAddOptions", schema_for!(MediaAddOptions)),
+        ("YouTubeDownloadOptions", schema_for!(YouTubeDownloadOptions)),
+        ("DownloadTask", schema_for!(DownloadTask)),
+        ("DownloadProgress", schema_for!(DownloadProgress)),
+        ("GlobalStats", schema_for!(GlobalStats)),
+        ("TorrentInfo", schema_for!(TorrentInfo)),
+        ("TorrentStats", schema_for!(TorrentStats)),
+        ("AppSettings", schema_for!(AppSettings)),
+        ("UserError", schema_for!(UserError)),
+    ]
+}
+
+/// Event payloads, by event name
+pub fn event_schemas() -> Vec<(&'static str, RootSchema)> {
+    vec![
+        ("download-progress", schema_for!(DownloadProgress)),
+        ("download-added", schema_for!(DownloadTask)),
+        ("download-started", schema_for!(DownloadTask)),
+        ("download-paused", schema_for!(DownloadTask)),
+        ("download-resumed", schema_for!(DownloadTask)),
+        ("download-cancelled", schema_for!(DownloadTask)),
+        ("download-complete", schema_for!(DownloadTask)),
+        ("download-failed", schema_for!(DownloadFailedEvent)),
+        ("download-waste-warning", schema_for!(WasteWarning)),
+        ("download-fallback", schema_for!(FallbackNotice)),
+        ("action-undone", schema_for!(UndoableAction)),
+        ("download-watchdog-recovered", schema_for!(WatchdogRecovery)),
+        ("global-speed-update", schema_for!(f64)),
+    ]
+}
+
+/// Messages the browser extension sends, and what the host answers
+pub fn native_messaging_schemas() -> Vec<(&'static str, RootSchema)> {
+    vec![
+        ("NativeMessage", schema_for!(NativeMessage)),
+        ("NativeResponse", schema_for!(NativeResponse)),
+    ]
+}
+
+/// Every schema as its path under the export folder and its JSON
+pub fn render() -> Vec<(PathBuf, String)> {
+    let files = |dir: &str, schemas: Vec<(&str, RootSchema)>| {
+        schemas
+            .into_iter()
+            .map(|(name, schema)| {
+                let json = serde_json::to_string_pretty(&schema).expect("schemas serialize");
+                (Path::new(dir).join(format!("{}.json", name)), json + "\n")
+            })
+            .collect::<Vec<_>>()
+    };
+    let mut rendered = files("types", type_schemas());
+    rendered.extend(files("events", event_schemas()));
+    rendered.extend(files("native-messaging", native_messaging_schemas()));
+    rendered
+}
+
+/// Write every schema under `dest_dir`, returning the files written
+pub async fn export(dest_dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut written = Vec::new();
+    for (relative, json) in render() {
+        let path = dest_dir.join(relative);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, json).await?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Missing or changed snapshots fail unless UPDATE_API_SCHEMA is set,
+    /// in which case they are written
+    #[test]
+    fn test_schemas_match_snapshots() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas");
+        let update = std::env::var_os(UPDATE_SCHEMAS_ENV).is_some();
+        let mut changed = Vec::new();
+        for (relative, json) in render() {
+            let path = dir.join(&relative);
+            match std::fs::read_to_string(&path) {
+                Ok(saved) if saved == json => {}
+                _ if !update => changed.push(relative),
+                _ => {
+                    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                    std::fs::write(&path, json).unwrap();
+                }
+            }
+        }
+        assert!(
+            changed.is_empty(),
+            "Schemas missing or changed: {:?}. Run `{}=1 cargo test` and commit src-tauri/schemas",
+            changed,
+            UPDATE_SCHEMAS_ENV
+        );
+    }
+
+    #[test]
+    fn test_event_names_are_unique() {
+        let mut names: Vec<_> = event_schemas().into_iter().map(|(name, _)| name).collect();
+        let count = names.len();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), count);
+    }
+}
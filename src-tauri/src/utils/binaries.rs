@@ -0,0 +1,380 @@
+// src-tauri/src/utils/binaries.rs
+
+//! Resolution of the external tools the app drives (yt-dlp, ffmpeg, aria2).
+//!
+//! Every tool is looked up in the same order:
+//! 1. an explicit path from settings
+//! 2. the app-managed `bin` directory in app data
+//! 3. the binary bundled with the app's resources
+//! 4. the system `PATH`
+//!
+//! The first candidate that exists and is executable wins.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::Command;
+
+/// How long a `--version` probe may take before the tool is reported as broken
+const VERSION_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tool {
+    YtDlp,
+    Ffmpeg,
+    Aria2,
+}
+
+impl Tool {
+    pub const ALL: [Tool; 3] = [Tool::YtDlp, Tool::Ffmpeg, Tool::Aria2];
+
+    /// Settings key holding an explicit path for this tool
+    pub fn setting_key(&self) -> &'static str {
+        match self {
+            Tool::YtDlp => "ytdlp_path",
+            Tool::Ffmpeg => "ffmpeg_path",
+            Tool::Aria2 => "aria2_path",
+        }
+    }
+
+    /// File name in the app-managed directory and on PATH
+    pub fn executable_name(&self) -> &'static str {
+        match self {
+            Tool::YtDlp if cfg!(target_os = "windows") => "yt-dlp.exe",
+            Tool::YtDlp => "yt-dlp",
+            Tool::Ffmpeg if cfg!(target_os = "windows") => "ffmpeg.exe",
+            Tool::Ffmpeg => "ffmpeg",
+            Tool::Aria2 if cfg!(target_os = "windows") => "aria2c.exe",
+            Tool::Aria2 => "aria2c",
+        }
+    }
+
+    /// File name under `resources/bin` for this platform
+    pub fn bundled_name(&self) -> &'static str {
+        match self {
+            Tool::YtDlp if cfg!(target_os = "macos") => "yt-dlp_macos",
+            Tool::YtDlp if cfg!(target_os = "linux") => "yt-dlp_linux",
+            _ => self.executable_name(),
+        }
+    }
+
+    fn version_arg(&self) -> &'static str {
+        match self {
+            Tool::Ffmpeg => "-version",
+            _ => "--version",
+        }
+    }
+}
+
+/// Where a resolved tool was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolOrigin {
+    Setting,
+    Managed,
+    Bundled,
+    Path,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedTool {
+    pub path: PathBuf,
+    pub origin: ToolOrigin,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolStatus {
+    pub tool: Tool,
+    pub path: Option<PathBuf>,
+    pub origin: Option<ToolOrigin>,
+    pub version: Option<String>,
+}
+
+/// Resolves managed tools following the documented precedence
+pub struct BinaryResolver {
+    managed_dir: PathBuf,
+    resource_dirs: Vec<PathBuf>,
+    path_var: Option<OsString>,
+    overrides: parking_lot::RwLock<HashMap<Tool, PathBuf>>,
+}
+
+impl BinaryResolver {
+    pub fn new(managed_dir: PathBuf, resource_dirs: Vec<PathBuf>, path_var: Option<OsString>) -> Self {
+        Self {
+            managed_dir,
+            resource_dirs,
+            path_var,
+            overrides: parking_lot::RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Resolver for the running app: `<app data>/bin`, the bundled
+    /// resources (plus the source tree in dev builds) and the process PATH
+    pub fn for_app(app_data_dir: &Path, resource_dir: Option<PathBuf>) -> Self {
+        let mut resource_dirs: Vec<PathBuf> = resource_dir.into_iter().collect();
+        if cfg!(debug_assertions) {
+            resource_dirs.push(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("resources"));
+        }
+        Self::new(app_data_dir.join("bin"), resource_dirs, std::env::var_os("PATH"))
+    }
+
+    pub fn managed_dir(&self) -> &Path {
+        &self.managed_dir
+    }
+
+    /// Path a tool is installed to inside the app-managed directory
+    pub fn managed_path(&self, tool: Tool) -> PathBuf {
+        self.managed_dir.join(tool.executable_name())
+    }
+
+    /// Set or clear the explicit path configured for a tool
+    pub fn set_override(&self, tool: Tool, path: Option<PathBuf>) {
+        let mut overrides = self.overrides.write();
+        match path {
+            Some(path) => overrides.insert(tool, path),
+            None => overrides.remove(&tool),
+        };
+    }
+
+    /// The bundled copy shipped with the app, if any
+    pub fn bundled_path(&self, tool: Tool) -> Option<PathBuf> {
+        self.resource_dirs
+            .iter()
+            .map(|dir| dir.join("bin").join(tool.bundled_name()))
+            .find(|path| path.is_file())
+    }
+
+    pub fn resolve(&self, tool: Tool) -> Option<ResolvedTool> {
+        if let Some(path) = self.overrides.read().get(&tool).cloned() {
+            if is_executable(&path) {
+                return Some(ResolvedTool { path, origin: ToolOrigin::Setting });
+            }
+            tracing::warn!("Configured {:?} path {:?} is not executable, ignoring", tool, path);
+        }
+
+        let managed = self.managed_path(tool);
+        if managed.is_file() {
+            // We own this directory, so repair permissions instead of skipping it
+            if let Err(e) = prepare_executable(&managed) {
+                tracing::warn!("Failed to prepare {:?}: {}", managed, e);
+            }
+            if is_executable(&managed) {
+                return Some(ResolvedTool { path: managed, origin: ToolOrigin::Managed });
+            }
+        }
+
+        if let Some(bundled) = self.bundled_path(tool).filter(|p| is_executable(p)) {
+            return Some(ResolvedTool { path: bundled, origin: ToolOrigin::Bundled });
+        }
+
+        let path_var = self.path_var.as_ref()?;
+        std::env::split_paths(path_var)
+            .map(|dir| dir.join(tool.executable_name()))
+            .find(|path| is_executable(path))
+            .map(|path| ResolvedTool { path, origin: ToolOrigin::Path })
+    }
+
+    /// Resolved path, or the bare executable name so spawning still
+    /// produces a clear "not found" error
+    pub fn path_or_default(&self, tool: Tool) -> PathBuf {
+        self.resolve(tool)
+            .map(|resolved| resolved.path)
+            .unwrap_or_else(|| PathBuf::from(tool.executable_name()))
+    }
+
+    pub async fn status(&self, tool: Tool) -> ToolStatus {
+        let resolved = self.resolve(tool);
+        let version = match &resolved {
+            Some(resolved) => probe_version(tool, &resolved.path).await,
+            None => None,
+        };
+        ToolStatus {
+            tool,
+            path: resolved.as_ref().map(|r| r.path.clone()),
+            origin: resolved.map(|r| r.origin),
+            version,
+        }
+    }
+
+    /// Path, version and origin for every managed tool
+    pub async fn get_tool_status(&self) -> Vec<ToolStatus> {
+        let mut statuses = Vec::with_capacity(Tool::ALL.len());
+        for tool in Tool::ALL {
+            statuses.push(self.status(tool).await);
+        }
+        statuses
+    }
+}
+
+async fn probe_version(tool: Tool, path: &Path) -> Option<String> {
+    let output = tokio::time::timeout(
+        VERSION_TIMEOUT,
+        Command::new(path).arg(tool.version_arg()).output(),
+    )
+    .await
+    .ok()?
+    .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+}
+
+pub fn is_executable(path: &Path) -> bool {
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return false;
+    };
+    if !metadata.is_file() {
+        return false;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        true
+    }
+}
+
+/// Make a downloaded or extracted binary runnable: set the executable bit
+/// and, on macOS, drop the quarantine attribute Gatekeeper would block on
+pub fn prepare_executable(path: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = std::fs::metadata(path)?.permissions();
+        if perms.mode() & 0o111 != 0o111 {
+            perms.set_mode(0o755);
+            std::fs::set_permissions(path, perms)?;
+        }
+    }
+    #[cfg(target_os = "macos")]
+    remove_quarantine(path);
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn remove_quarantine(path: &Path) {
+    let has_quarantine = std::process::Command::new("xattr")
+        .arg("-p")
+        .arg("com.apple.quarantine")
+        .arg(path)
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false);
+    if !has_quarantine {
+        return;
+    }
+    match std::process::Command::new("xattr")
+        .arg("-d")
+        .arg("com.apple.quarantine")
+        .arg(path)
+        .status()
+    {
+        Ok(status) if status.success() => {
+            tracing::info!("Removed quarantine attribute from {:?}", path)
+        }
+        Ok(status) => tracing::warn!("xattr exited with {} for {:?}", status, path),
+        Err(e) => tracing::warn!("Failed to run xattr for {:?}: {}", path, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("afk-bin-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn install(path: &Path) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, b"#!/bin/sh\n").unwrap();
+        prepare_executable(path).unwrap();
+    }
+
+    #[test]
+    fn test_resolution_precedence() {
+        let root = temp_dir();
+        let managed = root.join("managed");
+        let resources = root.join("resources");
+        let fake_path = root.join("path");
+        let resolver = BinaryResolver::new(
+            managed.clone(),
+            vec![resources.clone()],
+            Some(std::env::join_paths([&fake_path]).unwrap()),
+        );
+        let tool = Tool::YtDlp;
+
+        assert_eq!(resolver.resolve(tool), None);
+        assert_eq!(resolver.path_or_default(tool), PathBuf::from(tool.executable_name()));
+
+        install(&fake_path.join(tool.executable_name()));
+        assert_eq!(resolver.resolve(tool).unwrap().origin, ToolOrigin::Path);
+
+        install(&resources.join("bin").join(tool.bundled_name()));
+        assert_eq!(resolver.resolve(tool).unwrap().origin, ToolOrigin::Bundled);
+
+        install(&managed.join(tool.executable_name()));
+        assert_eq!(resolver.resolve(tool).unwrap().origin, ToolOrigin::Managed);
+
+        let custom = root.join("custom").join("my-yt-dlp");
+        install(&custom);
+        resolver.set_override(tool, Some(custom.clone()));
+        let resolved = resolver.resolve(tool).unwrap();
+        assert_eq!(resolved.origin, ToolOrigin::Setting);
+        assert_eq!(resolved.path, custom);
+
+        // A missing configured path falls through to the next candidate
+        resolver.set_override(tool, Some(root.join("missing")));
+        assert_eq!(resolver.resolve(tool).unwrap().origin, ToolOrigin::Managed);
+
+        // Other tools are resolved independently
+        assert_eq!(resolver.resolve(Tool::Ffmpeg), None);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_executable_candidates_are_skipped() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = temp_dir();
+        let resources = root.join("resources");
+        let fake_path = root.join("path");
+        let resolver = BinaryResolver::new(
+            root.join("managed"),
+            vec![resources.clone()],
+            Some(std::env::join_paths([&fake_path]).unwrap()),
+        );
+
+        let bundled = resources.join("bin").join(Tool::Ffmpeg.bundled_name());
+        std::fs::create_dir_all(bundled.parent().unwrap()).unwrap();
+        std::fs::write(&bundled, b"").unwrap();
+        std::fs::set_permissions(&bundled, std::fs::Permissions::from_mode(0o644)).unwrap();
+        install(&fake_path.join(Tool::Ffmpeg.executable_name()));
+
+        assert_eq!(resolver.resolve(Tool::Ffmpeg).unwrap().origin, ToolOrigin::Path);
+
+        // Managed binaries get their permissions repaired
+        let managed = resolver.managed_path(Tool::Ffmpeg);
+        std::fs::create_dir_all(managed.parent().unwrap()).unwrap();
+        std::fs::write(&managed, b"").unwrap();
+        std::fs::set_permissions(&managed, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert_eq!(resolver.resolve(Tool::Ffmpeg).unwrap().origin, ToolOrigin::Managed);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
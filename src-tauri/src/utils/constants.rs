@@ -12,6 +12,10 @@ pub const MIN_SIZE_FOR_SEGMENTS: u64 = 1_048_576;
 /// Minimum segment size (256 KB)
 pub const MIN_SEGMENT_SIZE: u64 = 262_144;
 
+/// Segment boundaries are aligned to this block size (1 MB), matching
+/// typical CDN cache blocks
+pub const CHUNK_ALIGNMENT: u64 = 1_048_576;
+
 /// Default max concurrent downloads
 pub const DEFAULT_MAX_CONCURRENT: u32 = 5;
 
@@ -21,6 +25,42 @@ pub const DEFAULT_CONNECT_TIMEOUT: u64 = 30;
 /// Default read timeout in seconds
 pub const DEFAULT_READ_TIMEOUT: u64 = 60;
 
+/// Default overall budget for probing a URL's file info, in seconds
+pub const DEFAULT_PROBE_TIMEOUT: u64 = 15;
+
+/// Default time a probed URL's file info is reused, in seconds
+pub const DEFAULT_FILE_INFO_TTL: u64 = 60;
+
+/// File info probes run against one host at a time
+pub const MAX_PROBES_PER_HOST: usize = 4;
+
+/// Default time a soft pause keeps connections open, in seconds
+pub const DEFAULT_SOFT_PAUSE_SECS: u64 = 30;
+
+/// Longest a soft pause may keep connections open, in seconds. Well under
+/// the watchdog's stall timeout, and short enough that servers aren't left
+/// holding idle connections for long.
+pub const MAX_SOFT_PAUSE_SECS: u64 = 300;
+
+/// Default cap on hops followed when resolving a link before adding it
+pub const DEFAULT_MAX_RESOLVE_HOPS: usize = 10;
+
+/// Times an expired signed URL is refreshed before the download fails
+pub const MAX_URL_REFRESHES: u32 = 3;
+
+/// Default number of URLs a batch add probes at once
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 5;
+
+/// Default number of playlist entries downloaded at once in per-entry mode
+pub const DEFAULT_PLAYLIST_CONCURRENCY: usize = 2;
+
+/// Extra tries a failed playlist entry gets in per-entry mode
+pub const DEFAULT_PLAYLIST_ENTRY_RETRIES: u32 = 2;
+
+/// Post-download conversions run at once; kept low so they don't compete
+/// with downloads for disk and CPU
+pub const MAX_CONCURRENT_CONVERSIONS: usize = 1;
+
 /// Default max retries
 pub const DEFAULT_MAX_RETRIES: u32 = 5;
 
@@ -36,6 +76,10 @@ pub const PROGRESS_UPDATE_INTERVAL_MS: u64 = 200;
 /// Buffer size for file I/O (64 KB)
 pub const BUFFER_SIZE: usize = 65_536;
 
+/// Buffer size for merging segments (4 MB), large enough that copying
+/// between volumes isn't dominated by per-call overhead
+pub const MERGE_BUFFER_SIZE: usize = 4 * 1024 * 1024;
+
 /// Speed calculation window in seconds
 pub const SPEED_WINDOW_SECONDS: f64 = 3.0;
 
@@ -51,5 +95,9 @@ pub const APP_NAME: &str = "SuperDownloader";
 /// Database file name
 pub const DB_FILE_NAME: &str = "downloads.db";
 
+/// Bundle identifier from tauri.conf.json; names the app data directory
+/// for entry points that run without the Tauri app
+pub const APP_IDENTIFIER: &str = "com.ankit.afk-dunld";
+
 /// Temp directory prefix
 pub const TEMP_DIR_PREFIX: &str = ".sdl_parts_";
\ No newline at end of file
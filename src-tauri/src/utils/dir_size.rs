@@ -0,0 +1,287 @@
+// src-tauri/src/utils/dir_size.rs
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{RwLock, Semaphore};
+use tokio_util::sync::CancellationToken;
+
+/// How long a computed directory size stays valid
+pub const DIR_SIZE_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Maximum number of directory walks running at once
+pub const MAX_CONCURRENT_WALKS: usize = 4;
+
+/// Report partial progress every this many files
+const PROGRESS_EVERY_FILES: u64 = 1000;
+
+/// Total size of a directory tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectorySize {
+    pub path: PathBuf,
+    pub total_bytes: u64,
+    pub file_count: u64,
+    /// False while the walk is still running or if it was cancelled
+    pub complete: bool,
+}
+
+/// Walk a directory tree and sum file sizes.
+///
+/// Symlinks are followed, but every directory is canonicalized and visited
+/// at most once so symlink loops terminate. Unreadable entries are skipped.
+pub fn walk_directory<F>(
+    root: &Path,
+    cancel: &CancellationToken,
+    mut on_progress: F,
+) -> DirectorySize
+where
+    F: FnMut(u64, u64),
+{
+    let mut total_bytes: u64 = 0;
+    let mut file_count: u64 = 0;
+    let mut visited: HashSet<PathBuf> = HashSet::new();
+    let mut stack = vec![root.to_path_buf()];
+
+    while let Some(dir) = stack.pop() {
+        if cancel.is_cancelled() {
+            return DirectorySize {
+                path: root.to_path_buf(),
+                total_bytes,
+                file_count,
+                complete: false,
+            };
+        }
+
+        let canonical = match std::fs::canonicalize(&dir) {
+            Ok(path) => path,
+            Err(_) => continue,
+        };
+        if !visited.insert(canonical) {
+            continue;
+        }
+
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::debug!("Skipping unreadable dir {}: {}", dir.display(), e);
+                continue;
+            }
+        };
+
+        for entry in entries.flatten() {
+            // Follows symlinks; broken links are skipped
+            let metadata = match std::fs::metadata(entry.path()) {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+
+            if metadata.is_dir() {
+                stack.push(entry.path());
+            } else if metadata.is_file() {
+                total_bytes += metadata.len();
+                file_count += 1;
+
+                if file_count % PROGRESS_EVERY_FILES == 0 {
+                    on_progress(total_bytes, file_count);
+                }
+            }
+        }
+    }
+
+    DirectorySize {
+        path: root.to_path_buf(),
+        total_bytes,
+        file_count,
+        complete: true,
+    }
+}
+
+/// Cache of directory sizes with bounded, cancellable background walks
+pub struct DirSizeCache {
+    entries: RwLock<HashMap<PathBuf, (DirectorySize, Instant)>>,
+    ttl: Duration,
+    semaphore: Arc<Semaphore>,
+    current_scan: parking_lot::Mutex<Option<CancellationToken>>,
+}
+
+impl DirSizeCache {
+    pub fn new(ttl: Duration, max_concurrent: usize) -> Self {
+        Self {
+            entries: RwLock::new(HashMap::new()),
+            ttl,
+            semaphore: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            current_scan: parking_lot::Mutex::new(None),
+        }
+    }
+
+    /// Get a cached size if it has not expired
+    pub async fn get(&self, path: &Path) -> Option<DirectorySize> {
+        let entries = self.entries.read().await;
+        entries
+            .get(path)
+            .filter(|(_, computed_at)| computed_at.elapsed() < self.ttl)
+            .map(|(size, _)| size.clone())
+    }
+
+    /// Store a completed size
+    pub async fn insert(&self, size: DirectorySize) {
+        self.entries
+            .write()
+            .await
+            .insert(size.path.clone(), (size, Instant::now()));
+    }
+
+    /// Drop every cached directory that contains `changed_path`
+    pub async fn invalidate(&self, changed_path: &Path) {
+        self.entries
+            .write()
+            .await
+            .retain(|dir, _| !changed_path.starts_with(dir));
+    }
+
+    /// Start a new scan, cancelling any scan still in progress
+    pub fn begin_scan(&self) -> CancellationToken {
+        let token = CancellationToken::new();
+        if let Some(previous) = self.current_scan.lock().replace(token.clone()) {
+            previous.cancel();
+        }
+        token
+    }
+
+    /// Cancel the scan in progress, if any
+    pub fn cancel_scan(&self) {
+        if let Some(token) = self.current_scan.lock().take() {
+            token.cancel();
+        }
+    }
+
+    /// Compute sizes for all paths, serving fresh entries from the cache.
+    /// Walks run on the blocking pool, at most `max_concurrent` at a time,
+    /// and `on_progress` receives running totals for large directories.
+    pub async fn compute<F>(
+        &self,
+        paths: Vec<PathBuf>,
+        cancel: CancellationToken,
+        on_progress: F,
+    ) -> Vec<DirectorySize>
+    where
+        F: Fn(DirectorySize) + Send + Sync + 'static,
+    {
+        let on_progress = Arc::new(on_progress);
+        let mut results: Vec<Option<DirectorySize>> = Vec::with_capacity(paths.len());
+        let mut handles = Vec::new();
+
+        for (index, path) in paths.into_iter().enumerate() {
+            if let Some(cached) = self.get(&path).await {
+                results.push(Some(cached));
+                continue;
+            }
+            results.push(None);
+
+            let semaphore = self.semaphore.clone();
+            let cancel = cancel.clone();
+            let on_progress = on_progress.clone();
+
+            handles.push((index, tokio::spawn(async move {
+                let _permit = semaphore.acquire_owned().await.ok()?;
+                tokio::task::spawn_blocking(move || {
+                    walk_directory(&path, &cancel, |total_bytes, file_count| {
+                        on_progress(DirectorySize {
+                            path: path.clone(),
+                            total_bytes,
+                            file_count,
+                            complete: false,
+                        });
+                    })
+                })
+                .await
+                .ok()
+            })));
+        }
+
+        for (index, handle) in handles {
+            if let Ok(Some(size)) = handle.await {
+                if size.complete {
+                    self.insert(size.clone()).await;
+                }
+                results[index] = Some(size);
+            }
+        }
+
+        results.into_iter().flatten().collect()
+    }
+}
+
+impl Default for DirSizeCache {
+    fn default() -> Self {
+        Self::new(DIR_SIZE_CACHE_TTL, MAX_CONCURRENT_WALKS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("dir_size_test_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("a.bin"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.join("nested").join("b.bin"), vec![0u8; 50]).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_walk_directory_sums_files() {
+        let dir = temp_dir();
+        let size = walk_directory(&dir, &CancellationToken::new(), |_, _| {});
+
+        assert!(size.complete);
+        assert_eq!(size.total_bytes, 150);
+        assert_eq!(size.file_count, 2);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_walk_directory_survives_symlink_loop() {
+        let dir = temp_dir();
+        std::os::unix::fs::symlink(&dir, dir.join("nested").join("loop")).unwrap();
+
+        let size = walk_directory(&dir, &CancellationToken::new(), |_, _| {});
+
+        assert!(size.complete);
+        assert_eq!(size.total_bytes, 150);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_walk_directory_cancelled() {
+        let dir = temp_dir();
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let size = walk_directory(&dir, &cancel, |_, _| {});
+        assert!(!size.complete);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_cache_invalidation() {
+        let dir = temp_dir();
+        let cache = DirSizeCache::default();
+
+        let sizes = cache.compute(vec![dir.clone()], CancellationToken::new(), |_| {}).await;
+        assert_eq!(sizes[0].total_bytes, 150);
+        assert!(cache.get(&dir).await.is_some());
+
+        cache.invalidate(&dir.join("nested").join("new.bin")).await;
+        assert!(cache.get(&dir).await.is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,9 +1,10 @@
 // Enhanced Error Handling with user-friendly messages
 use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
 use std::fmt;
 
 /// User-friendly error representation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct UserError {
     pub title: String,
     pub message: String,
@@ -141,6 +142,105 @@ impl UserError {
                 .with_recovery_hint("Check if the torrent is still active and has seeders")
             }
 
+            DownloadError::PathNotAllowed(msg) => {
+                UserError::new(
+                    "Folder Not Allowed",
+                    "The download would be saved outside your approved folders",
+                    "PATH_NOT_ALLOWED",
+                    false,
+                )
+                .with_details(msg)
+                .with_recovery_hint("Choose the folder in the save dialog to approve it")
+            }
+
+            DownloadError::ExpiredUrl(msg) => {
+                UserError::new(
+                    "Link Expired",
+                    "The download link stopped working partway through",
+                    "EXPIRED_URL",
+                    false,
+                )
+                .with_details(msg)
+                .with_recovery_hint("Paste a fresh link for the same file to continue where it stopped")
+            }
+
+            DownloadError::UrlMismatch(msg) => {
+                UserError::new(
+                    "Different File",
+                    "The new link is not for the file already partly downloaded",
+                    "URL_MISMATCH",
+                    false,
+                )
+                .with_details(msg)
+                .with_recovery_hint("Use the link anyway to restart the download from zero")
+            }
+
+            DownloadError::AlreadyActive(msg) => {
+                UserError::new(
+                    "Already Downloading",
+                    "This download is already running",
+                    "ALREADY_ACTIVE",
+                    false,
+                )
+                .with_details(msg)
+                .with_recovery_hint("Wait for it to finish or pause it first")
+            }
+
+            DownloadError::FileMissing(msg) => {
+                UserError::new(
+                    "File Missing",
+                    "The downloaded file is no longer on disk",
+                    "FILE_MISSING",
+                    false,
+                )
+                .with_details(msg)
+                .with_recovery_hint("It may have been moved or deleted; download it again to restore it")
+            }
+
+            DownloadError::DatabaseLocked(msg) => {
+                UserError::new(
+                    "Database In Use",
+                    "Another AFK-Dunld process is using the downloads database",
+                    "DATABASE_LOCKED",
+                    false,
+                )
+                .with_details(msg)
+                .with_recovery_hint("Close the other window, or end the process if it is stuck")
+            }
+
+            DownloadError::WatchdogRecovered(msg) => {
+                UserError::new(
+                    "Download Stopped Responding",
+                    "The download stopped and was recovered",
+                    "WATCHDOG_RECOVERED",
+                    true,
+                )
+                .with_details(msg)
+                .with_recovery_hint("Resume it to continue from where it stopped")
+            }
+
+            DownloadError::DestinationUnavailable(msg) => {
+                UserError::new(
+                    "Destination Unavailable",
+                    "The drive this download is saved to isn't available",
+                    "DESTINATION_UNAVAILABLE",
+                    true,
+                )
+                .with_details(msg)
+                .with_recovery_hint("Reconnect the drive and resume")
+            }
+
+            DownloadError::TlsError(msg) => {
+                UserError::new(
+                    "Secure Connection Failed",
+                    "The server's certificate could not be verified",
+                    "TLS_ERROR",
+                    false,
+                )
+                .with_details(msg)
+                .with_recovery_hint("Check whether a proxy or antivirus is intercepting connections")
+            }
+
             DownloadError::Unknown(msg) => {
                 UserError::new(
                     "Unknown Error",
@@ -211,6 +311,16 @@ impl UserError {
                 .with_details(msg)
             }
 
+            AppError::Unsupported(msg) => {
+                UserError::new(
+                    "Not Supported",
+                    "This feature is not available here",
+                    "UNSUPPORTED",
+                    false,
+                )
+                .with_details(msg)
+            }
+
             _ => {
                 UserError::new(
                     "Application Error",
@@ -230,6 +340,159 @@ impl fmt::Display for UserError {
     }
 }
 
+/// What the failure toast offers as its one-click fix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestedAction {
+    /// `retry_download`
+    Retry,
+    /// `update_download_url` with a new link
+    UpdateUrl,
+    /// `save_remote_credential` for the host, then retry
+    AddCredentials,
+    /// `open_file_location` so space can be freed, then retry
+    FreeSpace,
+    /// Nothing the app can do about it
+    None,
+}
+
+/// Classification of a failed download sent with `download-failed`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FailureSuggestion {
+    pub suggestion: Option<String>,
+    pub suggested_action: SuggestedAction,
+}
+
+impl FailureSuggestion {
+    fn new(suggestion: impl Into<String>, suggested_action: SuggestedAction) -> Self {
+        Self {
+            suggestion: Some(suggestion.into()),
+            suggested_action,
+        }
+    }
+
+    fn bare(suggested_action: SuggestedAction) -> Self {
+        Self {
+            suggestion: None,
+            suggested_action,
+        }
+    }
+
+    /// The suggestion for a failure of the download of `url`. There is
+    /// deliberately no catch-all arm, so every new error code has to be
+    /// given one here.
+    pub fn for_error(error: &crate::utils::error::DownloadError, url: &str) -> Self {
+        use crate::utils::error::DownloadError;
+
+        let add_credentials = || {
+            let host = url::Url::parse(url)
+                .ok()
+                .and_then(|parsed| parsed.host_str().map(str::to_string))
+                .unwrap_or_else(|| url.to_string());
+            Self::new(format!("Add credentials for {}", host), SuggestedAction::AddCredentials)
+        };
+
+        match error {
+            DownloadError::ExpiredUrl(_) => Self::new("Update the link", SuggestedAction::UpdateUrl),
+            DownloadError::ChecksumMismatch { .. } => {
+                Self::new("Re-download or contact the source", SuggestedAction::Retry)
+            }
+            DownloadError::DestinationUnavailable(_) => {
+                Self::new("Reconnect the drive and resume", SuggestedAction::Retry)
+            }
+            DownloadError::AuthenticationFailed(_) => add_credentials(),
+            DownloadError::ServerError { status: 401 | 407, .. } => add_credentials(),
+            DownloadError::ServerError { status: 404 | 410, .. } | DownloadError::NotFound(_) => {
+                Self::new("The file was removed", SuggestedAction::UpdateUrl)
+            }
+            DownloadError::ServerError { status: 403, .. } => {
+                Self::new("The server refused the link; try a new one", SuggestedAction::UpdateUrl)
+            }
+            DownloadError::ServerError { status, .. } if *status >= 500 => {
+                Self::new("The server is having trouble; try again later", SuggestedAction::Retry)
+            }
+            DownloadError::ServerError { .. } => Self::bare(SuggestedAction::Retry),
+            DownloadError::TlsError(_) => {
+                Self::new("Check proxy/antivirus interception", SuggestedAction::None)
+            }
+            DownloadError::InsufficientDiskSpace | DownloadError::DiskFull => {
+                Self::new("Free up disk space and resume", SuggestedAction::FreeSpace)
+            }
+            DownloadError::NetworkError(_)
+            | DownloadError::Timeout { .. }
+            | DownloadError::SegmentFailed { .. }
+            | DownloadError::MaxRetriesExceeded { .. } => {
+                Self::new("Check your connection and retry", SuggestedAction::Retry)
+            }
+            DownloadError::WatchdogRecovered(_) => {
+                Self::new("Resume from where it stopped", SuggestedAction::Retry)
+            }
+            DownloadError::MergeFailed(_) => {
+                Self::new("Retry to rebuild the file from its parts", SuggestedAction::Retry)
+            }
+            DownloadError::InvalidUrl(_) | DownloadError::UrlError(_) => {
+                Self::new("Check the link", SuggestedAction::UpdateUrl)
+            }
+            DownloadError::UrlMismatch(_) => {
+                Self::new("The new link is for a different file", SuggestedAction::UpdateUrl)
+            }
+            DownloadError::FileMissing(_) => {
+                Self::new("Download it again to restore it", SuggestedAction::Retry)
+            }
+            DownloadError::RangeNotSupported
+            | DownloadError::FileError(_)
+            | DownloadError::Unknown(_) => Self::bare(SuggestedAction::Retry),
+            DownloadError::Cancelled
+            | DownloadError::Paused
+            | DownloadError::AlreadyActive(_)
+            | DownloadError::DatabaseLocked(_)
+            | DownloadError::TorrentError(_)
+            | DownloadError::FileTooLarge { .. }
+            | DownloadError::FileExists(_)
+            | DownloadError::PathNotAllowed(_)
+            | DownloadError::PermissionDenied => Self::bare(SuggestedAction::None),
+        }
+    }
+
+    /// The suggestion for a failure only known by its message, as with
+    /// yt-dlp and anything restored from the database. Recognises the
+    /// error code prefixes and the usual yt-dlp wording.
+    pub fn for_message(message: &str, url: &str) -> Self {
+        use crate::utils::error::DownloadError;
+
+        let text = message.to_string();
+        let lower = message.to_ascii_lowercase();
+        let prefixes: [(&str, fn(String) -> DownloadError); 6] = [
+            ("EXPIRED_URL", DownloadError::ExpiredUrl),
+            ("DESTINATION_UNAVAILABLE", DownloadError::DestinationUnavailable),
+            ("TLS_ERROR", DownloadError::TlsError),
+            ("WATCHDOG_RECOVERED", DownloadError::WatchdogRecovered),
+            ("URL_MISMATCH", DownloadError::UrlMismatch),
+            ("FILE_MISSING", DownloadError::FileMissing),
+        ];
+        let error = if let Some((_, build)) =
+            prefixes.iter().find(|(code, _)| message.starts_with(code))
+        {
+            build(text)
+        } else if let Some(status) = [401u16, 403, 404, 407, 410]
+            .into_iter()
+            .find(|status| lower.contains(&format!("http error {}", status)))
+        {
+            DownloadError::ServerError { status, message: text }
+        } else if lower.contains("certificate_verify_failed") || lower.contains("ssl:") {
+            DownloadError::TlsError(text)
+        } else if lower.contains("no space left") || lower.contains("disk full") {
+            DownloadError::DiskFull
+        } else if lower.contains("timed out") || lower.contains("connection reset") {
+            DownloadError::NetworkError(text)
+        } else {
+            return Self::bare(SuggestedAction::Retry);
+        };
+        Self::for_error(&error, url)
+    }
+}
+
 /// Retry configuration
 #[derive(Debug, Clone)]
 pub struct RetryConfig {
@@ -313,6 +576,133 @@ mod tests {
         assert_eq!(error.recovery_hint, Some("Try this to fix".to_string()));
     }
 
+    /// Fails to compile when a DownloadError variant is added, as a
+    /// reminder to give it a suggestion in `FailureSuggestion::for_error`
+    /// and a case here
+    #[test]
+    fn test_every_error_has_a_suggestion() {
+        use crate::utils::error::DownloadError;
+
+        let url = "https://user@files.example.com/a.zip";
+        let s = String::new;
+        let cases = [
+            DownloadError::NetworkError(s()),
+            DownloadError::FileError(s()),
+            DownloadError::UrlError(s()),
+            DownloadError::InvalidUrl(s()),
+            DownloadError::ServerError { status: 500, message: s() },
+            DownloadError::Cancelled,
+            DownloadError::Paused,
+            DownloadError::ChecksumMismatch { expected: s(), actual: s() },
+            DownloadError::MaxRetriesExceeded { retries: 3 },
+            DownloadError::RangeNotSupported,
+            DownloadError::FileExists(s()),
+            DownloadError::InsufficientDiskSpace,
+            DownloadError::FileTooLarge { size: 2, limit: 1 },
+            DownloadError::SegmentFailed { segment_id: 0, message: s() },
+            DownloadError::MergeFailed(s()),
+            DownloadError::Timeout { seconds: 30 },
+            DownloadError::AuthenticationFailed(s()),
+            DownloadError::Unknown(s()),
+            DownloadError::NotFound(s()),
+            DownloadError::PermissionDenied,
+            DownloadError::DiskFull,
+            DownloadError::TorrentError(s()),
+            DownloadError::PathNotAllowed(s()),
+            DownloadError::ExpiredUrl(s()),
+            DownloadError::UrlMismatch(s()),
+            DownloadError::AlreadyActive(s()),
+            DownloadError::FileMissing(s()),
+            DownloadError::DatabaseLocked(s()),
+            DownloadError::WatchdogRecovered(s()),
+            DownloadError::DestinationUnavailable(s()),
+            DownloadError::TlsError(s()),
+        ];
+        for error in &cases {
+            let expected = match error {
+                DownloadError::ExpiredUrl(_)
+                | DownloadError::NotFound(_)
+                | DownloadError::InvalidUrl(_)
+                | DownloadError::UrlError(_)
+                | DownloadError::UrlMismatch(_) => SuggestedAction::UpdateUrl,
+                DownloadError::AuthenticationFailed(_) => SuggestedAction::AddCredentials,
+                DownloadError::InsufficientDiskSpace | DownloadError::DiskFull => {
+                    SuggestedAction::FreeSpace
+                }
+                DownloadError::NetworkError(_)
+                | DownloadError::FileError(_)
+                | DownloadError::ServerError { .. }
+                | DownloadError::ChecksumMismatch { .. }
+                | DownloadError::MaxRetriesExceeded { .. }
+                | DownloadError::RangeNotSupported
+                | DownloadError::SegmentFailed { .. }
+                | DownloadError::MergeFailed(_)
+                | DownloadError::Timeout { .. }
+                | DownloadError::Unknown(_)
+                | DownloadError::FileMissing(_)
+                | DownloadError::WatchdogRecovered(_)
+                | DownloadError::DestinationUnavailable(_) => SuggestedAction::Retry,
+                DownloadError::Cancelled
+                | DownloadError::Paused
+                | DownloadError::FileExists(_)
+                | DownloadError::FileTooLarge { .. }
+                | DownloadError::PermissionDenied
+                | DownloadError::TorrentError(_)
+                | DownloadError::PathNotAllowed(_)
+                | DownloadError::AlreadyActive(_)
+                | DownloadError::DatabaseLocked(_)
+                | DownloadError::TlsError(_) => SuggestedAction::None,
+            };
+            assert_eq!(
+                FailureSuggestion::for_error(error, url).suggested_action,
+                expected,
+                "{:?}",
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn test_failure_suggestions() {
+        use crate::utils::error::DownloadError;
+
+        let url = "https://files.example.com/a.zip";
+        let auth = FailureSuggestion::for_error(
+            &DownloadError::ServerError { status: 401, message: String::new() },
+            url,
+        );
+        assert_eq!(auth.suggested_action, SuggestedAction::AddCredentials);
+        assert_eq!(auth.suggestion.as_deref(), Some("Add credentials for files.example.com"));
+
+        let removed = FailureSuggestion::for_error(
+            &DownloadError::ServerError { status: 410, message: String::new() },
+            url,
+        );
+        assert_eq!(removed.suggestion.as_deref(), Some("The file was removed"));
+        assert_eq!(removed.suggested_action, SuggestedAction::UpdateUrl);
+
+        let tls = FailureSuggestion::for_error(&DownloadError::TlsError(String::new()), url);
+        assert_eq!(tls.suggestion.as_deref(), Some("Check proxy/antivirus interception"));
+
+        assert_eq!(
+            FailureSuggestion::for_message("EXPIRED_URL: link expired", url).suggested_action,
+            SuggestedAction::UpdateUrl
+        );
+        assert_eq!(
+            FailureSuggestion::for_message("ERROR: unable to download video data: HTTP Error 404: Not Found", url)
+                .suggested_action,
+            SuggestedAction::UpdateUrl
+        );
+        assert_eq!(
+            FailureSuggestion::for_message("[Errno 28] No space left on device", url).suggested_action,
+            SuggestedAction::FreeSpace
+        );
+        assert_eq!(
+            FailureSuggestion::for_message("something odd", url),
+            FailureSuggestion { suggestion: None, suggested_action: SuggestedAction::Retry }
+        );
+    }
+
     #[test]
     fn test_retry_config_delays() {
         let config = RetryConfig::default();
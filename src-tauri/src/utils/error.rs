@@ -36,6 +36,11 @@ pub enum AppError {
     #[error("Not implemented: {0}")]
     NotImplemented(String),
 
+    /// Not available with this build or backend; the UI disables the
+    /// control rather than reporting a failure
+    #[error("UNSUPPORTED: {0}")]
+    Unsupported(String),
+
     #[error("Torrent error: {0}")]
     TorrentError(String),
 
@@ -85,6 +90,9 @@ pub enum DownloadError {
     #[error("Insufficient disk space")]
     InsufficientDiskSpace,
 
+    #[error("File is {size} bytes, over the {limit} byte download limit")]
+    FileTooLarge { size: u64, limit: u64 },
+
     #[error("Segment download failed: segment {segment_id} - {message}")]
     SegmentFailed { segment_id: u32, message: String },
 
@@ -111,6 +119,93 @@ pub enum DownloadError {
 
     #[error("Torrent error: {0}")]
     TorrentError(String),
+
+    /// Save path outside the folders the user approved. The code prefix
+    /// lets the frontend recognise it and offer to approve the folder.
+    #[error("PATH_NOT_ALLOWED: {0}")]
+    PathNotAllowed(String),
+
+    /// The download's signed URL stopped working and couldn't be
+    /// refreshed; a new link can be applied with `update_download_url`
+    #[error("EXPIRED_URL: {0}")]
+    ExpiredUrl(String),
+
+    /// A replacement link leads to a different file than the one partly
+    /// downloaded; it can still be applied with `force`, from zero
+    #[error("URL_MISMATCH: {0}")]
+    UrlMismatch(String),
+
+    /// The download is already running
+    #[error("ALREADY_ACTIVE: {0}")]
+    AlreadyActive(String),
+
+    /// A downloaded file is no longer where it was saved
+    #[error("FILE_MISSING: {0}")]
+    FileMissing(String),
+
+    /// Another live process owns the downloads database
+    #[error("DATABASE_LOCKED: {0}")]
+    DatabaseLocked(String),
+
+    /// The watchdog failed a download whose task died or stalled; it can
+    /// be resumed from what was saved
+    #[error("WATCHDOG_RECOVERED: {0}")]
+    WatchdogRecovered(String),
+
+    /// The drive or share the download is saved to isn't there, e.g.
+    /// unplugged; it can be resumed once it is back
+    #[error("DESTINATION_UNAVAILABLE: {0}")]
+    DestinationUnavailable(String),
+
+    /// The TLS handshake or certificate check failed, which usually means
+    /// a proxy or antivirus is intercepting connections
+    #[error("TLS_ERROR: {0}")]
+    TlsError(String),
+}
+
+impl DownloadError {
+    /// A request that failed before a response, with the causes reqwest
+    /// leaves out of its own message
+    pub fn from_request(error: &reqwest::Error) -> Self {
+        let mut causes = Vec::new();
+        let mut source = std::error::Error::source(error);
+        while let Some(cause) = source {
+            causes.push(cause.to_string());
+            source = cause.source();
+        }
+        Self::from_request_causes(error.to_string(), &causes)
+    }
+
+    /// Classified by the causes alone: reqwest's own message carries the
+    /// URL, and a host like `ssl.example.com` says nothing about TLS
+    fn from_request_causes(error: String, causes: &[String]) -> Self {
+        let tls = causes.iter().any(|cause| {
+            let lower = cause.to_ascii_lowercase();
+            ["certificate", "tls", "ssl", "handshake"].iter().any(|word| lower.contains(word))
+        });
+        let message = std::iter::once(error).chain(causes.iter().cloned()).collect::<Vec<_>>().join(": ");
+        if tls {
+            Self::TlsError(message)
+        } else {
+            Self::NetworkError(message)
+        }
+    }
+}
+
+/// Queue bookkeeping error
+#[derive(Error, Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum QueueError {
+    #[error("Download {0} is not in the queue")]
+    NotFound(uuid::Uuid),
+
+    #[error("Download {0} is not waiting in the queue")]
+    NotWaiting(uuid::Uuid),
+}
+
+impl From<QueueError> for String {
+    fn from(err: QueueError) -> Self {
+        err.to_string()
+    }
 }
 
 // Allow DownloadError to be returned from Tauri commands
@@ -133,4 +228,22 @@ impl serde::Serialize for AppError {
     {
         serializer.serialize_str(&self.to_string())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_errors_are_classified_by_their_causes() {
+        let sending = "error sending request for url (https://ssl.example.com/tls/a.iso)".to_string();
+        let refused = DownloadError::from_request_causes(sending.clone(), &["Connection refused (os error 111)".to_string()]);
+        assert!(matches!(
+            refused,
+            DownloadError::NetworkError(message) if message == format!("{}: Connection refused (os error 111)", sending)
+        ));
+
+        let intercepted = DownloadError::from_request_causes(sending, &["invalid peer certificate: UnknownIssuer".to_string()]);
+        assert!(matches!(intercepted, DownloadError::TlsError(_)));
+    }
+}
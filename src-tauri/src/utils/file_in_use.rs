@@ -0,0 +1,112 @@
+// src-tauri/src/utils/file_in_use.rs
+// Whether another program has a file open, so a finished download doesn't
+// replace a file the user is watching or editing. Windows answers exactly
+// through sharing modes; elsewhere the check is best-effort and says "free"
+// when it can't tell.
+
+use std::path::{Path, PathBuf};
+
+/// Where a finished download waits while the file it replaces is in use:
+/// a hidden sibling, so the swap is a rename on the same file system
+pub fn pending_path(target: &Path) -> PathBuf {
+    let name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    target.with_file_name(format!(".{}.pending", name))
+}
+
+/// Blocking: may scan other processes' open files
+pub fn is_in_use(path: &Path) -> bool {
+    path.is_file() && platform::is_in_use(path)
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::path::Path;
+    use winapi::shared::winerror::{ERROR_LOCK_VIOLATION, ERROR_SHARING_VIOLATION};
+
+    /// Opening without sharing anything fails while any other handle is open
+    pub fn is_in_use(path: &Path) -> bool {
+        match std::fs::OpenOptions::new().read(true).share_mode(0).open(path) {
+            Ok(_) => false,
+            Err(e) => matches!(
+                e.raw_os_error().map(|code| code as u32),
+                Some(ERROR_SHARING_VIOLATION) | Some(ERROR_LOCK_VIOLATION)
+            ),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::path::Path;
+
+    /// Look for the file among other processes' open descriptors. Processes
+    /// of other users can't be read and don't count.
+    pub fn is_in_use(path: &Path) -> bool {
+        let Ok(target) = path.canonicalize() else {
+            return false;
+        };
+        let own_pid = std::process::id().to_string();
+        let Ok(processes) = std::fs::read_dir("/proc") else {
+            return false;
+        };
+        processes
+            .flatten()
+            .filter(|p| {
+                let name = p.file_name();
+                let name = name.to_string_lossy();
+                name.bytes().all(|b| b.is_ascii_digit()) && name != own_pid
+            })
+            .filter_map(|p| std::fs::read_dir(p.path().join("fd")).ok())
+            .flat_map(|fds| fds.flatten())
+            .any(|fd| std::fs::read_link(fd.path()).is_ok_and(|link| link == target))
+    }
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+mod platform {
+    use std::os::unix::io::AsRawFd;
+    use std::path::Path;
+
+    /// Programs that lock what they have open refuse an exclusive lock;
+    /// ones that don't lock can't be seen
+    pub fn is_in_use(path: &Path) -> bool {
+        let Ok(file) = std::fs::File::open(path) else {
+            return false;
+        };
+        let fd = file.as_raw_fd();
+        // SAFETY: fd stays open for both calls
+        if unsafe { libc::flock(fd, libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+            unsafe { libc::flock(fd, libc::LOCK_UN) };
+            return false;
+        }
+        std::io::Error::last_os_error().raw_os_error() == Some(libc::EWOULDBLOCK)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_path_is_a_hidden_sibling() {
+        assert_eq!(
+            pending_path(Path::new("/downloads/movie.mkv")),
+            PathBuf::from("/downloads/.movie.mkv.pending")
+        );
+    }
+
+    #[test]
+    fn test_closed_and_missing_files_are_free() {
+        let dir = std::env::temp_dir().join(format!("afk_in_use_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("a.bin");
+        assert!(!is_in_use(&file));
+        std::fs::write(&file, b"data").unwrap();
+        assert!(!is_in_use(&file));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -40,6 +40,56 @@ pub async fn get_unique_filename(path: &Path) -> PathBuf {
     }
 }
 
+/// The `n`th numbered variant of a path; 0 is the path itself
+/// e.g., file.zip → file (2).zip
+pub fn numbered_path(path: &Path, n: u32) -> PathBuf {
+    if n == 0 {
+        return path.to_path_buf();
+    }
+
+    let stem = path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let extension = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    let parent = path.parent().unwrap_or(Path::new("."));
+
+    parent.join(format!("{} ({}){}", stem, n, extension))
+}
+
+/// Path to move an outdated copy of a file to, tagged with the date it was
+/// downloaded. e.g., file.zip → file (old 2024-01-02).zip
+pub fn get_versioned_path(path: &Path, date: chrono::NaiveDate) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let extension = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    let parent = path.parent().unwrap_or(Path::new("."));
+
+    let candidate = parent.join(format!("{} (old {}){}", stem, date, extension));
+    if !candidate.exists() {
+        return candidate;
+    }
+
+    let mut counter = 2u32;
+    loop {
+        let new_path = parent.join(format!("{} (old {} {}){}", stem, date, counter, extension));
+        if !new_path.exists() || counter >= 10000 {
+            return new_path;
+        }
+        counter += 1;
+    }
+}
+
 /// Get available disk space at the given path
 pub async fn get_available_space(path: &Path) -> std::io::Result<u64> {
     // Use the parent directory if the file doesn't exist yet
@@ -78,6 +128,109 @@ pub async fn get_available_space(path: &Path) -> std::io::Result<u64> {
     }
 }
 
+/// Whether `a` and `b` are on the same volume, judged by their nearest
+/// existing folders. When that can't be told they are taken to be the
+/// same, which makes space checks ask for more rather than less.
+pub fn same_volume(a: &Path, b: &Path) -> bool {
+    match (volume_id(a), volume_id(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => true,
+    }
+}
+
+#[cfg(unix)]
+fn volume_id(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    path.ancestors()
+        .find_map(|dir| std::fs::metadata(dir).ok())
+        .map(|metadata| metadata.dev())
+}
+
+#[cfg(not(unix))]
+fn volume_id(path: &Path) -> Option<u64> {
+    use std::hash::{Hash, Hasher};
+
+    // The drive or share prefix
+    let prefix = path.components().next()?;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    prefix.as_os_str().to_ascii_lowercase().hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+/// Where removable drives are mounted, with how many folder levels below
+/// that are the user's rather than a drive's
+#[cfg(unix)]
+const MOUNT_ROOTS: &[(&str, usize)] = &[("/media", 1), ("/run/media", 1), ("/mnt", 0), ("/Volumes", 0)];
+
+/// Whether the drive or share `path` is on has gone away, e.g. a USB drive
+/// that was unplugged or a network share that disconnected
+#[cfg(unix)]
+pub fn volume_missing(path: &Path) -> bool {
+    let Some(nearest) = path.ancestors().find(|dir| dir.exists()) else {
+        return false;
+    };
+    if nearest == path {
+        return false;
+    }
+    // Unmounting removes the mount point, leaving its parent
+    MOUNT_ROOTS.iter().any(|(root, user_levels)| {
+        let root = Path::new(root);
+        nearest.starts_with(root)
+            && nearest.components().count() <= root.components().count() + user_levels
+    })
+}
+
+#[cfg(not(unix))]
+pub fn volume_missing(path: &Path) -> bool {
+    // The drive or share root, e.g. `D:\` or `\\server\share\`
+    let root: PathBuf = path.components().take(2).collect();
+    matches!(path.components().next(), Some(std::path::Component::Prefix(_))) && !root.exists()
+}
+
+/// Move a file or folder to `to`, copying and then deleting the original
+/// when a rename isn't possible (e.g. across file systems)
+pub async fn move_path(from: &Path, to: &Path) -> std::io::Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    if fs::rename(from, to).await.is_ok() {
+        return Ok(());
+    }
+
+    let (from, to) = (from.to_path_buf(), to.to_path_buf());
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = copy_recursive(&from, &to) {
+            let _ = remove_path(&to);
+            return Err(e);
+        }
+        remove_path(&from)
+    })
+    .await
+    .map_err(std::io::Error::other)?
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> std::io::Result<()> {
+    if from.is_dir() {
+        std::fs::create_dir_all(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        std::fs::copy(from, to).map(|_| ())
+    }
+}
+
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
 /// Ensure directory exists
 pub async fn ensure_dir(path: &Path) -> std::io::Result<()> {
     if !path.exists() {
@@ -109,4 +262,201 @@ pub fn get_default_download_dir() -> PathBuf {
     dirs::download_dir()
         .or_else(|| dirs::home_dir().map(|h| h.join("Downloads")))
         .unwrap_or_else(|| PathBuf::from("."))
-}
\ No newline at end of file
+}
+
+/// Standard folders the built-in categories save to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FolderKind {
+    Downloads,
+    Videos,
+    Music,
+    Documents,
+    Images,
+    Software,
+    Compressed,
+}
+
+impl std::str::FromStr for FolderKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "downloads" | "download" => Ok(FolderKind::Downloads),
+            "videos" | "video" | "movies" => Ok(FolderKind::Videos),
+            "music" | "audio" => Ok(FolderKind::Music),
+            "documents" | "document" => Ok(FolderKind::Documents),
+            "images" | "image" | "pictures" => Ok(FolderKind::Images),
+            "software" | "programs" => Ok(FolderKind::Software),
+            "compressed" | "archives" => Ok(FolderKind::Compressed),
+            _ => Err(format!("Unknown folder kind: {}", s)),
+        }
+    }
+}
+
+impl FolderKind {
+    /// The folder a built-in category saves to; None for custom categories
+    /// and `default`, which follows the global download folder
+    pub fn for_category(category_id: &str) -> Option<Self> {
+        match category_id {
+            "default" => None,
+            id => id.parse().ok(),
+        }
+    }
+}
+
+/// Platform folder for `kind`: XDG user dirs on Linux, Known Folders on
+/// Windows and the home folders on macOS. Software and archives go in
+/// subfolders of Downloads, as no platform has a folder for them. Falls
+/// back to Downloads when the platform folder can't be resolved.
+pub fn get_default_folder(kind: FolderKind) -> PathBuf {
+    let known = match kind {
+        FolderKind::Downloads => None,
+        FolderKind::Videos => dirs::video_dir().or_else(|| home_folder(VIDEOS_FOLDER)),
+        FolderKind::Music => dirs::audio_dir().or_else(|| home_folder("Music")),
+        FolderKind::Documents => dirs::document_dir().or_else(|| home_folder("Documents")),
+        FolderKind::Images => dirs::picture_dir().or_else(|| home_folder("Pictures")),
+        FolderKind::Software => Some(get_default_download_dir().join("Programs")),
+        FolderKind::Compressed => Some(get_default_download_dir().join("Compressed")),
+    };
+    known.unwrap_or_else(get_default_download_dir)
+}
+
+#[cfg(target_os = "macos")]
+const VIDEOS_FOLDER: &str = "Movies";
+#[cfg(not(target_os = "macos"))]
+const VIDEOS_FOLDER: &str = "Videos";
+
+fn home_folder(name: &str) -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(name))
+}
+
+/// Check that `path` can name a save folder on this platform, without
+/// touching the file system
+pub fn validate_folder_path(path: &Path) -> Result<(), String> {
+    if !path.is_absolute() {
+        return Err(format!("{} is not an absolute path", path.display()));
+    }
+    platform_path_check(path)
+}
+
+#[cfg(windows)]
+fn platform_path_check(path: &Path) -> Result<(), String> {
+    use std::path::Component;
+
+    const RESERVED: [&str; 22] = [
+        "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1",
+        "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+    ];
+    // Paths past MAX_PATH break Explorer and older tools
+    if path.as_os_str().len() > 248 {
+        return Err(format!("{} is too long for a folder path", path.display()));
+    }
+    for component in path.components() {
+        let Component::Normal(name) = component else { continue };
+        let name = name.to_string_lossy();
+        if name.chars().any(|c| matches!(c, '<' | '>' | ':' | '"' | '|' | '?' | '*') || c < ' ') {
+            return Err(format!("{:?} contains characters Windows doesn't allow", name));
+        }
+        let stem = name.split('.').next().unwrap_or_default().trim_end();
+        if RESERVED.iter().any(|r| r.eq_ignore_ascii_case(stem)) {
+            return Err(format!("{:?} is a reserved name on Windows", name));
+        }
+        if name.ends_with(['.', ' ']) {
+            return Err(format!("{:?} can't end with a dot or space on Windows", name));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn platform_path_check(path: &Path) -> Result<(), String> {
+    use std::os::unix::ffi::OsStrExt;
+
+    if path.as_os_str().as_bytes().contains(&0) {
+        return Err(format!("{} contains a NUL byte", path.display()));
+    }
+    // Finder shows ':' as '/', so names with one end up somewhere unexpected
+    #[cfg(target_os = "macos")]
+    if path.components().any(|c| c.as_os_str().as_bytes().contains(&b':')) {
+        return Err(format!("{} contains ':', which macOS doesn't allow in names", path.display()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_categories_map_to_folders() {
+        assert_eq!(FolderKind::for_category("videos"), Some(FolderKind::Videos));
+        assert_eq!(FolderKind::for_category("compressed"), Some(FolderKind::Compressed));
+        assert_eq!(FolderKind::for_category("default"), None);
+        assert_eq!(FolderKind::for_category("3f2c9d1e-custom"), None);
+
+        let downloads = get_default_download_dir();
+        assert_eq!(get_default_folder(FolderKind::Downloads), downloads);
+        assert_eq!(get_default_folder(FolderKind::Software), downloads.join("Programs"));
+    }
+
+    #[test]
+    fn test_relative_folder_rejected() {
+        assert!(validate_folder_path(Path::new("Downloads/Videos")).is_err());
+    }
+
+    #[test]
+    fn test_same_volume_for_missing_folders() {
+        let dir = std::env::temp_dir();
+        // Folders that don't exist yet are on their parent's volume
+        assert!(same_volume(&dir, &dir.join("not/yet/made")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_volume_missing_only_under_mount_roots() {
+        let dir = std::env::temp_dir();
+        assert!(!volume_missing(&dir));
+        assert!(!volume_missing(&dir.join("not/yet/made")));
+        let unplugged = Path::new("/mnt/sdl-test-unplugged-drive/Downloads");
+        assert_eq!(volume_missing(unplugged), Path::new("/mnt").is_dir());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_linux_folders() {
+        // dirs reads XDG_*_DIR from user-dirs.dirs; without one the folder is
+        // still under the home directory
+        let videos = get_default_folder(FolderKind::Videos);
+        match dirs::video_dir() {
+            Some(xdg) => assert_eq!(videos, xdg),
+            None => assert_eq!(videos, dirs::home_dir().unwrap().join("Videos")),
+        }
+        assert!(validate_folder_path(Path::new("/home/user/Vid:eos?")).is_ok());
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn test_macos_folders() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(get_default_folder(FolderKind::Videos), home.join("Movies"));
+        assert_eq!(get_default_folder(FolderKind::Music), home.join("Music"));
+        assert!(validate_folder_path(Path::new("/Users/user/a:b")).is_err());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_windows_folders() {
+        // Known Folders can be redirected, e.g. to OneDrive, so only check
+        // that they resolve to a valid absolute path
+        for kind in [FolderKind::Videos, FolderKind::Music, FolderKind::Documents, FolderKind::Images] {
+            let folder = get_default_folder(kind);
+            assert!(validate_folder_path(&folder).is_ok(), "{}", folder.display());
+        }
+        assert!(validate_folder_path(Path::new(r"C:\Users\me\CON")).is_err());
+        assert!(validate_folder_path(Path::new(r"C:\Users\me\nul.txt")).is_err());
+        assert!(validate_folder_path(Path::new(r"C:\Users\me\a|b")).is_err());
+        assert!(validate_folder_path(Path::new(r"C:\Users\me\trailing.")).is_err());
+        assert!(validate_folder_path(Path::new(r"C:\Users\me\Videos")).is_ok());
+    }
+}
@@ -0,0 +1,117 @@
+// src-tauri/src/utils/focus_mode.rs
+// Whether the OS is in do-not-disturb / focus mode. Each platform is asked
+// through what it exposes without extra permissions; None means the state
+// can't be determined and callers should treat it as not active.
+
+#[allow(unused_imports)]
+use crate::utils::network_info::command_output;
+
+/// Blocking: may run platform tools
+pub fn do_not_disturb_active() -> Option<bool> {
+    platform::do_not_disturb_active()
+}
+
+/// Whether Focus assertions in macOS's Assertions.json hold any records
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn assertions_active(json: &str) -> Option<bool> {
+    let value: serde_json::Value = serde_json::from_str(json).ok()?;
+    let records = value.get("data")?.as_array()?.iter().filter_map(|entry| {
+        entry.get("storeAssertionRecords").and_then(|r| r.as_array())
+    });
+    let mut seen = false;
+    for record in records {
+        if !record.is_empty() {
+            return Some(true);
+        }
+        seen = true;
+    }
+    seen.then_some(false)
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::*;
+
+    pub fn do_not_disturb_active() -> Option<bool> {
+        // GNOME turns banners off for Do Not Disturb
+        if let Some(banners) =
+            command_output("gsettings", &["get", "org.gnome.desktop.notifications", "show-banners"])
+        {
+            return Some(banners.trim() == "false");
+        }
+        // KDE and other servers that implement the inhibition property
+        command_output(
+            "gdbus",
+            &[
+                "call", "--session",
+                "--dest", "org.freedesktop.Notifications",
+                "--object-path", "/org/freedesktop/Notifications",
+                "--method", "org.freedesktop.DBus.Properties.Get",
+                "org.freedesktop.Notifications", "Inhibited",
+            ],
+        )
+        .map(|reply| reply.contains("true"))
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::*;
+
+    pub fn do_not_disturb_active() -> Option<bool> {
+        // Focus (Monterey and later) records active modes here; reading it
+        // can need Full Disk Access, so fall back to the older preference
+        let assertions = dirs::home_dir()?.join("Library/DoNotDisturb/DB/Assertions.json");
+        if let Some(active) = std::fs::read_to_string(assertions).ok().and_then(|json| assertions_active(&json)) {
+            return Some(active);
+        }
+        command_output(
+            "defaults",
+            &["-currentHost", "read", "com.apple.notificationcenterui", "doNotDisturb"],
+        )
+        .map(|value| value.trim() == "1")
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    pub fn do_not_disturb_active() -> Option<bool> {
+        use winapi::um::shellapi::{
+            SHQueryUserNotificationState, QUNS_BUSY, QUNS_PRESENTATION_MODE, QUNS_QUIET_TIME,
+            QUNS_RUNNING_D3D_FULL_SCREEN,
+        };
+
+        let mut state = 0;
+        // SAFETY: `state` is a valid, writable QUERY_USER_NOTIFICATION_STATE
+        if unsafe { SHQueryUserNotificationState(&mut state) } != 0 {
+            return None;
+        }
+        // Focus assist's own toggle isn't exposed; full screen apps,
+        // presentations and quiet hours are
+        Some(matches!(
+            state,
+            QUNS_BUSY | QUNS_RUNNING_D3D_FULL_SCREEN | QUNS_PRESENTATION_MODE | QUNS_QUIET_TIME
+        ))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    pub fn do_not_disturb_active() -> Option<bool> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_macos_focus_assertions() {
+        let active = r#"{"data":[{"storeAssertionRecords":[{"assertionDetails":{"assertionDetailsModeIdentifier":"com.apple.focus.work"}}]}]}"#;
+        assert_eq!(assertions_active(active), Some(true));
+        assert_eq!(assertions_active(r#"{"data":[{"storeAssertionRecords":[]}]}"#), Some(false));
+        assert_eq!(assertions_active(r#"{"data":[{}]}"#), None);
+        assert_eq!(assertions_active("not json"), None);
+    }
+}
@@ -1,9 +1,19 @@
 // Structured Logging and Monitoring System
 use serde::{Serialize, Deserialize};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use std::collections::VecDeque;
+use std::io::Write;
+
+use crate::utils::privacy;
+
+/// Settings key: JSON-encoded [`LogRetention`]
+pub const LOG_RETENTION_KEY: &str = "log_retention";
+
+/// Entries waiting for the store before the oldest are dropped
+const MAX_PENDING_LOGS: usize = 1000;
 
 /// Log level
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
@@ -25,6 +35,96 @@ impl LogLevel {
             LogLevel::Error => "ERROR",
         }
     }
+
+    const ALL: [LogLevel; 5] = [
+        LogLevel::Trace,
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warn,
+        LogLevel::Error,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// How long entries of one level are kept
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LevelRetention {
+    pub max_rows: usize,
+    pub max_age_secs: u64,
+}
+
+impl LevelRetention {
+    pub const fn new(max_rows: usize, max_age_secs: u64) -> Self {
+        Self { max_rows, max_age_secs }
+    }
+}
+
+/// Per-level retention; errors and warnings outlive debug noise
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LogRetention {
+    pub trace: LevelRetention,
+    pub debug: LevelRetention,
+    pub info: LevelRetention,
+    pub warn: LevelRetention,
+    pub error: LevelRetention,
+}
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+
+impl Default for LogRetention {
+    fn default() -> Self {
+        Self {
+            trace: LevelRetention::new(500, HOUR),
+            debug: LevelRetention::new(1000, DAY),
+            info: LevelRetention::new(2000, 7 * DAY),
+            warn: LevelRetention::new(2000, 14 * DAY),
+            error: LevelRetention::new(5000, 30 * DAY),
+        }
+    }
+}
+
+impl LogRetention {
+    pub fn for_level(&self, level: LogLevel) -> LevelRetention {
+        match level {
+            LogLevel::Trace => self.trace,
+            LogLevel::Debug => self.debug,
+            LogLevel::Info => self.info,
+            LogLevel::Warn => self.warn,
+            LogLevel::Error => self.error,
+        }
+    }
+
+    /// Upper bound on stored entries across all levels
+    pub fn total_rows(&self) -> usize {
+        LogLevel::ALL
+            .iter()
+            .fold(0usize, |total, l| total.saturating_add(self.for_level(*l).max_rows))
+    }
+}
+
+/// Entries removed by one prune pass
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PruneSummary {
+    pub removed: usize,
+    pub remaining: usize,
+}
+
+/// Size of the log store, reported alongside performance metrics
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogStoreStats {
+    pub rows: usize,
+    pub approx_bytes: u64,
+    /// Entries dropped because the store was busy and the insert queue full
+    pub dropped: u64,
+    pub last_prune: Option<DateTime<Utc>>,
 }
 
 /// Structured log entry
@@ -52,6 +152,102 @@ impl LogEntry {
         self.metadata = Some(metadata);
         self
     }
+
+    fn approx_bytes(&self) -> u64 {
+        let metadata = self.metadata.as_ref().map_or(0, |m| m.to_string().len());
+        (self.category.len() + self.message.len() + metadata) as u64
+    }
+}
+
+/// Standard output for `tracing`, each line filtered at the logging
+/// privacy level
+pub struct PrivateStdout;
+
+impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for PrivateStdout {
+    type Writer = PrivateWriter<std::io::Stdout>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        PrivateWriter(std::io::stdout())
+    }
+}
+
+/// Filters what goes through it; `tracing` writes a whole line at a time
+pub struct PrivateWriter<W>(pub W);
+
+impl<W: Write> Write for PrivateWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let level = privacy::logging_privacy();
+        if level == privacy::PrivacyLevel::Full {
+            return self.0.write(buf);
+        }
+        let text = privacy::redact_text(level, &String::from_utf8_lossy(buf));
+        self.0.write_all(text.as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Log entries kept per level, each queue oldest first
+#[derive(Default)]
+struct LogStore {
+    levels: [VecDeque<LogEntry>; 5],
+}
+
+impl LogStore {
+    fn len(&self) -> usize {
+        self.levels.iter().map(VecDeque::len).sum()
+    }
+
+    fn push(&mut self, entry: LogEntry, retention: &LogRetention, max_total: usize) {
+        let max_rows = retention.for_level(entry.level).max_rows;
+        let queue = &mut self.levels[entry.level.index()];
+        queue.push_back(entry);
+        while queue.len() > max_rows {
+            queue.pop_front();
+        }
+
+        // Over the global cap, the least severe entries go first
+        while self.len() > max_total {
+            match self.levels.iter_mut().find(|q| !q.is_empty()) {
+                Some(queue) => queue.pop_front(),
+                None => break,
+            };
+        }
+    }
+
+    fn prune(&mut self, retention: &LogRetention, now: DateTime<Utc>) -> usize {
+        let mut removed = 0;
+        for level in LogLevel::ALL {
+            let policy = retention.for_level(level);
+            let cutoff = i64::try_from(policy.max_age_secs)
+                .ok()
+                .and_then(Duration::try_seconds)
+                .and_then(|age| now.checked_sub_signed(age));
+            let queue = &mut self.levels[level.index()];
+
+            while queue.len() > policy.max_rows
+                || queue.front().zip(cutoff).is_some_and(|(e, cutoff)| e.timestamp < cutoff)
+            {
+                queue.pop_front();
+                removed += 1;
+            }
+        }
+        removed
+    }
+
+    /// All entries in the order they were logged
+    fn oldest_first(&self) -> Vec<&LogEntry> {
+        let mut entries: Vec<&LogEntry> = self.levels.iter().flatten().collect();
+        entries.sort_by_key(|e| e.timestamp);
+        entries
+    }
+
+    fn clear(&mut self) {
+        self.levels.iter_mut().for_each(VecDeque::clear);
+    }
 }
 
 /// Download history entry
@@ -83,29 +279,94 @@ pub struct PerformanceMetrics {
 }
 
 /// Logger with in-memory buffer
+///
+/// Writers never wait on the store: entries go through a bounded insert
+/// queue that drops its oldest entry when full, and are moved into the
+/// store whenever its lock is free.
 pub struct Logger {
-    logs: Arc<RwLock<VecDeque<LogEntry>>>,
+    logs: Arc<RwLock<LogStore>>,
+    pending: parking_lot::Mutex<VecDeque<LogEntry>>,
+    dropped: AtomicU64,
+    retention: parking_lot::RwLock<LogRetention>,
+    last_prune: parking_lot::Mutex<Option<DateTime<Utc>>>,
     history: Arc<RwLock<VecDeque<DownloadHistoryEntry>>>,
     metrics: Arc<RwLock<VecDeque<PerformanceMetrics>>>,
-    max_logs: usize,
+    max_logs: AtomicUsize,
     max_history: usize,
     max_metrics: usize,
 }
 
 impl Logger {
     pub fn new() -> Self {
+        let retention = LogRetention::default();
         Self {
-            logs: Arc::new(RwLock::new(VecDeque::new())),
+            logs: Arc::new(RwLock::new(LogStore::default())),
+            pending: parking_lot::Mutex::new(VecDeque::new()),
+            dropped: AtomicU64::new(0),
+            max_logs: AtomicUsize::new(retention.total_rows()),
+            retention: parking_lot::RwLock::new(retention),
+            last_prune: parking_lot::Mutex::new(None),
             history: Arc::new(RwLock::new(VecDeque::new())),
             metrics: Arc::new(RwLock::new(VecDeque::new())),
-            max_logs: 1000,
             max_history: 500,
             max_metrics: 100,
         }
     }
 
-    /// Log a message
-    pub async fn log(&self, entry: LogEntry) {
+    pub fn retention(&self) -> LogRetention {
+        *self.retention.read()
+    }
+
+    /// Replace the retention policy; the global row cap applies to the
+    /// next insert, the age and per-level limits on the next prune
+    pub fn set_retention(&self, retention: LogRetention) {
+        self.max_logs.store(retention.total_rows(), Ordering::Relaxed);
+        *self.retention.write() = retention;
+    }
+
+    fn move_pending(&self, store: &mut LogStore) {
+        let pending: Vec<LogEntry> = self.pending.lock().drain(..).collect();
+        let retention = self.retention();
+        let max_logs = self.max_logs.load(Ordering::Relaxed);
+        for entry in pending {
+            store.push(entry, &retention, max_logs);
+        }
+    }
+
+    /// Lock the store for reading once queued entries have been moved in
+    async fn read_logs(&self) -> tokio::sync::RwLockReadGuard<'_, LogStore> {
+        let mut store = self.logs.write().await;
+        self.move_pending(&mut store);
+        store.downgrade()
+    }
+
+    /// Remove entries past their level's age or row limit
+    pub async fn prune(&self, now: DateTime<Utc>) -> PruneSummary {
+        let mut store = self.logs.write().await;
+        self.move_pending(&mut store);
+        let removed = store.prune(&self.retention(), now);
+        *self.last_prune.lock() = Some(now);
+        PruneSummary { removed, remaining: store.len() }
+    }
+
+    pub async fn store_stats(&self) -> LogStoreStats {
+        let store = self.read_logs().await;
+        LogStoreStats {
+            rows: store.len(),
+            approx_bytes: store.levels.iter().flatten().map(LogEntry::approx_bytes).sum(),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            last_prune: *self.last_prune.lock(),
+        }
+    }
+
+    /// Log a message, filtered at the logging privacy level
+    pub async fn log(&self, mut entry: LogEntry) {
+        let level = privacy::logging_privacy();
+        entry.message = privacy::redact_text(level, &entry.message);
+        if let Some(metadata) = entry.metadata.as_mut() {
+            privacy::filter_payload(level, metadata);
+        }
+
         // Also log to tracing
         match entry.level {
             LogLevel::Trace => tracing::trace!("[{}] {}", entry.category, entry.message),
@@ -115,12 +376,18 @@ impl Logger {
             LogLevel::Error => tracing::error!("[{}] {}", entry.category, entry.message),
         }
 
-        let mut logs = self.logs.write().await;
-        logs.push_back(entry);
+        {
+            let mut pending = self.pending.lock();
+            if pending.len() >= MAX_PENDING_LOGS {
+                pending.pop_front();
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            pending.push_back(entry);
+        }
 
-        // Keep only max_logs entries
-        while logs.len() > self.max_logs {
-            logs.pop_front();
+        // Otherwise the next write or read moves the queued entries in
+        if let Ok(mut store) = self.logs.try_write() {
+            self.move_pending(&mut store);
         }
     }
 
@@ -146,9 +413,9 @@ impl Logger {
 
     /// Get recent logs
     pub async fn get_logs(&self, limit: Option<usize>) -> Vec<LogEntry> {
-        let logs = self.logs.read().await;
-        let limit = limit.unwrap_or(100).min(logs.len());
-        logs.iter().rev().take(limit).cloned().collect()
+        let logs = self.read_logs().await;
+        let limit = limit.unwrap_or(100);
+        logs.oldest_first().into_iter().rev().take(limit).cloned().collect()
     }
 
     /// Get download history
@@ -167,8 +434,8 @@ impl Logger {
 
     /// Get logs by level
     pub async fn get_logs_by_level(&self, level: LogLevel) -> Vec<LogEntry> {
-        let logs = self.logs.read().await;
-        logs.iter()
+        let logs = self.read_logs().await;
+        logs.oldest_first().into_iter()
             .filter(|entry| entry.level >= level)
             .cloned()
             .collect()
@@ -176,16 +443,33 @@ impl Logger {
 
     /// Get logs by category
     pub async fn get_logs_by_category(&self, category: &str) -> Vec<LogEntry> {
-        let logs = self.logs.read().await;
-        logs.iter()
+        let logs = self.read_logs().await;
+        logs.oldest_first().into_iter()
             .filter(|entry| entry.category == category)
             .cloned()
             .collect()
     }
 
+    /// Get the entries logged for one download
+    pub async fn get_logs_for_download(&self, download_id: &str) -> Vec<LogEntry> {
+        let logs = self.read_logs().await;
+        logs.oldest_first().into_iter()
+            .filter(|entry| {
+                entry.metadata
+                    .as_ref()
+                    .and_then(|m| m.get("download_id"))
+                    .and_then(|id| id.as_str())
+                    == Some(download_id)
+            })
+            .cloned()
+            .collect()
+    }
+
     /// Clear all logs
     pub async fn clear_logs(&self) {
-        self.logs.write().await.clear();
+        let mut store = self.logs.write().await;
+        self.pending.lock().clear();
+        store.clear();
     }
 
     /// Clear history
@@ -274,8 +558,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_logger_max_capacity() {
-        let mut logger = Logger::new();
-        logger.max_logs = 5;
+        let logger = Logger::new();
+        logger.max_logs.store(5, Ordering::Relaxed);
 
         for i in 0..10 {
             let entry = LogEntry::new(LogLevel::Info, "test", format!("Message {}", i));
@@ -286,6 +570,28 @@ mod tests {
         assert_eq!(logs.len(), 5);
     }
 
+    #[tokio::test]
+    async fn test_set_retention_updates_row_cap() {
+        let logger = Logger::new();
+        let default_total = LogRetention::default().total_rows();
+        let generous = LevelRetention::new(default_total, u64::MAX);
+        logger.set_retention(LogRetention {
+            trace: generous,
+            debug: generous,
+            info: generous,
+            warn: generous,
+            error: generous,
+        });
+
+        // More rows than the default policy allows in total
+        for level in [LogLevel::Info, LogLevel::Warn] {
+            for i in 0..default_total / 2 + 100 {
+                logger.log(LogEntry::new(level, "test", format!("Message {}", i))).await;
+            }
+        }
+        assert_eq!(logger.store_stats().await.rows, default_total + 200);
+    }
+
     #[tokio::test]
     async fn test_filter_by_level() {
         let logger = Logger::new();
@@ -313,4 +619,91 @@ mod tests {
         let download_logs = logger.get_logs_by_category("download").await;
         assert_eq!(download_logs.len(), 2);
     }
+
+    #[tokio::test]
+    async fn test_filter_by_download() {
+        let logger = Logger::new();
+
+        logger.log(LogEntry::new(LogLevel::Warn, "download", "Attempt 1/6 failed")
+            .with_metadata(serde_json::json!({ "download_id": "a" }))).await;
+        logger.log(LogEntry::new(LogLevel::Warn, "download", "Attempt 1/6 failed")
+            .with_metadata(serde_json::json!({ "download_id": "b" }))).await;
+        logger.log(LogEntry::new(LogLevel::Info, "download", "No metadata")).await;
+
+        let logs = logger.get_logs_for_download("a").await;
+        assert_eq!(logs.len(), 1);
+        assert!(logger.get_logs_for_download("c").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_prune_obeys_per_level_policy() {
+        let logger = Logger::new();
+        let unbounded = LevelRetention::new(usize::MAX, u64::MAX);
+        logger.set_retention(LogRetention {
+            trace: unbounded,
+            debug: unbounded,
+            info: unbounded,
+            warn: unbounded,
+            error: unbounded,
+        });
+
+        // 100k entries over the last 60 days, oldest first
+        let now = Utc::now();
+        let levels = [LogLevel::Debug, LogLevel::Info, LogLevel::Warn, LogLevel::Error];
+        for i in 0..100_000i64 {
+            let mut entry = LogEntry::new(levels[i as usize % levels.len()], "test", "seeded");
+            entry.timestamp = now - Duration::minutes(60 * 24 * 60 * (100_000 - i) / 100_000);
+            logger.log(entry).await;
+        }
+        assert_eq!(logger.store_stats().await.rows, 100_000);
+
+        let policy = LogRetention {
+            debug: LevelRetention::new(10_000, DAY),
+            info: LevelRetention::new(1_000, 7 * DAY),
+            warn: LevelRetention::new(100_000, 14 * DAY),
+            error: LevelRetention::new(100_000, 30 * DAY),
+            ..LogRetention::default()
+        };
+        logger.set_retention(policy);
+        let summary = logger.prune(now).await;
+
+        let mut counts = [0usize; 5];
+        for entry in logger.get_logs(Some(usize::MAX)).await {
+            let age = now - entry.timestamp;
+            let max_age = policy.for_level(entry.level).max_age_secs as i64;
+            assert!(age.num_seconds() <= max_age, "{:?} entry kept too long", entry.level);
+            counts[entry.level.index()] += 1;
+        }
+
+        // Debug is age-bound (~416 per day), info is row-bound
+        assert!(counts[LogLevel::Debug.index()] > 0);
+        assert!(counts[LogLevel::Debug.index()] <= 420);
+        assert_eq!(counts[LogLevel::Info.index()], 1_000);
+        // Errors outlive warnings, which outlive debug
+        assert!(counts[LogLevel::Error.index()] > counts[LogLevel::Warn.index()]);
+        assert!(counts[LogLevel::Warn.index()] > counts[LogLevel::Debug.index()]);
+
+        let stats = logger.store_stats().await;
+        assert_eq!(stats.rows, summary.remaining);
+        assert_eq!(summary.removed + summary.remaining, 100_000);
+        assert_eq!(stats.last_prune, Some(now));
+    }
+
+    #[tokio::test]
+    async fn test_writers_do_not_wait_for_busy_store() {
+        let logger = Logger::new();
+        let guard = logger.logs.write().await;
+
+        for i in 0..MAX_PENDING_LOGS + 10 {
+            logger.log(LogEntry::new(LogLevel::Info, "test", format!("Message {}", i))).await;
+        }
+        drop(guard);
+
+        let stats = logger.store_stats().await;
+        assert_eq!(stats.rows, MAX_PENDING_LOGS);
+        assert_eq!(stats.dropped, 10);
+        // The oldest entries were the ones dropped
+        let oldest = logger.get_logs(Some(usize::MAX)).await.pop().unwrap();
+        assert_eq!(oldest.message, "Message 10");
+    }
 }
@@ -1,11 +1,21 @@
 // src-tauri/src/utils/mod.rs
 
+pub mod binaries;
 pub mod error;
+pub mod api_schema;
 pub mod enhanced_error;
 pub mod logging;
 pub mod security;
 pub mod performance;
 pub mod constants;
 pub mod file_utils;
+pub mod dir_size;
+pub mod network_info;
+pub mod focus_mode;
+pub mod permissions;
 pub mod format_utils;
-pub mod ytdlp_manager;
\ No newline at end of file
+pub mod shell_open;
+pub mod ytdlp_manager;
+pub mod file_in_use;
+pub mod privacy;
+pub mod sandbox;
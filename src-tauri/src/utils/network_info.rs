@@ -0,0 +1,241 @@
+// src-tauri/src/utils/network_info.rs
+// Which network the machine is on and whether it runs on battery. Both use
+// the tools each OS ships with; anything that can't be determined comes
+// back as unknown rather than an error.
+
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, UdpSocket};
+use std::process::Command;
+
+/// The connection traffic currently goes out on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "ssid", rename_all = "camelCase")]
+pub enum NetworkIdentity {
+    Ethernet,
+    Wifi(String),
+    Unknown,
+}
+
+impl std::fmt::Display for NetworkIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetworkIdentity::Ethernet => write!(f, "Ethernet"),
+            NetworkIdentity::Wifi(ssid) => write!(f, "Wi-Fi \"{}\"", ssid),
+            NetworkIdentity::Unknown => write!(f, "an unidentified network"),
+        }
+    }
+}
+
+/// The way out to the internet: the interface of the default route and the
+/// local address the OS picks for it. Moving from Wi-Fi to Ethernet or a VPN
+/// coming up changes one or both, and strands connections on the old route.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteIdentity {
+    /// None where the OS doesn't report it cheaply
+    pub interface: Option<String>,
+    /// None while offline
+    pub local_addr: Option<IpAddr>,
+}
+
+impl RouteIdentity {
+    pub fn is_online(&self) -> bool {
+        self.local_addr.is_some()
+    }
+}
+
+/// Blocking, but cheap enough to poll every few seconds
+pub fn default_route() -> RouteIdentity {
+    RouteIdentity {
+        interface: platform::default_interface(),
+        local_addr: outbound_addr("0.0.0.0:0", "1.1.1.1:53")
+            .or_else(|| outbound_addr("[::]:0", "[2606:4700:4700::1111]:53")),
+    }
+}
+
+/// Connecting a UDP socket sends nothing; it only asks the OS which route
+/// and source address it would use
+fn outbound_addr(bind: &str, remote: &str) -> Option<IpAddr> {
+    let socket = UdpSocket::bind(bind).ok()?;
+    socket.connect(remote).ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_unspecified()).then_some(ip)
+}
+
+/// Run a command and return its stdout when it succeeds
+pub(crate) fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let mut command = Command::new(program);
+    command.args(args);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // CREATE_NO_WINDOW keeps a console window from flashing up on
+        // every poll
+        command.creation_flags(0x08000000);
+    }
+    let output = command.output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Blocking: may run platform tools
+pub fn current_network() -> NetworkIdentity {
+    platform::current_network()
+}
+
+/// Whether the machine runs on mains power. None when there is no battery
+/// to report on or the state can't be read. Blocking.
+pub fn on_ac_power() -> Option<bool> {
+    platform::on_ac_power()
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::*;
+    use std::path::Path;
+
+    /// Interface of the default route in /proc/net/route
+    pub fn default_interface() -> Option<String> {
+        let routes = std::fs::read_to_string("/proc/net/route").ok()?;
+        routes.lines().skip(1).find_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            (fields.get(1) == Some(&"00000000")).then(|| fields[0].to_string())
+        })
+    }
+
+    pub fn current_network() -> NetworkIdentity {
+        let Some(iface) = default_interface() else {
+            return NetworkIdentity::Unknown;
+        };
+        if !Path::new("/sys/class/net").join(&iface).join("wireless").exists() {
+            return NetworkIdentity::Ethernet;
+        }
+
+        let ssid = command_output("iwgetid", &[&iface, "-r"])
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .or_else(|| {
+                command_output("nmcli", &["-t", "-f", "active,ssid", "dev", "wifi"])?
+                    .lines()
+                    .find_map(|l| l.strip_prefix("yes:").map(str::to_string))
+            });
+        ssid.map(NetworkIdentity::Wifi).unwrap_or(NetworkIdentity::Unknown)
+    }
+
+    pub fn on_ac_power() -> Option<bool> {
+        let supplies = std::fs::read_dir("/sys/class/power_supply").ok()?;
+        let mut has_battery = false;
+        for supply in supplies.flatten() {
+            let path = supply.path();
+            let kind = std::fs::read_to_string(path.join("type")).unwrap_or_default();
+            match kind.trim() {
+                "Mains" | "USB" => {
+                    if std::fs::read_to_string(path.join("online")).is_ok_and(|o| o.trim() == "1") {
+                        return Some(true);
+                    }
+                }
+                "Battery" => has_battery = true,
+                _ => {}
+            }
+        }
+        has_battery.then_some(false)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::*;
+
+    pub fn default_interface() -> Option<String> {
+        command_output("route", &["-n", "get", "default"])?
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("interface:").map(|i| i.trim().to_string()))
+    }
+
+    pub fn current_network() -> NetworkIdentity {
+        // "Current Wi-Fi Network: Home" when associated
+        let wifi = ["en0", "en1"].iter().find_map(|device| {
+            command_output("networksetup", &["-getairportnetwork", device])?
+                .trim()
+                .split_once(": ")
+                .map(|(_, ssid)| ssid.to_string())
+        });
+        if let Some(ssid) = wifi {
+            return NetworkIdentity::Wifi(ssid);
+        }
+        // Online without Wi-Fi means a wired (or tethered) interface
+        match command_output("route", &["-n", "get", "default"]) {
+            Some(route) if route.contains("interface:") => NetworkIdentity::Ethernet,
+            _ => NetworkIdentity::Unknown,
+        }
+    }
+
+    pub fn on_ac_power() -> Option<bool> {
+        let batt = command_output("pmset", &["-g", "batt"])?;
+        if batt.contains("AC Power") {
+            Some(true)
+        } else if batt.contains("Battery Power") {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::*;
+
+    /// The local address tells routes apart; naming the interface would
+    /// need the IP helper API
+    pub fn default_interface() -> Option<String> {
+        None
+    }
+
+    pub fn current_network() -> NetworkIdentity {
+        // netsh also fails when the WLAN service isn't running, which
+        // says nothing about the connection we're on
+        let Some(interfaces) = command_output("netsh", &["wlan", "show", "interfaces"]) else {
+            return NetworkIdentity::Unknown;
+        };
+        let field = |name: &str| {
+            interfaces.lines().find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                (key.trim() == name).then(|| value.trim().to_string())
+            })
+        };
+        match (field("State").as_deref(), field("SSID")) {
+            (Some("connected"), Some(ssid)) => NetworkIdentity::Wifi(ssid),
+            _ => NetworkIdentity::Ethernet,
+        }
+    }
+
+    pub fn on_ac_power() -> Option<bool> {
+        let mut status: winapi::um::winbase::SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+        // SAFETY: `status` is a valid, writable SYSTEM_POWER_STATUS
+        if unsafe { winapi::um::winbase::GetSystemPowerStatus(&mut status) } == 0 {
+            return None;
+        }
+        match status.ACLineStatus {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    use super::*;
+
+    pub fn default_interface() -> Option<String> {
+        None
+    }
+
+    pub fn current_network() -> NetworkIdentity {
+        NetworkIdentity::Unknown
+    }
+
+    pub fn on_ac_power() -> Option<bool> {
+        None
+    }
+}
@@ -0,0 +1,195 @@
+// src-tauri/src/utils/permissions.rs
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Settings key: octal mode applied to completed downloads
+pub const COMPLETED_FILE_MODE_KEY: &str = "completed_file_mode";
+
+/// Settings key: group (name or gid) applied to completed downloads
+pub const COMPLETED_FILE_GROUP_KEY: &str = "completed_file_group";
+
+/// Mode and group given to a download once it completes. Only applied on
+/// Unix; elsewhere the settings are accepted and ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilePermissions {
+    /// Octal mode such as "0664"
+    pub mode: Option<String>,
+    /// Group name or numeric gid
+    pub group: Option<String>,
+}
+
+impl FilePermissions {
+    pub fn new(mode: Option<String>, group: Option<String>) -> Self {
+        let non_empty = |s: Option<String>| s.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        Self {
+            mode: non_empty(mode),
+            group: non_empty(group),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mode.is_none() && self.group.is_none()
+    }
+
+    /// These permissions with any field set in `overrides` taking precedence
+    pub fn with_overrides(&self, overrides: &FilePermissions) -> FilePermissions {
+        FilePermissions {
+            mode: overrides.mode.clone().or_else(|| self.mode.clone()),
+            group: overrides.group.clone().or_else(|| self.group.clone()),
+        }
+    }
+
+    /// Reject modes that aren't octal permission bits
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.mode {
+            Some(mode) => parse_mode(mode).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Parse an octal mode: "664", "0664" or "0o664"
+pub fn parse_mode(mode: &str) -> Result<u32, String> {
+    let digits = mode.trim();
+    let digits = digits.strip_prefix("0o").unwrap_or(digits);
+    u32::from_str_radix(digits, 8)
+        .ok()
+        .filter(|bits| *bits <= 0o7777)
+        .ok_or_else(|| format!("Invalid file mode '{}': expected octal such as 0664", mode))
+}
+
+/// Apply `permissions` to a completed file. Nothing here fails the
+/// download: problems come back as one warning message for the caller to
+/// report, since changing the group usually needs privileges the app
+/// doesn't have.
+#[cfg(unix)]
+pub fn apply(path: &Path, permissions: &FilePermissions) -> Option<String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut problems = Vec::new();
+
+    if let Some(mode) = &permissions.mode {
+        match parse_mode(mode) {
+            Ok(bits) => {
+                if let Err(e) = std::fs::set_permissions(path, std::fs::Permissions::from_mode(bits)) {
+                    problems.push(format!("cannot set mode {}: {}", mode, e));
+                }
+            }
+            Err(e) => problems.push(e),
+        }
+    }
+
+    if let Some(group) = &permissions.group {
+        match resolve_gid(group) {
+            Some(gid) => {
+                if let Err(e) = std::os::unix::fs::chown(path, None, Some(gid)) {
+                    problems.push(format!("cannot change group to {}: {}", group, e));
+                }
+            }
+            None => problems.push(format!("unknown group {}", group)),
+        }
+    }
+
+    if problems.is_empty() {
+        None
+    } else {
+        Some(format!("{}: {}", path.display(), problems.join("; ")))
+    }
+}
+
+#[cfg(not(unix))]
+pub fn apply(_path: &Path, _permissions: &FilePermissions) -> Option<String> {
+    None
+}
+
+/// Look up a group by name, or take it as a numeric gid
+#[cfg(unix)]
+fn resolve_gid(group: &str) -> Option<u32> {
+    if let Ok(gid) = group.parse::<u32>() {
+        return Some(gid);
+    }
+
+    let name = std::ffi::CString::new(group).ok()?;
+    let mut buf = vec![0 as libc::c_char; 16 * 1024];
+    // SAFETY: all pointers reference live locals sized as passed; on
+    // success `result` points at `entry`, whose strings live in `buf`
+    unsafe {
+        let mut entry: libc::group = std::mem::zeroed();
+        let mut result: *mut libc::group = std::ptr::null_mut();
+        let rc = libc::getgrnam_r(
+            name.as_ptr(),
+            &mut entry,
+            buf.as_mut_ptr(),
+            buf.len(),
+            &mut result,
+        );
+        (rc == 0 && !result.is_null()).then_some(entry.gr_gid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!(parse_mode("664"), Ok(0o664));
+        assert_eq!(parse_mode("0664"), Ok(0o664));
+        assert_eq!(parse_mode("0o2775"), Ok(0o2775));
+        assert!(parse_mode("0999").is_err());
+        assert!(parse_mode("77777").is_err());
+        assert!(parse_mode("rw-r--r--").is_err());
+    }
+
+    #[test]
+    fn test_category_overrides_global() {
+        let global = FilePermissions::new(Some("0664".into()), Some("media".into()));
+        let category = FilePermissions::new(Some("0640".into()), Some(" ".into()));
+        assert_eq!(
+            global.with_overrides(&category),
+            FilePermissions::new(Some("0640".into()), Some("media".into()))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_apply_sets_mode_bits() {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+        let path = std::env::temp_dir().join(format!("afk-dunld-perm-{}.bin", uuid::Uuid::new_v4()));
+        std::fs::write(&path, b"done").unwrap();
+
+        // Our own primary group needs no privileges
+        let gid = std::fs::metadata(&path).unwrap().gid();
+        let permissions = FilePermissions::new(Some("0640".into()), Some(gid.to_string()));
+        assert_eq!(apply(&path, &permissions), None);
+
+        let metadata = std::fs::metadata(&path).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o7777, 0o640);
+        assert_eq!(metadata.gid(), gid);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_apply_reports_unknown_group_without_failing() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("afk-dunld-perm-{}.bin", uuid::Uuid::new_v4()));
+        std::fs::write(&path, b"done").unwrap();
+
+        let permissions = FilePermissions::new(
+            Some("0604".into()),
+            Some("afk-dunld-no-such-group".into()),
+        );
+        let warning = apply(&path, &permissions).unwrap();
+        assert!(warning.contains("unknown group"));
+        // The mode is still applied
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o7777, 0o604);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,326 @@
+// src-tauri/src/utils/privacy.rs
+// How much about a download leaves the process. Everything sent outward,
+// like refresh webhooks or the metrics endpoint, goes through
+// `filter_payload` or `filter_field`, and log lines through `redact_text`
+// at the level `set_logging_privacy` last set. Events to the app's own
+// windows aren't filtered.
+// Fields are recognized by name, so new payloads follow the policy as long
+// as they name their URLs and file names the way existing ones do.
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use serde_json::Value;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Settings key for the level, stored like the enum serializes
+pub const PRIVACY_LEVEL_KEY: &str = "privacy_level";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PrivacyLevel {
+    /// Payloads go out as they are
+    #[default]
+    Full,
+    /// URLs are cut down to scheme and domain
+    RedactUrls,
+    /// Ids, sizes and statuses only: no URLs, hosts or file names
+    MetadataOnly,
+}
+
+/// What a field holds, judged by its name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldKind {
+    Url,
+    FileName,
+    Other,
+}
+
+/// Fields naming or locating the file, compared without case or underscores
+const FILE_NAME_FIELDS: &[&str] = &[
+    "filename",
+    "filenames",
+    "name",
+    "savepath",
+    "path",
+    "destination",
+    "title",
+    "pagetitle",
+    "sourcepagetitle",
+    "notes",
+];
+
+/// URL fields that don't end in "url"
+const URL_FIELDS: &[&str] = &["referrer", "redirectchain", "host", "magnet", "endpoint"];
+
+/// URLs inside free text start with one of these
+const URL_SCHEMES: &[&str] = &["https://", "http://", "sftp://", "ftps://", "ftp://", "magnet:?"];
+
+/// Stands in for a URL or path that can't be shown at all
+const REDACTED: &str = "[redacted]";
+
+/// The level log output follows. Logging starts before settings load, so
+/// it is kept here rather than handed to each component.
+static LOGGING_PRIVACY: AtomicU8 = AtomicU8::new(PrivacyLevel::Full as u8);
+
+pub fn set_logging_privacy(level: PrivacyLevel) {
+    LOGGING_PRIVACY.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn logging_privacy() -> PrivacyLevel {
+    match LOGGING_PRIVACY.load(Ordering::Relaxed) {
+        1 => PrivacyLevel::RedactUrls,
+        2 => PrivacyLevel::MetadataOnly,
+        _ => PrivacyLevel::Full,
+    }
+}
+
+fn field_kind(key: &str) -> FieldKind {
+    let normalized: String = key
+        .chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    if normalized.ends_with("url") || normalized.ends_with("urls") || URL_FIELDS.contains(&normalized.as_str()) {
+        FieldKind::Url
+    } else if FILE_NAME_FIELDS.contains(&normalized.as_str()) {
+        FieldKind::FileName
+    } else {
+        FieldKind::Other
+    }
+}
+
+/// Filter an outbound JSON payload in place
+pub fn filter_payload(level: PrivacyLevel, payload: &mut Value) {
+    if level == PrivacyLevel::Full {
+        return;
+    }
+    match payload {
+        Value::Object(fields) => {
+            fields.retain(|key, value| filter_field(level, key, value));
+        }
+        Value::Array(items) => {
+            for item in items {
+                filter_payload(level, item);
+            }
+        }
+        Value::String(text) => *text = redact_text(level, text),
+        _ => {}
+    }
+}
+
+/// Filter one named field in place; false when it has to be left out
+pub fn filter_field(level: PrivacyLevel, key: &str, value: &mut Value) -> bool {
+    match (level, field_kind(key)) {
+        (PrivacyLevel::Full, _) => true,
+        (PrivacyLevel::MetadataOnly, FieldKind::Url | FieldKind::FileName) => false,
+        (PrivacyLevel::RedactUrls, FieldKind::Url) => {
+            redact_urls_in(value);
+            true
+        }
+        _ => {
+            filter_payload(level, value);
+            true
+        }
+    }
+}
+
+/// Cut every string in a URL field down to scheme and domain
+fn redact_urls_in(value: &mut Value) {
+    match value {
+        Value::String(text) if find_url(text).is_some() => {
+            *text = redact_text(PrivacyLevel::RedactUrls, text);
+        }
+        // A bare host is already as far down as it goes
+        Value::String(_) => {}
+        Value::Array(items) => items.iter_mut().for_each(redact_urls_in),
+        Value::Object(fields) => fields.values_mut().for_each(redact_urls_in),
+        _ => {}
+    }
+}
+
+fn find_url(text: &str) -> Option<usize> {
+    URL_SCHEMES.iter().filter_map(|scheme| text.find(scheme)).min()
+}
+
+/// Replace the URLs in free text, such as an error message or a log line,
+/// and at metadata only its file paths too
+pub fn redact_text(level: PrivacyLevel, text: &str) -> String {
+    if level == PrivacyLevel::Full {
+        return text.to_string();
+    }
+    let text = if level == PrivacyLevel::MetadataOnly { redact_paths(text) } else { text.to_string() };
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+    while let Some(start) = find_url(rest) {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        let end = tail
+            .find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '<' | '>' | '(' | ')'))
+            .unwrap_or(tail.len());
+        out.push_str(&redact_url(level, &tail[..end]));
+        rest = &tail[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Whether a word of free text starts like an absolute or home path
+fn is_path_start(word: &str) -> bool {
+    let bytes = word.as_bytes();
+    let drive = bytes.len() > 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && matches!(bytes[2], b'\\' | b'/');
+    drive || word.starts_with("~/") || word.starts_with("\\\\") || (word.starts_with('/') && !word.starts_with("//"))
+}
+
+/// Replace the file paths in free text. A quoted path, as paths print with
+/// `{:?}`, is replaced up to its closing quote, spaces and all.
+fn redact_paths(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while !rest.is_empty() {
+        let word_start = rest.find(|c: char| !c.is_whitespace()).unwrap_or(rest.len());
+        out.push_str(&rest[..word_start]);
+        rest = &rest[word_start..];
+        let quote = rest.chars().next().filter(|c| matches!(c, '"' | '\''));
+        let body = match quote {
+            Some(q) => &rest[q.len_utf8()..],
+            None => rest.trim_start_matches('('),
+        };
+        let prefix_len = rest.len() - body.len();
+        let end = match quote {
+            Some(q) => body.find(q).unwrap_or(body.len()),
+            None => body.find(char::is_whitespace).unwrap_or(body.len()),
+        };
+        if is_path_start(body) {
+            out.push_str(&rest[..prefix_len]);
+            out.push_str(REDACTED);
+            rest = &body[end..];
+            // An unquoted path ends before trailing punctuation
+            if quote.is_none() {
+                let path = &body[..end];
+                let kept = path.trim_end_matches([')', ',', ';', ':', '.']);
+                out.push_str(&path[kept.len()..]);
+            }
+        } else {
+            let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            out.push_str(&rest[..word_end]);
+            rest = &rest[word_end..];
+        }
+    }
+    out
+}
+
+fn redact_url(level: PrivacyLevel, url: &str) -> String {
+    match level {
+        PrivacyLevel::Full => url.to_string(),
+        PrivacyLevel::RedactUrls => url::Url::parse(url)
+            .ok()
+            .and_then(|parsed| parsed.host_str().map(|host| format!("{}://{}", parsed.scheme(), host)))
+            .unwrap_or_else(|| REDACTED.to_string()),
+        PrivacyLevel::MetadataOnly => REDACTED.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::download_task::{DownloadStatus, DownloadTask};
+    use serde_json::json;
+
+    fn task_event() -> Value {
+        let mut task = DownloadTask::new(
+            "https://cdn.example/private/report.pdf?token=abc".to_string(),
+            "report.pdf".to_string(),
+            "/home/sam/Downloads/report.pdf".into(),
+            4,
+        );
+        task.final_url = Some("https://mirror.example:8443/report.pdf".to_string());
+        task.redirect_chain = vec!["https://short.example/x1".to_string()];
+        task.source_page_url = Some("https://blog.example/post".to_string());
+        task.total_size = Some(2048);
+        task.status = DownloadStatus::Failed;
+        task.error_message = Some("Server returned error: 403 - https://cdn.example/private/report.pdf?token=abc".to_string());
+        serde_json::to_value(task).unwrap()
+    }
+
+    fn webhook_body() -> Value {
+        json!({ "downloadId": "d1", "url": "ftp://files.example/pub/a.iso", "sizes": [1, 2] })
+    }
+
+    #[test]
+    fn test_full_sends_everything() {
+        let mut event = task_event();
+        let before = event.clone();
+        filter_payload(PrivacyLevel::Full, &mut event);
+        assert_eq!(event, before);
+    }
+
+    #[test]
+    fn test_redact_urls_keeps_scheme_and_domain() {
+        let mut event = task_event();
+        filter_payload(PrivacyLevel::RedactUrls, &mut event);
+        assert_eq!(event["url"], "https://cdn.example");
+        assert_eq!(event["finalUrl"], "https://mirror.example");
+        assert_eq!(event["redirectChain"], json!(["https://short.example"]));
+        assert_eq!(event["sourcePageUrl"], "https://blog.example");
+        assert_eq!(event["errorMessage"], "Server returned error: 403 - https://cdn.example");
+        // File names are fine at this level
+        assert_eq!(event["fileName"], "report.pdf");
+        assert_eq!(event["totalSize"], 2048);
+
+        let mut body = webhook_body();
+        filter_payload(PrivacyLevel::RedactUrls, &mut body);
+        assert_eq!(body, json!({ "downloadId": "d1", "url": "ftp://files.example", "sizes": [1, 2] }));
+    }
+
+    #[test]
+    fn test_metadata_only_keeps_ids_sizes_and_statuses() {
+        let mut event = task_event();
+        let id = event["id"].clone();
+        filter_payload(PrivacyLevel::MetadataOnly, &mut event);
+        for field in ["url", "finalUrl", "redirectChain", "sourcePageUrl", "fileName", "savePath"] {
+            assert!(event.get(field).is_none(), "{} was sent", field);
+        }
+        assert_eq!(event["id"], id);
+        assert_eq!(event["totalSize"], 2048);
+        assert_eq!(event["status"], json!(DownloadStatus::Failed));
+        assert_eq!(event["errorMessage"], "Server returned error: 403 - [redacted]");
+        assert!(!event.to_string().contains("example"));
+
+        let mut body = webhook_body();
+        filter_payload(PrivacyLevel::MetadataOnly, &mut body);
+        assert_eq!(body, json!({ "downloadId": "d1", "sizes": [1, 2] }));
+    }
+
+    #[test]
+    fn test_metadata_only_removes_paths_from_text() {
+        let level = PrivacyLevel::MetadataOnly;
+        assert_eq!(
+            redact_text(level, "Failed to write /home/sam/Downloads/report.pdf: disk full"),
+            "Failed to write [redacted]: disk full"
+        );
+        assert_eq!(
+            redact_text(level, r#"Moving "/home/sam/My Files/a b.iso" to (C:\Users\sam\x.iso)"#),
+            r#"Moving "[redacted]" to ([redacted])"#
+        );
+        assert_eq!(redact_text(level, "I/O error after 5/10 parts"), "I/O error after 5/10 parts");
+        // Redacting URLs alone leaves paths, which name no server
+        let text = "Saved ~/Downloads/a.iso from https://cdn.example/a.iso";
+        assert_eq!(redact_text(PrivacyLevel::RedactUrls, text), "Saved ~/Downloads/a.iso from https://cdn.example");
+        assert_eq!(redact_text(level, text), "Saved [redacted] from [redacted]");
+
+        let mut event = json!({ "id": "d1", "errorMessage": "Permission denied: /srv/media/a.iso" });
+        filter_payload(level, &mut event);
+        assert_eq!(event["errorMessage"], "Permission denied: [redacted]");
+    }
+
+    #[test]
+    fn test_labels() {
+        let mut host = json!("files.example");
+        assert!(filter_field(PrivacyLevel::RedactUrls, "host", &mut host));
+        assert_eq!(host, "files.example");
+        assert!(!filter_field(PrivacyLevel::MetadataOnly, "host", &mut host));
+        let mut protocol = json!("https");
+        assert!(filter_field(PrivacyLevel::MetadataOnly, "protocol", &mut protocol));
+        assert_eq!(serde_json::to_value(PrivacyLevel::RedactUrls).unwrap(), "redact_urls");
+    }
+}
@@ -0,0 +1,124 @@
+// src-tauri/src/utils/sandbox.rs
+// Starts external programs (ffmpeg for post-actions, the user's hook
+// scripts) with an environment built from scratch instead of inherited.
+// What reaches the program is an allowlist of variables it needs to run,
+// plus what the caller passes explicitly. Stdin is closed, no console
+// window opens, and the process dies with its handle.
+
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+
+/// Inherited variables: enough to find other programs, a home and a temp
+/// folder, and to decode text
+const INHERITED_VARS: &[&str] = &[
+    "PATH", "HOME", "USER", "LANG", "LC_ALL", "TMPDIR", "TEMP", "TMP", "SYSTEMROOT", "WINDIR", "COMSPEC",
+    "PATHEXT", "USERPROFILE", "APPDATA", "LOCALAPPDATA",
+];
+
+/// Captured output kept per stream; the rest is read and dropped
+pub const MAX_CAPTURED_OUTPUT: usize = 64 * 1024;
+
+/// How a program run with `run_captured` ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessOutput {
+    /// None when a signal ended it
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl ProcessOutput {
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// A command for `program` with the sandboxed environment. Callers add
+/// arguments, variables and pipes for stdout and stderr.
+pub fn command(program: &Path) -> tokio::process::Command {
+    let mut command = tokio::process::Command::new(program);
+    command.env_clear().stdin(Stdio::null()).kill_on_drop(true);
+    for name in INHERITED_VARS {
+        if let Some(value) = std::env::var_os(name) {
+            command.env(name, value);
+        }
+    }
+    #[cfg(windows)]
+    {
+        // CREATE_NO_WINDOW keeps a console window from appearing
+        command.creation_flags(0x08000000);
+    }
+    command
+}
+
+/// Run `command` to completion, capturing stdout and stderr. Killed and
+/// failed when it outlasts `timeout`.
+pub async fn run_captured(mut command: tokio::process::Command, timeout: Duration) -> Result<ProcessOutput, String> {
+    command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    let mut child = command.spawn().map_err(|e| format!("failed to start: {}", e))?;
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+
+    let finished = tokio::time::timeout(timeout, async {
+        let (stdout, stderr) = tokio::join!(read_capped(stdout), read_capped(stderr));
+        let status = child.wait().await.map_err(|e| format!("did not finish: {}", e))?;
+        Ok::<_, String>(ProcessOutput { exit_code: status.code(), stdout, stderr })
+    })
+    .await;
+    match finished {
+        Ok(output) => output,
+        Err(_) => {
+            let _ = child.kill().await;
+            Err(format!("timed out after {} seconds", timeout.as_secs()))
+        }
+    }
+}
+
+async fn read_capped(stream: Option<impl tokio::io::AsyncRead + Unpin>) -> String {
+    let Some(mut stream) = stream else {
+        return String::new();
+    };
+    let mut kept = Vec::new();
+    let mut buf = [0u8; 4096];
+    while let Ok(read) = stream.read(&mut buf).await {
+        if read == 0 {
+            break;
+        }
+        let room = MAX_CAPTURED_OUTPUT.saturating_sub(kept.len());
+        kept.extend_from_slice(&buf[..read.min(room)]);
+    }
+    String::from_utf8_lossy(&kept).into_owned()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn shell(script: &str) -> tokio::process::Command {
+        let mut command = command(Path::new("/bin/sh"));
+        command.arg("-c").arg(script);
+        command
+    }
+
+    #[tokio::test]
+    async fn test_environment_is_not_inherited() {
+        std::env::set_var("AFK_SANDBOX_SECRET", "leaked");
+        let mut command = shell("echo \"[$AFK_SANDBOX_SECRET][$AFK_GIVEN]\"; echo oops >&2; exit 3");
+        command.env("AFK_GIVEN", "given");
+        let output = run_captured(command, Duration::from_secs(5)).await.unwrap();
+        assert_eq!(output.stdout, "[][given]\n");
+        assert_eq!(output.stderr, "oops\n");
+        assert_eq!(output.exit_code, Some(3));
+        assert!(!output.success());
+    }
+
+    #[tokio::test]
+    async fn test_timeout_kills_the_program() {
+        let started = std::time::Instant::now();
+        let result = run_captured(shell("sleep 30"), Duration::from_millis(200)).await;
+        assert!(result.is_err());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
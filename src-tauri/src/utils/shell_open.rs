@@ -0,0 +1,100 @@
+// src-tauri/src/utils/shell_open.rs
+
+use std::path::{Path, PathBuf};
+
+use crate::utils::error::DownloadError;
+
+/// Open `path` with its default application
+pub async fn open(path: &Path) -> Result<(), DownloadError> {
+    ensure_exists(path).await?;
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        opener::open(&path).map_err(|e| DownloadError::FileError(format!("Failed to open file: {}", e)))
+    })
+    .await
+    .map_err(|e| DownloadError::Unknown(format!("Task failed: {}", e)))?
+}
+
+/// Show `path` in the system file manager, selected where the platform
+/// allows it. A missing file still opens its folder if that exists.
+pub async fn reveal(path: &Path) -> Result<(), DownloadError> {
+    tracing::info!("Opening file location for: {:?}", path);
+
+    let folder = path
+        .parent()
+        .ok_or_else(|| DownloadError::FileError("Invalid file path - no parent directory".to_string()))?;
+    if tokio::fs::metadata(folder).await.is_err() {
+        tracing::error!("Folder not found: {:?}", folder);
+        return Err(DownloadError::FileMissing(folder.display().to_string()));
+    }
+
+    let file_path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || reveal_blocking(file_path))
+        .await
+        .map_err(|e| DownloadError::Unknown(format!("Task failed: {}", e)))?
+}
+
+async fn ensure_exists(path: &Path) -> Result<(), DownloadError> {
+    if tokio::fs::metadata(path).await.is_err() {
+        return Err(DownloadError::FileMissing(path.display().to_string()));
+    }
+    Ok(())
+}
+
+fn reveal_blocking(file_path: PathBuf) -> Result<(), DownloadError> {
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        use std::process::Command;
+
+        // Normalize the path for Windows (convert forward slashes to backslashes)
+        let file_path_str = file_path.to_string_lossy().replace('/', "\\");
+        tracing::info!("Windows: Opening with explorer /select,{}", file_path_str);
+
+        // CREATE_NO_WINDOW keeps a console window from appearing
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        Command::new("explorer")
+            .creation_flags(CREATE_NO_WINDOW)
+            .arg(format!("/select,{}", file_path_str))
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| DownloadError::FileError(format!("Failed to open Windows Explorer: {}", e)))
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        use std::process::Command;
+
+        tracing::info!("macOS: Opening with 'open -R {}'", file_path.display());
+        Command::new("open")
+            .arg("-R")
+            .arg(&file_path)
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| DownloadError::FileError(format!("Failed to open Finder: {}", e)))
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        use std::process::Command;
+
+        // File managers differ in how they select a file; open the folder
+        let folder = file_path
+            .parent()
+            .ok_or_else(|| DownloadError::FileError("Invalid path".to_string()))?;
+        tracing::info!("Linux: Opening with 'xdg-open {}'", folder.display());
+        Command::new("xdg-open")
+            .arg(folder)
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| DownloadError::FileError(format!("Failed to open folder: {}", e)))
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        let folder = file_path
+            .parent()
+            .ok_or_else(|| DownloadError::FileError("Invalid path".to_string()))?;
+        opener::open(folder).map_err(|e| DownloadError::FileError(format!("Failed to open folder: {}", e)))
+    }
+}
@@ -1,46 +1,35 @@
 use std::path::PathBuf;
 use std::fs;
+use std::sync::Arc;
 use anyhow::{Result, Context, bail};
 use tracing::{info, warn, error, debug};
 use tokio::process::Command;
-use tauri::Manager;
 
-/// Manages the bundled yt-dlp binary
+use crate::utils::binaries::{prepare_executable, BinaryResolver, Tool};
+
+/// Manages the app-managed copy of the yt-dlp binary
 pub struct YtdlpManager {
     binary_path: PathBuf,
-    app_data_dir: PathBuf,
+    resolver: Arc<BinaryResolver>,
 }
 
 impl YtdlpManager {
     /// Create a new YtdlpManager instance
-    pub fn new(app_handle: &tauri::AppHandle) -> Result<Self> {
-        let app_data_dir = app_handle
-            .path()
-            .app_data_dir()
-            .map_err(|e| anyhow::anyhow!("Failed to get app data directory: {}", e))?;
-        
+    pub fn new(resolver: Arc<BinaryResolver>) -> Result<Self> {
         // Create bin directory in app data
-        let bin_dir = app_data_dir.join("bin");
-        fs::create_dir_all(&bin_dir)
+        fs::create_dir_all(resolver.managed_dir())
             .context("Failed to create bin directory")?;
         
-        // Determine binary name based on platform
-        let binary_name = if cfg!(target_os = "windows") {
-            "yt-dlp.exe"
-        } else {
-            "yt-dlp"
-        };
-        
-        let binary_path = bin_dir.join(binary_name);
+        let binary_path = resolver.managed_path(Tool::YtDlp);
         
         Ok(Self {
             binary_path,
-            app_data_dir,
+            resolver,
         })
     }
     
     /// Initialize yt-dlp: extract bundled binary if not present
-    pub async fn initialize(&self, app_handle: &tauri::AppHandle) -> Result<()> {
+    pub async fn initialize(&self) -> Result<()> {
         info!("Initializing yt-dlp manager");
         
         // Check if binary already exists
@@ -58,7 +47,7 @@ impl YtdlpManager {
         }
         
         // Extract bundled binary
-        self.extract_bundled_binary(app_handle).await?;
+        self.extract_bundled_binary().await?;
         
         // Verify the extracted binary
         if !self.verify_binary().await {
@@ -70,56 +59,22 @@ impl YtdlpManager {
     }
     
     /// Extract the bundled yt-dlp binary from resources
-    async fn extract_bundled_binary(&self, app_handle: &tauri::AppHandle) -> Result<()> {
+    async fn extract_bundled_binary(&self) -> Result<()> {
         info!("Extracting bundled yt-dlp binary");
         
-        // Determine which bundled binary to use based on platform
-        let resource_name = if cfg!(target_os = "windows") {
-            "resources/bin/yt-dlp.exe"
-        } else if cfg!(target_os = "macos") {
-            "resources/bin/yt-dlp_macos"
-        } else if cfg!(target_os = "linux") {
-            "resources/bin/yt-dlp_linux"
-        } else {
-            bail!("Unsupported platform for bundled yt-dlp");
+        let resource_path = match self.resolver.bundled_path(Tool::YtDlp) {
+            Some(path) => path,
+            None => bail!("Bundled yt-dlp binary ({}) not found", Tool::YtDlp.bundled_name()),
         };
-        
-        // Get resource path
-        let resource_path = app_handle
-            .path()
-            .resource_dir()
-            .map_err(|e| anyhow::anyhow!("Failed to get resource directory: {}", e))?
-            .join(resource_name.trim_start_matches("resources/"));
-        
         debug!("Resource path: {:?}", resource_path);
         
-        if !resource_path.exists() {
-            // Try alternative path (for development)
-            let dev_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-                .join(resource_name);
-            
-            if dev_path.exists() {
-                info!("Using development binary from {:?}", dev_path);
-                fs::copy(&dev_path, &self.binary_path)
-                    .context("Failed to copy development binary")?;
-            } else {
-                bail!("Bundled yt-dlp binary not found at {:?} or {:?}", resource_path, dev_path);
-            }
-        } else {
-            // Copy from resources to app data
-            fs::copy(&resource_path, &self.binary_path)
-                .context("Failed to copy bundled binary")?;
-        }
+        // Copy from resources to app data
+        fs::copy(&resource_path, &self.binary_path)
+            .context("Failed to copy bundled binary")?;
         
-        // Make executable on Unix-like systems
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let mut perms = fs::metadata(&self.binary_path)?.permissions();
-            perms.set_mode(0o755);
-            fs::set_permissions(&self.binary_path, perms)
-                .context("Failed to set executable permissions")?;
-        }
+        // Make executable and clear quarantine
+        prepare_executable(&self.binary_path)
+            .context("Failed to set executable permissions")?;
         
         info!("Successfully extracted yt-dlp binary to {:?}", self.binary_path);
         Ok(())
@@ -150,26 +105,14 @@ impl YtdlpManager {
         }
     }
     
-    /// Get the path to the yt-dlp binary
+    /// Get the path to the yt-dlp binary (see `BinaryResolver` for precedence)
     pub fn get_binary_path(&self) -> PathBuf {
-        // Use system yt-dlp if available (has latest features like --js-runtimes)
-        // Otherwise fall back to bundled binary
-        if cfg!(target_os = "windows") {
-            PathBuf::from("yt-dlp")
-        } else {
-            PathBuf::from("yt-dlp")
-        }
+        self.resolver.path_or_default(Tool::YtDlp)
     }
     
-    /// Check if yt-dlp is available (either bundled or system)
+    /// Check if a working yt-dlp can be resolved
     pub async fn is_available(&self) -> bool {
-        // First check bundled binary
-        if self.binary_path.exists() && self.verify_binary().await {
-            return true;
-        }
-        
-        // Fallback to system yt-dlp
-        let result = Command::new("yt-dlp")
+        let result = Command::new(self.get_binary_path())
             .arg("--version")
             .output()
             .await;
@@ -202,9 +145,9 @@ impl YtdlpManager {
         }
     }
     
-    /// Get the version of the installed yt-dlp
+    /// Get the version of the yt-dlp that will actually be used
     pub async fn get_version(&self) -> Result<String> {
-        let output = Command::new(&self.binary_path)
+        let output = Command::new(self.get_binary_path())
             .arg("--version")
             .output()
             .await
@@ -218,12 +161,10 @@ impl YtdlpManager {
         }
     }
     
-    /// Get the bundled version from the version file
+    /// Get the bundled version from the version file shipped next to the binary
     pub fn get_bundled_version(&self) -> Option<String> {
-        let version_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-            .join("resources")
-            .join("bin")
-            .join("ytdlp-version.txt");
+        let version_file = self.resolver.bundled_path(Tool::YtDlp)?
+            .with_file_name("ytdlp-version.txt");
         
         fs::read_to_string(version_file).ok()
     }
@@ -0,0 +1,819 @@
+// Integration tests for state that must survive an application restart
+// Run with: cargo test --test test_persistence
+
+#[cfg(test)]
+mod queue_persistence_tests {
+    use afk_dunld_lib::core::download_task::DownloadTask;
+    use afk_dunld_lib::core::queue_manager::QueueManager;
+    use afk_dunld_lib::database::db::Database;
+    use std::path::PathBuf;
+    use uuid::Uuid;
+
+    fn temp_data_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("afk-dunld-test-{}", Uuid::new_v4()))
+    }
+
+    async fn open_db(dir: &PathBuf) -> Database {
+        let db = Database::new(dir).await.unwrap();
+        db.run_migrations().await.unwrap();
+        db
+    }
+
+    #[tokio::test]
+    async fn test_queue_order_survives_restart() {
+        let dir = temp_data_dir();
+        let db = open_db(&dir).await;
+
+        let mut ids = Vec::new();
+        for i in 0..5 {
+            let task = DownloadTask::new(
+                format!("https://example.com/file{}.bin", i),
+                format!("file{}.bin", i),
+                dir.join(format!("file{}.bin", i)),
+                4,
+            );
+            db.insert_download(&task).await.unwrap();
+            ids.push(task.id);
+        }
+
+        let mut queue = QueueManager::new(5);
+        queue.restore(ids.clone());
+        queue.reorder(ids[4], 0);
+        db.save_queue(&queue.get_active(), &queue.get_queue()).await.unwrap();
+        let expected = queue.get_queue();
+        drop(db);
+
+        // Simulate a restart with a fresh connection over the same data dir
+        let db = open_db(&dir).await;
+        let mut restored = QueueManager::new(5);
+        restored.restore(db.load_queue().await.unwrap());
+
+        assert_eq!(restored.get_queue(), expected);
+        assert_eq!(restored.get_queue()[0], ids[4]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_removed_downloads_are_dropped_from_saved_queue() {
+        let dir = temp_data_dir();
+        let db = open_db(&dir).await;
+
+        let kept = DownloadTask::new(
+            "https://example.com/kept.bin".to_string(),
+            "kept.bin".to_string(),
+            dir.join("kept.bin"),
+            4,
+        );
+        let removed = DownloadTask::new(
+            "https://example.com/removed.bin".to_string(),
+            "removed.bin".to_string(),
+            dir.join("removed.bin"),
+            4,
+        );
+        db.insert_download(&kept).await.unwrap();
+        db.insert_download(&removed).await.unwrap();
+        db.save_queue(&[], &[removed.id, kept.id]).await.unwrap();
+
+        db.delete_download(removed.id).await.unwrap();
+
+        assert_eq!(db.load_queue().await.unwrap(), vec![kept.id]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
+
+#[cfg(test)]
+mod app_state_restart_tests {
+    use afk_dunld_lib::core::download_task::{DownloadStatus, DownloadTask};
+    use afk_dunld_lib::services::recent_files_service::{RecentFiles, ShellRecents};
+    use afk_dunld_lib::state::app_state::AppState;
+    use std::path::{Path, PathBuf};
+    use uuid::Uuid;
+
+    struct NoShell;
+
+    impl ShellRecents for NoShell {
+        fn add_recent_document(&self, _path: &Path) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    async fn launch(dir: &PathBuf) -> AppState {
+        AppState::load(dir.clone(), None, RecentFiles::new(Box::new(NoShell)))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_queue_order_survives_app_restart() {
+        let dir = std::env::temp_dir().join(format!("afk-dunld-test-{}", Uuid::new_v4()));
+        let state = launch(&dir).await;
+
+        let mut ids = Vec::new();
+        for i in 0..5 {
+            let task = DownloadTask::new(
+                format!("https://example.com/file{}.bin", i),
+                format!("file{}.bin", i),
+                dir.join(format!("file{}.bin", i)),
+                4,
+            );
+            state.db.insert_download(&task).await.unwrap();
+            ids.push(task.id);
+        }
+        let expected = {
+            let mut queue = state.queue.write().await;
+            for id in &ids {
+                queue.push_waiting(*id);
+            }
+            queue.reorder(ids[4], 0).unwrap();
+            state.db.save_queue(&queue.get_active(), &queue.get_queue()).await.unwrap();
+            queue.get_queue()
+        };
+        drop(state);
+
+        // A second launch over the same data dir rebuilds the queue from disk
+        let restarted = launch(&dir).await;
+        let queue = restarted.queue.read().await;
+        assert_eq!(queue.get_queue(), expected);
+        assert_eq!(queue.get_queue()[0], ids[4]);
+        drop(queue);
+        drop(restarted);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_playlist_fails_instead_of_queueing() {
+        let dir = std::env::temp_dir().join(format!("afk-dunld-test-{}", Uuid::new_v4()));
+        let state = launch(&dir).await;
+
+        let mut group = DownloadTask::new(
+            "https://example.com/playlist".to_string(),
+            "Playlist".to_string(),
+            dir.join("Playlist"),
+            1,
+        );
+        group.status = DownloadStatus::Downloading;
+        group.playlist_id = Some(group.id);
+        let mut entry = DownloadTask::new(
+            "https://example.com/watch?v=1".to_string(),
+            "one.mp4".to_string(),
+            dir.join("Playlist").join("one.mp4"),
+            1,
+        );
+        entry.playlist_id = Some(group.id);
+        state.db.insert_download(&group).await.unwrap();
+        state.db.insert_download(&entry).await.unwrap();
+        drop(state);
+
+        let restarted = launch(&dir).await;
+        assert!(restarted.queue.read().await.get_queue().is_empty());
+        for id in [group.id, entry.id] {
+            let task = restarted.db.get_download(id).await.unwrap().unwrap();
+            assert_eq!(task.status, DownloadStatus::Failed);
+        }
+        drop(restarted);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
+
+#[cfg(test)]
+mod stats_breakdown_tests {
+    use afk_dunld_lib::core::download_task::{DownloadStatus, DownloadTask};
+    use afk_dunld_lib::database::db::Database;
+    use afk_dunld_lib::database::queries::StatsGroupBy;
+    use chrono::{Duration, NaiveDate};
+    use std::path::PathBuf;
+    use uuid::Uuid;
+
+    const ROWS: usize = 300;
+    const DOMAINS: [&str; 3] = ["https://example.com", "https://WWW.mirror.org", "http://cdn.test.net"];
+
+    async fn seeded_db() -> (Database, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("afk-dunld-test-{}", Uuid::new_v4()));
+        let db = Database::new(&dir).await.unwrap();
+        db.run_migrations().await.unwrap();
+
+        let start = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap().and_hms_opt(12, 0, 0).unwrap();
+
+        for i in 0..ROWS {
+            let mut task = DownloadTask::new(
+                format!("{}/files/file{}.bin", DOMAINS[i % 3], i),
+                format!("file{}.bin", i),
+                dir.join(format!("file{}.bin", i)),
+                4,
+            );
+            task.category = Some(if i % 2 == 0 { "videos" } else { "music" }.to_string());
+            task.downloaded_size = 1000;
+            task.total_size = Some(1000);
+            task.created_at = start + Duration::days(31 * (i % 3) as i64);
+            if i % 5 == 0 {
+                task.status = DownloadStatus::Failed;
+            } else {
+                task.status = DownloadStatus::Completed;
+                // 1000 bytes in 10 seconds
+                task.completed_at = Some(task.created_at + Duration::seconds(10));
+            }
+            db.insert_download(&task).await.unwrap();
+        }
+
+        (db, dir)
+    }
+
+    #[tokio::test]
+    async fn test_breakdown_by_domain() {
+        let (db, dir) = seeded_db().await;
+
+        let buckets = db.get_stats_breakdown(StatsGroupBy::Domain, None, None).await.unwrap();
+        let keys: Vec<&str> = buckets.iter().map(|b| b.key.as_str()).collect();
+        assert_eq!(keys, vec!["cdn.test.net", "example.com", "mirror.org"]);
+
+        for bucket in &buckets {
+            assert_eq!(bucket.count, 100);
+            assert_eq!(bucket.failed, 20);
+            assert_eq!(bucket.completed, 80);
+            assert_eq!(bucket.total_bytes, 100_000);
+            assert!((bucket.failure_rate - 0.2).abs() < 1e-9);
+            let speed = bucket.average_speed.unwrap();
+            assert!((speed - 100.0).abs() < 0.5, "unexpected speed {}", speed);
+        }
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_average_speed_excludes_time_queued() {
+        let dir = std::env::temp_dir().join(format!("afk-dunld-test-{}", Uuid::new_v4()));
+        let db = Database::new(&dir).await.unwrap();
+        db.run_migrations().await.unwrap();
+
+        // Queued for an hour, then 1000 bytes in about 10 seconds
+        let mut task = DownloadTask::new(
+            "https://example.com/queued.bin".to_string(),
+            "queued.bin".to_string(),
+            dir.join("queued.bin"),
+            4,
+        );
+        task.created_at = chrono::Local::now().naive_local() - Duration::hours(1);
+        db.insert_download(&task).await.unwrap();
+        db.update_status(task.id, DownloadStatus::Downloading).await.unwrap();
+
+        task.status = DownloadStatus::Completed;
+        task.downloaded_size = 1000;
+        task.total_size = Some(1000);
+        task.completed_at = Some(chrono::Local::now().naive_local() + Duration::seconds(10));
+        db.update_download(&task).await.unwrap();
+
+        let buckets = db.get_stats_breakdown(StatsGroupBy::Status, None, None).await.unwrap();
+        let speed = buckets[0].average_speed.unwrap();
+        assert!(speed > 80.0 && speed <= 101.0, "unexpected speed {}", speed);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_breakdown_by_category_and_status() {
+        let (db, dir) = seeded_db().await;
+
+        let categories = db.get_stats_breakdown(StatsGroupBy::Category, None, None).await.unwrap();
+        assert_eq!(categories.len(), 2);
+        assert!(categories.iter().all(|b| b.count == 150));
+        assert_eq!(categories.iter().map(|b| b.failed).sum::<u64>(), 60);
+
+        let statuses = db.get_stats_breakdown(StatsGroupBy::Status, None, None).await.unwrap();
+        let failed = statuses.iter().find(|b| b.key == "Failed").unwrap();
+        assert_eq!(failed.count, 60);
+        assert!(failed.average_speed.is_none());
+        assert!((failed.failure_rate - 1.0).abs() < 1e-9);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_breakdown_by_month_with_range() {
+        let (db, dir) = seeded_db().await;
+
+        let months = db.get_stats_breakdown(StatsGroupBy::Month, None, None).await.unwrap();
+        let keys: Vec<&str> = months.iter().map(|b| b.key.as_str()).collect();
+        assert_eq!(keys, vec!["2024-01", "2024-02", "2024-03"]);
+        assert!(months.iter().all(|b| b.count == 100));
+
+        let from = NaiveDate::from_ymd_opt(2024, 2, 1).unwrap().and_hms_opt(0, 0, 0);
+        let to = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().and_hms_opt(0, 0, 0);
+        let february = db.get_stats_breakdown(StatsGroupBy::Month, from, to).await.unwrap();
+        assert_eq!(february.len(), 1);
+        assert_eq!(february[0].key, "2024-02");
+        assert_eq!(february[0].count, 100);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
+
+#[cfg(test)]
+mod redownload_history_tests {
+    use afk_dunld_lib::core::download_task::{DownloadStatus, DownloadTask};
+    use afk_dunld_lib::database::db::Database;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_superseded_link_is_persisted() {
+        let dir = std::env::temp_dir().join(format!("afk-dunld-test-{}", Uuid::new_v4()));
+        let db = Database::new(&dir).await.unwrap();
+        db.run_migrations().await.unwrap();
+
+        let mut old = DownloadTask::new(
+            "https://example.com/release.zip".to_string(),
+            "release.zip".to_string(),
+            dir.join("release.zip"),
+            4,
+        );
+        old.status = DownloadStatus::Completed;
+        let new = DownloadTask::new(
+            old.url.clone(),
+            "release.zip".to_string(),
+            dir.join("release.zip"),
+            4,
+        );
+        db.insert_download(&old).await.unwrap();
+        db.insert_download(&new).await.unwrap();
+
+        db.mark_superseded(old.id, new.id).await.unwrap();
+
+        let old = db.get_download(old.id).await.unwrap().unwrap();
+        let new = db.get_download(new.id).await.unwrap().unwrap();
+        assert_eq!(old.superseded_by, Some(new.id));
+        assert_eq!(old.status, DownloadStatus::Completed);
+        assert!(new.superseded_by.is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
+
+#[cfg(test)]
+mod download_metadata_tests {
+    use afk_dunld_lib::core::download_task::{DownloadMetadataPatch, DownloadTask, PrivateText};
+    use afk_dunld_lib::database::db::Database;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_notes_and_source_page_round_trip() {
+        let dir = std::env::temp_dir().join(format!("afk-dunld-test-{}", Uuid::new_v4()));
+        let db = Database::new(&dir).await.unwrap();
+        db.run_migrations().await.unwrap();
+
+        let mut task = DownloadTask::new(
+            "https://example.com/paper.pdf".to_string(),
+            "paper.pdf".to_string(),
+            dir.join("paper.pdf"),
+            4,
+        );
+        task.source_page_url = Some("https://example.com/papers".to_string());
+        db.insert_download(&task).await.unwrap();
+
+        let patch = DownloadMetadataPatch {
+            notes: Some(PrivateText("Reference for the thesis".to_string())),
+            source_page_url: None,
+            source_page_title: Some("Papers".to_string()),
+        };
+        patch.apply(&mut task);
+        db.update_download_metadata(&task).await.unwrap();
+
+        let loaded = db.get_download(task.id).await.unwrap().unwrap();
+        assert_eq!(loaded.notes.as_ref().map(|n| n.as_str()), Some("Reference for the thesis"));
+        assert_eq!(loaded.source_page_url.as_deref(), Some("https://example.com/papers"));
+        assert_eq!(loaded.source_page_title.as_deref(), Some("Papers"));
+
+        // Notes are searchable but stay out of Debug output and events
+        assert_eq!(db.search_downloads("thesis").await.unwrap().len(), 1);
+        assert!(!format!("{:?}", loaded).contains("thesis"));
+        assert!(loaded.for_event().notes.is_none());
+
+        // An empty string clears a field
+        let mut cleared = loaded.clone();
+        DownloadMetadataPatch {
+            notes: Some(PrivateText(String::new())),
+            ..Default::default()
+        }
+        .apply(&mut cleared);
+        assert!(cleared.notes.is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
+
+mod integrity_sweep_tests {
+    use afk_dunld_lib::core::download_task::{DownloadStatus, DownloadTask, IntegrityStatus};
+    use afk_dunld_lib::database::db::Database;
+    use afk_dunld_lib::services::integrity_service::sweep;
+    use std::path::Path;
+    use uuid::Uuid;
+
+    async fn completed(db: &Database, path: &Path) -> DownloadTask {
+        let mut task = DownloadTask::new(
+            format!("https://example.com/{}", Uuid::new_v4()),
+            path.file_name().unwrap().to_string_lossy().to_string(),
+            path.to_path_buf(),
+            1,
+        );
+        task.status = DownloadStatus::Completed;
+        db.insert_download(&task).await.unwrap();
+        task
+    }
+
+    #[tokio::test]
+    async fn test_sweep_flags_missing_and_skips_offline_mounts() {
+        let dir = std::env::temp_dir().join(format!("afk-dunld-test-{}", Uuid::new_v4()));
+        let db = Database::new(&dir).await.unwrap();
+        db.run_migrations().await.unwrap();
+
+        let present_path = dir.join("present.bin");
+        std::fs::write(&present_path, b"data").unwrap();
+        let present = completed(&db, &present_path).await;
+        let missing = completed(&db, &dir.join("missing.bin")).await;
+        let offline_mount = dir.join("offline-share");
+        let on_mount = completed(&db, &offline_mount.join("remote.bin")).await;
+
+        let summary = sweep(&db, &[offline_mount.clone()]).await.unwrap();
+        assert_eq!(summary.checked, 2);
+        assert_eq!(summary.missing, 1);
+        assert_eq!(summary.skipped, 1);
+        assert_eq!(summary.newly_missing.len(), 1);
+        assert_eq!(summary.newly_missing[0].id, missing.id);
+
+        let flagged = db.get_download(missing.id).await.unwrap().unwrap();
+        assert_eq!(flagged.integrity_status, Some(IntegrityStatus::Missing));
+        assert_eq!(flagged.status, DownloadStatus::Completed);
+        assert_eq!(db.get_download(present.id).await.unwrap().unwrap().integrity_status, None);
+        assert_eq!(db.get_download(on_mount.id).await.unwrap().unwrap().integrity_status, None);
+
+        // Already flagged files are not reported as new, restored ones are cleared
+        let again = sweep(&db, &[offline_mount.clone()]).await.unwrap();
+        assert_eq!(again.missing, 1);
+        assert!(again.newly_missing.is_empty());
+
+        std::fs::write(&missing.save_path, b"back").unwrap();
+        let restored = sweep(&db, &[offline_mount]).await.unwrap();
+        assert_eq!(restored.restored, 1);
+        assert_eq!(
+            db.get_download(missing.id).await.unwrap().unwrap().integrity_status,
+            Some(IntegrityStatus::Ok)
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
+
+#[cfg(test)]
+mod maintenance_tests {
+    use afk_dunld_lib::core::download_task::DownloadTask;
+    use afk_dunld_lib::database::db::Database;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_incremental_vacuum_returns_freed_pages() {
+        let dir = std::env::temp_dir().join(format!("afk-dunld-test-{}", Uuid::new_v4()));
+        let db = Database::new(&dir).await.unwrap();
+        db.run_migrations().await.unwrap();
+
+        let mut ids = Vec::new();
+        for i in 0..500 {
+            let task = DownloadTask::new(
+                format!("https://example.com/{}/file{}.bin", "x".repeat(200), i),
+                format!("file{}.bin", i),
+                dir.join(format!("file{}.bin", i)),
+                4,
+            );
+            db.insert_download(&task).await.unwrap();
+            ids.push(task.id);
+        }
+        for id in ids {
+            db.delete_download(id).await.unwrap();
+        }
+
+        let before = db.size_bytes().await.unwrap();
+        db.incremental_vacuum().await.unwrap();
+        assert!(db.size_bytes().await.unwrap() < before);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
+
+#[cfg(test)]
+mod task_launch_tests {
+    use afk_dunld_lib::core::download_task::{DownloadStatus, DownloadTask};
+    use afk_dunld_lib::core::task_launch::{launch, TaskStore};
+    use afk_dunld_lib::database::db::Database;
+    use afk_dunld_lib::utils::error::DownloadError;
+    use std::future::Future;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use uuid::Uuid;
+
+    /// Database wrapper that can be told to fail the next insert or update
+    struct FaultyStore {
+        db: Database,
+        fail_next_insert: AtomicBool,
+        fail_next_update: AtomicBool,
+    }
+
+    impl FaultyStore {
+        fn new(db: Database) -> Self {
+            Self {
+                db,
+                fail_next_insert: AtomicBool::new(false),
+                fail_next_update: AtomicBool::new(false),
+            }
+        }
+    }
+
+    fn injected() -> DownloadError {
+        DownloadError::Unknown("injected failure".to_string())
+    }
+
+    impl TaskStore for FaultyStore {
+        fn reserve_and_insert(
+            &self,
+            task: &mut DownloadTask,
+        ) -> impl Future<Output = Result<(), DownloadError>> + Send {
+            let fail = self.fail_next_insert.swap(false, Ordering::SeqCst);
+            async move {
+                if fail {
+                    return Err(injected());
+                }
+                self.db.reserve_and_insert(task).await
+            }
+        }
+
+        fn update_download(
+            &self,
+            task: &DownloadTask,
+        ) -> impl Future<Output = Result<(), DownloadError>> + Send {
+            let fail = self.fail_next_update.swap(false, Ordering::SeqCst);
+            async move {
+                if fail {
+                    return Err(injected());
+                }
+                self.db.update_download(task).await
+            }
+        }
+    }
+
+    async fn open_store() -> (FaultyStore, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("afk-dunld-test-{}", Uuid::new_v4()));
+        let db = Database::new(&dir).await.unwrap();
+        db.run_migrations().await.unwrap();
+        (FaultyStore::new(db), dir)
+    }
+
+    fn new_task(dir: &PathBuf, name: &str) -> DownloadTask {
+        DownloadTask::new(
+            format!("https://example.com/{}", name),
+            name.to_string(),
+            dir.join(name),
+            4,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_failed_insert_starts_nothing() {
+        let (store, dir) = open_store().await;
+        let mut task = new_task(&dir, "file.bin");
+        let starts = AtomicUsize::new(0);
+
+        store.fail_next_insert.store(true, Ordering::SeqCst);
+        let result = launch(&store, &mut task, |_| async {
+            starts.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(starts.load(Ordering::SeqCst), 0);
+        assert!(store.db.get_download(task.id).await.unwrap().is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_failed_start_marks_row_failed() {
+        let (store, dir) = open_store().await;
+        let mut task = new_task(&dir, "file.bin");
+
+        let result = launch(&store, &mut task, |started| async move {
+            assert_eq!(started.status, DownloadStatus::Downloading);
+            Err("spawn failed".to_string())
+        })
+        .await;
+
+        assert!(result.is_err());
+        let row = store.db.get_download(task.id).await.unwrap().unwrap();
+        assert_eq!(row.status, DownloadStatus::Failed);
+        assert_eq!(row.error_message.as_deref(), Some("Unknown error: spawn failed"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_failed_update_starts_nothing_and_marks_row_failed() {
+        let (store, dir) = open_store().await;
+        let mut task = new_task(&dir, "file.bin");
+        let starts = AtomicUsize::new(0);
+
+        store.fail_next_update.store(true, Ordering::SeqCst);
+        let result = launch(&store, &mut task, |_| async {
+            starts.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(starts.load(Ordering::SeqCst), 0);
+        let row = store.db.get_download(task.id).await.unwrap().unwrap();
+        assert_eq!(row.status, DownloadStatus::Failed);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_successful_launch_writes_row_first() {
+        let (store, dir) = open_store().await;
+        let mut task = new_task(&dir, "file.bin");
+
+        launch(&store, &mut task, |started| {
+            let db = store.db.clone();
+            async move {
+                // The row already exists when the download starts
+                let row = db.get_download(started.id).await.unwrap().unwrap();
+                assert_eq!(row.status, DownloadStatus::Downloading);
+                Ok(())
+            }
+        })
+        .await
+        .unwrap();
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_adds_reserve_distinct_paths() {
+        let (store, dir) = open_store().await;
+        let mut first = new_task(&dir, "file.bin");
+        let mut second = new_task(&dir, "file.bin");
+
+        store.db.reserve_and_insert(&mut first).await.unwrap();
+        store.db.reserve_and_insert(&mut second).await.unwrap();
+
+        assert_eq!(first.file_name, "file.bin");
+        assert_eq!(second.file_name, "file (1).bin");
+        assert_eq!(second.save_path, dir.join("file (1).bin"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
+
+#[cfg(test)]
+mod retry_tests {
+    use afk_dunld_lib::commands::download_commands::{queue_consistency, requeue_for_retry, requeue_missing};
+    use afk_dunld_lib::core::download_task::{DownloadStatus, DownloadTask, IntegrityStatus};
+    use afk_dunld_lib::services::recent_files_service::{RecentFiles, ShellRecents};
+    use afk_dunld_lib::state::app_state::AppState;
+    use std::path::{Path, PathBuf};
+    use uuid::Uuid;
+
+    struct NoShell;
+
+    impl ShellRecents for NoShell {
+        fn add_recent_document(&self, _path: &Path) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    async fn launch(dir: &PathBuf) -> AppState {
+        AppState::load(dir.clone(), None, RecentFiles::new(Box::new(NoShell)))
+            .await
+            .unwrap()
+    }
+
+    fn task(dir: &Path, name: &str, status: DownloadStatus) -> DownloadTask {
+        let mut task = DownloadTask::new(
+            format!("https://example.com/{}", name),
+            name.to_string(),
+            dir.join(name),
+            4,
+        );
+        task.status = status;
+        task
+    }
+
+    #[tokio::test]
+    async fn test_retry_requeues_the_same_row() {
+        let dir = std::env::temp_dir().join(format!("afk-dunld-test-{}", Uuid::new_v4()));
+        let state = launch(&dir).await;
+
+        let mut failed = task(&dir, "failed.bin", DownloadStatus::Failed);
+        failed.downloaded_size = 512;
+        failed.error_message = Some("NETWORK_ERROR: reset".to_string());
+        state.db.insert_download(&failed).await.unwrap();
+
+        requeue_for_retry(&state, &mut failed).await.unwrap();
+
+        let rows = state.db.get_all_downloads().await.unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].id, failed.id);
+        assert_eq!(rows[0].status, DownloadStatus::Queued);
+        assert_eq!(rows[0].downloaded_size, 512);
+        assert!(rows[0].error_message.is_none());
+        assert_eq!(state.queue.read().await.get_queue(), vec![failed.id]);
+        assert!(queue_consistency(&state).await.unwrap().is_consistent());
+
+        // Retrying again while it waits doesn't queue it twice
+        requeue_for_retry(&state, &mut failed).await.unwrap();
+        assert_eq!(state.queue.read().await.get_queue(), vec![failed.id]);
+        drop(state);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_missing_files_are_redownloaded_in_place() {
+        let dir = std::env::temp_dir().join(format!("afk-dunld-test-{}", Uuid::new_v4()));
+        let state = launch(&dir).await;
+
+        let mut missing = task(&dir, "missing.bin", DownloadStatus::Completed);
+        missing.downloaded_size = 4096;
+        missing.total_size = Some(4096);
+        missing.completed_at = Some(chrono::Local::now().naive_local());
+        state.db.insert_download(&missing).await.unwrap();
+        state.db.set_integrity_status(missing.id, Some(IntegrityStatus::Missing)).await.unwrap();
+        let present = task(&dir, "present.bin", DownloadStatus::Completed);
+        std::fs::write(&present.save_path, b"data").unwrap();
+        state.db.insert_download(&present).await.unwrap();
+
+        let retried = requeue_missing(&state, None).await.unwrap();
+        assert_eq!(retried, vec![missing.id.to_string()]);
+
+        let rows = state.db.get_all_downloads().await.unwrap();
+        assert_eq!(rows.len(), 2);
+        let row = state.db.get_download(missing.id).await.unwrap().unwrap();
+        assert_eq!(row.status, DownloadStatus::Queued);
+        assert_eq!(row.downloaded_size, 0);
+        assert!(row.completed_at.is_none());
+        assert_eq!(row.integrity_status, None);
+        let untouched = state.db.get_download(present.id).await.unwrap().unwrap();
+        assert_eq!(untouched.status, DownloadStatus::Completed);
+
+        assert_eq!(state.queue.read().await.get_queue(), vec![missing.id]);
+        assert!(queue_consistency(&state).await.unwrap().is_consistent());
+        drop(state);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
+
+#[cfg(test)]
+mod run_end_tests {
+    use afk_dunld_lib::commands::download_commands::{save_run_end, RunEnd};
+    use afk_dunld_lib::core::download_task::{DownloadStatus, DownloadTask};
+    use afk_dunld_lib::database::db::Database;
+    use afk_dunld_lib::utils::error::DownloadError;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_paused_run_is_not_saved_as_failed() {
+        let dir = std::env::temp_dir().join(format!("afk-dunld-test-{}", Uuid::new_v4()));
+        let db = Database::new(&dir).await.unwrap();
+        db.run_migrations().await.unwrap();
+
+        let mut running = DownloadTask::new(
+            "https://example.com/running.bin".to_string(),
+            "running.bin".to_string(),
+            dir.join("running.bin"),
+            4,
+        );
+        running.status = DownloadStatus::Downloading;
+        db.insert_download(&running).await.unwrap();
+
+        // pause_download writes Paused and fires the token; the engine
+        // then returns Cancelled to the task that was running it
+        db.update_status(running.id, DownloadStatus::Paused).await.unwrap();
+        let mut engine_copy = running.clone();
+        let end = save_run_end(&db, &mut engine_copy, &Err(DownloadError::Cancelled), true).await;
+        assert_eq!(end, RunEnd::Interrupted);
+
+        let saved = db.get_download(running.id).await.unwrap().unwrap();
+        assert_eq!(saved.status, DownloadStatus::Paused);
+        assert!(saved.error_message.is_none());
+
+        // A run that fails on its own is still saved as Failed
+        let error = DownloadError::NetworkError("connection reset".to_string());
+        let end = save_run_end(&db, &mut running, &Err(error), false).await;
+        assert_eq!(end, RunEnd::Failed);
+        let saved = db.get_download(running.id).await.unwrap().unwrap();
+        assert_eq!(saved.status, DownloadStatus::Failed);
+        assert!(saved.error_message.unwrap().contains("connection reset"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
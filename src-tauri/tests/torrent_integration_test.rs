@@ -148,6 +148,7 @@ mod torrent_tests {
         assert_eq!(metadata.category, None);
         assert!(metadata.tags.is_empty());
         assert_eq!(metadata.completed_time, None);
+        assert_eq!(metadata.only_files, None);
     }
 
     #[test]
@@ -8,6 +8,10 @@ interface Category {
   color: string | null;
   icon: string | null;
   save_path: string | null;
+  file_permissions?: { mode: string | null; group: string | null };
+  default_segments?: number | null;
+  default_max_retries?: number | null;
+  require_checksum?: boolean;
   created_at: number;
   updated_at: number;
 }
@@ -1,10 +1,12 @@
 import { useState, useEffect, useRef } from "react";
-import { X, Plus, Link, FolderOpen, Download } from "lucide-react";
+import { X, Plus, Link, FolderOpen, Download, AlertTriangle } from "lucide-react";
 import { useDownloadStore } from "../../stores/downloadStore";
 import { open } from "@tauri-apps/plugin-dialog";
 import toast from "react-hot-toast";
 import YouTubeDownloadDialog from "../dialogs/YouTubeDownloadDialog";
 import { useYouTubeDownload } from "../../hooks/useYouTubeDownload";
+import { downloadApi } from "../../services/tauriApi";
+import type { FileInfo } from "../../types/download";
 
 interface AddDownloadDialogProps {
   onClose: () => void;
@@ -16,6 +18,7 @@ export function AddDownloadDialog({ onClose }: AddDownloadDialogProps) {
   const [fileName, setFileName] = useState("");
   const [loading, setLoading] = useState(false);
   const [showYTDialog, setShowYTDialog] = useState(false);
+  const [fileInfo, setFileInfo] = useState<FileInfo | null>(null);
   
   const { addDownload } = useDownloadStore();
   const { isSupportedUrl } = useYouTubeDownload();
@@ -33,6 +36,28 @@ export function AddDownloadDialog({ onClose }: AddDownloadDialogProps) {
     }
   }, [url, isSupportedUrl]);
 
+  // Check what the server supports once the URL stops changing
+  useEffect(() => {
+    setFileInfo(null);
+    const target = url.trim();
+    if (!/^https?:\/\//i.test(target) || isSupportedUrl(target)) {
+      return;
+    }
+    let cancelled = false;
+    const timer = setTimeout(async () => {
+      try {
+        const info = await downloadApi.getFileInfo(target);
+        if (!cancelled) setFileInfo(info);
+      } catch {
+        // The add itself reports unreachable URLs
+      }
+    }, 600);
+    return () => {
+      cancelled = true;
+      clearTimeout(timer);
+    };
+  }, [url, isSupportedUrl]);
+
   // Draggable handlers
   const handleMouseDown = (e: React.MouseEvent) => {
     if ((e.target as HTMLElement).tagName === 'INPUT' || 
@@ -124,6 +149,7 @@ export function AddDownloadDialog({ onClose }: AddDownloadDialogProps) {
       });
       
       if (selected && typeof selected === 'string') {
+        await downloadApi.allowSaveRoot(selected, true);
         setSavePath(selected);
       }
     } catch (error) {
@@ -203,6 +229,18 @@ export function AddDownloadDialog({ onClose }: AddDownloadDialogProps) {
                 </svg>
               </button>
             </div>
+            {fileInfo?.capabilityNotes && (
+              <p className="text-xs text-yellow-400 flex items-center gap-1">
+                <AlertTriangle size={12} />
+                {fileInfo.capabilityNotes}
+              </p>
+            )}
+            {fileInfo?.httpsDowngraded && (
+              <p className="text-xs text-red-400 flex items-center gap-1">
+                <AlertTriangle size={12} />
+                This link redirects from HTTPS to an unencrypted HTTP address
+              </p>
+            )}
           </div>
 
 
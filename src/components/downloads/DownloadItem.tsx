@@ -3,7 +3,7 @@ import React from "react";
   import { 
     Pause, Play, X, Trash2, RotateCcw, 
     FolderOpen, CheckCircle2,
-    AlertCircle, Clock, Loader2
+    AlertCircle, Clock, Loader2, Link2
   } from "lucide-react";
   import { useDownloadStore, Download } from "../../stores/downloadStore";
   import { formatBytes, formatSpeed, formatEta } from "../../utils/format";
@@ -39,9 +39,12 @@ import React from "react";
       }
     };
   
-    const progress = download.totalSize
-      ? (download.downloadedSize / download.totalSize) * 100
-      : 0;
+    // A completed empty file has a total of zero and is still 100%
+    const progress = download.status === 'completed'
+      ? 100
+      : download.totalSize
+        ? (download.downloadedSize / download.totalSize) * 100
+        : 0;
   
     const statusIcon: Record<string, JSX.Element> = {
       queued: <Clock className="w-4 h-4 text-yellow-400" />,
@@ -53,6 +56,8 @@ import React from "react";
       cancelled: <X className="w-4 h-4 text-gray-400" />,
       merging: <Loader2 className="w-4 h-4 text-purple-400 animate-spin" />,
       verifying: <Loader2 className="w-4 h-4 text-cyan-400 animate-spin" />,
+      waiting_for_condition: <Clock className="w-4 h-4 text-amber-400" />,
+      needs_confirmation: <AlertCircle className="w-4 h-4 text-amber-400" />,
     };
   
     const statusColor: Record<string, string> = {
@@ -65,6 +70,8 @@ import React from "react";
       cancelled: "bg-gray-500",
       merging: "bg-purple-500",
       verifying: "bg-cyan-500",
+      waiting_for_condition: "bg-amber-500",
+      needs_confirmation: "bg-amber-500",
     };
 
     const getFileTypeIcon = (fileName?: string) => {
@@ -101,6 +108,24 @@ import React from "react";
       }
     };
 
+    const handleChangeLink = async () => {
+      const newUrl = window.prompt("New link for this download:", download.url);
+      if (!newUrl || newUrl === download.url) return;
+      try {
+        await downloadApi.updateDownloadUrl(download.id, newUrl);
+        toast.success("Link updated, resuming...");
+      } catch (error) {
+        if (
+          String(error).startsWith("URL_MISMATCH") &&
+          window.confirm(`${error}\n\nUse this link anyway and start over?`)
+        ) {
+          await downloadApi.updateDownloadUrl(download.id, newUrl, true, true);
+          return;
+        }
+        toast.error(String(error));
+      }
+    };
+
     // Format date and time
     const formatDateTime = (dateString: string) => {
       try {
@@ -169,6 +194,11 @@ import React from "react";
                download.status === 'queued' ? '⏳ Queued' :
                download.status}
             </span>
+            {download.checksumRequired && !download.expectedChecksum && (
+              <span className="text-xs text-yellow-400" title="This category requires a checksum">
+                ⚠ Unverified
+              </span>
+            )}
           </div>
         </div>
   
@@ -228,7 +258,7 @@ import React from "react";
         <div className="col-span-1 flex items-center justify-end gap-1">
           {download.status === "downloading" && (
             <button
-              onClick={() => pauseDownload(download.id)}
+              onClick={() => pauseDownload(download.id, true)}
               className="p-1.5 hover:bg-gray-700 rounded transition-colors"
               title="Pause"
             >
@@ -256,6 +286,16 @@ import React from "react";
             </button>
           )}
   
+          {["paused", "failed"].includes(download.status) && (
+            <button
+              onClick={handleChangeLink}
+              className="p-1.5 hover:bg-gray-700 rounded transition-colors"
+              title="Change link"
+            >
+              <Link2 className="w-3.5 h-3.5 text-blue-400" />
+            </button>
+          )}
+
           {["failed", "cancelled"].includes(download.status) && (
             <button
               onClick={() => retryDownload(download.id)}
@@ -5,7 +5,7 @@ import { useUIStore } from "../../stores/uiStore";
 import { DownloadTableRow } from "./DownloadTableRow";
 import { DownloadGridView } from "./DownloadGridView";
 import { Download, ArrowUpDown, ArrowUp, ArrowDown, Pause, Play, Trash2, X } from "lucide-react";
-import { DownloadSort } from "../../types/download";
+import { DownloadSort, priorityRank } from "../../types/download";
 
 interface DownloadTableProps {
   filter: "all" | "downloading" | "completed" | "failed" | "missing" | "torrent" | "video" | "music" | "youtube";
@@ -82,7 +82,7 @@ export function DownloadTable({ filter }: DownloadTableProps) {
         comparison = aProgress - bProgress;
         break;
       case "priority":
-        comparison = (a.priority || 0) - (b.priority || 0);
+        comparison = priorityRank(a.priority) - priorityRank(b.priority);
         break;
       default:
         comparison = 0;
@@ -37,7 +37,7 @@ export function DownloadTableRow({ download }: DownloadTableRowProps) {
   const handlePause = async (e: React.MouseEvent) => {
     e.stopPropagation();
     try {
-      await pauseDownload(download.id);
+      await pauseDownload(download.id, true);
       toast.success("Download paused");
     } catch (error) {
       console.error("Failed to pause:", error);
@@ -186,6 +186,20 @@ export function DownloadTableRow({ download }: DownloadTableRowProps) {
           color: 'text-blue-300',
           bgColor: 'bg-blue-300/10'
         };
+      case 'needs_confirmation':
+        return {
+          icon: <AlertCircle className="w-4 h-4 text-amber-400" />,
+          text: 'Needs confirmation',
+          color: 'text-amber-400',
+          bgColor: 'bg-amber-400/10'
+        };
+      case 'waiting_for_condition':
+        return {
+          icon: <Clock className="w-4 h-4 text-amber-400" />,
+          text: download.errorMessage ? `Waiting: ${download.errorMessage}` : 'Waiting for conditions',
+          color: 'text-amber-400',
+          bgColor: 'bg-amber-400/10'
+        };
       default:
         return { 
           icon: <Clock className="w-4 h-4 text-gray-400" />, 
@@ -237,9 +251,12 @@ export function DownloadTableRow({ download }: DownloadTableRowProps) {
   };
 
   const status = getStatusDisplay();
-  const progress = download.totalSize && download.totalSize > 0
-    ? (download.downloadedSize / download.totalSize) * 100
-    : 0;
+  // A completed empty file has a total of zero and is still 100%
+  const progress = download.status === 'completed'
+    ? 100
+    : download.totalSize && download.totalSize > 0
+      ? (download.downloadedSize / download.totalSize) * 100
+      : 0;
 
   const handleDelete = async () => {
     const fileName = download.fileName || download.url?.split('/').pop() || 'this download';
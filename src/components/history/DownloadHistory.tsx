@@ -33,6 +33,7 @@ interface HistoryStats {
   failed_downloads: number;
   total_bytes_downloaded: number;
   average_speed: number;
+  total_wasted_bytes?: number;
   most_downloaded_category?: string;
 }
 
@@ -2,6 +2,7 @@ import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { List, Play, Pause, SkipForward, Settings, ChevronUp, ChevronDown } from 'lucide-react';
 import toast from 'react-hot-toast';
+import { DownloadPriority, PRIORITY_LEVELS, priorityRank } from '../../types/download';
 
 interface QueueInfo {
   active_count: number;
@@ -14,7 +15,7 @@ interface QueuedDownload {
   id: string;
   url: string;
   file_name: string;
-  priority: number;
+  priority: DownloadPriority;
   status: string;
   total_size?: number;
 }
@@ -55,7 +56,7 @@ export const QueueManager = () => {
       // Filter for queued/downloading items
       const queued = downloads.filter(d => 
         d.status === 'Queued' || d.status === 'Downloading'
-      ).sort((a, b) => b.priority - a.priority);
+      ).sort((a, b) => priorityRank(b.priority) - priorityRank(a.priority));
       setQueuedDownloads(queued);
     } catch (error) {
       console.error('Failed to load queued downloads:', error);
@@ -101,7 +102,8 @@ export const QueueManager = () => {
     const download = queuedDownloads.find(d => d.id === id);
     if (!download) return;
 
-    const newPriority = direction === 'up' ? download.priority + 1 : download.priority - 1;
+    const rank = priorityRank(download.priority) + (direction === 'up' ? 1 : -1);
+    const newPriority = PRIORITY_LEVELS[Math.max(0, Math.min(PRIORITY_LEVELS.length - 1, rank))];
     
     try {
       // Note: You'll need to add this command to backend
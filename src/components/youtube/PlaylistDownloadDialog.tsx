@@ -66,7 +66,7 @@ export const PlaylistDownloadDialog = ({ url, onClose }: PlaylistDownloadDialogP
           file_name: playlistInfo.title,
           save_path: savePath || null,
           headers: null,
-          priority: 0,
+          priority: null,
           youtube_format: downloadType,
           youtube_quality: downloadType === 'video' ? videoQuality : null,
           youtube_video_format: downloadType === 'video' ? videoFormat : null,
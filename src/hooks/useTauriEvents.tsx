@@ -2,9 +2,67 @@
 import { useEffect } from "react";
 import { listen } from "@tauri-apps/api/event";
 import { useDownloadStore } from "../stores/downloadStore";
-import type { Download, DownloadProgress } from "../types/download";
+import { downloadService } from "../services/downloadService";
+import { downloadApi, remoteConnectionApi } from "../services/tauriApi";
+import { torrentApi } from "../services/torrentApi";
+import type { BatchAddProgress, DataUsage, Download, DownloadFailedEvent, DownloadProgress, PostActionProgress, SuggestedAction, UndoableAction } from "../types/download";
+import { formatBytes } from "../utils/format";
 import toast from "react-hot-toast";
 
+const SUGGESTED_ACTION_LABELS: Record<Exclude<SuggestedAction, 'none'>, string> = {
+  retry: 'Retry',
+  update_url: 'Update link',
+  add_credentials: 'Add credentials',
+  free_space: 'Open folder',
+};
+
+// An expired or removed link can be swapped for a new one without losing progress
+async function promptForNewUrl(download: Download) {
+  const newUrl = window.prompt(`Paste a new link for ${download.fileName} to continue:`);
+  if (!newUrl) return;
+  try {
+    await downloadApi.updateDownloadUrl(download.id, newUrl);
+  } catch (error) {
+    if (
+      String(error).startsWith("URL_MISMATCH") &&
+      window.confirm(`${error}\n\nUse this link anyway and start over?`)
+    ) {
+      await downloadApi.updateDownloadUrl(download.id, newUrl, true, true);
+      return;
+    }
+    toast.error(String(error));
+  }
+}
+
+async function runSuggestedAction(download: DownloadFailedEvent) {
+  switch (download.suggestedAction) {
+    case 'retry':
+      await downloadApi.retryDownload(download.id);
+      break;
+    case 'update_url':
+      await promptForNewUrl(download);
+      break;
+    case 'add_credentials': {
+      // Only FTP and SFTP passwords are kept in the vault; other links
+      // carry their access in the link itself
+      if (!/^s?ftps?:/i.test(download.url)) {
+        await promptForNewUrl(download);
+        break;
+      }
+      const password = window.prompt(`Password for ${download.url}:`);
+      if (!password) return;
+      await remoteConnectionApi.saveCredential(download.url, password);
+      await downloadApi.retryDownload(download.id);
+      break;
+    }
+    case 'free_space':
+      await downloadApi.openFileLocation(download.id);
+      break;
+    case 'none':
+      break;
+  }
+}
+
 export function useDownloadEvents() {
   const { updateProgress, updateDownload, fetchDownloads } = useDownloadStore();
 
@@ -43,13 +101,40 @@ export function useDownloadEvents() {
         listeners.push(unlisten2);
 
         // Download failed
-        const unlisten3 = await listen<Download>(
+        const unlisten3 = await listen<DownloadFailedEvent>(
           "download-failed",
           (event) => {
+            const failed = event.payload;
+            updateDownload(failed);
+            const message = `❌ ${failed.fileName} failed: ${failed.errorMessage || 'Unknown error'}`;
+            if (failed.suggestedAction === 'none') {
+              toast.error(failed.suggestion ? `${message}. ${failed.suggestion}` : message);
+              return;
+            }
             toast.error(
-              `❌ ${event.payload.fileName} failed: ${event.payload.errorMessage || 'Unknown error'}`
+              (t) => (
+                <div className="flex items-center gap-2">
+                  <span>
+                    {message}
+                    {failed.suggestion && <><br />{failed.suggestion}</>}
+                  </span>
+                  <button
+                    onClick={async () => {
+                      toast.dismiss(t.id);
+                      try {
+                        await runSuggestedAction(failed);
+                      } catch (error) {
+                        toast.error(String(error));
+                      }
+                    }}
+                    className="px-2 py-1 bg-blue-500 hover:bg-blue-600 rounded text-sm transition-colors"
+                  >
+                    {SUGGESTED_ACTION_LABELS[failed.suggestedAction]}
+                  </button>
+                </div>
+              ),
+              { duration: 15000 }
             );
-            updateDownload(event.payload);
           }
         );
         listeners.push(unlisten3);
@@ -101,7 +186,7 @@ export function useDownloadEvents() {
                   <span>URL detected! Download?</span>
                   <button
                     onClick={() => {
-                      useDownloadStore.getState().addDownload(event.payload);
+                      useDownloadStore.getState().addDownload(event.payload, { source: 'clipboard' });
                       toast.dismiss(t.id);
                     }}
                     className="px-2 py-1 bg-blue-500 hover:bg-blue-600 rounded text-sm transition-colors"
@@ -132,6 +217,269 @@ export function useDownloadEvents() {
         );
         listeners.push(unlisten8);
 
+        // Automatically added download too large to start unasked
+        const unlisten9 = await listen<{ download: Download; message: string }>(
+          "download-needs-confirmation",
+          (event) => {
+            const { download, message } = event.payload;
+            fetchDownloads();
+            toast(
+              (t) => (
+                <div className="flex items-center gap-2">
+                  <span>Start {download.fileName}? {message}</span>
+                  <button
+                    onClick={async () => {
+                      toast.dismiss(t.id);
+                      try {
+                        updateDownload(await downloadService.confirmDownload(download.id));
+                      } catch (error) {
+                        toast.error(String(error));
+                      }
+                    }}
+                    className="px-2 py-1 bg-blue-500 hover:bg-blue-600 rounded text-sm transition-colors"
+                  >
+                    Download
+                  </button>
+                  <button
+                    onClick={() => toast.dismiss(t.id)}
+                    className="px-2 py-1 bg-gray-600 hover:bg-gray-700 rounded text-sm transition-colors"
+                  >
+                    Later
+                  </button>
+                </div>
+              ),
+              { duration: 15000 }
+            );
+          }
+        );
+        listeners.push(unlisten9);
+
+        // Segment count changed by the user or by contention
+        const unlisten10 = await listen<{ id: string; segments: number }>(
+          "download-segments-changed",
+          (event) => {
+            useDownloadStore.setState((state) => ({
+              downloads: state.downloads.map((d) =>
+                d.id === event.payload.id ? { ...d, segments: event.payload.segments } : d
+              ),
+            }));
+          }
+        );
+        listeners.push(unlisten10);
+
+        // Data cap thresholds
+        const unlisten11 = await listen<{ percent: number; usage: DataUsage }>(
+          "data-usage-warning",
+          (event) => {
+            const { percent, usage } = event.payload;
+            toast(`${percent}% of your data cap used (${formatBytes(usage.usedBytes)} of ${formatBytes(usage.budgetBytes ?? 0)})`, {
+              icon: "📶",
+            });
+          }
+        );
+        listeners.push(unlisten11);
+
+        const unlisten12 = await listen<DataUsage>(
+          "data-cap-reached",
+          (event) => {
+            fetchDownloads();
+            toast.error(`Data cap reached, downloads held until ${event.payload.periodEnd}`);
+          }
+        );
+        listeners.push(unlisten12);
+
+        const unlisten13 = await listen(
+          "data-cap-lifted",
+          () => {
+            fetchDownloads();
+            toast.success("Data cap lifted, resuming downloads");
+          }
+        );
+        listeners.push(unlisten13);
+
+        // Batch adds report each probed URL
+        const unlisten14 = await listen<BatchAddProgress>(
+          "batch-add-progress",
+          (event) => {
+            const { batchId, completed, total, failed } = event.payload;
+            const message = failed > 0
+              ? `${completed}/${total} added (${failed} failed)`
+              : `${completed}/${total} added`;
+            if (completed === total) {
+              toast.dismiss(batchId);
+            } else {
+              toast.loading(message, { id: batchId });
+            }
+          }
+        );
+        listeners.push(unlisten14);
+
+        // A category's folder was deleted or its drive is gone
+        const unlisten15 = await listen<{ categoryId: string; name: string; path: string; fallback: string }>(
+          "category-folder-missing",
+          (event) => {
+            const { name, path, fallback } = event.payload;
+            toast.error(`Folder for ${name} is missing (${path}), saving to ${fallback} instead`, { duration: 8000 });
+          }
+        );
+        listeners.push(unlisten15);
+
+        // Conversions and other post-actions run after the download completes
+        const unlisten16 = await listen<PostActionProgress>(
+          "post-action-progress",
+          (event) => {
+            const { id, status, percent } = event.payload;
+            const toastId = `post-action-${id}`;
+            if (status.state === "running") {
+              toast.loading(percent != null ? `Converting… ${Math.floor(percent)}%` : "Converting…", { id: toastId });
+            } else if (status.state === "completed") {
+              fetchDownloads();
+              toast.success("Conversion finished", { id: toastId });
+            } else if (status.state === "failed") {
+              toast.error(`Conversion failed, original kept: ${status.error}`, { id: toastId });
+            }
+          }
+        );
+        listeners.push(unlisten16);
+
+        // Transfers paused for sleep come back a few seconds after wake
+        const unlisten17 = await listen<{ downloads: number; torrents: number }>(
+          "system-resumed",
+          (event) => {
+            const { downloads, torrents } = event.payload;
+            if (downloads + torrents > 0) {
+              toast(`Resuming ${downloads + torrents} transfer(s) paused for sleep`);
+            }
+          }
+        );
+        listeners.push(unlisten17);
+
+        // A previous run crashed and left the database locked
+        const unlisten18 = await listen<{ pid: number }>(
+          "database-lock-recovered",
+          (event) => {
+            toast(`AFK-Dunld didn't close cleanly last time (process ${event.payload.pid}); its database lock was released`, { duration: 8000 });
+          }
+        );
+        listeners.push(unlisten18);
+
+        // Clipboard link to a .torrent file
+        const unlisten19 = await listen<string>(
+          "clipboard-torrent-detected",
+          (event) => {
+            toast(
+              (t) => (
+                <div className="flex items-center gap-2">
+                  <span>Torrent link detected! Add it?</span>
+                  <button
+                    onClick={async () => {
+                      toast.dismiss(t.id);
+                      try {
+                        await torrentApi.addTorrentUrl(event.payload);
+                        toast.success("Torrent added");
+                      } catch (error) {
+                        toast.error(String(error));
+                      }
+                    }}
+                    className="px-2 py-1 bg-blue-500 hover:bg-blue-600 rounded text-sm transition-colors"
+                  >
+                    Yes
+                  </button>
+                  <button
+                    onClick={() => toast.dismiss(t.id)}
+                    className="px-2 py-1 bg-gray-600 hover:bg-gray-700 rounded text-sm transition-colors"
+                  >
+                    No
+                  </button>
+                </div>
+              ),
+              { duration: 5000 }
+            );
+          }
+        );
+        listeners.push(unlisten19);
+
+        // Finished, but the file it replaces is open in another program
+        const unlisten20 = await listen<Download>(
+          "download-pending-replace",
+          (event) => {
+            toast(`${event.payload.fileName} is open in another program; it will be replaced once closed`, { duration: 8000 });
+            updateDownload(event.payload);
+          }
+        );
+        listeners.push(unlisten20);
+
+        // Default route changed; stalled segments reconnect on the new one
+        const unlisten21 = await listen<{ reconnectedSegments: number; reannouncedTorrents: number }>(
+          "network-changed",
+          (event) => {
+            const { reconnectedSegments, reannouncedTorrents } = event.payload;
+            if (reconnectedSegments + reannouncedTorrents > 0) {
+              toast("Network changed, reconnecting transfers");
+            }
+          }
+        );
+        listeners.push(unlisten21);
+
+        // A hook script failed; the download itself is unaffected
+        const unlisten22 = await listen<{ hook: string; script: string; error: string }>(
+          "hook-failed",
+          (event) => {
+            const { script, error } = event.payload;
+            const name = script.split(/[\\/]/).pop() || script;
+            toast.error(`Hook ${name} ${error}`);
+          }
+        );
+        listeners.push(unlisten22);
+
+        // A download threw away more than the configured share of its size
+        const unlisten23 = await listen<{ id: string; fileName: string; wastedBytes: number; totalSize: number | null }>(
+          "download-waste-warning",
+          (event) => {
+            const { fileName, wastedBytes } = event.payload;
+            toast(`${fileName} has re-downloaded ${formatBytes(wastedBytes)}; the server may be unreliable`, {
+              icon: "⚠️",
+            });
+          }
+        );
+        listeners.push(unlisten23);
+
+        // A refused media link is being retried through yt-dlp with its page
+        const unlisten24 = await listen<{ originalId: string; fallbackId: string; fileName: string; pageUrl: string; reason: string }>(
+          "download-fallback",
+          (event) => {
+            toast(`${event.payload.fileName} was refused, downloading it from its page with yt-dlp instead`, {
+              icon: "🔁",
+            });
+            fetchDownloads();
+          }
+        );
+        listeners.push(unlisten24);
+
+        // An action was undone; downloads and categories may be back
+        const unlisten25 = await listen<UndoableAction>(
+          "action-undone",
+          (event) => {
+            toast.success(`Undone: ${event.payload.description}`);
+            fetchDownloads();
+          }
+        );
+        listeners.push(unlisten25);
+
+        // The watchdog failed a download whose task died or stalled
+        const unlisten26 = await listen<{ id: string; fileName: string; reason: "task_ended" | "stalled"; failed: boolean }>(
+          "download-watchdog-recovered",
+          (event) => {
+            const { fileName, reason, failed } = event.payload;
+            if (failed) {
+              const why = reason === "stalled" ? "stopped making progress" : "stopped unexpectedly";
+              toast.error(`${fileName} ${why}; resume it to continue`);
+            }
+            fetchDownloads();
+          }
+        );
+        listeners.push(unlisten26);
+
       } catch (error) {
         console.error("Failed to setup event listeners:", error);
       }
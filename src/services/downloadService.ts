@@ -1,6 +1,6 @@
 // src/services/downloadService.ts
 import { downloadApi, type AddDownloadRequest } from './tauriApi';
-import type { Download, DownloadProgress, FileInfo } from '../types/download';
+import type { BatchAddSummary, ContentFilter, Download, DownloadProgress, FileInfo } from '../types/download';
 
 export class DownloadService {
   async addDownload(url: string, options?: Partial<AddDownloadRequest>): Promise<Download> {
@@ -11,12 +11,16 @@ export class DownloadService {
     return await downloadApi.addDownload(request);
   }
 
-  async addBatchDownloads(urls: string[], savePath?: string): Promise<Download[]> {
-    return await downloadApi.addBatchDownloads(urls, savePath);
+  async addBatchDownloads(urls: string[], savePath?: string, filter?: ContentFilter): Promise<BatchAddSummary> {
+    return await downloadApi.addBatchDownloads(urls, savePath, filter);
   }
 
-  async pauseDownload(id: string): Promise<void> {
-    return await downloadApi.pauseDownload(id);
+  async confirmDownload(id: string): Promise<Download> {
+    return await downloadApi.confirmDownload(id);
+  }
+
+  async pauseDownload(id: string, soft?: boolean): Promise<void> {
+    return await downloadApi.pauseDownload(id, soft);
   }
 
   async resumeDownload(id: string): Promise<void> {
@@ -14,6 +14,9 @@ export interface GlobalStats {
   total_size_bytes: number;
   current_speed: number;
   estimated_time_remaining: number | null;
+  http_speed: number;
+  torrent_download_speed: number;
+  torrent_upload_speed: number;
 }
 
 export interface QueueInfo {
@@ -25,6 +25,15 @@ export const testNotification = async (): Promise<void> => {
   return await invoke('test_notification');
 };
 
+// Plays a sound file, or the bundled sound for `event` when no path is given;
+// rejects files that can't be decoded
+export const previewNotificationSound = async (
+  path?: string,
+  event: 'complete' | 'failure' = 'complete'
+): Promise<void> => {
+  return await invoke('preview_notification_sound', { path, event });
+};
+
 // ========== System Tray ==========
 
 export const handleTrayMenuClick = async (menuId: string): Promise<void> => {
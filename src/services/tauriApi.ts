@@ -1,6 +1,6 @@
 // src/services/tauriApi.ts
 import { invoke } from '@tauri-apps/api/core';
-import type { Download, DownloadProgress, FileInfo, DownloadStats, QueueInfo } from '../types/download';
+import type { Download, DownloadPlan, DownloadPriority, PostAction, DownloadProgress, FileInfo, DownloadStats, BandwidthBreakdown, UndoableAction, QueueInfo, DataUsage, UsagePeriod, ResolvedUrl, BatchAddSummary, NormalizeOptions, NormalizedUrl, ProbeBatchSummary, MediaAddOptions, DuplicateReport, DedupStrategy, DedupOutcome, RebuildReport, RebuildSelection, ContentFilter, FilterSample, FilterPreview, DirectoryDownloadSummary, QueueForecast, RemoteProtocol, RemoteUrlCheck, ConnectionTest } from '../types/download';
 import type { VideoInfo, QualityOption, YouTubeDownloadOptions } from '../types/youtube';
 
 export interface AddDownloadRequest {
@@ -12,9 +12,26 @@ export interface AddDownloadRequest {
   checksum_type?: string;
   file_name?: string;
   category?: string;
-  priority?: number;
+  priority?: DownloadPriority;
+  source?: 'manual' | 'clipboard' | 'browserExtension';
+  /** Whether adding waits on the server probe (default 'required') */
+  probe?: 'none' | 'best_effort' | 'required';
+  /** Where to get a fresh link if this one expires mid-download */
+  url_refresh?: UrlRefresh;
+  /** Folder for segment data instead of beside the file */
+  temp_dir?: string;
+  /** e.g. { action: 'convert', profile: 'to_mp3_192k' } */
+  post_action?: PostAction;
+  /** Only plan the download and return the plan */
+  simulate?: boolean;
+  /** For playlist links: one yt-dlp process, or one per entry with retries */
+  playlist_mode?: 'single_process' | 'per_entry';
 }
 
+export type UrlRefresh =
+  | { kind: 'extension'; requestId: string }
+  | { kind: 'webhook'; endpoint: string };
+
 export interface BatchDownloadItem {
   url: string;
   fileName?: string;
@@ -54,7 +71,7 @@ export const downloadApi = {
         errorMessage: null,
         createdAt: new Date().toISOString(),
         completedAt: null,
-        priority: request.priority || 0,
+        priority: request.priority || 'Normal',
         category: request.category || null,
       };
       return mockDownload;
@@ -62,12 +79,56 @@ export const downloadApi = {
     return await invoke<Download>('add_download', { request });
   },
 
-  pauseDownload: async (id: string): Promise<void> => {
+  /** Everything adding `request` would decide, without adding it */
+  simulateDownload: async (request: AddDownloadRequest): Promise<DownloadPlan> => {
+    return await invoke<DownloadPlan>('add_download', { request: { ...request, simulate: true } });
+  },
+
+  /** Add `request` paused until tonight's start time (HH:MM, default from settings) */
+  scheduleForTonight: async (request: AddDownloadRequest, startTime?: string): Promise<Download> => {
+    return await invoke<Download>('schedule_for_tonight', { request, startTime });
+  },
+
+  setDownloadSegments: async (id: string, segments: number): Promise<void> => {
+    return await invoke('set_download_segments', { id, segments });
+  },
+
+  confirmDownload: async (id: string): Promise<Download> => {
+    return await invoke<Download>('confirm_download', { id });
+  },
+
+  resolveUrl: async (url: string): Promise<ResolvedUrl> => {
+    return await invoke<ResolvedUrl>('resolve_url', { url });
+  },
+
+  // Point a download at a new link and resume it. With validate, a link to
+  // a different file fails with URL_MISMATCH unless force restarts from zero
+  updateDownloadUrl: async (id: string, newUrl: string, validate = true, force = false): Promise<Download> => {
+    return await invoke<Download>('update_download_url', { id, newUrl, validate, force });
+  },
+
+  // Runs right away when the download has already completed; null clears it
+  setPostAction: async (id: string, postAction: PostAction | null): Promise<void> => {
+    return await invoke('set_post_action', { id, postAction });
+  },
+
+  // Approve a folder as a download location; without remember only
+  // until the app quits
+  allowSaveRoot: async (path: string, remember: boolean): Promise<void> => {
+    return await invoke('allow_save_root', { path, remember });
+  },
+
+  getDataUsage: async (period?: UsagePeriod): Promise<DataUsage> => {
+    return await invoke<DataUsage>('get_data_usage', { period });
+  },
+
+  // soft keeps the connections open for a quick resume, when enabled
+  pauseDownload: async (id: string, soft?: boolean): Promise<void> => {
     if (!isTauri()) {
-      console.log('Mock: pauseDownload called with:', id);
+      console.log('Mock: pauseDownload called with:', id, soft);
       return;
     }
-    return await invoke('pause_download', { id });
+    return await invoke('pause_download', { id, soft });
   },
 
   resumeDownload: async (id: string): Promise<void> => {
@@ -126,17 +187,57 @@ export const downloadApi = {
         contentType: 'application/octet-stream',
         totalSize: null,
         supportsRange: true,
+        acceptRanges: 'bytes',
+        server: null,
+        supportsResume: true,
+        redirectCount: 0,
+        httpsDowngraded: false,
+        capabilityNotes: null,
       };
     }
     return await invoke<FileInfo>('get_file_info', { url });
   },
 
-  addBatchDownloads: async (urls: string[], savePath?: string): Promise<Download[]> => {
+  // Probed files the filter leaves out come back as skipped
+  addBatchDownloads: async (
+    urls: string[],
+    savePath?: string,
+    filter?: ContentFilter,
+    normalize?: NormalizeOptions
+  ): Promise<BatchAddSummary> => {
     if (!isTauri()) {
       console.log('Mock: addBatchDownloads called with:', urls, savePath);
-      return [];
+      return { batchId: '', added: [], failed: [], skipped: [], normalized: [] };
+    }
+    return await invoke<BatchAddSummary>('add_batch_downloads', { urls, savePath, filter, normalize });
+  },
+
+  // Normalize and de-duplicate pasted or imported URLs without adding them
+  normalizeUrls: async (urls: string[], options?: NormalizeOptions): Promise<NormalizedUrl[]> => {
+    if (!isTauri()) {
+      console.log('Mock: normalizeUrls called with:', urls);
+      return urls.map((url) => ({ input: url, normalized: url, action: 'added' as const, error: null }));
     }
-    return await invoke<Download[]>('add_batch_downloads', { urls, savePath });
+    return await invoke<NormalizedUrl[]>('normalize_urls', { urls, options });
+  },
+
+  // Probe a page's media list without adding anything; each result also
+  // arrives as a probe-batch-progress event
+  probeBatch: async (urls: string[], referrer?: string): Promise<ProbeBatchSummary> => {
+    if (!isTauri()) {
+      console.log('Mock: probeBatch called with:', urls, referrer);
+      return { batchId: '', items: [] };
+    }
+    return await invoke<ProbeBatchSummary>('probe_batch', { urls, referrer });
+  },
+
+  // Add the media picked from a page as one group
+  addSelected: async (urls: string[], options: MediaAddOptions): Promise<BatchAddSummary> => {
+    if (!isTauri()) {
+      console.log('Mock: addSelected called with:', urls, options);
+      return { batchId: '', added: [], failed: [], skipped: [], normalized: [] };
+    }
+    return await invoke<BatchAddSummary>('add_selected', { urls, options });
   },
 
   pauseAll: async (): Promise<void> => {
@@ -203,6 +304,19 @@ export const downloadApi = {
     return await invoke<DownloadStats>('get_global_stats');
   },
 
+  getLiveBandwidthBreakdown: async (limit?: number): Promise<BandwidthBreakdown> => {
+    return await invoke<BandwidthBreakdown>('get_live_bandwidth_breakdown', { limit });
+  },
+
+  /** Undo the newest remove, cancel or category delete of this session */
+  undoLastAction: async (): Promise<UndoableAction> => {
+    return await invoke<UndoableAction>('undo_last_action');
+  },
+
+  getUndoableActions: async (): Promise<UndoableAction[]> => {
+    return await invoke<UndoableAction[]>('get_undoable_actions');
+  },
+
   setSpeedLimit: async (limit: number | null): Promise<void> => {
     if (!isTauri()) {
       console.log('Mock: setSpeedLimit called with:', limit);
@@ -224,6 +338,22 @@ export const downloadApi = {
     return await invoke<QueueInfo>('get_queue_info');
   },
 
+  // Retry moving a finished download over a file that was open elsewhere;
+  // fails with FILE_IN_USE while it still is
+  finalizeDownload: async (id: string): Promise<void> => {
+    return await invoke('finalize_download', { id });
+  },
+
+  // "Everything done by ~03:40": per-download and whole-queue finish times
+  getQueueCompletionForecast: async (): Promise<QueueForecast> => {
+    return await invoke<QueueForecast>('get_queue_completion_forecast');
+  },
+
+  // RFC 3339; null clears it. Missing it raises `deadline-at-risk`
+  setDownloadDeadline: async (id: string, deadlineAt: string | null): Promise<void> => {
+    return await invoke('set_download_deadline', { id, deadlineAt });
+  },
+
   setMaxConcurrent: async (max: number): Promise<void> => {
     if (!isTauri()) {
       console.log('Mock: setMaxConcurrent called with:', max);
@@ -257,6 +387,131 @@ export const downloadApi = {
   },
 };
 
+export type CopyKind = 'url' | 'final_url' | 'save_path' | 'checksum' | 'magnet';
+
+/** Same shape as the "All transfers" filter */
+export interface TransferFilter {
+  kind?: 'download' | 'torrent';
+  status?: string;
+  category?: string;
+  search?: string;
+}
+
+// Copy values from the backend's own records; each returns what was copied
+export const clipboardApi = {
+  // `id` is a download id or a torrent info hash
+  copy: async (kind: CopyKind, id: string): Promise<string> => {
+    return await invoke<string>('copy_to_clipboard', { kind, id });
+  },
+
+  // URLs (magnet links for torrents) of the matching transfers, one per line
+  copyAllUrls: async (filter?: TransferFilter): Promise<string> => {
+    return await invoke<string>('copy_all_urls', { filter });
+  },
+};
+
+export type FolderKind = 'downloads' | 'videos' | 'music' | 'documents' | 'images' | 'software' | 'compressed';
+
+export const folderApi = {
+  // The platform's standard folder for a category kind, to start folder pickers in
+  getDefaultFolder: async (kind: FolderKind): Promise<string> => {
+    return await invoke<string>('get_default_folder', { kind });
+  },
+};
+
+export const schemaApi = {
+  // JSON Schemas per payload type and per event name; returns the files written
+  exportApiSchema: async (destDir: string): Promise<string[]> => {
+    return await invoke<string[]>('export_api_schema', { destDir });
+  },
+};
+
+export const historyApi = {
+  findDuplicateFiles: async (): Promise<DuplicateReport> => {
+    return await invoke<DuplicateReport>('find_duplicate_files');
+  },
+
+  // Removed copies go to the system trash
+  deduplicate: async (groupId: string, strategy: DedupStrategy): Promise<DedupOutcome> => {
+    return await invoke<DedupOutcome>('deduplicate', { groupId, strategy });
+  },
+
+  // A dry run unless `apply` is given; run it once for the report first
+  rebuildFromDisk: async (scanDirs: string[], historyFile?: string, apply?: RebuildSelection): Promise<RebuildReport> => {
+    return await invoke<RebuildReport>('rebuild_from_disk', { scanDirs, historyFile, apply });
+  },
+};
+
+export const contentFilterApi = {
+  // What the filter would do with each sample; rejects invalid filters
+  test: async (filter: ContentFilter, samples: FilterSample[]): Promise<FilterPreview[]> => {
+    return await invoke<FilterPreview[]>('test_content_filter', { filter, samples });
+  },
+};
+
+// Whole remote folders; subfolders are not entered. Cancel with
+// cancel_download on transferId
+export const remoteFolderApi = {
+  ftpDownloadDirectory: async (url: string, localDir: string, filter?: ContentFilter, transferId?: string): Promise<DirectoryDownloadSummary> => {
+    return await invoke<DirectoryDownloadSummary>('ftp_download_directory', { url, localDir, filter, transferId });
+  },
+
+  sftpDownloadDirectory: async (
+    url: string,
+    localDir: string,
+    password?: string,
+    keyPath?: string,
+    filter?: ContentFilter,
+    transferId?: string,
+  ): Promise<DirectoryDownloadSummary> => {
+    return await invoke<DirectoryDownloadSummary>('sftp_download_directory', { url, localDir, password, keyPath, filter, transferId });
+  },
+};
+
+// Connect dialog checks; saved passwords are kept, encrypted, across restarts
+export const remoteConnectionApi = {
+  // Parses without connecting; protocolHint fills in a missing scheme
+  validateUrl: async (url: string, protocolHint?: RemoteProtocol): Promise<RemoteUrlCheck> => {
+    return await invoke<RemoteUrlCheck>('validate_remote_url', { url, protocolHint });
+  },
+
+  testConnection: async (url: string, password?: string, keyPath?: string): Promise<ConnectionTest> => {
+    return await invoke<ConnectionTest>('test_remote_connection', { url, password, keyPath });
+  },
+
+  saveCredential: async (url: string, password: string): Promise<void> => {
+    await invoke('save_remote_credential', { url, password });
+  },
+
+  forgetCredential: async (url: string): Promise<boolean> => {
+    return await invoke<boolean>('forget_remote_credential', { url });
+  },
+};
+
+// Matches Rust MiniSummary, polled once a second by the mini widget
+export interface MiniSummary {
+  active: number;
+  paused: number;
+  /** Bytes per second across running downloads */
+  speed: number;
+  top: { id: string; fileName: string; percent: number | null } | null;
+}
+
+export const miniWindowApi = {
+  // Opens the always-on-top widget, or focuses it when already open
+  open: async (): Promise<void> => {
+    return await invoke('create_mini_window');
+  },
+
+  close: async (): Promise<void> => {
+    return await invoke('close_mini_window');
+  },
+
+  getSummary: async (): Promise<MiniSummary> => {
+    return await invoke<MiniSummary>('get_mini_summary');
+  },
+};
+
 // YouTube/Video download commands
 export const youtubeApi = {
   checkYtDlpInstalled: async (): Promise<boolean> => {
@@ -329,7 +584,7 @@ export const youtubeApi = {
         errorMessage: null,
         createdAt: new Date().toISOString(),
         completedAt: null,
-        priority: options.priority || 0,
+        priority: options.priority || 'Normal',
         category: options.category || 'youtube',
       };
       return mockDownload;
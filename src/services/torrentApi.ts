@@ -10,18 +10,29 @@ import type {
   TorrentPriority,
   BandwidthLimit,
   TorrentSchedule,
+  WebSeedStatus,
+  TorrentSessionInfo,
+  TorrentSettingsPatch,
+  TorrentRelocation,
 } from '../types/torrent';
+import type { ContentFilter } from '../types/download';
 
 export const torrentApi = {
   // Basic operations
-  async addTorrentFile(filePath: string): Promise<string> {
-    return invoke('add_torrent_file', { filePath });
+  // With a filter only the matching files of the torrent are downloaded
+  async addTorrentFile(filePath: string, addPaused?: boolean, filter?: ContentFilter): Promise<string> {
+    return invoke('add_torrent_file', { filePath, addPaused, filter });
   },
 
   async addMagnetLink(magnetLink: string): Promise<string> {
     return invoke('add_magnet_link', { magnetLink });
   },
 
+  // Fetches the .torrent at url; errors start with TORRENT_FETCH_FAILED or TORRENT_PARSE_FAILED
+  async addTorrentUrl(url: string, addPaused?: boolean, category?: string, filter?: ContentFilter): Promise<string> {
+    return invoke('add_torrent_url', { url, addPaused, category, filter });
+  },
+
   async listTorrents(): Promise<TorrentInfo[]> {
     return invoke('list_torrents');
   },
@@ -50,6 +61,38 @@ export const torrentApi = {
     return invoke('remove_torrent', { infoHash, deleteFiles });
   },
 
+  // Sharing
+  async exportTorrentFile(infoHash: string, destPath: string): Promise<void> {
+    return invoke('export_torrent_file', { infoHash, destPath });
+  },
+
+  async getMagnetUri(infoHash: string): Promise<string> {
+    return invoke('get_magnet_uri', { infoHash });
+  },
+
+  async getTorrentSessionInfo(): Promise<TorrentSessionInfo> {
+    return invoke('get_torrent_session_info');
+  },
+
+  async applyTorrentSettings(patch: TorrentSettingsPatch): Promise<TorrentRelocation[]> {
+    return invoke('apply_torrent_settings', { patch });
+  },
+
+  async getWebSeeds(infoHash: string): Promise<WebSeedStatus[]> {
+    return invoke('get_web_seeds', { infoHash });
+  },
+
+  // Try a backed-off web seed again on the next request
+  async resetWebSeedHealth(infoHash: string, url: string): Promise<void> {
+    return invoke('reset_web_seed_health', { infoHash, url });
+  },
+
+  // Open a finished torrent's file (the largest when no index is given),
+  // or show it in the file manager with reveal
+  async openTorrentContent(infoHash: string, fileIndex?: number, reveal = false): Promise<void> {
+    return invoke('open_torrent_content', { infoHash, fileIndex, reveal });
+  },
+
   // Priority management
   async setTorrentPriority(infoHash: string, priority: TorrentPriority): Promise<void> {
     return invoke('set_torrent_priority', { infoHash, priority });
@@ -117,4 +160,9 @@ export const torrentApi = {
   async getTorrentMetadata(infoHash: string): Promise<TorrentMetadata> {
     return invoke('get_torrent_metadata', { infoHash });
   },
+
+  // Rejects with an error starting "UNSUPPORTED:" where the backend can't super-seed
+  async setTorrentSuperSeeding(infoHash: string, enabled: boolean): Promise<void> {
+    return invoke('set_torrent_super_seeding', { infoHash, enabled });
+  },
 };
@@ -1,5 +1,6 @@
 import { create } from "zustand";
 import { downloadService } from "../services/downloadService";
+import { downloadApi, type AddDownloadRequest } from "../services/tauriApi";
 import type { Download, DownloadProgress as DownloadProgressType } from "../types/download";
 import toast from "react-hot-toast";
 
@@ -8,10 +9,10 @@ interface DownloadState {
   isLoading: boolean;
   error: string | null;
   fetchDownloads: () => Promise<void>;
-  addDownload: (url: string, options?: { savePath?: string; fileName?: string; category?: string }) => Promise<void>;
+  addDownload: (url: string, options?: { savePath?: string; fileName?: string; category?: string; source?: AddDownloadRequest['source'] }) => Promise<void>;
   updateProgress: (progress: DownloadProgressType) => void;
   updateDownload: (download: Download) => void;
-  pauseDownload: (id: string) => Promise<void>;
+  pauseDownload: (id: string, soft?: boolean) => Promise<void>;
   resumeDownload: (id: string) => Promise<void>;
   cancelDownload: (id: string) => Promise<void>;
   removeDownload: (id: string, deleteFile?: boolean) => Promise<void>;
@@ -50,6 +51,7 @@ export const useDownloadStore = create<DownloadState>((set, get) => ({
         save_path: options.savePath,
         file_name: options.fileName,
         category: options.category,
+        source: options.source,
       });
       
       set((state) => ({
@@ -58,6 +60,19 @@ export const useDownloadStore = create<DownloadState>((set, get) => ({
       
       toast.success(`Download started: ${download.fileName}`);
     } catch (error) {
+      // Folders outside the approved ones need the user's go-ahead;
+      // remembering the folder is a separate choice
+      if (
+        String(error).startsWith("PATH_NOT_ALLOWED") &&
+        options.savePath &&
+        window.confirm(`${options.savePath} is not one of your download folders. Save this download there?`)
+      ) {
+        const remember = window.confirm(
+          `Always allow downloads in ${options.savePath}? Cancel allows it until the app is closed.`
+        );
+        await downloadApi.allowSaveRoot(options.savePath, remember);
+        return get().addDownload(url, options);
+      }
       const errorMessage = error instanceof Error ? error.message : "Failed to add download";
       set({ error: errorMessage });
       toast.error(errorMessage);
@@ -90,9 +105,9 @@ export const useDownloadStore = create<DownloadState>((set, get) => ({
     }));
   },
 
-  pauseDownload: async (id: string) => {
+  pauseDownload: async (id: string, soft?: boolean) => {
     try {
-      await downloadService.pauseDownload(id);
+      await downloadService.pauseDownload(id, soft);
       set((state) => ({
         downloads: state.downloads.map((d) =>
           d.id === id ? { ...d, status: "paused" as const } : d
@@ -203,11 +218,15 @@ export const useDownloadStore = create<DownloadState>((set, get) => ({
 
   addBatchDownloads: async (urls: string[], savePath?: string) => {
     try {
-      const downloads = await downloadService.addBatchDownloads(urls, savePath);
+      const { added, failed } = await downloadService.addBatchDownloads(urls, savePath);
       set((state) => ({
-        downloads: [...state.downloads, ...downloads],
+        downloads: [...state.downloads, ...added],
       }));
-      toast.success(`${downloads.length} downloads added`);
+      if (failed.length > 0) {
+        toast.error(`${added.length} downloads added, ${failed.length} failed`);
+      } else {
+        toast.success(`${added.length} downloads added`);
+      }
     } catch (error) {
       const errorMessage = error instanceof Error ? error.message : "Failed to add batch downloads";
       toast.error(errorMessage);
@@ -8,15 +8,27 @@ export type DownloadStatus =
   | 'failed' 
   | 'cancelled'
   | 'merging'
-  | 'verifying';
+  | 'verifying'
+  | 'waiting_for_condition'
+  | 'needs_confirmation'
+  | 'completed_pending_replace';
 
 export type ChecksumType = 'md5' | 'sha1' | 'sha256' | null;
 
 // Main Download interface - matches Rust DownloadTask
+// Matches Rust DownloadPriority; the backend also accepts legacy numbers
+export type DownloadPriority = 'Low' | 'Normal' | 'High' | 'Critical';
+
+export const PRIORITY_LEVELS: DownloadPriority[] = ['Low', 'Normal', 'High', 'Critical'];
+
+export const priorityRank = (priority: DownloadPriority | null | undefined): number =>
+  PRIORITY_LEVELS.indexOf(priority ?? 'Normal');
+
 export interface Download {
   id: string;
   url: string;
   finalUrl: string | null;
+  redirectChain?: string[];
   fileName: string;
   savePath: string;
   totalSize: number | null;
@@ -35,8 +47,85 @@ export interface Download {
   errorMessage: string | null;
   createdAt: string;
   completedAt: string | null;
-  priority: number;
+  priority: DownloadPriority;
+  category: string | null;
+  /** Its category requires a checksum */
+  checksumRequired?: boolean;
+  capabilityNotes?: string;
+  /** Runs on the file once the download completes */
+  postAction?: PostAction;
+  postActionStatus?: PostActionStatus;
+  /** Local time a "download tonight" starts; cleared once it runs */
+  scheduledFor?: string | null;
+  /** When the user needs it finished by (UTC) */
+  deadlineAt?: string | null;
+  /** Imported from disk with no record of its source; url is a file:// URL */
+  unknownOrigin?: boolean;
+  /** Bytes downloaded and then thrown away: restarts, ignored ranges, failed checksums */
+  wastedBytes?: number;
+  /** The refused direct download this yt-dlp download took over from */
+  fallbackOf?: string;
+  /** The per-entry playlist this row belongs to; the playlist's own row names itself */
+  playlistId?: string;
+  tempDir?: string;
+}
+
+// Matches Rust SuggestedAction: the one-click fix offered for a failure
+export type SuggestedAction = 'retry' | 'update_url' | 'add_credentials' | 'free_space' | 'none';
+
+// Payload of download-failed, matches Rust DownloadFailedEvent
+export interface DownloadFailedEvent extends Download {
+  /** What to do about it, e.g. "Reconnect the drive and resume" */
+  suggestion?: string | null;
+  suggestedAction: SuggestedAction;
+}
+
+// Matches Rust PostAction; profiles are defined in settings
+export type PostAction = { action: 'convert'; profile: string };
+
+// Matches Rust PostActionStatus; a failed action keeps the original file
+export type PostActionStatus =
+  | { state: 'queued' }
+  | { state: 'running' }
+  | { state: 'completed'; output: string }
+  | { state: 'failed'; error: string };
+
+// Payload of `post-action-progress`
+export interface PostActionProgress {
+  id: string;
+  action: PostAction;
+  status: PostActionStatus;
+  percent: number | null;
+}
+
+// Returned by add_download with `simulate` - matches Rust DownloadPlan
+export interface DownloadPlan {
+  url: string;
+  resolvedUrl: string | null;
+  redirectChain: string[];
+  probed: boolean;
+  totalSize: number | null;
+  supportsRange: boolean;
+  contentType: string | null;
+  etag: string | null;
+  requestedFileName: string;
+  fileName: string;
+  savePath: string;
   category: string | null;
+  categorySource: 'requested' | 'detected' | 'unset';
+  segments: number;
+  maxRetries: number;
+  checksumRequired: boolean;
+  speedLimit: number | null;
+  /** Where segment data goes, and whether the file downloads in segments */
+  tempRoot: string;
+  segmented: boolean;
+  diskSpace: { available: number; required: number; sufficient: boolean } | null;
+  tempSpace: { available: number; required: number; sufficient: boolean } | null;
+  duplicates: string[];
+  confirmation: { reason: 'overThreshold'; size: number; limit: number } | { reason: 'unknownSize' } | null;
+  heldFor: Array<{ condition: string }>;
+  warnings: string[];
 }
 
 // Download progress event - matches Rust DownloadProgress
@@ -57,6 +146,13 @@ export interface FileInfo {
   contentType: string | null;
   totalSize: number | null;
   supportsRange: boolean;
+  etag?: string;
+  acceptRanges: string | null;
+  server: string | null;
+  supportsResume: boolean;
+  redirectCount: number;
+  httpsDowngraded: boolean;
+  capabilityNotes: string | null;
 }
 
 export interface DownloadStats {
@@ -96,6 +192,211 @@ export interface DownloadStats {
   failedDownloads: number;
 }
 
+export interface BandwidthEntry {
+  name: string;
+  bytesPerSec: number;
+  /** Bytes moved in the window */
+  bytes: number;
+}
+
+// Matches Rust BandwidthBreakdown; biggest consumers first
+export interface BandwidthBreakdown {
+  windowSecs: number;
+  totalBytesPerSec: number;
+  categories: BandwidthEntry[];
+  domains: BandwidthEntry[];
+}
+
+// Matches Rust UndoableAction; newest first
+export interface UndoableAction {
+  id: string;
+  kind: 'removeDownload' | 'cancelDownload' | 'cancelAll' | 'deleteCategory';
+  description: string;
+  recordedAt: string;
+  expiresInSecs: number;
+}
+
+export interface BatchAddSummary {
+  batchId: string;
+  added: Download[];
+  failed: { url: string; reason: string }[];
+  /** Left out by the content filter */
+  skipped: { url: string; reason: string }[];
+  /** Every input, what it normalized to and what was done with it */
+  normalized: NormalizedUrl[];
+}
+
+// Matches Rust NormalizeOptions; tracking parameters come from settings
+export interface NormalizeOptions {
+  stripTracking?: boolean;
+  /** Relative URLs are resolved against this */
+  baseUrl?: string;
+}
+
+export interface NormalizedUrl {
+  input: string;
+  normalized: string | null;
+  action: 'added' | 'skipped_duplicate' | 'invalid';
+  error: string | null;
+}
+
+// Matches Rust MediaProbe; one URL from a page's media list
+export interface MediaProbe {
+  index: number;
+  url: string;
+  fileName: string | null;
+  totalSize: number | null;
+  contentType: string | null;
+  /** Listed earlier on the page, or already in the download list */
+  duplicate: boolean;
+  /** A download of this URL has completed before */
+  alreadyDownloaded: boolean;
+  error: string | null;
+}
+
+export interface ProbeBatchSummary {
+  batchId: string;
+  items: MediaProbe[];
+}
+
+// Payload of the probe-batch-progress event
+export interface ProbeBatchProgress {
+  batchId: string;
+  completed: number;
+  total: number;
+  item: MediaProbe;
+}
+
+// Matches Rust MediaAddOptions; shared by every picked URL
+export interface MediaAddOptions {
+  savePath?: string;
+  category?: string;
+  priority?: DownloadPriority;
+  /** The page the media came from */
+  referrer?: string;
+  pageTitle?: string;
+  /** Save into a folder named after the page */
+  pageFolder?: boolean;
+}
+
+// Matches Rust ContentFilter; every rule must pass, unset rules pass everything
+export interface ContentFilter {
+  include_ext?: string[];
+  exclude_ext?: string[];
+  /** Bytes; files of unknown size pass both size rules */
+  min_size?: number | null;
+  max_size?: number | null;
+  name_regex?: string | null;
+}
+
+export interface ContentFilterPreset {
+  name: string;
+  filter: ContentFilter;
+}
+
+export interface FilterSample {
+  name: string;
+  size: number | null;
+}
+
+export interface FilterPreview {
+  name: string;
+  size: number | null;
+  included: boolean;
+  reason: string | null;
+}
+
+export interface SkippedFile {
+  name: string;
+  size: number | null;
+  reason: string;
+}
+
+export interface DirectoryDownloadSummary {
+  downloaded: string[];
+  skipped: SkippedFile[];
+  failed: { name: string; reason: string }[];
+  bytes: number;
+  cancelled: boolean;
+}
+
+export type RemoteProtocol = 'ftp' | 'ftps' | 'sftp';
+
+// Matches Rust RemoteUrlParts; never carries the password
+export interface RemoteUrlParts {
+  protocol: RemoteProtocol;
+  host: string;
+  port: number;
+  username: string | null;
+  path: string;
+  normalizedUrl: string;
+}
+
+export type RemoteUrlWarning =
+  | { kind: 'plaintextCredentials' }
+  | { kind: 'nonstandardPort'; port: number; standard: number }
+  | { kind: 'protocolMismatch'; selected: RemoteProtocol; used: RemoteProtocol }
+  | { kind: 'missingUsername' };
+
+export interface RemoteUrlCheck {
+  parts: RemoteUrlParts;
+  warnings: RemoteUrlWarning[];
+  hasSavedCredentials: boolean;
+}
+
+export interface ConnectionTest {
+  latencyMs: number;
+  banner: string | null;
+}
+
+// Matches Rust TaskForecast; times are RFC 3339
+export interface TaskForecast {
+  id: string;
+  /** null for downloads of unknown size, or with no speed to go on */
+  completesAt: string | null;
+  deadlineAt: string | null;
+  atRisk: boolean;
+}
+
+export interface QueueForecast {
+  generatedAt: string;
+  /** Bytes per second the forecast assumes */
+  rate: number | null;
+  finishAt: string | null;
+  tasks: TaskForecast[];
+}
+
+export interface BatchAddProgress {
+  batchId: string;
+  completed: number;
+  total: number;
+  added: number;
+  failed: number;
+  url: string;
+  error: string | null;
+}
+
+export interface ResolvedUrl {
+  url: string;
+  chain: string[];
+}
+
+export type UsagePeriod = 'weekly' | 'monthly';
+
+export interface DataUsage {
+  period: UsagePeriod;
+  periodStart: string;
+  periodEnd: string;
+  downloadedBytes: number;
+  uploadedBytes: number;
+  usedBytes: number;
+  budgetBytes: number | null;
+  remainingBytes: number | null;
+  percentUsed: number | null;
+  projectedBytes: number;
+  state: 'normal' | 'throttled' | 'capped';
+}
+
 export interface DownloadFilter {
   status?: DownloadStatus | DownloadStatus[];
   category?: string;
@@ -108,3 +409,65 @@ export interface DownloadSort {
   field: 'createdAt' | 'completedAt' | 'fileName' | 'progress' | 'priority';
   order: 'asc' | 'desc';
 }
+
+// Matches Rust DuplicateReport: completed downloads with identical files
+export interface DuplicateGroup {
+  id: string;
+  checksum: string;
+  size: number;
+  /** Newest first; the first is the copy that is kept */
+  files: { id: string; fileName: string; path: string; completedAt: string | null; linked: boolean }[];
+  reclaimableBytes: number;
+}
+
+export interface DuplicateReport {
+  groups: DuplicateGroup[];
+  reclaimableBytes: number;
+}
+
+export type DedupStrategy = 'keep_newest_delete_rest' | 'keep_all_hardlink' | 'report_only';
+
+export interface DedupOutcome {
+  groupId: string;
+  strategy: DedupStrategy;
+  kept: string;
+  trashed: string[];
+  linked: string[];
+  skipped: { id: string; reason: string }[];
+  reclaimedBytes: number;
+}
+
+// Matches Rust RebuildReport from rebuild_from_disk
+export interface RebuildReport {
+  recognized: {
+    path: string;
+    size: number;
+    url: string;
+    source: 'database' | 'export';
+    matchedBy: 'name_and_size' | 'checksum';
+    historyId: string | null;
+  }[];
+  /** Imported only when listed in RebuildSelection.importUnknown */
+  unknown: { path: string; size: number }[];
+  /** Orphaned segment folders that can become paused downloads */
+  resumable: {
+    id: string;
+    tempDir: string;
+    url: string;
+    fileName: string;
+    savePath: string;
+    totalSize: number | null;
+    downloaded: number;
+  }[];
+  /** Partial and temporary files left alone */
+  skipped: string[];
+  alreadyTracked: number;
+  applied: boolean;
+  imported: number;
+}
+
+export interface RebuildSelection {
+  importUnknown?: string[];
+  /** Ids from RebuildReport.resumable */
+  reconstruct?: string[];
+}
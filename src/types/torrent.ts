@@ -40,6 +40,52 @@ export enum TorrentPriority {
   Critical = 3,
 }
 
+export interface TorrentSessionInfo {
+  sessionRunning: boolean;
+  dhtEnabled: boolean;
+  dhtNodes: number | null;
+  downloadRate: number;
+  uploadRate: number;
+  totalDownloaded: number;
+  totalUploaded: number;
+  activeTorrents: number;
+  pausedTorrents: number;
+  seedingTorrents: number;
+  listenPort: number | null;
+  incomingConnectionsSeen: boolean;
+  uptimeSecs: number;
+}
+
+export interface TorrentSettingsPatch {
+  downloadDir?: string;
+  moveExisting?: boolean;
+}
+
+// Emitted as `torrent-relocation` for each torrent being moved
+export interface TorrentRelocation {
+  infoHash: string;
+  name: string;
+  from: string;
+  to: string;
+  status: 'moving' | 'moved' | 'failed';
+  error: string | null;
+}
+
+export interface SourceHealth {
+  consecutiveFailures: number;
+  lastError: string | null;
+  /** Skipped until then (RFC 3339) */
+  retryAt: string | null;
+}
+
+/** A web seed and how it has been responding */
+export interface WebSeedStatus {
+  url: string;
+  seed_type: 'GetRight' | 'WebSeed';
+  status: 'healthy' | 'failing' | 'backedOff';
+  health: SourceHealth;
+}
+
 export interface BandwidthLimit {
   download_limit: number | null;
   upload_limit: number | null;
@@ -63,6 +109,7 @@ export interface TorrentMetadata {
   added_time: string;
   completed_time: string | null;
   save_path: string;
+  super_seeding?: boolean;
 }
 
 export interface TorrentWithMetadata {
@@ -1,5 +1,7 @@
 // YouTube download types
 
+import type { DownloadPriority } from './download';
+
 export interface VideoInfo {
   title: string;
   duration: number;
@@ -43,7 +45,7 @@ export interface YouTubeDownloadOptions {
   expected_checksum?: string | null;
   checksum_type?: string | null;
   category?: string | null;
-  priority?: number | null;
+  priority?: DownloadPriority | null;
 }
 
 export type VideoQuality = 'best' | '2160p' | '1440p' | '1080p' | '720p' | '480p' | '360p';